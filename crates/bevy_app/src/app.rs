@@ -1,5 +1,6 @@
-use crate::app_builder::AppBuilder;
+use crate::{app_builder::AppBuilder, ManualEventReader};
 use bevy_ecs::{
+    event::Events,
     schedule::{Schedule, Stage},
     world::World,
 };
@@ -54,10 +55,21 @@ impl App {
         AppBuilder::default()
     }
 
+    /// Runs the [Schedule] once, which advances the app by a single frame. Custom runners call
+    /// this once per iteration of their loop.
     pub fn update(&mut self) {
         self.schedule.run(&mut self.world);
     }
 
+    /// Returns true if an [AppExit] event was sent since the last time `reader` was used
+    pub fn exit_requested(&self, reader: &mut ManualEventReader<AppExit>) -> bool {
+        self.world
+            .get_resource::<Events<AppExit>>()
+            .map_or(false, |app_exit_events| {
+                reader.iter(app_exit_events).next_back().is_some()
+            })
+    }
+
     pub fn run(mut self) {
         #[cfg(feature = "trace")]
         let bevy_app_run_span = info_span!("bevy_app");
@@ -54,6 +54,13 @@ impl AppBuilder {
         app.run();
     }
 
+    /// Finishes building and returns the [App] without calling its runner. This is useful when
+    /// the app is driven from the outside, such as from an existing application's main loop, an
+    /// editor or a test, by calling [App::update] once per frame.
+    pub fn finish(&mut self) -> App {
+        std::mem::take(&mut self.app)
+    }
+
     pub fn world(&mut self) -> &World {
         &self.app.world
     }
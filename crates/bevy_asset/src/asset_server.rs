@@ -1,17 +1,21 @@
 use crate::{
     path::{AssetPath, AssetPathId, SourcePathId},
     Asset, AssetIo, AssetIoError, AssetLifecycle, AssetLifecycleChannel, AssetLifecycleEvent,
-    AssetLoader, Assets, Handle, HandleId, HandleUntyped, LabelId, LoadContext, LoadState,
-    RefChange, RefChangeChannel, SourceInfo, SourceMeta,
+    AssetLoader, AssetProcessing, Assets, Handle, HandleId, HandleUntyped, LabelId, LoadContext,
+    LoadState, RefChange, RefChangeChannel, SourceInfo, SourceMeta,
 };
 use anyhow::Result;
 use bevy_ecs::system::Res;
 use bevy_log::warn;
 use bevy_tasks::TaskPool;
-use bevy_utils::{HashMap, Uuid};
+use bevy_utils::{HashMap, HashSet, Uuid};
 use crossbeam_channel::TryRecvError;
 use parking_lot::RwLock;
-use std::{collections::hash_map::Entry, path::Path, sync::Arc};
+use std::{
+    collections::hash_map::Entry,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 use thiserror::Error;
 
 /// Errors that occur while loading assets with an AssetServer
@@ -27,6 +31,10 @@ pub enum AssetServerError {
     AssetLoaderError(anyhow::Error),
     #[error("encountered an error while reading an asset: {0}")]
     AssetIoError(#[from] AssetIoError),
+    #[error("encountered an error while processing an asset: {0}")]
+    AssetProcessorError(anyhow::Error),
+    #[error("invalid import settings or processed asset index: {0}")]
+    ProcessingMetadataError(#[from] ron::Error),
 }
 
 fn format_missing_asset_ext(exts: &[String]) -> String {
@@ -55,6 +63,9 @@ pub struct AssetServerInternal {
     loaders: RwLock<Vec<Arc<Box<dyn AssetLoader>>>>,
     extension_to_loader_index: RwLock<HashMap<String, usize>>,
     handle_to_path: Arc<RwLock<HashMap<HandleId, AssetPath<'static>>>>,
+    /// The sources whose loaders read each file, which are reloaded when it changes
+    read_dependents: RwLock<HashMap<PathBuf, HashSet<PathBuf>>>,
+    pub(crate) processing: RwLock<AssetProcessing>,
     task_pool: TaskPool,
 }
 
@@ -85,6 +96,8 @@ impl AssetServer {
                 asset_ref_counter: Default::default(),
                 handle_to_path: Default::default(),
                 asset_lifecycles: Default::default(),
+                read_dependents: Default::default(),
+                processing: Default::default(),
                 task_pool,
                 asset_io,
             }),
@@ -129,7 +142,7 @@ impl AssetServer {
         HandleUntyped::strong(id.into(), sender)
     }
 
-    fn get_asset_loader(
+    pub(crate) fn get_asset_loader(
         &self,
         extension: &str,
     ) -> Result<Arc<Box<dyn AssetLoader>>, AssetServerError> {
@@ -143,7 +156,7 @@ impl AssetServer {
             })
     }
 
-    fn get_path_asset_loader<P: AsRef<Path>>(
+    pub(crate) fn get_path_asset_loader<P: AsRef<Path>>(
         &self,
         path: P,
     ) -> Result<Arc<Box<dyn AssetLoader>>, AssetServerError> {
@@ -212,6 +225,82 @@ impl AssetServer {
         load_state
     }
 
+    /// The load state of an asset combined with those of every asset it depends on,
+    /// recursively. Assets in the same file as one of these assets are included too, since they
+    /// are loaded together. Dependencies are known once the asset that declares them is loaded,
+    /// so this is [LoadState::Loaded] only when the whole dependency tree is.
+    pub fn get_recursive_dependency_load_state<H: Into<HandleId>>(&self, handle: H) -> LoadState {
+        let id = match handle.into() {
+            HandleId::AssetPathId(id) => id.source_path_id(),
+            HandleId::Id(_, _) => return LoadState::NotLoaded,
+        };
+        let asset_sources = self.server.asset_sources.read();
+        let mut visited = HashSet::default();
+        let mut pending = vec![id];
+        let mut load_state = LoadState::Loaded;
+        while let Some(source_id) = pending.pop() {
+            if !visited.insert(source_id) {
+                continue;
+            }
+            let source_info = match asset_sources.get(&source_id) {
+                Some(source_info) => source_info,
+                None if source_id == id => return LoadState::NotLoaded,
+                // dependencies are requested when their dependent is loaded, but might not have
+                // started loading yet
+                None => {
+                    load_state = LoadState::Loading;
+                    continue;
+                }
+            };
+            match source_info.load_state {
+                LoadState::Loaded => {}
+                LoadState::Loading => load_state = LoadState::Loading,
+                state => return state,
+            }
+            if let Some(meta) = &source_info.meta {
+                for asset in meta.assets.iter() {
+                    pending.extend(
+                        asset
+                            .dependencies
+                            .iter()
+                            .map(|dependency| dependency.get_id().source_path_id()),
+                    );
+                }
+            }
+        }
+        load_state
+    }
+
+    /// The assets that the asset declared as its dependencies when it was loaded
+    pub fn get_dependencies<H: Into<HandleId>>(&self, handle: H) -> Vec<AssetPath<'static>> {
+        let id = match handle.into() {
+            HandleId::AssetPathId(id) => id,
+            HandleId::Id(_, _) => return Vec::new(),
+        };
+        let asset_sources = self.server.asset_sources.read();
+        asset_sources
+            .get(&id.source_path_id())
+            .and_then(|source_info| source_info.meta.as_ref())
+            .and_then(|meta| {
+                meta.assets.iter().find(|asset| {
+                    LabelId::from(asset.label.as_ref().map(|label| label.as_str())) == id.label_id()
+                })
+            })
+            .map_or_else(Vec::new, |asset| asset.dependencies.clone())
+    }
+
+    /// Reloads the asset in the file at `path` after it changed, along with the assets whose
+    /// loaders read that file
+    pub(crate) fn reload_changed_path(&self, path: &Path) {
+        if self.get_path_asset_loader(path).is_ok() || self.get_path_processor(path).is_some() {
+            self.load_untracked(path.into(), true);
+        }
+        let dependents = self.server.read_dependents.read().get(path).cloned();
+        for dependent in dependents.into_iter().flatten() {
+            self.load_untracked(dependent.as_path().into(), true);
+        }
+    }
+
     /// Loads an Asset at the provided relative path.
     ///
     /// The absolute Path to the asset is "ROOT/ASSET_FOLDER_NAME/path".
@@ -234,7 +323,9 @@ impl AssetServer {
         asset_path: AssetPath<'_>,
         force: bool,
     ) -> Result<AssetPathId, AssetServerError> {
-        let asset_loader = self.get_path_asset_loader(asset_path.path())?;
+        if self.get_path_processor(asset_path.path()).is_none() {
+            self.get_path_asset_loader(asset_path.path())?;
+        }
         let asset_path_id: AssetPathId = asset_path.get_id();
 
         // load metadata and update source info. this is done in a scope to ensure we release the
@@ -269,16 +360,16 @@ impl AssetServer {
             source_info.version
         };
 
-        // load the asset bytes
-        let bytes = match self.server.asset_io.load_path(asset_path.path()).await {
-            Ok(bytes) => bytes,
+        // load the asset bytes, or those of its processed artifact
+        let (bytes, asset_loader) = match self.read_asset(asset_path.path()).await {
+            Ok(result) => result,
             Err(err) => {
                 let mut asset_sources = self.server.asset_sources.write();
                 let source_info = asset_sources
                     .get_mut(&asset_path_id.source_path_id())
                     .expect("`AssetSource` should exist at this point.");
                 source_info.load_state = LoadState::Failed;
-                return Err(AssetServerError::AssetIoError(err));
+                return Err(err);
             }
         };
 
@@ -289,10 +380,14 @@ impl AssetServer {
             &*self.server.asset_io,
             version,
         );
-        asset_loader
-            .load(&bytes, &mut load_context)
-            .await
-            .map_err(AssetServerError::AssetLoaderError)?;
+        if let Err(err) = asset_loader.load(&bytes, &mut load_context).await {
+            let mut asset_sources = self.server.asset_sources.write();
+            let source_info = asset_sources
+                .get_mut(&asset_path_id.source_path_id())
+                .expect("`AssetSource` should exist at this point.");
+            source_info.load_state = LoadState::Failed;
+            return Err(AssetServerError::AssetLoaderError(err));
+        }
 
         // if version has changed since we loaded and grabbed a lock, return. theres is a newer
         // version being loaded
@@ -332,6 +427,20 @@ impl AssetServer {
             .asset_io
             .watch_path_for_changes(asset_path.path())
             .unwrap();
+        let read_paths = std::mem::take(&mut *load_context.read_paths.lock());
+        {
+            let mut read_dependents = self.server.read_dependents.write();
+            for dependents in read_dependents.values_mut() {
+                dependents.remove(asset_path.path());
+            }
+            for read_path in read_paths {
+                let _ = self.server.asset_io.watch_path_for_changes(&read_path);
+                read_dependents
+                    .entry(read_path)
+                    .or_default()
+                    .insert(asset_path.path().to_owned());
+            }
+        }
         self.create_assets_in_load_context(&mut load_context);
         Ok(asset_path_id)
     }
@@ -371,7 +480,9 @@ impl AssetServer {
             if self.server.asset_io.is_directory(&child_path) {
                 handles.extend(self.load_folder(&child_path)?);
             } else {
-                if self.get_path_asset_loader(&child_path).is_err() {
+                if self.get_path_asset_loader(&child_path).is_err()
+                    && self.get_path_processor(&child_path).is_none()
+                {
                     continue;
                 }
                 let handle =
@@ -508,6 +619,7 @@ pub fn free_unused_assets_system(asset_server: Res<AssetServer>) {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::AssetMeta;
     use bevy_utils::BoxedFuture;
 
     struct FakePngLoader;
@@ -551,6 +663,8 @@ mod test {
                 asset_ref_counter: Default::default(),
                 handle_to_path: Default::default(),
                 asset_lifecycles: Default::default(),
+                read_dependents: Default::default(),
+                processing: Default::default(),
                 task_pool: Default::default(),
                 asset_io: Box::new(FileAssetIo::new(&".")),
             }),
@@ -623,6 +737,65 @@ mod test {
         assert_eq!(t.unwrap().extensions()[0], "png");
     }
 
+    #[test]
+    fn recursive_dependency_load_state() {
+        let asset_server = setup();
+        let add_source = |path: &str, load_state: LoadState, dependencies: &[&str]| {
+            let asset_path = AssetPath::from(path);
+            let meta = SourceMeta {
+                assets: vec![AssetMeta {
+                    label: None,
+                    dependencies: dependencies
+                        .iter()
+                        .map(|dependency| AssetPath::from(*dependency).to_owned())
+                        .collect(),
+                    type_uuid: Uuid::nil(),
+                }],
+            };
+            asset_server.server.asset_sources.write().insert(
+                asset_path.get_id().source_path_id(),
+                SourceInfo {
+                    meta: Some(meta),
+                    path: asset_path.path().to_owned(),
+                    asset_types: Default::default(),
+                    load_state,
+                    committed_assets: Default::default(),
+                    version: 1,
+                },
+            );
+        };
+        let state = |path: &str| {
+            asset_server.get_recursive_dependency_load_state(AssetPath::from(path).get_id())
+        };
+
+        add_source(
+            "scene.gltf",
+            LoadState::Loaded,
+            &["grass.png", "rock.png#normal"],
+        );
+        add_source("grass.png", LoadState::Loaded, &[]);
+        assert_eq!(state("scene.gltf"), LoadState::Loading);
+        assert_eq!(state("grass.png"), LoadState::Loaded);
+        let dependencies = asset_server
+            .get_dependencies(AssetPath::from("scene.gltf").get_id())
+            .iter()
+            .map(AssetPath::get_id)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            dependencies,
+            vec![
+                AssetPath::from("grass.png").get_id(),
+                AssetPath::from("rock.png#normal").get_id()
+            ]
+        );
+
+        add_source("rock.png", LoadState::Loaded, &["scene.gltf"]);
+        assert_eq!(state("scene.gltf"), LoadState::Loaded);
+        add_source("grass.png", LoadState::Failed, &[]);
+        assert_eq!(state("scene.gltf"), LoadState::Failed);
+        assert_eq!(state("missing.png"), LoadState::NotLoaded);
+    }
+
     #[test]
     fn multiple_extensions() {
         let asset_server = setup();
@@ -0,0 +1,75 @@
+use crate::{AssetIo, AssetIoError};
+use anyhow::Result;
+use bevy_utils::{BoxedFuture, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Reads assets compiled into the binary, usually with [embed_asset!](crate::embed_asset)
+#[derive(Default)]
+pub struct EmbeddedAssetIo {
+    assets: HashMap<PathBuf, &'static [u8]>,
+}
+
+impl EmbeddedAssetIo {
+    pub fn add<P: AsRef<Path>>(&mut self, path: P, bytes: &'static [u8]) -> &mut Self {
+        self.assets.insert(path.as_ref().to_owned(), bytes);
+        self
+    }
+
+    pub fn contains<P: AsRef<Path>>(&self, path: P) -> bool {
+        self.assets.contains_key(path.as_ref())
+    }
+}
+
+/// Embeds the files at the given paths, relative to the current source file, in an
+/// [EmbeddedAssetIo] under the given asset paths
+///
+/// ```ignore
+/// let mut embedded = EmbeddedAssetIo::default();
+/// embed_asset!(embedded, "icon.png", "../assets/icon.png");
+/// ```
+#[macro_export]
+macro_rules! embed_asset {
+    ($io:expr, $($asset_path:expr, $file:expr),+ $(,)?) => {
+        $($io.add($asset_path, include_bytes!($file));)+
+    };
+}
+
+impl AssetIo for EmbeddedAssetIo {
+    fn load_path<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<Vec<u8>, AssetIoError>> {
+        Box::pin(async move {
+            self.assets
+                .get(path)
+                .map(|bytes| bytes.to_vec())
+                .ok_or_else(|| AssetIoError::NotFound(path.to_owned()))
+        })
+    }
+
+    fn read_directory(
+        &self,
+        path: &Path,
+    ) -> Result<Box<dyn Iterator<Item = PathBuf>>, AssetIoError> {
+        let children = self
+            .assets
+            .keys()
+            .filter_map(|asset_path| {
+                let child = asset_path.strip_prefix(path).ok()?.components().next()?;
+                Some(path.join(child))
+            })
+            .collect::<HashSet<_>>();
+        Ok(Box::new(children.into_iter()))
+    }
+
+    fn is_directory(&self, path: &Path) -> bool {
+        self.assets
+            .keys()
+            .any(|asset_path| asset_path.starts_with(path) && asset_path != path)
+    }
+
+    fn watch_path_for_changes(&self, _path: &Path) -> Result<(), AssetIoError> {
+        Ok(())
+    }
+
+    fn watch_for_changes(&self) -> Result<(), AssetIoError> {
+        Ok(())
+    }
+}
@@ -1,4 +1,7 @@
-use crate::{filesystem_watcher::FilesystemWatcher, AssetIo, AssetIoError, AssetServer};
+use crate::{
+    filesystem_watcher::FilesystemWatcher, AssetIo, AssetIoError, AssetServer, ImportSettings,
+    PackAssetIo, VirtualAssetIo,
+};
 use anyhow::Result;
 use bevy_ecs::system::Res;
 use bevy_utils::{BoxedFuture, HashSet};
@@ -26,6 +29,23 @@ impl FileAssetIo {
         }
     }
 
+    /// The path of the asset folder
+    pub fn root_path(&self) -> &Path {
+        &self.root_path
+    }
+
+    /// Finds the [FileAssetIo] reading the asset folder, when it is used directly or wrapped by
+    /// a [VirtualAssetIo] root or a [PackAssetIo] fallback
+    pub fn find(asset_io: &dyn AssetIo) -> Option<&FileAssetIo> {
+        if let Some(asset_io) = asset_io.downcast_ref::<FileAssetIo>() {
+            Some(asset_io)
+        } else if let Some(asset_io) = asset_io.downcast_ref::<VirtualAssetIo>() {
+            Self::find(asset_io.root())
+        } else {
+            Self::find(asset_io.downcast_ref::<PackAssetIo>()?.fallback()?)
+        }
+    }
+
     pub fn get_root_path() -> PathBuf {
         if let Ok(manifest_dir) = env::var("CARGO_MANIFEST_DIR") {
             PathBuf::from(manifest_dir)
@@ -110,12 +130,11 @@ impl AssetIo for FileAssetIo {
 ))]
 pub fn filesystem_watcher_system(asset_server: Res<AssetServer>) {
     let mut changed = HashSet::default();
-    let asset_io =
-        if let Some(asset_io) = asset_server.server.asset_io.downcast_ref::<FileAssetIo>() {
-            asset_io
-        } else {
-            return;
-        };
+    let asset_io = if let Some(asset_io) = FileAssetIo::find(&*asset_server.server.asset_io) {
+        asset_io
+    } else {
+        return;
+    };
     let watcher = asset_io.filesystem_watcher.read();
     if let Some(ref watcher) = *watcher {
         loop {
@@ -133,7 +152,10 @@ pub fn filesystem_watcher_system(asset_server: Res<AssetServer>) {
                 for path in paths.iter() {
                     if !changed.contains(path) {
                         let relative_path = path.strip_prefix(&asset_io.root_path).unwrap();
-                        let _ = asset_server.load_untracked(relative_path.into(), true);
+                        // changed import settings reprocess their asset
+                        let relative_path = ImportSettings::source_path(relative_path)
+                            .unwrap_or_else(|| relative_path.to_owned());
+                        asset_server.reload_changed_path(&relative_path);
                     }
                 }
                 changed.extend(paths);
@@ -1,14 +1,20 @@
 #[cfg(target_os = "android")]
 mod android_asset_io;
+mod embedded_asset_io;
 #[cfg(all(not(target_arch = "wasm32"), not(target_os = "android")))]
 mod file_asset_io;
+mod pack_asset_io;
+mod virtual_asset_io;
 #[cfg(target_arch = "wasm32")]
 mod wasm_asset_io;
 
 #[cfg(target_os = "android")]
 pub use android_asset_io::*;
+pub use embedded_asset_io::*;
 #[cfg(all(not(target_arch = "wasm32"), not(target_os = "android")))]
 pub use file_asset_io::*;
+pub use pack_asset_io::*;
+pub use virtual_asset_io::*;
 #[cfg(target_arch = "wasm32")]
 pub use wasm_asset_io::*;
 
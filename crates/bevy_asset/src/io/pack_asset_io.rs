@@ -0,0 +1,365 @@
+use crate::{AssetIo, AssetIoError};
+use anyhow::Result;
+use bevy_utils::{BoxedFuture, HashMap, HashSet};
+use std::{
+    convert::TryInto,
+    io,
+    path::{Component, Path, PathBuf},
+};
+use thiserror::Error;
+
+const PACK_IDENTIFIER: &[u8; 8] = b"BEVYPACK";
+const PACK_VERSION: u32 = 1;
+
+/// An error that occurs when reading an [AssetPack]
+#[derive(Error, Debug)]
+pub enum AssetPackError {
+    #[error("not an asset pack")]
+    InvalidIdentifier,
+    #[error("unsupported asset pack version {0}")]
+    UnsupportedVersion(u32),
+    #[error("the asset pack ends before the data it describes")]
+    UnexpectedEnd,
+    #[error("invalid asset path in the asset pack")]
+    InvalidPath,
+    #[error("encountered an io error while reading an asset pack: {0}")]
+    Io(#[from] io::Error),
+}
+
+struct PackEntry {
+    offset: usize,
+    length: usize,
+    compressed: bool,
+}
+
+/// A single archive holding many assets, with an index of their paths.
+///
+/// Packs are written by an [AssetPackBuilder] and read with a [PackAssetIo], usually for release
+/// builds. The archive starts with the `BEVYPACK` identifier, a version and the number of
+/// entries, followed by the entries and their data. Each entry has its path, offset, length and
+/// whether it is deflate compressed.
+pub struct AssetPack {
+    bytes: Vec<u8>,
+    entries: HashMap<PathBuf, PackEntry>,
+}
+
+fn read_u32(bytes: &[u8], offset: &mut usize) -> Result<u32, AssetPackError> {
+    let value = bytes
+        .get(*offset..*offset + 4)
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or(AssetPackError::UnexpectedEnd)?;
+    *offset += 4;
+    Ok(value)
+}
+
+fn read_u64(bytes: &[u8], offset: &mut usize) -> Result<usize, AssetPackError> {
+    let value = bytes
+        .get(*offset..*offset + 8)
+        .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()) as usize)
+        .ok_or(AssetPackError::UnexpectedEnd)?;
+    *offset += 8;
+    Ok(value)
+}
+
+/// Asset paths in packs use `/` separators on every platform
+fn pack_path(path: &Path) -> String {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => name.to_str(),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+impl AssetPack {
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, AssetPackError> {
+        if bytes.get(..PACK_IDENTIFIER.len()) != Some(&PACK_IDENTIFIER[..]) {
+            return Err(AssetPackError::InvalidIdentifier);
+        }
+        let mut offset = PACK_IDENTIFIER.len();
+        let version = read_u32(&bytes, &mut offset)?;
+        if version != PACK_VERSION {
+            return Err(AssetPackError::UnsupportedVersion(version));
+        }
+        let entry_count = read_u32(&bytes, &mut offset)?;
+
+        let mut entries = HashMap::default();
+        for _ in 0..entry_count {
+            let path_length = read_u32(&bytes, &mut offset)? as usize;
+            let path = bytes
+                .get(offset..offset + path_length)
+                .ok_or(AssetPackError::UnexpectedEnd)?;
+            let path = std::str::from_utf8(path).map_err(|_| AssetPackError::InvalidPath)?;
+            offset += path_length;
+            let entry = PackEntry {
+                offset: read_u64(&bytes, &mut offset)?,
+                length: read_u64(&bytes, &mut offset)?,
+                compressed: *bytes.get(offset).ok_or(AssetPackError::UnexpectedEnd)? != 0,
+            };
+            offset += 1;
+            if entry
+                .offset
+                .checked_add(entry.length)
+                .map_or(true, |end| end > bytes.len())
+            {
+                return Err(AssetPackError::UnexpectedEnd);
+            }
+            entries.insert(PathBuf::from(path), entry);
+        }
+        Ok(AssetPack { bytes, entries })
+    }
+
+    /// Reads the pack at `path` on the local filesystem
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, AssetPackError> {
+        Self::from_bytes(std::fs::read(path)?)
+    }
+
+    pub fn contains<P: AsRef<Path>>(&self, path: P) -> bool {
+        self.entries
+            .contains_key(Path::new(&pack_path(path.as_ref())))
+    }
+
+    /// The paths of the assets in the pack
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.entries.keys().map(|path| path.as_path())
+    }
+
+    /// The content of the asset at `path`, decompressed if needed
+    pub fn read<P: AsRef<Path>>(&self, path: P) -> Result<Vec<u8>, AssetIoError> {
+        let path = path.as_ref();
+        let entry = self
+            .entries
+            .get(Path::new(&pack_path(path)))
+            .ok_or_else(|| AssetIoError::NotFound(path.to_owned()))?;
+        let bytes = &self.bytes[entry.offset..entry.offset + entry.length];
+        if entry.compressed {
+            miniz_oxide::inflate::decompress_to_vec(bytes).map_err(|status| {
+                AssetIoError::Io(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("failed to decompress {:?}: {:?}", path, status),
+                ))
+            })
+        } else {
+            Ok(bytes.to_vec())
+        }
+    }
+}
+
+/// Writes [AssetPack]s
+#[derive(Default)]
+pub struct AssetPackBuilder {
+    entries: Vec<(String, Vec<u8>, bool)>,
+    compression: bool,
+}
+
+impl AssetPackBuilder {
+    /// Compresses the added assets with deflate, except those it doesn't make smaller
+    pub fn with_compression(mut self, compression: bool) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    pub fn add<P: AsRef<Path>>(&mut self, path: P, bytes: Vec<u8>) -> &mut Self {
+        let path = pack_path(path.as_ref());
+        self.entries
+            .retain(|(entry_path, _, _)| *entry_path != path);
+        if self.compression {
+            let compressed = miniz_oxide::deflate::compress_to_vec(&bytes, 6);
+            if compressed.len() < bytes.len() {
+                self.entries.push((path, compressed, true));
+                return self;
+            }
+        }
+        self.entries.push((path, bytes, false));
+        self
+    }
+
+    /// Adds every file in `folder` and its subfolders, with paths relative to `folder`
+    pub fn add_folder<P: AsRef<Path>>(&mut self, folder: P) -> Result<&mut Self, io::Error> {
+        let folder = folder.as_ref();
+        let mut folders = vec![folder.to_owned()];
+        while let Some(current) = folders.pop() {
+            for entry in std::fs::read_dir(&current)? {
+                let path = entry?.path();
+                if path.is_dir() {
+                    folders.push(path);
+                } else {
+                    let bytes = std::fs::read(&path)?;
+                    self.add(path.strip_prefix(folder).unwrap(), bytes);
+                }
+            }
+        }
+        Ok(self)
+    }
+
+    pub fn build(&self) -> Vec<u8> {
+        let mut index = PACK_IDENTIFIER.to_vec();
+        index.extend_from_slice(&PACK_VERSION.to_le_bytes());
+        index.extend_from_slice(&(self.entries.len() as u32).to_le_bytes());
+        let index_length = index.len()
+            + self
+                .entries
+                .iter()
+                .map(|(path, _, _)| 4 + path.len() + 8 + 8 + 1)
+                .sum::<usize>();
+
+        let mut offset = index_length;
+        for (path, bytes, compressed) in self.entries.iter() {
+            index.extend_from_slice(&(path.len() as u32).to_le_bytes());
+            index.extend_from_slice(path.as_bytes());
+            index.extend_from_slice(&(offset as u64).to_le_bytes());
+            index.extend_from_slice(&(bytes.len() as u64).to_le_bytes());
+            index.push(*compressed as u8);
+            offset += bytes.len();
+        }
+        for (_, bytes, _) in self.entries.iter() {
+            index.extend_from_slice(bytes);
+        }
+        index
+    }
+
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<(), io::Error> {
+        std::fs::write(path, self.build())
+    }
+}
+
+/// Reads assets from [AssetPack]s, falling back to another [AssetIo] for assets they don't
+/// contain. Packs added later take precedence, so they can patch earlier ones.
+pub struct PackAssetIo {
+    packs: Vec<AssetPack>,
+    fallback: Option<Box<dyn AssetIo>>,
+}
+
+impl PackAssetIo {
+    pub fn new(packs: Vec<AssetPack>, fallback: Option<Box<dyn AssetIo>>) -> Self {
+        PackAssetIo { packs, fallback }
+    }
+
+    pub fn add_pack(&mut self, pack: AssetPack) {
+        self.packs.push(pack);
+    }
+
+    /// The [AssetIo] reading assets that aren't in a pack
+    pub fn fallback(&self) -> Option<&dyn AssetIo> {
+        self.fallback.as_deref()
+    }
+
+    fn find_pack(&self, path: &Path) -> Option<&AssetPack> {
+        self.packs.iter().rev().find(|pack| pack.contains(path))
+    }
+}
+
+impl AssetIo for PackAssetIo {
+    fn load_path<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<Vec<u8>, AssetIoError>> {
+        Box::pin(async move {
+            match (self.find_pack(path), &self.fallback) {
+                (Some(pack), _) => pack.read(path),
+                (None, Some(fallback)) => fallback.load_path(path).await,
+                (None, None) => Err(AssetIoError::NotFound(path.to_owned())),
+            }
+        })
+    }
+
+    fn read_directory(
+        &self,
+        path: &Path,
+    ) -> Result<Box<dyn Iterator<Item = PathBuf>>, AssetIoError> {
+        let directory = pack_path(path);
+        let mut children = HashSet::default();
+        for pack in self.packs.iter() {
+            for asset_path in pack.paths() {
+                let relative_path = if directory.is_empty() {
+                    Some(asset_path)
+                } else {
+                    asset_path.strip_prefix(&directory).ok()
+                };
+                if let Some(child) = relative_path.and_then(|path| path.components().next()) {
+                    children.insert(path.join(child));
+                }
+            }
+        }
+        if let Some(fallback) = &self.fallback {
+            if fallback.is_directory(path) {
+                children.extend(fallback.read_directory(path)?);
+            }
+        }
+        if children.is_empty() && !self.is_directory(path) {
+            return Err(AssetIoError::NotFound(path.to_owned()));
+        }
+        Ok(Box::new(children.into_iter()))
+    }
+
+    fn is_directory(&self, path: &Path) -> bool {
+        let directory = pack_path(path);
+        let in_pack = self.packs.iter().any(|pack| {
+            pack.paths().any(|asset_path| {
+                directory.is_empty()
+                    || (asset_path.starts_with(&directory) && asset_path != Path::new(&directory))
+            })
+        });
+        in_pack
+            || self
+                .fallback
+                .as_ref()
+                .map_or(false, |fallback| fallback.is_directory(path))
+    }
+
+    fn watch_path_for_changes(&self, path: &Path) -> Result<(), AssetIoError> {
+        match (self.find_pack(path), &self.fallback) {
+            (None, Some(fallback)) => fallback.watch_path_for_changes(path),
+            _ => Ok(()),
+        }
+    }
+
+    fn watch_for_changes(&self) -> Result<(), AssetIoError> {
+        match &self.fallback {
+            Some(fallback) => fallback.watch_for_changes(),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packs_round_trip() {
+        let mut builder = AssetPackBuilder::default().with_compression(true);
+        // too small to be compressed
+        builder.add("textures/a.png", vec![1, 2, 3]);
+        builder.add("levels/one.ron", vec![7; 1000]);
+        assert!(builder.build().len() < 1000);
+        let pack = AssetPack::from_bytes(builder.build()).unwrap();
+        assert_eq!(pack.read("textures/a.png").unwrap(), vec![1, 2, 3]);
+        assert_eq!(pack.read("levels/one.ron").unwrap(), vec![7; 1000]);
+        assert!(pack.read("missing.png").is_err());
+    }
+
+    #[test]
+    fn pack_asset_io_lists_directories() {
+        let mut builder = AssetPackBuilder::default();
+        builder
+            .add("textures/a.png", Vec::new())
+            .add("textures/ui/b.png", Vec::new())
+            .add("music.ogg", Vec::new());
+        let io = PackAssetIo::new(vec![AssetPack::from_bytes(builder.build()).unwrap()], None);
+        assert!(io.is_directory(Path::new("textures")));
+        assert!(io.is_directory(Path::new("textures/ui")));
+        assert!(!io.is_directory(Path::new("music.ogg")));
+
+        let mut children = io
+            .read_directory(Path::new("textures"))
+            .unwrap()
+            .collect::<Vec<_>>();
+        children.sort();
+        assert_eq!(
+            children,
+            vec![
+                PathBuf::from("textures/a.png"),
+                PathBuf::from("textures/ui")
+            ]
+        );
+    }
+}
@@ -0,0 +1,157 @@
+use crate::{AssetIo, AssetIoError};
+use anyhow::Result;
+use bevy_utils::BoxedFuture;
+use std::path::{Path, PathBuf};
+
+/// Reads assets from different [AssetIo]s depending on their path. Each is mounted at a folder,
+/// and reads the assets inside it with paths relative to that folder. Assets outside of every
+/// mount point are read from the root [AssetIo].
+///
+/// For example, with an [EmbeddedAssetIo](crate::EmbeddedAssetIo) mounted at `embedded`, the
+/// asset `embedded/icon.png` is read from the binary as `icon.png`, while `textures/grass.png`
+/// is read from the platform default [AssetIo].
+pub struct VirtualAssetIo {
+    root: Box<dyn AssetIo>,
+    mounts: Vec<(PathBuf, Box<dyn AssetIo>)>,
+}
+
+impl VirtualAssetIo {
+    pub fn new(root: Box<dyn AssetIo>) -> Self {
+        VirtualAssetIo {
+            root,
+            mounts: Vec::new(),
+        }
+    }
+
+    /// Mounts `asset_io` at `path`, replacing any [AssetIo] mounted there before
+    pub fn mount<P: AsRef<Path>>(&mut self, path: P, asset_io: Box<dyn AssetIo>) -> &mut Self {
+        let path = path.as_ref().to_owned();
+        self.mounts.retain(|(mount_path, _)| *mount_path != path);
+        self.mounts.push((path, asset_io));
+        // deeper mount points are matched first
+        self.mounts
+            .sort_by_key(|(mount_path, _)| std::cmp::Reverse(mount_path.components().count()));
+        self
+    }
+
+    /// The [AssetIo] reading assets outside of every mount point
+    pub fn root(&self) -> &dyn AssetIo {
+        &*self.root
+    }
+
+    /// The [AssetIo] reading `path`, with `path` relative to its mount point
+    fn resolve<'a>(&self, path: &'a Path) -> (Option<&Path>, &dyn AssetIo, &'a Path) {
+        for (mount_path, asset_io) in self.mounts.iter() {
+            if let Ok(relative_path) = path.strip_prefix(mount_path) {
+                return (Some(mount_path), &**asset_io, relative_path);
+            }
+        }
+        (None, &*self.root, path)
+    }
+}
+
+impl AssetIo for VirtualAssetIo {
+    fn load_path<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<Vec<u8>, AssetIoError>> {
+        let (_, asset_io, relative_path) = self.resolve(path);
+        asset_io.load_path(relative_path)
+    }
+
+    fn read_directory(
+        &self,
+        path: &Path,
+    ) -> Result<Box<dyn Iterator<Item = PathBuf>>, AssetIoError> {
+        let (mount_path, asset_io, relative_path) = self.resolve(path);
+        let mut children = if asset_io.is_directory(relative_path) {
+            match mount_path {
+                Some(mount_path) => {
+                    let mount_path = mount_path.to_owned();
+                    let children = asset_io.read_directory(relative_path)?;
+                    Box::new(children.map(move |child| mount_path.join(child)))
+                        as Box<dyn Iterator<Item = PathBuf>>
+                }
+                None => asset_io.read_directory(relative_path)?,
+            }
+        } else {
+            Box::new(std::iter::empty())
+        };
+
+        // mount points show up as folders of their parent
+        let mounted_children = self
+            .mounts
+            .iter()
+            .filter_map(|(mount_path, _)| {
+                let child = mount_path.strip_prefix(path).ok()?.components().next()?;
+                Some(path.join(child))
+            })
+            .collect::<Vec<_>>();
+        if !mounted_children.is_empty() {
+            let mut all_children = children.collect::<Vec<_>>();
+            for child in mounted_children {
+                if !all_children.contains(&child) {
+                    all_children.push(child);
+                }
+            }
+            children = Box::new(all_children.into_iter());
+        }
+        Ok(children)
+    }
+
+    fn is_directory(&self, path: &Path) -> bool {
+        let (_, asset_io, relative_path) = self.resolve(path);
+        asset_io.is_directory(relative_path)
+            || self
+                .mounts
+                .iter()
+                .any(|(mount_path, _)| mount_path.starts_with(path))
+    }
+
+    fn watch_path_for_changes(&self, path: &Path) -> Result<(), AssetIoError> {
+        let (_, asset_io, relative_path) = self.resolve(path);
+        asset_io.watch_path_for_changes(relative_path)
+    }
+
+    fn watch_for_changes(&self) -> Result<(), AssetIoError> {
+        self.root.watch_for_changes()?;
+        for (_, asset_io) in self.mounts.iter() {
+            asset_io.watch_for_changes()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EmbeddedAssetIo;
+
+    #[test]
+    fn reads_mounted_assets() {
+        let mut root = EmbeddedAssetIo::default();
+        root.add("textures/grass.png", b"grass");
+        let mut embedded = EmbeddedAssetIo::default();
+        embedded.add("icon.png", b"icon");
+        let mut io = VirtualAssetIo::new(Box::new(root));
+        io.mount("embedded", Box::new(embedded));
+
+        let load = |path: &str| futures_lite::future::block_on(io.load_path(Path::new(path)));
+        assert_eq!(load("embedded/icon.png").unwrap(), b"icon");
+        assert_eq!(load("textures/grass.png").unwrap(), b"grass");
+        assert!(load("icon.png").is_err());
+
+        assert!(io.is_directory(Path::new("embedded")));
+        let mut children = io
+            .read_directory(Path::new(""))
+            .unwrap()
+            .collect::<Vec<_>>();
+        children.sort();
+        assert_eq!(
+            children,
+            vec![PathBuf::from("embedded"), PathBuf::from("textures")]
+        );
+        let children = io
+            .read_directory(Path::new("embedded"))
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert_eq!(children, vec![PathBuf::from("embedded/icon.png")]);
+    }
+}
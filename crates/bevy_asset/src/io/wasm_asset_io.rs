@@ -2,8 +2,11 @@ use crate::{AssetIo, AssetIoError};
 use anyhow::Result;
 use bevy_utils::BoxedFuture;
 use js_sys::Uint8Array;
-use std::path::{Path, PathBuf};
-use wasm_bindgen::JsCast;
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+use wasm_bindgen::{JsCast, JsValue};
 use wasm_bindgen_futures::JsFuture;
 use web_sys::Response;
 
@@ -23,12 +26,20 @@ impl AssetIo for WasmAssetIo {
     fn load_path<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<Vec<u8>, AssetIoError>> {
         Box::pin(async move {
             let path = self.root_path.join(path);
+            let fetch_error = |err: JsValue| {
+                AssetIoError::Io(io::Error::new(io::ErrorKind::Other, format!("{:?}", err)))
+            };
             let window = web_sys::window().unwrap();
             let resp_value = JsFuture::from(window.fetch_with_str(path.to_str().unwrap()))
                 .await
-                .unwrap();
+                .map_err(fetch_error)?;
             let resp: Response = resp_value.dyn_into().unwrap();
-            let data = JsFuture::from(resp.array_buffer().unwrap()).await.unwrap();
+            if !resp.ok() {
+                return Err(AssetIoError::NotFound(path));
+            }
+            let data = JsFuture::from(resp.array_buffer().map_err(fetch_error)?)
+                .await
+                .map_err(fetch_error)?;
             let bytes = Uint8Array::new(&data).to_vec();
             Ok(bytes)
         })
@@ -10,10 +10,15 @@ mod handle;
 mod info;
 mod io;
 mod loader;
+mod loading_group;
 mod path;
+mod processor;
 
 pub mod prelude {
-    pub use crate::{AddAsset, AssetEvent, AssetServer, Assets, Handle, HandleUntyped};
+    pub use crate::{
+        AddAsset, AssetEvent, AssetServer, Assets, Handle, HandleUntyped, LoadingGroup,
+        LoadingGroupFinished,
+    };
 }
 
 pub use asset_server::*;
@@ -23,7 +28,9 @@ pub use handle::*;
 pub use info::*;
 pub use io::*;
 pub use loader::*;
+pub use loading_group::*;
 pub use path::*;
+pub use processor::*;
 
 use bevy_app::{prelude::Plugin, AppBuilder};
 use bevy_ecs::{
@@ -56,6 +63,57 @@ impl Default for AssetServerSettings {
     }
 }
 
+/// Configures reading assets from [AssetPack]s instead of loose files
+#[derive(Default)]
+pub struct AssetPackSettings {
+    /// Paths of the packs, relative to the directory of the Application. Packs later in the list
+    /// take precedence. Assets that aren't in a pack are read from the asset folder.
+    pub packs: Vec<String>,
+    /// Reads the packs in debug builds too. By default only release builds do, so loose files
+    /// can be edited during development
+    pub use_in_debug: bool,
+}
+
+/// Wraps `source` in a [PackAssetIo] reading the packs listed in [AssetPackSettings], if they
+/// are used by this build. Packs that can't be read are skipped with a warning.
+#[cfg(all(not(target_arch = "wasm32"), not(target_os = "android")))]
+pub fn create_pack_asset_io(app: &mut AppBuilder, source: Box<dyn AssetIo>) -> Box<dyn AssetIo> {
+    let settings = app
+        .world_mut()
+        .get_resource_or_insert_with(AssetPackSettings::default);
+    if settings.packs.is_empty() || (cfg!(debug_assertions) && !settings.use_in_debug) {
+        return source;
+    }
+
+    let root_path = FileAssetIo::get_root_path();
+    let packs = settings
+        .packs
+        .iter()
+        .filter_map(|path| match AssetPack::open(root_path.join(path)) {
+            Ok(pack) => Some(pack),
+            Err(err) => {
+                bevy_log::warn!("failed to open asset pack {}: {}", path, err);
+                None
+            }
+        })
+        .collect();
+    Box::new(PackAssetIo::new(packs, Some(source)))
+}
+
+/// [AssetIo]s mounted at asset folders, read instead of the platform default [AssetIo] for the
+/// assets inside them. See [VirtualAssetIo]
+#[derive(Default)]
+pub struct AssetMounts {
+    mounts: Vec<(String, Box<dyn AssetIo>)>,
+}
+
+impl AssetMounts {
+    pub fn mount<T: AssetIo>(&mut self, path: &str, asset_io: T) -> &mut Self {
+        self.mounts.push((path.to_string(), Box::new(asset_io)));
+        self
+    }
+}
+
 /// Create an instance of the platform default `AssetIo`
 ///
 /// This is useful when providing a custom `AssetIo` instance that needs to
@@ -86,8 +144,24 @@ impl Plugin for AssetPlugin {
                 .clone();
 
             let source = create_platform_default_asset_io(app);
+            #[cfg(all(not(target_arch = "wasm32"), not(target_os = "android")))]
+            let source = create_pack_asset_io(app, source);
+            let source: Box<dyn AssetIo> = match app.world_mut().remove_resource::<AssetMounts>() {
+                Some(mounts) if !mounts.mounts.is_empty() => {
+                    let mut virtual_io = VirtualAssetIo::new(source);
+                    for (path, asset_io) in mounts.mounts {
+                        virtual_io.mount(path, asset_io);
+                    }
+                    Box::new(virtual_io)
+                }
+                _ => source,
+            };
 
             let asset_server = AssetServer::with_boxed_io(source, task_pool);
+            let processing_settings = app
+                .world_mut()
+                .get_resource_or_insert_with(AssetProcessingSettings::default);
+            asset_server.set_processing_settings(&processing_settings);
 
             app.insert_resource(asset_server);
         }
@@ -103,9 +177,14 @@ impl Plugin for AssetPlugin {
             SystemStage::parallel(),
         )
         .register_type::<HandleId>()
+        .add_event::<LoadingGroupFinished>()
         .add_system_to_stage(
             bevy_app::CoreStage::PreUpdate,
             asset_server::free_unused_assets_system.system(),
+        )
+        .add_system_to_stage(
+            bevy_app::CoreStage::PreUpdate,
+            loading_group::loading_group_system.system(),
         );
 
         #[cfg(all(
@@ -8,10 +8,11 @@ use bevy_ecs::{
     system::{Res, ResMut},
 };
 use bevy_reflect::{TypeUuid, TypeUuidDynamic};
-use bevy_utils::{BoxedFuture, HashMap};
+use bevy_utils::{BoxedFuture, HashMap, HashSet};
 use crossbeam_channel::{Receiver, Sender};
 use downcast_rs::{impl_downcast, Downcast};
-use std::path::Path;
+use parking_lot::Mutex;
+use std::path::{Path, PathBuf};
 
 /// A loader for an asset source
 pub trait AssetLoader: Send + Sync + 'static {
@@ -78,6 +79,8 @@ pub struct LoadContext<'a> {
     pub(crate) labeled_assets: HashMap<Option<String>, BoxedLoadedAsset>,
     pub(crate) path: &'a Path,
     pub(crate) version: usize,
+    /// The files read with [LoadContext::read_asset_bytes], which reload the asset when changed
+    pub(crate) read_paths: Mutex<HashSet<PathBuf>>,
 }
 
 impl<'a> LoadContext<'a> {
@@ -93,6 +96,7 @@ impl<'a> LoadContext<'a> {
             labeled_assets: Default::default(),
             version,
             path,
+            read_paths: Default::default(),
         }
     }
 
@@ -119,7 +123,9 @@ impl<'a> LoadContext<'a> {
         Handle::strong(id.into(), self.ref_change_channel.sender.clone())
     }
 
+    /// Reads the bytes of another file. The asset is reloaded when that file changes.
     pub async fn read_asset_bytes<P: AsRef<Path>>(&self, path: P) -> Result<Vec<u8>, AssetIoError> {
+        self.read_paths.lock().insert(path.as_ref().to_owned());
         self.asset_io.load_path(path.as_ref()).await
     }
 
@@ -0,0 +1,154 @@
+use crate::{Asset, AssetServer, Handle, HandleUntyped, LoadState};
+use bevy_app::EventWriter;
+use bevy_ecs::{
+    entity::Entity,
+    system::{Query, Res},
+};
+
+/// A set of assets loaded together, for example behind a loading screen. Add it as a component,
+/// and it tracks the progress of its assets each frame, including the assets they depend on.
+/// A [LoadingGroupFinished] event is sent once every asset is either loaded or failed.
+///
+/// The group holds strong handles, which keep its assets loaded while it exists.
+#[derive(Debug, Default)]
+pub struct LoadingGroup {
+    handles: Vec<HandleUntyped>,
+    failed: Vec<HandleUntyped>,
+    progress: f32,
+    finished: bool,
+}
+
+/// Sent when every asset of a [LoadingGroup] is either loaded or failed
+#[derive(Debug, Clone)]
+pub struct LoadingGroupFinished {
+    pub group: Entity,
+    /// The number of assets that failed to load
+    pub failed: usize,
+}
+
+impl LoadingGroup {
+    pub fn new(handles: impl IntoIterator<Item = HandleUntyped>) -> Self {
+        let mut group = Self::default();
+        group.handles.extend(handles);
+        group
+    }
+
+    pub fn add<T: Asset>(&mut self, handle: &Handle<T>) -> &mut Self {
+        self.add_untyped(handle.clone_untyped())
+    }
+
+    /// Adds an asset to the group. A finished group starts loading again if needed.
+    pub fn add_untyped(&mut self, handle: HandleUntyped) -> &mut Self {
+        if !self.handles.contains(&handle) {
+            self.handles.push(handle);
+            self.finished = false;
+        }
+        self
+    }
+
+    pub fn handles(&self) -> &[HandleUntyped] {
+        &self.handles
+    }
+
+    /// The fraction of the assets that finished loading or failed, between 0 and 1
+    pub fn progress(&self) -> f32 {
+        self.progress
+    }
+
+    /// The assets that failed to load, or which depend on an asset that failed
+    pub fn failed(&self) -> &[HandleUntyped] {
+        &self.failed
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Updates the progress of the group, returning `true` when it just finished
+    pub fn update(&mut self, asset_server: &AssetServer) -> bool {
+        let mut done = 0;
+        self.failed.clear();
+        for handle in self.handles.iter() {
+            match asset_server.get_recursive_dependency_load_state(handle) {
+                LoadState::Loaded => done += 1,
+                LoadState::Failed => {
+                    done += 1;
+                    self.failed.push(handle.clone());
+                }
+                LoadState::NotLoaded | LoadState::Loading => {}
+            }
+        }
+        self.progress = if self.handles.is_empty() {
+            1.0
+        } else {
+            done as f32 / self.handles.len() as f32
+        };
+
+        let was_finished = self.finished;
+        self.finished = done == self.handles.len();
+        self.finished && !was_finished
+    }
+}
+
+pub fn loading_group_system(
+    asset_server: Res<AssetServer>,
+    mut groups: Query<(Entity, &mut LoadingGroup)>,
+    mut finished_events: EventWriter<LoadingGroupFinished>,
+) {
+    for (entity, mut group) in groups.iter_mut() {
+        // finished groups are left alone, so they aren't marked as changed every frame
+        if group.finished {
+            continue;
+        }
+        if group.update(&asset_server) {
+            finished_events.send(LoadingGroupFinished {
+                group: entity,
+                failed: group.failed.len(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AssetPath, EmbeddedAssetIo, SourceInfo, SourceMeta};
+
+    #[test]
+    fn tracks_progress_and_failures() {
+        let asset_server = AssetServer::new(EmbeddedAssetIo::default(), Default::default());
+        let set_load_state = |path: &str, load_state: LoadState| {
+            let asset_path = AssetPath::from(path);
+            asset_server.server.asset_sources.write().insert(
+                asset_path.get_id().source_path_id(),
+                SourceInfo {
+                    meta: Some(SourceMeta { assets: Vec::new() }),
+                    path: asset_path.path().to_owned(),
+                    asset_types: Default::default(),
+                    load_state,
+                    committed_assets: Default::default(),
+                    version: 1,
+                },
+            );
+        };
+        let mut group = LoadingGroup::new(
+            ["a.png", "b.png", "c.png", "d.png"]
+                .iter()
+                .map(|path| asset_server.get_handle_untyped(AssetPath::from(*path))),
+        );
+
+        set_load_state("a.png", LoadState::Loaded);
+        set_load_state("b.png", LoadState::Failed);
+        set_load_state("c.png", LoadState::Loading);
+        assert!(!group.update(&asset_server));
+        assert_eq!(group.progress(), 0.5);
+        assert_eq!(group.failed().len(), 1);
+
+        set_load_state("c.png", LoadState::Loaded);
+        set_load_state("d.png", LoadState::Loaded);
+        assert!(group.update(&asset_server));
+        assert!(group.is_finished());
+        assert_eq!(group.progress(), 1.0);
+        assert!(!group.update(&asset_server));
+    }
+}
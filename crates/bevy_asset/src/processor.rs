@@ -0,0 +1,425 @@
+use crate::{AssetIoError, AssetLoader, AssetServer, AssetServerError};
+use bevy_log::warn;
+use bevy_utils::HashMap;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// The extension of the files holding the [ImportSettings] of an asset, next to its source
+pub const IMPORT_SETTINGS_EXTENSION: &str = "meta";
+
+/// The name of the [ProcessedIndex] file in the cache folder
+pub const PROCESSED_INDEX_FILE: &str = "index.ron";
+
+/// Settings used by an [AssetProcessor] when importing an asset.
+///
+/// They are read from a RON file named after the source with a `.meta` extension added, for
+/// example `textures/grass.png.meta`:
+///
+/// ```ron
+/// (
+///     compression: true,
+///     generate_mipmaps: true,
+///     srgb: false,
+/// )
+/// ```
+///
+/// Missing fields and missing files use the default settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ImportSettings {
+    pub compression: bool,
+    pub generate_mipmaps: bool,
+    pub srgb: bool,
+    /// Processor-specific settings
+    pub custom: BTreeMap<String, String>,
+}
+
+impl Default for ImportSettings {
+    fn default() -> Self {
+        Self {
+            compression: false,
+            generate_mipmaps: false,
+            srgb: true,
+            custom: Default::default(),
+        }
+    }
+}
+
+impl ImportSettings {
+    /// The path of the settings of the asset at `path`
+    pub fn path_for<P: AsRef<Path>>(path: P) -> PathBuf {
+        let mut path = path.as_ref().as_os_str().to_owned();
+        path.push(".");
+        path.push(IMPORT_SETTINGS_EXTENSION);
+        path.into()
+    }
+
+    /// The path of the asset configured by the settings at `path`, if it is a settings file
+    pub fn source_path<P: AsRef<Path>>(path: P) -> Option<PathBuf> {
+        let path = path.as_ref();
+        if path.extension()? == IMPORT_SETTINGS_EXTENSION {
+            Some(path.with_extension(""))
+        } else {
+            None
+        }
+    }
+}
+
+/// Converts asset sources into artifacts which are faster to load, for example compressed
+/// textures with precomputed mipmaps
+pub trait AssetProcessor: Send + Sync + 'static {
+    fn process(&self, bytes: &[u8], settings: &ImportSettings) -> Result<Vec<u8>, anyhow::Error>;
+
+    /// The extensions of the sources handled by this processor
+    fn extensions(&self) -> &[&str];
+
+    /// The extension of the [AssetLoader] reading the artifacts
+    fn artifact_extension(&self) -> &str;
+
+    /// Artifacts produced by another version of the processor are processed again
+    fn version(&self) -> u32 {
+        0
+    }
+}
+
+/// How an [AssetServer] uses its [AssetProcessor]s
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssetProcessingMode {
+    /// Sources are loaded directly
+    Off,
+    /// Sources are processed when loaded, unless the cache already holds an artifact for their
+    /// current content and settings
+    OnDemand,
+    /// Artifacts listed in the [ProcessedIndex] of the cache are loaded instead of their sources,
+    /// which don't need to be shipped. See [AssetServer::process_folder]
+    Processed,
+}
+
+pub struct AssetProcessingSettings {
+    pub mode: AssetProcessingMode,
+    /// The folder holding the artifacts, relative to the asset folder
+    pub cache_folder: String,
+}
+
+impl Default for AssetProcessingSettings {
+    fn default() -> Self {
+        Self {
+            mode: AssetProcessingMode::Off,
+            cache_folder: "imported".to_string(),
+        }
+    }
+}
+
+/// An artifact in the cache folder, named after the hash of its source content, settings and
+/// processor version
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProcessedArtifact {
+    pub file: String,
+    pub loader_extension: String,
+}
+
+/// Maps asset source paths to their artifact in the cache folder
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProcessedIndex {
+    pub artifacts: BTreeMap<String, ProcessedArtifact>,
+}
+
+pub(crate) struct AssetProcessing {
+    mode: AssetProcessingMode,
+    cache_folder: PathBuf,
+    /// Where artifacts are written, when the asset folder is on the local filesystem
+    cache_path: Option<PathBuf>,
+    processors: Vec<Arc<dyn AssetProcessor>>,
+    extension_to_processor_index: HashMap<String, usize>,
+    index: Option<ProcessedIndex>,
+}
+
+impl Default for AssetProcessing {
+    fn default() -> Self {
+        let settings = AssetProcessingSettings::default();
+        Self {
+            mode: settings.mode,
+            cache_folder: settings.cache_folder.into(),
+            cache_path: None,
+            processors: Vec::new(),
+            extension_to_processor_index: Default::default(),
+            index: None,
+        }
+    }
+}
+
+/// 64 bit FNV-1a hash of the given byte slices
+pub(crate) fn content_hash(parts: &[&[u8]]) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325u64;
+    for part in parts {
+        for byte in part.iter().chain(&(part.len() as u64).to_le_bytes()) {
+            hash ^= *byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+fn index_key(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+impl AssetServer {
+    pub fn add_processor<T>(&self, processor: T)
+    where
+        T: AssetProcessor,
+    {
+        let mut processing = self.server.processing.write();
+        let processor_index = processing.processors.len();
+        for extension in processor.extensions().iter() {
+            processing
+                .extension_to_processor_index
+                .insert(extension.to_string(), processor_index);
+        }
+        processing.processors.push(Arc::new(processor));
+    }
+
+    pub fn set_processing_settings(&self, settings: &AssetProcessingSettings) {
+        let mut processing = self.server.processing.write();
+        processing.mode = settings.mode;
+        processing.cache_folder = settings.cache_folder.clone().into();
+        #[cfg(all(not(target_arch = "wasm32"), not(target_os = "android")))]
+        {
+            processing.cache_path = crate::FileAssetIo::find(&*self.server.asset_io)
+                .map(|asset_io| asset_io.root_path().join(&settings.cache_folder));
+        }
+        processing.index = None;
+    }
+
+    pub fn processing_mode(&self) -> AssetProcessingMode {
+        self.server.processing.read().mode
+    }
+
+    pub(crate) fn get_path_processor(&self, path: &Path) -> Option<Arc<dyn AssetProcessor>> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        let processing = self.server.processing.read();
+        processing
+            .extension_to_processor_index
+            .get(&extension)
+            .map(|index| processing.processors[*index].clone())
+    }
+
+    /// Processes every asset in a folder and its subfolders which has an [AssetProcessor], then
+    /// writes the [ProcessedIndex] used by [AssetProcessingMode::Processed]. Returns the number
+    /// of processed assets. Artifacts already in the cache are reused.
+    ///
+    /// This blocks until all assets are processed and is meant to run before packaging a game.
+    pub fn process_folder<P: AsRef<Path>>(&self, path: P) -> Result<usize, AssetServerError> {
+        let mut sources = Vec::new();
+        self.collect_processed_sources(path.as_ref(), &mut sources)?;
+        let mut index = ProcessedIndex::default();
+        for (source, processor) in sources {
+            let (_, artifact) =
+                futures_lite::future::block_on(self.process_asset(&source, &*processor))?;
+            index.artifacts.insert(index_key(&source), artifact);
+        }
+        let count = index.artifacts.len();
+        self.server.processing.write().index = Some(index);
+        self.save_processed_index()?;
+        Ok(count)
+    }
+
+    fn collect_processed_sources(
+        &self,
+        path: &Path,
+        sources: &mut Vec<(PathBuf, Arc<dyn AssetProcessor>)>,
+    ) -> Result<(), AssetServerError> {
+        let cache_folder = self.server.processing.read().cache_folder.clone();
+        for child_path in self.server.asset_io.read_directory(path)? {
+            if child_path == cache_folder {
+                continue;
+            }
+            if self.server.asset_io.is_directory(&child_path) {
+                self.collect_processed_sources(&child_path, sources)?;
+            } else if let Some(processor) = self.get_path_processor(&child_path) {
+                sources.push((child_path, processor));
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the bytes to load for the asset at `path`, with the loader they need. Depending on
+    /// the [AssetProcessingMode], these are the source bytes or those of its artifact.
+    pub(crate) async fn read_asset(
+        &self,
+        path: &Path,
+    ) -> Result<(Vec<u8>, Arc<Box<dyn AssetLoader>>), AssetServerError> {
+        let mode = self.processing_mode();
+        let processor = self.get_path_processor(path);
+        let artifact = match (mode, processor) {
+            (AssetProcessingMode::OnDemand, Some(processor)) => {
+                let (bytes, artifact) = self.process_asset(path, &*processor).await?;
+                let indexed = self
+                    .server
+                    .processing
+                    .write()
+                    .index
+                    .get_or_insert_with(Default::default)
+                    .artifacts
+                    .insert(index_key(path), artifact.clone());
+                if indexed.as_ref() != Some(&artifact) {
+                    if let Err(err) = self.save_processed_index() {
+                        warn!("failed to save the processed asset index: {}", err);
+                    }
+                }
+                let loader = self.get_asset_loader(&artifact.loader_extension)?;
+                return Ok((bytes, loader));
+            }
+            (AssetProcessingMode::Processed, _) => self.processed_artifact(path).await?,
+            _ => None,
+        };
+
+        match artifact {
+            Some(artifact) => {
+                let artifact_path = self
+                    .server
+                    .processing
+                    .read()
+                    .cache_folder
+                    .join(&artifact.file);
+                let bytes = self.server.asset_io.load_path(&artifact_path).await?;
+                let loader = self.get_asset_loader(&artifact.loader_extension)?;
+                Ok((bytes, loader))
+            }
+            None => {
+                let loader = self.get_path_asset_loader(path)?;
+                let bytes = self.server.asset_io.load_path(path).await?;
+                Ok((bytes, loader))
+            }
+        }
+    }
+
+    async fn processed_artifact(
+        &self,
+        path: &Path,
+    ) -> Result<Option<ProcessedArtifact>, AssetServerError> {
+        if self.server.processing.read().index.is_none() {
+            let index_path = self
+                .server
+                .processing
+                .read()
+                .cache_folder
+                .join(PROCESSED_INDEX_FILE);
+            let index = match self.server.asset_io.load_path(&index_path).await {
+                Ok(bytes) => ron::de::from_bytes(&bytes)?,
+                Err(AssetIoError::NotFound(_)) => ProcessedIndex::default(),
+                Err(err) => return Err(err.into()),
+            };
+            self.server.processing.write().index = Some(index);
+        }
+        Ok(self
+            .server
+            .processing
+            .read()
+            .index
+            .as_ref()
+            .and_then(|index| index.artifacts.get(&index_key(path)).cloned()))
+    }
+
+    async fn process_asset(
+        &self,
+        path: &Path,
+        processor: &dyn AssetProcessor,
+    ) -> Result<(Vec<u8>, ProcessedArtifact), AssetServerError> {
+        let asset_io = &*self.server.asset_io;
+        let source = asset_io.load_path(path).await?;
+        let settings_path = ImportSettings::path_for(path);
+        let settings_bytes = match asset_io.load_path(&settings_path).await {
+            Ok(bytes) => {
+                let _ = asset_io.watch_path_for_changes(&settings_path);
+                Some(bytes)
+            }
+            Err(AssetIoError::NotFound(_)) => None,
+            Err(err) => return Err(err.into()),
+        };
+        let settings = match &settings_bytes {
+            Some(bytes) => ron::de::from_bytes(bytes)?,
+            None => ImportSettings::default(),
+        };
+
+        let hash = content_hash(&[
+            &processor.version().to_le_bytes(),
+            processor.artifact_extension().as_bytes(),
+            &source,
+            settings_bytes.as_deref().unwrap_or_default(),
+        ]);
+        let artifact = ProcessedArtifact {
+            file: format!("{:016x}", hash),
+            loader_extension: processor.artifact_extension().to_string(),
+        };
+
+        let (cache_folder, cache_path) = {
+            let processing = self.server.processing.read();
+            (
+                processing.cache_folder.clone(),
+                processing.cache_path.clone(),
+            )
+        };
+        if let Ok(bytes) = asset_io.load_path(&cache_folder.join(&artifact.file)).await {
+            return Ok((bytes, artifact));
+        }
+
+        let bytes = processor
+            .process(&source, &settings)
+            .map_err(AssetServerError::AssetProcessorError)?;
+        if let Some(cache_path) = cache_path {
+            std::fs::create_dir_all(&cache_path).map_err(AssetIoError::from)?;
+            std::fs::write(cache_path.join(&artifact.file), &bytes).map_err(AssetIoError::from)?;
+        }
+        Ok((bytes, artifact))
+    }
+
+    fn save_processed_index(&self) -> Result<(), AssetServerError> {
+        let processing = self.server.processing.read();
+        let (cache_path, index) = match (&processing.cache_path, &processing.index) {
+            (Some(cache_path), Some(index)) => (cache_path, index),
+            _ => return Ok(()),
+        };
+        let ron = ron::ser::to_string_pretty(index, Default::default())?;
+        std::fs::create_dir_all(cache_path).map_err(AssetIoError::from)?;
+        std::fs::write(cache_path.join(PROCESSED_INDEX_FILE), ron).map_err(AssetIoError::from)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn import_settings_paths() {
+        let settings_path = ImportSettings::path_for("textures/grass.png");
+        assert_eq!(settings_path, Path::new("textures/grass.png.meta"));
+        assert_eq!(
+            ImportSettings::source_path(&settings_path).unwrap(),
+            Path::new("textures/grass.png")
+        );
+        assert_eq!(ImportSettings::source_path("textures/grass.png"), None);
+    }
+
+    #[test]
+    fn import_settings_defaults() {
+        let settings: ImportSettings = ron::de::from_str("(compression: true)").unwrap();
+        assert!(settings.compression);
+        assert!(settings.srgb);
+        assert!(!settings.generate_mipmaps);
+    }
+
+    #[test]
+    fn content_hash_separates_parts() {
+        assert_ne!(
+            content_hash(&[&b"ab"[..], &b"c"[..]]),
+            content_hash(&[&b"a"[..], &b"bc"[..]])
+        );
+        assert_eq!(content_hash(&[&b"abc"[..]]), content_hash(&[&b"abc"[..]]));
+    }
+}
@@ -1,14 +1,221 @@
-use crate::{AudioSource, Decodable};
+use crate::{streaming::StreamControl, AudioBus, AudioSource, Decodable};
 use bevy_asset::{Asset, Handle};
-use parking_lot::RwLock;
-use std::{collections::VecDeque, fmt};
+use bevy_utils::Duration;
+use parking_lot::{Mutex, RwLock};
+use std::{collections::VecDeque, fmt, sync::Arc};
+
+/// How a sound queued in [Audio] is played
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlaybackSettings {
+    pub bus: AudioBus,
+    pub volume: f32,
+    /// Restarts the sound without a gap when it ends, until it is stopped
+    pub looped: bool,
+    /// Fades the volume in from silence when the sound starts
+    pub fade_in: Option<Duration>,
+}
+
+impl Default for PlaybackSettings {
+    fn default() -> Self {
+        PlaybackSettings {
+            bus: AudioBus::MASTER,
+            volume: 1.0,
+            looped: false,
+            fade_in: None,
+        }
+    }
+}
+
+impl PlaybackSettings {
+    pub fn on_bus(bus: AudioBus) -> Self {
+        PlaybackSettings {
+            bus,
+            ..Default::default()
+        }
+    }
+
+    pub fn looped(mut self) -> Self {
+        self.looped = true;
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Fade {
+    from: f32,
+    to: f32,
+    duration: f32,
+    elapsed: f32,
+    /// Stops the playback when the fade ends
+    stop: bool,
+}
+
+#[derive(Debug, Clone)]
+struct PlaybackState {
+    bus: AudioBus,
+    volume: f32,
+    fade: Option<Fade>,
+    paused: bool,
+    stopped: bool,
+    finished: bool,
+}
+
+/// Controls a sound played with [Audio::play_with], while it is queued or playing. Clones control
+/// the same sound.
+#[derive(Debug, Clone)]
+pub struct AudioPlayback {
+    state: Arc<Mutex<PlaybackState>>,
+    pub(crate) stream: Arc<StreamControl>,
+}
+
+impl AudioPlayback {
+    fn new(settings: &PlaybackSettings) -> Self {
+        let fade = settings.fade_in.map(|fade_in| Fade {
+            from: 0.0,
+            to: settings.volume,
+            duration: fade_in.as_secs_f32(),
+            elapsed: 0.0,
+            stop: false,
+        });
+        AudioPlayback {
+            state: Arc::new(Mutex::new(PlaybackState {
+                bus: settings.bus.clone(),
+                volume: if fade.is_some() { 0.0 } else { settings.volume },
+                fade,
+                paused: false,
+                stopped: false,
+                finished: false,
+            })),
+            stream: Default::default(),
+        }
+    }
+
+    /// The volume of the sound, before the volume of its bus
+    pub fn volume(&self) -> f32 {
+        self.state.lock().volume
+    }
+
+    /// Sets the volume, cancelling any fade
+    pub fn set_volume(&self, volume: f32) {
+        let mut state = self.state.lock();
+        state.volume = volume;
+        state.fade = None;
+    }
+
+    /// Changes the volume to `volume` over `duration`
+    pub fn fade_to(&self, volume: f32, duration: Duration) {
+        self.fade(volume, duration, false);
+    }
+
+    /// Fades the volume out over `duration`, then stops the sound
+    pub fn fade_out(&self, duration: Duration) {
+        self.fade(0.0, duration, true);
+    }
+
+    fn fade(&self, to: f32, duration: Duration, stop: bool) {
+        let mut state = self.state.lock();
+        state.fade = Some(Fade {
+            from: state.volume,
+            to,
+            duration: duration.as_secs_f32(),
+            elapsed: 0.0,
+            stop,
+        });
+    }
+
+    pub fn bus(&self) -> AudioBus {
+        self.state.lock().bus.clone()
+    }
+
+    /// Moves the sound to `bus`. The effects of the new bus only apply to sounds started on it.
+    pub fn set_bus(&self, bus: AudioBus) {
+        self.state.lock().bus = bus;
+    }
+
+    pub fn pause(&self) {
+        self.state.lock().paused = true;
+    }
+
+    pub fn resume(&self) {
+        self.state.lock().paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.state.lock().paused
+    }
+
+    /// The time since the start of the sound, or of the current loop
+    pub fn position(&self) -> Duration {
+        self.stream.position()
+    }
+
+    /// Continues playback from `position`. The sound is decoded up to `position` on its own
+    /// thread and keeps playing until then, so seeking far into long sounds takes some time.
+    pub fn seek(&self, position: Duration) {
+        self.stream.seek(position);
+    }
+
+    pub fn stop(&self) {
+        self.state.lock().stopped = true;
+    }
+
+    /// Returns true once the sound has ended or was stopped
+    pub fn is_finished(&self) -> bool {
+        let state = self.state.lock();
+        state.finished || state.stopped
+    }
+
+    /// Advances the fade, returning the volume, the bus and whether the sound is paused
+    pub(crate) fn update(&self, delta_seconds: f32) -> (f32, AudioBus, bool) {
+        let mut state = self.state.lock();
+        if let Some(mut fade) = state.fade.take() {
+            fade.elapsed += delta_seconds;
+            let t = if fade.duration > 0.0 {
+                (fade.elapsed / fade.duration).min(1.0)
+            } else {
+                1.0
+            };
+            state.volume = fade.from + (fade.to - fade.from) * t;
+            if t < 1.0 {
+                state.fade = Some(fade);
+            } else if fade.stop {
+                state.stopped = true;
+            }
+        }
+        (state.volume, state.bus.clone(), state.paused)
+    }
+
+    pub(crate) fn is_stopped(&self) -> bool {
+        self.state.lock().stopped
+    }
+
+    pub(crate) fn finish(&self) {
+        self.state.lock().finished = true;
+    }
+}
+
+/// A sound waiting for its source to load
+pub struct QueuedAudio<P: Asset> {
+    pub source: Handle<P>,
+    pub settings: PlaybackSettings,
+    pub playback: AudioPlayback,
+}
+
+impl<P: Asset> fmt::Debug for QueuedAudio<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("QueuedAudio")
+            .field("source", &self.source)
+            .field("settings", &self.settings)
+            .finish()
+    }
+}
 
 /// The external struct used to play audio
 pub struct Audio<P = AudioSource>
 where
     P: Asset + Decodable,
 {
-    pub queue: RwLock<VecDeque<Handle<P>>>,
+    pub queue: RwLock<VecDeque<QueuedAudio<P>>>,
 }
 
 impl<P: Asset> fmt::Debug for Audio<P>
@@ -38,6 +245,57 @@ where
     <<P as Decodable>::Decoder as Iterator>::Item: rodio::Sample + Send + Sync,
 {
     pub fn play(&self, audio_source: Handle<P>) {
-        self.queue.write().push_front(audio_source);
+        self.play_with(audio_source, PlaybackSettings::default());
+    }
+
+    /// Plays `audio_source` with `settings`, returning a handle controlling the playback
+    pub fn play_with(&self, audio_source: Handle<P>, settings: PlaybackSettings) -> AudioPlayback {
+        let playback = AudioPlayback::new(&settings);
+        self.queue.write().push_front(QueuedAudio {
+            source: audio_source,
+            settings,
+            playback: playback.clone(),
+        });
+        playback
+    }
+
+    /// Fades `from` out and `to` in over `duration`, returning the playback of `to`
+    pub fn crossfade(
+        &self,
+        from: &AudioPlayback,
+        to: Handle<P>,
+        settings: PlaybackSettings,
+        duration: Duration,
+    ) -> AudioPlayback {
+        from.fade_out(duration);
+        self.play_with(
+            to,
+            PlaybackSettings {
+                fade_in: Some(duration),
+                ..settings
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn playback_fades() {
+        let playback = AudioPlayback::new(&PlaybackSettings {
+            volume: 0.8,
+            fade_in: Some(Duration::from_secs(2)),
+            ..Default::default()
+        });
+        assert_eq!(playback.update(1.0).0, 0.4);
+        assert_eq!(playback.update(2.0).0, 0.8);
+
+        playback.fade_out(Duration::from_secs(1));
+        assert_eq!(playback.update(0.5).0, 0.4);
+        assert!(!playback.is_finished());
+        assert_eq!(playback.update(0.5).0, 0.0);
+        assert!(playback.is_finished());
     }
 }
@@ -1,9 +1,20 @@
-use crate::{Audio, AudioSource, Decodable};
+use crate::{
+    bus::MixedSource, streaming::StreamDecoders, Audio, AudioBus, AudioMixer, AudioPlayback,
+    AudioSource, Decodable, QueuedAudio,
+};
 use bevy_asset::{Asset, Assets};
+use bevy_core::Time;
 use bevy_ecs::world::World;
+use bevy_utils::HashSet;
 use rodio::{OutputStream, OutputStreamHandle, Sink};
 use std::marker::PhantomData;
 
+struct PlayingAudio<P: Decodable> {
+    sink: Sink,
+    playback: AudioPlayback,
+    decoders: StreamDecoders<P>,
+}
+
 /// Used internally to play audio on the current "audio device"
 pub struct AudioOutput<P = AudioSource>
 where
@@ -11,6 +22,7 @@ where
 {
     _stream: OutputStream,
     stream_handle: OutputStreamHandle,
+    playing: Vec<PlayingAudio<P>>,
     phantom: PhantomData<P>,
 }
 
@@ -24,6 +36,7 @@ where
         Self {
             _stream: stream,
             stream_handle,
+            playing: Vec::new(),
             phantom: PhantomData,
         }
     }
@@ -31,45 +44,123 @@ where
 
 impl<P> AudioOutput<P>
 where
-    P: Asset + Decodable,
-    <P as Decodable>::Decoder: rodio::Source + Send + Sync,
+    P: Asset + Decodable + Clone,
+    <P as Decodable>::Decoder: rodio::Source + Send + Sync + 'static,
     <<P as Decodable>::Decoder as Iterator>::Item: rodio::Sample + Send + Sync,
 {
-    fn play_source(&self, audio_source: &P) {
+    pub(crate) fn stream_handle(&self) -> &OutputStreamHandle {
+        &self.stream_handle
+    }
+
+    fn play_source(&mut self, audio_source: &P, queued: QueuedAudio<P>, mixer: &AudioMixer) {
         let sink = Sink::try_new(&self.stream_handle).unwrap();
-        sink.append(audio_source.decoder());
-        sink.detach();
+        let (decoders, source) = StreamDecoders::new(
+            audio_source.clone(),
+            queued.settings.looped,
+            queued.playback.stream.clone(),
+        );
+        let mut source: MixedSource = Box::new(source);
+        for effect in mixer.effects(&queued.settings.bus) {
+            source = effect.apply(source);
+        }
+        sink.append(source);
+        let (volume, bus, paused) = queued.playback.update(0.0);
+        sink.set_volume(volume * mixer.volume(&bus));
+        if paused {
+            sink.pause();
+        }
+        self.playing.push(PlayingAudio {
+            sink,
+            playback: queued.playback,
+            decoders,
+        });
     }
 
-    fn try_play_queued(&self, audio_sources: &Assets<P>, audio: &mut Audio<P>) {
+    fn try_play_queued(
+        &mut self,
+        audio_sources: &Assets<P>,
+        audio: &mut Audio<P>,
+        mixer: &AudioMixer,
+    ) {
         let mut queue = audio.queue.write();
         let len = queue.len();
         let mut i = 0;
         while i < len {
-            let audio_source_handle = queue.pop_back().unwrap();
-            if let Some(audio_source) = audio_sources.get(&audio_source_handle) {
-                self.play_source(audio_source);
+            let queued = queue.pop_back().unwrap();
+            if queued.playback.is_stopped() {
+                // stopped before its source loaded
+            } else if let Some(audio_source) = audio_sources.get(&queued.source) {
+                self.play_source(audio_source, queued, mixer);
             } else {
                 // audio source hasn't loaded yet. add it back to the queue
-                queue.push_front(audio_source_handle);
+                queue.push_front(queued);
             }
             i += 1;
         }
     }
+
+    /// Applies the state of the playbacks and the mixer to the sinks, dropping the sinks that
+    /// ended. Returns the buses with sounds playing.
+    fn update_playing(&mut self, delta_seconds: f32, mixer: &AudioMixer) -> HashSet<AudioBus> {
+        let mut active = HashSet::default();
+        for playing in self.playing.iter_mut() {
+            playing.decoders.update();
+        }
+        self.playing.retain(|playing| {
+            let (volume, bus, paused) = playing.playback.update(delta_seconds);
+            if playing.playback.is_stopped() || playing.sink.empty() {
+                // dropping the sink stops its playback
+                playing.playback.finish();
+                return false;
+            }
+            playing.sink.set_volume(volume * mixer.volume(&bus));
+            if paused != playing.sink.is_paused() {
+                if paused {
+                    playing.sink.pause();
+                } else {
+                    playing.sink.play();
+                }
+            }
+            if !paused {
+                active.insert(bus);
+            }
+            true
+        });
+        active
+    }
 }
 
-/// Plays audio currently queued in the [Audio] resource through the [AudioOutput] resource
+/// Plays audio currently queued in the [Audio] resource through the [AudioOutput] resource, and
+/// updates the volumes of the playing sounds from their [AudioPlayback] and the [AudioMixer], if
+/// there is one
 pub fn play_queued_audio_system<P: Asset>(world: &mut World)
 where
-    P: Decodable,
-    <P as Decodable>::Decoder: rodio::Source + Send + Sync,
+    P: Decodable + Clone,
+    <P as Decodable>::Decoder: rodio::Source + Send + Sync + 'static,
     <<P as Decodable>::Decoder as Iterator>::Item: rodio::Sample + Send + Sync,
 {
     let world = world.cell();
-    let audio_output = world.get_non_send::<AudioOutput<P>>().unwrap();
+    let mut audio_output = world.get_non_send_mut::<AudioOutput<P>>().unwrap();
     let mut audio = world.get_resource_mut::<Audio<P>>().unwrap();
+    let mut mixer = world.get_resource_mut::<AudioMixer>();
+    let delta_seconds = world
+        .get_resource::<Time>()
+        .map_or(0.0, |time| time.delta_seconds());
 
+    // without a mixer, sounds play as on buses with default settings
+    let default_mixer;
+    let settings = match mixer.as_deref() {
+        Some(mixer) => mixer,
+        None => {
+            default_mixer = AudioMixer::default();
+            &default_mixer
+        }
+    };
     if let Some(audio_sources) = world.get_resource::<Assets<P>>() {
-        audio_output.try_play_queued(&*audio_sources, &mut *audio);
+        audio_output.try_play_queued(&*audio_sources, &mut *audio, settings);
     };
+    let active = audio_output.update_playing(delta_seconds, settings);
+    if let Some(mixer) = &mut mixer {
+        mixer.update(delta_seconds, active);
+    }
 }
@@ -4,7 +4,7 @@ use bevy_reflect::TypeUuid;
 use bevy_utils::BoxedFuture;
 use std::{io::Cursor, sync::Arc};
 
-/// A source of audio data
+/// A source of audio data. The encoded bytes are kept in memory and decoded while the sound plays.
 #[derive(Debug, Clone, TypeUuid)]
 #[uuid = "7a14806a-672b-443b-8d16-4f18afefa463"]
 pub struct AudioSource {
@@ -0,0 +1,331 @@
+use bevy_utils::{Duration, HashMap, HashSet};
+use rodio::Source;
+use std::borrow::Cow;
+
+/// A named group of sounds sharing a volume, effects and ducking. Every bus is mixed into
+/// [AudioBus::MASTER].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AudioBus(pub Cow<'static, str>);
+
+impl AudioBus {
+    pub const MASTER: AudioBus = AudioBus(Cow::Borrowed("master"));
+    pub const MUSIC: AudioBus = AudioBus(Cow::Borrowed("music"));
+    pub const SFX: AudioBus = AudioBus(Cow::Borrowed("sfx"));
+    pub const VOICE: AudioBus = AudioBus(Cow::Borrowed("voice"));
+
+    pub fn new(name: impl Into<Cow<'static, str>>) -> Self {
+        AudioBus(name.into())
+    }
+}
+
+impl Default for AudioBus {
+    fn default() -> Self {
+        AudioBus::MASTER
+    }
+}
+
+/// An effect inserted in a bus, applied to its sounds when they start playing
+#[derive(Debug, Clone, PartialEq)]
+pub enum AudioEffect {
+    /// Removes the frequencies above `frequency`, in Hz
+    LowPass { frequency: u32 },
+    /// Mixes in a copy of the sound delayed by `delay` and scaled by `amplitude`. Only `delay`
+    /// of the sound is kept, so looped and streamed sounds can use it too.
+    Reverb { delay: Duration, amplitude: f32 },
+    /// Multiplies the samples by `gain`, before the volume of the bus
+    Gain(f32),
+}
+
+pub(crate) type MixedSource = Box<dyn Source<Item = f32> + Send>;
+
+impl AudioEffect {
+    pub(crate) fn apply(&self, source: MixedSource) -> MixedSource {
+        match *self {
+            AudioEffect::LowPass { frequency } => Box::new(source.low_pass(frequency)),
+            AudioEffect::Reverb { delay, amplitude } => {
+                Box::new(Reverb::new(source, delay, amplitude))
+            }
+            AudioEffect::Gain(gain) => Box::new(source.amplify(gain)),
+        }
+    }
+}
+
+/// Mixes the samples of a source from `delay` ago into it, through a delay line holding only
+/// `delay` of samples. Unlike [Source::reverb], which buffers the whole source, memory doesn't
+/// grow with infinite sources.
+pub(crate) struct Reverb<I> {
+    input: I,
+    delay: Duration,
+    amplitude: f32,
+    delay_line: Vec<f32>,
+    position: usize,
+    /// The channels and sample rate the delay line is sized for
+    format: (u16, u32),
+    /// The samples left in the current frame, after which the format can change
+    frame_remaining: Option<usize>,
+}
+
+impl<I: Source<Item = f32>> Reverb<I> {
+    pub fn new(input: I, delay: Duration, amplitude: f32) -> Self {
+        let frame_remaining = input.current_frame_len();
+        let mut reverb = Reverb {
+            input,
+            delay,
+            amplitude,
+            delay_line: Vec::new(),
+            position: 0,
+            format: (0, 0),
+            frame_remaining,
+        };
+        reverb.resize_delay_line();
+        reverb
+    }
+
+    fn resize_delay_line(&mut self) {
+        let format = (self.input.channels(), self.input.sample_rate());
+        if format == self.format {
+            return;
+        }
+        self.format = format;
+        let frames = (self.delay.as_secs_f64() * format.1 as f64).round() as usize;
+        self.delay_line.clear();
+        self.delay_line.resize(frames * format.0 as usize, 0.0);
+        self.position = 0;
+    }
+}
+
+impl<I: Source<Item = f32>> Iterator for Reverb<I> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let sample = self.input.next()?;
+        let output = if self.delay_line.is_empty() {
+            sample
+        } else {
+            let delayed = std::mem::replace(&mut self.delay_line[self.position], sample);
+            self.position = (self.position + 1) % self.delay_line.len();
+            sample + delayed * self.amplitude
+        };
+        if let Some(remaining) = self.frame_remaining.as_mut() {
+            *remaining = remaining.saturating_sub(1);
+            if *remaining == 0 {
+                self.frame_remaining = self.input.current_frame_len();
+                self.resize_delay_line();
+            }
+        }
+        Some(output)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I: Source<Item = f32>> Source for Reverb<I> {
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+}
+
+/// Lowers the volume of a bus while sounds play on another one, like music under dialogue
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ducking {
+    /// The bus whose sounds duck this one
+    pub by: AudioBus,
+    /// The volume multiplier applied while ducked
+    pub volume: f32,
+    /// The time taken to duck and to recover
+    pub fade: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioBusSettings {
+    pub volume: f32,
+    pub muted: bool,
+    /// Applied in order to the sounds starting on the bus. Changing them doesn't affect the
+    /// sounds already playing.
+    pub effects: Vec<AudioEffect>,
+    pub ducking: Option<Ducking>,
+}
+
+impl Default for AudioBusSettings {
+    fn default() -> Self {
+        AudioBusSettings {
+            volume: 1.0,
+            muted: false,
+            effects: Vec::new(),
+            ducking: None,
+        }
+    }
+}
+
+/// The settings of the [AudioBus]es. Buses are created with default settings when first used.
+#[derive(Debug, Clone)]
+pub struct AudioMixer {
+    buses: HashMap<AudioBus, AudioBusSettings>,
+    /// The ducking multiplier of each ducked bus
+    duck_levels: HashMap<AudioBus, f32>,
+    /// The buses with sounds playing
+    active: HashSet<AudioBus>,
+}
+
+impl Default for AudioMixer {
+    fn default() -> Self {
+        let mut mixer = AudioMixer {
+            buses: Default::default(),
+            duck_levels: Default::default(),
+            active: Default::default(),
+        };
+        for bus in [
+            AudioBus::MASTER,
+            AudioBus::MUSIC,
+            AudioBus::SFX,
+            AudioBus::VOICE,
+        ]
+        .iter()
+        {
+            mixer.bus_mut(bus);
+        }
+        mixer
+    }
+}
+
+impl AudioMixer {
+    pub fn bus(&self, bus: &AudioBus) -> Option<&AudioBusSettings> {
+        self.buses.get(bus)
+    }
+
+    pub fn bus_mut(&mut self, bus: &AudioBus) -> &mut AudioBusSettings {
+        self.buses.entry(bus.clone()).or_default()
+    }
+
+    pub fn buses(&self) -> impl Iterator<Item = (&AudioBus, &AudioBusSettings)> {
+        self.buses.iter()
+    }
+
+    pub fn set_volume(&mut self, bus: &AudioBus, volume: f32) {
+        self.bus_mut(bus).volume = volume;
+    }
+
+    /// Returns true if sounds are playing on `bus`
+    pub fn is_active(&self, bus: &AudioBus) -> bool {
+        self.active.contains(bus)
+    }
+
+    fn own_volume(&self, bus: &AudioBus) -> f32 {
+        match self.buses.get(bus) {
+            Some(settings) if settings.muted => 0.0,
+            Some(settings) => settings.volume * self.duck_levels.get(bus).copied().unwrap_or(1.0),
+            None => 1.0,
+        }
+    }
+
+    /// The volume sounds on `bus` are played with, including the master volume and ducking
+    pub fn volume(&self, bus: &AudioBus) -> f32 {
+        if *bus == AudioBus::MASTER {
+            self.own_volume(bus)
+        } else {
+            self.own_volume(bus) * self.own_volume(&AudioBus::MASTER)
+        }
+    }
+
+    /// The effects applied to sounds starting on `bus`, followed by the master effects
+    pub fn effects(&self, bus: &AudioBus) -> Vec<AudioEffect> {
+        let mut effects = Vec::new();
+        let mut add = |bus: &AudioBus| {
+            if let Some(settings) = self.buses.get(bus) {
+                effects.extend(settings.effects.iter().cloned());
+            }
+        };
+        add(bus);
+        if *bus != AudioBus::MASTER {
+            add(&AudioBus::MASTER);
+        }
+        effects
+    }
+
+    /// Records the buses with playing sounds and moves the ducked buses toward their level
+    pub fn update(&mut self, delta_seconds: f32, active: HashSet<AudioBus>) {
+        self.active = active;
+        for (bus, settings) in self.buses.iter() {
+            let ducking = match &settings.ducking {
+                Some(ducking) => ducking,
+                None => {
+                    self.duck_levels.remove(bus);
+                    continue;
+                }
+            };
+            let target = if self.active.contains(&ducking.by) {
+                ducking.volume
+            } else {
+                1.0
+            };
+            let level = self.duck_levels.entry(bus.clone()).or_insert(1.0);
+            let fade = ducking.fade.as_secs_f32();
+            let step = if fade > 0.0 {
+                delta_seconds / fade
+            } else {
+                f32::INFINITY
+            };
+            *level = if *level < target {
+                (*level + step).min(target)
+            } else {
+                (*level - step).max(target)
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bus_volumes_and_ducking() {
+        let mut mixer = AudioMixer::default();
+        mixer.set_volume(&AudioBus::MASTER, 0.5);
+        mixer.set_volume(&AudioBus::MUSIC, 0.8);
+        mixer.bus_mut(&AudioBus::SFX).muted = true;
+        assert_eq!(mixer.volume(&AudioBus::MUSIC), 0.4);
+        assert_eq!(mixer.volume(&AudioBus::SFX), 0.0);
+        assert_eq!(mixer.volume(&AudioBus::new("ambience")), 0.5);
+
+        mixer.bus_mut(&AudioBus::MUSIC).ducking = Some(Ducking {
+            by: AudioBus::VOICE,
+            volume: 0.5,
+            fade: Duration::from_secs(1),
+        });
+        let mut active = HashSet::default();
+        active.insert(AudioBus::VOICE);
+        mixer.update(0.25, active.clone());
+        assert_eq!(mixer.volume(&AudioBus::MUSIC), 0.3);
+        mixer.update(1.0, active);
+        assert_eq!(mixer.volume(&AudioBus::MUSIC), 0.2);
+        mixer.update(2.0, HashSet::default());
+        assert_eq!(mixer.volume(&AudioBus::MUSIC), 0.4);
+    }
+
+    #[test]
+    fn reverb_keeps_only_the_delay() {
+        let samples = rodio::buffer::SamplesBuffer::new(1, 10, vec![1.0f32, 0.0, 0.0, 0.0]);
+        let reverb = Reverb::new(samples, Duration::from_millis(200), 0.5);
+        assert_eq!(reverb.collect::<Vec<_>>(), vec![1.0, 0.0, 0.5, 0.0]);
+
+        let looped = rodio::buffer::SamplesBuffer::new(2, 10, vec![1.0f32, 0.0]).repeat_infinite();
+        let mut reverb = Reverb::new(looped, Duration::from_millis(200), 0.5);
+        assert_eq!(reverb.by_ref().take(10_000).count(), 10_000);
+        assert_eq!(reverb.delay_line.len(), 4);
+    }
+}
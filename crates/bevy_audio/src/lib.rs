@@ -1,18 +1,34 @@
 mod audio;
 mod audio_output;
 mod audio_source;
+mod bus;
+#[cfg(feature = "microphone")]
+mod microphone;
+mod spatial_audio;
+mod streaming;
 
 pub mod prelude {
-    pub use crate::{Audio, AudioOutput, AudioSource, Decodable};
+    pub use crate::{
+        Audio, AudioBus, AudioEmitter, AudioListener, AudioMixer, AudioOutput, AudioPlayback,
+        AudioSource, Decodable, PlaybackSettings, Rolloff,
+    };
+
+    #[cfg(feature = "microphone")]
+    pub use crate::{Microphone, MicrophonePlugin};
 }
 
 pub use audio::*;
 pub use audio_output::*;
 pub use audio_source::*;
+pub use bus::*;
+#[cfg(feature = "microphone")]
+pub use microphone::*;
+pub use spatial_audio::*;
 
 use bevy_app::prelude::*;
 use bevy_asset::AddAsset;
-use bevy_ecs::system::IntoExclusiveSystem;
+use bevy_ecs::system::{IntoExclusiveSystem, IntoSystem};
+use bevy_transform::TransformSystem;
 
 /// Adds support for audio playback to an App
 #[derive(Default)]
@@ -23,10 +39,19 @@ impl Plugin for AudioPlugin {
         app.init_non_send_resource::<AudioOutput<AudioSource>>()
             .add_asset::<AudioSource>()
             .init_asset_loader::<Mp3Loader>()
+            .init_non_send_resource::<SpatialAudioSinks>()
             .init_resource::<Audio<AudioSource>>()
+            .init_resource::<AudioMixer>()
+            .register_type::<AudioListener>()
             .add_system_to_stage(
                 CoreStage::PostUpdate,
                 play_queued_audio_system::<AudioSource>.exclusive_system(),
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                spatial_audio_system::<AudioSource>
+                    .system()
+                    .after(TransformSystem::TransformPropagate),
             );
     }
 }
@@ -0,0 +1,211 @@
+use anyhow::anyhow;
+use bevy_app::prelude::*;
+use bevy_ecs::{system::IntoExclusiveSystem, world::World};
+use bevy_utils::{tracing::warn, Duration};
+use cpal::{
+    traits::{DeviceTrait, HostTrait, StreamTrait},
+    Sample, SampleFormat, StreamConfig,
+};
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+/// Captures sound from the default input device into the [Microphone] resource
+#[derive(Default)]
+pub struct MicrophonePlugin;
+
+impl Plugin for MicrophonePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        let mut microphone = Microphone::default();
+        match MicrophoneStream::open() {
+            Ok(stream) => {
+                microphone.device_name = Some(stream.device_name.clone());
+                microphone.channels = stream.channels;
+                microphone.sample_rate = stream.sample_rate;
+                app.insert_non_send_resource(stream);
+            }
+            Err(err) => warn!("Failed to open the microphone. {}", err),
+        }
+        app.insert_resource(microphone)
+            .add_system_to_stage(CoreStage::PreUpdate, microphone_system.exclusive_system());
+    }
+}
+
+/// The sound captured by the microphone during the last frame
+#[derive(Debug, Clone)]
+pub struct Microphone {
+    /// Pauses the capture while false
+    pub capturing: bool,
+    device_name: Option<String>,
+    channels: u16,
+    sample_rate: u32,
+    samples: Vec<f32>,
+}
+
+impl Default for Microphone {
+    fn default() -> Self {
+        Microphone {
+            capturing: true,
+            device_name: None,
+            channels: 0,
+            sample_rate: 0,
+            samples: Vec::new(),
+        }
+    }
+}
+
+impl Microphone {
+    /// Returns false if no input device could be opened
+    pub fn is_available(&self) -> bool {
+        self.device_name.is_some()
+    }
+
+    pub fn device_name(&self) -> Option<&str> {
+        self.device_name.as_deref()
+    }
+
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// The PCM samples captured since the previous frame, interleaved by channel
+    pub fn samples(&self) -> &[f32] {
+        &self.samples
+    }
+
+    /// The length of the sound captured since the previous frame
+    pub fn duration(&self) -> Duration {
+        if self.sample_rate == 0 || self.channels == 0 {
+            return Duration::from_secs(0);
+        }
+        let frames = self.samples.len() as f64 / self.channels as f64;
+        Duration::from_secs_f64(frames / self.sample_rate as f64)
+    }
+
+    /// The root mean square of the captured samples, from 0 for silence to 1
+    pub fn level(&self) -> f32 {
+        if self.samples.is_empty() {
+            return 0.0;
+        }
+        let sum: f32 = self.samples.iter().map(|sample| sample * sample).sum();
+        (sum / self.samples.len() as f32).sqrt()
+    }
+}
+
+/// The capture stream of the [MicrophonePlugin]. The samples are buffered on the audio thread
+/// until [microphone_system] moves them to the [Microphone] resource.
+pub struct MicrophoneStream {
+    stream: cpal::Stream,
+    buffer: Arc<Mutex<Vec<f32>>>,
+    capturing: bool,
+    device_name: String,
+    channels: u16,
+    sample_rate: u32,
+}
+
+impl MicrophoneStream {
+    fn open() -> anyhow::Result<Self> {
+        let device = cpal::default_host()
+            .default_input_device()
+            .ok_or_else(|| anyhow!("No input device is available"))?;
+        let device_name = device.name()?;
+        let supported_config = device.default_input_config()?;
+        let sample_format = supported_config.sample_format();
+        let config: StreamConfig = supported_config.into();
+        // keeps at most a second of sound when the frames are slow
+        let capacity = config.sample_rate.0 as usize * config.channels as usize;
+        let buffer = Arc::new(Mutex::new(Vec::with_capacity(capacity)));
+        let stream = match sample_format {
+            SampleFormat::F32 => build_stream::<f32>(&device, &config, buffer.clone(), capacity),
+            SampleFormat::I16 => build_stream::<i16>(&device, &config, buffer.clone(), capacity),
+            SampleFormat::U16 => build_stream::<u16>(&device, &config, buffer.clone(), capacity),
+        }?;
+        stream.play()?;
+        Ok(MicrophoneStream {
+            stream,
+            buffer,
+            capturing: true,
+            device_name,
+            channels: config.channels,
+            sample_rate: config.sample_rate.0,
+        })
+    }
+}
+
+fn build_stream<T: Sample>(
+    device: &cpal::Device,
+    config: &StreamConfig,
+    buffer: Arc<Mutex<Vec<f32>>>,
+    capacity: usize,
+) -> Result<cpal::Stream, cpal::BuildStreamError> {
+    device.build_input_stream(
+        config,
+        move |data: &[T], _: &cpal::InputCallbackInfo| {
+            push_samples(&mut buffer.lock(), data, capacity);
+        },
+        |err| warn!("Microphone stream error. {}", err),
+    )
+}
+
+/// Appends `data` to `buffer`, dropping the oldest samples past `capacity`
+fn push_samples<T: Sample>(buffer: &mut Vec<f32>, data: &[T], capacity: usize) {
+    buffer.extend(data.iter().map(|sample| sample.to_f32()));
+    if buffer.len() > capacity {
+        let excess = buffer.len() - capacity;
+        buffer.drain(..excess);
+    }
+}
+
+/// Moves the samples captured since the previous frame to the [Microphone] resource, and pauses
+/// or resumes the capture to match [Microphone::capturing]
+pub fn microphone_system(world: &mut World) {
+    let world = world.cell();
+    let mut microphone = world.get_resource_mut::<Microphone>().unwrap();
+    microphone.samples.clear();
+    let mut stream = match world.get_non_send_mut::<MicrophoneStream>() {
+        Some(stream) => stream,
+        None => return,
+    };
+    if microphone.capturing != stream.capturing {
+        let result = if microphone.capturing {
+            stream.stream.play().map_err(anyhow::Error::from)
+        } else {
+            stream.stream.pause().map_err(anyhow::Error::from)
+        };
+        match result {
+            Ok(()) => stream.capturing = microphone.capturing,
+            Err(err) => warn!("Failed to pause or resume the microphone. {}", err),
+        }
+    }
+    let mut buffer = stream.buffer.lock();
+    if stream.capturing {
+        microphone.samples.extend(buffer.drain(..));
+    } else {
+        buffer.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buffers_and_measures_samples() {
+        let mut buffer = Vec::new();
+        push_samples(&mut buffer, &[0.5f32, -0.5, 0.5], 4);
+        push_samples(&mut buffer, &[i16::MIN, 0], 4);
+        assert_eq!(buffer, vec![-0.5, 0.5, -1.0, 0.0]);
+
+        let microphone = Microphone {
+            channels: 2,
+            sample_rate: 4,
+            samples: vec![0.5, -0.5, 0.5, -0.5],
+            ..Default::default()
+        };
+        assert_eq!(microphone.level(), 0.5);
+        assert_eq!(microphone.duration(), Duration::from_millis(500));
+    }
+}
@@ -0,0 +1,341 @@
+use crate::{AudioBus, AudioMixer, AudioOutput, AudioSource, Decodable};
+use bevy_asset::{Asset, Assets, Handle, HandleId};
+use bevy_core::Time;
+use bevy_ecs::{
+    entity::Entity,
+    reflect::ReflectComponent,
+    system::{NonSend, NonSendMut, Query, Res},
+};
+use bevy_math::Vec3;
+use bevy_reflect::Reflect;
+use bevy_transform::components::GlobalTransform;
+use bevy_utils::HashMap;
+use rodio::SpatialSink;
+
+/// The point spatial audio is heard from. Usually attached to the camera entity.
+///
+/// Only the first listener found is used.
+#[derive(Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct AudioListener {
+    /// The speed of sound in world units per second, used for the doppler effect
+    pub speed_of_sound: f32,
+}
+
+impl Default for AudioListener {
+    fn default() -> Self {
+        AudioListener {
+            speed_of_sound: 343.0,
+        }
+    }
+}
+
+/// How the volume of an [AudioEmitter] falls off with its distance to the [AudioListener]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rolloff {
+    /// The volume does not depend on the distance
+    None,
+    /// Full volume up to `min_distance`, then falls off linearly to silence at `max_distance`
+    Linear {
+        min_distance: f32,
+        max_distance: f32,
+    },
+    /// Full volume up to `reference_distance`, then falls off with the inverse of the distance.
+    /// This is the most physically accurate model when `rolloff_factor` is 1.0.
+    Inverse {
+        reference_distance: f32,
+        rolloff_factor: f32,
+    },
+    /// Full volume up to `reference_distance`, then falls off with the distance raised to
+    /// `-rolloff_factor`
+    Exponential {
+        reference_distance: f32,
+        rolloff_factor: f32,
+    },
+}
+
+/// The smallest reference distance used by [Rolloff::Inverse], so that a reference distance of
+/// 0.0 doesn't divide by a distance of 0.0
+const MIN_REFERENCE_DISTANCE: f32 = 1e-3;
+
+impl Default for Rolloff {
+    fn default() -> Self {
+        Rolloff::Inverse {
+            reference_distance: 1.0,
+            rolloff_factor: 1.0,
+        }
+    }
+}
+
+impl Rolloff {
+    /// Returns the volume multiplier, from [0.0, 1.0], for an emitter at the given distance
+    pub fn attenuation(&self, distance: f32) -> f32 {
+        let attenuation = match *self {
+            Rolloff::None => 1.0,
+            Rolloff::Linear {
+                min_distance,
+                max_distance,
+            } => {
+                if max_distance <= min_distance {
+                    return if distance > min_distance { 0.0 } else { 1.0 };
+                }
+                1.0 - (distance - min_distance) / (max_distance - min_distance)
+            }
+            Rolloff::Inverse {
+                reference_distance,
+                rolloff_factor,
+            } => {
+                let reference_distance = reference_distance.max(MIN_REFERENCE_DISTANCE);
+                let distance = distance.max(reference_distance);
+                reference_distance
+                    / (reference_distance + rolloff_factor * (distance - reference_distance))
+            }
+            Rolloff::Exponential {
+                reference_distance,
+                rolloff_factor,
+            } => {
+                if reference_distance <= 0.0 {
+                    return 1.0;
+                }
+                let distance = distance.max(reference_distance);
+                (distance / reference_distance).powf(-rolloff_factor)
+            }
+        };
+        attenuation.clamp(0.0, 1.0)
+    }
+}
+
+/// Plays an audio source positioned at the entity's [GlobalTransform]. Panning, attenuation and
+/// doppler shift are updated every frame relative to the [AudioListener].
+///
+/// Playback starts once the source has loaded and stops when the component is removed. Changing
+/// `source` restarts playback with the new source.
+#[derive(Debug, Clone)]
+pub struct AudioEmitter<P = AudioSource>
+where
+    P: Asset + Decodable,
+{
+    pub source: Handle<P>,
+    pub volume: f32,
+    /// The bus whose volume applies to the emitter. Bus effects don't apply to spatial audio.
+    pub bus: AudioBus,
+    pub rolloff: Rolloff,
+    /// Scales the doppler effect. 0.0 disables it.
+    pub doppler_factor: f32,
+}
+
+impl<P> AudioEmitter<P>
+where
+    P: Asset + Decodable,
+{
+    pub fn new(source: Handle<P>) -> Self {
+        AudioEmitter {
+            source,
+            volume: 1.0,
+            bus: AudioBus::SFX,
+            rolloff: Rolloff::default(),
+            doppler_factor: 1.0,
+        }
+    }
+}
+
+/// Returns the playback speed multiplier caused by the relative motion of an emitter and a
+/// listener. `direction` points from the listener to the emitter.
+pub fn doppler_pitch(
+    speed_of_sound: f32,
+    doppler_factor: f32,
+    listener_velocity: Vec3,
+    emitter_velocity: Vec3,
+    direction: Vec3,
+) -> f32 {
+    if doppler_factor <= 0.0 || speed_of_sound <= 0.0 {
+        return 1.0;
+    }
+    // anything moving at or above the speed of sound would produce an infinite or negative pitch
+    let max_speed = speed_of_sound * 0.99;
+    let listener_speed =
+        (listener_velocity.dot(direction) * doppler_factor).clamp(-max_speed, max_speed);
+    let emitter_speed =
+        (emitter_velocity.dot(direction) * doppler_factor).clamp(-max_speed, max_speed);
+    (speed_of_sound + listener_speed) / (speed_of_sound + emitter_speed)
+}
+
+// the emitter is placed on a unit sphere around the listener, so rodio's built in attenuation
+// doesn't compound with the emitter's rolloff. the ears are close enough together to keep
+// rodio's own distance falloff at ~1.0 while still producing full panning.
+const LEFT_EAR: [f32; 3] = [-0.05, 0.0, 0.0];
+const RIGHT_EAR: [f32; 3] = [0.05, 0.0, 0.0];
+
+struct EmitterState {
+    sink: SpatialSink,
+    source: HandleId,
+    previous_position: Vec3,
+}
+
+/// Tracks the playing [AudioEmitter] sinks
+#[derive(Default)]
+pub struct SpatialAudioSinks {
+    emitters: HashMap<Entity, EmitterState>,
+    previous_listener_position: Option<Vec3>,
+}
+
+/// Starts, stops and updates the playback of [AudioEmitter]s relative to the [AudioListener]
+pub fn spatial_audio_system<P>(
+    time: Res<Time>,
+    audio_output: NonSend<AudioOutput<P>>,
+    mut sinks: NonSendMut<SpatialAudioSinks>,
+    audio_sources: Option<Res<Assets<P>>>,
+    mixer: Option<Res<AudioMixer>>,
+    listeners: Query<(&AudioListener, &GlobalTransform)>,
+    emitters: Query<(Entity, &AudioEmitter<P>, &GlobalTransform)>,
+) where
+    P: Asset + Decodable,
+    <P as Decodable>::Decoder: rodio::Source + Send + Sync,
+    <<P as Decodable>::Decoder as Iterator>::Item: rodio::Sample + Send + Sync,
+{
+    let sinks = &mut *sinks;
+    // dropping a sink stops its playback
+    sinks
+        .emitters
+        .retain(|entity, _| emitters.get(*entity).is_ok());
+
+    let (listener, listener_transform) = match listeners.iter().next() {
+        Some(listener) => listener,
+        None => return,
+    };
+
+    let delta_seconds = time.delta_seconds();
+    let velocity = |position: Vec3, previous_position: Vec3| {
+        if delta_seconds > 0.0 {
+            (position - previous_position) / delta_seconds
+        } else {
+            Vec3::ZERO
+        }
+    };
+    let listener_position = listener_transform.translation;
+    let listener_velocity = velocity(
+        listener_position,
+        sinks
+            .previous_listener_position
+            .unwrap_or(listener_position),
+    );
+    sinks.previous_listener_position = Some(listener_position);
+    let listener_rotation = listener_transform.rotation.inverse();
+
+    for (entity, emitter, transform) in emitters.iter() {
+        let position = transform.translation;
+        let offset = position - listener_position;
+        let distance = offset.length();
+        let direction = if distance > f32::EPSILON {
+            offset / distance
+        } else {
+            Vec3::ZERO
+        };
+        let local_direction = listener_rotation * direction;
+
+        let restart = sinks
+            .emitters
+            .get(&entity)
+            .map_or(true, |state| state.source != emitter.source.id);
+        if restart {
+            let audio_source = match audio_sources
+                .as_ref()
+                .and_then(|audio_sources| audio_sources.get(&emitter.source))
+            {
+                Some(audio_source) => audio_source,
+                // the source hasn't loaded yet, try again next frame
+                None => continue,
+            };
+            let sink = SpatialSink::try_new(
+                audio_output.stream_handle(),
+                local_direction.into(),
+                LEFT_EAR,
+                RIGHT_EAR,
+            )
+            .unwrap();
+            sink.append(audio_source.decoder());
+            sinks.emitters.insert(
+                entity,
+                EmitterState {
+                    sink,
+                    source: emitter.source.id,
+                    previous_position: position,
+                },
+            );
+        }
+
+        let state = sinks.emitters.get_mut(&entity).unwrap();
+        let emitter_velocity = velocity(position, state.previous_position);
+        state.previous_position = position;
+
+        state.sink.set_emitter_position(local_direction.into());
+        let bus_volume = mixer
+            .as_ref()
+            .map_or(1.0, |mixer| mixer.volume(&emitter.bus));
+        state
+            .sink
+            .set_volume(emitter.volume * bus_volume * emitter.rolloff.attenuation(distance));
+        state.sink.set_speed(doppler_pitch(
+            listener.speed_of_sound,
+            emitter.doppler_factor,
+            listener_velocity,
+            emitter_velocity,
+            direction,
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rolloff_attenuation() {
+        let linear = Rolloff::Linear {
+            min_distance: 1.0,
+            max_distance: 3.0,
+        };
+        assert_eq!(linear.attenuation(0.5), 1.0);
+        assert_eq!(linear.attenuation(2.0), 0.5);
+        assert_eq!(linear.attenuation(5.0), 0.0);
+
+        let inverse = Rolloff::Inverse {
+            reference_distance: 1.0,
+            rolloff_factor: 1.0,
+        };
+        assert_eq!(inverse.attenuation(0.5), 1.0);
+        assert_eq!(inverse.attenuation(4.0), 0.25);
+        let point_source = Rolloff::Inverse {
+            reference_distance: 0.0,
+            rolloff_factor: 1.0,
+        };
+        assert_eq!(point_source.attenuation(0.0), 1.0);
+        assert!(point_source.attenuation(2.0) > 0.0);
+
+        let exponential = Rolloff::Exponential {
+            reference_distance: 1.0,
+            rolloff_factor: 2.0,
+        };
+        assert_eq!(exponential.attenuation(2.0), 0.25);
+        assert_eq!(Rolloff::None.attenuation(100.0), 1.0);
+    }
+
+    #[test]
+    fn doppler() {
+        let direction = Vec3::X;
+        // stationary
+        assert_eq!(
+            doppler_pitch(343.0, 1.0, Vec3::ZERO, Vec3::ZERO, direction),
+            1.0
+        );
+        // emitter approaching the listener raises the pitch
+        assert!(doppler_pitch(343.0, 1.0, Vec3::ZERO, -Vec3::X * 10.0, direction) > 1.0);
+        // listener moving away from the emitter lowers the pitch
+        assert!(doppler_pitch(343.0, 1.0, -Vec3::X * 10.0, Vec3::ZERO, direction) < 1.0);
+        // disabled
+        assert_eq!(
+            doppler_pitch(343.0, 0.0, Vec3::ZERO, -Vec3::X * 10.0, direction),
+            1.0
+        );
+    }
+}
@@ -0,0 +1,283 @@
+use crate::Decodable;
+use bevy_utils::Duration;
+use parking_lot::Mutex;
+use rodio::{source::SamplesConverter, Sample, Source};
+use std::sync::{
+    atomic::{AtomicBool, AtomicU64, Ordering},
+    Arc,
+};
+
+/// Shared between an [AudioPlayback](crate::AudioPlayback) and the source decoding its sound on
+/// the audio thread
+#[derive(Debug, Default)]
+pub(crate) struct StreamControl {
+    /// The samples played since the start of the sound, counting every channel
+    samples: AtomicU64,
+    samples_per_second: AtomicU64,
+    seek: Mutex<Option<Duration>>,
+}
+
+impl StreamControl {
+    pub fn position(&self) -> Duration {
+        let samples_per_second = self.samples_per_second.load(Ordering::Relaxed);
+        if samples_per_second == 0 {
+            return Duration::from_secs(0);
+        }
+        let samples = self.samples.load(Ordering::Relaxed);
+        Duration::from_secs_f64(samples as f64 / samples_per_second as f64)
+    }
+
+    pub fn seek(&self, position: Duration) {
+        *self.seek.lock() = Some(position);
+    }
+
+    fn take_seek(&self) -> Option<Duration> {
+        self.seek.lock().take()
+    }
+}
+
+/// A decoder positioned by a seek, with the samples it skipped
+struct SeekedDecoder<D> {
+    seek: u64,
+    decoder: SamplesConverter<D, f32>,
+    samples: u64,
+}
+
+/// Decoders prepared off the audio thread, that a [StreamingSource] swaps in without blocking
+struct PreparedDecoders<D> {
+    /// The last seek requested. Decoders prepared for earlier seeks are dropped.
+    latest_seek: AtomicU64,
+    seeked: Mutex<Option<SeekedDecoder<D>>>,
+    seek_ready: AtomicBool,
+    /// A decoder at the start of the sound, continuing a looped sound once it ends
+    restart: Mutex<Option<SamplesConverter<D, f32>>>,
+}
+
+impl<D> PreparedDecoders<D>
+where
+    D: Source,
+    D::Item: Sample,
+{
+    fn offer_seek(&self, seeked: SeekedDecoder<D>) {
+        let mut slot = self.seeked.lock();
+        if seeked.seek == self.latest_seek.load(Ordering::Acquire) {
+            *slot = Some(seeked);
+            self.seek_ready.store(true, Ordering::Release);
+        }
+    }
+}
+
+/// Prepares the decoders of a [StreamingSource] off the audio thread: seeks decode up to the
+/// requested position on their own thread, and looped sounds always have their next loop's
+/// decoder ready.
+pub(crate) struct StreamDecoders<P: Decodable> {
+    source: P,
+    looped: bool,
+    control: Arc<StreamControl>,
+    prepared: Arc<PreparedDecoders<P::Decoder>>,
+    seeks: u64,
+}
+
+impl<P> StreamDecoders<P>
+where
+    P: Decodable + Clone,
+    P::Decoder: Source + Send + 'static,
+    <P::Decoder as Iterator>::Item: Sample,
+{
+    /// Returns the decoders of `source` and the source playing it from the start
+    pub fn new(
+        source: P,
+        looped: bool,
+        control: Arc<StreamControl>,
+    ) -> (Self, StreamingSource<P::Decoder>) {
+        let decoder = source.decoder().convert_samples();
+        let samples_per_second = decoder.sample_rate() as u64 * decoder.channels() as u64;
+        control
+            .samples_per_second
+            .store(samples_per_second, Ordering::Relaxed);
+        control.samples.store(0, Ordering::Relaxed);
+        let prepared = Arc::new(PreparedDecoders {
+            latest_seek: AtomicU64::new(0),
+            seeked: Mutex::new(None),
+            seek_ready: AtomicBool::new(false),
+            restart: Mutex::new(None),
+        });
+        let mut decoders = StreamDecoders {
+            source,
+            looped,
+            control: control.clone(),
+            prepared: prepared.clone(),
+            seeks: 0,
+        };
+        decoders.update();
+        let streaming_source = StreamingSource {
+            decoder,
+            looped,
+            control,
+            prepared,
+        };
+        (decoders, streaming_source)
+    }
+
+    /// Starts preparing the decoder of the last seek requested, and replaces the decoder of the
+    /// next loop once it was used
+    pub fn update(&mut self) {
+        if let Some(position) = self.control.take_seek() {
+            self.seeks += 1;
+            let seek = self.seeks;
+            self.prepared.latest_seek.store(seek, Ordering::Release);
+            let source = self.source.clone();
+            let prepared = self.prepared.clone();
+            std::thread::Builder::new()
+                .name("audio seek".to_string())
+                .spawn(move || prepared.offer_seek(decode_to(&source, seek, position)))
+                .expect("failed to spawn the audio seek thread");
+        }
+
+        if self.looped {
+            let mut restart = self.prepared.restart.lock();
+            if restart.is_none() {
+                *restart = Some(self.source.decoder().convert_samples());
+            }
+        }
+    }
+}
+
+fn decode_to<P>(source: &P, seek: u64, position: Duration) -> SeekedDecoder<P::Decoder>
+where
+    P: Decodable,
+    P::Decoder: Source,
+    <P::Decoder as Iterator>::Item: Sample,
+{
+    let mut decoder = source.decoder().convert_samples();
+    let channels = decoder.channels() as u64;
+    let frames = (position.as_secs_f64() * decoder.sample_rate() as f64) as u64;
+    let mut samples = 0;
+    while samples < frames * channels && decoder.next().is_some() {
+        samples += 1;
+    }
+    SeekedDecoder {
+        seek,
+        decoder,
+        samples,
+    }
+}
+
+/// Decodes a sound while it plays, so only its encoded data is kept in memory. Seeks and loops
+/// switch to decoders prepared by its [StreamDecoders], so the audio thread never creates one.
+pub(crate) struct StreamingSource<D>
+where
+    D: Source,
+    D::Item: Sample,
+{
+    decoder: SamplesConverter<D, f32>,
+    looped: bool,
+    control: Arc<StreamControl>,
+    prepared: Arc<PreparedDecoders<D>>,
+}
+
+impl<D> Iterator for StreamingSource<D>
+where
+    D: Source,
+    D::Item: Sample,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.prepared.seek_ready.swap(false, Ordering::Acquire) {
+            if let Some(seeked) = self.prepared.seeked.lock().take() {
+                self.decoder = seeked.decoder;
+                self.control
+                    .samples
+                    .store(seeked.samples, Ordering::Relaxed);
+            }
+        }
+        let sample = match self.decoder.next() {
+            Some(sample) => Some(sample),
+            None if self.looped => match self.prepared.restart.lock().take() {
+                Some(decoder) => {
+                    self.decoder = decoder;
+                    self.control.samples.store(0, Ordering::Relaxed);
+                    self.decoder.next()
+                }
+                // the next loop's decoder isn't ready yet
+                None => return Some(0.0),
+            },
+            None => None,
+        };
+        if sample.is_some() {
+            self.control.samples.fetch_add(1, Ordering::Relaxed);
+        }
+        sample
+    }
+}
+
+impl<D> Source for StreamingSource<D>
+where
+    D: Source,
+    D::Item: Sample,
+{
+    fn current_frame_len(&self) -> Option<usize> {
+        self.decoder.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.decoder.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.decoder.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        if self.looped {
+            None
+        } else {
+            self.decoder.total_duration()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rodio::buffer::SamplesBuffer;
+
+    #[derive(Clone)]
+    struct Samples(Vec<f32>);
+
+    impl Decodable for Samples {
+        type Decoder = SamplesBuffer<f32>;
+
+        fn decoder(&self) -> Self::Decoder {
+            // a single channel at 4 samples per second
+            SamplesBuffer::new(1, 4, self.0.clone())
+        }
+    }
+
+    #[test]
+    fn loops_and_seeks() {
+        let control = Arc::new(StreamControl::default());
+        let samples = Samples(vec![0.0, 0.25, 0.5, 0.75]);
+        let (mut decoders, mut source) = StreamDecoders::new(samples, true, control.clone());
+        let played = source.by_ref().take(6).collect::<Vec<_>>();
+        assert_eq!(played, vec![0.0, 0.25, 0.5, 0.75, 0.0, 0.25]);
+        assert_eq!(control.position(), Duration::from_millis(500));
+
+        // the next loop's decoder was used, so it plays silence until a new one is prepared
+        let played = source.by_ref().take(3).collect::<Vec<_>>();
+        assert_eq!(played, vec![0.5, 0.75, 0.0]);
+        decoders.update();
+        assert_eq!(source.next(), Some(0.0));
+
+        // the source keeps playing until the seek is decoded
+        control.seek(Duration::from_millis(500));
+        control.seek(Duration::from_millis(750));
+        decoders.update();
+        while !decoders.prepared.seek_ready.load(Ordering::Acquire) {
+            std::thread::yield_now();
+        }
+        assert_eq!(source.next(), Some(0.75));
+        assert_eq!(control.position(), Duration::from_secs(1));
+    }
+}
@@ -2,6 +2,7 @@ mod bytes;
 mod float_ord;
 mod label;
 mod name;
+mod rng;
 mod task_pool_options;
 mod time;
 
@@ -9,11 +10,15 @@ pub use bytes::*;
 pub use float_ord::*;
 pub use label::*;
 pub use name::*;
+pub use rng::*;
 pub use task_pool_options::DefaultTaskPoolOptions;
 pub use time::*;
 
 pub mod prelude {
-    pub use crate::{DefaultTaskPoolOptions, EntityLabels, Labels, Name, Time, Timer};
+    pub use crate::{
+        DefaultTaskPoolOptions, EntityLabels, EntityNames, Labels, Name, Rng, SystemRng, Time,
+        Timer,
+    };
 }
 
 use bevy_app::prelude::*;
@@ -47,7 +52,9 @@ impl Plugin for CorePlugin {
 
         app.init_resource::<Time>()
             .init_resource::<EntityLabels>()
+            .init_resource::<EntityNames>()
             .init_resource::<FixedTimesteps>()
+            .init_resource::<Rng>()
             .register_type::<HashSet<String>>()
             .register_type::<Option<String>>()
             .register_type::<Entity>()
@@ -62,7 +69,9 @@ impl Plugin for CorePlugin {
                 time_system.exclusive_system().label(CoreSystem::Time),
             )
             .add_startup_system_to_stage(StartupStage::PostStartup, entity_labels_system.system())
-            .add_system_to_stage(CoreStage::PostUpdate, entity_labels_system.system());
+            .add_system_to_stage(CoreStage::PostUpdate, entity_labels_system.system())
+            .add_startup_system_to_stage(StartupStage::PostStartup, entity_names_system.system())
+            .add_system_to_stage(CoreStage::PostUpdate, entity_names_system.system());
 
         register_rust_types(app);
         register_math_types(app);
@@ -1,10 +1,15 @@
-use bevy_ecs::reflect::ReflectComponent;
+use bevy_ecs::{
+    entity::Entity,
+    query::Changed,
+    reflect::ReflectComponent,
+    system::{Query, RemovedComponents, ResMut},
+};
 use bevy_reflect::Reflect;
-use bevy_utils::AHasher;
+use bevy_utils::{AHasher, HashMap};
 use std::{
     borrow::Cow,
     hash::{Hash, Hasher},
-    ops::Deref,
+    ops::{Deref, DerefMut},
 };
 
 /// Component used to identify an entity. Stores a hash for faster comparisons
@@ -97,3 +102,135 @@ impl Deref for Name {
         &self.name
     }
 }
+
+/// Maintains a mapping from [Name]s to the [Entities](bevy_ecs::prelude::Entity) that have them,
+/// so entities can be looked up by name.
+#[derive(Debug, Default)]
+pub struct EntityNames {
+    name_entities: HashMap<Cow<'static, str>, Vec<Entity>>,
+    entity_names: HashMap<Entity, Cow<'static, str>>,
+}
+
+impl EntityNames {
+    /// Returns all entities with the given name, in the order they were named
+    pub fn get(&self, name: &str) -> &[Entity] {
+        self.name_entities
+            .get(name)
+            .map(|entities| entities.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Returns the first entity that was given the name, if any
+    pub fn get_single(&self, name: &str) -> Option<Entity> {
+        self.get(name).first().copied()
+    }
+
+    /// Returns the name of the given entity, if it has one
+    pub fn name(&self, entity: Entity) -> Option<&str> {
+        self.entity_names.get(&entity).map(|name| name.deref())
+    }
+
+    fn remove(&mut self, entity: Entity) {
+        if let Some(name) = self.entity_names.remove(&entity) {
+            if let Some(entities) = self.name_entities.get_mut(&name) {
+                entities.retain(|e| *e != entity);
+                if entities.is_empty() {
+                    self.name_entities.remove(&name);
+                }
+            }
+        }
+    }
+}
+
+pub(crate) fn entity_names_system(
+    mut entity_names: ResMut<EntityNames>,
+    removed_names: RemovedComponents<Name>,
+    query: Query<(Entity, &Name), Changed<Name>>,
+) {
+    let entity_names = entity_names.deref_mut();
+
+    for entity in removed_names.iter() {
+        entity_names.remove(entity);
+    }
+
+    for (entity, name) in query.iter() {
+        if entity_names.name(entity) == Some(name.as_str()) {
+            continue;
+        }
+
+        entity_names.remove(entity);
+        entity_names
+            .name_entities
+            .entry(name.name.clone())
+            .or_insert_with(Vec::new)
+            .push(entity);
+        entity_names.entity_names.insert(entity, name.name.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::{
+        schedule::{Schedule, Stage, SystemStage},
+        system::IntoSystem,
+        world::World,
+    };
+
+    use super::*;
+
+    fn setup() -> (World, Schedule) {
+        let mut world = World::new();
+        world.insert_resource(EntityNames::default());
+        let mut schedule = Schedule::default();
+        schedule.add_stage("test", SystemStage::single_threaded());
+        schedule.add_system_to_stage("test", entity_names_system.system());
+        (world, schedule)
+    }
+
+    #[test]
+    fn looks_up_entities_by_name() {
+        let (mut world, mut schedule) = setup();
+        let e1 = world.spawn().insert(Name::new("player")).id();
+        let e2 = world.spawn().insert(Name::new("enemy")).id();
+        let e3 = world.spawn().insert(Name::new("enemy")).id();
+        schedule.run(&mut world);
+
+        let entity_names = world.get_resource::<EntityNames>().unwrap();
+        assert_eq!(entity_names.get_single("player"), Some(e1));
+        assert_eq!(entity_names.get("enemy"), &[e2, e3]);
+        assert_eq!(entity_names.get("camera"), &[]);
+        assert_eq!(entity_names.name(e2), Some("enemy"));
+    }
+
+    #[test]
+    fn renames_entity() {
+        let (mut world, mut schedule) = setup();
+        let e1 = world.spawn().insert(Name::new("player")).id();
+        schedule.run(&mut world);
+
+        world.get_mut::<Name>(e1).unwrap().set("hero");
+        schedule.run(&mut world);
+
+        let entity_names = world.get_resource::<EntityNames>().unwrap();
+        assert_eq!(entity_names.get_single("player"), None);
+        assert_eq!(entity_names.get_single("hero"), Some(e1));
+        assert_eq!(entity_names.name(e1), Some("hero"));
+    }
+
+    #[test]
+    fn removes_despawned_entity() {
+        let (mut world, mut schedule) = setup();
+        let e1 = world.spawn().insert(Name::new("player")).id();
+        let e2 = world.spawn().insert(Name::new("player")).id();
+        schedule.run(&mut world);
+
+        assert!(world.despawn(e1));
+        world.entity_mut(e2).remove::<Name>().unwrap();
+        schedule.run(&mut world);
+
+        let entity_names = world.get_resource::<EntityNames>().unwrap();
+        assert_eq!(entity_names.get("player"), &[]);
+        assert_eq!(entity_names.name(e1), None);
+        assert_eq!(entity_names.name(e2), None);
+    }
+}
@@ -0,0 +1,269 @@
+use bevy_ecs::world::{FromWorld, World};
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hash, Hasher},
+    ops::{Deref, DerefMut, Range},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+fn split_mix(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// An FNV-1a hasher, which unlike the standard hashers gives the same hashes on every platform
+/// and Rust version for the same bytes
+struct StableHasher(u64);
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        StableHasher(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+/// A seedable pseudo random number generator (xoshiro256++), giving the same numbers on every
+/// platform for the same seed.
+///
+/// The [CorePlugin](crate::CorePlugin) adds one as a resource, seeded from the system entropy
+/// unless an [Rng] was inserted before it. Insert one with [Rng::seed_from_u64] to make a run
+/// reproducible, and give each system its own generator with a `Local<`[SystemRng]`>` parameter
+/// so that the numbers drawn by a system don't depend on the order other systems ran in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rng {
+    seed: u64,
+    state: [u64; 4],
+}
+
+impl Default for Rng {
+    fn default() -> Self {
+        Rng::from_entropy()
+    }
+}
+
+impl Rng {
+    pub fn seed_from_u64(seed: u64) -> Self {
+        let mut split_mix_state = seed;
+        Rng {
+            seed,
+            state: [
+                split_mix(&mut split_mix_state),
+                split_mix(&mut split_mix_state),
+                split_mix(&mut split_mix_state),
+                split_mix(&mut split_mix_state),
+            ],
+        }
+    }
+
+    /// Creates a generator with a different seed at each call
+    pub fn from_entropy() -> Self {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+        Rng::seed_from_u64(hasher.finish())
+    }
+
+    /// The seed the generator was created with, to save for replaying a run
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn u64(&mut self) -> u64 {
+        let state = &mut self.state;
+        let result = state[0]
+            .wrapping_add(state[3])
+            .rotate_left(23)
+            .wrapping_add(state[0]);
+        let t = state[1] << 17;
+        state[2] ^= state[0];
+        state[3] ^= state[1];
+        state[1] ^= state[2];
+        state[0] ^= state[3];
+        state[2] ^= t;
+        state[3] = state[3].rotate_left(45);
+        result
+    }
+
+    pub fn u32(&mut self) -> u32 {
+        (self.u64() >> 32) as u32
+    }
+
+    /// A number in `[0, 1)`
+    pub fn f32(&mut self) -> f32 {
+        (self.u64() >> 40) as f32 * (1.0 / (1u32 << 24) as f32)
+    }
+
+    /// A number in `[0, 1)`
+    pub fn f64(&mut self) -> f64 {
+        (self.u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+
+    /// Returns `true` with the given probability
+    pub fn bool(&mut self, probability: f64) -> bool {
+        self.f64() < probability
+    }
+
+    /// An index in `0..len`
+    ///
+    /// # Panics
+    /// Panics if `len` is 0
+    pub fn index(&mut self, len: usize) -> usize {
+        assert!(len > 0, "cannot pick an index in an empty range");
+        ((self.u64() as u128 * len as u128) >> 64) as usize
+    }
+
+    /// # Panics
+    /// Panics if the range is empty
+    pub fn range_u32(&mut self, range: Range<u32>) -> u32 {
+        assert!(
+            range.start < range.end,
+            "cannot pick a number in an empty range"
+        );
+        range.start + self.index((range.end - range.start) as usize) as u32
+    }
+
+    /// # Panics
+    /// Panics if the range is empty
+    pub fn range_i32(&mut self, range: Range<i32>) -> i32 {
+        assert!(
+            range.start < range.end,
+            "cannot pick a number in an empty range"
+        );
+        let span = range.end.wrapping_sub(range.start) as u32;
+        range.start.wrapping_add(self.index(span as usize) as i32)
+    }
+
+    pub fn range_f32(&mut self, range: Range<f32>) -> f32 {
+        range.start + self.f32() * (range.end - range.start)
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        if items.is_empty() {
+            None
+        } else {
+            Some(&items[self.index(items.len())])
+        }
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.index(i + 1));
+        }
+    }
+
+    /// Creates a new generator seeded from this one, advancing it
+    pub fn fork(&mut self) -> Rng {
+        Rng::seed_from_u64(self.u64())
+    }
+
+    /// Creates a new generator seeded from the state of this one and a key, without advancing
+    /// it. Forks with different keys give different numbers, whatever the order they are made in.
+    /// Keys should be strings or fixed size integers, as the hash of `usize` depends on the
+    /// platform.
+    pub fn fork_with(&self, key: impl Hash) -> Rng {
+        let mut hasher = StableHasher::default();
+        for word in self.state.iter() {
+            hasher.write_u64(*word);
+        }
+        key.hash(&mut hasher);
+        Rng::seed_from_u64(hasher.finish())
+    }
+}
+
+/// A generator of a single system, forked from the [Rng] resource when the system is
+/// initialized. Use it as a `Local<SystemRng>` system parameter.
+///
+/// Systems are initialized in the order they were added to the app, so the numbers of each
+/// system are the same from one run to the next for the same [Rng] seed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SystemRng(pub Rng);
+
+impl FromWorld for SystemRng {
+    fn from_world(world: &mut World) -> Self {
+        SystemRng(world.get_resource_or_insert_with(Rng::default).fork())
+    }
+}
+
+impl Deref for SystemRng {
+    type Target = Rng;
+
+    fn deref(&self) -> &Rng {
+        &self.0
+    }
+}
+
+impl DerefMut for SystemRng {
+    fn deref_mut(&mut self) -> &mut Rng {
+        &mut self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeats_sequences_for_a_seed() {
+        let mut a = Rng::seed_from_u64(42);
+        let mut b = Rng::seed_from_u64(42);
+        let mut c = Rng::seed_from_u64(43);
+        let a_numbers = (0..8).map(|_| a.u64()).collect::<Vec<_>>();
+        assert_eq!(a_numbers, (0..8).map(|_| b.u64()).collect::<Vec<_>>());
+        assert_ne!(a_numbers, (0..8).map(|_| c.u64()).collect::<Vec<_>>());
+        assert_eq!(a.seed(), 42);
+        assert_ne!(Rng::from_entropy(), Rng::from_entropy());
+    }
+
+    #[test]
+    fn stays_in_ranges() {
+        let mut rng = Rng::seed_from_u64(1);
+        for _ in 0..1000 {
+            let f = rng.f32();
+            assert!((0.0..1.0).contains(&f));
+            assert!((3..7).contains(&rng.range_u32(3..7)));
+            assert!((-5..-2).contains(&rng.range_i32(-5..-2)));
+            let f = rng.range_f32(-1.0..1.0);
+            assert!((-1.0..1.0).contains(&f));
+        }
+        assert_eq!(rng.range_u32(4..5), 4);
+        assert!(rng.choose::<u32>(&[]).is_none());
+
+        let mut items = (0..20).collect::<Vec<u32>>();
+        rng.shuffle(&mut items);
+        assert_ne!(items, (0..20).collect::<Vec<_>>());
+        items.sort_unstable();
+        assert_eq!(items, (0..20).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn forks_independently_of_order() {
+        let rng = Rng::seed_from_u64(7);
+        let mut physics = rng.fork_with("physics");
+        let mut ai = rng.fork_with("ai");
+        assert_eq!(physics, rng.fork_with("physics"));
+        assert_ne!(physics.u64(), ai.u64());
+
+        let mut world = World::new();
+        world.insert_resource(Rng::seed_from_u64(7));
+        let first = SystemRng::from_world(&mut world);
+        let second = SystemRng::from_world(&mut world);
+        assert_ne!(first, second);
+        let mut expected = Rng::seed_from_u64(7);
+        assert_eq!(first.0, expected.fork());
+    }
+}
@@ -13,6 +13,7 @@ use std::borrow::Cow;
 pub struct FixedTimestepState {
     pub step: f64,
     pub accumulator: f64,
+    pub steps: u64,
 }
 
 impl FixedTimestepState {
@@ -31,6 +32,12 @@ impl FixedTimestepState {
         self.accumulator
     }
 
+    /// The number of steps run since the app started, counting the one running. Unlike
+    /// [Time::seconds_since_startup], it is the same at each step from one run to the next.
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
     /// The percentage of "step" stored inside the accumulator. Calculated as accumulator / step
     pub fn overstep_percentage(&self) -> f64 {
         self.accumulator / self.step
@@ -48,6 +55,22 @@ impl FixedTimesteps {
     }
 }
 
+/// A run criteria running a stage as many times as steps of a fixed duration fit in the time
+/// elapsed since the last update.
+///
+/// Unlike the frame rate, the steps are the same on every machine, which makes a fixed timestep
+/// stage the place for the systems of a deterministic simulation, such as a game kept in lockstep
+/// over the network or replayed from recorded inputs. Such systems should:
+/// * advance by [FixedTimestepState::step] and count time in [FixedTimestepState::steps], rather
+///   than reading [Time],
+/// * draw random numbers from a seeded [Rng](crate::Rng), each system from its own
+///   [SystemRng](crate::SystemRng) or [Rng::fork_with](crate::Rng::fork_with) fork,
+/// * sort the entities they iterate over by a stable key when the order matters, as the order of
+///   query results depends on the order entities were spawned and moved between archetypes in,
+/// * avoid iterating over [HashMap]s and `HashSet`s, whose order changes from one run to the next.
+///
+/// Floating point results can still differ between platforms and compiler versions, so lockstep
+/// games between different machines should check that their states match.
 pub struct FixedTimestep {
     state: State,
     internal_system: Box<dyn System<In = (), Out = ShouldRun>>,
@@ -98,6 +121,7 @@ impl FixedTimestep {
             let res_state = fixed_timesteps.fixed_timesteps.get_mut(label).unwrap();
             res_state.step = state.step;
             res_state.accumulator = state.accumulator;
+            res_state.steps = state.steps;
         }
 
         should_run
@@ -109,6 +133,7 @@ pub struct State {
     label: Option<String>, // TODO: consider making this a TypedLabel
     step: f64,
     accumulator: f64,
+    steps: u64,
     looping: bool,
 }
 
@@ -117,6 +142,7 @@ impl Default for State {
         Self {
             step: 1.0 / 60.0,
             accumulator: 0.0,
+            steps: 0,
             label: None,
             looping: false,
         }
@@ -131,6 +157,7 @@ impl State {
 
         if self.accumulator >= self.step {
             self.accumulator -= self.step;
+            self.steps += 1;
             self.looping = true;
             ShouldRun::YesAndCheckAgain
         } else {
@@ -192,6 +219,7 @@ impl System for FixedTimestep {
                 FixedTimestepState {
                     accumulator: 0.0,
                     step: self.state.step,
+                    steps: 0,
                 },
             );
         }
@@ -2,14 +2,26 @@ use bevy_ecs::system::ResMut;
 use bevy_utils::{Duration, Instant};
 
 /// Tracks elapsed time since the last update and since the App has started
+///
+/// [`Time::delta`] is the time that game logic such as animation and physics should advance by.
+/// It is clamped to [`Time::max_delta`] so a long stall (a breakpoint, a window drag, loading)
+/// doesn't produce one huge step, scaled by [`Time::relative_speed`], and zero while paused. The
+/// unmodified wall clock delta is available through [`Time::raw_delta`].
 #[derive(Debug)]
 pub struct Time {
     delta: Duration,
     last_update: Option<Instant>,
     delta_seconds_f64: f64,
     delta_seconds: f32,
+    raw_delta: Duration,
     seconds_since_startup: f64,
     startup: Instant,
+    elapsed: Duration,
+    frame_count: u64,
+    max_delta: Duration,
+    relative_speed: f64,
+    paused: bool,
+    raw_delta_override: Option<Duration>,
 }
 
 impl Default for Time {
@@ -21,6 +33,13 @@ impl Default for Time {
             delta_seconds_f64: 0.0,
             seconds_since_startup: 0.0,
             delta_seconds: 0.0,
+            raw_delta: Duration::from_secs(0),
+            elapsed: Duration::from_secs(0),
+            frame_count: 0,
+            max_delta: Duration::from_millis(250),
+            relative_speed: 1.0,
+            paused: false,
+            raw_delta_override: None,
         }
     }
 }
@@ -33,9 +52,20 @@ impl Time {
 
     pub(crate) fn update_with_instant(&mut self, instant: Instant) {
         if let Some(last_update) = self.last_update {
-            self.delta = instant - last_update;
+            self.raw_delta = self
+                .raw_delta_override
+                .unwrap_or_else(|| instant - last_update);
+            self.delta = if self.paused {
+                Duration::from_secs(0)
+            } else {
+                self.raw_delta
+                    .min(self.max_delta)
+                    .mul_f64(self.relative_speed)
+            };
             self.delta_seconds_f64 = self.delta.as_secs_f64();
             self.delta_seconds = self.delta.as_secs_f32();
+            self.elapsed += self.delta;
+            self.frame_count += 1;
         }
 
         let duration_since_startup = instant - self.startup;
@@ -61,6 +91,83 @@ impl Time {
         self.delta_seconds_f64
     }
 
+    /// The delta between the current and last tick, ignoring [`Time::max_delta`],
+    /// [`Time::relative_speed`] and pausing. Use this for measuring real frame times.
+    #[inline]
+    pub fn raw_delta(&self) -> Duration {
+        self.raw_delta
+    }
+
+    /// The sum of all deltas since startup, i.e. the game time that has passed. Unlike
+    /// [`Time::seconds_since_startup`], this doesn't advance while paused.
+    #[inline]
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// The sum of all deltas since startup as [`f64`] seconds
+    #[inline]
+    pub fn elapsed_seconds_f64(&self) -> f64 {
+        self.elapsed.as_secs_f64()
+    }
+
+    /// The number of ticks since startup, not counting the first
+    #[inline]
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    /// The largest delta a single tick can advance by, before scaling
+    #[inline]
+    pub fn max_delta(&self) -> Duration {
+        self.max_delta
+    }
+
+    /// Sets the largest delta a single tick can advance by, before scaling. Defaults to 250ms.
+    pub fn set_max_delta(&mut self, max_delta: Duration) {
+        self.max_delta = max_delta;
+    }
+
+    /// The speed of game time relative to real time
+    #[inline]
+    pub fn relative_speed(&self) -> f64 {
+        self.relative_speed
+    }
+
+    /// Scales the speed of game time relative to real time, e.g. `0.5` for slow motion. Negative
+    /// speeds are treated as `0.0`.
+    pub fn set_relative_speed(&mut self, relative_speed: f64) {
+        self.relative_speed = relative_speed.max(0.0);
+    }
+
+    /// Stops game time: until [`Time::unpause`] is called, [`Time::delta`] is zero and
+    /// [`Time::elapsed`] doesn't advance
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn unpause(&mut self) {
+        self.paused = false;
+    }
+
+    #[inline]
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// The raw delta every tick advances by instead of the wall clock time, if any
+    #[inline]
+    pub fn raw_delta_override(&self) -> Option<Duration> {
+        self.raw_delta_override
+    }
+
+    /// Makes the next ticks advance by `raw_delta` instead of the wall clock time, or by the wall
+    /// clock time again with `None`. Replaying the raw deltas of a run makes [`Time::delta`] the
+    /// same as in that run. [`Time::seconds_since_startup`] still follows the wall clock.
+    pub fn set_raw_delta_override(&mut self, raw_delta: Option<Duration>) {
+        self.raw_delta_override = raw_delta;
+    }
+
     /// The time since startup in seconds
     #[inline]
     pub fn seconds_since_startup(&self) -> f64 {
@@ -143,5 +250,43 @@ mod tests {
             (second_update_instant - start_instant).as_secs_f64()
         );
         assert_eq!(time.delta_seconds(), time.delta().as_secs_f32());
+        assert_eq!(time.elapsed(), time.delta());
+        assert_eq!(time.frame_count(), 1);
+    }
+
+    #[test]
+    fn clamp_scale_and_pause() {
+        let start_instant = Instant::now();
+        let mut time = Time {
+            startup: start_instant,
+            ..Default::default()
+        };
+        time.set_max_delta(Duration::from_secs(1));
+        time.update_with_instant(start_instant);
+
+        // long stalls are clamped
+        time.update_with_instant(start_instant + Duration::from_secs(5));
+        assert_eq!(time.raw_delta(), Duration::from_secs(5));
+        assert_eq!(time.delta(), Duration::from_secs(1));
+
+        time.set_relative_speed(0.5);
+        time.update_with_instant(start_instant + Duration::from_secs(6));
+        assert_eq!(time.delta(), Duration::from_millis(500));
+        assert_eq!(time.elapsed(), Duration::from_millis(1500));
+
+        time.pause();
+        time.update_with_instant(start_instant + Duration::from_secs(7));
+        assert_eq!(time.delta(), Duration::from_secs(0));
+        assert_eq!(time.delta_seconds(), 0.0);
+        assert_eq!(time.elapsed(), Duration::from_millis(1500));
+        assert_eq!(time.seconds_since_startup(), 7.0);
+        assert_eq!(time.frame_count(), 3);
+
+        time.unpause();
+        time.set_raw_delta_override(Some(Duration::from_millis(100)));
+        time.update_with_instant(start_instant + Duration::from_secs(9));
+        assert_eq!(time.raw_delta(), Duration::from_millis(100));
+        assert_eq!(time.delta(), Duration::from_millis(50));
+        assert_eq!(time.seconds_since_startup(), 9.0);
     }
 }
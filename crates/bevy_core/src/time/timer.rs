@@ -339,6 +339,33 @@ impl Timer {
         1.0 - self.percent()
     }
 
+    /// Returns the time remaining until the timer finishes.
+    ///
+    /// Timers are ticked with [`Time::delta`](crate::Time::delta), so this is in game time and
+    /// doesn't pass while [`Time`](crate::Time) is paused.
+    ///
+    /// # Examples
+    /// ```
+    /// # use bevy_core::*;
+    /// use std::time::Duration;
+    /// let mut timer = Timer::from_seconds(2.0, false);
+    /// timer.tick(Duration::from_secs_f32(0.5));
+    /// assert_eq!(timer.remaining(), Duration::from_secs_f32(1.5));
+    /// ```
+    #[inline]
+    pub fn remaining(&self) -> Duration {
+        self.duration()
+            .checked_sub(self.elapsed())
+            .unwrap_or_default()
+    }
+
+    /// Returns the time remaining until the timer finishes in seconds.
+    /// See also [`Timer::remaining`](Timer::remaining).
+    #[inline]
+    pub fn remaining_secs(&self) -> f32 {
+        self.remaining().as_secs_f32()
+    }
+
     /// Returns the number of times a repeating timer
     /// finished during the last [`tick`](Timer<T>::tick) call.
     ///
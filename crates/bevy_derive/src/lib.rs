@@ -8,6 +8,7 @@ mod render_resource;
 mod render_resources;
 mod resource;
 mod shader_defs;
+mod uniform;
 
 use proc_macro::TokenStream;
 
@@ -31,6 +32,15 @@ pub fn derive_render_resources(input: TokenStream) -> TokenStream {
     render_resources::derive_render_resources(input)
 }
 
+/// Derives the RenderResources and Uniform traits, so the component can be bound to shaders with
+/// `app.add_uniform::<T>()`. Accepts the same attributes as `RenderResources`, and
+/// `#[uniform(per_entity_buffers)]` gives each entity its own buffers instead of sharing a dynamic
+/// uniform buffer.
+#[proc_macro_derive(Uniform, attributes(uniform, render_resources, as_crate))]
+pub fn derive_uniform(input: TokenStream) -> TokenStream {
+    uniform::derive_uniform(input)
+}
+
 /// Derives the RenderResource trait. The type must also implement `Bytes` or this will fail.
 #[proc_macro_derive(RenderResource, attributes(as_crate))]
 pub fn derive_render_resource(input: TokenStream) -> TokenStream {
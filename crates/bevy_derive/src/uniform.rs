@@ -0,0 +1,39 @@
+use crate::{
+    modules::{get_modules, get_path},
+    render_resources,
+};
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse::ParseStream, parse_macro_input, DeriveInput, Path};
+
+static UNIFORM_ATTRIBUTE_NAME: &str = "uniform";
+
+pub fn derive_uniform(input: TokenStream) -> TokenStream {
+    let render_resources =
+        proc_macro2::TokenStream::from(render_resources::derive_render_resources(input.clone()));
+    let ast = parse_macro_input!(input as DeriveInput);
+    let modules = get_modules(&ast.attrs);
+    let bevy_render_path: Path = get_path(&modules.bevy_render);
+
+    let dynamic_uniforms = !ast
+        .attrs
+        .iter()
+        .filter(|a| *a.path.get_ident().as_ref().unwrap() == UNIFORM_ATTRIBUTE_NAME)
+        .any(|a| {
+            syn::custom_keyword!(per_entity_buffers);
+            a.parse_args_with(|input: ParseStream| {
+                Ok(input.parse::<Option<per_entity_buffers>>()?.is_some())
+            })
+            .expect("Invalid 'uniform' attribute format.")
+        });
+
+    let struct_name = &ast.ident;
+
+    TokenStream::from(quote! {
+        #render_resources
+
+        impl #bevy_render_path::render_graph::Uniform for #struct_name {
+            const DYNAMIC_UNIFORMS: bool = #dynamic_uniforms;
+        }
+    })
+}
@@ -98,6 +98,14 @@ impl Diagnostic {
         }
     }
 
+    /// The measured values, from oldest to newest
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = f64> + '_ {
+        self.history
+            .iter()
+            .rev()
+            .map(|measurement| measurement.value)
+    }
+
     pub fn history_len(&self) -> usize {
         self.history.len()
     }
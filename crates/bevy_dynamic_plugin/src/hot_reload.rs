@@ -0,0 +1,208 @@
+use bevy_app::{AppBuilder, CoreStage, Events, Plugin};
+use bevy_ecs::{
+    schedule::{Stage, StageLabel, SystemStage},
+    world::World,
+};
+use bevy_utils::{tracing::warn, Duration, Instant};
+use libloading::{Library, Symbol};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+use thiserror::Error;
+
+/// The function a hot reloaded library exports as `_bevy_hot_reload` to add its systems:
+///
+/// ```ignore
+/// #[no_mangle]
+/// pub fn _bevy_hot_reload(stage: &mut SystemStage) {
+///     stage.add_system(movement.system());
+/// }
+/// ```
+pub type HotReloadSystems = unsafe fn(&mut SystemStage);
+
+#[derive(Error, Debug)]
+pub enum HotReloadError {
+    #[error("failed to copy the library: {0}")]
+    Io(#[from] io::Error),
+    #[error("failed to load the library: {0}")]
+    Library(#[from] libloading::Error),
+}
+
+/// Sent each time the systems of a library are loaded by a [HotReloadPlugin]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LibraryReloaded {
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, StageLabel)]
+pub struct HotReloadStageLabel(pub PathBuf);
+
+struct LoadedLibrary {
+    // the systems are dropped before the library their code is in
+    stage: SystemStage,
+    _library: Library,
+    copy_path: PathBuf,
+}
+
+impl LoadedLibrary {
+    fn unload(self) {
+        let copy_path = self.copy_path.clone();
+        drop(self);
+        let _ = fs::remove_file(copy_path);
+    }
+}
+
+/// A stage running the systems of a library, and reloading them when the library file changes.
+///
+/// The library is copied before being loaded, so that it can be rebuilt while loaded. The systems
+/// are added again by each reload, which resets their [Local](bevy_ecs::system::Local)s, while
+/// the [World] keeps its entities and resources.
+pub struct HotReloadStage {
+    path: PathBuf,
+    loaded: Option<LoadedLibrary>,
+    last_modified: Option<SystemTime>,
+    last_check: Option<Instant>,
+    loads: u32,
+    /// How often the library file is checked for changes
+    pub check_interval: Duration,
+}
+
+impl HotReloadStage {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        HotReloadStage {
+            path: path.into(),
+            loaded: None,
+            last_modified: None,
+            last_check: None,
+            loads: 0,
+            check_interval: Duration::from_millis(500),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        self.loaded.is_some()
+    }
+
+    fn load(&mut self) -> Result<LoadedLibrary, HotReloadError> {
+        let file_name = self.path.file_name().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "the path has no file name")
+        })?;
+        let directory = std::env::temp_dir().join("bevy_hot_reload");
+        fs::create_dir_all(&directory)?;
+        self.loads += 1;
+        let copy_path = directory.join(format!(
+            "{}-{}-{}",
+            std::process::id(),
+            self.loads,
+            file_name.to_string_lossy()
+        ));
+        fs::copy(&self.path, &copy_path)?;
+
+        let mut stage = SystemStage::parallel();
+        // SAFE: the library is expected to export `_bevy_hot_reload` as [HotReloadSystems], and
+        // to be built with the same compiler and Bevy version as the app
+        let library = unsafe {
+            let library = Library::new(&copy_path)?;
+            {
+                let add_systems: Symbol<HotReloadSystems> = library.get(b"_bevy_hot_reload")?;
+                add_systems(&mut stage);
+            }
+            library
+        };
+        Ok(LoadedLibrary {
+            stage,
+            _library: library,
+            copy_path,
+        })
+    }
+
+    fn reload_if_changed(&mut self, world: &mut World) {
+        let now = Instant::now();
+        match self.last_check {
+            Some(last_check) if now - last_check < self.check_interval => return,
+            _ => self.last_check = Some(now),
+        }
+        let modified = match fs::metadata(&self.path).and_then(|metadata| metadata.modified()) {
+            Ok(modified) => modified,
+            // the library is being rebuilt, or isn't built yet
+            Err(_) => return,
+        };
+        if self.last_modified == Some(modified) {
+            return;
+        }
+        self.last_modified = Some(modified);
+
+        match self.load() {
+            Ok(loaded) => {
+                if let Some(previous) = self.loaded.replace(loaded) {
+                    previous.unload();
+                }
+                if let Some(mut events) = world.get_resource_mut::<Events<LibraryReloaded>>() {
+                    events.send(LibraryReloaded {
+                        path: self.path.clone(),
+                    });
+                }
+            }
+            // the previous systems keep running until the library loads
+            Err(err) => warn!("failed to reload {}: {}", self.path.display(), err),
+        }
+    }
+}
+
+impl Stage for HotReloadStage {
+    fn run(&mut self, world: &mut World) {
+        self.reload_if_changed(world);
+        if let Some(loaded) = &mut self.loaded {
+            loaded.stage.run(world);
+        }
+    }
+}
+
+impl Drop for HotReloadStage {
+    fn drop(&mut self) {
+        if let Some(loaded) = self.loaded.take() {
+            loaded.unload();
+        }
+    }
+}
+
+/// Runs the systems of a library after [CoreStage::Update], and reloads them each time the
+/// library is rebuilt, so that game logic can be changed without restarting the app.
+///
+/// The library is a `cdylib` exporting [HotReloadSystems]. It must be built with the same compiler
+/// as the app, and both must link Bevy dynamically (with its `dynamic` feature). Types stored in
+/// the [World], such as components and resources, must be defined in a crate the app links
+/// rather than in the library: their code is unloaded with the library.
+#[derive(Debug, Clone)]
+pub struct HotReloadPlugin {
+    pub path: PathBuf,
+}
+
+impl HotReloadPlugin {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        HotReloadPlugin { path: path.into() }
+    }
+}
+
+impl Plugin for HotReloadPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        if app
+            .world()
+            .get_resource::<Events<LibraryReloaded>>()
+            .is_none()
+        {
+            app.add_event::<LibraryReloaded>();
+        }
+        app.add_stage_after(
+            CoreStage::Update,
+            HotReloadStageLabel(self.path.clone()),
+            HotReloadStage::new(self.path.clone()),
+        );
+    }
+}
@@ -13,7 +13,7 @@ pub mod world;
 
 pub mod prelude {
     #[cfg(feature = "bevy_reflect")]
-    pub use crate::reflect::ReflectComponent;
+    pub use crate::reflect::{ReflectComponent, ReflectResource};
     pub use crate::{
         bundle::Bundle,
         entity::Entity,
@@ -1,9 +1,10 @@
 use crate::{
     archetype::{Archetype, ArchetypeComponentId},
-    component::ComponentId,
+    component::{Component, ComponentId},
+    event::EventReader,
     query::Access,
     schedule::{BoxedRunCriteriaLabel, GraphNode, RunCriteriaLabel},
-    system::{BoxedSystem, System, SystemId},
+    system::{BoxedSystem, IntoSystem, Local, Res, System, SystemId},
     world::World,
 };
 use std::borrow::Cow;
@@ -20,6 +21,16 @@ pub enum ShouldRun {
     NoAndCheckAgain,
 }
 
+impl From<bool> for ShouldRun {
+    fn from(should_run: bool) -> Self {
+        if should_run {
+            ShouldRun::Yes
+        } else {
+            ShouldRun::No
+        }
+    }
+}
+
 pub(crate) struct BoxedRunCriteria {
     criteria_system: Option<BoxedSystem<(), ShouldRun>>,
     initialized: bool,
@@ -395,3 +406,38 @@ impl System for RunOnce {
 
     fn check_change_tick(&mut self, _change_tick: u32) {}
 }
+
+/// Run criteria that runs while a resource of type `T` exists.
+pub fn resource_exists<T: Component>() -> impl System<In = (), Out = ShouldRun> {
+    fn resource_exists<T: Component>(resource: Option<Res<T>>) -> ShouldRun {
+        resource.is_some().into()
+    }
+    resource_exists::<T>.system()
+}
+
+/// Run criteria that runs while a resource of type `T` exists and is equal to `value`.
+pub fn resource_equals<T: Component + PartialEq>(
+    value: T,
+) -> impl System<In = (), Out = ShouldRun> {
+    fn resource_equals<T: Component + PartialEq>(
+        resource: Option<Res<T>>,
+        value: Local<Option<T>>,
+    ) -> ShouldRun {
+        resource
+            .is_some_and(|resource| Some(&*resource) == value.as_ref())
+            .into()
+    }
+    resource_equals::<T>
+        .system()
+        .config(|(_, local)| *local = Some(Some(value)))
+}
+
+/// Run criteria that runs if at least one event of type `T` was sent since the last time it was
+/// checked.
+pub fn on_event<T: Component>() -> impl System<In = (), Out = ShouldRun> {
+    fn on_event<T: Component>(mut reader: EventReader<T>) -> ShouldRun {
+        // consume all events so each one only triggers a single run
+        (reader.iter().count() > 0).into()
+    }
+    on_event::<T>.system()
+}
@@ -894,9 +894,9 @@ mod tests {
         query::ChangeTrackers,
         query::Changed,
         schedule::{
-            BoxedSystemLabel, ExclusiveSystemDescriptorCoercion, ParallelSystemDescriptorCoercion,
-            RunCriteria, RunCriteriaDescriptorCoercion, RunCriteriaPiping, ShouldRun,
-            SingleThreadedExecutor, Stage, SystemSet, SystemStage,
+            resource_equals, resource_exists, BoxedSystemLabel, ExclusiveSystemDescriptorCoercion,
+            ParallelSystemDescriptorCoercion, RunCriteria, RunCriteriaDescriptorCoercion,
+            RunCriteriaPiping, ShouldRun, SingleThreadedExecutor, Stage, SystemSet, SystemStage,
         },
         system::{In, IntoExclusiveSystem, IntoSystem, Local, Query, ResMut},
         world::World,
@@ -1188,6 +1188,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn resource_run_criteria() {
+        let mut world = World::new();
+        world.insert_resource(Vec::<usize>::new());
+        let mut stage = SystemStage::parallel()
+            .with_system_set(
+                SystemSet::new()
+                    .with_run_criteria(resource_exists::<u32>())
+                    .with_system(make_parallel!(0).system()),
+            )
+            .with_system_set(
+                SystemSet::new()
+                    .with_run_criteria(resource_equals(1u32))
+                    .with_system(make_parallel!(1).system()),
+            );
+        stage.run(&mut world);
+        world.insert_resource(0u32);
+        stage.run(&mut world);
+        world.insert_resource(1u32);
+        stage.run(&mut world);
+        let mut ran = world.get_resource::<Vec<usize>>().unwrap().clone();
+        ran.sort_unstable();
+        assert_eq!(ran, vec![0, 0, 1]);
+    }
+
     #[test]
     #[should_panic]
     fn exclusive_cycle_1() {
@@ -380,6 +380,21 @@ where
     pub fn inactives(&self) -> &[T] {
         self.stack.split_last().map(|(_, rest)| rest).unwrap()
     }
+
+    /// Returns the `(leaving, entering)` states of the transition in progress, if any. This lets
+    /// `on_enter`, `on_exit`, `on_pause` and `on_resume` systems behave differently depending on
+    /// where the state is coming from or going to, e.g. entering `InGame` from `Paused` instead
+    /// of from `Menu`.
+    pub fn transition(&self) -> Option<(&T, &T)> {
+        match &self.transition {
+            Some(StateTransition::ExitingToResume(leaving, entering))
+            | Some(StateTransition::ExitingFull(leaving, entering))
+            | Some(StateTransition::Entering(leaving, entering))
+            | Some(StateTransition::Resuming(leaving, entering))
+            | Some(StateTransition::Pausing(leaving, entering)) => Some((leaving, entering)),
+            Some(StateTransition::PreStartup) | Some(StateTransition::Startup) | None => None,
+        }
+    }
 }
 
 #[derive(Debug, Error)]
@@ -681,4 +696,54 @@ mod test {
         stage.run(&mut world);
         assert!(*world.get_resource::<bool>().unwrap(), "after test");
     }
+
+    #[test]
+    fn transition_test() {
+        let mut world = World::default();
+
+        world.insert_resource(Vec::<(MyState, MyState)>::new());
+        world.insert_resource(State::new(MyState::S1));
+
+        let mut stage = SystemStage::parallel();
+        stage.add_system_set(State::<MyState>::get_driver());
+        stage
+            .add_system_set(State::on_update_set(MyState::S1).with_system(
+                (|mut s: ResMut<State<MyState>>| s.set(MyState::S2).unwrap()).system(),
+            ))
+            .add_system_set(State::on_update_set(MyState::S2).with_system(
+                (|mut s: ResMut<State<MyState>>| s.push(MyState::S3).unwrap()).system(),
+            ))
+            .add_system_set(
+                State::on_exit_set(MyState::S1).with_system(
+                    (|mut r: ResMut<Vec<(MyState, MyState)>>, s: Res<State<MyState>>| {
+                        let (leaving, entering) = s.transition().unwrap();
+                        r.push((*leaving, *entering));
+                    })
+                    .system(),
+                ),
+            )
+            .add_system_set(
+                State::on_pause_set(MyState::S2).with_system(
+                    (|mut r: ResMut<Vec<(MyState, MyState)>>, s: Res<State<MyState>>| {
+                        let (leaving, entering) = s.transition().unwrap();
+                        r.push((*leaving, *entering));
+                    })
+                    .system(),
+                ),
+            );
+
+        stage.run(&mut world);
+        assert_eq!(
+            world.get_resource::<State<MyState>>().unwrap().transition(),
+            None
+        );
+        stage.run(&mut world);
+        stage.run(&mut world);
+
+        let transitions = world.get_resource::<Vec<(MyState, MyState)>>().unwrap();
+        assert_eq!(
+            transitions,
+            &vec![(MyState::S1, MyState::S2), (MyState::S2, MyState::S3)]
+        );
+    }
 }
@@ -1,11 +1,13 @@
 mod converter;
 mod gilrs_system;
+mod rumble;
 
 use bevy_app::{AppBuilder, CoreStage, Plugin, StartupStage};
 use bevy_ecs::system::IntoExclusiveSystem;
 use bevy_utils::tracing::error;
 use gilrs::GilrsBuilder;
 use gilrs_system::{gilrs_event_startup_system, gilrs_event_system};
+use rumble::{gilrs_rumble_system, RunningRumbleEffects};
 
 #[derive(Default)]
 pub struct GilrsPlugin;
@@ -19,6 +21,7 @@ impl Plugin for GilrsPlugin {
         {
             Ok(gilrs) => {
                 app.insert_non_send_resource(gilrs)
+                    .insert_non_send_resource(RunningRumbleEffects::default())
                     .add_startup_system_to_stage(
                         StartupStage::PreStartup,
                         gilrs_event_startup_system.exclusive_system(),
@@ -26,6 +29,10 @@ impl Plugin for GilrsPlugin {
                     .add_system_to_stage(
                         CoreStage::PreUpdate,
                         gilrs_event_system.exclusive_system(),
+                    )
+                    .add_system_to_stage(
+                        CoreStage::PostUpdate,
+                        gilrs_rumble_system.exclusive_system(),
                     );
             }
             Err(err) => error!("Failed to start Gilrs. {}", err),
@@ -0,0 +1,96 @@
+use crate::converter::convert_gamepad_id;
+use bevy_app::{Events, ManualEventReader};
+use bevy_ecs::world::World;
+use bevy_input::gamepad::{Gamepad, GamepadRumbleIntensity, GamepadRumbleRequest};
+use bevy_utils::{tracing::warn, Duration, HashMap, Instant};
+use gilrs::{
+    ff::{self, BaseEffect, BaseEffectType, EffectBuilder, Repeat, Replay, Ticks},
+    GamepadId, Gilrs,
+};
+
+/// The rumble effects playing on each gamepad. Dropping an effect stops it.
+#[derive(Default)]
+pub struct RunningRumbleEffects {
+    reader: ManualEventReader<GamepadRumbleRequest>,
+    effects: HashMap<Gamepad, Vec<(ff::Effect, Instant)>>,
+}
+
+fn play_effect(
+    gilrs: &mut Gilrs,
+    id: GamepadId,
+    intensity: GamepadRumbleIntensity,
+    duration: Duration,
+) -> Result<ff::Effect, ff::Error> {
+    let ticks = Ticks::from_ms(duration.as_millis().min(u32::MAX as u128) as u32);
+    let magnitude = |intensity: f32| (intensity.max(0.0).min(1.0) * u16::MAX as f32) as u16;
+    let scheduling = Replay {
+        play_for: ticks,
+        ..Default::default()
+    };
+    let effect = EffectBuilder::new()
+        .add_effect(BaseEffect {
+            kind: BaseEffectType::Strong {
+                magnitude: magnitude(intensity.strong_motor),
+            },
+            scheduling,
+            ..Default::default()
+        })
+        .add_effect(BaseEffect {
+            kind: BaseEffectType::Weak {
+                magnitude: magnitude(intensity.weak_motor),
+            },
+            scheduling,
+            ..Default::default()
+        })
+        .repeat(Repeat::For(ticks))
+        .gamepads(&[id])
+        .finish(gilrs)?;
+    effect.play()?;
+    Ok(effect)
+}
+
+/// Plays the [GamepadRumbleRequest]s on gamepads supporting force feedback
+pub fn gilrs_rumble_system(world: &mut World) {
+    let world = world.cell();
+    let mut gilrs = world.get_non_send_mut::<Gilrs>().unwrap();
+    let mut running = world.get_non_send_mut::<RunningRumbleEffects>().unwrap();
+    let requests = world
+        .get_resource::<Events<GamepadRumbleRequest>>()
+        .unwrap();
+    let now = Instant::now();
+
+    let running = &mut *running;
+    for effects in running.effects.values_mut() {
+        effects.retain(|(_, end)| *end > now);
+    }
+    for request in running.reader.iter(&requests) {
+        let gamepad = request.gamepad();
+        let (intensity, duration) = match request {
+            GamepadRumbleRequest::Add {
+                intensity,
+                duration,
+                ..
+            } => (*intensity, *duration),
+            GamepadRumbleRequest::Stop { .. } => {
+                running.effects.remove(&gamepad);
+                continue;
+            }
+        };
+        let id = gilrs
+            .gamepads()
+            .map(|(id, _)| id)
+            .find(|id| convert_gamepad_id(*id) == gamepad);
+        let id = match id {
+            Some(id) if gilrs.gamepad(id).is_ff_supported() => id,
+            _ => continue,
+        };
+        match play_effect(&mut gilrs, id, intensity, duration) {
+            Ok(effect) => running
+                .effects
+                .entry(gamepad)
+                .or_insert_with(Vec::new)
+                .push((effect, now + duration)),
+            Err(err) => warn!("Failed to rumble {:?}. {}", gamepad, err),
+        }
+    }
+}
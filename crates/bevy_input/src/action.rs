@@ -0,0 +1,354 @@
+use crate::{
+    gamepad::{
+        Gamepad, GamepadAxis, GamepadAxisType, GamepadButton, GamepadButtonType, GamepadEvent,
+        GamepadEventType,
+    },
+    keyboard::KeyCode,
+    mouse::MouseButton,
+    Axis, Input,
+};
+use bevy_app::EventReader;
+use bevy_ecs::system::{Local, Res, ResMut};
+use bevy_utils::{HashMap, HashSet};
+use std::collections::BTreeMap;
+
+/// A button of the keyboard, the mouse or any gamepad
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum InputButton {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Gamepad(GamepadButtonType),
+}
+
+impl From<KeyCode> for InputButton {
+    fn from(key: KeyCode) -> Self {
+        InputButton::Key(key)
+    }
+}
+
+impl From<MouseButton> for InputButton {
+    fn from(button: MouseButton) -> Self {
+        InputButton::Mouse(button)
+    }
+}
+
+impl From<GamepadButtonType> for InputButton {
+    fn from(button: GamepadButtonType) -> Self {
+        InputButton::Gamepad(button)
+    }
+}
+
+/// An input triggering an action of the [InputMap]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum Binding {
+    /// `1.0` while `button` and all the `modifiers` are pressed. Other buttons held at the same
+    /// time don't prevent it.
+    Button {
+        button: InputButton,
+        modifiers: Vec<InputButton>,
+    },
+    /// The value of a gamepad axis multiplied by `scale`, which is negative to invert the axis
+    GamepadAxis { axis: GamepadAxisType, scale: f32 },
+    /// `-1.0` while `negative` is pressed and `1.0` while `positive` is pressed, like an axis
+    ButtonAxis {
+        negative: InputButton,
+        positive: InputButton,
+    },
+}
+
+impl Binding {
+    pub fn button(button: impl Into<InputButton>) -> Self {
+        Binding::Button {
+            button: button.into(),
+            modifiers: Vec::new(),
+        }
+    }
+
+    /// `button` pressed while holding `modifier`
+    pub fn with_modifier(button: impl Into<InputButton>, modifier: impl Into<InputButton>) -> Self {
+        Binding::Button {
+            button: button.into(),
+            modifiers: vec![modifier.into()],
+        }
+    }
+
+    pub fn gamepad_axis(axis: GamepadAxisType) -> Self {
+        Binding::GamepadAxis { axis, scale: 1.0 }
+    }
+
+    pub fn button_axis(negative: impl Into<InputButton>, positive: impl Into<InputButton>) -> Self {
+        Binding::ButtonAxis {
+            negative: negative.into(),
+            positive: positive.into(),
+        }
+    }
+}
+
+/// Named actions and the bindings triggering them. An action can have any number of bindings,
+/// the one with the largest value wins.
+///
+/// With the `serialize` feature the map can be saved and loaded, so players can rebind controls.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct InputMap {
+    actions: BTreeMap<String, Vec<Binding>>,
+}
+
+impl InputMap {
+    /// Adds a binding to `action`
+    pub fn bind(&mut self, action: impl Into<String>, binding: Binding) -> &mut Self {
+        let bindings = self.actions.entry(action.into()).or_insert_with(Vec::new);
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+        self
+    }
+
+    /// Removes a binding from `action`, returning true if it was bound
+    pub fn unbind(&mut self, action: &str, binding: &Binding) -> bool {
+        match self.actions.get_mut(action) {
+            Some(bindings) => {
+                let len = bindings.len();
+                bindings.retain(|b| b != binding);
+                bindings.len() != len
+            }
+            None => false,
+        }
+    }
+
+    /// Replaces the bindings of `action`
+    pub fn set_bindings(&mut self, action: impl Into<String>, bindings: Vec<Binding>) {
+        self.actions.insert(action.into(), bindings);
+    }
+
+    /// Removes `action` and its bindings
+    pub fn remove(&mut self, action: &str) -> Option<Vec<Binding>> {
+        self.actions.remove(action)
+    }
+
+    pub fn bindings(&self, action: &str) -> &[Binding] {
+        self.actions.get(action).map_or(&[], |bindings| bindings)
+    }
+
+    pub fn actions(&self) -> impl Iterator<Item = &str> {
+        self.actions.keys().map(|action| action.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct ActionData {
+    value: f32,
+    pressed: bool,
+    just_pressed: bool,
+    just_released: bool,
+}
+
+/// The state of the actions of the [InputMap], updated each frame after the raw input.
+///
+/// An action is pressed while the magnitude of its value reaches `press_threshold`.
+#[derive(Debug, Clone)]
+pub struct ActionState {
+    actions: HashMap<String, ActionData>,
+    pub press_threshold: f32,
+}
+
+impl Default for ActionState {
+    fn default() -> Self {
+        ActionState {
+            actions: Default::default(),
+            press_threshold: 0.5,
+        }
+    }
+}
+
+/// The raw input the bindings are read from
+pub struct RawInput<'a> {
+    pub keys: &'a Input<KeyCode>,
+    pub mouse_buttons: &'a Input<MouseButton>,
+    pub gamepad_buttons: &'a Input<GamepadButton>,
+    pub gamepad_axes: &'a Axis<GamepadAxis>,
+    pub gamepads: &'a HashSet<Gamepad>,
+}
+
+impl<'a> RawInput<'a> {
+    fn pressed(&self, button: InputButton) -> bool {
+        match button {
+            InputButton::Key(key) => self.keys.pressed(key),
+            InputButton::Mouse(button) => self.mouse_buttons.pressed(button),
+            InputButton::Gamepad(button) => self.gamepads.iter().any(|gamepad| {
+                self.gamepad_buttons
+                    .pressed(GamepadButton(*gamepad, button))
+            }),
+        }
+    }
+
+    fn value(&self, binding: &Binding) -> f32 {
+        let button_value = |button| if self.pressed(button) { 1.0 } else { 0.0 };
+        match binding {
+            Binding::Button { button, modifiers } => {
+                if modifiers.iter().all(|modifier| self.pressed(*modifier)) {
+                    button_value(*button)
+                } else {
+                    0.0
+                }
+            }
+            Binding::GamepadAxis { axis, scale } => self
+                .gamepads
+                .iter()
+                .filter_map(|gamepad| self.gamepad_axes.get(GamepadAxis(*gamepad, *axis)))
+                .map(|value| value * scale)
+                .fold(0.0, largest),
+            Binding::ButtonAxis { negative, positive } => {
+                button_value(*positive) - button_value(*negative)
+            }
+        }
+    }
+}
+
+/// The value of larger magnitude
+fn largest(a: f32, b: f32) -> f32 {
+    if b.abs() > a.abs() {
+        b
+    } else {
+        a
+    }
+}
+
+impl ActionState {
+    /// Returns true while `action` is pressed
+    pub fn pressed(&self, action: &str) -> bool {
+        self.actions.get(action).map_or(false, |data| data.pressed)
+    }
+
+    /// Returns true the frame `action` is pressed
+    pub fn just_pressed(&self, action: &str) -> bool {
+        self.actions
+            .get(action)
+            .map_or(false, |data| data.just_pressed)
+    }
+
+    /// Returns true the frame `action` is released
+    pub fn just_released(&self, action: &str) -> bool {
+        self.actions
+            .get(action)
+            .map_or(false, |data| data.just_released)
+    }
+
+    /// The value of `action`, from `-1.0` to `1.0` for axes and `0.0` or `1.0` for buttons
+    pub fn value(&self, action: &str) -> f32 {
+        self.actions.get(action).map_or(0.0, |data| data.value)
+    }
+
+    /// Updates the actions of `input_map` from the raw input
+    pub fn update(&mut self, input_map: &InputMap, input: &RawInput) {
+        self.actions
+            .retain(|action, _| input_map.actions.contains_key(action));
+        for (action, bindings) in input_map.actions.iter() {
+            let value = bindings
+                .iter()
+                .map(|binding| input.value(binding))
+                .fold(0.0, largest);
+            let pressed = value.abs() >= self.press_threshold;
+            let data = self.actions.entry(action.clone()).or_default();
+            *data = ActionData {
+                value,
+                pressed,
+                just_pressed: pressed && !data.pressed,
+                just_released: !pressed && data.pressed,
+            };
+        }
+    }
+}
+
+/// Updates the [ActionState] from the [InputMap] and the raw input
+pub fn action_system(
+    mut gamepads: Local<HashSet<Gamepad>>,
+    mut gamepad_events: EventReader<GamepadEvent>,
+    input_map: Res<InputMap>,
+    keys: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    mut action_state: ResMut<ActionState>,
+) {
+    for GamepadEvent(gamepad, event_type) in gamepad_events.iter() {
+        match event_type {
+            GamepadEventType::Connected => {
+                gamepads.insert(*gamepad);
+            }
+            GamepadEventType::Disconnected => {
+                gamepads.remove(gamepad);
+            }
+            _ => {}
+        }
+    }
+
+    action_state.update(
+        &input_map,
+        &RawInput {
+            keys: &keys,
+            mouse_buttons: &mouse_buttons,
+            gamepad_buttons: &gamepad_buttons,
+            gamepad_axes: &gamepad_axes,
+            gamepads: &gamepads,
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn updates_actions_from_bindings() {
+        let mut input_map = InputMap::default();
+        input_map
+            .bind("jump", Binding::button(KeyCode::Space))
+            .bind("jump", Binding::button(GamepadButtonType::South))
+            .bind(
+                "save",
+                Binding::with_modifier(KeyCode::S, KeyCode::LControl),
+            )
+            .bind("move", Binding::button_axis(KeyCode::A, KeyCode::D))
+            .bind("move", Binding::gamepad_axis(GamepadAxisType::LeftStickX));
+
+        let mut keys = Input::<KeyCode>::default();
+        let mouse_buttons = Input::<MouseButton>::default();
+        let mut gamepad_buttons = Input::<GamepadButton>::default();
+        let mut gamepad_axes = Axis::<GamepadAxis>::default();
+        let mut gamepads = HashSet::default();
+        gamepads.insert(Gamepad(0));
+        let mut action_state = ActionState::default();
+
+        keys.press(KeyCode::S);
+        keys.press(KeyCode::A);
+        gamepad_buttons.press(GamepadButton(Gamepad(0), GamepadButtonType::South));
+        gamepad_axes.set(GamepadAxis(Gamepad(0), GamepadAxisType::LeftStickX), 0.25);
+        let input = RawInput {
+            keys: &keys,
+            mouse_buttons: &mouse_buttons,
+            gamepad_buttons: &gamepad_buttons,
+            gamepad_axes: &gamepad_axes,
+            gamepads: &gamepads,
+        };
+        action_state.update(&input_map, &input);
+        assert!(action_state.just_pressed("jump"));
+        assert!(!action_state.pressed("save"));
+        assert_eq!(action_state.value("move"), -1.0);
+
+        action_state.update(&input_map, &input);
+        assert!(action_state.pressed("jump"));
+        assert!(!action_state.just_pressed("jump"));
+
+        input_map.set_bindings("jump", vec![Binding::button(MouseButton::Left)]);
+        action_state.update(&input_map, &input);
+        assert!(action_state.just_released("jump"));
+        assert!(!input_map.unbind("save", &Binding::button(KeyCode::S)));
+        assert!(input_map.unbind("move", &Binding::button_axis(KeyCode::A, KeyCode::D)));
+        action_state.update(&input_map, &input);
+        assert_eq!(action_state.value("move"), 0.25);
+        assert!(!action_state.pressed("move"));
+    }
+}
@@ -1,7 +1,7 @@
 use crate::{Axis, Input};
 use bevy_app::{EventReader, EventWriter};
 use bevy_ecs::system::{Res, ResMut};
-use bevy_utils::HashMap;
+use bevy_utils::{Duration, HashMap};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
@@ -69,6 +69,61 @@ pub enum GamepadAxisType {
 #[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct GamepadAxis(pub Gamepad, pub GamepadAxisType);
 
+/// The intensity of the two rumble motors of a gamepad, from `0.0` to `1.0`. The strong motor
+/// is the low frequency one, usually on the left, and the weak motor the high frequency one.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct GamepadRumbleIntensity {
+    pub strong_motor: f32,
+    pub weak_motor: f32,
+}
+
+impl GamepadRumbleIntensity {
+    pub const MAX: GamepadRumbleIntensity = GamepadRumbleIntensity {
+        strong_motor: 1.0,
+        weak_motor: 1.0,
+    };
+
+    pub fn strong_motor(intensity: f32) -> Self {
+        GamepadRumbleIntensity {
+            strong_motor: intensity,
+            weak_motor: 0.0,
+        }
+    }
+
+    pub fn weak_motor(intensity: f32) -> Self {
+        GamepadRumbleIntensity {
+            strong_motor: 0.0,
+            weak_motor: intensity,
+        }
+    }
+}
+
+/// Sent to make a gamepad rumble. Handled by the gamepad backend, and ignored by gamepads without
+/// force feedback.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum GamepadRumbleRequest {
+    /// Rumbles for `duration`, on top of the rumbles already playing
+    Add {
+        gamepad: Gamepad,
+        intensity: GamepadRumbleIntensity,
+        duration: Duration,
+    },
+    /// Stops all the rumbles of the gamepad
+    Stop { gamepad: Gamepad },
+}
+
+impl GamepadRumbleRequest {
+    pub fn gamepad(&self) -> Gamepad {
+        match self {
+            GamepadRumbleRequest::Add { gamepad, .. } | GamepadRumbleRequest::Stop { gamepad } => {
+                *gamepad
+            }
+        }
+    }
+}
+
 #[derive(Default, Debug)]
 pub struct GamepadSettings {
     pub default_button_settings: ButtonSettings,
@@ -1,11 +1,15 @@
+mod action;
 mod axis;
 pub mod gamepad;
 mod input;
 pub mod keyboard;
 pub mod mouse;
+#[cfg(feature = "replay")]
+pub mod replay;
 pub mod system;
 pub mod touch;
 
+pub use action::*;
 pub use axis::*;
 use bevy_ecs::{
     schedule::{ParallelSystemDescriptorCoercion, SystemLabel},
@@ -17,23 +21,23 @@ pub mod prelude {
     pub use crate::{
         gamepad::{
             Gamepad, GamepadAxis, GamepadAxisType, GamepadButton, GamepadButtonType, GamepadEvent,
-            GamepadEventType,
+            GamepadEventType, GamepadRumbleIntensity, GamepadRumbleRequest,
         },
         keyboard::KeyCode,
         mouse::MouseButton,
-        touch::{TouchInput, Touches},
-        Axis, Input,
+        touch::{TouchGesture, TouchInput, Touches},
+        ActionState, Axis, Binding, Input, InputButton, InputMap,
     };
 }
 
 use bevy_app::prelude::*;
 use keyboard::{keyboard_input_system, KeyCode, KeyboardInput};
 use mouse::{mouse_button_input_system, MouseButton, MouseButtonInput, MouseMotion, MouseWheel};
-use touch::{touch_screen_input_system, TouchInput, Touches};
+use touch::{touch_gesture_system, touch_screen_input_system, TouchGesture, TouchInput, Touches};
 
 use gamepad::{
     gamepad_event_system, GamepadAxis, GamepadButton, GamepadEvent, GamepadEventRaw,
-    GamepadSettings,
+    GamepadRumbleRequest, GamepadSettings,
 };
 
 /// Adds keyboard and mouse input to an App
@@ -65,6 +69,7 @@ impl Plugin for InputPlugin {
             // gamepad
             .add_event::<GamepadEvent>()
             .add_event::<GamepadEventRaw>()
+            .add_event::<GamepadRumbleRequest>()
             .init_resource::<GamepadSettings>()
             .init_resource::<Input<GamepadButton>>()
             .init_resource::<Axis<GamepadAxis>>()
@@ -76,9 +81,21 @@ impl Plugin for InputPlugin {
             // touch
             .add_event::<TouchInput>()
             .init_resource::<Touches>()
+            .add_event::<TouchGesture>()
             .add_system_to_stage(
                 CoreStage::PreUpdate,
                 touch_screen_input_system.system().label(InputSystem),
+            )
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                touch_gesture_system.system().after(InputSystem),
+            )
+            // actions
+            .init_resource::<InputMap>()
+            .init_resource::<ActionState>()
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                action_system.system().after(InputSystem),
             );
     }
 }
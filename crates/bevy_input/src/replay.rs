@@ -0,0 +1,407 @@
+use crate::{
+    gamepad::GamepadEventRaw,
+    keyboard::KeyboardInput,
+    mouse::{MouseButtonInput, MouseMotion, MouseWheel},
+    InputSystem,
+};
+use bevy_app::{prelude::*, AppExit, EventReader, EventWriter, Events};
+use bevy_core::{Rng, Time};
+use bevy_ecs::{
+    schedule::{ParallelSystemDescriptorCoercion, SystemLabel},
+    system::{IntoSystem, Res, ResMut},
+};
+use bevy_utils::{
+    tracing::{error, info},
+    Duration,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum InputReplayError {
+    #[error("failed to read or write the recording: {0}")]
+    Io(#[from] io::Error),
+    #[error("invalid recording: {0}")]
+    Ron(#[from] ron::Error),
+}
+
+/// An input event of an [InputRecording]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RecordedInput {
+    Keyboard(KeyboardInput),
+    MouseButton(MouseButtonInput),
+    MouseMotion(MouseMotion),
+    MouseWheel(MouseWheel),
+    Gamepad(GamepadEventRaw),
+}
+
+/// The input events received during a frame
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RecordedFrame {
+    /// The [Time::raw_delta] of the frame
+    pub delta: Duration,
+    pub inputs: Vec<RecordedInput>,
+}
+
+/// The input events of a run, frame by frame, saved as a RON file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InputRecording {
+    /// The seed of the [Rng] resource of the recorded run
+    pub seed: Option<u64>,
+    pub frames: Vec<RecordedFrame>,
+}
+
+impl InputRecording {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, InputReplayError> {
+        let bytes = fs::read(path)?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), InputReplayError> {
+        let ron = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        fs::write(path, ron)?;
+        Ok(())
+    }
+
+    /// The time the recorded frames lasted
+    pub fn duration(&self) -> Duration {
+        self.frames.iter().map(|frame| frame.delta).sum()
+    }
+}
+
+/// Records the keyboard, mouse and gamepad input events of each frame once started
+#[derive(Debug, Default)]
+pub struct InputRecorder {
+    recording: Option<InputRecording>,
+}
+
+impl InputRecorder {
+    /// Starts a new recording, dropping the current one. `seed` should be the seed of the [Rng]
+    /// resource, so that playing the recording seeds it the same way.
+    pub fn start(&mut self, seed: Option<u64>) {
+        self.recording = Some(InputRecording {
+            seed,
+            frames: Vec::new(),
+        });
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    /// The frames recorded so far
+    pub fn recording(&self) -> Option<&InputRecording> {
+        self.recording.as_ref()
+    }
+
+    pub fn stop(&mut self) -> Option<InputRecording> {
+        self.recording.take()
+    }
+}
+
+/// Plays an [InputRecording], sending its events and advancing [Time] by its deltas frame by
+/// frame. The events of the input devices are discarded while playing.
+///
+/// A run is reproduced when its recording is played from the start of the app, by setting the
+/// [InputReplayMode::Playback] mode: the systems then receive the same inputs at the same frames
+/// with the same deltas, and draw the same random numbers. Playing from the middle of a run also
+/// works, but starts from whatever state the app and the pressed inputs are in.
+#[derive(Debug, Default)]
+pub struct InputPlayer {
+    recording: Option<InputRecording>,
+    next_frame: usize,
+    controls_time: bool,
+}
+
+impl InputPlayer {
+    /// Starts playing a recording from its first frame, stopping the current one
+    pub fn play(&mut self, recording: InputRecording) {
+        self.recording = Some(recording);
+        self.next_frame = 0;
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    /// The index of the next frame to play
+    pub fn frame(&self) -> usize {
+        self.next_frame
+    }
+
+    pub fn stop(&mut self) -> Option<InputRecording> {
+        self.recording.take()
+    }
+}
+
+/// Sent when an [InputPlayer] played the last frame of its recording
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlaybackFinished;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputReplayMode {
+    Off,
+    /// Records the inputs from the start, and saves them to the file when the app exits
+    Record(PathBuf),
+    /// Plays the recording of the file from the start
+    Playback(PathBuf),
+}
+
+impl Default for InputReplayMode {
+    fn default() -> Self {
+        InputReplayMode::Off
+    }
+}
+
+/// Insert it before adding the [InputReplayPlugin] to record or play inputs from the start
+#[derive(Debug, Clone, Default)]
+pub struct InputReplaySettings {
+    pub mode: InputReplayMode,
+    /// Sends [AppExit] when the playback finishes, to end automated tests
+    pub exit_when_finished: bool,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash, SystemLabel)]
+pub enum InputReplaySystem {
+    Playback,
+    Record,
+}
+
+/// Records input events to files and plays them back, for automated gameplay tests and bug
+/// reproduction
+#[derive(Default)]
+pub struct InputReplayPlugin;
+
+impl Plugin for InputReplayPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        let settings = app
+            .world_mut()
+            .get_resource_or_insert_with(InputReplaySettings::default)
+            .clone();
+        let mut recorder = InputRecorder::default();
+        let mut player = InputPlayer::default();
+        match &settings.mode {
+            InputReplayMode::Off => {}
+            InputReplayMode::Record(_) => {
+                let seed = app
+                    .world_mut()
+                    .get_resource_or_insert_with(Rng::default)
+                    .seed();
+                recorder.start(Some(seed));
+            }
+            InputReplayMode::Playback(path) => match InputRecording::load(path) {
+                Ok(recording) => {
+                    if let Some(seed) = recording.seed {
+                        app.insert_resource(Rng::seed_from_u64(seed));
+                    }
+                    player.play(recording);
+                }
+                Err(err) => error!(
+                    "failed to load the input recording {}: {}",
+                    path.display(),
+                    err
+                ),
+            },
+        }
+
+        app.insert_resource(recorder)
+            .insert_resource(player)
+            .add_event::<PlaybackFinished>()
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                input_playback_system
+                    .system()
+                    .label(InputReplaySystem::Playback)
+                    .before(InputSystem),
+            )
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                input_record_system
+                    .system()
+                    .label(InputReplaySystem::Record)
+                    .after(InputReplaySystem::Playback)
+                    .before(InputSystem),
+            )
+            .add_system_to_stage(CoreStage::Last, save_recording_on_exit_system.system());
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn input_playback_system(
+    mut player: ResMut<InputPlayer>,
+    mut time: ResMut<Time>,
+    settings: Res<InputReplaySettings>,
+    mut keyboard: ResMut<Events<KeyboardInput>>,
+    mut mouse_button: ResMut<Events<MouseButtonInput>>,
+    mut mouse_motion: ResMut<Events<MouseMotion>>,
+    mut mouse_wheel: ResMut<Events<MouseWheel>>,
+    mut gamepad: ResMut<Events<GamepadEventRaw>>,
+    mut playback_finished: EventWriter<PlaybackFinished>,
+    mut app_exit: EventWriter<AppExit>,
+) {
+    let player = &mut *player;
+    let recording = match &player.recording {
+        Some(recording) => recording,
+        None => {
+            if player.controls_time {
+                player.controls_time = false;
+                time.set_raw_delta_override(None);
+            }
+            return;
+        }
+    };
+
+    keyboard.clear();
+    mouse_button.clear();
+    mouse_motion.clear();
+    mouse_wheel.clear();
+    gamepad.clear();
+    if let Some(frame) = recording.frames.get(player.next_frame) {
+        for input in frame.inputs.iter().cloned() {
+            match input {
+                RecordedInput::Keyboard(event) => keyboard.send(event),
+                RecordedInput::MouseButton(event) => mouse_button.send(event),
+                RecordedInput::MouseMotion(event) => mouse_motion.send(event),
+                RecordedInput::MouseWheel(event) => mouse_wheel.send(event),
+                RecordedInput::Gamepad(event) => gamepad.send(event),
+            }
+        }
+    }
+
+    player.next_frame += 1;
+    match recording.frames.get(player.next_frame) {
+        Some(next_frame) => {
+            player.controls_time = true;
+            time.set_raw_delta_override(Some(next_frame.delta));
+        }
+        None => {
+            player.recording = None;
+            player.controls_time = false;
+            time.set_raw_delta_override(None);
+            playback_finished.send(PlaybackFinished);
+            if settings.exit_when_finished {
+                app_exit.send(AppExit);
+            }
+        }
+    }
+}
+
+pub fn input_record_system(
+    mut recorder: ResMut<InputRecorder>,
+    time: Res<Time>,
+    mut keyboard: EventReader<KeyboardInput>,
+    mut mouse_button: EventReader<MouseButtonInput>,
+    mut mouse_motion: EventReader<MouseMotion>,
+    mut mouse_wheel: EventReader<MouseWheel>,
+    mut gamepad: EventReader<GamepadEventRaw>,
+) {
+    // the events are read even when not recording, so that a recording doesn't start with the
+    // events of the previous frames
+    let mut inputs = Vec::new();
+    inputs.extend(keyboard.iter().cloned().map(RecordedInput::Keyboard));
+    inputs.extend(mouse_button.iter().cloned().map(RecordedInput::MouseButton));
+    inputs.extend(mouse_motion.iter().cloned().map(RecordedInput::MouseMotion));
+    inputs.extend(mouse_wheel.iter().cloned().map(RecordedInput::MouseWheel));
+    inputs.extend(gamepad.iter().cloned().map(RecordedInput::Gamepad));
+    if let Some(recording) = &mut recorder.recording {
+        recording.frames.push(RecordedFrame {
+            delta: time.raw_delta(),
+            inputs,
+        });
+    }
+}
+
+/// Saves the recording of the [InputReplayMode::Record] mode when [AppExit] is sent
+pub fn save_recording_on_exit_system(
+    mut recorder: ResMut<InputRecorder>,
+    settings: Res<InputReplaySettings>,
+    mut app_exit: EventReader<AppExit>,
+) {
+    if app_exit.iter().next().is_none() {
+        return;
+    }
+    if let InputReplayMode::Record(path) = &settings.mode {
+        if let Some(recording) = recorder.stop() {
+            match recording.save(path) {
+                Ok(()) => info!("saved the input recording to {}", path.display()),
+                Err(err) => error!(
+                    "failed to save the input recording to {}: {}",
+                    path.display(),
+                    err
+                ),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{keyboard::KeyCode, ElementState, Input, InputPlugin};
+    use bevy_core::CorePlugin;
+
+    fn key_press(key_code: KeyCode) -> KeyboardInput {
+        KeyboardInput {
+            scan_code: 0,
+            key_code: Some(key_code),
+            state: ElementState::Pressed,
+        }
+    }
+
+    #[test]
+    fn records_and_plays_inputs() {
+        let mut app = App::build();
+        app.add_plugin(CorePlugin)
+            .add_plugin(InputPlugin)
+            .add_plugin(InputReplayPlugin);
+        app.world_mut()
+            .get_resource_mut::<InputRecorder>()
+            .unwrap()
+            .start(Some(3));
+        app.app.update();
+        app.world_mut()
+            .get_resource_mut::<Events<KeyboardInput>>()
+            .unwrap()
+            .send(key_press(KeyCode::Space));
+        app.app.update();
+        let recording = app
+            .world_mut()
+            .get_resource_mut::<InputRecorder>()
+            .unwrap()
+            .stop()
+            .unwrap();
+        assert_eq!(recording.seed, Some(3));
+        assert_eq!(recording.frames.len(), 2);
+        assert!(recording.frames[0].inputs.is_empty());
+        assert_eq!(
+            recording.frames[1].inputs,
+            vec![RecordedInput::Keyboard(key_press(KeyCode::Space))]
+        );
+
+        app.world_mut().insert_resource(Input::<KeyCode>::default());
+        app.world_mut()
+            .get_resource_mut::<InputPlayer>()
+            .unwrap()
+            .play(recording);
+        app.app.update();
+        // the inputs of the devices are ignored while playing
+        app.world_mut()
+            .get_resource_mut::<Events<KeyboardInput>>()
+            .unwrap()
+            .send(key_press(KeyCode::A));
+        app.app.update();
+        let world = app.world_mut();
+        let keys = world.get_resource::<Input<KeyCode>>().unwrap();
+        assert!(keys.just_pressed(KeyCode::Space));
+        assert!(!keys.pressed(KeyCode::A));
+        assert!(!world.get_resource::<InputPlayer>().unwrap().is_playing());
+        assert_eq!(
+            world.get_resource::<Time>().unwrap().raw_delta_override(),
+            None
+        );
+    }
+}
@@ -1,5 +1,5 @@
-use bevy_app::EventReader;
-use bevy_ecs::system::ResMut;
+use bevy_app::{EventReader, EventWriter};
+use bevy_ecs::system::{Local, Res, ResMut};
 use bevy_math::Vec2;
 use bevy_utils::HashMap;
 
@@ -232,6 +232,89 @@ pub fn touch_screen_input_system(
     }
 }
 
+/// A multi-touch gesture performed with two fingers
+///
+/// Gestures are derived from the [Touches] resource each frame, so every event describes the
+/// change since the previous frame. A single two-finger motion can produce a `Pinch`, a `Rotate`
+/// and a `Pan` event at the same time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TouchGesture {
+    /// The fingers moved apart (`scale > 1.0`) or together (`scale < 1.0`)
+    Pinch { scale: f32 },
+    /// The fingers rotated around their midpoint, in radians (counter-clockwise is positive)
+    Rotate { angle: f32 },
+    /// The midpoint between the fingers moved
+    Pan { delta: Vec2 },
+}
+
+/// Fingers tracked by [touch_gesture_system] between frames
+#[derive(Debug, Default)]
+pub struct TouchGestureState {
+    fingers: Option<[(u64, Vec2); 2]>,
+}
+
+/// Computes the gestures performed by two fingers moving from `previous` to `current`
+pub fn two_finger_gestures(previous: [Vec2; 2], current: [Vec2; 2]) -> Vec<TouchGesture> {
+    let mut gestures = Vec::new();
+    let previous_span = previous[1] - previous[0];
+    let current_span = current[1] - current[0];
+
+    let previous_length = previous_span.length();
+    let current_length = current_span.length();
+    if previous_length > f32::EPSILON && current_length > f32::EPSILON {
+        let scale = current_length / previous_length;
+        if (scale - 1.0).abs() > f32::EPSILON {
+            gestures.push(TouchGesture::Pinch { scale });
+        }
+
+        let angle = previous_span
+            .perp_dot(current_span)
+            .atan2(previous_span.dot(current_span));
+        if angle.abs() > f32::EPSILON {
+            gestures.push(TouchGesture::Rotate { angle });
+        }
+    }
+
+    let delta = (current[0] + current[1] - previous[0] - previous[1]) * 0.5;
+    if delta != Vec2::ZERO {
+        gestures.push(TouchGesture::Pan { delta });
+    }
+
+    gestures
+}
+
+/// Sends [TouchGesture] events while exactly two fingers are touching the screen
+pub fn touch_gesture_system(
+    mut state: Local<TouchGestureState>,
+    touches: Res<Touches>,
+    mut gesture_events: EventWriter<TouchGesture>,
+) {
+    let mut pressed = touches.iter();
+    let fingers = match (pressed.next(), pressed.next(), pressed.next()) {
+        (Some(a), Some(b), None) => {
+            let (first, second) = if a.id() < b.id() { (a, b) } else { (b, a) };
+            Some([
+                (first.id(), first.position()),
+                (second.id(), second.position()),
+            ])
+        }
+        _ => None,
+    };
+
+    if let (Some(previous), Some(current)) = (state.fingers, fingers) {
+        // a finger was swapped out since the last frame, so there is nothing to compare against
+        if previous[0].0 == current[0].0 && previous[1].0 == current[1].0 {
+            for gesture in
+                two_finger_gestures([previous[0].1, previous[1].1], [current[0].1, current[1].1])
+            {
+                gesture_events.send(gesture);
+            }
+        }
+    }
+
+    state.fingers = fingers;
+}
+
 #[cfg(test)]
 mod test {
 
@@ -405,4 +488,34 @@ mod test {
         assert!(touches.just_cancelled(touch_event.id));
         assert_eq!(touches.iter_just_cancelled().count(), 1);
     }
+
+    #[test]
+    fn two_finger_gesture() {
+        use crate::touch::{two_finger_gestures, TouchGesture};
+        use bevy_math::Vec2;
+
+        let previous = [Vec2::new(-1.0, 0.0), Vec2::new(1.0, 0.0)];
+
+        // fingers moved apart
+        let gestures = two_finger_gestures(previous, [Vec2::new(-2.0, 0.0), Vec2::new(2.0, 0.0)]);
+        assert_eq!(gestures, vec![TouchGesture::Pinch { scale: 2.0 }]);
+
+        // fingers rotated a quarter turn counter-clockwise
+        let gestures = two_finger_gestures(previous, [Vec2::new(0.0, -1.0), Vec2::new(0.0, 1.0)]);
+        assert_eq!(
+            gestures,
+            vec![TouchGesture::Rotate {
+                angle: std::f32::consts::FRAC_PI_2
+            }]
+        );
+
+        // fingers moved together
+        let gestures = two_finger_gestures(previous, [Vec2::new(0.0, 3.0), Vec2::new(2.0, 3.0)]);
+        assert_eq!(
+            gestures,
+            vec![TouchGesture::Pan {
+                delta: Vec2::new(1.0, 3.0)
+            }]
+        );
+    }
 }
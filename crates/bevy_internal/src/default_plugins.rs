@@ -9,6 +9,10 @@ impl PluginGroup for DefaultPlugins {
         group.add(bevy_transform::TransformPlugin::default());
         group.add(bevy_diagnostic::DiagnosticsPlugin::default());
         group.add(bevy_input::InputPlugin::default());
+
+        #[cfg(feature = "input_replay")]
+        group.add(bevy_input::replay::InputReplayPlugin::default());
+
         group.add(bevy_window::WindowPlugin::default());
         group.add(bevy_asset::AssetPlugin::default());
         group.add(bevy_scene::ScenePlugin::default());
@@ -37,6 +41,21 @@ impl PluginGroup for DefaultPlugins {
         #[cfg(feature = "bevy_gltf")]
         group.add(bevy_gltf::GltfPlugin::default());
 
+        #[cfg(feature = "bevy_navmesh")]
+        group.add(bevy_navmesh::NavMeshPlugin::default());
+
+        #[cfg(feature = "bevy_physics")]
+        group.add(bevy_physics::PhysicsPlugin::default());
+
+        #[cfg(feature = "bevy_net")]
+        group.add(bevy_net::NetworkPlugin::default());
+
+        #[cfg(feature = "bevy_script")]
+        group.add(bevy_script::ScriptPlugin::default());
+
+        #[cfg(feature = "bevy_video")]
+        group.add(bevy_video::VideoPlugin::default());
+
         #[cfg(feature = "bevy_winit")]
         group.add(bevy_winit::WinitPlugin::default());
 
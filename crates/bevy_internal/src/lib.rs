@@ -93,12 +93,36 @@ pub mod gltf {
     pub use bevy_gltf::*;
 }
 
+#[cfg(feature = "bevy_navmesh")]
+pub mod navmesh {
+    //! Navigation meshes and pathfinding.
+    pub use bevy_navmesh::*;
+}
+
+#[cfg(feature = "bevy_net")]
+pub mod net {
+    //! Network transports, connections and component replication.
+    pub use bevy_net::*;
+}
+
 #[cfg(feature = "bevy_pbr")]
 pub mod pbr {
     //! Physically based rendering.
     pub use bevy_pbr::*;
 }
 
+#[cfg(feature = "bevy_physics")]
+pub mod physics {
+    //! Rigid bodies, colliders and collision events.
+    pub use bevy_physics::*;
+}
+
+#[cfg(feature = "bevy_script")]
+pub mod script {
+    //! Systems written as Lua scripts.
+    pub use bevy_script::*;
+}
+
 #[cfg(feature = "bevy_render")]
 pub mod render {
     //! Cameras, meshes, textures, shaders, and pipelines.
@@ -123,6 +147,12 @@ pub mod ui {
     pub use bevy_ui::*;
 }
 
+#[cfg(feature = "bevy_video")]
+pub mod video {
+    //! Video playback to textures.
+    pub use bevy_video::*;
+}
+
 #[cfg(feature = "bevy_winit")]
 pub mod winit {
     pub use bevy_winit::*;
@@ -9,12 +9,24 @@ pub use bevy_derive::bevy_main;
 #[cfg(feature = "bevy_audio")]
 pub use crate::audio::prelude::*;
 
+#[cfg(feature = "bevy_navmesh")]
+pub use crate::navmesh::prelude::*;
+
+#[cfg(feature = "bevy_net")]
+pub use crate::net::prelude::*;
+
 #[cfg(feature = "bevy_pbr")]
 pub use crate::pbr::prelude::*;
 
+#[cfg(feature = "bevy_physics")]
+pub use crate::physics::prelude::*;
+
 #[cfg(feature = "bevy_render")]
 pub use crate::render::prelude::*;
 
+#[cfg(feature = "bevy_script")]
+pub use crate::script::prelude::*;
+
 #[cfg(feature = "bevy_sprite")]
 pub use crate::sprite::prelude::*;
 
@@ -24,6 +36,9 @@ pub use crate::text::prelude::*;
 #[cfg(feature = "bevy_ui")]
 pub use crate::ui::prelude::*;
 
+#[cfg(feature = "bevy_video")]
+pub use crate::video::prelude::*;
+
 #[cfg(feature = "bevy_dynamic_plugin")]
 pub use crate::dynamic_plugin::*;
 
@@ -1,5 +1,6 @@
 #[cfg(target_os = "android")]
 mod android_tracing;
+mod log_buffer;
 
 pub mod prelude {
     pub use bevy_utils::tracing::{
@@ -10,6 +11,7 @@ pub use bevy_utils::tracing::{
     debug, debug_span, error, error_span, info, info_span, trace, trace_span, warn, warn_span,
     Level,
 };
+pub use log_buffer::*;
 
 use bevy_app::{AppBuilder, Plugin};
 #[cfg(feature = "tracing-chrome")]
@@ -28,6 +30,9 @@ pub struct LogSettings {
     /// Filters out logs that are "less than" the given level.
     /// This can be further filtered using the `filter` setting.
     pub level: Level,
+
+    /// The number of recent log events kept in the [LogBuffer] resource
+    pub buffer_capacity: usize,
 }
 
 impl Default for LogSettings {
@@ -35,23 +40,31 @@ impl Default for LogSettings {
         Self {
             filter: "wgpu=error".to_string(),
             level: Level::INFO,
+            buffer_capacity: 256,
         }
     }
 }
 
 impl Plugin for LogPlugin {
     fn build(&self, app: &mut AppBuilder) {
-        let default_filter = {
+        let (default_filter, buffer_capacity) = {
             let settings = app
                 .world_mut()
                 .get_resource_or_insert_with(LogSettings::default);
-            format!("{},{}", settings.level, settings.filter)
+            (
+                format!("{},{}", settings.level, settings.filter),
+                settings.buffer_capacity,
+            )
         };
+        let log_buffer = LogBuffer::new(buffer_capacity);
+        app.insert_resource(log_buffer.clone());
 
         let filter_layer = EnvFilter::try_from_default_env()
             .or_else(|_| EnvFilter::try_new(&default_filter))
             .unwrap();
-        let subscriber = Registry::default().with(filter_layer);
+        let subscriber = Registry::default()
+            .with(filter_layer)
+            .with(LogBufferLayer(log_buffer));
 
         #[cfg(all(not(target_arch = "wasm32"), not(target_os = "android")))]
         {
@@ -0,0 +1,132 @@
+use bevy_utils::tracing::{
+    field::{Field, Visit},
+    Event, Level, Subscriber,
+};
+use std::{
+    collections::VecDeque,
+    fmt::{self, Write},
+    sync::{Arc, Mutex},
+};
+use tracing_subscriber::layer::{Context, Layer};
+
+/// A log event kept by the [LogBuffer]
+#[derive(Debug, Clone)]
+pub struct LogRecord {
+    pub level: Level,
+    pub target: String,
+    /// The message, followed by the other fields of the event
+    pub message: String,
+}
+
+#[derive(Debug, Default)]
+struct LogBufferState {
+    records: VecDeque<LogRecord>,
+    /// The number of records pushed since the buffer was created
+    pushed: usize,
+}
+
+/// The most recent log events that passed the [LogSettings](crate::LogSettings) filter, so they can
+/// be shown in the app. Inserted as a resource by the [LogPlugin](crate::LogPlugin).
+///
+/// Clones share the same records.
+#[derive(Debug, Clone)]
+pub struct LogBuffer {
+    state: Arc<Mutex<LogBufferState>>,
+    capacity: usize,
+}
+
+impl LogBuffer {
+    /// A buffer keeping the last `capacity` records
+    pub fn new(capacity: usize) -> Self {
+        LogBuffer {
+            state: Default::default(),
+            capacity,
+        }
+    }
+
+    pub fn push(&self, record: LogRecord) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        if state.records.len() == self.capacity {
+            state.records.pop_front();
+        }
+        state.records.push_back(record);
+        state.pushed += 1;
+    }
+
+    /// Returns the records pushed since the last read with the same `cursor`, which starts at `0`.
+    /// Records dropped from the buffer in between are skipped.
+    pub fn read(&self, cursor: &mut usize) -> Vec<LogRecord> {
+        let state = self.state.lock().unwrap();
+        let first = state.pushed - state.records.len();
+        let start = (*cursor).max(first) - first;
+        *cursor = state.pushed;
+        state.records.iter().skip(start).cloned().collect()
+    }
+}
+
+/// Pushes the events it receives to a [LogBuffer]
+pub(crate) struct LogBufferLayer(pub LogBuffer);
+
+impl<S: Subscriber> Layer<S> for LogBufferLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        let metadata = event.metadata();
+        self.0.push(LogRecord {
+            level: *metadata.level(),
+            target: metadata.target().to_string(),
+            message: visitor.message + &visitor.fields,
+        });
+    }
+}
+
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{:?}", value);
+        } else {
+            let _ = write!(self.fields, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(message: &str) -> LogRecord {
+        LogRecord {
+            level: Level::INFO,
+            target: "test".to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn reads_new_records() {
+        let buffer = LogBuffer::new(2);
+        let mut cursor = 0;
+        buffer.push(record("a"));
+        assert_eq!(buffer.read(&mut cursor).len(), 1);
+        assert!(buffer.read(&mut cursor).is_empty());
+
+        buffer.push(record("b"));
+        buffer.push(record("c"));
+        buffer.push(record("d"));
+        let records = buffer.read(&mut cursor);
+        let messages = records
+            .iter()
+            .map(|r| r.message.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(messages, vec!["c", "d"]);
+    }
+}
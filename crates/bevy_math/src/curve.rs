@@ -0,0 +1,242 @@
+use crate::{Quat, Vec2, Vec3, Vec4};
+use bevy_reflect::{Reflect, ReflectDeserialize};
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+
+/// A value that can be blended along a [Curve]
+pub trait Interpolate: Copy {
+    /// Blends from `self` at a `t` of 0 to `other` at a `t` of 1
+    fn interpolate(self, other: Self, t: f32) -> Self;
+}
+
+impl Interpolate for f32 {
+    fn interpolate(self, other: Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Interpolate for Vec2 {
+    fn interpolate(self, other: Self, t: f32) -> Self {
+        self.lerp(other, t)
+    }
+}
+
+impl Interpolate for Vec3 {
+    fn interpolate(self, other: Self, t: f32) -> Self {
+        self.lerp(other, t)
+    }
+}
+
+impl Interpolate for Vec4 {
+    fn interpolate(self, other: Self, t: f32) -> Self {
+        self.lerp(other, t)
+    }
+}
+
+impl Interpolate for Quat {
+    fn interpolate(self, other: Self, t: f32) -> Self {
+        self.slerp(other, t)
+    }
+}
+
+/// How a [Curve] moves from a keyframe to the next one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect, Serialize, Deserialize)]
+#[reflect_value(PartialEq, Hash, Serialize, Deserialize)]
+pub enum Interpolation {
+    /// Holds the value of the keyframe until the next one
+    Step,
+    /// Blends at a constant rate
+    Linear,
+    /// Blends slowly near both keyframes and quickly between them
+    Smooth,
+}
+
+impl Default for Interpolation {
+    fn default() -> Self {
+        Interpolation::Linear
+    }
+}
+
+impl Interpolation {
+    /// Eases `t`, the fraction of the way from a keyframe to the next one
+    pub fn ease(self, t: f32) -> f32 {
+        match self {
+            Interpolation::Step => 0.0,
+            Interpolation::Linear => t,
+            Interpolation::Smooth => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+/// A value of a [Curve] at a point in time
+#[derive(Debug, Clone, Copy, PartialEq, Reflect, Serialize, Deserialize)]
+pub struct Keyframe<T: Reflect> {
+    pub time: f32,
+    pub value: T,
+    /// How the curve moves from this keyframe to the next one
+    pub interpolation: Interpolation,
+}
+
+impl<T: Reflect> Keyframe<T> {
+    pub fn new(time: f32, value: T) -> Self {
+        Keyframe {
+            time,
+            value,
+            interpolation: Interpolation::Linear,
+        }
+    }
+
+    pub fn with_interpolation(mut self, interpolation: Interpolation) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+}
+
+/// A value changing over time, defined by keyframes. Before the first keyframe and after the
+/// last one, the curve holds their values.
+///
+/// Curves can be part of scenes and assets. Their keyframes are sorted when they are loaded, and
+/// loading a curve without any fails.
+#[derive(Debug, Clone, PartialEq, Reflect, Serialize, Deserialize)]
+#[serde(try_from = "CurveKeyframes<T>")]
+pub struct Curve<T: Reflect> {
+    keyframes: Vec<Keyframe<T>>,
+}
+
+/// The serialized form of a [Curve], checked before it becomes one
+#[derive(Deserialize)]
+struct CurveKeyframes<T: Reflect> {
+    keyframes: Vec<Keyframe<T>>,
+}
+
+impl<T: Reflect> TryFrom<CurveKeyframes<T>> for Curve<T> {
+    type Error = &'static str;
+
+    fn try_from(CurveKeyframes { mut keyframes }: CurveKeyframes<T>) -> Result<Self, Self::Error> {
+        if keyframes.is_empty() {
+            return Err("a Curve needs at least one keyframe");
+        }
+        sort_keyframes(&mut keyframes);
+        Ok(Curve { keyframes })
+    }
+}
+
+/// Sorts keyframes by time, putting keyframes with a NaN time last
+fn sort_keyframes<T: Reflect>(keyframes: &mut [Keyframe<T>]) {
+    keyframes.sort_by(|a, b| {
+        a.time
+            .partial_cmp(&b.time)
+            .unwrap_or_else(|| a.time.is_nan().cmp(&b.time.is_nan()))
+    });
+}
+
+impl<T: Reflect + Interpolate> Curve<T> {
+    /// Creates a curve from keyframes in any order. Panics if there are no keyframes.
+    pub fn new(keyframes: impl IntoIterator<Item = Keyframe<T>>) -> Self {
+        let mut keyframes = keyframes.into_iter().collect::<Vec<_>>();
+        assert!(!keyframes.is_empty(), "a Curve needs at least one keyframe");
+        sort_keyframes(&mut keyframes);
+        Curve { keyframes }
+    }
+
+    /// Creates a curve linearly interpolated between `(time, value)` keyframes, in any order.
+    /// Panics if there are no keyframes.
+    pub fn linear(keyframes: impl IntoIterator<Item = (f32, T)>) -> Self {
+        Curve::new(
+            keyframes
+                .into_iter()
+                .map(|(time, value)| Keyframe::new(time, value)),
+        )
+    }
+
+    /// A curve that always has `value`
+    pub fn constant(value: T) -> Self {
+        Curve {
+            keyframes: vec![Keyframe::new(0.0, value)],
+        }
+    }
+
+    pub fn keyframes(&self) -> &[Keyframe<T>] {
+        &self.keyframes
+    }
+
+    /// The time of the first keyframe
+    pub fn start_time(&self) -> f32 {
+        self.keyframes.first().map_or(0.0, |keyframe| keyframe.time)
+    }
+
+    /// The time of the last keyframe
+    pub fn end_time(&self) -> f32 {
+        self.keyframes.last().map_or(0.0, |keyframe| keyframe.time)
+    }
+
+    /// The value at `time`
+    pub fn sample(&self, time: f32) -> T {
+        let next = self
+            .keyframes
+            .iter()
+            .position(|keyframe| keyframe.time > time);
+        match next {
+            Some(0) => self.keyframes[0].value,
+            Some(next) => {
+                let from = &self.keyframes[next - 1];
+                let to = &self.keyframes[next];
+                let t = (time - from.time) / (to.time - from.time);
+                from.value.interpolate(to.value, from.interpolation.ease(t))
+            }
+            None => self.keyframes[self.keyframes.len() - 1].value,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn samples_keyframes() {
+        let curve: Curve<f32> = Curve::linear(vec![(1.0, 4.0), (0.0, 0.0), (0.5, 1.0)]);
+        assert_eq!(curve.sample(-1.0), 0.0);
+        assert_eq!(curve.sample(0.25), 0.5);
+        assert_eq!(curve.sample(0.5), 1.0);
+        assert_eq!(curve.sample(0.75), 2.5);
+        assert_eq!(curve.sample(2.0), 4.0);
+        assert_eq!((curve.start_time(), curve.end_time()), (0.0, 1.0));
+        assert_eq!(Curve::constant(3.0f32).sample(10.0), 3.0);
+    }
+
+    #[test]
+    fn eases_between_keyframes() {
+        let curve = Curve::new(vec![
+            Keyframe::new(0.0, Vec2::ZERO).with_interpolation(Interpolation::Step),
+            Keyframe::new(1.0, Vec2::ONE).with_interpolation(Interpolation::Smooth),
+            Keyframe::new(2.0, Vec2::new(2.0, 3.0)),
+        ]);
+        assert_eq!(curve.sample(0.9), Vec2::ZERO);
+        assert_eq!(curve.sample(1.0), Vec2::ONE);
+        assert_eq!(curve.sample(1.5), Vec2::new(1.5, 2.0));
+        assert!(curve.sample(1.25).x < 1.25);
+    }
+
+    #[test]
+    fn sorts_keyframes_with_nan_times() {
+        let curve: Curve<f32> = Curve::linear(vec![(1.0, 1.0), (f32::NAN, 2.0), (0.0, 0.0)]);
+        let times = curve.keyframes().iter().map(|keyframe| keyframe.time);
+        assert_eq!(times.take(2).collect::<Vec<_>>(), vec![0.0, 1.0]);
+        assert!(curve.end_time().is_nan());
+    }
+
+    #[test]
+    fn checks_loaded_keyframes() {
+        let curve: Curve<f32> = ron::from_str(
+            "(keyframes: [
+                (time: 1.0, value: 1.0, interpolation: Linear),
+                (time: 0.0, value: 0.0, interpolation: Linear),
+            ])",
+        )
+        .unwrap();
+        assert_eq!((curve.start_time(), curve.end_time()), (0.0, 1.0));
+        assert_eq!(curve.sample(0.5), 0.5);
+        assert!(ron::from_str::<Curve<f32>>("(keyframes: [])").is_err());
+    }
+}
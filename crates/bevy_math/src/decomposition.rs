@@ -0,0 +1,182 @@
+use crate::{Mat3, Mat4, Quat, Vec3};
+
+/// The squared length under which an axis of a matrix is considered collapsed
+const EPSILON: f32 = 1e-12;
+
+/// An affine transformation split into a scale, then a shear, then a rotation and finally a
+/// translation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AffineDecomposition {
+    pub translation: Vec3,
+    pub rotation: Quat,
+    /// How far the y axis leans along x, the z axis along x, and the z axis along y
+    pub shear: Vec3,
+    /// The scale along each axis. A mirroring transformation has a negative x scale.
+    pub scale: Vec3,
+}
+
+impl AffineDecomposition {
+    /// Decomposes an affine transformation `matrix`, whose bottom row is ignored. The rotation
+    /// stays valid when the matrix collapses an axis, which then has a scale of 0.
+    pub fn from_matrix(matrix: &Mat4) -> Self {
+        let mut columns = [
+            matrix.x_axis.truncate(),
+            matrix.y_axis.truncate(),
+            matrix.z_axis.truncate(),
+        ];
+        let collapsed = [
+            columns[0].length_squared() <= EPSILON,
+            columns[1].length_squared() <= EPSILON,
+            columns[2].length_squared() <= EPSILON,
+        ];
+        // stand in for the collapsed axes so the remaining ones still define a rotation
+        let unit_axes = [Vec3::X, Vec3::Y, Vec3::Z];
+        for i in 0..3 {
+            if collapsed[i] {
+                let cross = columns[(i + 1) % 3].cross(columns[(i + 2) % 3]);
+                columns[i] = if cross.length_squared() > EPSILON {
+                    cross
+                } else {
+                    unit_axes[i]
+                };
+            }
+        }
+
+        // Gram-Schmidt orthogonalization, the removed parts being the shear
+        let (mut x_axis, mut x_scale) = split_length(columns[0], Vec3::X);
+        let mut xy_shear = x_axis.dot(columns[1]);
+        let (y_axis, y_scale) = split_length(columns[1] - x_axis * xy_shear, orthogonal(x_axis));
+        let mut xz_shear = x_axis.dot(columns[2]);
+        let yz_shear = y_axis.dot(columns[2]);
+        let (z_axis, z_scale) = split_length(
+            columns[2] - x_axis * xz_shear - y_axis * yz_shear,
+            x_axis.cross(y_axis),
+        );
+
+        if x_axis.cross(y_axis).dot(z_axis) < 0.0 {
+            x_axis = -x_axis;
+            x_scale = -x_scale;
+            xy_shear = -xy_shear;
+            xz_shear = -xz_shear;
+        }
+
+        let scale = Vec3::new(
+            if collapsed[0] { 0.0 } else { x_scale },
+            if collapsed[1] { 0.0 } else { y_scale },
+            if collapsed[2] { 0.0 } else { z_scale },
+        );
+        let shear_ratio = |shear: f32, scale: f32| if scale == 0.0 { 0.0 } else { shear / scale };
+        AffineDecomposition {
+            translation: matrix.w_axis.truncate(),
+            rotation: Quat::from_rotation_mat3(&Mat3::from_cols(x_axis, y_axis, z_axis))
+                .normalize(),
+            shear: Vec3::new(
+                shear_ratio(xy_shear, scale.y),
+                shear_ratio(xz_shear, scale.z),
+                shear_ratio(yz_shear, scale.z),
+            ),
+            scale,
+        }
+    }
+
+    pub fn to_matrix(&self) -> Mat4 {
+        let rotation = Mat3::from_quat(self.rotation);
+        let x_axis = rotation.x_axis * self.scale.x;
+        let y_axis = (rotation.x_axis * self.shear.x + rotation.y_axis) * self.scale.y;
+        let z_axis =
+            (rotation.x_axis * self.shear.y + rotation.y_axis * self.shear.z + rotation.z_axis)
+                * self.scale.z;
+        Mat4::from_cols(
+            x_axis.extend(0.0),
+            y_axis.extend(0.0),
+            z_axis.extend(0.0),
+            self.translation.extend(1.0),
+        )
+    }
+}
+
+/// Splits `vector` into its direction and length, using `fallback` as the direction when it is
+/// too short to have one
+fn split_length(vector: Vec3, fallback: Vec3) -> (Vec3, f32) {
+    let length_squared = vector.length_squared();
+    if length_squared > EPSILON {
+        let length = length_squared.sqrt();
+        (vector / length, length)
+    } else {
+        (fallback, 0.0)
+    }
+}
+
+/// A unit vector orthogonal to the unit vector `vector`
+fn orthogonal(vector: Vec3) -> Vec3 {
+    let other = if vector.x.abs() < 0.9 {
+        Vec3::X
+    } else {
+        Vec3::Y
+    };
+    vector.cross(other).normalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_matrix_eq(a: Mat4, b: Mat4) {
+        assert!(a.abs_diff_eq(b, 1e-4), "{:?} != {:?}", a, b);
+    }
+
+    // a quaternion and its negation are the same rotation
+    fn assert_rotation_eq(a: Quat, b: Quat) {
+        assert_matrix_eq(Mat4::from_quat(a), Mat4::from_quat(b));
+    }
+
+    #[test]
+    fn decomposes_non_uniform_scale_and_shear() {
+        let rotation = Quat::from_rotation_ypr(0.3, -1.1, 0.7);
+        let shear = Mat4::from_cols_array(&[
+            1.0, 0.0, 0.0, 0.0, //
+            0.5, 1.0, 0.0, 0.0, //
+            -0.25, 0.75, 1.0, 0.0, //
+            0.0, 0.0, 0.0, 1.0,
+        ]);
+        let matrix = Mat4::from_rotation_translation(rotation, Vec3::new(1.0, 2.0, 3.0))
+            * shear
+            * Mat4::from_scale(Vec3::new(2.0, 0.5, 3.0));
+
+        let decomposition = AffineDecomposition::from_matrix(&matrix);
+        assert_rotation_eq(decomposition.rotation, rotation);
+        assert!(decomposition
+            .shear
+            .abs_diff_eq(Vec3::new(0.5, -0.25, 0.75), 1e-4));
+        assert!(decomposition
+            .scale
+            .abs_diff_eq(Vec3::new(2.0, 0.5, 3.0), 1e-4));
+        assert_eq!(decomposition.translation, Vec3::new(1.0, 2.0, 3.0));
+        assert_matrix_eq(decomposition.to_matrix(), matrix);
+    }
+
+    #[test]
+    fn decomposes_mirroring() {
+        let matrix = Mat4::from_quat(Quat::from_rotation_y(0.5))
+            * Mat4::from_scale(Vec3::new(1.0, -2.0, 1.0));
+        let decomposition = AffineDecomposition::from_matrix(&matrix);
+        assert!(decomposition.scale.x < 0.0);
+        assert_matrix_eq(decomposition.to_matrix(), matrix);
+    }
+
+    #[test]
+    fn keeps_a_rotation_when_an_axis_collapses() {
+        let rotation = Quat::from_rotation_z(1.0);
+        let matrix = Mat4::from_quat(rotation) * Mat4::from_scale(Vec3::new(2.0, 0.0, 1.0));
+        let decomposition = AffineDecomposition::from_matrix(&matrix);
+        assert_rotation_eq(decomposition.rotation, rotation);
+        assert!(decomposition
+            .scale
+            .abs_diff_eq(Vec3::new(2.0, 0.0, 1.0), 1e-4));
+        assert_matrix_eq(decomposition.to_matrix(), matrix);
+
+        let collapsed = AffineDecomposition::from_matrix(&Mat4::from_scale(Vec3::ZERO));
+        assert_eq!(collapsed.scale, Vec3::ZERO);
+        assert!(collapsed.rotation.is_normalized());
+    }
+}
@@ -1,9 +1,15 @@
+mod curve;
+mod decomposition;
 mod face_toward;
 mod geometry;
+mod primitives;
 
+pub use curve::*;
+pub use decomposition::*;
 pub use face_toward::*;
 pub use geometry::*;
 pub use glam::*;
+pub use primitives::*;
 
 pub mod prelude {
     pub use crate::{
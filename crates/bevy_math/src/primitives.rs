@@ -0,0 +1,336 @@
+use crate::{Mat4, Vec2, Vec3, Vec4};
+
+/// A two dimensional axis-aligned rectangle, defined by its corners
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb2d {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl Aabb2d {
+    pub fn from_center_size(center: Vec2, size: Vec2) -> Self {
+        let half_size = size * 0.5;
+        Aabb2d {
+            min: center - half_size,
+            max: center + half_size,
+        }
+    }
+
+    pub fn center(&self) -> Vec2 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn size(&self) -> Vec2 {
+        self.max - self.min
+    }
+
+    pub fn contains_point(&self, point: Vec2) -> bool {
+        self.min.cmple(point).all() && point.cmple(self.max).all()
+    }
+
+    pub fn intersects(&self, other: &Aabb2d) -> bool {
+        self.min.cmple(other.max).all() && other.min.cmple(self.max).all()
+    }
+
+    /// The area covered by both rectangles, which [is empty](Aabb2d::is_empty) if they don't
+    /// intersect
+    pub fn intersection(&self, other: &Aabb2d) -> Aabb2d {
+        Aabb2d {
+            min: self.min.max(other.min),
+            max: self.max.min(other.max),
+        }
+    }
+
+    /// Returns true if the rectangle covers no area
+    pub fn is_empty(&self) -> bool {
+        self.max.cmple(self.min).any()
+    }
+}
+
+/// An axis-aligned bounding box
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    pub fn from_center_half_extents(center: Vec3, half_extents: Vec3) -> Self {
+        Aabb {
+            min: center - half_extents,
+            max: center + half_extents,
+        }
+    }
+
+    /// The smallest box containing all of `points`. The box is inverted, with `min` above `max`,
+    /// when there are no points.
+    pub fn from_points(points: impl IntoIterator<Item = Vec3>) -> Self {
+        let empty = Aabb {
+            min: Vec3::splat(f32::MAX),
+            max: Vec3::splat(f32::MIN),
+        };
+        points.into_iter().fold(empty, |aabb, point| Aabb {
+            min: aabb.min.min(point),
+            max: aabb.max.max(point),
+        })
+    }
+
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn half_extents(&self) -> Vec3 {
+        (self.max - self.min) * 0.5
+    }
+
+    /// The corner of the box selected by the bits of `index`, which is below 8: the lowest bit
+    /// picks `max.x` over `min.x`, the next one `max.y` and the highest one `max.z`
+    pub fn corner(&self, index: usize) -> Vec3 {
+        Vec3::new(
+            if index & 1 == 0 {
+                self.min.x
+            } else {
+                self.max.x
+            },
+            if index & 2 == 0 {
+                self.min.y
+            } else {
+                self.max.y
+            },
+            if index & 4 == 0 {
+                self.min.z
+            } else {
+                self.max.z
+            },
+        )
+    }
+
+    pub fn contains_point(&self, point: Vec3) -> bool {
+        self.min.cmple(point).all() && point.cmple(self.max).all()
+    }
+
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        self.min.cmple(other.max).all() && other.min.cmple(self.max).all()
+    }
+
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        let closest = sphere.center.max(self.min).min(self.max);
+        closest.distance_squared(sphere.center) <= sphere.radius * sphere.radius
+    }
+
+    pub fn expand(&self, margin: f32) -> Aabb {
+        Aabb {
+            min: self.min - Vec3::splat(margin),
+            max: self.max + Vec3::splat(margin),
+        }
+    }
+
+    /// The smallest box containing both boxes
+    pub fn merge(&self, other: &Aabb) -> Aabb {
+        Aabb {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+
+    /// The smallest box containing this box after it is transformed by `matrix`
+    pub fn transformed(&self, matrix: &Mat4) -> Aabb {
+        Aabb::from_points((0..8).map(|i| matrix.transform_point3(self.corner(i))))
+    }
+}
+
+/// A sphere enclosing a volume
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingSphere {
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+impl BoundingSphere {
+    pub fn new(center: Vec3, radius: f32) -> Self {
+        BoundingSphere { center, radius }
+    }
+
+    /// The sphere passing through the corners of `aabb`
+    pub fn from_aabb(aabb: &Aabb) -> Self {
+        BoundingSphere {
+            center: aabb.center(),
+            radius: aabb.half_extents().length(),
+        }
+    }
+
+    pub fn contains_point(&self, point: Vec3) -> bool {
+        self.center.distance_squared(point) <= self.radius * self.radius
+    }
+
+    pub fn intersects(&self, other: &BoundingSphere) -> bool {
+        let radius = self.radius + other.radius;
+        self.center.distance_squared(other.center) <= radius * radius
+    }
+}
+
+/// A plane made of the points `p` for which `normal.dot(p) + distance` is zero. The normal
+/// points to the positive half space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Plane {
+    pub normal: Vec3,
+    pub distance: f32,
+}
+
+impl Plane {
+    /// The plane through `point` facing the normalized `normal`
+    pub fn from_point_normal(point: Vec3, normal: Vec3) -> Self {
+        Plane {
+            normal,
+            distance: -normal.dot(point),
+        }
+    }
+
+    /// The plane made of the points `p` for which `coefficients.dot(p.extend(1.0))` is zero,
+    /// scaled to a normalized normal
+    pub fn from_coefficients(coefficients: Vec4) -> Self {
+        let normal = coefficients.truncate();
+        let length = normal.length();
+        Plane {
+            normal: normal / length,
+            distance: coefficients.w / length,
+        }
+    }
+
+    /// The distance of `point` to the plane, negative behind it
+    pub fn signed_distance(&self, point: Vec3) -> f32 {
+        self.normal.dot(point) + self.distance
+    }
+
+    /// The point of the ray from `origin` along `direction` on the plane, if the ray isn't
+    /// parallel to the plane or pointing away from it
+    pub fn intersect_ray(&self, origin: Vec3, direction: Vec3) -> Option<Vec3> {
+        let denominator = self.normal.dot(direction);
+        if denominator.abs() <= f32::EPSILON {
+            return None;
+        }
+        let t = -self.signed_distance(origin) / denominator;
+        if t < 0.0 {
+            return None;
+        }
+        Some(origin + direction * t)
+    }
+}
+
+/// The volume visible to a camera, bounded by six planes facing inwards
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frustum {
+    /// The left, right, bottom, top, near and far planes
+    pub planes: [Plane; 6],
+}
+
+impl Frustum {
+    /// The frustum of a view projection matrix mapping to clip space with depth between 0 and 1.
+    /// The planes are in the space transformed by the matrix.
+    pub fn from_view_projection(view_projection: &Mat4) -> Self {
+        let rows = view_projection.transpose();
+        let (x, y, z, w) = (rows.x_axis, rows.y_axis, rows.z_axis, rows.w_axis);
+        Frustum {
+            planes: [
+                Plane::from_coefficients(w + x),
+                Plane::from_coefficients(w - x),
+                Plane::from_coefficients(w + y),
+                Plane::from_coefficients(w - y),
+                Plane::from_coefficients(z),
+                Plane::from_coefficients(w - z),
+            ],
+        }
+    }
+
+    pub fn contains_point(&self, point: Vec3) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.signed_distance(point) >= 0.0)
+    }
+
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.signed_distance(sphere.center) >= -sphere.radius)
+    }
+
+    /// Returns false when the box is entirely behind one of the planes. Boxes near the edges of
+    /// the frustum may be reported as intersecting while being outside of it.
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        self.planes.iter().all(|plane| {
+            // the corner furthest along the normal
+            let corner = Vec3::new(
+                if plane.normal.x >= 0.0 {
+                    aabb.max.x
+                } else {
+                    aabb.min.x
+                },
+                if plane.normal.y >= 0.0 {
+                    aabb.max.y
+                } else {
+                    aabb.min.y
+                },
+                if plane.normal.z >= 0.0 {
+                    aabb.max.z
+                } else {
+                    aabb.min.z
+                },
+            );
+            plane.signed_distance(corner) >= 0.0
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intersects_boxes_and_spheres() {
+        let aabb = Aabb::from_center_half_extents(Vec3::ZERO, Vec3::ONE);
+        assert!(aabb.intersects(&Aabb::from_points(vec![Vec3::ONE, Vec3::splat(2.0)])));
+        assert!(!aabb.intersects(&aabb.transformed(&Mat4::from_translation(Vec3::X * 3.0))));
+        assert!(aabb.intersects_sphere(&BoundingSphere::new(Vec3::new(1.5, 0.0, 0.0), 0.6)));
+        assert!(!aabb.intersects_sphere(&BoundingSphere::new(Vec3::splat(1.5), 0.6)));
+
+        let rect = Aabb2d::from_center_size(Vec2::ZERO, Vec2::new(4.0, 2.0));
+        assert!(rect.contains_point(Vec2::new(2.0, -1.0)));
+        assert!(!rect.contains_point(Vec2::new(0.0, 1.5)));
+        assert!(!rect.intersects(&Aabb2d::from_center_size(Vec2::X * 5.0, Vec2::ONE)));
+        let overlap = rect.intersection(&Aabb2d::from_center_size(Vec2::X * 2.0, Vec2::ONE));
+        assert_eq!(overlap.min, Vec2::new(1.5, -0.5));
+        assert_eq!(overlap.max, Vec2::new(2.0, 0.5));
+        assert!(rect
+            .intersection(&Aabb2d::from_center_size(Vec2::X * 5.0, Vec2::ONE))
+            .is_empty());
+    }
+
+    #[test]
+    fn culls_against_perspective_frustum() {
+        let projection = Mat4::perspective_rh(std::f32::consts::FRAC_PI_2, 1.0, 1.0, 100.0);
+        let frustum = Frustum::from_view_projection(&projection);
+        assert!(frustum.contains_point(Vec3::new(0.0, 0.0, -10.0)));
+        assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, -0.5)));
+        assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, -101.0)));
+        assert!(!frustum.contains_point(Vec3::new(11.0, 0.0, -10.0)));
+
+        let visible = Aabb::from_center_half_extents(Vec3::new(10.5, 0.0, -10.0), Vec3::ONE);
+        assert!(frustum.intersects_aabb(&visible));
+        let behind = Aabb::from_center_half_extents(Vec3::new(0.0, 0.0, 5.0), Vec3::ONE);
+        assert!(!frustum.intersects_aabb(&behind));
+        assert!(!frustum.intersects_sphere(&BoundingSphere::new(Vec3::new(0.0, 0.0, 5.0), 1.0)));
+        assert!(frustum.intersects_sphere(&BoundingSphere::new(Vec3::ZERO, 1.5)));
+    }
+
+    #[test]
+    fn intersects_planes_with_rays() {
+        let plane = Plane::from_point_normal(Vec3::Y, Vec3::Y);
+        assert_eq!(plane.signed_distance(Vec3::new(3.0, 4.0, 0.0)), 3.0);
+        assert_eq!(
+            plane.intersect_ray(Vec3::new(1.0, 5.0, 0.0), -Vec3::Y),
+            Some(Vec3::new(1.0, 1.0, 0.0))
+        );
+        assert_eq!(plane.intersect_ray(Vec3::new(1.0, 5.0, 0.0), Vec3::Y), None);
+        assert_eq!(plane.intersect_ray(Vec3::new(1.0, 5.0, 0.0), Vec3::X), None);
+    }
+}
@@ -0,0 +1,161 @@
+mod navmesh;
+mod pathfinding;
+
+pub use navmesh::*;
+
+pub mod prelude {
+    pub use crate::{
+        NavMesh, NavMeshBakeSettings, NavMeshDebug, NavMeshPlugin, PathRequest, PathResult,
+    };
+}
+
+use bevy_app::prelude::*;
+use bevy_asset::{AddAsset, AssetEvent, Assets, Handle, HandleId};
+use bevy_ecs::{
+    entity::Entity,
+    event::EventReader,
+    schedule::{ParallelSystemDescriptorCoercion, SystemLabel},
+    system::{Commands, IntoSystem, Local, Query, Res, ResMut},
+};
+use bevy_math::Vec3;
+use bevy_render::mesh::Mesh;
+use bevy_tasks::{AsyncComputeTaskPool, Task};
+use bevy_utils::{HashMap, HashSet};
+use futures_lite::future;
+use std::sync::Arc;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub enum NavMeshSystem {
+    Pathfinding,
+    DebugMesh,
+}
+
+/// Asks for a path over a [NavMesh]. The path is searched on the [AsyncComputeTaskPool] once the
+/// navigation mesh is loaded, and written to the [PathResult] of the entity a few frames later.
+/// The request is removed once the search started, and inserting a new one cancels the search.
+#[derive(Debug, Clone, Default)]
+pub struct PathRequest {
+    pub navmesh: Handle<NavMesh>,
+    pub start: Vec3,
+    pub end: Vec3,
+}
+
+/// The result of the last [PathRequest] of an entity
+#[derive(Debug, Clone, Default)]
+pub struct PathResult {
+    /// The points where the path turns, from the start to the end of the request, or `None` when
+    /// the end can't be reached
+    pub path: Option<Vec<Vec3>>,
+}
+
+struct PathSearch(Task<Option<Vec<Vec3>>>);
+
+/// Keeps the `Handle<Mesh>` of an entity set to the [debug mesh](NavMesh::debug_mesh) of its
+/// `Handle<NavMesh>`. Add it with a `Handle<NavMesh>` to an entity with a mesh bundle, such as a
+/// `PbrBundle` with an unlit material, to draw the edges of the navigation mesh.
+#[derive(Debug, Clone, Default)]
+pub struct NavMeshDebug {
+    built_from: Option<HandleId>,
+}
+
+/// Starts a search for each new [PathRequest], and writes the [PathResult] of finished searches
+pub fn pathfinding_system(
+    mut commands: Commands,
+    task_pool: Res<AsyncComputeTaskPool>,
+    navmeshes: Res<Assets<NavMesh>>,
+    mut navmesh_events: EventReader<AssetEvent<NavMesh>>,
+    // shared with the searches, so navigation meshes aren't cloned for each of them
+    mut shared_navmeshes: Local<HashMap<HandleId, Arc<NavMesh>>>,
+    requests: Query<(Entity, &PathRequest)>,
+    mut searches: Query<(Entity, &mut PathSearch)>,
+) {
+    for event in navmesh_events.iter() {
+        match event {
+            AssetEvent::Modified { handle } | AssetEvent::Removed { handle } => {
+                shared_navmeshes.remove(&handle.id);
+            }
+            AssetEvent::Created { .. } => {}
+        }
+    }
+
+    for (entity, mut search) in searches.iter_mut() {
+        if let Some(path) = future::block_on(future::poll_once(&mut search.0)) {
+            commands
+                .entity(entity)
+                .remove::<PathSearch>()
+                .insert(PathResult { path });
+        }
+    }
+
+    for (entity, request) in requests.iter() {
+        let navmesh = match shared_navmeshes.get(&request.navmesh.id) {
+            Some(navmesh) => navmesh.clone(),
+            None => match navmeshes.get(&request.navmesh) {
+                Some(navmesh) => {
+                    let navmesh = Arc::new(navmesh.clone());
+                    shared_navmeshes.insert(request.navmesh.id, navmesh.clone());
+                    navmesh
+                }
+                // wait for the navigation mesh to load
+                None => continue,
+            },
+        };
+        let (start, end) = (request.start, request.end);
+        let task = task_pool.spawn(async move { navmesh.find_path(start, end) });
+        // replacing a search drops its task, which cancels it
+        commands
+            .entity(entity)
+            .remove::<PathRequest>()
+            .insert(PathSearch(task));
+    }
+}
+
+/// Rebuilds the meshes of the [NavMeshDebug] entities when their navigation mesh changes
+pub fn navmesh_debug_system(
+    navmeshes: Res<Assets<NavMesh>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut navmesh_events: EventReader<AssetEvent<NavMesh>>,
+    mut debug_entities: Query<(&Handle<NavMesh>, &mut Handle<Mesh>, &mut NavMeshDebug)>,
+) {
+    let modified_navmeshes = navmesh_events
+        .iter()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { handle } => Some(handle.id),
+            AssetEvent::Created { .. } | AssetEvent::Removed { .. } => None,
+        })
+        .collect::<HashSet<_>>();
+
+    for (navmesh_handle, mut mesh, mut debug) in debug_entities.iter_mut() {
+        if debug.built_from == Some(navmesh_handle.id)
+            && !modified_navmeshes.contains(&navmesh_handle.id)
+        {
+            continue;
+        }
+        if let Some(navmesh) = navmeshes.get(navmesh_handle) {
+            *mesh = meshes.add(navmesh.debug_mesh());
+            debug.built_from = Some(navmesh_handle.id);
+        }
+    }
+}
+
+/// Adds the [NavMesh] asset, the [PathRequest] API and the [NavMeshDebug] visualization
+#[derive(Default)]
+pub struct NavMeshPlugin;
+
+impl Plugin for NavMeshPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_asset::<NavMesh>()
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                pathfinding_system
+                    .system()
+                    .label(NavMeshSystem::Pathfinding),
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                navmesh_debug_system
+                    .system()
+                    .label(NavMeshSystem::DebugMesh),
+            );
+    }
+}
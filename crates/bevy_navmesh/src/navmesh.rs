@@ -0,0 +1,415 @@
+use bevy_core::FloatOrd;
+use bevy_math::{Mat4, Vec3};
+use bevy_reflect::TypeUuid;
+use bevy_render::{
+    mesh::{Indices, Mesh, VertexAttributeValues},
+    pipeline::PrimitiveTopology,
+};
+use bevy_utils::HashMap;
+use thiserror::Error;
+
+/// How high above the navigation mesh its debug lines are drawn, to keep them visible over the
+/// level geometry
+const DEBUG_LINE_OFFSET: f32 = 0.02;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum NavMeshError {
+    #[error("polygon {polygon} has fewer than 3 vertices")]
+    DegeneratePolygon { polygon: usize },
+    #[error("polygon {polygon} uses vertex {vertex}, but there are only {count} vertices")]
+    InvalidVertex {
+        polygon: usize,
+        vertex: u32,
+        count: usize,
+    },
+    #[error("polygon {polygon} is not convex")]
+    NonConvexPolygon { polygon: usize },
+    #[error("meshes with a {0:?} topology can't be baked, only triangle lists can")]
+    UnsupportedTopology(PrimitiveTopology),
+    #[error("baked meshes need float3 vertex positions")]
+    MissingPositions,
+}
+
+/// A convex polygon of a [NavMesh]
+#[derive(Debug, Clone, PartialEq)]
+pub struct NavPolygon {
+    /// The indices of the vertices, counter-clockwise seen from above
+    pub vertices: Vec<u32>,
+    /// For each edge from `vertices[i]` to `vertices[i + 1]`, the polygon on its other side
+    pub neighbors: Vec<Option<u32>>,
+    /// The average of the vertices
+    pub center: Vec3,
+}
+
+impl NavPolygon {
+    /// The index of the edge shared with the polygon `neighbor`
+    pub fn edge_to(&self, neighbor: u32) -> Option<usize> {
+        self.neighbors
+            .iter()
+            .position(|edge_neighbor| *edge_neighbor == Some(neighbor))
+    }
+}
+
+/// Settings of [NavMesh::bake]
+#[derive(Debug, Clone)]
+pub struct NavMeshBakeSettings {
+    /// The steepest slope an agent can walk up, in radians
+    pub max_slope: f32,
+    /// Vertices closer than this are merged, so that separate meshes touching each other are
+    /// connected
+    pub weld_distance: f32,
+}
+
+impl Default for NavMeshBakeSettings {
+    fn default() -> Self {
+        NavMeshBakeSettings {
+            max_slope: std::f32::consts::FRAC_PI_4,
+            weld_distance: 0.01,
+        }
+    }
+}
+
+/// The walkable surfaces of a level, as convex polygons connected by their shared edges. Agents
+/// are moved along [NavMesh::find_path] from their center, so the polygons should already be
+/// shrunk by the radius of the agents away from walls.
+#[derive(Debug, Clone, Default, TypeUuid)]
+#[uuid = "d92965cd-a847-4735-8ce0-48fc4e413608"]
+pub struct NavMesh {
+    vertices: Vec<Vec3>,
+    polygons: Vec<NavPolygon>,
+}
+
+/// How much `b` turns counter-clockwise from `a` around `origin`, seen from above
+pub(crate) fn turn(origin: Vec3, a: Vec3, b: Vec3) -> f32 {
+    (a - origin).cross(b - origin).y
+}
+
+fn closest_point_on_segment(point: Vec3, start: Vec3, end: Vec3) -> Vec3 {
+    let segment = end - start;
+    let length_squared = segment.length_squared();
+    if length_squared <= f32::EPSILON {
+        return start;
+    }
+    let t = ((point - start).dot(segment) / length_squared)
+        .max(0.0)
+        .min(1.0);
+    start + segment * t
+}
+
+impl NavMesh {
+    /// Builds a navigation mesh from convex polygons indexing into `vertices`, for example ones
+    /// imported from a level editor. Polygons can be wound either way, and are connected to the
+    /// polygons they share an edge with.
+    pub fn from_polygons(
+        vertices: Vec<Vec3>,
+        polygons: Vec<Vec<u32>>,
+    ) -> Result<NavMesh, NavMeshError> {
+        let mut nav_polygons = Vec::with_capacity(polygons.len());
+        for (index, mut polygon) in polygons.into_iter().enumerate() {
+            if polygon.len() < 3 {
+                return Err(NavMeshError::DegeneratePolygon { polygon: index });
+            }
+            if let Some(vertex) = polygon
+                .iter()
+                .find(|vertex| **vertex as usize >= vertices.len())
+            {
+                return Err(NavMeshError::InvalidVertex {
+                    polygon: index,
+                    vertex: *vertex,
+                    count: vertices.len(),
+                });
+            }
+            let positions = polygon
+                .iter()
+                .map(|vertex| vertices[*vertex as usize])
+                .collect::<Vec<_>>();
+            let center = positions.iter().copied().sum::<Vec3>() / positions.len() as f32;
+            let area = (0..positions.len())
+                .map(|i| turn(center, positions[i], positions[(i + 1) % positions.len()]))
+                .sum::<f32>();
+            if area < 0.0 {
+                polygon.reverse();
+            }
+            let count = polygon.len();
+            let sign = area.signum();
+            let convex = (0..count).all(|i| {
+                let (a, b, c) = (
+                    positions[i],
+                    positions[(i + 1) % count],
+                    positions[(i + 2) % count],
+                );
+                sign * turn(a, b, c) >= -1e-5
+            });
+            if !convex {
+                return Err(NavMeshError::NonConvexPolygon { polygon: index });
+            }
+            nav_polygons.push(NavPolygon {
+                neighbors: vec![None; count],
+                vertices: polygon,
+                center,
+            });
+        }
+
+        // edges shared by exactly two polygons connect them
+        let mut edges = HashMap::<(u32, u32), Vec<(usize, usize)>>::default();
+        for (index, polygon) in nav_polygons.iter().enumerate() {
+            let count = polygon.vertices.len();
+            for edge in 0..count {
+                let (a, b) = (polygon.vertices[edge], polygon.vertices[(edge + 1) % count]);
+                edges
+                    .entry((a.min(b), a.max(b)))
+                    .or_default()
+                    .push((index, edge));
+            }
+        }
+        for sides in edges.values() {
+            if let [(polygon_a, edge_a), (polygon_b, edge_b)] = sides[..] {
+                if polygon_a != polygon_b {
+                    nav_polygons[polygon_a].neighbors[edge_a] = Some(polygon_b as u32);
+                    nav_polygons[polygon_b].neighbors[edge_b] = Some(polygon_a as u32);
+                }
+            }
+        }
+
+        Ok(NavMesh {
+            vertices,
+            polygons: nav_polygons,
+        })
+    }
+
+    /// Bakes a navigation mesh from the triangles of level meshes placed by their transform. The
+    /// triangles facing up and not steeper than `max_slope` become the polygons of the navigation
+    /// mesh. Triangles are wound counter-clockwise, like the front faces rendered by Bevy.
+    pub fn bake<'a>(
+        meshes: impl IntoIterator<Item = (&'a Mesh, Mat4)>,
+        settings: &NavMeshBakeSettings,
+    ) -> Result<NavMesh, NavMeshError> {
+        let min_up = settings.max_slope.cos();
+        let weld_distance = settings.weld_distance.max(f32::EPSILON);
+        let mut vertices = Vec::new();
+        let mut welded = HashMap::<(i32, i32, i32), u32>::default();
+        let mut polygons = Vec::new();
+
+        for (mesh, transform) in meshes {
+            if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+                return Err(NavMeshError::UnsupportedTopology(mesh.primitive_topology()));
+            }
+            let positions = match mesh.attribute(Mesh::ATTRIBUTE_POSITION) {
+                Some(VertexAttributeValues::Float3(positions)) => positions,
+                _ => return Err(NavMeshError::MissingPositions),
+            };
+            let indices: Vec<usize> = match mesh.indices() {
+                Some(Indices::U16(indices)) => indices.iter().map(|i| *i as usize).collect(),
+                Some(Indices::U32(indices)) => indices.iter().map(|i| *i as usize).collect(),
+                None => (0..positions.len()).collect(),
+            };
+
+            for triangle in indices.chunks_exact(3) {
+                let corners = [
+                    transform.transform_point3(positions[triangle[0]].into()),
+                    transform.transform_point3(positions[triangle[1]].into()),
+                    transform.transform_point3(positions[triangle[2]].into()),
+                ];
+                let normal = (corners[1] - corners[0]).cross(corners[2] - corners[0]);
+                if normal.length_squared() <= f32::EPSILON || normal.normalize().y < min_up - 1e-5 {
+                    continue;
+                }
+                let mut polygon = Vec::with_capacity(3);
+                for corner in corners.iter() {
+                    let cell = *corner / weld_distance;
+                    let key = (
+                        cell.x.round() as i32,
+                        cell.y.round() as i32,
+                        cell.z.round() as i32,
+                    );
+                    let vertex = *welded.entry(key).or_insert_with(|| {
+                        vertices.push(*corner);
+                        vertices.len() as u32 - 1
+                    });
+                    if !polygon.contains(&vertex) {
+                        polygon.push(vertex);
+                    }
+                }
+                if polygon.len() == 3 {
+                    polygons.push(polygon);
+                }
+            }
+        }
+        NavMesh::from_polygons(vertices, polygons)
+    }
+
+    pub fn vertices(&self) -> &[Vec3] {
+        &self.vertices
+    }
+
+    pub fn polygons(&self) -> &[NavPolygon] {
+        &self.polygons
+    }
+
+    pub(crate) fn vertex(&self, polygon: &NavPolygon, index: usize) -> Vec3 {
+        self.vertices[polygon.vertices[index % polygon.vertices.len()] as usize]
+    }
+
+    /// The height of the surface of `polygon` below or above `point`, when it is inside the
+    /// polygon seen from above
+    fn height_at(&self, polygon: &NavPolygon, point: Vec3) -> Option<f32> {
+        let count = polygon.vertices.len();
+        let inside = (0..count).all(|edge| {
+            turn(
+                self.vertex(polygon, edge),
+                self.vertex(polygon, edge + 1),
+                point,
+            ) >= -1e-5
+        });
+        if !inside {
+            return None;
+        }
+        // interpolate over the triangle fan of the polygon
+        let origin = self.vertex(polygon, 0);
+        for i in 1..count - 1 {
+            let (b, c) = (self.vertex(polygon, i), self.vertex(polygon, i + 1));
+            let area = turn(origin, b, c);
+            if area.abs() <= f32::EPSILON {
+                continue;
+            }
+            let u = turn(point, b, c) / area;
+            let v = turn(origin, point, c) / area;
+            let w = 1.0 - u - v;
+            if u >= -1e-5 && v >= -1e-5 && w >= -1e-5 {
+                return Some(origin.y * u + b.y * v + c.y * w);
+            }
+        }
+        Some(polygon.center.y)
+    }
+
+    /// Finds the polygon under or above `point`, vertically closest to it
+    pub fn find_polygon(&self, point: Vec3) -> Option<usize> {
+        self.polygons
+            .iter()
+            .enumerate()
+            .filter_map(|(index, polygon)| {
+                self.height_at(polygon, point)
+                    .map(|height| (index, FloatOrd((height - point.y).abs())))
+            })
+            .min_by_key(|(_, distance)| *distance)
+            .map(|(index, _)| index)
+    }
+
+    /// Finds the point of the navigation mesh closest to `point`, and the polygon it is on.
+    /// Points above or below a polygon are projected on its surface.
+    pub fn closest_point(&self, point: Vec3) -> Option<(usize, Vec3)> {
+        if let Some(index) = self.find_polygon(point) {
+            let height = self.height_at(&self.polygons[index], point).unwrap();
+            return Some((index, Vec3::new(point.x, height, point.z)));
+        }
+        let mut closest: Option<(usize, Vec3, f32)> = None;
+        for (index, polygon) in self.polygons.iter().enumerate() {
+            for edge in 0..polygon.vertices.len() {
+                let on_edge = closest_point_on_segment(
+                    point,
+                    self.vertex(polygon, edge),
+                    self.vertex(polygon, edge + 1),
+                );
+                let distance = on_edge.distance_squared(point);
+                if closest.map_or(true, |(_, _, closest)| distance < closest) {
+                    closest = Some((index, on_edge, distance));
+                }
+            }
+        }
+        closest.map(|(index, point, _)| (index, point))
+    }
+
+    /// Builds a line list mesh of the edges of the polygons, to visualize the navigation mesh
+    /// with the [NavMeshDebug](crate::NavMeshDebug) component
+    pub fn debug_mesh(&self) -> Mesh {
+        let mut positions = Vec::new();
+        for (index, polygon) in self.polygons.iter().enumerate() {
+            for (edge, neighbor) in polygon.neighbors.iter().enumerate() {
+                // shared edges are only drawn once
+                if neighbor.map_or(false, |neighbor| (neighbor as usize) < index) {
+                    continue;
+                }
+                for vertex in [edge, edge + 1].iter() {
+                    let position = self.vertex(polygon, *vertex) + Vec3::Y * DEBUG_LINE_OFFSET;
+                    positions.push([position.x, position.y, position.z]);
+                }
+            }
+        }
+        let normals = vec![[0.0, 1.0, 0.0]; positions.len()];
+        let uvs = vec![[0.0, 0.0]; positions.len()];
+        let mut mesh = Mesh::new(PrimitiveTopology::LineList);
+        mesh.set_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.set_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+        mesh.set_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+        mesh
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_render::mesh::shape;
+
+    #[test]
+    fn connects_polygons_sharing_edges() {
+        let vertices = vec![
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 1.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(2.0, 0.0, 1.0),
+        ];
+        let navmesh =
+            NavMesh::from_polygons(vertices.clone(), vec![vec![0, 1, 2, 3], vec![1, 4, 5, 2]])
+                .unwrap();
+        let polygons = navmesh.polygons();
+        assert!(polygons[0].edge_to(1).is_some());
+        assert!(polygons[1].edge_to(0).is_some());
+        assert_eq!(polygons[0].neighbors.iter().flatten().count(), 1);
+        assert_eq!(navmesh.find_polygon(Vec3::new(1.5, 1.0, 0.5)), Some(1));
+        assert_eq!(navmesh.find_polygon(Vec3::new(3.0, 0.0, 0.5)), None);
+        let (polygon, point) = navmesh.closest_point(Vec3::new(3.0, 0.0, 0.5)).unwrap();
+        assert_eq!(polygon, 1);
+        assert!(point.abs_diff_eq(Vec3::new(2.0, 0.0, 0.5), 1e-5));
+
+        assert_eq!(
+            NavMesh::from_polygons(vertices.clone(), vec![vec![0, 1, 6]]),
+            Err(NavMeshError::InvalidVertex {
+                polygon: 0,
+                vertex: 6,
+                count: 6,
+            })
+        );
+        assert_eq!(
+            NavMesh::from_polygons(vertices, vec![vec![0, 2, 1, 3]]),
+            Err(NavMeshError::NonConvexPolygon { polygon: 0 })
+        );
+    }
+
+    #[test]
+    fn bakes_walkable_triangles() {
+        let floor = Mesh::from(shape::Plane { size: 2.0 });
+        let cube = Mesh::from(shape::Cube { size: 1.0 });
+        let navmesh = NavMesh::bake(
+            vec![
+                (&floor, Mat4::IDENTITY),
+                (&floor, Mat4::from_translation(Vec3::new(2.0, 0.0, 0.0))),
+                (&cube, Mat4::from_translation(Vec3::new(0.0, 3.0, 0.0))),
+            ],
+            &NavMeshBakeSettings::default(),
+        )
+        .unwrap();
+        // the two planes and the top of the cube, without its sides and bottom
+        assert_eq!(navmesh.polygons().len(), 6);
+        assert!(navmesh
+            .polygons()
+            .iter()
+            .all(|polygon| polygon.center.y == 0.0 || polygon.center.y == 3.5));
+        // the planes are welded together
+        assert_eq!(navmesh.vertices().len(), 6 + 4);
+        let left = navmesh.find_polygon(Vec3::new(0.5, 0.0, 0.0)).unwrap();
+        let right = navmesh.find_polygon(Vec3::new(1.5, 0.0, 0.0)).unwrap();
+        assert!(navmesh.polygons()[left].edge_to(right as u32).is_some());
+    }
+}
@@ -0,0 +1,213 @@
+use crate::{navmesh::turn, NavMesh};
+use bevy_core::FloatOrd;
+use bevy_math::Vec3;
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+impl NavMesh {
+    /// Finds the shortest path over the navigation mesh from `start` to `end`, as the points
+    /// where the path turns, starting with `start` and ending with `end`. Both points are first
+    /// moved to the closest point of the navigation mesh. Returns `None` when `end` can't be
+    /// reached from `start`.
+    pub fn find_path(&self, start: Vec3, end: Vec3) -> Option<Vec<Vec3>> {
+        let (start_polygon, start) = self.closest_point(start)?;
+        let (end_polygon, end) = self.closest_point(end)?;
+        let polygons = self.find_polygon_path(start_polygon, start, end_polygon, end)?;
+        Some(pull_string(&self.portals(&polygons, start, end)))
+    }
+
+    /// Finds the polygons crossed by the shortest path between the polygon centers with A*
+    fn find_polygon_path(
+        &self,
+        start_polygon: usize,
+        start: Vec3,
+        end_polygon: usize,
+        end: Vec3,
+    ) -> Option<Vec<usize>> {
+        let position = |polygon: usize| {
+            if polygon == start_polygon {
+                start
+            } else if polygon == end_polygon {
+                end
+            } else {
+                self.polygons()[polygon].center
+            }
+        };
+        let mut costs = vec![f32::INFINITY; self.polygons().len()];
+        let mut previous = vec![None; self.polygons().len()];
+        let mut open = BinaryHeap::new();
+        costs[start_polygon] = 0.0;
+        open.push((Reverse(FloatOrd(start.distance(end))), start_polygon));
+
+        while let Some((Reverse(FloatOrd(estimate)), polygon)) = open.pop() {
+            if polygon == end_polygon {
+                let mut path = vec![end_polygon];
+                while let Some(polygon) = previous[*path.last().unwrap()] {
+                    path.push(polygon);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            // skip the entries of polygons reached again with a lower cost since
+            if estimate > costs[polygon] + position(polygon).distance(end) + 1e-4 {
+                continue;
+            }
+            for neighbor in self.polygons()[polygon].neighbors.iter().flatten() {
+                let neighbor = *neighbor as usize;
+                let cost = costs[polygon] + position(polygon).distance(position(neighbor));
+                if cost < costs[neighbor] {
+                    costs[neighbor] = cost;
+                    previous[neighbor] = Some(polygon);
+                    let estimate = cost + position(neighbor).distance(end);
+                    open.push((Reverse(FloatOrd(estimate)), neighbor));
+                }
+            }
+        }
+        None
+    }
+
+    /// The edges crossed going through `polygons`, as their left and right ends seen from the
+    /// direction of the path, between a first portal at `start` and a last one at `end`
+    fn portals(&self, polygons: &[usize], start: Vec3, end: Vec3) -> Vec<(Vec3, Vec3)> {
+        let mut portals = vec![(start, start)];
+        for pair in polygons.windows(2) {
+            let polygon = &self.polygons()[pair[0]];
+            let edge = polygon.edge_to(pair[1] as u32).unwrap();
+            // polygons are counter-clockwise, so the end of an edge is on the left when leaving
+            // the polygon through it
+            portals.push((self.vertex(polygon, edge + 1), self.vertex(polygon, edge)));
+        }
+        portals.push((end, end));
+        portals
+    }
+}
+
+/// Pulls the path taut through the portals with the simple stupid funnel algorithm, keeping the
+/// points where it turns around a portal end
+fn pull_string(portals: &[(Vec3, Vec3)]) -> Vec<Vec3> {
+    let (start, _) = portals[0];
+    let (end, _) = portals[portals.len() - 1];
+    let mut path = vec![start];
+    let (mut apex, mut left, mut right) = (start, start, start);
+    let (mut apex_index, mut left_index, mut right_index) = (0, 0, 0);
+
+    let mut i = 1;
+    while i < portals.len() {
+        let (portal_left, portal_right) = portals[i];
+
+        // narrow the funnel from the right
+        if turn(apex, right, portal_right) >= 0.0 {
+            if apex == right || turn(apex, left, portal_right) < 0.0 {
+                right = portal_right;
+                right_index = i;
+            } else {
+                // the right side crossed the left one, so the path turns around the left end
+                apex = left;
+                apex_index = left_index;
+                if path.last() != Some(&apex) {
+                    path.push(apex);
+                }
+                right = apex;
+                right_index = apex_index;
+                i = apex_index + 1;
+                continue;
+            }
+        }
+
+        // narrow the funnel from the left
+        if turn(apex, left, portal_left) <= 0.0 {
+            if apex == left || turn(apex, right, portal_left) > 0.0 {
+                left = portal_left;
+                left_index = i;
+            } else {
+                apex = right;
+                apex_index = right_index;
+                if path.last() != Some(&apex) {
+                    path.push(apex);
+                }
+                left = apex;
+                left_index = apex_index;
+                right = apex;
+                right_index = apex_index;
+                i = apex_index + 1;
+                continue;
+            }
+        }
+        i += 1;
+    }
+
+    if path.last() != Some(&end) {
+        path.push(end);
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Three squares in an L shape: one at the origin, one on its right and one above that one
+    fn corner() -> NavMesh {
+        let vertices = vec![
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 1.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(2.0, 0.0, 1.0),
+            Vec3::new(2.0, 0.0, 2.0),
+            Vec3::new(1.0, 0.0, 2.0),
+        ];
+        let polygons = vec![vec![0, 1, 2, 3], vec![1, 4, 5, 2], vec![2, 5, 6, 7]];
+        NavMesh::from_polygons(vertices, polygons).unwrap()
+    }
+
+    #[test]
+    fn finds_straight_paths() {
+        let navmesh = corner();
+        let path = navmesh
+            .find_path(Vec3::new(0.2, 0.0, 0.5), Vec3::new(1.8, 0.0, 0.5))
+            .unwrap();
+        assert_eq!(
+            path,
+            vec![Vec3::new(0.2, 0.0, 0.5), Vec3::new(1.8, 0.0, 0.5)]
+        );
+
+        // points off the navigation mesh are moved onto it
+        let path = navmesh
+            .find_path(Vec3::new(1.5, 1.0, 0.5), Vec3::new(1.5, 0.0, 3.0))
+            .unwrap();
+        assert_eq!(
+            path,
+            vec![Vec3::new(1.5, 0.0, 0.5), Vec3::new(1.5, 0.0, 2.0)]
+        );
+    }
+
+    #[test]
+    fn turns_around_corners() {
+        let navmesh = corner();
+        let start = Vec3::new(0.5, 0.0, 0.5);
+        let end = Vec3::new(1.2, 0.0, 1.8);
+        let path = navmesh.find_path(start, end).unwrap();
+        assert_eq!(path, vec![start, Vec3::new(1.0, 0.0, 1.0), end]);
+
+        // the same path the other way
+        let path = navmesh.find_path(end, start).unwrap();
+        assert_eq!(path, vec![end, Vec3::new(1.0, 0.0, 1.0), start]);
+    }
+
+    #[test]
+    fn fails_to_reach_disconnected_polygons() {
+        let vertices = vec![
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(3.0, 0.0, 0.0),
+            Vec3::new(4.0, 0.0, 0.0),
+            Vec3::new(3.0, 0.0, 1.0),
+        ];
+        let navmesh = NavMesh::from_polygons(vertices, vec![vec![0, 1, 2], vec![3, 4, 5]]).unwrap();
+        assert!(navmesh
+            .find_path(Vec3::new(0.2, 0.0, 0.2), Vec3::new(3.2, 0.0, 0.2))
+            .is_none());
+    }
+}
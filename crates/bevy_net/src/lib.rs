@@ -0,0 +1,69 @@
+mod network;
+mod replication;
+mod transport;
+
+pub use network::*;
+pub use replication::*;
+pub use transport::*;
+
+pub mod prelude {
+    pub use crate::{
+        AddReplicatedComponent, ConnectionId, Interest, Network, NetworkEvent, NetworkObserver,
+        NetworkPlugin, Replicated, ReplicatedEntities, ReplicationSettings,
+    };
+}
+
+use bevy_app::prelude::*;
+use bevy_ecs::{
+    schedule::{ExclusiveSystemDescriptorCoercion, ParallelSystemDescriptorCoercion, SystemLabel},
+    system::{IntoExclusiveSystem, IntoSystem},
+};
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub enum NetworkSystem {
+    Receive,
+    ClientReplication,
+    ServerReplication,
+}
+
+/// Sends and receives messages through the [Network] resource once it is inserted, and
+/// replicates the [Replicated] entities of servers to their clients.
+///
+/// Clients spawn their own entities for the replicated ones, listed in [ReplicatedEntities],
+/// and update them from the snapshots received at the end of [CoreStage::PreUpdate].
+/// Servers send snapshots at the end of [CoreStage::PostUpdate]: the entities changed since the
+/// last snapshot each client acknowledged, split into messages of
+/// [ReplicationSettings::chunk_size] bytes.
+#[derive(Default)]
+pub struct NetworkPlugin;
+
+impl Plugin for NetworkPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_event::<NetworkEvent>()
+            .init_resource::<ReplicationSettings>()
+            .init_resource::<ReplicationRegistry>()
+            .init_resource::<ReplicatedEntities>()
+            .init_resource::<IncomingSnapshots>()
+            .init_resource::<IncomingAcks>()
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                network_receive_system
+                    .system()
+                    .label(NetworkSystem::Receive),
+            )
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                client_replication_system
+                    .exclusive_system()
+                    .at_end()
+                    .label(NetworkSystem::ClientReplication),
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                server_replication_system
+                    .exclusive_system()
+                    .at_end()
+                    .label(NetworkSystem::ServerReplication),
+            );
+    }
+}
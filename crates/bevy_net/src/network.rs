@@ -0,0 +1,208 @@
+use crate::{
+    ConnectionId, IncomingAcks, IncomingSnapshots, NetworkError, Transport, TransportEvent,
+    UdpTransport, WebSocketTransport,
+};
+use bevy_app::EventWriter;
+use bevy_ecs::system::ResMut;
+use bevy_utils::tracing::warn;
+use std::{fmt, net::ToSocketAddrs};
+
+/// The first byte of each message, telling which part of the engine it is for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Channel {
+    Message = 0,
+    Replication = 1,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetworkRole {
+    /// Accepts connections from clients, and replicates components to them
+    Server,
+    /// Connects to a server, and receives the components it replicates
+    Client,
+}
+
+/// Sent when a connection opens or closes, and when a message is received
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NetworkEvent {
+    Connected(ConnectionId),
+    Disconnected(ConnectionId),
+    Message(ConnectionId, Vec<u8>),
+}
+
+/// The connections of the app to a server or to its clients. Insert it as a resource to start
+/// networking, and remove it to close every connection.
+pub struct Network {
+    transport: Box<dyn Transport>,
+    role: NetworkRole,
+    connections: Vec<ConnectionId>,
+}
+
+impl fmt::Debug for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Network")
+            .field("role", &self.role)
+            .field("connections", &self.connections)
+            .finish()
+    }
+}
+
+impl Network {
+    pub fn new(transport: impl Transport, role: NetworkRole) -> Self {
+        Network {
+            transport: Box::new(transport),
+            role,
+            connections: Vec::new(),
+        }
+    }
+
+    /// Starts a server accepting UDP connections on `address`
+    pub fn listen_udp(address: impl ToSocketAddrs) -> Result<Self, NetworkError> {
+        Ok(Self::new(
+            UdpTransport::listen(address)?,
+            NetworkRole::Server,
+        ))
+    }
+
+    /// Connects to a server accepting UDP connections at `address`
+    pub fn connect_udp(address: impl ToSocketAddrs) -> Result<Self, NetworkError> {
+        Ok(Self::new(
+            UdpTransport::connect(address)?,
+            NetworkRole::Client,
+        ))
+    }
+
+    /// Starts a server accepting websocket connections on `address`
+    pub fn listen_websocket(address: impl ToSocketAddrs) -> Result<Self, NetworkError> {
+        Ok(Self::new(
+            WebSocketTransport::listen(address)?,
+            NetworkRole::Server,
+        ))
+    }
+
+    /// Connects to a websocket server at a `ws://` url
+    pub fn connect_websocket(url: &str) -> Result<Self, NetworkError> {
+        Ok(Self::new(
+            WebSocketTransport::connect(url)?,
+            NetworkRole::Client,
+        ))
+    }
+
+    pub fn role(&self) -> NetworkRole {
+        self.role
+    }
+
+    pub fn is_server(&self) -> bool {
+        self.role == NetworkRole::Server
+    }
+
+    /// The open connections, to the clients of a server or to the server of a client
+    pub fn connections(&self) -> &[ConnectionId] {
+        &self.connections
+    }
+
+    /// Sends a message, received as a [NetworkEvent::Message] on the other side
+    pub fn send(&mut self, connection: ConnectionId, message: &[u8]) -> Result<(), NetworkError> {
+        self.send_on(Channel::Message, connection, message)
+    }
+
+    /// Sends a message to every open connection
+    pub fn broadcast(&mut self, message: &[u8]) -> Result<(), NetworkError> {
+        for connection in self.connections.clone() {
+            self.send(connection, message)?;
+        }
+        Ok(())
+    }
+
+    /// Closes a connection. Its [NetworkEvent::Disconnected] is sent at the next update.
+    pub fn disconnect(&mut self, connection: ConnectionId) {
+        self.transport.disconnect(connection);
+    }
+
+    pub(crate) fn send_on(
+        &mut self,
+        channel: Channel,
+        connection: ConnectionId,
+        message: &[u8],
+    ) -> Result<(), NetworkError> {
+        let mut data = Vec::with_capacity(message.len() + 1);
+        data.push(channel as u8);
+        data.extend_from_slice(message);
+        self.transport.send(connection, &data)
+    }
+
+    /// Polls the transport, and sorts the received messages by channel
+    pub(crate) fn receive(&mut self) -> Result<Vec<(Channel, TransportEvent)>, NetworkError> {
+        let mut events = Vec::new();
+        self.transport.poll(&mut events)?;
+        let mut received = Vec::with_capacity(events.len());
+        for event in events {
+            match event {
+                TransportEvent::Connected(connection) => {
+                    self.connections.push(connection);
+                    received.push((Channel::Message, TransportEvent::Connected(connection)));
+                }
+                TransportEvent::Disconnected(connection) => {
+                    self.connections.retain(|open| *open != connection);
+                    received.push((Channel::Message, TransportEvent::Disconnected(connection)));
+                }
+                TransportEvent::Message(connection, mut data) => {
+                    let channel = match data.first() {
+                        Some(0) => Channel::Message,
+                        Some(1) => Channel::Replication,
+                        _ => continue,
+                    };
+                    data.remove(0);
+                    received.push((channel, TransportEvent::Message(connection, data)));
+                }
+            }
+        }
+        Ok(received)
+    }
+}
+
+/// Polls the [Network] and sends the [NetworkEvent]s. Replication messages are kept for the
+/// [client_replication_system](crate::client_replication_system), and their acknowledgements
+/// for the [server_replication_system](crate::server_replication_system).
+pub fn network_receive_system(
+    network: Option<ResMut<Network>>,
+    mut incoming: ResMut<IncomingSnapshots>,
+    mut acks: ResMut<IncomingAcks>,
+    mut network_events: EventWriter<NetworkEvent>,
+) {
+    let mut network = match network {
+        Some(network) => network,
+        None => return,
+    };
+    let received = match network.receive() {
+        Ok(received) => received,
+        Err(err) => {
+            warn!("failed to receive network messages: {}", err);
+            return;
+        }
+    };
+    for (channel, event) in received {
+        match (channel, event) {
+            (Channel::Replication, TransportEvent::Message(connection, data)) => {
+                // only servers replicate entities, and only clients acknowledge them
+                if network.is_server() {
+                    acks.push(connection, &data);
+                } else {
+                    incoming.push(data);
+                }
+            }
+            (_, TransportEvent::Connected(connection)) => {
+                network_events.send(NetworkEvent::Connected(connection))
+            }
+            (_, TransportEvent::Disconnected(connection)) => {
+                if !network.is_server() {
+                    incoming.reset();
+                }
+                network_events.send(NetworkEvent::Disconnected(connection))
+            }
+            (_, TransportEvent::Message(connection, data)) => {
+                network_events.send(NetworkEvent::Message(connection, data))
+            }
+        }
+    }
+}
@@ -0,0 +1,901 @@
+use crate::{network::Channel, ConnectionId, Network, MAX_UDP_MESSAGE_SIZE};
+use bevy_app::AppBuilder;
+use bevy_core::Time;
+use bevy_ecs::{
+    component::Component,
+    entity::{Entity, EntityMap},
+    query::With,
+    reflect::ReflectComponent,
+    world::{Mut, World},
+};
+use bevy_math::Vec3;
+use bevy_reflect::{GetTypeRegistration, TypeRegistry, TypeRegistryArc};
+use bevy_scene::{
+    serde::{SceneDeserializer, SceneSerializer},
+    DynamicScene, Entity as SceneEntity, SceneSpawnError,
+};
+use bevy_transform::components::GlobalTransform;
+use bevy_utils::{tracing::warn, HashMap, HashSet};
+use serde::de::DeserializeSeed;
+use std::{
+    any::TypeId,
+    collections::{hash_map::DefaultHasher, VecDeque},
+    convert::{TryFrom, TryInto},
+    hash::{Hash, Hasher},
+};
+
+/// The largest message a snapshot is split into by default. A lost fragment of a UDP message
+/// loses all of it, so chunks fit in the single packet most networks can carry.
+pub const DEFAULT_SNAPSHOT_CHUNK_SIZE: usize = 1200;
+
+/// Snapshots are sent in full again to clients that didn't acknowledge this many
+const MAX_UNACKED_SNAPSHOTS: usize = 64;
+
+/// The sequence, the baseline, the index and count of the chunk, and the counts of removed and
+/// updated entities
+const CHUNK_HEADER_SIZE: usize = 8 + 8 + 2 + 2 + 4 + 4;
+
+/// Marks an entity of a server to replicate to its clients, with its components registered
+/// with [AddReplicatedComponent::add_replicated_component]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Replicated;
+
+/// Which [Replicated] entities are sent to each client
+#[derive(Debug, Clone, PartialEq)]
+pub enum Interest {
+    Everything,
+    /// Entities are sent to the clients with a [NetworkObserver] closer than this distance.
+    /// Entities without a [GlobalTransform] are sent to every client.
+    Radius(f32),
+}
+
+/// The entity a client observes the world from, on the server, for [Interest::Radius]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NetworkObserver {
+    pub connection: ConnectionId,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReplicationSettings {
+    /// The time between two snapshots of the replicated entities sent to the clients, in seconds
+    pub interval: f64,
+    pub interest: Interest,
+    /// The largest message a snapshot is split into, at most [MAX_UDP_MESSAGE_SIZE] minus the
+    /// byte of the replication channel
+    pub chunk_size: usize,
+}
+
+impl Default for ReplicationSettings {
+    fn default() -> Self {
+        ReplicationSettings {
+            interval: 1.0 / 20.0,
+            interest: Interest::Everything,
+            chunk_size: DEFAULT_SNAPSHOT_CHUNK_SIZE,
+        }
+    }
+}
+
+/// The components replicated from servers to clients
+#[derive(Debug, Default)]
+pub struct ReplicationRegistry {
+    components: Vec<TypeId>,
+}
+
+impl ReplicationRegistry {
+    pub fn contains(&self, type_id: TypeId) -> bool {
+        self.components.contains(&type_id)
+    }
+}
+
+pub trait AddReplicatedComponent {
+    /// Replicates a component of the [Replicated] entities from servers to clients. The component
+    /// has to reflect `Component`, and be registered the same way on both sides.
+    fn add_replicated_component<T>(&mut self) -> &mut Self
+    where
+        T: Component + GetTypeRegistration;
+}
+
+impl AddReplicatedComponent for AppBuilder {
+    fn add_replicated_component<T>(&mut self) -> &mut Self
+    where
+        T: Component + GetTypeRegistration,
+    {
+        self.register_type::<T>();
+        let mut registry = self
+            .world_mut()
+            .get_resource_or_insert_with(ReplicationRegistry::default);
+        if !registry.contains(TypeId::of::<T>()) {
+            registry.components.push(TypeId::of::<T>());
+        }
+        self
+    }
+}
+
+/// The entities a client spawned for the [Replicated] entities of the server
+#[derive(Debug, Default)]
+pub struct ReplicatedEntities {
+    map: EntityMap,
+}
+
+impl ReplicatedEntities {
+    /// The client entity replicating an entity of the server
+    pub fn client_entity(&self, server_entity: Entity) -> Option<Entity> {
+        self.map.get(server_entity).ok()
+    }
+
+    /// Every replicated entity, as the server entity and the client entity replicating it
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, Entity)> + '_ {
+        self.map.keys().filter_map(move |server_entity| {
+            Some((server_entity, self.map.get(server_entity).ok()?))
+        })
+    }
+
+    fn despawn_all(&mut self, world: &mut World) {
+        for (_, client_entity) in self.iter().collect::<Vec<_>>() {
+            world.despawn(client_entity);
+        }
+        self.map = EntityMap::default();
+    }
+
+    /// Despawns the replicated entities missing from `server_entities`
+    fn despawn_missing(&mut self, world: &mut World, server_entities: &HashSet<u32>) {
+        for (server_entity, client_entity) in self.iter().collect::<Vec<_>>() {
+            if !server_entities.contains(&server_entity.id()) {
+                world.despawn(client_entity);
+                self.map.remove(server_entity);
+            }
+        }
+    }
+}
+
+/// The chunks applied of a snapshot that the client hasn't received entirely yet
+#[derive(Debug, Default)]
+struct PartialSnapshot {
+    chunks: HashSet<u16>,
+    /// The entities of a snapshot without a baseline. The replicated entities missing from it are
+    /// despawned once it is complete.
+    entities: HashSet<u32>,
+}
+
+/// The replication snapshots received by a client, waiting to be applied
+#[derive(Debug, Default)]
+pub struct IncomingSnapshots {
+    received: Vec<Vec<u8>>,
+    /// The newest snapshot applied, entirely or in part. Chunks of older snapshots are dropped.
+    newest: Option<u64>,
+    partial: HashMap<u64, PartialSnapshot>,
+    reset: bool,
+}
+
+impl IncomingSnapshots {
+    pub(crate) fn push(&mut self, message: Vec<u8>) {
+        self.received.push(message);
+    }
+
+    /// Drops the replicated entities, when the client disconnects
+    pub(crate) fn reset(&mut self) {
+        *self = IncomingSnapshots {
+            reset: true,
+            ..Default::default()
+        };
+    }
+}
+
+/// The snapshots acknowledged by the clients of a server, waiting for its next snapshot
+#[derive(Debug, Default)]
+pub struct IncomingAcks {
+    acks: Vec<(ConnectionId, u64)>,
+}
+
+impl IncomingAcks {
+    pub(crate) fn push(&mut self, connection: ConnectionId, message: &[u8]) {
+        if let Ok(sequence) = <[u8; 8]>::try_from(message) {
+            self.acks.push((connection, u64::from_le_bytes(sequence)));
+        }
+    }
+}
+
+/// The replicated state sent to a client in a snapshot, as a hash of the serialized components
+/// of each entity
+type SentState = HashMap<u32, u64>;
+
+/// What a client received of the replicated entities
+#[derive(Debug, Default)]
+struct ClientReplication {
+    /// The last snapshot the client received entirely
+    acked: Option<u64>,
+    /// The snapshots sent since the acknowledged one, oldest first, starting with it
+    sent: VecDeque<(u64, SentState)>,
+}
+
+impl ClientReplication {
+    fn acknowledge(&mut self, sequence: u64) {
+        if self.acked.is_some_and(|acked| sequence <= acked)
+            || !self.sent.iter().any(|(sent, _)| *sent == sequence)
+        {
+            return;
+        }
+        self.acked = Some(sequence);
+        while self.sent.front().is_some_and(|(sent, _)| *sent < sequence) {
+            self.sent.pop_front();
+        }
+    }
+
+    /// Encodes the snapshot `sequence` of `entities` for this client, as messages of at most
+    /// `chunk_size` bytes. Once the client acknowledged a snapshot, only the entities changed
+    /// since then are sent, along with the entities it may have that are no longer relevant.
+    fn snapshot(
+        &mut self,
+        sequence: u64,
+        entities: &[&SerializedEntity],
+        chunk_size: usize,
+    ) -> Vec<Vec<u8>> {
+        if self.sent.len() >= MAX_UNACKED_SNAPSHOTS {
+            // the client stopped acknowledging, so it gets every entity again
+            *self = ClientReplication::default();
+        }
+        let state = entities
+            .iter()
+            .map(|entity| (entity.id, entity.hash))
+            .collect::<SentState>();
+        let baseline = self.acked.and_then(|acked| {
+            self.sent
+                .iter()
+                .find(|(sent, _)| *sent == acked)
+                .map(|(_, state)| state)
+        });
+        let (changed, removed) = match baseline {
+            Some(baseline) => {
+                let changed = entities
+                    .iter()
+                    .filter(|entity| baseline.get(&entity.id) != Some(&entity.hash))
+                    .map(|entity| entity.data.as_slice())
+                    .collect::<Vec<_>>();
+                let mut removed = self
+                    .sent
+                    .iter()
+                    .flat_map(|(_, sent)| sent.keys())
+                    .filter(|id| !state.contains_key(id))
+                    .copied()
+                    .collect::<Vec<_>>();
+                removed.sort_unstable();
+                removed.dedup();
+                (changed, removed)
+            }
+            None => (
+                entities
+                    .iter()
+                    .map(|entity| entity.data.as_slice())
+                    .collect(),
+                Vec::new(),
+            ),
+        };
+        let chunks = encode_chunks(sequence, self.acked, &removed, &changed, chunk_size);
+        self.sent.push_back((sequence, state));
+        chunks
+    }
+}
+
+/// Sequences the snapshots of a server, and tracks what each client received
+#[derive(Debug, Default)]
+struct ServerReplication {
+    last_sent: Option<f64>,
+    sequence: u64,
+    clients: HashMap<ConnectionId, ClientReplication>,
+}
+
+/// A [Replicated] entity, with its replicated components serialized as a scene of this entity
+struct SerializedEntity {
+    id: u32,
+    position: Option<Vec3>,
+    data: Vec<u8>,
+    hash: u64,
+}
+
+fn serialize_entities(
+    world: &mut World,
+    registry: &TypeRegistryArc,
+    components: &[TypeId],
+) -> Vec<SerializedEntity> {
+    let entities = world
+        .query_filtered::<(Entity, Option<&GlobalTransform>), With<Replicated>>()
+        .iter(world)
+        .map(|(entity, transform)| (entity, transform.map(|transform| transform.translation)))
+        .collect::<Vec<_>>();
+    let scenes = {
+        let type_registry = registry.read();
+        let reflect_components = components
+            .iter()
+            .filter_map(|type_id| type_registry.get(*type_id))
+            .filter_map(|registration| registration.data::<ReflectComponent>())
+            .collect::<Vec<_>>();
+        entities
+            .into_iter()
+            .map(|(entity, position)| {
+                let components = reflect_components
+                    .iter()
+                    .filter_map(|reflect| reflect.reflect_component(world, entity))
+                    .map(|component| component.clone_value())
+                    .collect();
+                let scene = DynamicScene {
+                    entities: vec![SceneEntity {
+                        entity: entity.id(),
+                        components,
+                    }],
+                };
+                (entity, position, scene)
+            })
+            .collect::<Vec<_>>()
+    };
+
+    scenes
+        .into_iter()
+        .filter_map(|(entity, position, scene)| {
+            match ron::ser::to_string(&SceneSerializer::new(&scene, registry)) {
+                Ok(ron) => {
+                    let mut hasher = DefaultHasher::new();
+                    ron.hash(&mut hasher);
+                    Some(SerializedEntity {
+                        id: entity.id(),
+                        position,
+                        data: ron.into_bytes(),
+                        hash: hasher.finish(),
+                    })
+                }
+                Err(err) => {
+                    warn!(
+                        "failed to serialize replicated entity {:?}: {}",
+                        entity, err
+                    );
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// The entities relevant to each connection, with the [Interest] of the [ReplicationSettings]
+fn relevant_entities<'a>(
+    world: &mut World,
+    entities: &'a [SerializedEntity],
+    interest: &Interest,
+    connections: &[ConnectionId],
+) -> Vec<(ConnectionId, Vec<&'a SerializedEntity>)> {
+    let observers = world
+        .query::<(&NetworkObserver, &GlobalTransform)>()
+        .iter(world)
+        .map(|(observer, transform)| (observer.connection, transform.translation))
+        .collect::<HashMap<_, _>>();
+    let is_relevant = |connection: ConnectionId, position: Option<Vec3>| match interest {
+        Interest::Everything => true,
+        Interest::Radius(radius) => match (position, observers.get(&connection)) {
+            (None, _) => true,
+            (Some(position), Some(observer)) => position.distance(*observer) <= *radius,
+            (Some(_), None) => false,
+        },
+    };
+    connections
+        .iter()
+        .map(|connection| {
+            let entities = entities
+                .iter()
+                .filter(|entity| is_relevant(*connection, entity.position))
+                .collect();
+            (*connection, entities)
+        })
+        .collect()
+}
+
+/// Splits a snapshot into messages of at most `chunk_size` bytes, each applied on its own by the
+/// client. Entities too large for a message are left out.
+fn encode_chunks(
+    sequence: u64,
+    baseline: Option<u64>,
+    removed: &[u32],
+    entities: &[&[u8]],
+    chunk_size: usize,
+) -> Vec<Vec<u8>> {
+    let mut chunks = vec![(Vec::new(), Vec::new())];
+    let mut size = CHUNK_HEADER_SIZE;
+    for id in removed {
+        if size + 4 > chunk_size {
+            chunks.push((Vec::new(), Vec::new()));
+            size = CHUNK_HEADER_SIZE;
+        }
+        chunks.last_mut().unwrap().0.push(*id);
+        size += 4;
+    }
+    for data in entities {
+        let entity_size = 4 + data.len();
+        if CHUNK_HEADER_SIZE + entity_size > chunk_size {
+            warn!(
+                "a replicated entity of {} bytes is too large for snapshots of {} bytes",
+                data.len(),
+                chunk_size
+            );
+            continue;
+        }
+        if size + entity_size > chunk_size {
+            chunks.push((Vec::new(), Vec::new()));
+            size = CHUNK_HEADER_SIZE;
+        }
+        chunks.last_mut().unwrap().1.push(*data);
+        size += entity_size;
+    }
+
+    let count = chunks.len() as u16;
+    chunks
+        .into_iter()
+        .enumerate()
+        .map(|(index, (removed, entities))| {
+            let mut message = Vec::with_capacity(chunk_size);
+            message.extend_from_slice(&sequence.to_le_bytes());
+            message.extend_from_slice(&baseline.unwrap_or(0).to_le_bytes());
+            message.extend_from_slice(&(index as u16).to_le_bytes());
+            message.extend_from_slice(&count.to_le_bytes());
+            message.extend_from_slice(&(removed.len() as u32).to_le_bytes());
+            for id in removed {
+                message.extend_from_slice(&id.to_le_bytes());
+            }
+            message.extend_from_slice(&(entities.len() as u32).to_le_bytes());
+            for data in entities {
+                message.extend_from_slice(&(data.len() as u32).to_le_bytes());
+                message.extend_from_slice(data);
+            }
+            message
+        })
+        .collect()
+}
+
+/// A part of a snapshot received by a client
+#[derive(Debug)]
+struct SnapshotChunk {
+    sequence: u64,
+    /// The snapshot the entities changed since, or None if the snapshot has every relevant entity
+    baseline: Option<u64>,
+    index: u16,
+    count: u16,
+    removed: Vec<u32>,
+    entities: Vec<Vec<u8>>,
+}
+
+impl SnapshotChunk {
+    fn decode(message: &[u8]) -> Option<SnapshotChunk> {
+        let mut reader = Reader(message);
+        let sequence = reader.u64()?;
+        let baseline = Some(reader.u64()?).filter(|baseline| *baseline != 0);
+        let index = reader.u16()?;
+        let count = reader.u16()?;
+        if index >= count {
+            return None;
+        }
+        let removed = (0..reader.u32()?)
+            .map(|_| reader.u32())
+            .collect::<Option<Vec<_>>>()?;
+        let entities = (0..reader.u32()?)
+            .map(|_| {
+                let len = reader.u32()? as usize;
+                reader.bytes(len).map(<[u8]>::to_vec)
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(SnapshotChunk {
+            sequence,
+            baseline,
+            index,
+            count,
+            removed,
+            entities,
+        })
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.0.len() < len {
+            return None;
+        }
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Some(bytes)
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        Some(u16::from_le_bytes(self.bytes(2)?.try_into().ok()?))
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.bytes(4)?.try_into().ok()?))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_le_bytes(self.bytes(8)?.try_into().ok()?))
+    }
+}
+
+fn decode_snapshot(data: &[u8], type_registry: &TypeRegistry) -> Result<DynamicScene, ron::Error> {
+    let mut deserializer = ron::de::Deserializer::from_bytes(data)?;
+    SceneDeserializer { type_registry }.deserialize(&mut deserializer)
+}
+
+/// Despawns the removed entities of a chunk, and spawns or updates its other entities. Returns
+/// the server entities it updated.
+fn apply_chunk(
+    world: &mut World,
+    chunk: &SnapshotChunk,
+    replicated: &mut ReplicatedEntities,
+    type_registry: &TypeRegistry,
+) -> Result<Vec<u32>, String> {
+    for id in &chunk.removed {
+        let server_entity = Entity::new(*id);
+        if let Ok(client_entity) = replicated.map.get(server_entity) {
+            world.despawn(client_entity);
+            replicated.map.remove(server_entity);
+        }
+    }
+    let mut scene = DynamicScene::default();
+    for data in &chunk.entities {
+        let entity_scene = decode_snapshot(data, type_registry).map_err(|err| err.to_string())?;
+        scene.entities.extend(entity_scene.entities);
+    }
+    for entity in &scene.entities {
+        let server_entity = Entity::new(entity.entity);
+        if let Ok(client_entity) = replicated.map.get(server_entity) {
+            if world.get_entity(client_entity).is_none() {
+                // despawned by the client, so spawned again
+                replicated.map.remove(server_entity);
+            }
+        }
+    }
+    scene
+        .write_to_world(world, &mut replicated.map)
+        .map_err(|err: SceneSpawnError| err.to_string())?;
+    Ok(scene.entities.iter().map(|entity| entity.entity).collect())
+}
+
+/// Applies the chunks received by a client in order, dropping the ones older than a chunk
+/// already applied. Returns the snapshots received entirely.
+fn apply_chunks(
+    world: &mut World,
+    incoming: &mut IncomingSnapshots,
+    replicated: &mut ReplicatedEntities,
+    type_registry: &TypeRegistry,
+) -> Vec<u64> {
+    let mut chunks = incoming
+        .received
+        .drain(..)
+        .filter_map(|message| SnapshotChunk::decode(&message))
+        .collect::<Vec<_>>();
+    chunks.sort_by_key(|chunk| (chunk.sequence, chunk.index));
+
+    let mut complete = Vec::new();
+    for chunk in chunks {
+        // unreliable transports can deliver chunks out of order
+        if incoming
+            .newest
+            .is_some_and(|newest| chunk.sequence < newest)
+        {
+            continue;
+        }
+        incoming.newest = Some(chunk.sequence);
+        incoming
+            .partial
+            .retain(|sequence, _| *sequence >= chunk.sequence);
+
+        let entities = match apply_chunk(world, &chunk, replicated, type_registry) {
+            Ok(entities) => entities,
+            Err(err) => {
+                warn!(
+                    "failed to apply replication snapshot {}: {}",
+                    chunk.sequence, err
+                );
+                continue;
+            }
+        };
+        let partial = incoming.partial.entry(chunk.sequence).or_default();
+        partial.chunks.insert(chunk.index);
+        if chunk.baseline.is_none() {
+            partial.entities.extend(entities);
+        }
+        if partial.chunks.len() == chunk.count as usize {
+            let partial = incoming.partial.remove(&chunk.sequence).unwrap();
+            if chunk.baseline.is_none() {
+                replicated.despawn_missing(world, &partial.entities);
+            }
+            complete.push(chunk.sequence);
+        }
+    }
+    complete
+}
+
+/// Sends the changes of the [Replicated] entities to each client of a server, at the interval of
+/// the [ReplicationSettings]
+pub fn server_replication_system(world: &mut World) {
+    let is_server = world
+        .get_resource::<Network>()
+        .map_or(false, |network| network.is_server());
+    let now = match world.get_resource::<Time>() {
+        Some(time) if is_server => time.seconds_since_startup(),
+        _ => return,
+    };
+    let settings = world
+        .get_resource_or_insert_with(ReplicationSettings::default)
+        .clone();
+    let connections = world
+        .get_resource::<Network>()
+        .unwrap()
+        .connections()
+        .to_vec();
+    let acks = world
+        .get_resource_mut::<IncomingAcks>()
+        .map(|mut incoming| std::mem::take(&mut incoming.acks))
+        .unwrap_or_default();
+    let sequence = {
+        let mut server = world.get_resource_or_insert_with(ServerReplication::default);
+        for (connection, sequence) in acks {
+            if let Some(client) = server.clients.get_mut(&connection) {
+                client.acknowledge(sequence);
+            }
+        }
+        if server
+            .last_sent
+            .is_some_and(|last_sent| now - last_sent < settings.interval)
+        {
+            return;
+        }
+        server.last_sent = Some(now);
+        server.sequence += 1;
+        server
+            .clients
+            .retain(|connection, _| connections.contains(connection));
+        server.sequence
+    };
+
+    let registry = world.get_resource::<TypeRegistryArc>().unwrap().clone();
+    let components = world
+        .get_resource_or_insert_with(ReplicationRegistry::default)
+        .components
+        .clone();
+    let entities = serialize_entities(world, &registry, &components);
+    let relevant = relevant_entities(world, &entities, &settings.interest, &connections);
+    let chunk_size = settings
+        .chunk_size
+        .clamp(CHUNK_HEADER_SIZE + 4, MAX_UDP_MESSAGE_SIZE - 1);
+    let messages = {
+        let mut server = world.get_resource_mut::<ServerReplication>().unwrap();
+        relevant
+            .iter()
+            .map(|(connection, entities)| {
+                let client = server.clients.entry(*connection).or_default();
+                (*connection, client.snapshot(sequence, entities, chunk_size))
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let mut network = world.get_resource_mut::<Network>().unwrap();
+    for (connection, chunks) in messages {
+        for chunk in chunks {
+            if let Err(err) = network.send_on(Channel::Replication, connection, &chunk) {
+                warn!("failed to replicate entities to {:?}: {}", connection, err);
+                break;
+            }
+        }
+    }
+}
+
+/// Applies the snapshot chunks received by a client to its [ReplicatedEntities], and
+/// acknowledges the last snapshot received entirely to the server
+pub fn client_replication_system(world: &mut World) {
+    match world.get_resource::<IncomingSnapshots>() {
+        Some(incoming) if incoming.reset || !incoming.received.is_empty() => {}
+        _ => return,
+    }
+    let registry = world.get_resource::<TypeRegistryArc>().unwrap().clone();
+    let complete = world.resource_scope(|world, mut incoming: Mut<IncomingSnapshots>| {
+        world.resource_scope(|world, mut replicated: Mut<ReplicatedEntities>| {
+            if std::mem::take(&mut incoming.reset) {
+                replicated.despawn_all(world);
+            }
+            apply_chunks(world, &mut incoming, &mut replicated, &registry.read())
+        })
+    });
+
+    let (sequence, mut network) = match (complete.last(), world.get_resource_mut::<Network>()) {
+        (Some(sequence), Some(network)) => (*sequence, network),
+        _ => return,
+    };
+    // clients only have a connection, to the server
+    if let Some(server) = network.connections().first().copied() {
+        if let Err(err) = network.send_on(Channel::Replication, server, &sequence.to_le_bytes()) {
+            warn!(
+                "failed to acknowledge replication snapshot {}: {}",
+                sequence, err
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_math::Quat;
+    use bevy_transform::components::Transform;
+
+    fn registry() -> TypeRegistryArc {
+        let registry = TypeRegistryArc::default();
+        {
+            let mut registry = registry.write();
+            registry.register::<Vec3>();
+            registry.register::<Quat>();
+            registry.register::<Transform>();
+        }
+        registry
+    }
+
+    fn spawn_replicated(world: &mut World, x: f32) -> Entity {
+        world
+            .spawn()
+            .insert_bundle((
+                Replicated,
+                Transform::from_xyz(x, 0.0, 0.0),
+                GlobalTransform::from_xyz(x, 0.0, 0.0),
+            ))
+            .id()
+    }
+
+    /// Encodes the next snapshot of every replicated entity of `server` for `client`
+    fn snapshot(
+        server: &mut World,
+        registry: &TypeRegistryArc,
+        client: &mut ClientReplication,
+        sequence: u64,
+        chunk_size: usize,
+    ) -> Vec<Vec<u8>> {
+        let entities = serialize_entities(server, registry, &[TypeId::of::<Transform>()]);
+        let entities = entities.iter().collect::<Vec<_>>();
+        client.snapshot(sequence, &entities, chunk_size)
+    }
+
+    #[test]
+    fn sends_relevant_entities() {
+        let registry = registry();
+        let mut server = World::new();
+        let near = spawn_replicated(&mut server, 1.0);
+        spawn_replicated(&mut server, 100.0);
+        server
+            .spawn()
+            .insert_bundle((Transform::identity(), GlobalTransform::identity()));
+        server.spawn().insert_bundle((
+            NetworkObserver {
+                connection: ConnectionId(0),
+            },
+            GlobalTransform::identity(),
+        ));
+
+        let entities = serialize_entities(&mut server, &registry, &[TypeId::of::<Transform>()]);
+        let relevant = relevant_entities(
+            &mut server,
+            &entities,
+            &Interest::Radius(10.0),
+            &[ConnectionId(0), ConnectionId(1)],
+        );
+        assert_eq!(relevant[0].1.len(), 1);
+        assert_eq!(relevant[0].1[0].id, near.id());
+        assert!(relevant[1].1.is_empty());
+    }
+
+    #[test]
+    fn sends_changes_since_the_acknowledged_snapshot() {
+        let registry = registry();
+        let mut server = World::new();
+        let moving = spawn_replicated(&mut server, 1.0);
+        let removed = spawn_replicated(&mut server, 2.0);
+        let mut client_replication = ClientReplication::default();
+
+        let mut client = World::new();
+        client.insert_resource(registry.clone());
+        let mut incoming = IncomingSnapshots::default();
+        let mut replicated = ReplicatedEntities::default();
+        let mut receive = |client: &mut World, messages: Vec<Vec<u8>>| {
+            for message in messages {
+                incoming.push(message);
+            }
+            apply_chunks(client, &mut incoming, &mut replicated, &registry.read())
+        };
+
+        // the first snapshot has every entity
+        let messages = snapshot(&mut server, &registry, &mut client_replication, 1, 1200);
+        assert_eq!(
+            SnapshotChunk::decode(&messages[0]).unwrap().entities.len(),
+            2
+        );
+        assert_eq!(receive(&mut client, messages), vec![1]);
+        client_replication.acknowledge(1);
+
+        // then only the entities changed since the acknowledged snapshot
+        server.get_mut::<Transform>(moving).unwrap().translation = Vec3::Y;
+        let messages = snapshot(&mut server, &registry, &mut client_replication, 2, 1200);
+        let chunk = SnapshotChunk::decode(&messages[0]).unwrap();
+        assert_eq!((chunk.baseline, chunk.entities.len()), (Some(1), 1));
+        assert_eq!(receive(&mut client, messages), vec![2]);
+
+        // and the entities that are no longer replicated, while snapshot 2 isn't acknowledged
+        server.despawn(removed);
+        let messages = snapshot(&mut server, &registry, &mut client_replication, 3, 1200);
+        let chunk = SnapshotChunk::decode(&messages[0]).unwrap();
+        assert_eq!((chunk.baseline, chunk.entities.len()), (Some(1), 1));
+        assert_eq!(chunk.removed, vec![removed.id()]);
+        // a chunk older than the ones applied is dropped
+        let stale = snapshot(
+            &mut server,
+            &registry,
+            &mut ClientReplication::default(),
+            2,
+            1200,
+        );
+        assert_eq!(receive(&mut client, messages), vec![3]);
+        assert!(receive(&mut client, stale).is_empty());
+
+        let client_moving = replicated.client_entity(moving).unwrap();
+        assert_eq!(
+            client.get::<Transform>(client_moving).unwrap().translation,
+            Vec3::Y
+        );
+        assert!(replicated.client_entity(removed).is_none());
+        assert_eq!(client.query::<&Transform>().iter(&client).count(), 1);
+    }
+
+    #[test]
+    fn splits_snapshots_into_chunks() {
+        let registry = registry();
+        let mut server = World::new();
+        for x in 0..4 {
+            spawn_replicated(&mut server, x as f32);
+        }
+        let entity_size = serialize_entities(&mut server, &registry, &[TypeId::of::<Transform>()])
+            .iter()
+            .map(|entity| entity.data.len())
+            .max()
+            .unwrap();
+        let chunk_size = CHUNK_HEADER_SIZE + 4 + entity_size;
+        let mut messages = snapshot(
+            &mut server,
+            &registry,
+            &mut ClientReplication::default(),
+            1,
+            chunk_size,
+        );
+        assert_eq!(messages.len(), 4);
+        assert!(messages.iter().all(|message| message.len() <= chunk_size));
+
+        let mut client = World::new();
+        client.insert_resource(registry.clone());
+        let mut incoming = IncomingSnapshots::default();
+        let mut replicated = ReplicatedEntities::default();
+        let last = messages.pop().unwrap();
+        for message in messages {
+            incoming.push(message);
+        }
+        // the chunks received are applied, but the snapshot is only complete with all of them
+        assert!(apply_chunks(
+            &mut client,
+            &mut incoming,
+            &mut replicated,
+            &registry.read()
+        )
+        .is_empty());
+        assert_eq!(replicated.iter().count(), 3);
+        incoming.push(last);
+        assert_eq!(
+            apply_chunks(
+                &mut client,
+                &mut incoming,
+                &mut replicated,
+                &registry.read()
+            ),
+            vec![1]
+        );
+        assert_eq!(replicated.iter().count(), 4);
+    }
+}
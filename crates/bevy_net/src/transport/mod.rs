@@ -0,0 +1,69 @@
+mod udp;
+mod websocket;
+
+pub use udp::*;
+pub use websocket::*;
+
+use thiserror::Error;
+
+/// Identifies a connection of a [Transport]. Clients have a single connection, to the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ConnectionId(pub u32);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransportEvent {
+    Connected(ConnectionId),
+    /// Sent when the other side closed the connection or timed out, after a local
+    /// [Transport::disconnect], and when a client fails to connect
+    Disconnected(ConnectionId),
+    Message(ConnectionId, Vec<u8>),
+}
+
+#[derive(Error, Debug)]
+pub enum NetworkError {
+    #[error("network io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("websocket error: {0}")]
+    WebSocket(#[from] tungstenite::Error),
+    #[error("invalid websocket url {0}")]
+    InvalidUrl(String),
+    #[error("{0:?} is not an open connection")]
+    UnknownConnection(ConnectionId),
+    #[error("a message of {0} bytes is too large for the transport")]
+    MessageTooLarge(usize),
+}
+
+/// Sends and receives messages over connections. Transports never block: they are polled once
+/// per frame by the [Network](crate::Network) resource.
+pub trait Transport: Send + Sync + 'static {
+    /// Accepts new connections and receives the pending messages
+    fn poll(&mut self, events: &mut Vec<TransportEvent>) -> Result<(), NetworkError>;
+
+    fn send(&mut self, connection: ConnectionId, message: &[u8]) -> Result<(), NetworkError>;
+
+    /// Closes a connection. Its [TransportEvent::Disconnected] is sent at the next poll.
+    fn disconnect(&mut self, connection: ConnectionId);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    pub(super) fn poll_until(
+        transport: &mut impl Transport,
+        other: &mut impl Transport,
+        condition: impl Fn(&TransportEvent) -> bool,
+    ) -> TransportEvent {
+        for _ in 0..200 {
+            let mut events = Vec::new();
+            other.poll(&mut Vec::new()).unwrap();
+            transport.poll(&mut events).unwrap();
+            if let Some(event) = events.into_iter().find(|event| condition(event)) {
+                return event;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        panic!("the transport didn't receive the expected event");
+    }
+}
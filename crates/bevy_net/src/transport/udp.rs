@@ -0,0 +1,309 @@
+use super::{ConnectionId, NetworkError, Transport, TransportEvent};
+use bevy_utils::HashMap;
+use std::{
+    io,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket},
+    time::{Duration, Instant},
+};
+
+/// The largest message a [UdpTransport] can send: the largest UDP payload over IPv4, minus the
+/// packet header
+pub const MAX_UDP_MESSAGE_SIZE: usize = 65507 - 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PacketKind {
+    Connect = 0,
+    Accept = 1,
+    Message = 2,
+    Disconnect = 3,
+    KeepAlive = 4,
+}
+
+impl PacketKind {
+    fn from_byte(byte: u8) -> Option<PacketKind> {
+        match byte {
+            0 => Some(PacketKind::Connect),
+            1 => Some(PacketKind::Accept),
+            2 => Some(PacketKind::Message),
+            3 => Some(PacketKind::Disconnect),
+            4 => Some(PacketKind::KeepAlive),
+            _ => None,
+        }
+    }
+}
+
+fn send_packet(
+    socket: &UdpSocket,
+    address: SocketAddr,
+    kind: PacketKind,
+    payload: &[u8],
+) -> io::Result<()> {
+    let mut packet = Vec::with_capacity(payload.len() + 1);
+    packet.push(kind as u8);
+    packet.extend_from_slice(payload);
+    match socket.send_to(&packet, address) {
+        // packets can be dropped anyway, so a full send buffer drops them too
+        Err(err) if err.kind() == io::ErrorKind::WouldBlock => Ok(()),
+        result => result.map(|_| ()),
+    }
+}
+
+#[derive(Debug)]
+struct UdpConnection {
+    id: ConnectionId,
+    accepted: bool,
+    last_received: Instant,
+    last_sent: Instant,
+}
+
+/// A [Transport] sending each message in a UDP datagram. Messages can be lost, duplicated or
+/// received out of order, which suits state that is sent again and again like replicated
+/// components. Connections are kept open by keep-alive packets and closed after a timeout.
+#[derive(Debug)]
+pub struct UdpTransport {
+    socket: UdpSocket,
+    is_server: bool,
+    connections: HashMap<SocketAddr, UdpConnection>,
+    next_id: u32,
+    disconnected: Vec<ConnectionId>,
+    buffer: Vec<u8>,
+    /// Connections that don't receive any packet for this long are closed
+    pub timeout: Duration,
+    /// How often a packet is sent over idle connections, and a connection request is sent again
+    /// while a client is connecting
+    pub keep_alive_interval: Duration,
+}
+
+impl UdpTransport {
+    fn new(socket: UdpSocket, is_server: bool) -> Result<Self, NetworkError> {
+        socket.set_nonblocking(true)?;
+        Ok(UdpTransport {
+            socket,
+            is_server,
+            connections: Default::default(),
+            next_id: 0,
+            disconnected: Vec::new(),
+            buffer: vec![0; MAX_UDP_MESSAGE_SIZE + 1],
+            timeout: Duration::from_secs(10),
+            keep_alive_interval: Duration::from_millis(250),
+        })
+    }
+
+    /// Accepts connections from clients on `address`
+    pub fn listen(address: impl ToSocketAddrs) -> Result<Self, NetworkError> {
+        Self::new(UdpSocket::bind(address)?, true)
+    }
+
+    /// Connects to the server at `address`. [TransportEvent::Connected] is sent once the server
+    /// accepted the connection.
+    pub fn connect(address: impl ToSocketAddrs) -> Result<Self, NetworkError> {
+        let server = address.to_socket_addrs()?.next().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "no address to connect to")
+        })?;
+        let local = if server.is_ipv4() {
+            SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), 0)
+        } else {
+            SocketAddr::new(Ipv6Addr::UNSPECIFIED.into(), 0)
+        };
+        let mut transport = Self::new(UdpSocket::bind(local)?, false)?;
+        let now = Instant::now();
+        send_packet(&transport.socket, server, PacketKind::Connect, &[])?;
+        transport.connections.insert(
+            server,
+            UdpConnection {
+                id: ConnectionId(0),
+                accepted: false,
+                last_received: now,
+                last_sent: now,
+            },
+        );
+        transport.next_id = 1;
+        Ok(transport)
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr, NetworkError> {
+        Ok(self.socket.local_addr()?)
+    }
+
+    fn address_of(&self, id: ConnectionId) -> Option<SocketAddr> {
+        self.connections
+            .iter()
+            .find(|(_, connection)| connection.id == id)
+            .map(|(address, _)| *address)
+    }
+
+    fn receive(
+        &mut self,
+        address: SocketAddr,
+        packet: &[u8],
+        now: Instant,
+        events: &mut Vec<TransportEvent>,
+    ) -> Result<(), NetworkError> {
+        let kind = match packet.first().and_then(|byte| PacketKind::from_byte(*byte)) {
+            Some(kind) => kind,
+            None => return Ok(()),
+        };
+        let connection = match self.connections.get_mut(&address) {
+            Some(connection) => connection,
+            None => {
+                if self.is_server && kind == PacketKind::Connect {
+                    let id = ConnectionId(self.next_id);
+                    self.next_id += 1;
+                    send_packet(&self.socket, address, PacketKind::Accept, &[])?;
+                    self.connections.insert(
+                        address,
+                        UdpConnection {
+                            id,
+                            accepted: true,
+                            last_received: now,
+                            last_sent: now,
+                        },
+                    );
+                    events.push(TransportEvent::Connected(id));
+                }
+                return Ok(());
+            }
+        };
+
+        connection.last_received = now;
+        match kind {
+            PacketKind::Accept if !connection.accepted => {
+                connection.accepted = true;
+                events.push(TransportEvent::Connected(connection.id));
+            }
+            PacketKind::Message if connection.accepted => {
+                events.push(TransportEvent::Message(connection.id, packet[1..].to_vec()));
+            }
+            // the accept packet was lost
+            PacketKind::Connect if self.is_server => {
+                connection.last_sent = now;
+                send_packet(&self.socket, address, PacketKind::Accept, &[])?;
+            }
+            PacketKind::Disconnect => {
+                let id = connection.id;
+                self.connections.remove(&address);
+                events.push(TransportEvent::Disconnected(id));
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+impl Transport for UdpTransport {
+    fn poll(&mut self, events: &mut Vec<TransportEvent>) -> Result<(), NetworkError> {
+        let now = Instant::now();
+        events.extend(
+            self.disconnected
+                .drain(..)
+                .map(TransportEvent::Disconnected),
+        );
+
+        let mut buffer = std::mem::take(&mut self.buffer);
+        let result = loop {
+            match self.socket.recv_from(&mut buffer) {
+                Ok((length, address)) => {
+                    if let Err(err) = self.receive(address, &buffer[..length], now, events) {
+                        break Err(err);
+                    }
+                }
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => break Ok(()),
+                // reported by some platforms when a previous packet couldn't be delivered
+                Err(err) if err.kind() == io::ErrorKind::ConnectionReset => continue,
+                Err(err) => break Err(err.into()),
+            }
+        };
+        self.buffer = buffer;
+        result?;
+
+        let timeout = self.timeout;
+        let timed_out = self
+            .connections
+            .iter()
+            .filter(|(_, connection)| now.duration_since(connection.last_received) > timeout)
+            .map(|(address, _)| *address)
+            .collect::<Vec<_>>();
+        for address in timed_out {
+            if let Some(connection) = self.connections.remove(&address) {
+                events.push(TransportEvent::Disconnected(connection.id));
+            }
+        }
+
+        for (address, connection) in self.connections.iter_mut() {
+            if now.duration_since(connection.last_sent) >= self.keep_alive_interval {
+                let kind = if connection.accepted {
+                    PacketKind::KeepAlive
+                } else {
+                    PacketKind::Connect
+                };
+                connection.last_sent = now;
+                send_packet(&self.socket, *address, kind, &[])?;
+            }
+        }
+        Ok(())
+    }
+
+    fn send(&mut self, connection: ConnectionId, message: &[u8]) -> Result<(), NetworkError> {
+        if message.len() > MAX_UDP_MESSAGE_SIZE {
+            return Err(NetworkError::MessageTooLarge(message.len()));
+        }
+        let address = self
+            .address_of(connection)
+            .filter(|address| self.connections[address].accepted)
+            .ok_or(NetworkError::UnknownConnection(connection))?;
+        send_packet(&self.socket, address, PacketKind::Message, message)?;
+        if let Some(connection) = self.connections.get_mut(&address) {
+            connection.last_sent = Instant::now();
+        }
+        Ok(())
+    }
+
+    fn disconnect(&mut self, connection: ConnectionId) {
+        if let Some(address) = self.address_of(connection) {
+            // the other side times out if this packet is lost
+            let _ = send_packet(&self.socket, address, PacketKind::Disconnect, &[]);
+            self.connections.remove(&address);
+            self.disconnected.push(connection);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::tests::poll_until;
+
+    #[test]
+    fn connects_and_exchanges_messages() {
+        let mut server = UdpTransport::listen("127.0.0.1:0").unwrap();
+        let mut client = UdpTransport::connect(server.local_addr().unwrap()).unwrap();
+
+        let event = poll_until(&mut server, &mut client, |event| {
+            matches!(event, TransportEvent::Connected(_))
+        });
+        let client_id = match event {
+            TransportEvent::Connected(id) => id,
+            _ => unreachable!(),
+        };
+        let event = poll_until(&mut client, &mut server, |event| {
+            matches!(event, TransportEvent::Connected(_))
+        });
+        assert_eq!(event, TransportEvent::Connected(ConnectionId(0)));
+
+        client.send(ConnectionId(0), b"hello").unwrap();
+        let event = poll_until(&mut server, &mut client, |event| {
+            matches!(event, TransportEvent::Message(..))
+        });
+        assert_eq!(event, TransportEvent::Message(client_id, b"hello".to_vec()));
+
+        client.disconnect(ConnectionId(0));
+        let event = poll_until(&mut server, &mut client, |event| {
+            matches!(event, TransportEvent::Disconnected(_))
+        });
+        assert_eq!(event, TransportEvent::Disconnected(client_id));
+        assert!(matches!(
+            server.send(client_id, b"bye"),
+            Err(NetworkError::UnknownConnection(_))
+        ));
+    }
+}
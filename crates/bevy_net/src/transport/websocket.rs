@@ -0,0 +1,276 @@
+use super::{ConnectionId, NetworkError, Transport, TransportEvent};
+use bevy_utils::HashMap;
+use std::{
+    io,
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    time::{Duration, Instant},
+};
+use tungstenite::{
+    handshake::{
+        client::ClientHandshake,
+        server::{NoCallback, ServerHandshake},
+        HandshakeError, HandshakeRole, MidHandshake,
+    },
+    http::Uri,
+    Message, WebSocket,
+};
+
+/// How long a websocket handshake can take before the connection is dropped
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+fn is_would_block(error: &tungstenite::Error) -> bool {
+    matches!(error, tungstenite::Error::Io(error) if error.kind() == io::ErrorKind::WouldBlock)
+}
+
+/// A handshake over a non-blocking stream, resumed by each poll until it completes
+#[derive(Debug)]
+struct PendingHandshake<Role: HandshakeRole> {
+    handshake: MidHandshake<Role>,
+    started: Instant,
+}
+
+enum HandshakeProgress<Role: HandshakeRole> {
+    Done(Role::FinalResult),
+    Pending(PendingHandshake<Role>),
+    Failed,
+}
+
+impl<Role: HandshakeRole> HandshakeProgress<Role> {
+    fn new(
+        result: Result<Role::FinalResult, HandshakeError<Role>>,
+        started: Instant,
+    ) -> HandshakeProgress<Role> {
+        match result {
+            Ok(result) => HandshakeProgress::Done(result),
+            Err(HandshakeError::Interrupted(handshake))
+                if started.elapsed() < HANDSHAKE_TIMEOUT =>
+            {
+                HandshakeProgress::Pending(PendingHandshake { handshake, started })
+            }
+            Err(_) => HandshakeProgress::Failed,
+        }
+    }
+
+    fn resume(pending: PendingHandshake<Role>) -> HandshakeProgress<Role> {
+        Self::new(pending.handshake.handshake(), pending.started)
+    }
+}
+
+/// A [Transport] sending messages over websockets, which are reliable and ordered, and can be
+/// used from web browsers. Handshakes don't block: they progress a little at each poll.
+#[derive(Debug)]
+pub struct WebSocketTransport {
+    listener: Option<TcpListener>,
+    accepting: Vec<PendingHandshake<ServerHandshake<TcpStream, NoCallback>>>,
+    connecting: Option<PendingHandshake<ClientHandshake<TcpStream>>>,
+    sockets: HashMap<ConnectionId, WebSocket<TcpStream>>,
+    next_id: u32,
+    pending_events: Vec<TransportEvent>,
+}
+
+impl WebSocketTransport {
+    /// Accepts connections from clients on `address`
+    pub fn listen(address: impl ToSocketAddrs) -> Result<Self, NetworkError> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        Ok(WebSocketTransport {
+            listener: Some(listener),
+            accepting: Vec::new(),
+            connecting: None,
+            sockets: Default::default(),
+            next_id: 0,
+            pending_events: Vec::new(),
+        })
+    }
+
+    /// Connects to the server at a `ws://` url. The handshake completes during the following polls,
+    /// which send [TransportEvent::Connected] once it succeeds, or [TransportEvent::Disconnected]
+    /// if it fails.
+    pub fn connect(url: &str) -> Result<Self, NetworkError> {
+        let uri = url
+            .parse::<Uri>()
+            .map_err(|_| NetworkError::InvalidUrl(url.to_string()))?;
+        let host = uri
+            .host()
+            .ok_or_else(|| NetworkError::InvalidUrl(url.to_string()))?;
+        let stream = TcpStream::connect((host, uri.port_u16().unwrap_or(80)))?;
+        stream.set_nonblocking(true)?;
+
+        let mut transport = WebSocketTransport {
+            listener: None,
+            accepting: Vec::new(),
+            connecting: None,
+            sockets: Default::default(),
+            next_id: 1,
+            pending_events: Vec::new(),
+        };
+        match tungstenite::client(url, stream) {
+            Ok((socket, _)) => transport.connected(ConnectionId(0), socket),
+            Err(HandshakeError::Interrupted(handshake)) => {
+                transport.connecting = Some(PendingHandshake {
+                    handshake,
+                    started: Instant::now(),
+                })
+            }
+            Err(HandshakeError::Failure(error)) => return Err(error.into()),
+        }
+        Ok(transport)
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr, NetworkError> {
+        match &self.listener {
+            Some(listener) => Ok(listener.local_addr()?),
+            None => Err(io::Error::from(io::ErrorKind::NotConnected).into()),
+        }
+    }
+
+    fn connected(&mut self, id: ConnectionId, socket: WebSocket<TcpStream>) {
+        self.sockets.insert(id, socket);
+        self.pending_events.push(TransportEvent::Connected(id));
+    }
+
+    fn poll_handshakes(&mut self) -> Result<(), NetworkError> {
+        if let Some(listener) = &self.listener {
+            let started = Instant::now();
+            loop {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        // a client failing its handshake doesn't stop the server
+                        if stream.set_nonblocking(true).is_err() {
+                            continue;
+                        }
+                        match HandshakeProgress::new(tungstenite::accept(stream), started) {
+                            HandshakeProgress::Done(socket) => {
+                                let id = ConnectionId(self.next_id);
+                                self.next_id += 1;
+                                self.connected(id, socket);
+                            }
+                            HandshakeProgress::Pending(pending) => self.accepting.push(pending),
+                            HandshakeProgress::Failed => {}
+                        }
+                    }
+                    Err(err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                    Err(err) => return Err(err.into()),
+                }
+            }
+        }
+
+        for pending in std::mem::take(&mut self.accepting) {
+            match HandshakeProgress::resume(pending) {
+                HandshakeProgress::Done(socket) => {
+                    let id = ConnectionId(self.next_id);
+                    self.next_id += 1;
+                    self.connected(id, socket);
+                }
+                HandshakeProgress::Pending(pending) => self.accepting.push(pending),
+                HandshakeProgress::Failed => {}
+            }
+        }
+
+        if let Some(pending) = self.connecting.take() {
+            match HandshakeProgress::resume(pending) {
+                HandshakeProgress::Done((socket, _)) => self.connected(ConnectionId(0), socket),
+                HandshakeProgress::Pending(pending) => self.connecting = Some(pending),
+                HandshakeProgress::Failed => self
+                    .pending_events
+                    .push(TransportEvent::Disconnected(ConnectionId(0))),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Transport for WebSocketTransport {
+    fn poll(&mut self, events: &mut Vec<TransportEvent>) -> Result<(), NetworkError> {
+        self.poll_handshakes()?;
+        events.append(&mut self.pending_events);
+
+        let mut closed = Vec::new();
+        for (id, socket) in self.sockets.iter_mut() {
+            loop {
+                match socket.read_message() {
+                    Ok(Message::Binary(data)) => events.push(TransportEvent::Message(*id, data)),
+                    Ok(Message::Text(text)) => {
+                        events.push(TransportEvent::Message(*id, text.into_bytes()))
+                    }
+                    Ok(Message::Close(_)) => {
+                        closed.push(*id);
+                        break;
+                    }
+                    Ok(_) => {}
+                    Err(err) if is_would_block(&err) => break,
+                    Err(_) => {
+                        closed.push(*id);
+                        break;
+                    }
+                }
+            }
+            // sends the replies to pings and the messages that didn't fit in the socket buffer
+            match socket.write_pending() {
+                Err(err) if !is_would_block(&err) => closed.push(*id),
+                _ => {}
+            }
+        }
+        closed.sort();
+        closed.dedup();
+        for id in closed {
+            self.sockets.remove(&id);
+            events.push(TransportEvent::Disconnected(id));
+        }
+        Ok(())
+    }
+
+    fn send(&mut self, connection: ConnectionId, message: &[u8]) -> Result<(), NetworkError> {
+        let socket = self
+            .sockets
+            .get_mut(&connection)
+            .ok_or(NetworkError::UnknownConnection(connection))?;
+        match socket.write_message(Message::Binary(message.to_vec())) {
+            // the message is queued and sent by the next polls
+            Err(err) if is_would_block(&err) => Ok(()),
+            result => Ok(result?),
+        }
+    }
+
+    fn disconnect(&mut self, connection: ConnectionId) {
+        if let Some(mut socket) = self.sockets.remove(&connection) {
+            let _ = socket.close(None);
+            let _ = socket.write_pending();
+            self.pending_events
+                .push(TransportEvent::Disconnected(connection));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::tests::poll_until;
+
+    #[test]
+    fn handshakes_without_blocking() {
+        let mut server = WebSocketTransport::listen("127.0.0.1:0").unwrap();
+        let url = format!("ws://{}", server.local_addr().unwrap());
+        // the server and the client are polled from the same thread, so neither can block on
+        // its handshake
+        let mut client = WebSocketTransport::connect(&url).unwrap();
+
+        let event = poll_until(&mut server, &mut client, |event| {
+            matches!(event, TransportEvent::Connected(_))
+        });
+        let client_id = match event {
+            TransportEvent::Connected(id) => id,
+            _ => unreachable!(),
+        };
+        let event = poll_until(&mut client, &mut server, |event| {
+            matches!(event, TransportEvent::Connected(_))
+        });
+        assert_eq!(event, TransportEvent::Connected(ConnectionId(0)));
+
+        client.send(ConnectionId(0), b"hello").unwrap();
+        let event = poll_until(&mut server, &mut client, |event| {
+            matches!(event, TransportEvent::Message(..))
+        });
+        assert_eq!(event, TransportEvent::Message(client_id, b"hello".to_vec()));
+    }
+}
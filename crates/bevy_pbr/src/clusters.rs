@@ -0,0 +1,320 @@
+use crate::{
+    material::StandardMaterial,
+    render_graph::{node, ClustersNode},
+};
+use bevy_app::prelude::*;
+use bevy_asset::Handle;
+use bevy_core::Byteable;
+use bevy_ecs::{
+    query::{With, Without},
+    system::{IntoSystem, Query},
+};
+use bevy_math::{Mat4, Vec3, Vec4};
+use bevy_render::{
+    draw::OutsideFrustum,
+    pipeline::RenderPipelines,
+    render_graph::{base, RenderGraph},
+};
+
+/// Splits the view frustum of the main pass camera into a grid of clusters and assigns each point
+/// light to the clusters its range overlaps. [StandardMaterial]s then only shade the lights of the
+/// cluster a fragment is in, instead of the first [MAX_POINT_LIGHTS] lights of the scene.
+///
+/// Lights are assigned on the cpu every frame. The plugin has to be added after [PbrPlugin].
+///
+/// [PbrPlugin]: crate::PbrPlugin
+/// [MAX_POINT_LIGHTS]: crate::render_graph::MAX_POINT_LIGHTS
+#[derive(Debug, Default)]
+pub struct ClusteredLightingPlugin;
+
+impl Plugin for ClusteredLightingPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<ClusterConfig>().add_system_to_stage(
+            CoreStage::PostUpdate,
+            clustered_lighting_shader_defs_system.system(),
+        );
+
+        let mut graph = app.world_mut().get_resource_mut::<RenderGraph>().unwrap();
+        graph.add_system_node(node::CLUSTERS, ClustersNode::default());
+        graph
+            .add_node_edge(node::CLUSTERS, base::node::MAIN_PASS)
+            .unwrap();
+    }
+}
+
+/// The shader def that switches [StandardMaterial]s to clustered lighting
+pub const CLUSTERED_LIGHTING_SHADER_DEF: &str = "CLUSTERED_LIGHTING";
+
+/// How the view frustum is divided into clusters
+#[derive(Debug, Clone)]
+pub struct ClusterConfig {
+    /// The number of clusters along the width, the height and the depth of the view. Dimensions
+    /// of 0 are used as 1.
+    pub dimensions: [u32; 3],
+    /// The view depth where depth slices start. Depth slices grow exponentially from `near` to
+    /// `far`, anything closer than `near` is in the first slice.
+    pub near: f32,
+    /// The view depth where depth slices end, anything further is in the last slice
+    pub far: f32,
+}
+
+impl Default for ClusterConfig {
+    fn default() -> Self {
+        ClusterConfig {
+            dimensions: [16, 9, 24],
+            near: 0.1,
+            far: 1000.0,
+        }
+    }
+}
+
+impl ClusterConfig {
+    /// The [dimensions](ClusterConfig::dimensions) of the grid, with at least one cluster along
+    /// each axis
+    pub fn grid_dimensions(&self) -> [u32; 3] {
+        let [width, height, depth] = self.dimensions;
+        [width.max(1), height.max(1), depth.max(1)]
+    }
+
+    pub fn cluster_count(&self) -> usize {
+        self.grid_dimensions()
+            .iter()
+            .map(|dimension| *dimension as usize)
+            .product()
+    }
+
+    /// The scale and bias that map the log of a view depth to its depth slice. The fragment
+    /// shader computes slices the same way.
+    fn z_slice_scale_bias(&self) -> (f32, f32) {
+        let near = self.near.max(f32::EPSILON);
+        let slices = self.grid_dimensions()[2] as f32;
+        let scale = slices / (self.far.max(near * 2.0) / near).ln();
+        (scale, -near.ln() * scale)
+    }
+
+    /// The depth slice of a point `depth` units in front of the camera
+    pub fn z_slice(&self, depth: f32) -> u32 {
+        let (scale, bias) = self.z_slice_scale_bias();
+        let slice = depth.max(f32::EPSILON).ln() * scale + bias;
+        (slice.max(0.0) as u32).min(self.grid_dimensions()[2] - 1)
+    }
+}
+
+/// The lights of each cluster, indexed by `x + y * width + z * width * height`, where y grows
+/// downwards like fragment coordinates
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ClusterLights {
+    /// The offset into `indices` and the number of lights of each cluster
+    pub offsets_and_counts: Vec<[u32; 2]>,
+    /// The indices of the lights of every cluster, one cluster after another
+    pub indices: Vec<u32>,
+}
+
+/// Assigns the point lights at `lights`, given as world space positions and ranges, to the
+/// clusters of a view. Lights are assigned conservatively, using the screen space bounds of the
+/// box around their range.
+pub fn assign_lights_to_clusters(
+    config: &ClusterConfig,
+    view: &Mat4,
+    projection: &Mat4,
+    lights: impl Iterator<Item = (Vec3, f32)>,
+) -> ClusterLights {
+    let [width, height, _] = config.grid_dimensions();
+    let cluster_count = config.cluster_count();
+    let mut light_ranges = Vec::new();
+    let mut counts = vec![0u32; cluster_count];
+    for (index, (position, range)) in lights.enumerate() {
+        let center = view.transform_point3(position);
+        // the camera looks towards -z
+        let (min_depth, max_depth) = (-center.z - range, -center.z + range);
+        if max_depth <= 0.0 {
+            continue;
+        }
+        let z = config.z_slice(min_depth)..=config.z_slice(max_depth);
+
+        let (x, y) = match screen_bounds(projection, center, range) {
+            Some((min, max)) => {
+                if min.x > 1.0 || min.y > 1.0 || max.x < -1.0 || max.y < -1.0 {
+                    continue;
+                }
+                (
+                    tile(min.x * 0.5 + 0.5, width)..=tile(max.x * 0.5 + 0.5, width),
+                    tile(0.5 - max.y * 0.5, height)..=tile(0.5 - min.y * 0.5, height),
+                )
+            }
+            None => (0..=width - 1, 0..=height - 1),
+        };
+
+        for z in z.clone() {
+            for y in y.clone() {
+                for x in x.clone() {
+                    counts[(x + y * width + z * width * height) as usize] += 1;
+                }
+            }
+        }
+        light_ranges.push((index as u32, x, y, z));
+    }
+
+    let mut offsets_and_counts = Vec::with_capacity(cluster_count);
+    let mut offset = 0;
+    for count in counts {
+        offsets_and_counts.push([offset, 0]);
+        offset += count;
+    }
+    let mut indices = vec![0; offset as usize];
+    for (index, x, y, z) in light_ranges {
+        for z in z {
+            for y in y.clone() {
+                for x in x.clone() {
+                    let cluster =
+                        &mut offsets_and_counts[(x + y * width + z * width * height) as usize];
+                    indices[(cluster[0] + cluster[1]) as usize] = index;
+                    cluster[1] += 1;
+                }
+            }
+        }
+    }
+    ClusterLights {
+        offsets_and_counts,
+        indices,
+    }
+}
+
+fn tile(position: f32, tiles: u32) -> u32 {
+    ((position * tiles as f32).max(0.0) as u32).min(tiles - 1)
+}
+
+/// The normalized device coordinate bounds of the box around a sphere in view space, or `None`
+/// if the box crosses the plane of the camera
+fn screen_bounds(projection: &Mat4, center: Vec3, radius: f32) -> Option<(Vec3, Vec3)> {
+    let mut min = Vec3::splat(f32::MAX);
+    let mut max = Vec3::splat(f32::MIN);
+    for i in 0..8 {
+        let corner = center
+            + Vec3::new(
+                if i & 1 == 0 { -radius } else { radius },
+                if i & 2 == 0 { -radius } else { radius },
+                if i & 4 == 0 { -radius } else { radius },
+            );
+        let clip = *projection * Vec4::new(corner.x, corner.y, corner.z, 1.0);
+        if clip.w <= f32::EPSILON {
+            return None;
+        }
+        let ndc = clip.truncate() / clip.w;
+        min = min.min(ndc);
+        max = max.max(ndc);
+    }
+    Some((min, max))
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ClustersUniform {
+    pub view: [[f32; 4]; 4],
+    // storing as a `[u32; 4]` for memory alignment
+    pub dimensions: [u32; 4],
+    /// the depth slice scale and bias, and the size of the target in pixels
+    pub params: [f32; 4],
+}
+
+unsafe impl Byteable for ClustersUniform {}
+
+impl ClustersUniform {
+    pub fn new(config: &ClusterConfig, view: &Mat4, target_size: (f32, f32)) -> Self {
+        let [width, height, depth] = config.grid_dimensions();
+        let (scale, bias) = config.z_slice_scale_bias();
+        ClustersUniform {
+            view: view.to_cols_array_2d(),
+            dimensions: [width, height, depth, 0],
+            params: [scale, bias, target_size.0, target_size.1],
+        }
+    }
+}
+
+pub fn clustered_lighting_shader_defs_system(
+    mut query: Query<
+        &mut RenderPipelines,
+        (With<Handle<StandardMaterial>>, Without<OutsideFrustum>),
+    >,
+) {
+    for mut render_pipelines in query.iter_mut() {
+        for render_pipeline in render_pipelines.pipelines.iter_mut() {
+            render_pipeline
+                .specialization
+                .shader_specialization
+                .shader_defs
+                .insert(CLUSTERED_LIGHTING_SHADER_DEF.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> ClusterConfig {
+        ClusterConfig {
+            dimensions: [4, 2, 8],
+            near: 1.0,
+            far: 256.0,
+        }
+    }
+
+    #[test]
+    fn slices_depth_exponentially() {
+        let config = config();
+        assert_eq!(config.z_slice(0.5), 0);
+        assert_eq!(config.z_slice(1.5), 0);
+        assert_eq!(config.z_slice(2.5), 1);
+        assert_eq!(config.z_slice(5.0), 2);
+        assert_eq!(config.z_slice(129.0), 7);
+        assert_eq!(config.z_slice(10000.0), 7);
+    }
+
+    #[test]
+    fn assigns_lights_to_overlapping_clusters() {
+        let config = config();
+        let projection = Mat4::perspective_rh(std::f32::consts::FRAC_PI_2, 2.0, 1.0, 1000.0);
+        let lights = vec![
+            // in the top left quarter of the screen, at depth 3
+            (Vec3::new(-2.5, 1.0, -3.0), 0.5),
+            // behind the camera
+            (Vec3::new(0.0, 0.0, 10.0), 1.0),
+            // around the camera, it is in every cluster of the first depth slices
+            (Vec3::new(0.0, 0.0, 0.0), 2.0),
+        ];
+        let clusters =
+            assign_lights_to_clusters(&config, &Mat4::IDENTITY, &projection, lights.into_iter());
+        let lights_of = |x: u32, y: u32, z: u32| {
+            let [offset, count] = clusters.offsets_and_counts[(x + y * 4 + z * 8) as usize];
+            clusters.indices[offset as usize..(offset + count) as usize].to_vec()
+        };
+
+        assert_eq!(lights_of(0, 0, 1), vec![0, 2]);
+        assert_eq!(lights_of(1, 0, 1), vec![0, 2]);
+        assert_eq!(lights_of(2, 0, 1), vec![2]);
+        assert_eq!(lights_of(0, 1, 1), vec![2]);
+        assert_eq!(lights_of(3, 1, 0), vec![2]);
+        assert_eq!(lights_of(0, 0, 3), Vec::<u32>::new());
+        assert!(!clusters.indices.contains(&1));
+    }
+
+    #[test]
+    fn uses_empty_dimensions_as_one_cluster() {
+        let config = ClusterConfig {
+            dimensions: [0, 2, 0],
+            ..config()
+        };
+        assert_eq!(config.grid_dimensions(), [1, 2, 1]);
+        assert_eq!(config.cluster_count(), 2);
+        assert_eq!(config.z_slice(10.0), 0);
+
+        let projection = Mat4::perspective_rh(std::f32::consts::FRAC_PI_2, 2.0, 1.0, 1000.0);
+        let lights = vec![(Vec3::new(0.0, 0.0, -3.0), 0.5)];
+        let clusters =
+            assign_lights_to_clusters(&config, &Mat4::IDENTITY, &projection, lights.into_iter());
+        // the light spans both rows of clusters
+        assert_eq!(clusters.offsets_and_counts, vec![[0, 1], [1, 1]]);
+        assert_eq!(clusters.indices, vec![0, 0]);
+    }
+}
@@ -0,0 +1,138 @@
+use bevy_ecs::{
+    reflect::ReflectComponent,
+    system::{Query, Res},
+};
+use bevy_reflect::Reflect;
+use bevy_render::{
+    camera::ActiveCameras,
+    render_graph::base,
+    renderer::RenderResources,
+    shader::{ShaderDefIterator, ShaderDefs},
+};
+use bevy_transform::components::GlobalTransform;
+
+/// Fades an entity out using a screen-door dither pattern instead of alpha blending.
+///
+/// Dithered fragments are discarded in an ordered (Bayer) pattern proportional to `factor`, which
+/// keeps the entity in the opaque pass with depth writes enabled. This is used to smooth out LOD
+/// transitions and to fade geometry that obstructs the camera.
+#[derive(Debug, Clone, Copy, Reflect, RenderResources)]
+#[reflect(Component)]
+pub struct DitherFade {
+    /// From [0.0, 1.0], where 0.0 is fully faded out and 1.0 is fully visible
+    pub factor: f32,
+}
+
+impl Default for DitherFade {
+    fn default() -> Self {
+        DitherFade { factor: 1.0 }
+    }
+}
+
+impl DitherFade {
+    pub fn new(factor: f32) -> Self {
+        DitherFade {
+            factor: factor.clamp(0.0, 1.0),
+        }
+    }
+}
+
+const DITHER_FADE_SHADER_DEF: &str = "DITHERFADE";
+
+/// Fully visible entities don't pay for the dither test, so the shader def is only defined while
+/// the entity is actually fading
+impl ShaderDefs for DitherFade {
+    fn shader_defs_len(&self) -> usize {
+        1
+    }
+
+    fn get_shader_def(&self, index: usize) -> Option<&str> {
+        if index == 0 && self.factor < 1.0 {
+            Some(DITHER_FADE_SHADER_DEF)
+        } else {
+            None
+        }
+    }
+
+    fn iter_shader_defs(&self) -> ShaderDefIterator {
+        ShaderDefIterator::new(self)
+    }
+}
+
+/// Drives an entity's [DitherFade] from its distance to the active 3d camera, fading it out as the
+/// camera gets closer. Useful for geometry that would otherwise obstruct the view.
+#[derive(Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct CameraProximityFade {
+    /// The distance at which the entity is fully faded out
+    pub near: f32,
+    /// The distance at which the entity starts fading out
+    pub far: f32,
+}
+
+impl Default for CameraProximityFade {
+    fn default() -> Self {
+        CameraProximityFade {
+            near: 1.0,
+            far: 3.0,
+        }
+    }
+}
+
+impl CameraProximityFade {
+    /// Returns the fade factor for an entity at the given distance from the camera
+    pub fn factor(&self, distance: f32) -> f32 {
+        if self.far <= self.near {
+            return if distance > self.near { 1.0 } else { 0.0 };
+        }
+        ((distance - self.near) / (self.far - self.near)).clamp(0.0, 1.0)
+    }
+}
+
+pub fn camera_proximity_fade_system(
+    active_cameras: Res<ActiveCameras>,
+    camera_query: Query<&GlobalTransform>,
+    mut query: Query<(&CameraProximityFade, &GlobalTransform, &mut DitherFade)>,
+) {
+    let camera_position = match active_cameras
+        .get(base::camera::CAMERA_3D)
+        .and_then(|camera| camera.entity)
+        .and_then(|entity| camera_query.get(entity).ok())
+    {
+        Some(camera_transform) => camera_transform.translation,
+        None => return,
+    };
+
+    for (proximity_fade, global_transform, mut dither_fade) in query.iter_mut() {
+        let distance = global_transform.translation.distance(camera_position);
+        let factor = proximity_fade.factor(distance);
+        // only write on change to avoid re-uploading the uniform every frame
+        if dither_fade.factor != factor {
+            dither_fade.factor = factor;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn proximity_fade_factor() {
+        let fade = CameraProximityFade {
+            near: 1.0,
+            far: 3.0,
+        };
+        assert_eq!(fade.factor(0.5), 0.0);
+        assert_eq!(fade.factor(2.0), 0.5);
+        assert_eq!(fade.factor(10.0), 1.0);
+    }
+
+    #[test]
+    fn dither_fade_shader_def() {
+        let defs = DitherFade::new(1.0).iter_shader_defs().collect::<Vec<_>>();
+        assert!(defs.is_empty());
+        let defs = DitherFade::new(0.25).iter_shader_defs().collect::<Vec<_>>();
+        assert_eq!(defs, vec![DITHER_FADE_SHADER_DEF]);
+    }
+}
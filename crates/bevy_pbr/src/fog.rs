@@ -0,0 +1,91 @@
+use bevy_core::Byteable;
+use bevy_ecs::reflect::ReflectComponent;
+use bevy_reflect::Reflect;
+use bevy_render::color::Color;
+
+/// How fog thickens with distance from the camera
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect_value(PartialEq)]
+pub enum FogMode {
+    /// No fog closer than `start`, full fog from `end`
+    Linear { start: f32, end: f32 },
+    /// Visibility falls off with `e^(-density * distance)`
+    Exponential { density: f32 },
+    /// Visibility falls off with `e^(-(density * distance)^2)`, which keeps nearby objects
+    /// clearer than [Exponential](FogMode::Exponential) fog
+    ExponentialSquared { density: f32 },
+}
+
+/// Blends the color of [StandardMaterial](crate::StandardMaterial) surfaces towards `color` with
+/// their distance to the camera.
+///
+/// Insert it as a resource to fog every camera, or add it to a camera to override the resource.
+/// The component is read from the
+/// [main_pass_camera](bevy_render::render_graph::base::main_pass_camera).
+#[derive(Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct DistanceFog {
+    pub color: Color,
+    pub mode: FogMode,
+}
+
+impl Default for DistanceFog {
+    fn default() -> Self {
+        DistanceFog {
+            color: Color::rgb(0.5, 0.5, 0.5),
+            mode: FogMode::Linear {
+                start: 10.0,
+                end: 100.0,
+            },
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct FogUniform {
+    pub color: [f32; 4],
+    // storing as `[_; 4]`s for memory alignment
+    pub mode: [u32; 4],
+    pub params: [f32; 4],
+}
+
+unsafe impl Byteable for FogUniform {}
+
+impl FogUniform {
+    pub fn from(fog: Option<&DistanceFog>) -> FogUniform {
+        // mode 0 disables fog
+        let (mode, params) = match fog.map(|fog| fog.mode) {
+            None => (0, [0.0; 4]),
+            Some(FogMode::Linear { start, end }) => (1, [start, end, 0.0, 0.0]),
+            Some(FogMode::Exponential { density }) => (2, [density, 0.0, 0.0, 0.0]),
+            Some(FogMode::ExponentialSquared { density }) => (3, [density, 0.0, 0.0, 0.0]),
+        };
+        FogUniform {
+            color: fog.map_or([0.0; 4], |fog| fog.color.as_linear_rgba_f32()),
+            mode: [mode, 0, 0, 0],
+            params,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_core::AsBytes;
+
+    #[test]
+    fn fog_uniform_matches_std140_layout() {
+        let uniform = FogUniform::from(Some(&DistanceFog {
+            color: Color::rgba_linear(0.1, 0.2, 0.3, 1.0),
+            mode: FogMode::Exponential { density: 0.5 },
+        }));
+        let bytes = uniform.as_bytes();
+
+        // vec4 color, uvec4 mode and vec4 params, each 16 byte aligned
+        assert_eq!(bytes.len(), 48);
+        assert_eq!(bytes[0..4], 0.1f32.to_ne_bytes());
+        assert_eq!(bytes[16..20], 2u32.to_ne_bytes());
+        assert_eq!(bytes[32..36], 0.5f32.to_ne_bytes());
+    }
+}
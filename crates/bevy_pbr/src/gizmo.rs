@@ -0,0 +1,587 @@
+use crate::{
+    entity::PbrBundle,
+    material::StandardMaterial,
+    render_graph::{build_gizmo_pipeline, GIZMO_PIPELINE_HANDLE},
+};
+use bevy_app::prelude::*;
+use bevy_asset::{Assets, Handle};
+use bevy_ecs::{
+    entity::Entity,
+    query::Without,
+    schedule::ParallelSystemDescriptorCoercion,
+    system::{Commands, IntoSystem, Query, Res, ResMut},
+    world::{FromWorld, World},
+};
+use bevy_input::{keyboard::KeyCode, mouse::MouseButton, Input};
+use bevy_math::{Plane, Quat, Vec2, Vec3};
+use bevy_render::{
+    camera::Camera,
+    color::Color,
+    draw::Visible,
+    mesh::Mesh,
+    picking::Ray,
+    pipeline::{PipelineDescriptor, PrimitiveTopology, RenderPipeline, RenderPipelines},
+    render_graph::base,
+    shader::Shader,
+};
+use bevy_transform::{
+    components::{GlobalTransform, Parent, Transform},
+    TransformSystem,
+};
+use bevy_window::Windows;
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+/// The number of segments of the circles of the rotation handles
+const CIRCLE_SEGMENTS: usize = 48;
+
+/// Adds handles to move, rotate and scale the [TransformGizmo::selected] entity with the mouse,
+/// along or around the x, y and z axes. The handles are grabbed with the left mouse button, and
+/// the mode is switched with the W, E and R keys.
+///
+/// The handles are drawn as lines through the 3d camera of the base render graph, on top of
+/// the meshes in front of them.
+#[derive(Default)]
+pub struct TransformGizmoPlugin;
+
+impl Plugin for TransformGizmoPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        let world = app.world_mut();
+        let pipeline =
+            build_gizmo_pipeline(&mut world.get_resource_mut::<Assets<Shader>>().unwrap());
+        world
+            .get_resource_mut::<Assets<PipelineDescriptor>>()
+            .unwrap()
+            .set_untracked(GIZMO_PIPELINE_HANDLE, pipeline);
+
+        app.init_resource::<TransformGizmo>()
+            .init_resource::<GizmoAssets>()
+            .add_startup_system(spawn_gizmo_handles_system.system())
+            .add_system(transform_gizmo_system.system())
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                gizmo_handle_system
+                    .system()
+                    .after(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GizmoMode {
+    /// Moves the entity along the world axes
+    Translate,
+    /// Rotates the entity around the world axes
+    Rotate,
+    /// Scales the entity along its own axes
+    Scale,
+}
+
+impl Default for GizmoMode {
+    fn default() -> Self {
+        GizmoMode::Translate
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TransformGizmo {
+    /// The entity whose [Transform] is edited. The handles are hidden when `None`.
+    pub selected: Option<Entity>,
+    pub mode: GizmoMode,
+    /// The length of the handles, relative to their distance to the camera so that they keep the
+    /// same size on screen
+    pub size: f32,
+    /// How close to a handle, in pixels, the cursor must be to grab it
+    pub grab_distance: f32,
+    /// The keys switching to the translate, rotate and scale modes
+    pub mode_keys: Option<[KeyCode; 3]>,
+    hovered_axis: Option<usize>,
+    drag: Option<GizmoDrag>,
+}
+
+impl Default for TransformGizmo {
+    fn default() -> Self {
+        TransformGizmo {
+            selected: None,
+            mode: GizmoMode::Translate,
+            size: 0.15,
+            grab_distance: 8.0,
+            mode_keys: Some([KeyCode::W, KeyCode::E, KeyCode::R]),
+            hovered_axis: None,
+            drag: None,
+        }
+    }
+}
+
+impl TransformGizmo {
+    /// The axis of the handle under the cursor or being dragged: 0 for x, 1 for y and 2 for z
+    pub fn hovered_axis(&self) -> Option<usize> {
+        self.drag
+            .as_ref()
+            .map(|drag| drag.axis_index)
+            .or(self.hovered_axis)
+    }
+
+    /// Whether a handle is being dragged. Other systems using the mouse, such as camera
+    /// controllers, can ignore it while this is `true`.
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+}
+
+/// A handle of the [TransformGizmo]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GizmoHandle {
+    pub mode: GizmoMode,
+    pub axis: usize,
+}
+
+pub struct GizmoAssets {
+    pub translate_mesh: Handle<Mesh>,
+    pub rotate_mesh: Handle<Mesh>,
+    pub scale_mesh: Handle<Mesh>,
+    /// The materials of the x, y and z handles. Only their base color is used.
+    pub axis_materials: [Handle<StandardMaterial>; 3],
+    /// The material of the hovered or dragged handle
+    pub hovered_material: Handle<StandardMaterial>,
+}
+
+fn line_mesh(lines: Vec<[Vec3; 2]>) -> Mesh {
+    let positions = lines
+        .iter()
+        .flat_map(|line| line.iter())
+        .map(|point| [point.x, point.y, point.z])
+        .collect::<Vec<_>>();
+    let mut mesh = Mesh::new(PrimitiveTopology::LineList);
+    mesh.set_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh
+}
+
+/// The handles are built along or around the x axis, with a length of 1
+fn handle_mesh(mode: GizmoMode) -> Mesh {
+    let mut lines = Vec::new();
+    match mode {
+        GizmoMode::Translate => {
+            lines.push([Vec3::ZERO, Vec3::X]);
+            for side in [Vec3::Y, -Vec3::Y, Vec3::Z, -Vec3::Z].iter() {
+                lines.push([Vec3::X, Vec3::new(0.85, 0.0, 0.0) + *side * 0.05]);
+            }
+        }
+        GizmoMode::Rotate => {
+            let point = |i: usize| {
+                let angle = i as f32 / CIRCLE_SEGMENTS as f32 * TAU;
+                Vec3::new(0.0, angle.cos(), angle.sin())
+            };
+            for i in 0..CIRCLE_SEGMENTS {
+                lines.push([point(i), point(i + 1)]);
+            }
+        }
+        GizmoMode::Scale => {
+            lines.push([Vec3::ZERO, Vec3::new(0.95, 0.0, 0.0)]);
+            let corner = |x: f32, y: f32, z: f32| Vec3::new(1.0 + x, y, z) - Vec3::X * 0.05;
+            let size = 0.05;
+            for &(a, b) in [(-1.0, -1.0), (-1.0, 1.0), (1.0, -1.0), (1.0, 1.0)].iter() {
+                let (a, b) = (a * size, b * size);
+                lines.push([corner(-size, a, b), corner(size, a, b)]);
+                lines.push([corner(a, -size, b), corner(a, size, b)]);
+                lines.push([corner(a, b, -size), corner(a, b, size)]);
+            }
+        }
+    }
+    line_mesh(lines)
+}
+
+impl FromWorld for GizmoAssets {
+    fn from_world(world: &mut World) -> Self {
+        let world = world.cell();
+        let mut meshes = world.get_resource_mut::<Assets<Mesh>>().unwrap();
+        let mut materials = world
+            .get_resource_mut::<Assets<StandardMaterial>>()
+            .unwrap();
+        let mut material = |color: Color| {
+            materials.add(StandardMaterial {
+                base_color: color,
+                ..Default::default()
+            })
+        };
+        GizmoAssets {
+            translate_mesh: meshes.add(handle_mesh(GizmoMode::Translate)),
+            rotate_mesh: meshes.add(handle_mesh(GizmoMode::Rotate)),
+            scale_mesh: meshes.add(handle_mesh(GizmoMode::Scale)),
+            axis_materials: [
+                material(Color::rgb(0.9, 0.2, 0.2)),
+                material(Color::rgb(0.2, 0.9, 0.2)),
+                material(Color::rgb(0.2, 0.4, 0.95)),
+            ],
+            hovered_material: material(Color::rgb(1.0, 0.9, 0.1)),
+        }
+    }
+}
+
+/// The rotation from the x axis to each axis
+fn axis_rotation(axis: usize) -> Quat {
+    match axis {
+        0 => Quat::IDENTITY,
+        1 => Quat::from_rotation_z(FRAC_PI_2),
+        _ => Quat::from_rotation_y(-FRAC_PI_2),
+    }
+}
+
+/// The world axes the handles are along or around
+fn gizmo_axes(mode: GizmoMode, target: &GlobalTransform) -> [Vec3; 3] {
+    let rotation = match mode {
+        GizmoMode::Translate | GizmoMode::Rotate => Quat::IDENTITY,
+        GizmoMode::Scale => target.rotation,
+    };
+    [rotation * Vec3::X, rotation * Vec3::Y, rotation * Vec3::Z]
+}
+
+/// The distance along the axis line to the point closest to the ray
+fn closest_on_axis(ray: &Ray, origin: Vec3, axis: Vec3) -> Option<f32> {
+    let offset = origin - ray.origin;
+    let alignment = axis.dot(ray.direction);
+    let denominator = 1.0 - alignment * alignment;
+    // the ray is parallel to the axis
+    if denominator < 1e-6 {
+        return None;
+    }
+    Some((alignment * offset.dot(ray.direction) - offset.dot(axis)) / denominator)
+}
+
+fn distance_to_segment(point: Vec2, start: Vec2, end: Vec2) -> f32 {
+    let segment = end - start;
+    let t = if segment.length_squared() > 0.0 {
+        ((point - start).dot(segment) / segment.length_squared())
+            .max(0.0)
+            .min(1.0)
+    } else {
+        0.0
+    };
+    point.distance(start + segment * t)
+}
+
+/// The point grabbed on a handle: on the axis line when moving or scaling, and on the plane of
+/// the circle when rotating
+fn grab_point(mode: GizmoMode, center: Vec3, axis: Vec3, ray: &Ray) -> Option<Vec3> {
+    match mode {
+        GizmoMode::Translate | GizmoMode::Scale => {
+            closest_on_axis(ray, center, axis).map(|distance| center + axis * distance)
+        }
+        GizmoMode::Rotate => {
+            Plane::from_point_normal(center, axis).intersect_ray(ray.origin, ray.direction)
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct GizmoDrag {
+    mode: GizmoMode,
+    axis_index: usize,
+    axis: Vec3,
+    center: Vec3,
+    start_point: Vec3,
+    start_transform: Transform,
+    /// The global transform of the parent of the entity, as its [Transform] is relative to it
+    parent: GlobalTransform,
+}
+
+impl GizmoDrag {
+    fn transform(&self, ray: &Ray) -> Option<Transform> {
+        let point = grab_point(self.mode, self.center, self.axis, ray)?;
+        let mut transform = self.start_transform;
+        match self.mode {
+            GizmoMode::Translate => {
+                let offset = point - self.start_point;
+                transform.translation += self
+                    .parent
+                    .compute_matrix()
+                    .inverse()
+                    .transform_vector3(offset);
+            }
+            GizmoMode::Rotate => {
+                let from = self.start_point - self.center;
+                let to = point - self.center;
+                if from.length_squared() < 1e-8 || to.length_squared() < 1e-8 {
+                    return None;
+                }
+                let angle = self.axis.dot(from.cross(to)).atan2(from.dot(to));
+                let axis = self.parent.rotation.inverse() * self.axis;
+                transform.rotation = Quat::from_axis_angle(axis, angle) * transform.rotation;
+            }
+            GizmoMode::Scale => {
+                let start = (self.start_point - self.center).dot(self.axis);
+                if start.abs() < 1e-6 {
+                    return None;
+                }
+                let factor = (point - self.center).dot(self.axis) / start;
+                match self.axis_index {
+                    0 => transform.scale.x *= factor,
+                    1 => transform.scale.y *= factor,
+                    _ => transform.scale.z *= factor,
+                }
+            }
+        }
+        Some(transform)
+    }
+}
+
+pub fn spawn_gizmo_handles_system(mut commands: Commands, assets: Res<GizmoAssets>) {
+    let modes = [
+        (GizmoMode::Translate, &assets.translate_mesh),
+        (GizmoMode::Rotate, &assets.rotate_mesh),
+        (GizmoMode::Scale, &assets.scale_mesh),
+    ];
+    for (mode, mesh) in modes.iter() {
+        for axis in 0..3 {
+            commands
+                .spawn_bundle(PbrBundle {
+                    mesh: (*mesh).clone(),
+                    material: assets.axis_materials[axis].clone(),
+                    render_pipelines: RenderPipelines::from_pipelines(vec![RenderPipeline::new(
+                        GIZMO_PIPELINE_HANDLE.typed(),
+                    )]),
+                    // transparent entities are drawn after opaque ones, so the handles, which
+                    // ignore the depth buffer, are drawn over them
+                    visible: Visible {
+                        is_visible: false,
+                        is_transparent: true,
+                    },
+                    ..Default::default()
+                })
+                .insert(GizmoHandle { mode: *mode, axis });
+        }
+    }
+}
+
+fn camera_3d<'a>(
+    cameras: &'a Query<(&Camera, &GlobalTransform), Without<GizmoHandle>>,
+) -> Option<(&'a Camera, &'a GlobalTransform)> {
+    cameras
+        .iter()
+        .find(|(camera, _)| camera.name.as_deref() == Some(base::camera::CAMERA_3D))
+}
+
+/// Switches the mode, grabs the handle under the cursor and moves, rotates or scales the selected
+/// entity while a handle is dragged
+#[allow(clippy::too_many_arguments)]
+pub fn transform_gizmo_system(
+    mut gizmo: ResMut<TransformGizmo>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    cameras: Query<(&Camera, &GlobalTransform), Without<GizmoHandle>>,
+    mut targets: Query<(&mut Transform, &GlobalTransform, Option<&Parent>), Without<GizmoHandle>>,
+    parents: Query<&GlobalTransform, Without<GizmoHandle>>,
+) {
+    if let (Some(mode_keys), false) = (gizmo.mode_keys, gizmo.is_dragging()) {
+        let modes = [GizmoMode::Translate, GizmoMode::Rotate, GizmoMode::Scale];
+        for (key, mode) in mode_keys.iter().zip(modes.iter()) {
+            if keyboard_input.just_pressed(*key) {
+                gizmo.mode = *mode;
+            }
+        }
+    }
+
+    gizmo.hovered_axis = None;
+    let target = gizmo
+        .selected
+        .and_then(|selected| targets.get_mut(selected).ok());
+    let (mut transform, target_transform, parent) = match target {
+        Some(target) => target,
+        None => {
+            gizmo.drag = None;
+            return;
+        }
+    };
+    let ray = camera_3d(&cameras).and_then(|(camera, camera_transform)| {
+        let cursor = windows.get(camera.window)?.cursor_position()?;
+        let ray = Ray::from_screen(camera, camera_transform, &windows, cursor)?;
+        Some((camera, camera_transform, cursor, ray))
+    });
+    let (camera, camera_transform, cursor, ray) = match ray {
+        Some(ray) => ray,
+        None => return,
+    };
+
+    if gizmo.is_dragging() {
+        if mouse_input.pressed(MouseButton::Left) {
+            if let Some(dragged) = gizmo.drag.as_ref().and_then(|drag| drag.transform(&ray)) {
+                *transform = dragged;
+            }
+        } else {
+            gizmo.drag = None;
+        }
+        return;
+    }
+
+    let center = target_transform.translation;
+    let length = gizmo.size * camera_transform.translation.distance(center);
+    let axes = gizmo_axes(gizmo.mode, target_transform);
+    let to_screen = |point: Vec3| camera.world_to_screen(&windows, camera_transform, point);
+    let mut hovered: Option<(usize, f32)> = None;
+    for (i, axis) in axes.iter().enumerate() {
+        let points = match gizmo.mode {
+            GizmoMode::Translate | GizmoMode::Scale => vec![center, center + *axis * length],
+            GizmoMode::Rotate => {
+                let (u, v) = (axes[(i + 1) % 3], axes[(i + 2) % 3]);
+                (0..=CIRCLE_SEGMENTS)
+                    .map(|segment| {
+                        let angle = segment as f32 / CIRCLE_SEGMENTS as f32 * TAU;
+                        center + (u * angle.cos() + v * angle.sin()) * length
+                    })
+                    .collect()
+            }
+        };
+        let points = match points
+            .into_iter()
+            .map(to_screen)
+            .collect::<Option<Vec<_>>>()
+        {
+            Some(points) => points,
+            // the handle is partly behind the camera
+            None => continue,
+        };
+        let distance = points
+            .windows(2)
+            .map(|segment| distance_to_segment(cursor, segment[0], segment[1]))
+            .fold(f32::INFINITY, f32::min);
+        if distance <= gizmo.grab_distance
+            && hovered.map_or(true, |(_, closest)| distance < closest)
+        {
+            hovered = Some((i, distance));
+        }
+    }
+    gizmo.hovered_axis = hovered.map(|(axis, _)| axis);
+
+    if let (Some(axis_index), true) = (
+        gizmo.hovered_axis,
+        mouse_input.just_pressed(MouseButton::Left),
+    ) {
+        let axis = axes[axis_index];
+        if let Some(start_point) = grab_point(gizmo.mode, center, axis, &ray) {
+            let parent = parent
+                .and_then(|parent| parents.get(parent.0).ok())
+                .copied()
+                .unwrap_or_else(GlobalTransform::identity);
+            gizmo.drag = Some(GizmoDrag {
+                mode: gizmo.mode,
+                axis_index,
+                axis,
+                center,
+                start_point,
+                start_transform: *transform,
+                parent,
+            });
+        }
+    }
+}
+
+/// Places the handles of the current mode on the selected entity, and highlights the hovered one
+pub fn gizmo_handle_system(
+    gizmo: Res<TransformGizmo>,
+    assets: Res<GizmoAssets>,
+    cameras: Query<(&Camera, &GlobalTransform), Without<GizmoHandle>>,
+    targets: Query<&GlobalTransform, Without<GizmoHandle>>,
+    mut handles: Query<(
+        &GizmoHandle,
+        &mut Transform,
+        &mut GlobalTransform,
+        &mut Visible,
+        &mut Handle<StandardMaterial>,
+    )>,
+) {
+    let target = gizmo
+        .selected
+        .and_then(|selected| targets.get(selected).ok());
+    let camera = camera_3d(&cameras);
+    for (handle, mut transform, mut global_transform, mut visible, mut material) in
+        handles.iter_mut()
+    {
+        let (target, (_, camera_transform)) = match (target, camera) {
+            (Some(target), Some(camera)) if handle.mode == gizmo.mode => (target, camera),
+            _ => {
+                if visible.is_visible {
+                    visible.is_visible = false;
+                }
+                continue;
+            }
+        };
+        let rotation = match handle.mode {
+            GizmoMode::Translate | GizmoMode::Rotate => Quat::IDENTITY,
+            GizmoMode::Scale => target.rotation,
+        };
+        let length = gizmo.size * camera_transform.translation.distance(target.translation);
+        *transform = Transform {
+            translation: target.translation,
+            rotation: rotation * axis_rotation(handle.axis),
+            scale: Vec3::splat(length),
+        };
+        // the handles are placed after the transforms are propagated, to follow the entity in
+        // the same frame
+        *global_transform = GlobalTransform::from(*transform);
+        if !visible.is_visible {
+            visible.is_visible = true;
+        }
+        let wanted_material = if gizmo.hovered_axis() == Some(handle.axis) {
+            &assets.hovered_material
+        } else {
+            &assets.axis_materials[handle.axis]
+        };
+        if *material != *wanted_material {
+            *material = wanted_material.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn drag(mode: GizmoMode, axis_index: usize, axis: Vec3, start_ray: &Ray) -> GizmoDrag {
+        GizmoDrag {
+            mode,
+            axis_index,
+            axis,
+            center: Vec3::ZERO,
+            start_point: grab_point(mode, Vec3::ZERO, axis, start_ray).unwrap(),
+            start_transform: Transform::identity(),
+            parent: GlobalTransform::from_translation(Vec3::new(5.0, 0.0, 0.0)),
+        }
+    }
+
+    #[test]
+    fn drags_along_and_around_axes() {
+        // looking down from above the x axis
+        let ray_at = |x: f32, z: f32| Ray::new(Vec3::new(x, 10.0, z), -Vec3::Y);
+
+        let translate = drag(GizmoMode::Translate, 0, Vec3::X, &ray_at(1.0, 0.0));
+        let moved = translate.transform(&ray_at(3.0, 2.0)).unwrap();
+        assert!(moved
+            .translation
+            .abs_diff_eq(Vec3::new(2.0, 0.0, 0.0), 1e-5));
+
+        let scale = drag(GizmoMode::Scale, 0, Vec3::X, &ray_at(1.0, 0.0));
+        let scaled = scale.transform(&ray_at(3.0, 0.0)).unwrap();
+        assert!(scaled.scale.abs_diff_eq(Vec3::new(3.0, 1.0, 1.0), 1e-5));
+
+        let rotate = drag(GizmoMode::Rotate, 1, Vec3::Y, &ray_at(1.0, 0.0));
+        let rotated = rotate.transform(&ray_at(0.0, -1.0)).unwrap();
+        // a quarter turn counterclockwise seen from above
+        assert!((rotated.rotation * Vec3::X).abs_diff_eq(-Vec3::Z, 1e-5));
+
+        // a ray along the axis doesn't move the entity
+        let along_axis = Ray::new(Vec3::new(-10.0, 0.0, 0.0), Vec3::X);
+        assert!(translate.transform(&along_axis).is_none());
+    }
+
+    #[test]
+    fn measures_distance_to_segments() {
+        let start = Vec2::new(0.0, 0.0);
+        let end = Vec2::new(10.0, 0.0);
+        assert_eq!(distance_to_segment(Vec2::new(5.0, 3.0), start, end), 3.0);
+        assert_eq!(distance_to_segment(Vec2::new(14.0, 3.0), start, end), 5.0);
+        assert_eq!(
+            distance_to_segment(Vec2::new(1.0, 1.0), start, start),
+            2f32.sqrt()
+        );
+    }
+}
@@ -1,24 +1,55 @@
 pub mod render_graph;
 
+mod clusters;
 mod entity;
+mod fade;
+mod fog;
+mod gizmo;
 mod light;
 mod material;
+mod material_overrides;
+mod terrain;
 
+pub use clusters::*;
 pub use entity::*;
+pub use fade::*;
+pub use fog::*;
+pub use gizmo::*;
 pub use light::*;
 pub use material::*;
+pub use material_overrides::*;
+pub use terrain::*;
 
 pub mod prelude {
-    pub use crate::{entity::*, light::PointLight, material::StandardMaterial};
+    pub use crate::{
+        clusters::{ClusterConfig, ClusteredLightingPlugin},
+        entity::*,
+        fade::{CameraProximityFade, DitherFade},
+        fog::{DistanceFog, FogMode},
+        gizmo::{GizmoMode, TransformGizmo, TransformGizmoPlugin},
+        light::PointLight,
+        material::StandardMaterial,
+        material_overrides::MaterialOverrides,
+        terrain::{Heightmap, Terrain, TerrainBundle, TerrainMaterial, TerrainPlugin},
+    };
 }
 
 use bevy_app::prelude::*;
 use bevy_asset::{AddAsset, Assets, Handle};
-use bevy_ecs::system::IntoSystem;
+use bevy_ecs::{
+    schedule::{ParallelSystemDescriptorCoercion, SystemLabel},
+    system::IntoSystem,
+};
 use bevy_render::{prelude::Color, shader};
+use bevy_transform::TransformSystem;
 use material::StandardMaterial;
 use render_graph::add_pbr_graph;
 
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub enum PbrSystem {
+    CameraProximityFade,
+}
+
 /// NOTE: this isn't PBR yet. consider this name "aspirational" :)
 #[derive(Default)]
 pub struct PbrPlugin;
@@ -27,10 +58,32 @@ impl Plugin for PbrPlugin {
     fn build(&self, app: &mut AppBuilder) {
         app.add_asset::<StandardMaterial>()
             .register_type::<PointLight>()
+            .register_type::<DitherFade>()
+            .register_type::<MaterialOverrides>()
+            .register_type::<CameraProximityFade>()
+            .register_type::<DistanceFog>()
+            .register_type::<FogMode>()
             .add_system_to_stage(
                 CoreStage::PostUpdate,
                 shader::asset_shader_defs_system::<StandardMaterial>.system(),
             )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                camera_proximity_fade_system
+                    .system()
+                    .label(PbrSystem::CameraProximityFade)
+                    .after(TransformSystem::TransformPropagate),
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                shader::shader_defs_system::<DitherFade>
+                    .system()
+                    .after(PbrSystem::CameraProximityFade),
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                shader::shader_defs_system::<MaterialOverrides>.system(),
+            )
             .init_resource::<AmbientLight>();
         add_pbr_graph(app.world_mut());
 
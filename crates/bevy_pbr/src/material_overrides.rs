@@ -0,0 +1,89 @@
+use bevy_ecs::reflect::ReflectComponent;
+use bevy_reflect::Reflect;
+use bevy_render::{
+    color::Color,
+    renderer::RenderResources,
+    shader::{ShaderDefIterator, ShaderDefs},
+};
+
+/// Adjusts the material of a single entity without creating a new material.
+///
+/// The overrides are uploaded with the entity's transform instead of the material, so entities
+/// sharing a material still share its bind group and are batched together.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect, RenderResources)]
+#[reflect(Component)]
+pub struct MaterialOverrides {
+    /// Multiplies the base color of the material, including its alpha
+    pub tint: Color,
+    /// Multiplies the emissive color of the material
+    pub emissive_strength: f32,
+}
+
+impl Default for MaterialOverrides {
+    fn default() -> Self {
+        MaterialOverrides {
+            tint: Color::WHITE,
+            emissive_strength: 1.0,
+        }
+    }
+}
+
+impl MaterialOverrides {
+    pub fn tint(tint: Color) -> Self {
+        MaterialOverrides {
+            tint,
+            ..Default::default()
+        }
+    }
+
+    pub fn emissive_strength(emissive_strength: f32) -> Self {
+        MaterialOverrides {
+            emissive_strength,
+            ..Default::default()
+        }
+    }
+
+    /// Returns true if the overrides leave the material unchanged
+    pub fn is_identity(&self) -> bool {
+        *self == MaterialOverrides::default()
+    }
+}
+
+const MATERIAL_OVERRIDES_SHADER_DEF: &str = "MATERIALOVERRIDES";
+
+/// Like [DitherFade](crate::DitherFade), the shader def is only defined while the overrides
+/// change something
+impl ShaderDefs for MaterialOverrides {
+    fn shader_defs_len(&self) -> usize {
+        1
+    }
+
+    fn get_shader_def(&self, index: usize) -> Option<&str> {
+        if index == 0 && !self.is_identity() {
+            Some(MATERIAL_OVERRIDES_SHADER_DEF)
+        } else {
+            None
+        }
+    }
+
+    fn iter_shader_defs(&self) -> ShaderDefIterator {
+        ShaderDefIterator::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn material_overrides_shader_def() {
+        let defs = MaterialOverrides::default()
+            .iter_shader_defs()
+            .collect::<Vec<_>>();
+        assert!(defs.is_empty());
+        let defs = MaterialOverrides::emissive_strength(2.0)
+            .iter_shader_defs()
+            .collect::<Vec<_>>();
+        assert_eq!(defs, vec![MATERIAL_OVERRIDES_SHADER_DEF]);
+    }
+}
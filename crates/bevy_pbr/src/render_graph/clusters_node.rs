@@ -0,0 +1,222 @@
+use crate::{
+    clusters::{assign_lights_to_clusters, ClusterConfig, ClustersUniform},
+    light::{PointLight, PointLightUniform},
+    render_graph::uniform,
+};
+use bevy_core::AsBytes;
+use bevy_ecs::{
+    system::{BoxedSystem, IntoSystem, Local, Query, Res, ResMut},
+    world::World,
+};
+use bevy_math::Mat4;
+use bevy_render::{
+    camera::{ActiveCameras, Camera},
+    render_graph::{base::main_pass_camera, CommandQueue, Node, ResourceSlots, SystemNode},
+    renderer::{
+        BufferId, BufferInfo, BufferMapMode, BufferUsage, RenderContext, RenderResourceBinding,
+        RenderResourceBindings, RenderResourceContext,
+    },
+};
+use bevy_transform::prelude::*;
+use bevy_window::Windows;
+
+/// A Render Graph [Node] that assigns point lights to the clusters of the main pass camera and
+/// writes the clusters and their lights to GPU buffers
+#[derive(Debug, Default)]
+pub struct ClustersNode {
+    command_queue: CommandQueue,
+}
+
+impl Node for ClustersNode {
+    fn update(
+        &mut self,
+        _world: &World,
+        render_context: &mut dyn RenderContext,
+        _input: &ResourceSlots,
+        _output: &mut ResourceSlots,
+    ) {
+        self.command_queue.execute(render_context);
+    }
+}
+
+impl SystemNode for ClustersNode {
+    fn get_system(&self) -> BoxedSystem {
+        let system = clusters_node_system.system().config(|config| {
+            config.0 = Some(ClustersNodeSystemState {
+                command_queue: self.command_queue.clone(),
+                ..Default::default()
+            })
+        });
+        Box::new(system)
+    }
+}
+
+/// A GPU buffer and its staging buffer, recreated when the data outgrows them
+#[derive(Debug, Default)]
+struct ClusterBuffer {
+    buffer: Option<BufferId>,
+    staging_buffer: Option<BufferId>,
+    capacity: usize,
+}
+
+impl ClusterBuffer {
+    fn write(
+        &mut self,
+        name: &str,
+        buffer_usage: BufferUsage,
+        data: &[u8],
+        render_resource_context: &dyn RenderResourceContext,
+        render_resource_bindings: &mut RenderResourceBindings,
+        command_queue: &mut CommandQueue,
+    ) {
+        // empty buffers can't be bound
+        let size = data.len().max(16);
+        if let (Some(staging_buffer), true) = (self.staging_buffer, size <= self.capacity) {
+            render_resource_context.map_buffer(staging_buffer, BufferMapMode::Write);
+        } else {
+            if let Some(buffer) = self.buffer.take() {
+                render_resource_context.remove_buffer(buffer);
+            }
+            if let Some(staging_buffer) = self.staging_buffer.take() {
+                render_resource_context.remove_buffer(staging_buffer);
+            }
+            self.capacity = size.next_power_of_two();
+
+            let buffer = render_resource_context.create_buffer(BufferInfo {
+                size: self.capacity,
+                buffer_usage: buffer_usage | BufferUsage::COPY_DST,
+                ..Default::default()
+            });
+            render_resource_bindings.set(
+                name,
+                RenderResourceBinding::Buffer {
+                    buffer,
+                    range: 0..self.capacity as u64,
+                    dynamic_index: None,
+                },
+            );
+            self.buffer = Some(buffer);
+            self.staging_buffer = Some(render_resource_context.create_buffer(BufferInfo {
+                size: self.capacity,
+                buffer_usage: BufferUsage::COPY_SRC | BufferUsage::MAP_WRITE,
+                mapped_at_creation: true,
+            }));
+        }
+
+        let staging_buffer = self.staging_buffer.unwrap();
+        render_resource_context.write_mapped_buffer(
+            staging_buffer,
+            0..data.len() as u64,
+            &mut |mapped, _renderer| {
+                mapped[0..data.len()].copy_from_slice(data);
+            },
+        );
+        render_resource_context.unmap_buffer(staging_buffer);
+        if !data.is_empty() {
+            command_queue.copy_buffer_to_buffer(
+                staging_buffer,
+                0,
+                self.buffer.unwrap(),
+                0,
+                data.len() as u64,
+            );
+        }
+    }
+}
+
+/// Local "clusters node system" state
+#[derive(Debug, Default)]
+pub struct ClustersNodeSystemState {
+    clusters: ClusterBuffer,
+    lights: ClusterBuffer,
+    light_indices: ClusterBuffer,
+    offsets: ClusterBuffer,
+    command_queue: CommandQueue,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn clusters_node_system(
+    mut state: Local<ClustersNodeSystemState>,
+    render_resource_context: Res<Box<dyn RenderResourceContext>>,
+    config: Res<ClusterConfig>,
+    active_cameras: Res<ActiveCameras>,
+    windows: Res<Windows>,
+    mut render_resource_bindings: ResMut<RenderResourceBindings>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    lights: Query<(&PointLight, &GlobalTransform)>,
+) {
+    let state = &mut *state;
+    let render_resource_context = &**render_resource_context;
+
+    let camera = main_pass_camera(&active_cameras).and_then(|entity| cameras.get(entity).ok());
+    let (view, projection, target_size) = match camera {
+        Some((camera, global_transform)) => {
+            let target_size = windows
+                .get(camera.window)
+                .map(|window| {
+                    (
+                        window.physical_width() as f32,
+                        window.physical_height() as f32,
+                    )
+                })
+                .unwrap_or((1.0, 1.0));
+            (
+                global_transform.compute_matrix().inverse(),
+                camera.projection_matrix,
+                target_size,
+            )
+        }
+        None => (Mat4::IDENTITY, Mat4::IDENTITY, (1.0, 1.0)),
+    };
+
+    let cluster_lights = assign_lights_to_clusters(
+        &config,
+        &view,
+        &projection,
+        lights
+            .iter()
+            .map(|(light, global_transform)| (global_transform.translation, light.range)),
+    );
+    let point_lights = lights
+        .iter()
+        .map(|(light, global_transform)| PointLightUniform::from(light, global_transform))
+        .collect::<Vec<_>>();
+    let clusters_uniform = ClustersUniform::new(&config, &view, target_size);
+
+    let writes: [(&mut ClusterBuffer, &str, BufferUsage, &[u8]); 4] = [
+        (
+            &mut state.clusters,
+            uniform::CLUSTERS,
+            BufferUsage::UNIFORM,
+            clusters_uniform.as_bytes(),
+        ),
+        (
+            &mut state.lights,
+            uniform::CLUSTER_LIGHTS,
+            BufferUsage::STORAGE,
+            point_lights.as_bytes(),
+        ),
+        (
+            &mut state.light_indices,
+            uniform::CLUSTER_LIGHT_INDICES,
+            BufferUsage::STORAGE,
+            cluster_lights.indices.as_bytes(),
+        ),
+        (
+            &mut state.offsets,
+            uniform::CLUSTER_OFFSETS,
+            BufferUsage::STORAGE,
+            cluster_lights.offsets_and_counts.as_bytes(),
+        ),
+    ];
+    for (buffer, name, buffer_usage, data) in writes.iter_mut() {
+        buffer.write(
+            name,
+            *buffer_usage,
+            data,
+            render_resource_context,
+            &mut render_resource_bindings,
+            &mut state.command_queue,
+        );
+    }
+}
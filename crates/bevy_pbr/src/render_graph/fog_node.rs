@@ -0,0 +1,118 @@
+use crate::{
+    fog::{DistanceFog, FogUniform},
+    render_graph::uniform,
+};
+use bevy_core::AsBytes;
+use bevy_ecs::{
+    system::{BoxedSystem, IntoSystem, Local, Query, Res, ResMut},
+    world::World,
+};
+use bevy_render::{
+    camera::ActiveCameras,
+    render_graph::{base::main_pass_camera, CommandQueue, Node, ResourceSlots, SystemNode},
+    renderer::{
+        BufferId, BufferInfo, BufferMapMode, BufferUsage, RenderContext, RenderResourceBinding,
+        RenderResourceBindings, RenderResourceContext,
+    },
+};
+
+/// A Render Graph [Node] that writes the [DistanceFog] of the main pass camera, or the
+/// [DistanceFog] resource, to a GPU buffer
+#[derive(Debug, Default)]
+pub struct FogNode {
+    command_queue: CommandQueue,
+}
+
+impl Node for FogNode {
+    fn update(
+        &mut self,
+        _world: &World,
+        render_context: &mut dyn RenderContext,
+        _input: &ResourceSlots,
+        _output: &mut ResourceSlots,
+    ) {
+        self.command_queue.execute(render_context);
+    }
+}
+
+impl SystemNode for FogNode {
+    fn get_system(&self) -> BoxedSystem {
+        let system = fog_node_system.system().config(|config| {
+            config.0 = Some(FogNodeSystemState {
+                command_queue: self.command_queue.clone(),
+                fog_buffer: None,
+                staging_buffer: None,
+            })
+        });
+        Box::new(system)
+    }
+}
+
+/// Local "fog node system" state
+#[derive(Debug, Default)]
+pub struct FogNodeSystemState {
+    fog_buffer: Option<BufferId>,
+    staging_buffer: Option<BufferId>,
+    command_queue: CommandQueue,
+}
+
+pub fn fog_node_system(
+    mut state: Local<FogNodeSystemState>,
+    render_resource_context: Res<Box<dyn RenderResourceContext>>,
+    active_cameras: Res<ActiveCameras>,
+    fog_resource: Option<Res<DistanceFog>>,
+    mut render_resource_bindings: ResMut<RenderResourceBindings>,
+    query: Query<&DistanceFog>,
+) {
+    let state = &mut state;
+    let render_resource_context = &**render_resource_context;
+
+    let fog = main_pass_camera(&active_cameras)
+        .and_then(|entity| query.get(entity).ok())
+        .or_else(|| fog_resource.as_deref());
+    let fog_uniform = FogUniform::from(fog);
+    let size = std::mem::size_of::<FogUniform>();
+
+    if let Some(staging_buffer) = state.staging_buffer {
+        render_resource_context.map_buffer(staging_buffer, BufferMapMode::Write);
+    } else {
+        let buffer = render_resource_context.create_buffer(BufferInfo {
+            size,
+            buffer_usage: BufferUsage::UNIFORM | BufferUsage::COPY_DST,
+            ..Default::default()
+        });
+        render_resource_bindings.set(
+            uniform::FOG,
+            RenderResourceBinding::Buffer {
+                buffer,
+                range: 0..size as u64,
+                dynamic_index: None,
+            },
+        );
+        state.fog_buffer = Some(buffer);
+
+        let staging_buffer = render_resource_context.create_buffer(BufferInfo {
+            size,
+            buffer_usage: BufferUsage::COPY_SRC | BufferUsage::MAP_WRITE,
+            mapped_at_creation: true,
+        });
+        state.staging_buffer = Some(staging_buffer);
+    }
+
+    let staging_buffer = state.staging_buffer.unwrap();
+    render_resource_context.write_mapped_buffer(
+        staging_buffer,
+        0..size as u64,
+        &mut |data, _renderer| {
+            data[0..size].copy_from_slice(fog_uniform.as_bytes());
+        },
+    );
+    render_resource_context.unmap_buffer(staging_buffer);
+    state.command_queue.copy_buffer_to_buffer(
+        staging_buffer,
+        0,
+        state.fog_buffer.unwrap(),
+        0,
+        size as u64,
+    );
+}
@@ -0,0 +1,11 @@
+#version 450
+
+layout(location = 0) out vec4 o_Target;
+
+layout(set = 2, binding = 0) uniform StandardMaterial_base_color {
+    vec4 base_color;
+};
+
+void main() {
+    o_Target = base_color;
+}
@@ -0,0 +1,46 @@
+use bevy_asset::{Assets, HandleUntyped};
+use bevy_reflect::TypeUuid;
+use bevy_render::{
+    pipeline::{
+        BlendMode, ColorTargetState, CompareFunction, DepthBiasState, DepthStencilState,
+        PipelineDescriptor, StencilState,
+    },
+    shader::{Shader, ShaderStage, ShaderStages},
+    texture::TextureFormat,
+};
+
+pub const GIZMO_PIPELINE_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(PipelineDescriptor::TYPE_UUID, 7016942180541523287);
+
+/// Draws unlit lines in the base color of their [StandardMaterial](crate::StandardMaterial) over
+/// everything else in the main pass, ignoring the depth buffer
+pub(crate) fn build_gizmo_pipeline(shaders: &mut Assets<Shader>) -> PipelineDescriptor {
+    PipelineDescriptor {
+        depth_stencil: Some(DepthStencilState {
+            format: TextureFormat::Depth32Float,
+            depth_write_enabled: false,
+            depth_compare: CompareFunction::Always,
+            stencil: StencilState::IGNORE,
+            bias: DepthBiasState {
+                constant: 0,
+                slope_scale: 0.0,
+                clamp: 0.0,
+            },
+            clamp_depth: false,
+        }),
+        color_target_states: vec![ColorTargetState::new(
+            TextureFormat::default(),
+            BlendMode::Alpha,
+        )],
+        ..PipelineDescriptor::new(ShaderStages {
+            vertex: shaders.add(Shader::from_glsl(
+                ShaderStage::Vertex,
+                include_str!("gizmo.vert"),
+            )),
+            fragment: Some(shaders.add(Shader::from_glsl(
+                ShaderStage::Fragment,
+                include_str!("gizmo.frag"),
+            ))),
+        })
+    }
+}
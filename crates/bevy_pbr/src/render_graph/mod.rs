@@ -1,7 +1,13 @@
+mod clusters_node;
+mod fog_node;
+mod gizmo_pipeline;
 mod lights_node;
 mod pbr_pipeline;
 
 use bevy_ecs::world::World;
+pub use clusters_node::*;
+pub use fog_node::*;
+pub use gizmo_pipeline::*;
 pub use lights_node::*;
 pub use pbr_pipeline::*;
 
@@ -9,15 +15,25 @@ pub use pbr_pipeline::*;
 pub mod node {
     pub const TRANSFORM: &str = "transform";
     pub const STANDARD_MATERIAL: &str = "standard_material";
+    pub const DITHER_FADE: &str = "dither_fade";
+    pub const MATERIAL_OVERRIDES: &str = "material_overrides";
     pub const LIGHTS: &str = "lights";
+    pub const FOG: &str = "fog";
+    pub const CLUSTERS: &str = "clusters";
+    pub const TERRAIN_MATERIAL: &str = "terrain_material";
 }
 
 /// the names of pbr uniforms
 pub mod uniform {
     pub const LIGHTS: &str = "Lights";
+    pub const FOG: &str = "Fog";
+    pub const CLUSTERS: &str = "Clusters";
+    pub const CLUSTER_LIGHTS: &str = "ClusterLights";
+    pub const CLUSTER_LIGHT_INDICES: &str = "ClusterLightIndices";
+    pub const CLUSTER_OFFSETS: &str = "ClusterOffsets";
 }
 
-use crate::prelude::StandardMaterial;
+use crate::prelude::{DitherFade, MaterialOverrides, StandardMaterial};
 use bevy_asset::Assets;
 use bevy_render::{
     pipeline::PipelineDescriptor,
@@ -38,8 +54,17 @@ pub(crate) fn add_pbr_graph(world: &mut World) {
             node::STANDARD_MATERIAL,
             AssetRenderResourcesNode::<StandardMaterial>::new(true),
         );
+        graph.add_system_node(
+            node::DITHER_FADE,
+            RenderResourcesNode::<DitherFade>::new(true),
+        );
+        graph.add_system_node(
+            node::MATERIAL_OVERRIDES,
+            RenderResourcesNode::<MaterialOverrides>::new(true),
+        );
 
         graph.add_system_node(node::LIGHTS, LightsNode::new(MAX_POINT_LIGHTS));
+        graph.add_system_node(node::FOG, FogNode::default());
 
         // TODO: replace these with "autowire" groups
         graph
@@ -48,9 +73,18 @@ pub(crate) fn add_pbr_graph(world: &mut World) {
         graph
             .add_node_edge(node::TRANSFORM, base::node::MAIN_PASS)
             .unwrap();
+        graph
+            .add_node_edge(node::DITHER_FADE, base::node::MAIN_PASS)
+            .unwrap();
+        graph
+            .add_node_edge(node::MATERIAL_OVERRIDES, base::node::MAIN_PASS)
+            .unwrap();
         graph
             .add_node_edge(node::LIGHTS, base::node::MAIN_PASS)
             .unwrap();
+        graph
+            .add_node_edge(node::FOG, base::node::MAIN_PASS)
+            .unwrap();
     }
     let pipeline = build_pbr_pipeline(&mut world.get_resource_mut::<Assets<Shader>>().unwrap());
     let mut pipelines = world
@@ -1,9 +1,10 @@
 use bevy_asset::{Assets, HandleUntyped};
 use bevy_reflect::TypeUuid;
 use bevy_render::{
+    billboard::BILLBOARD_GLSL,
     pipeline::{
-        BlendFactor, BlendOperation, BlendState, ColorTargetState, ColorWrite, CompareFunction,
-        DepthBiasState, DepthStencilState, PipelineDescriptor, StencilFaceState, StencilState,
+        BlendMode, ColorTargetState, CompareFunction, DepthBiasState, DepthStencilState,
+        PipelineDescriptor, StencilState,
     },
     shader::{Shader, ShaderStage, ShaderStages},
     texture::TextureFormat,
@@ -18,12 +19,7 @@ pub(crate) fn build_pbr_pipeline(shaders: &mut Assets<Shader>) -> PipelineDescri
             format: TextureFormat::Depth32Float,
             depth_write_enabled: true,
             depth_compare: CompareFunction::Less,
-            stencil: StencilState {
-                front: StencilFaceState::IGNORE,
-                back: StencilFaceState::IGNORE,
-                read_mask: 0,
-                write_mask: 0,
-            },
+            stencil: StencilState::IGNORE,
             bias: DepthBiasState {
                 constant: 0,
                 slope_scale: 0.0,
@@ -31,24 +27,16 @@ pub(crate) fn build_pbr_pipeline(shaders: &mut Assets<Shader>) -> PipelineDescri
             },
             clamp_depth: false,
         }),
-        color_target_states: vec![ColorTargetState {
-            format: TextureFormat::default(),
-            color_blend: BlendState {
-                src_factor: BlendFactor::SrcAlpha,
-                dst_factor: BlendFactor::OneMinusSrcAlpha,
-                operation: BlendOperation::Add,
-            },
-            alpha_blend: BlendState {
-                src_factor: BlendFactor::One,
-                dst_factor: BlendFactor::One,
-                operation: BlendOperation::Add,
-            },
-            write_mask: ColorWrite::ALL,
-        }],
+        color_target_states: vec![ColorTargetState::new(
+            TextureFormat::default(),
+            BlendMode::Alpha,
+        )],
         ..PipelineDescriptor::new(ShaderStages {
-            vertex: shaders.add(Shader::from_glsl(
+            vertex: shaders.add(Shader::from_glsl_with_includes(
                 ShaderStage::Vertex,
+                "bevy_pbr/pbr.vert",
                 include_str!("pbr.vert"),
+                &[BILLBOARD_GLSL],
             )),
             fragment: Some(shaders.add(Shader::from_glsl(
                 ShaderStage::Fragment,
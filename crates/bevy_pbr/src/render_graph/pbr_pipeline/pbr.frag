@@ -1,5 +1,10 @@
 #version 450
 
+// normal maps need the tangents of the mesh
+#if defined(STANDARDMATERIAL_NORMAL_MAP) && defined(VERTEX_TANGENTS)
+#    define NORMAL_MAPPING
+#endif
+
 // From the Filament design doc
 // https://google.github.io/filament/Filament.html#table_symbols
 // Symbol Definition
@@ -46,10 +51,18 @@ layout(location = 0) in vec3 v_WorldPosition;
 layout(location = 1) in vec3 v_WorldNormal;
 layout(location = 2) in vec2 v_Uv;
 
-#ifdef STANDARDMATERIAL_NORMAL_MAP
+#ifdef NORMAL_MAPPING
 layout(location = 3) in vec4 v_WorldTangent;
 #endif
 
+#ifdef VERTEX_COLORS
+layout(location = 4) in vec4 v_Color;
+#endif
+
+#ifdef VERTEX_UV_1
+layout(location = 5) in vec2 v_Uv1;
+#endif
+
 layout(location = 0) out vec4 o_Target;
 
 layout(set = 0, binding = 0) uniform CameraViewProj {
@@ -65,6 +78,36 @@ layout(std140, set = 1, binding = 0) uniform Lights {
     PointLight PointLights[MAX_LIGHTS];
 };
 
+layout(std140, set = 1, binding = 1) uniform Fog {
+    vec4 FogColor;
+    // 0: no fog, 1: linear, 2: exponential, 3: exponential squared
+    uvec4 FogMode;
+    // linear: start, end. exponential: density
+    vec4 FogParams;
+};
+
+#ifdef CLUSTERED_LIGHTING
+layout(std140, set = 1, binding = 2) uniform Clusters {
+    mat4 ClusterView;
+    uvec4 ClusterDimensions;
+    // xy: depth slice scale and bias, zw: size of the target in pixels
+    vec4 ClusterParams;
+};
+
+layout(std430, set = 1, binding = 3) readonly buffer ClusterLights {
+    PointLight ClusterPointLights[];
+};
+
+layout(std430, set = 1, binding = 4) readonly buffer ClusterLightIndices {
+    uint ClusterLightIndexList[];
+};
+
+// the offset into ClusterLightIndexList and the number of lights of each cluster
+layout(std430, set = 1, binding = 5) readonly buffer ClusterOffsets {
+    uvec2 ClusterOffsetsAndCounts[];
+};
+#endif
+
 layout(set = 3, binding = 0) uniform StandardMaterial_base_color {
     vec4 base_color;
 };
@@ -75,6 +118,83 @@ layout(set = 3,
        binding = 2) uniform sampler StandardMaterial_base_color_texture_sampler;
 #endif
 
+#ifdef TERRAINMATERIAL_SPLAT_MAP
+layout(set = 3, binding = 15) uniform texture2D TerrainMaterial_splat_map;
+layout(set = 3,
+       binding = 16) uniform sampler TerrainMaterial_splat_map_sampler;
+#    ifdef TERRAINMATERIAL_LAYER_0
+layout(set = 3, binding = 17) uniform texture2D TerrainMaterial_layer_0;
+layout(set = 3,
+       binding = 18) uniform sampler TerrainMaterial_layer_0_sampler;
+#    endif
+#    ifdef TERRAINMATERIAL_LAYER_1
+layout(set = 3, binding = 19) uniform texture2D TerrainMaterial_layer_1;
+layout(set = 3,
+       binding = 20) uniform sampler TerrainMaterial_layer_1_sampler;
+#    endif
+#    ifdef TERRAINMATERIAL_LAYER_2
+layout(set = 3, binding = 21) uniform texture2D TerrainMaterial_layer_2;
+layout(set = 3,
+       binding = 22) uniform sampler TerrainMaterial_layer_2_sampler;
+#    endif
+#    ifdef TERRAINMATERIAL_LAYER_3
+layout(set = 3, binding = 23) uniform texture2D TerrainMaterial_layer_3;
+layout(set = 3,
+       binding = 24) uniform sampler TerrainMaterial_layer_3_sampler;
+#    endif
+layout(set = 3, binding = 25) uniform TerrainMaterial_layer_uv_scale {
+    float layer_uv_scale;
+};
+
+// blends the terrain layers with the weights of the splat map, layers without a texture are white
+vec4 terrain_color() {
+    vec4 weights = texture(sampler2D(TerrainMaterial_splat_map, TerrainMaterial_splat_map_sampler), v_Uv);
+    weights /= max(dot(weights, vec4(1.0)), 1e-4);
+    vec2 layer_uv = v_Uv * layer_uv_scale;
+    vec4 layers[4] = vec4[4](vec4(1.0), vec4(1.0), vec4(1.0), vec4(1.0));
+#    ifdef TERRAINMATERIAL_LAYER_0
+    layers[0] = texture(sampler2D(TerrainMaterial_layer_0, TerrainMaterial_layer_0_sampler), layer_uv);
+#    endif
+#    ifdef TERRAINMATERIAL_LAYER_1
+    layers[1] = texture(sampler2D(TerrainMaterial_layer_1, TerrainMaterial_layer_1_sampler), layer_uv);
+#    endif
+#    ifdef TERRAINMATERIAL_LAYER_2
+    layers[2] = texture(sampler2D(TerrainMaterial_layer_2, TerrainMaterial_layer_2_sampler), layer_uv);
+#    endif
+#    ifdef TERRAINMATERIAL_LAYER_3
+    layers[3] = texture(sampler2D(TerrainMaterial_layer_3, TerrainMaterial_layer_3_sampler), layer_uv);
+#    endif
+    return weights.r * layers[0] + weights.g * layers[1] + weights.b * layers[2] + weights.a * layers[3];
+}
+#endif
+
+#ifdef DITHERFADE
+layout(set = 2, binding = 1) uniform DitherFade_factor {
+    float fade_factor;
+};
+
+// 4x4 ordered dither (Bayer) matrix, normalized to (0, 1)
+float dither_threshold(vec2 frag_coord) {
+    const float bayer[16] = float[16](
+         0.0,  8.0,  2.0, 10.0,
+        12.0,  4.0, 14.0,  6.0,
+         3.0, 11.0,  1.0,  9.0,
+        15.0,  7.0, 13.0,  5.0
+    );
+    ivec2 p = ivec2(mod(frag_coord, 4.0));
+    return (bayer[p.y * 4 + p.x] + 0.5) / 16.0;
+}
+#endif
+
+#ifdef MATERIALOVERRIDES
+layout(set = 2, binding = 2) uniform MaterialOverrides_tint {
+    vec4 override_tint;
+};
+layout(set = 2, binding = 3) uniform MaterialOverrides_emissive_strength {
+    float override_emissive_strength;
+};
+#endif
+
 #ifndef STANDARDMATERIAL_UNLIT
 
 layout(set = 3, binding = 3) uniform StandardMaterial_roughness {
@@ -240,6 +360,37 @@ float perceptualRoughnessToRoughness(float perceptualRoughness) {
     return clampedPerceptualRoughness * clampedPerceptualRoughness;
 }
 
+vec3 point_light(PointLight light, float roughness, float NdotV, vec3 N, vec3 V,
+                 vec3 F0, vec3 diffuseColor) {
+    vec3 light_to_frag = light.pos.xyz - v_WorldPosition.xyz;
+    vec3 L = normalize(light_to_frag);
+    float distance_square = dot(light_to_frag, light_to_frag);
+
+    float rangeAttenuation =
+        getDistanceAttenuation(distance_square, light.inverseRangeSquared);
+
+    vec3 H = normalize(L + V);
+    float NoL = saturate(dot(N, L));
+    float NoH = saturate(dot(N, H));
+    float LoH = saturate(dot(L, H));
+
+    vec3 specular = specular(F0, roughness, H, NdotV, NoL, NoH, LoH);
+    vec3 diffuse = diffuseColor * Fd_Burley(roughness, NdotV, NoL, LoH);
+
+    // Lout = f(v,l) Φ / { 4 π d^2 }⟨n⋅l⟩
+    // where
+    // f(v,l) = (f_d(v,l) + f_r(v,l)) * light_color
+    // Φ is light intensity
+
+    // our rangeAttentuation = 1 / d^2 multiplied with an attenuation factor for smoothing at the edge of the non-physical maximum light radius
+    // It's not 100% clear where the 1/4π goes in the derivation, but we follow the filament shader and leave it out
+
+    // See https://google.github.io/filament/Filament.html#mjx-eqn-pointLightLuminanceEquation
+    // TODO compensate for energy loss https://google.github.io/filament/Filament.html#materialsystem/improvingthebrdfs/energylossinspecularreflectance
+    // light.color.rgb is premultiplied with light.intensity on the CPU
+    return ((diffuse + specular) * light.color.rgb) * (rangeAttenuation * NoL);
+}
+
 // from https://64.github.io/tonemapping/
 // reinhard on RGB oversaturates colors
 vec3 reinhard(vec3 color) {
@@ -278,12 +429,28 @@ vec3 reinhard_extended_luminance(vec3 color, float max_white_l) {
 #endif
 
 void main() {
+#ifdef DITHERFADE
+    // screen-door transparency: keeps faded geometry in the opaque pass with depth writes
+    if (fade_factor < dither_threshold(gl_FragCoord.xy)) {
+        discard;
+    }
+#endif
+
     vec4 output_color = base_color;
 #ifdef STANDARDMATERIAL_BASE_COLOR_TEXTURE
     output_color *= texture(sampler2D(StandardMaterial_base_color_texture,
                                       StandardMaterial_base_color_texture_sampler),
                             v_Uv);
 #endif
+#ifdef TERRAINMATERIAL_SPLAT_MAP
+    output_color *= terrain_color();
+#endif
+#ifdef VERTEX_COLORS
+    output_color *= v_Color;
+#endif
+#ifdef MATERIALOVERRIDES
+    output_color *= override_tint;
+#endif
 
 #ifndef STANDARDMATERIAL_UNLIT
     // calculate non-linear roughness from linear perceptualRoughness
@@ -298,26 +465,32 @@ void main() {
 
     vec3 N = normalize(v_WorldNormal);
 
-#    ifdef STANDARDMATERIAL_NORMAL_MAP
+#    ifdef NORMAL_MAPPING
     vec3 T = normalize(v_WorldTangent.xyz);
     vec3 B = cross(N, T) * v_WorldTangent.w;
 #    endif
 
 #    ifdef STANDARDMATERIAL_DOUBLE_SIDED
     N = gl_FrontFacing ? N : -N;
-#        ifdef STANDARDMATERIAL_NORMAL_MAP
+#        ifdef NORMAL_MAPPING
     T = gl_FrontFacing ? T : -T;
     B = gl_FrontFacing ? B : -B;
 #        endif
 #    endif
 
-#    ifdef STANDARDMATERIAL_NORMAL_MAP
+#    ifdef NORMAL_MAPPING
     mat3 TBN = mat3(T, B, N);
     N = TBN * normalize(texture(sampler2D(StandardMaterial_normal_map, StandardMaterial_normal_map_sampler), v_Uv).rgb * 2.0 - 1.0);
 #    endif
 
 #    ifdef STANDARDMATERIAL_OCCLUSION_TEXTURE
-    float occlusion = texture(sampler2D(StandardMaterial_occlusion_texture, StandardMaterial_occlusion_texture_sampler), v_Uv).r;
+    // lightmapped meshes bake their occlusion in their second uv set
+#        ifdef VERTEX_UV_1
+    vec2 occlusion_uv = v_Uv1;
+#        else
+    vec2 occlusion_uv = v_Uv;
+#        endif
+    float occlusion = texture(sampler2D(StandardMaterial_occlusion_texture, StandardMaterial_occlusion_texture_sampler), occlusion_uv).r;
 #    else
     float occlusion = 1.0;
 #    endif
@@ -341,52 +514,57 @@ void main() {
 
     // accumulate color
     vec3 light_accum = vec3(0.0);
+#    ifdef CLUSTERED_LIGHTING
+    float view_depth = max(-(ClusterView * vec4(v_WorldPosition, 1.0)).z, 1e-4);
+    uvec3 cluster = uvec3(
+        uvec2(gl_FragCoord.xy / ClusterParams.zw * vec2(ClusterDimensions.xy)),
+        uint(max(log(view_depth) * ClusterParams.x + ClusterParams.y, 0.0)));
+    cluster = min(cluster, ClusterDimensions.xyz - 1u);
+    uvec2 offset_and_count = ClusterOffsetsAndCounts[
+        cluster.x + ClusterDimensions.x * (cluster.y + ClusterDimensions.y * cluster.z)];
+    for (uint i = 0u; i < offset_and_count.y; ++i) {
+        PointLight light = ClusterPointLights[ClusterLightIndexList[offset_and_count.x + i]];
+        light_accum += point_light(light, roughness, NdotV, N, V, F0, diffuseColor);
+    }
+#    else
     for (int i = 0; i < int(NumLights.x) && i < MAX_LIGHTS; ++i) {
-        PointLight light = PointLights[i];
-
-        vec3 light_to_frag = light.pos.xyz - v_WorldPosition.xyz;
-        vec3 L = normalize(light_to_frag);
-        float distance_square = dot(light_to_frag, light_to_frag);
-
-        float rangeAttenuation =
-            getDistanceAttenuation(distance_square, light.inverseRangeSquared);
-
-        vec3 H = normalize(L + V);
-        float NoL = saturate(dot(N, L));
-        float NoH = saturate(dot(N, H));
-        float LoH = saturate(dot(L, H));
-
-        vec3 specular = specular(F0, roughness, H, NdotV, NoL, NoH, LoH);
-        vec3 diffuse = diffuseColor * Fd_Burley(roughness, NdotV, NoL, LoH);
-
-        // Lout = f(v,l) Φ / { 4 π d^2 }⟨n⋅l⟩
-        // where
-        // f(v,l) = (f_d(v,l) + f_r(v,l)) * light_color
-        // Φ is light intensity
-
-        // our rangeAttentuation = 1 / d^2 multiplied with an attenuation factor for smoothing at the edge of the non-physical maximum light radius
-        // It's not 100% clear where the 1/4π goes in the derivation, but we follow the filament shader and leave it out
-
-        // See https://google.github.io/filament/Filament.html#mjx-eqn-pointLightLuminanceEquation
-        // TODO compensate for energy loss https://google.github.io/filament/Filament.html#materialsystem/improvingthebrdfs/energylossinspecularreflectance
-        // light.color.rgb is premultiplied with light.intensity on the CPU
-        light_accum +=
-            ((diffuse + specular) * light.color.rgb) * (rangeAttenuation * NoL);
+        light_accum += point_light(PointLights[i], roughness, NdotV, N, V, F0, diffuseColor);
     }
+#    endif
 
     vec3 diffuse_ambient = EnvBRDFApprox(diffuseColor, 1.0, NdotV);
     vec3 specular_ambient = EnvBRDFApprox(F0, perceptual_roughness, NdotV);
 
     output_color.rgb = light_accum;
     output_color.rgb += (diffuse_ambient + specular_ambient) * AmbientColor.xyz * occlusion;
+#    ifdef MATERIALOVERRIDES
+    output_color.rgb += emissive.rgb * override_emissive_strength * output_color.a;
+#    else
     output_color.rgb += emissive.rgb * output_color.a;
+#    endif
 
-    // tone_mapping
+    // tone_mapping, done by the tonemapping pass when rendering to an hdr target
+#ifndef HDR
     output_color.rgb = reinhard_luminance(output_color.rgb);
+#endif
     // Gamma correction.
     // Not needed with sRGB buffer
     // output_color.rgb = pow(output_color.rgb, vec3(1.0 / 2.2));
 #endif
 
+    // fog
+    float view_distance = length(v_WorldPosition - CameraPos.xyz);
+    float fog_visibility = 1.0;
+    if (FogMode.x == 1u) {
+        float fog_range = max(FogParams.y - FogParams.x, 0.0001);
+        fog_visibility = clamp((FogParams.y - view_distance) / fog_range, 0.0, 1.0);
+    } else if (FogMode.x == 2u) {
+        fog_visibility = exp(-FogParams.x * view_distance);
+    } else if (FogMode.x == 3u) {
+        float fog_density = FogParams.x * view_distance;
+        fog_visibility = exp(-fog_density * fog_density);
+    }
+    output_color.rgb = mix(FogColor.rgb, output_color.rgb, fog_visibility);
+
     o_Target = output_color;
 }
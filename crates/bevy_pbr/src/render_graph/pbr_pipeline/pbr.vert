@@ -1,13 +1,26 @@
 #version 450
 
+// normal maps need the tangents of the mesh
+#if defined(STANDARDMATERIAL_NORMAL_MAP) && defined(VERTEX_TANGENTS)
+#    define NORMAL_MAPPING
+#endif
+
 layout(location = 0) in vec3 Vertex_Position;
 layout(location = 1) in vec3 Vertex_Normal;
 layout(location = 2) in vec2 Vertex_Uv;
 
-#ifdef STANDARDMATERIAL_NORMAL_MAP
+#ifdef NORMAL_MAPPING
 layout(location = 3) in vec4 Vertex_Tangent;
 #endif
 
+#ifdef VERTEX_COLORS
+layout(location = 4) in vec4 Vertex_Color;
+#endif
+
+#ifdef VERTEX_UV_1
+layout(location = 5) in vec2 Vertex_Uv_1;
+#endif
+
 layout(location = 0) out vec3 v_WorldPosition;
 layout(location = 1) out vec3 v_WorldNormal;
 layout(location = 2) out vec2 v_Uv;
@@ -16,21 +29,48 @@ layout(set = 0, binding = 0) uniform CameraViewProj {
     mat4 ViewProj;
 };
 
-#ifdef STANDARDMATERIAL_NORMAL_MAP
+#ifdef NORMAL_MAPPING
 layout(location = 3) out vec4 v_WorldTangent;
 #endif
 
+#ifdef VERTEX_COLORS
+layout(location = 4) out vec4 v_Color;
+#endif
+
+#ifdef VERTEX_UV_1
+layout(location = 5) out vec2 v_Uv1;
+#endif
+
 layout(set = 2, binding = 0) uniform Transform {
     mat4 Model;
 };
 
+#ifdef BILLBOARD
+layout(set = 0, binding = 2) uniform CameraView {
+    mat4 View;
+};
+
+#include "bevy_render/billboard.glsl"
+#endif
+
 void main() {
-    vec4 world_position = Model * vec4(Vertex_Position, 1.0);
+#ifdef BILLBOARD
+    mat4 model = billboard(Model, View);
+#else
+    mat4 model = Model;
+#endif
+    vec4 world_position = model * vec4(Vertex_Position, 1.0);
     v_WorldPosition = world_position.xyz;
-    v_WorldNormal = mat3(Model) * Vertex_Normal;
+    v_WorldNormal = mat3(model) * Vertex_Normal;
     v_Uv = Vertex_Uv;
-#ifdef STANDARDMATERIAL_NORMAL_MAP
-    v_WorldTangent = vec4(mat3(Model) * Vertex_Tangent.xyz, Vertex_Tangent.w);
+#ifdef NORMAL_MAPPING
+    v_WorldTangent = vec4(mat3(model) * Vertex_Tangent.xyz, Vertex_Tangent.w);
+#endif
+#ifdef VERTEX_COLORS
+    v_Color = Vertex_Color;
+#endif
+#ifdef VERTEX_UV_1
+    v_Uv1 = Vertex_Uv_1;
 #endif
     gl_Position = ViewProj * world_position;
 }
@@ -0,0 +1,154 @@
+use super::{ChunkKey, Heightmap, Terrain};
+use bevy_math::Vec3;
+use bevy_render::{
+    mesh::{Indices, Mesh},
+    pipeline::PrimitiveTopology,
+};
+
+/// A chunk of a [Terrain] at one level of detail. Chunks are spawned as children of the terrain
+/// entity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TerrainChunk {
+    pub key: ChunkKey,
+    /// The corner of the bounding box of the chunk with the lowest coordinates, in the local space
+    /// of the terrain
+    pub min: Vec3,
+    /// The corner of the bounding box of the chunk with the highest coordinates
+    pub max: Vec3,
+}
+
+/// Builds the mesh of a chunk: a grid of [Terrain::chunk_resolution] quads along each side,
+/// displaced by the heightmap. A skirt hangs [Terrain::skirt_depth] below each edge, which hides
+/// the cracks between neighbouring chunks of different levels of detail.
+///
+/// UVs span the whole terrain rather than the chunk, so a splat map covers the terrain once.
+pub fn build_chunk_mesh(
+    terrain: &Terrain,
+    heightmap: &Heightmap,
+    key: ChunkKey,
+) -> (Mesh, TerrainChunk) {
+    let resolution = terrain.chunk_resolution.max(1);
+    let row = resolution + 1;
+    let size = key.size(terrain);
+    let step = size / resolution as f32;
+    let (origin_x, origin_z) = key.origin(terrain);
+    let uv = |x: f32, z: f32| [x / terrain.size + 0.5, z / terrain.size + 0.5];
+    let height = |x: f32, z: f32| {
+        let [u, v] = uv(x, z);
+        heightmap.sample(u, v) * terrain.height_scale
+    };
+
+    let vertex_count = (row * row + 4 * row) as usize;
+    let mut positions = Vec::with_capacity(vertex_count);
+    let mut normals = Vec::with_capacity(vertex_count);
+    let mut uvs = Vec::with_capacity(vertex_count);
+    let (mut min_height, mut max_height) = (f32::MAX, f32::MIN);
+    for j in 0..row {
+        for i in 0..row {
+            let x = origin_x + i as f32 * step;
+            let z = origin_z + j as f32 * step;
+            let y = height(x, z);
+            min_height = min_height.min(y);
+            max_height = max_height.max(y);
+            // central differences, so chunks agree on the normals of their shared edges
+            let dx = height(x + step, z) - height(x - step, z);
+            let dz = height(x, z + step) - height(x, z - step);
+            positions.push([x, y, z]);
+            normals.push(Vec3::new(-dx, 2.0 * step, -dz).normalize().into());
+            uvs.push(uv(x, z));
+        }
+    }
+
+    let mut indices =
+        Vec::with_capacity((resolution * resolution * 6 + 4 * resolution * 6) as usize);
+    for j in 0..resolution {
+        for i in 0..resolution {
+            let a = i + j * row;
+            let b = a + row;
+            indices.extend_from_slice(&[a, b, a + 1, a + 1, b, b + 1]);
+        }
+    }
+
+    // the edges are walked so the skirt triangles face away from the chunk
+    let last = resolution;
+    let edges: [Vec<u32>; 4] = [
+        (0..row).collect(),
+        (0..row).map(|j| last + j * row).collect(),
+        (0..row).rev().map(|i| i + last * row).collect(),
+        (0..row).rev().map(|j| j * row).collect(),
+    ];
+    for edge in edges.iter() {
+        let first_bottom = positions.len() as u32;
+        for top in edge.iter() {
+            let [x, y, z] = positions[*top as usize];
+            positions.push([x, y - terrain.skirt_depth, z]);
+            normals.push(normals[*top as usize]);
+            uvs.push(uvs[*top as usize]);
+        }
+        for (k, top) in edge.windows(2).enumerate() {
+            let bottom = first_bottom + k as u32;
+            indices.extend_from_slice(&[top[0], top[1], bottom, top[1], bottom + 1, bottom]);
+        }
+    }
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.set_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.set_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.set_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.set_indices(Some(Indices::U32(indices)));
+
+    let chunk = TerrainChunk {
+        key,
+        min: Vec3::new(origin_x, min_height - terrain.skirt_depth, origin_z),
+        max: Vec3::new(origin_x + size, max_height, origin_z + size),
+    };
+    (mesh, chunk)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_render::mesh::VertexAttributeValues;
+
+    #[test]
+    fn builds_chunks_with_skirts() {
+        let terrain = Terrain {
+            size: 8.0,
+            height_scale: 2.0,
+            chunk_resolution: 4,
+            skirt_depth: 1.0,
+            ..Default::default()
+        };
+        let heightmap = Heightmap::new(2, 2, vec![0.5; 4]).unwrap();
+        let key = ChunkKey {
+            depth: 1,
+            x: 1,
+            z: 0,
+        };
+        let (mesh, chunk) = build_chunk_mesh(&terrain, &heightmap, key);
+
+        assert_eq!(chunk.min, Vec3::new(0.0, 0.0, -4.0));
+        assert_eq!(chunk.max, Vec3::new(4.0, 1.0, 0.0));
+        let positions = match mesh.attribute(Mesh::ATTRIBUTE_POSITION) {
+            Some(VertexAttributeValues::Float3(positions)) => positions,
+            _ => panic!("the chunk has no positions"),
+        };
+        assert_eq!(positions.len(), 25 + 4 * 5);
+        let indices = match mesh.indices() {
+            Some(Indices::U32(indices)) => indices,
+            _ => panic!("the chunk has no indices"),
+        };
+        assert_eq!(indices.len(), 16 * 6 + 4 * 4 * 6);
+
+        // every triangle faces up, or away from the center of the chunk for skirts
+        let center = Vec3::new(2.0, 1.0, -2.0);
+        for triangle in indices.chunks_exact(3) {
+            let a = Vec3::from(positions[triangle[0] as usize]);
+            let b = Vec3::from(positions[triangle[1] as usize]);
+            let c = Vec3::from(positions[triangle[2] as usize]);
+            let normal = (b - a).cross(c - a);
+            let outwards = (a + b + c) / 3.0 - center;
+            assert!(normal.y > 0.0 || normal.dot(outwards) > 0.0);
+        }
+    }
+}
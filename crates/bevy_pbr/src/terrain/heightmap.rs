@@ -0,0 +1,193 @@
+use anyhow::Result;
+use bevy_asset::{AssetLoader, LoadContext, LoadedAsset};
+use bevy_reflect::TypeUuid;
+use bevy_render::texture::{Texture, TextureFormat};
+use bevy_utils::BoxedFuture;
+use std::{convert::TryInto, sync::Arc};
+use thiserror::Error;
+
+/// A grid of heights in the `0.0..=1.0` range. Rows run along the Z axis of a [Terrain], columns
+/// along its X axis.
+///
+/// Heights are shared between clones, so terrain chunks can be built from a heightmap on other
+/// threads without copying it.
+///
+/// [Terrain]: super::Terrain
+#[derive(Debug, Clone, TypeUuid)]
+#[uuid = "4c1f8e2a-9b3d-4e67-a5f0-2d8c6b1e7a93"]
+pub struct Heightmap {
+    width: u32,
+    height: u32,
+    heights: Arc<[f32]>,
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum HeightmapError {
+    #[error("Expected {expected} heights but got {actual}.")]
+    InvalidSize { expected: usize, actual: usize },
+    #[error("Heightmaps can't be created from textures of format {0:?}.")]
+    UnsupportedFormat(TextureFormat),
+    #[error("Raw heightmaps must be square, but {0} samples don't make a square.")]
+    NotSquare(usize),
+}
+
+impl Heightmap {
+    pub fn new(width: u32, height: u32, heights: Vec<f32>) -> Result<Self, HeightmapError> {
+        let expected = width as usize * height as usize;
+        if expected == 0 || heights.len() != expected {
+            return Err(HeightmapError::InvalidSize {
+                expected,
+                actual: heights.len(),
+            });
+        }
+        Ok(Heightmap {
+            width,
+            height,
+            heights: heights.into(),
+        })
+    }
+
+    /// Reads the first channel of a 2d texture. `R8Unorm`, `R16Uint`, `R32Float` and 8 bit rgba
+    /// textures are supported.
+    pub fn from_texture(texture: &Texture) -> Result<Self, HeightmapError> {
+        let (width, height) = (texture.size.width, texture.size.height);
+        let texel_count = width as usize * height as usize;
+        let heights = match texture.format {
+            TextureFormat::R8Unorm => texture
+                .data
+                .iter()
+                .take(texel_count)
+                .map(|value| *value as f32 / u8::MAX as f32)
+                .collect(),
+            TextureFormat::Rgba8Unorm
+            | TextureFormat::Rgba8UnormSrgb
+            | TextureFormat::Bgra8Unorm
+            | TextureFormat::Bgra8UnormSrgb => {
+                let channel = match texture.format {
+                    TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => 2,
+                    _ => 0,
+                };
+                texture
+                    .data
+                    .chunks_exact(4)
+                    .take(texel_count)
+                    .map(|texel| texel[channel] as f32 / u8::MAX as f32)
+                    .collect()
+            }
+            TextureFormat::R16Uint => texture
+                .data
+                .chunks_exact(2)
+                .take(texel_count)
+                .map(|value| u16::from_ne_bytes([value[0], value[1]]) as f32 / u16::MAX as f32)
+                .collect(),
+            TextureFormat::R32Float => texture
+                .data
+                .chunks_exact(4)
+                .take(texel_count)
+                .map(|value| f32::from_ne_bytes(value.try_into().unwrap()))
+                .collect(),
+            format => return Err(HeightmapError::UnsupportedFormat(format)),
+        };
+        Heightmap::new(width, height, heights)
+    }
+
+    /// Reads a square grid of little endian 16 bit heights, the "raw" format most terrain tools
+    /// export
+    pub fn from_r16(bytes: &[u8]) -> Result<Self, HeightmapError> {
+        let sample_count = bytes.len() / 2;
+        let size = (sample_count as f64).sqrt() as usize;
+        if size * size != sample_count || bytes.len() % 2 != 0 {
+            return Err(HeightmapError::NotSquare(sample_count));
+        }
+        let heights = bytes
+            .chunks_exact(2)
+            .map(|value| u16::from_le_bytes([value[0], value[1]]) as f32 / u16::MAX as f32)
+            .collect();
+        Heightmap::new(size as u32, size as u32, heights)
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The height at column `x` and row `y`, clamped to the edges of the heightmap
+    pub fn get(&self, x: i64, y: i64) -> f32 {
+        let x = x.clamp(0, self.width as i64 - 1) as usize;
+        let y = y.clamp(0, self.height as i64 - 1) as usize;
+        self.heights[x + y * self.width as usize]
+    }
+
+    /// Bilinearly samples the heightmap at `u` and `v` in `0.0..=1.0`, where `(0.0, 0.0)` is the
+    /// center of the first height and `(1.0, 1.0)` the center of the last one
+    pub fn sample(&self, u: f32, v: f32) -> f32 {
+        let x = u.clamp(0.0, 1.0) * (self.width - 1) as f32;
+        let y = v.clamp(0.0, 1.0) * (self.height - 1) as f32;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+        let top = lerp(self.get(x0, y0), self.get(x0 + 1, y0), tx);
+        let bottom = lerp(self.get(x0, y0 + 1), self.get(x0 + 1, y0 + 1), tx);
+        lerp(top, bottom, ty)
+    }
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+/// Loads `.r16` and `.raw` files as [Heightmap]s, see [Heightmap::from_r16]
+#[derive(Clone, Default)]
+pub struct HeightmapLoader;
+
+impl AssetLoader for HeightmapLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let heightmap = Heightmap::from_r16(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(heightmap));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["r16", "raw"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_r16() {
+        let bytes = [0u16, u16::MAX, u16::MAX / 2, 0]
+            .iter()
+            .flat_map(|value| value.to_le_bytes().to_vec())
+            .collect::<Vec<_>>();
+        let heightmap = Heightmap::from_r16(&bytes).unwrap();
+        assert_eq!((heightmap.width(), heightmap.height()), (2, 2));
+        assert_eq!(heightmap.get(1, 0), 1.0);
+        assert_eq!(heightmap.get(5, -1), 1.0);
+        assert_eq!(
+            Heightmap::from_r16(&bytes[..6]).unwrap_err(),
+            HeightmapError::NotSquare(3)
+        );
+    }
+
+    #[test]
+    fn samples_bilinearly() {
+        let heightmap = Heightmap::new(2, 2, vec![0.0, 1.0, 0.5, 0.5]).unwrap();
+        assert_eq!(heightmap.sample(0.0, 0.0), 0.0);
+        assert_eq!(heightmap.sample(1.0, 0.0), 1.0);
+        assert_eq!(heightmap.sample(0.5, 0.0), 0.5);
+        assert_eq!(heightmap.sample(0.5, 0.5), 0.5);
+        assert_eq!(heightmap.sample(0.0, 2.0), 0.5);
+    }
+}
@@ -0,0 +1,388 @@
+mod chunk;
+mod heightmap;
+mod quadtree;
+
+pub use chunk::*;
+pub use heightmap::*;
+pub use quadtree::*;
+
+use crate::{
+    material::StandardMaterial,
+    render_graph::{node, PBR_PIPELINE_HANDLE},
+};
+use bevy_app::{prelude::*, EventReader};
+use bevy_asset::{AddAsset, AssetEvent, Assets, Handle};
+use bevy_ecs::{
+    bundle::Bundle,
+    entity::Entity,
+    query::With,
+    reflect::ReflectComponent,
+    schedule::ParallelSystemDescriptorCoercion,
+    system::{Commands, IntoSystem, Query, Res, ResMut},
+};
+use bevy_math::{Aabb, Frustum, Mat4, Vec3};
+use bevy_reflect::{Reflect, TypeUuid};
+use bevy_render::{
+    camera::{ActiveCameras, Camera},
+    draw::{Draw, OutsideFrustum, Visible},
+    mesh::Mesh,
+    pipeline::{RenderPipeline, RenderPipelines},
+    render_graph::{
+        base::{self, main_pass_camera, MainPass},
+        AssetRenderResourcesNode, RenderGraph,
+    },
+    renderer::RenderResources,
+    shader::{self, ShaderDefs},
+    texture::Texture,
+};
+use bevy_tasks::{AsyncComputeTaskPool, Task};
+use bevy_transform::{
+    hierarchy::{BuildChildren, DespawnRecursiveExt},
+    prelude::{GlobalTransform, Transform},
+    TransformSystem,
+};
+use bevy_utils::{HashMap, HashSet};
+use futures_lite::future;
+
+/// Adds heightmap terrains, see [TerrainBundle]. The plugin has to be added after [PbrPlugin].
+///
+/// [PbrPlugin]: crate::PbrPlugin
+#[derive(Debug, Default)]
+pub struct TerrainPlugin;
+
+impl Plugin for TerrainPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_asset::<Heightmap>()
+            .add_asset::<TerrainMaterial>()
+            .init_asset_loader::<HeightmapLoader>()
+            .register_type::<Terrain>()
+            .add_system_to_stage(CoreStage::Update, terrain_lod_system.system())
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                terrain_frustum_culling_system
+                    .system()
+                    .after(TransformSystem::TransformPropagate),
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                shader::asset_shader_defs_system::<TerrainMaterial>.system(),
+            );
+
+        let mut graph = app.world_mut().get_resource_mut::<RenderGraph>().unwrap();
+        graph.add_system_node(
+            node::TERRAIN_MATERIAL,
+            AssetRenderResourcesNode::<TerrainMaterial>::new(true),
+        );
+        graph
+            .add_node_edge(node::TERRAIN_MATERIAL, base::node::MAIN_PASS)
+            .unwrap();
+    }
+}
+
+/// A terrain displaced by a [Heightmap], centered on its entity. It is drawn as a quadtree of
+/// chunks: chunks close to the main pass camera are split into smaller, more detailed ones.
+/// Chunk meshes are built on the [AsyncComputeTaskPool] and culled against the view frustum.
+#[derive(Debug, Clone, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct Terrain {
+    /// The width of the terrain along the X and Z axes
+    pub size: f32,
+    /// The height of a heightmap value of 1.0
+    pub height_scale: f32,
+    /// The number of quads along each side of a chunk
+    pub chunk_resolution: u32,
+    /// The number of levels of detail. The most detailed chunks are `size / 2^(lod_levels - 1)`
+    /// wide.
+    pub lod_levels: u32,
+    /// Chunks are split while the camera is closer to them than `lod_distance` times their width
+    pub lod_distance: f32,
+    /// How far the skirts hanging below the edges of chunks reach. Skirts hide the cracks between
+    /// chunks of different levels of detail, so they should be deeper than the height difference
+    /// between neighbouring levels.
+    pub skirt_depth: f32,
+}
+
+impl Default for Terrain {
+    fn default() -> Self {
+        Terrain {
+            size: 1024.0,
+            height_scale: 128.0,
+            chunk_resolution: 32,
+            lod_levels: 6,
+            lod_distance: 2.0,
+            skirt_depth: 4.0,
+        }
+    }
+}
+
+/// Blends up to four textures over a [Terrain] using a splat map, whose red, green, blue and alpha
+/// channels hold the weights of each layer. Layers without a texture are white.
+///
+/// The blended color multiplies the base color of the terrain's [StandardMaterial], which provides
+/// the rest of its surface properties. Layer textures are tiled `layer_uv_scale` times across the
+/// terrain, so they need a repeating sampler.
+#[derive(Debug, Clone, RenderResources, ShaderDefs, TypeUuid)]
+#[uuid = "9a7e3d15-2c4b-4f80-b6e1-58d0f3a2c7b4"]
+pub struct TerrainMaterial {
+    #[shader_def]
+    pub splat_map: Option<Handle<Texture>>,
+    #[shader_def]
+    pub layer_0: Option<Handle<Texture>>,
+    #[shader_def]
+    pub layer_1: Option<Handle<Texture>>,
+    #[shader_def]
+    pub layer_2: Option<Handle<Texture>>,
+    #[shader_def]
+    pub layer_3: Option<Handle<Texture>>,
+    pub layer_uv_scale: f32,
+}
+
+impl Default for TerrainMaterial {
+    fn default() -> Self {
+        TerrainMaterial {
+            splat_map: None,
+            layer_0: None,
+            layer_1: None,
+            layer_2: None,
+            layer_3: None,
+            layer_uv_scale: 64.0,
+        }
+    }
+}
+
+/// The chunks of a [Terrain] entity, managed by the [terrain_lod_system]
+#[derive(Debug, Default)]
+pub struct TerrainChunks {
+    terrain: Option<Terrain>,
+    heightmap: Option<Handle<Heightmap>>,
+    spawned: HashMap<ChunkKey, Entity>,
+    building: HashMap<ChunkKey, Task<(Mesh, TerrainChunk)>>,
+}
+
+/// A component bundle for terrain entities. Add a [TerrainMaterial] handle to splat textures
+/// over the terrain.
+#[derive(Bundle, Default)]
+pub struct TerrainBundle {
+    pub terrain: Terrain,
+    pub heightmap: Handle<Heightmap>,
+    pub material: Handle<StandardMaterial>,
+    pub chunks: TerrainChunks,
+    pub transform: Transform,
+    pub global_transform: GlobalTransform,
+}
+
+#[derive(Bundle)]
+struct TerrainChunkBundle {
+    chunk: TerrainChunk,
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+    main_pass: MainPass,
+    draw: Draw,
+    visible: Visible,
+    render_pipelines: RenderPipelines,
+    transform: Transform,
+    global_transform: GlobalTransform,
+}
+
+/// Selects the chunks of each terrain for the main pass camera, builds the missing ones and
+/// replaces the previous selection once every selected chunk is built, so the terrain never has
+/// holes or overlapping chunks
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn terrain_lod_system(
+    mut commands: Commands,
+    task_pool: Res<AsyncComputeTaskPool>,
+    active_cameras: Res<ActiveCameras>,
+    heightmaps: Res<Assets<Heightmap>>,
+    mut heightmap_events: EventReader<AssetEvent<Heightmap>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    cameras: Query<&GlobalTransform, With<Camera>>,
+    mut terrains: Query<(
+        Entity,
+        &Terrain,
+        &Handle<Heightmap>,
+        &GlobalTransform,
+        &mut TerrainChunks,
+        Option<&Handle<StandardMaterial>>,
+        Option<&Handle<TerrainMaterial>>,
+    )>,
+    mut chunk_visibility: Query<&mut Visible, With<TerrainChunk>>,
+) {
+    let modified_heightmaps = heightmap_events
+        .iter()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { handle } => Some(handle.clone_weak()),
+            AssetEvent::Created { .. } | AssetEvent::Removed { .. } => None,
+        })
+        .collect::<HashSet<_>>();
+    let camera_position =
+        match main_pass_camera(&active_cameras).and_then(|entity| cameras.get(entity).ok()) {
+            Some(camera_transform) => camera_transform.translation,
+            None => return,
+        };
+
+    for (
+        entity,
+        terrain,
+        heightmap_handle,
+        global_transform,
+        mut chunks,
+        material,
+        terrain_material,
+    ) in terrains.iter_mut()
+    {
+        let heightmap = match heightmaps.get(heightmap_handle) {
+            Some(heightmap) => heightmap,
+            None => continue,
+        };
+        let chunks = &mut *chunks;
+        if chunks.terrain.as_ref() != Some(terrain)
+            || chunks.heightmap.as_ref() != Some(heightmap_handle)
+            || modified_heightmaps.contains(heightmap_handle)
+        {
+            for (_, chunk) in chunks.spawned.drain() {
+                commands.entity(chunk).despawn_recursive();
+            }
+            chunks.building.clear();
+            chunks.terrain = Some(terrain.clone());
+            chunks.heightmap = Some(heightmap_handle.clone_weak());
+        }
+
+        let camera = global_transform
+            .compute_matrix()
+            .inverse()
+            .transform_point3(camera_position);
+        let selected = select_chunks(terrain, camera)
+            .into_iter()
+            .collect::<HashSet<_>>();
+
+        // dropping a task cancels it
+        chunks.building.retain(|key, _| selected.contains(key));
+        for key in selected.iter() {
+            if !chunks.spawned.contains_key(key) && !chunks.building.contains_key(key) {
+                let (terrain, heightmap, key) = (terrain.clone(), heightmap.clone(), *key);
+                let task =
+                    task_pool.spawn(async move { build_chunk_mesh(&terrain, &heightmap, key) });
+                chunks.building.insert(key, task);
+            }
+        }
+
+        let mut built = Vec::new();
+        for (key, task) in chunks.building.iter_mut() {
+            if let Some(chunk) = future::block_on(future::poll_once(task)) {
+                built.push((*key, chunk));
+            }
+        }
+        let complete = selected.iter().all(|key| {
+            chunks.spawned.contains_key(key) || built.iter().any(|(built, _)| built == key)
+        });
+
+        for (key, (mesh, chunk)) in built {
+            chunks.building.remove(&key);
+            let mut chunk_entity = commands.spawn_bundle(TerrainChunkBundle {
+                chunk,
+                mesh: meshes.add(mesh),
+                material: material.cloned().unwrap_or_default(),
+                main_pass: MainPass,
+                draw: Default::default(),
+                visible: Visible {
+                    is_visible: complete,
+                    ..Default::default()
+                },
+                render_pipelines: RenderPipelines::from_pipelines(vec![RenderPipeline::new(
+                    PBR_PIPELINE_HANDLE.typed(),
+                )]),
+                transform: Default::default(),
+                global_transform: Default::default(),
+            });
+            if let Some(terrain_material) = terrain_material {
+                chunk_entity.insert(terrain_material.clone());
+            }
+            let chunk_entity = chunk_entity.id();
+            commands.entity(entity).push_children(&[chunk_entity]);
+            chunks.spawned.insert(key, chunk_entity);
+        }
+
+        if complete {
+            chunks.spawned.retain(|key, chunk| {
+                if selected.contains(key) {
+                    if let Ok(mut visible) = chunk_visibility.get_mut(*chunk) {
+                        if !visible.is_visible {
+                            visible.is_visible = true;
+                        }
+                    }
+                    true
+                } else {
+                    commands.entity(*chunk).despawn_recursive();
+                    false
+                }
+            });
+        }
+    }
+}
+
+/// Returns true if the box between `min` and `max` is entirely outside the view frustum of
+/// `view_projection`, which maps the space of the box to clip space
+pub fn is_outside_frustum(view_projection: &Mat4, min: Vec3, max: Vec3) -> bool {
+    !Frustum::from_view_projection(view_projection).intersects_aabb(&Aabb { min, max })
+}
+
+/// Adds [OutsideFrustum] to the terrain chunks the main pass camera can't see
+pub fn terrain_frustum_culling_system(
+    mut commands: Commands,
+    active_cameras: Res<ActiveCameras>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    chunks: Query<(
+        Entity,
+        &TerrainChunk,
+        &GlobalTransform,
+        Option<&OutsideFrustum>,
+    )>,
+) {
+    let view_projection =
+        match main_pass_camera(&active_cameras).and_then(|entity| cameras.get(entity).ok()) {
+            Some((camera, camera_transform)) => camera.view_projection(camera_transform),
+            None => return,
+        };
+
+    for (entity, chunk, global_transform, outside_frustum) in chunks.iter() {
+        let outside = is_outside_frustum(
+            &(view_projection * global_transform.compute_matrix()),
+            chunk.min,
+            chunk.max,
+        );
+        if outside && outside_frustum.is_none() {
+            commands.entity(entity).insert(OutsideFrustum);
+        } else if !outside && outside_frustum.is_some() {
+            commands.entity(entity).remove::<OutsideFrustum>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn culls_boxes_outside_the_frustum() {
+        let projection = Mat4::perspective_rh(std::f32::consts::FRAC_PI_2, 1.0, 0.1, 100.0);
+        let cull = |min: Vec3, max: Vec3| is_outside_frustum(&projection, min, max);
+        assert!(!cull(
+            Vec3::new(-1.0, -1.0, -5.0),
+            Vec3::new(1.0, 1.0, -4.0)
+        ));
+        // behind the camera
+        assert!(cull(Vec3::new(-1.0, -1.0, 1.0), Vec3::new(1.0, 1.0, 2.0)));
+        // left of the camera
+        assert!(cull(
+            Vec3::new(-20.0, -1.0, -5.0),
+            Vec3::new(-10.0, 1.0, -4.0)
+        ));
+        // beyond the far plane
+        assert!(cull(
+            Vec3::new(-1.0, -1.0, -200.0),
+            Vec3::new(1.0, 1.0, -150.0)
+        ));
+        // around the camera
+        assert!(!cull(Vec3::splat(-1.0), Vec3::splat(1.0)));
+    }
+}
@@ -0,0 +1,114 @@
+use super::Terrain;
+use bevy_math::Vec3;
+
+/// A node of the terrain quadtree. The root at depth 0 covers the whole terrain, each following
+/// depth splits its parent into four chunks half as wide.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ChunkKey {
+    pub depth: u32,
+    /// The column of the chunk along the X axis, in `0..2^depth`
+    pub x: u32,
+    /// The row of the chunk along the Z axis, in `0..2^depth`
+    pub z: u32,
+}
+
+impl ChunkKey {
+    pub const ROOT: ChunkKey = ChunkKey {
+        depth: 0,
+        x: 0,
+        z: 0,
+    };
+
+    /// The width of the chunk along the X and Z axes
+    pub fn size(&self, terrain: &Terrain) -> f32 {
+        terrain.size / (1u32 << self.depth) as f32
+    }
+
+    /// The corner of the chunk with the lowest X and Z coordinates, in the local space of the
+    /// terrain
+    pub fn origin(&self, terrain: &Terrain) -> (f32, f32) {
+        let size = self.size(terrain);
+        (
+            -terrain.size / 2.0 + self.x as f32 * size,
+            -terrain.size / 2.0 + self.z as f32 * size,
+        )
+    }
+
+    pub fn children(&self) -> [ChunkKey; 4] {
+        let (x, z, depth) = (self.x * 2, self.z * 2, self.depth + 1);
+        [
+            ChunkKey { depth, x, z },
+            ChunkKey { depth, x: x + 1, z },
+            ChunkKey { depth, x, z: z + 1 },
+            ChunkKey {
+                depth,
+                x: x + 1,
+                z: z + 1,
+            },
+        ]
+    }
+}
+
+/// Selects the chunks drawn for a camera at `camera`, in the local space of the terrain. Chunks
+/// are split while the camera is closer to them than [Terrain::lod_distance] times their width,
+/// until they reach the finest level of detail. The selected chunks cover the terrain without
+/// overlapping.
+pub fn select_chunks(terrain: &Terrain, camera: Vec3) -> Vec<ChunkKey> {
+    let mut chunks = Vec::new();
+    let mut stack = vec![ChunkKey::ROOT];
+    while let Some(key) = stack.pop() {
+        let size = key.size(terrain);
+        let (x, z) = key.origin(terrain);
+        // heights are unknown until the chunk is built, so the whole height range is used
+        let min = Vec3::new(x, 0.0, z);
+        let max = Vec3::new(x + size, terrain.height_scale.max(0.0), z + size);
+        let distance = (min - camera).max(camera - max).max(Vec3::ZERO).length();
+        if key.depth + 1 < terrain.lod_levels && distance < terrain.lod_distance * size {
+            stack.extend_from_slice(&key.children());
+        } else {
+            chunks.push(key);
+        }
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_chunks_near_the_camera() {
+        let terrain = Terrain {
+            size: 64.0,
+            height_scale: 0.0,
+            lod_levels: 3,
+            lod_distance: 0.5,
+            ..Default::default()
+        };
+        let mut chunks = select_chunks(&terrain, Vec3::new(-31.0, 0.0, -31.0));
+        chunks.sort();
+        let key = |depth, x, z| ChunkKey { depth, x, z };
+        assert_eq!(
+            chunks,
+            vec![
+                key(1, 0, 1),
+                key(1, 1, 0),
+                key(1, 1, 1),
+                key(2, 0, 0),
+                key(2, 0, 1),
+                key(2, 1, 0),
+                key(2, 1, 1),
+            ]
+        );
+
+        let covered = chunks
+            .iter()
+            .map(|chunk| chunk.size(&terrain).powi(2))
+            .sum::<f32>();
+        assert_eq!(covered, 64.0 * 64.0);
+        assert_eq!(
+            select_chunks(&terrain, Vec3::new(0.0, 1000.0, 0.0)),
+            vec![ChunkKey::ROOT]
+        );
+    }
+}
@@ -0,0 +1,362 @@
+use crate::{cast_shape_against, overlaps, Collider, ColliderShape, SceneQueryFilter, ShapeHit};
+use bevy_ecs::{entity::Entity, reflect::ReflectComponent, system::Query};
+use bevy_math::Vec3;
+use bevy_reflect::Reflect;
+use bevy_transform::components::{GlobalTransform, Transform};
+
+/// How many times a move can be deflected by the colliders in the way
+const MAX_SLIDES: usize = 4;
+
+/// Moves an upright capsule by a given translation each frame, sliding along the colliders in
+/// its way instead of going through them.
+///
+/// The character climbs obstacles up to `step_offset` high and slopes up to `max_slope`, and
+/// stays on the ground when walking down them. Steeper slopes block it like walls. The capsule is
+/// kept along the y axis whatever the rotation of the entity. Other colliders are seen where they
+/// were at the last transform propagation, and the character doesn't push them: add a kinematic
+/// [RigidBody](crate::RigidBody) with a matching [Collider] for that.
+#[derive(Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct CharacterController {
+    /// Half the distance between the centers of the two half spheres of the capsule
+    pub half_height: f32,
+    pub radius: f32,
+    /// The highest obstacle the character can step on
+    pub step_offset: f32,
+    /// The steepest slope the character can walk up, in radians
+    pub max_slope: f32,
+    /// The gap kept between the capsule and the colliders, so that moves along a collider don't
+    /// start overlapping it
+    pub skin_width: f32,
+    /// The translation to apply at the next update, in world space. Reset once applied.
+    pub translation: Vec3,
+    #[reflect(ignore)]
+    grounded: bool,
+    #[reflect(ignore)]
+    ground_normal: Option<Vec3>,
+}
+
+impl Default for CharacterController {
+    fn default() -> Self {
+        CharacterController {
+            half_height: 0.5,
+            radius: 0.4,
+            step_offset: 0.3,
+            max_slope: std::f32::consts::FRAC_PI_4,
+            skin_width: 0.02,
+            translation: Vec3::ZERO,
+            grounded: false,
+            ground_normal: None,
+        }
+    }
+}
+
+impl CharacterController {
+    /// Whether the character stood on a walkable collider after its last move
+    pub fn is_grounded(&self) -> bool {
+        self.grounded
+    }
+
+    /// The normal of the ground the character stood on after its last move
+    pub fn ground_normal(&self) -> Option<Vec3> {
+        self.ground_normal
+    }
+
+    pub fn shape(&self) -> ColliderShape {
+        ColliderShape::Capsule {
+            half_height: self.half_height,
+            radius: self.radius,
+        }
+    }
+
+    fn is_walkable(&self, normal: Vec3) -> bool {
+        normal.dot(Vec3::Y) >= self.max_slope.cos()
+    }
+}
+
+/// The result of moving a character
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct CharacterMove {
+    position: Vec3,
+    ground_normal: Option<Vec3>,
+}
+
+struct Mover<'a, 'c> {
+    controller: &'a CharacterController,
+    shape: ColliderShape,
+    filter: SceneQueryFilter,
+    colliders: &'a [(Entity, &'c Collider, Transform)],
+}
+
+impl<'a, 'c> Mover<'a, 'c> {
+    fn cast(&self, position: Vec3, direction: Vec3, distance: f32) -> Option<ShapeHit> {
+        cast_shape_against(
+            self.colliders.iter().copied(),
+            &self.shape,
+            &Transform::from_translation(position),
+            direction,
+            distance,
+            &self.filter,
+        )
+    }
+
+    /// Pushes the capsule out of the colliders it overlaps
+    fn depenetrate(&self, mut position: Vec3) -> Vec3 {
+        for _ in 0..MAX_SLIDES {
+            let overlapping = overlaps(
+                self.colliders.iter().copied(),
+                &self.shape,
+                &Transform::from_translation(position),
+                &self.filter,
+            );
+            if overlapping.is_empty() {
+                break;
+            }
+            for (_, manifold) in overlapping {
+                position -= manifold.normal * (manifold.max_depth() + self.controller.skin_width);
+            }
+        }
+        position
+    }
+
+    /// Moves the capsule along `motion`, sliding along the colliders in the way. Steep slopes are
+    /// slid along horizontally when `flatten_steep_slopes` is set, so they can't be climbed.
+    /// Returns where the capsule ends up and whether it hit a collider that isn't walkable.
+    fn slide(&self, mut position: Vec3, motion: Vec3, flatten_steep_slopes: bool) -> (Vec3, bool) {
+        let skin_width = self.controller.skin_width;
+        let mut remaining = motion;
+        let mut blocked = false;
+        for _ in 0..MAX_SLIDES {
+            let distance = remaining.length();
+            if distance <= 1e-5 {
+                break;
+            }
+            let direction = remaining / distance;
+            let hit = match self.cast(position, direction, distance + skin_width) {
+                Some(hit) => hit,
+                None => {
+                    position += remaining;
+                    break;
+                }
+            };
+            let travel = (hit.distance - skin_width).max(0.0).min(distance);
+            position += direction * travel;
+            remaining = direction * (distance - travel);
+
+            let mut normal = -hit.manifold.normal;
+            if !self.controller.is_walkable(normal) {
+                blocked = true;
+                let horizontal = Vec3::new(normal.x, 0.0, normal.z);
+                if flatten_steep_slopes && horizontal.length_squared() > 1e-6 {
+                    normal = horizontal.normalize();
+                }
+            }
+            // only the part of the move going into the collider is removed
+            remaining -= normal * remaining.dot(normal).min(0.0);
+        }
+        (position, blocked)
+    }
+
+    /// Moves the capsule along the horizontal `motion` after raising it by the step offset, and
+    /// lowers it back onto walkable ground
+    fn step_up(&self, position: Vec3, motion: Vec3) -> Option<Vec3> {
+        let skin_width = self.controller.skin_width;
+        let step_offset = self.controller.step_offset;
+        let raise = self
+            .cast(position, Vec3::Y, step_offset + skin_width)
+            .map_or(step_offset, |hit| (hit.distance - skin_width).max(0.0));
+        if raise <= 0.0 {
+            return None;
+        }
+        let (raised, _) = self.slide(position + Vec3::Y * raise, motion, true);
+        let ground = self.cast(raised, -Vec3::Y, raise + skin_width)?;
+        if !self.controller.is_walkable(-ground.manifold.normal) {
+            return None;
+        }
+        Some(raised - Vec3::Y * (ground.distance - skin_width).max(0.0))
+    }
+}
+
+/// Moves the character of `entity` from `position` by its translation
+fn move_character(
+    controller: &CharacterController,
+    entity: Entity,
+    position: Vec3,
+    colliders: &[(Entity, &Collider, Transform)],
+) -> CharacterMove {
+    let mover = Mover {
+        controller,
+        shape: controller.shape(),
+        filter: SceneQueryFilter::excluding(entity),
+        colliders,
+    };
+    let skin_width = controller.skin_width;
+    let start = mover.depenetrate(position);
+    let vertical = Vec3::Y * controller.translation.y;
+    let horizontal = controller.translation - vertical;
+
+    let (mut position, blocked) = mover.slide(start, horizontal, true);
+    if blocked && controller.grounded && controller.step_offset > 0.0 {
+        if let Some(stepped) = mover.step_up(start, horizontal) {
+            let progress = |position: Vec3| {
+                let offset = position - start;
+                Vec3::new(offset.x, 0.0, offset.z).length()
+            };
+            if progress(stepped) > progress(position) + 1e-3 {
+                position = stepped;
+            }
+        }
+    }
+    let (mut position, _) = mover.slide(position, vertical, false);
+
+    // characters walking on the ground stick to it when going down steps and slopes
+    let snap_distance = if controller.grounded && vertical.y <= 0.0 {
+        controller.step_offset
+    } else {
+        0.0
+    };
+    let ground = mover
+        .cast(position, -Vec3::Y, snap_distance + 2.0 * skin_width)
+        .filter(|hit| controller.is_walkable(-hit.manifold.normal));
+    if let Some(ground) = &ground {
+        position -= Vec3::Y * (ground.distance - skin_width).max(0.0);
+    }
+    CharacterMove {
+        position,
+        ground_normal: ground.map(|ground| -ground.manifold.normal),
+    }
+}
+
+/// Applies the translation of every [CharacterController] to its [Transform]
+pub fn character_controller_system(
+    mut controllers: Query<(Entity, &mut CharacterController, &mut Transform)>,
+    colliders: Query<(Entity, &Collider, &GlobalTransform)>,
+) {
+    let colliders = colliders
+        .iter()
+        .map(|(entity, collider, global_transform)| {
+            (entity, collider, Transform::from(*global_transform))
+        })
+        .collect::<Vec<_>>();
+    for (entity, mut controller, mut transform) in controllers.iter_mut() {
+        let character_move = move_character(&controller, entity, transform.translation, &colliders);
+        // only write on change, so change detection only picks up the characters that moved
+        if transform.translation != character_move.position {
+            transform.translation = character_move.position;
+        }
+        controller.translation = Vec3::ZERO;
+        controller.grounded = character_move.ground_normal.is_some();
+        controller.ground_normal = character_move.ground_normal;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHARACTER: u32 = 100;
+    /// The height of the center of a default character standing on the ground
+    const STANDING: f32 = 0.92;
+
+    fn ground() -> (Entity, Collider, Transform) {
+        (
+            Entity::new(0),
+            Collider::cuboid(Vec3::new(10.0, 1.0, 10.0)),
+            Transform::from_xyz(0.0, -1.0, 0.0),
+        )
+    }
+
+    fn walk(
+        scene: &[(Entity, Collider, Transform)],
+        position: Vec3,
+        translation: Vec3,
+    ) -> CharacterMove {
+        let colliders = scene
+            .iter()
+            .map(|(entity, collider, transform)| (*entity, collider, *transform))
+            .collect::<Vec<_>>();
+        let controller = CharacterController {
+            translation,
+            grounded: true,
+            ..Default::default()
+        };
+        move_character(&controller, Entity::new(CHARACTER), position, &colliders)
+    }
+
+    #[test]
+    fn walks_on_the_ground() {
+        let scene = vec![ground()];
+        let character_move = walk(
+            &scene,
+            Vec3::new(0.0, STANDING, 0.0),
+            Vec3::new(1.0, -0.1, 0.0),
+        );
+        assert!(character_move
+            .position
+            .abs_diff_eq(Vec3::new(1.0, STANDING, 0.0), 0.01));
+        assert!(character_move
+            .ground_normal
+            .unwrap()
+            .abs_diff_eq(Vec3::Y, 1e-3));
+
+        // walking off the edge
+        let character_move = walk(
+            &scene,
+            Vec3::new(9.0, STANDING, 0.0),
+            Vec3::new(2.0, 0.0, 0.0),
+        );
+        assert!(character_move.ground_normal.is_none());
+    }
+
+    #[test]
+    fn slides_along_walls() {
+        let scene = vec![
+            ground(),
+            (
+                Entity::new(1),
+                Collider::cuboid(Vec3::new(0.5, 2.0, 10.0)),
+                Transform::from_xyz(2.0, 2.0, 0.0),
+            ),
+        ];
+        let character_move = walk(
+            &scene,
+            Vec3::new(0.0, STANDING, 0.0),
+            Vec3::new(3.0, 0.0, 3.0),
+        );
+        assert!(character_move
+            .position
+            .abs_diff_eq(Vec3::new(1.08, STANDING, 3.0), 0.02));
+        assert!(character_move.ground_normal.is_some());
+    }
+
+    #[test]
+    fn climbs_steps() {
+        let step = |height: f32| {
+            vec![
+                ground(),
+                (
+                    Entity::new(1),
+                    Collider::cuboid(Vec3::new(1.0, height / 2.0, 10.0)),
+                    Transform::from_xyz(2.0, height / 2.0, 0.0),
+                ),
+            ]
+        };
+        let character_move = walk(
+            &step(0.2),
+            Vec3::new(0.0, STANDING, 0.0),
+            Vec3::new(1.5, 0.0, 0.0),
+        );
+        assert!(character_move
+            .position
+            .abs_diff_eq(Vec3::new(1.5, STANDING + 0.2, 0.0), 0.01));
+
+        // too high to step on
+        let character_move = walk(
+            &step(0.5),
+            Vec3::new(0.0, STANDING, 0.0),
+            Vec3::new(1.5, 0.0, 0.0),
+        );
+        assert!(character_move.position.x < 1.0);
+        assert!((character_move.position.y - STANDING).abs() < 0.01);
+    }
+}
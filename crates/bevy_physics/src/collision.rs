@@ -0,0 +1,741 @@
+use crate::ColliderShape;
+use bevy_math::{Aabb, Mat3, Vec3};
+use bevy_transform::components::Transform;
+
+const EPSILON: f32 = 1e-6;
+
+/// A point where two colliders touch
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContactPoint {
+    /// Halfway between the surfaces of the two colliders, in world space
+    pub point: Vec3,
+    /// How far the colliders overlap along the normal
+    pub depth: f32,
+}
+
+/// The points where two colliders touch, sharing a normal
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContactManifold {
+    /// The direction from the first collider to the second one, in world space
+    pub normal: Vec3,
+    pub points: Vec<ContactPoint>,
+}
+
+impl ContactManifold {
+    fn single(normal: Vec3, point: Vec3, depth: f32) -> Self {
+        ContactManifold {
+            normal,
+            points: vec![ContactPoint { point, depth }],
+        }
+    }
+
+    /// The manifold seen from the second collider
+    pub fn flipped(mut self) -> Self {
+        self.normal = -self.normal;
+        self
+    }
+
+    pub fn max_depth(&self) -> f32 {
+        self.points
+            .iter()
+            .map(|point| point.depth)
+            .fold(0.0, f32::max)
+    }
+}
+
+impl ColliderShape {
+    /// The world space bounding box of the shape placed at `transform`
+    pub fn aabb(&self, transform: &Transform) -> Aabb {
+        let half_size = match *self {
+            ColliderShape::Sphere { radius } => Vec3::splat(radius),
+            ColliderShape::Cuboid { half_extents } => {
+                let rotation = Mat3::from_quat(transform.rotation);
+                rotation.x_axis.abs() * half_extents.x
+                    + rotation.y_axis.abs() * half_extents.y
+                    + rotation.z_axis.abs() * half_extents.z
+            }
+            ColliderShape::Capsule {
+                half_height,
+                radius,
+            } => (transform.rotation * Vec3::Y * half_height).abs() + Vec3::splat(radius),
+        };
+        Aabb::from_center_half_extents(transform.translation, half_size)
+    }
+
+    /// The distance along the ray from `origin` in the normalized `direction` at which it enters
+    /// the shape placed at `transform`, and the normal of the surface there. Rays starting inside
+    /// the shape hit it at a distance of 0, with a normal opposite to the ray.
+    pub fn cast_ray(
+        &self,
+        transform: &Transform,
+        origin: Vec3,
+        direction: Vec3,
+        max_distance: f32,
+    ) -> Option<(f32, Vec3)> {
+        let inverse_rotation = transform.rotation.conjugate();
+        let local_origin = inverse_rotation * (origin - transform.translation);
+        let local_direction = inverse_rotation * direction;
+        let (distance, local_normal) = match *self {
+            ColliderShape::Sphere { radius } => {
+                ray_sphere(local_origin, local_direction, Vec3::ZERO, radius)?
+            }
+            ColliderShape::Cuboid { half_extents } => {
+                ray_cuboid(local_origin, local_direction, half_extents)?
+            }
+            ColliderShape::Capsule {
+                half_height,
+                radius,
+            } => ray_capsule(local_origin, local_direction, half_height, radius)?,
+        };
+        if distance > max_distance {
+            return None;
+        }
+        Some((distance, transform.rotation * local_normal))
+    }
+
+    /// Half the smallest size of the shape
+    fn min_half_size(&self) -> f32 {
+        match *self {
+            ColliderShape::Sphere { radius } => radius,
+            ColliderShape::Cuboid { half_extents } => half_extents.min_element(),
+            ColliderShape::Capsule { radius, .. } => radius,
+        }
+    }
+
+    /// The segment and radius of spheres and capsules, which are the points within the radius
+    /// of the segment
+    fn rounded_segment(&self, transform: &Transform) -> Option<(Vec3, Vec3, f32)> {
+        match *self {
+            ColliderShape::Sphere { radius } => {
+                Some((transform.translation, transform.translation, radius))
+            }
+            ColliderShape::Capsule {
+                half_height,
+                radius,
+            } => {
+                let half_segment = transform.rotation * Vec3::Y * half_height;
+                Some((
+                    transform.translation - half_segment,
+                    transform.translation + half_segment,
+                    radius,
+                ))
+            }
+            ColliderShape::Cuboid { .. } => None,
+        }
+    }
+}
+
+/// The contact between `shape_a` placed at `transform_a` and `shape_b` placed at `transform_b`,
+/// if they overlap
+pub fn collide(
+    shape_a: &ColliderShape,
+    transform_a: &Transform,
+    shape_b: &ColliderShape,
+    transform_b: &Transform,
+) -> Option<ContactManifold> {
+    match (shape_a, shape_b) {
+        (
+            ColliderShape::Cuboid {
+                half_extents: half_extents_a,
+            },
+            ColliderShape::Cuboid {
+                half_extents: half_extents_b,
+            },
+        ) => collide_cuboids(*half_extents_a, transform_a, *half_extents_b, transform_b),
+        (ColliderShape::Cuboid { half_extents }, _) => {
+            let (start, end, radius) = shape_b.rounded_segment(transform_b).unwrap();
+            collide_rounded_segment_cuboid(start, end, radius, *half_extents, transform_a)
+                .map(ContactManifold::flipped)
+        }
+        (_, ColliderShape::Cuboid { half_extents }) => {
+            let (start, end, radius) = shape_a.rounded_segment(transform_a).unwrap();
+            collide_rounded_segment_cuboid(start, end, radius, *half_extents, transform_b)
+        }
+        _ => {
+            let (start_a, end_a, radius_a) = shape_a.rounded_segment(transform_a).unwrap();
+            let (start_b, end_b, radius_b) = shape_b.rounded_segment(transform_b).unwrap();
+            let (closest_a, closest_b) = closest_points_on_segments(start_a, end_a, start_b, end_b);
+            let offset = closest_b - closest_a;
+            let distance = offset.length();
+            let radii = radius_a + radius_b;
+            if distance >= radii {
+                return None;
+            }
+            let normal = if distance > EPSILON {
+                offset / distance
+            } else {
+                Vec3::Y
+            };
+            let depth = radii - distance;
+            Some(ContactManifold::single(
+                normal,
+                closest_a + normal * (radius_a - depth / 2.0),
+                depth,
+            ))
+        }
+    }
+}
+
+/// The distance `shape` placed at `transform` can move along the normalized `direction` before
+/// touching `other_shape` placed at `other_transform`, and their contact once they touch. Shapes
+/// that already overlap touch at a distance of 0.
+///
+/// The motion is sampled in steps of half the size of the smallest shape before refining the
+/// distance, so shapes grazing each other for less than a step can be missed.
+pub fn cast_shape(
+    shape: &ColliderShape,
+    transform: &Transform,
+    direction: Vec3,
+    max_distance: f32,
+    other_shape: &ColliderShape,
+    other_transform: &Transform,
+) -> Option<(f32, ContactManifold)> {
+    let moved = |distance: f32| Transform {
+        translation: transform.translation + direction * distance,
+        ..*transform
+    };
+    let collide_at = |distance: f32| collide(shape, &moved(distance), other_shape, other_transform);
+
+    if let Some(manifold) = collide_at(0.0) {
+        return Some((0.0, manifold));
+    }
+    let step = (shape.min_half_size().min(other_shape.min_half_size())).max(1e-3);
+    let mut before = 0.0;
+    loop {
+        let after = (before + step).min(max_distance);
+        if let Some(mut manifold) = collide_at(after) {
+            // bisect between the last distance without contact and the first one with contact
+            let mut after = after;
+            for _ in 0..16 {
+                let middle = (before + after) / 2.0;
+                match collide_at(middle) {
+                    Some(middle_manifold) => {
+                        after = middle;
+                        manifold = middle_manifold;
+                    }
+                    None => before = middle,
+                }
+            }
+            return Some((before, manifold));
+        }
+        if after >= max_distance {
+            return None;
+        }
+        before = after;
+    }
+}
+
+/// The closest point to `point` on the segment from `start` to `end`
+pub fn closest_point_on_segment(point: Vec3, start: Vec3, end: Vec3) -> Vec3 {
+    let direction = end - start;
+    let length_squared = direction.length_squared();
+    if length_squared <= EPSILON {
+        return start;
+    }
+    let t = ((point - start).dot(direction) / length_squared)
+        .max(0.0)
+        .min(1.0);
+    start + direction * t
+}
+
+/// The closest points of the segment from `start_a` to `end_a` and the segment from `start_b` to
+/// `end_b`
+pub fn closest_points_on_segments(
+    start_a: Vec3,
+    end_a: Vec3,
+    start_b: Vec3,
+    end_b: Vec3,
+) -> (Vec3, Vec3) {
+    let direction_a = end_a - start_a;
+    let direction_b = end_b - start_b;
+    let offset = start_a - start_b;
+    let length_squared_a = direction_a.length_squared();
+    let length_squared_b = direction_b.length_squared();
+    let f = direction_b.dot(offset);
+    let clamp = |t: f32| t.max(0.0).min(1.0);
+
+    let (s, t) = if length_squared_a <= EPSILON && length_squared_b <= EPSILON {
+        (0.0, 0.0)
+    } else if length_squared_a <= EPSILON {
+        (0.0, clamp(f / length_squared_b))
+    } else {
+        let c = direction_a.dot(offset);
+        if length_squared_b <= EPSILON {
+            (clamp(-c / length_squared_a), 0.0)
+        } else {
+            let b = direction_a.dot(direction_b);
+            let denominator = length_squared_a * length_squared_b - b * b;
+            let s = if denominator > EPSILON {
+                clamp((b * f - c * length_squared_b) / denominator)
+            } else {
+                0.0
+            };
+            let t = (b * s + f) / length_squared_b;
+            if t < 0.0 {
+                (clamp(-c / length_squared_a), 0.0)
+            } else if t > 1.0 {
+                (clamp((b - c) / length_squared_a), 1.0)
+            } else {
+                (s, t)
+            }
+        }
+    };
+    (start_a + direction_a * s, start_b + direction_b * t)
+}
+
+/// The contact of a sphere or capsule with a cuboid, with the normal going towards the cuboid
+fn collide_rounded_segment_cuboid(
+    start: Vec3,
+    end: Vec3,
+    radius: f32,
+    half_extents: Vec3,
+    transform: &Transform,
+) -> Option<ContactManifold> {
+    let inverse_rotation = transform.rotation.conjugate();
+    let start = inverse_rotation * (start - transform.translation);
+    let end = inverse_rotation * (end - transform.translation);
+
+    // alternate between the closest points of the segment and the cuboid, which converges as
+    // both are convex
+    let mut on_segment = closest_point_on_segment(Vec3::ZERO, start, end);
+    let mut on_cuboid = on_segment.max(-half_extents).min(half_extents);
+    for _ in 0..4 {
+        on_segment = closest_point_on_segment(on_cuboid, start, end);
+        on_cuboid = on_segment.max(-half_extents).min(half_extents);
+    }
+
+    let offset = on_segment - on_cuboid;
+    let distance = offset.length();
+    let (normal, depth, point) = if distance > EPSILON {
+        if distance >= radius {
+            return None;
+        }
+        let normal = offset / distance;
+        let depth = radius - distance;
+        (normal, depth, on_cuboid - normal * (depth / 2.0))
+    } else {
+        // the segment goes through the cuboid, so push it out through the closest face
+        let penetration = half_extents - on_segment.abs();
+        let (normal, penetration) =
+            if penetration.x <= penetration.y && penetration.x <= penetration.z {
+                (Vec3::X * on_segment.x.signum(), penetration.x)
+            } else if penetration.y <= penetration.z {
+                (Vec3::Y * on_segment.y.signum(), penetration.y)
+            } else {
+                (Vec3::Z * on_segment.z.signum(), penetration.z)
+            };
+        let depth = penetration + radius;
+        (normal, depth, on_segment + normal * (depth / 2.0 - radius))
+    };
+
+    // the normal points out of the cuboid, towards the segment
+    Some(ContactManifold::single(
+        -(transform.rotation * normal),
+        transform.translation + transform.rotation * point,
+        depth,
+    ))
+}
+
+/// A cuboid in world space
+struct Cuboid {
+    center: Vec3,
+    axes: [Vec3; 3],
+    half_extents: [f32; 3],
+}
+
+impl Cuboid {
+    fn new(half_extents: Vec3, transform: &Transform) -> Self {
+        let rotation = Mat3::from_quat(transform.rotation);
+        Cuboid {
+            center: transform.translation,
+            axes: [rotation.x_axis, rotation.y_axis, rotation.z_axis],
+            half_extents: half_extents.into(),
+        }
+    }
+
+    /// Half the length of the projection of the cuboid on `axis`
+    fn projected_radius(&self, axis: Vec3) -> f32 {
+        (0..3)
+            .map(|i| self.axes[i].dot(axis).abs() * self.half_extents[i])
+            .sum()
+    }
+
+    fn vertices(&self) -> impl Iterator<Item = Vec3> + '_ {
+        (0..8).map(move |i| {
+            let sign = |bit: usize| if i & bit == 0 { -1.0 } else { 1.0 };
+            self.center
+                + self.axes[0] * self.half_extents[0] * sign(1)
+                + self.axes[1] * self.half_extents[1] * sign(2)
+                + self.axes[2] * self.half_extents[2] * sign(4)
+        })
+    }
+
+    /// The edge parallel to the axis `axis_index` that is the furthest along `direction`
+    fn support_edge(&self, axis_index: usize, direction: Vec3) -> (Vec3, Vec3) {
+        let mut center = self.center;
+        for i in 0..3 {
+            if i != axis_index {
+                let sign = if self.axes[i].dot(direction) < 0.0 {
+                    -1.0
+                } else {
+                    1.0
+                };
+                center += self.axes[i] * self.half_extents[i] * sign;
+            }
+        }
+        let half_edge = self.axes[axis_index] * self.half_extents[axis_index];
+        (center - half_edge, center + half_edge)
+    }
+}
+
+enum SeparatingAxis {
+    FaceA(usize),
+    FaceB(usize),
+    Edges(usize, usize),
+}
+
+/// The contact of two cuboids, found with the separating axis theorem
+fn collide_cuboids(
+    half_extents_a: Vec3,
+    transform_a: &Transform,
+    half_extents_b: Vec3,
+    transform_b: &Transform,
+) -> Option<ContactManifold> {
+    let a = Cuboid::new(half_extents_a, transform_a);
+    let b = Cuboid::new(half_extents_b, transform_b);
+    let offset = b.center - a.center;
+
+    let mut best: Option<(f32, Vec3, SeparatingAxis)> = None;
+    let mut test_axis = |axis: Vec3, separating_axis: SeparatingAxis, bias: f32| {
+        let length = axis.length();
+        if length <= 1e-4 {
+            // parallel edges, already covered by the face axes
+            return true;
+        }
+        let axis = axis / length;
+        let overlap = a.projected_radius(axis) + b.projected_radius(axis) - offset.dot(axis).abs();
+        if overlap < 0.0 {
+            return false;
+        }
+        if best
+            .as_ref()
+            .map_or(true, |(best_overlap, _, _)| overlap * bias < *best_overlap)
+        {
+            let normal = if offset.dot(axis) < 0.0 { -axis } else { axis };
+            best = Some((overlap, normal, separating_axis));
+        }
+        true
+    };
+
+    for i in 0..3 {
+        if !test_axis(a.axes[i], SeparatingAxis::FaceA(i), 1.0) {
+            return None;
+        }
+    }
+    for i in 0..3 {
+        if !test_axis(b.axes[i], SeparatingAxis::FaceB(i), 1.0) {
+            return None;
+        }
+    }
+    // edge axes only win by a margin, as face contacts are more stable
+    for i in 0..3 {
+        for j in 0..3 {
+            if !test_axis(
+                a.axes[i].cross(b.axes[j]),
+                SeparatingAxis::Edges(i, j),
+                1.05,
+            ) {
+                return None;
+            }
+        }
+    }
+
+    let (depth, normal, separating_axis) = best?;
+    match separating_axis {
+        SeparatingAxis::FaceA(axis) => Some(face_contact(&a, axis, normal, &b, normal, depth)),
+        SeparatingAxis::FaceB(axis) => Some(face_contact(&b, axis, -normal, &a, normal, depth)),
+        SeparatingAxis::Edges(axis_a, axis_b) => {
+            let (start_a, end_a) = a.support_edge(axis_a, normal);
+            let (start_b, end_b) = b.support_edge(axis_b, -normal);
+            let (closest_a, closest_b) = closest_points_on_segments(start_a, end_a, start_b, end_b);
+            Some(ContactManifold::single(
+                normal,
+                (closest_a + closest_b) / 2.0,
+                depth,
+            ))
+        }
+    }
+}
+
+/// The contact of the vertices of the `incident` cuboid with the face of the `reference` cuboid
+/// along `face_normal`
+fn face_contact(
+    reference: &Cuboid,
+    axis: usize,
+    face_normal: Vec3,
+    incident: &Cuboid,
+    normal: Vec3,
+    depth: f32,
+) -> ContactManifold {
+    let face_distance = face_normal.dot(reference.center) + reference.half_extents[axis];
+    let points = incident
+        .vertices()
+        .filter_map(|vertex| {
+            let vertex_depth = face_distance - face_normal.dot(vertex);
+            if vertex_depth <= 0.0 {
+                return None;
+            }
+            // keep the contact within the face
+            let offset = vertex - reference.center;
+            let vertex = (0..3).fold(reference.center, |vertex, i| {
+                let mut distance = reference.axes[i].dot(offset);
+                if i != axis {
+                    let half_extent = reference.half_extents[i];
+                    distance = distance.max(-half_extent).min(half_extent);
+                }
+                vertex + reference.axes[i] * distance
+            });
+            Some(ContactPoint {
+                point: vertex + face_normal * (vertex_depth / 2.0),
+                depth: vertex_depth,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    if points.is_empty() {
+        // an edge of the incident cuboid crosses the face without any vertex going through it
+        let (start, end) = incident.support_edge(0, -face_normal);
+        let point = (start + end) / 2.0 - face_normal * (depth / 2.0);
+        ContactManifold::single(normal, point, depth)
+    } else {
+        ContactManifold { normal, points }
+    }
+}
+
+/// The first intersection of a ray with a sphere, and the normal there
+fn ray_sphere(origin: Vec3, direction: Vec3, center: Vec3, radius: f32) -> Option<(f32, Vec3)> {
+    let offset = origin - center;
+    let c = offset.length_squared() - radius * radius;
+    if c <= 0.0 {
+        return Some((0.0, -direction));
+    }
+    let b = offset.dot(direction);
+    let discriminant = b * b - c;
+    if b > 0.0 || discriminant < 0.0 {
+        return None;
+    }
+    let distance = -b - discriminant.sqrt();
+    Some((distance, (offset + direction * distance) / radius))
+}
+
+/// The first intersection of a ray with a cuboid centered on the origin, and the normal there
+fn ray_cuboid(origin: Vec3, direction: Vec3, half_extents: Vec3) -> Option<(f32, Vec3)> {
+    if origin.abs().cmple(half_extents).all() {
+        return Some((0.0, -direction));
+    }
+    let origin: [f32; 3] = origin.into();
+    let direction: [f32; 3] = direction.into();
+    let half_extents: [f32; 3] = half_extents.into();
+    let axes = [Vec3::X, Vec3::Y, Vec3::Z];
+    let (mut near, mut far) = (f32::NEG_INFINITY, f32::INFINITY);
+    let mut normal = Vec3::ZERO;
+    for i in 0..3 {
+        if direction[i].abs() <= EPSILON {
+            if origin[i].abs() > half_extents[i] {
+                return None;
+            }
+            continue;
+        }
+        let first = (-half_extents[i] - origin[i]) / direction[i];
+        let second = (half_extents[i] - origin[i]) / direction[i];
+        let (entry, exit) = (first.min(second), first.max(second));
+        if entry > near {
+            near = entry;
+            normal = axes[i] * -direction[i].signum();
+        }
+        far = far.min(exit);
+    }
+    if near > far || near < 0.0 {
+        return None;
+    }
+    Some((near, normal))
+}
+
+/// The first intersection of a ray with a capsule centered on the origin along the y axis, and
+/// the normal there
+fn ray_capsule(
+    origin: Vec3,
+    direction: Vec3,
+    half_height: f32,
+    radius: f32,
+) -> Option<(f32, Vec3)> {
+    let (bottom, top) = (-Vec3::Y * half_height, Vec3::Y * half_height);
+    if closest_point_on_segment(origin, bottom, top).distance_squared(origin) <= radius * radius {
+        return Some((0.0, -direction));
+    }
+
+    let mut closest = None;
+    let mut keep_closest = |hit: Option<(f32, Vec3)>| {
+        if let Some(hit) = hit {
+            if closest.map_or(true, |(distance, _)| hit.0 < distance) {
+                closest = Some(hit);
+            }
+        }
+    };
+    keep_closest(ray_sphere(origin, direction, bottom, radius));
+    keep_closest(ray_sphere(origin, direction, top, radius));
+
+    // the side of the capsule, as an infinite cylinder cut between the half spheres
+    let a = direction.x * direction.x + direction.z * direction.z;
+    if a > EPSILON {
+        let b = origin.x * direction.x + origin.z * direction.z;
+        let c = origin.x * origin.x + origin.z * origin.z - radius * radius;
+        let discriminant = b * b - a * c;
+        if discriminant >= 0.0 {
+            let distance = (-b - discriminant.sqrt()) / a;
+            let hit = origin + direction * distance;
+            if distance >= 0.0 && hit.y.abs() <= half_height {
+                keep_closest(Some((distance, Vec3::new(hit.x, 0.0, hit.z) / radius)));
+            }
+        }
+    }
+    closest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_math::Quat;
+
+    fn at(x: f32, y: f32, z: f32) -> Transform {
+        Transform::from_xyz(x, y, z)
+    }
+
+    #[test]
+    fn collides_spheres() {
+        let sphere = ColliderShape::Sphere { radius: 1.0 };
+        let contact = collide(&sphere, &at(0.0, 0.0, 0.0), &sphere, &at(1.5, 0.0, 0.0)).unwrap();
+        assert!(contact.normal.abs_diff_eq(Vec3::X, 1e-5));
+        assert_eq!(contact.points.len(), 1);
+        assert!((contact.points[0].depth - 0.5).abs() < 1e-5);
+        assert!(contact.points[0]
+            .point
+            .abs_diff_eq(Vec3::new(0.75, 0.0, 0.0), 1e-5));
+        assert!(collide(&sphere, &at(0.0, 0.0, 0.0), &sphere, &at(2.5, 0.0, 0.0)).is_none());
+    }
+
+    #[test]
+    fn collides_capsule_with_cuboid() {
+        let capsule = ColliderShape::Capsule {
+            half_height: 1.0,
+            radius: 0.5,
+        };
+        let ground = ColliderShape::Cuboid {
+            half_extents: Vec3::new(10.0, 1.0, 10.0),
+        };
+        let contact = collide(&capsule, &at(0.0, 2.25, 0.0), &ground, &at(0.0, 0.0, 0.0)).unwrap();
+        assert!(contact.normal.abs_diff_eq(-Vec3::Y, 1e-5));
+        assert!((contact.max_depth() - 0.25).abs() < 1e-5);
+
+        let flipped = collide(&ground, &at(0.0, 0.0, 0.0), &capsule, &at(0.0, 2.25, 0.0)).unwrap();
+        assert!(flipped.normal.abs_diff_eq(Vec3::Y, 1e-5));
+        assert!(collide(&capsule, &at(0.0, 2.75, 0.0), &ground, &at(0.0, 0.0, 0.0)).is_none());
+    }
+
+    #[test]
+    fn collides_cuboids() {
+        let cuboid = ColliderShape::Cuboid {
+            half_extents: Vec3::splat(0.5),
+        };
+        let ground = ColliderShape::Cuboid {
+            half_extents: Vec3::new(10.0, 1.0, 10.0),
+        };
+        let contact = collide(&ground, &at(0.0, 0.0, 0.0), &cuboid, &at(0.0, 1.4, 0.0)).unwrap();
+        assert!(contact.normal.abs_diff_eq(Vec3::Y, 1e-5));
+        assert_eq!(contact.points.len(), 4);
+        for point in contact.points.iter() {
+            assert!((point.depth - 0.1).abs() < 1e-5);
+            assert!((point.point.y - 0.95).abs() < 1e-5);
+        }
+
+        let rotated = Transform {
+            rotation: Quat::from_rotation_y(0.5),
+            ..at(0.0, 0.0, 0.9)
+        };
+        assert!(collide(&cuboid, &at(0.0, 0.0, 0.0), &cuboid, &rotated).is_some());
+        assert!(collide(&cuboid, &at(0.0, 0.0, 0.0), &cuboid, &at(0.0, 0.0, 1.1)).is_none());
+    }
+
+    #[test]
+    fn casts_rays() {
+        let down = -Vec3::Y;
+        let sphere = ColliderShape::Sphere { radius: 1.0 };
+        let (distance, normal) = sphere
+            .cast_ray(&at(0.0, 0.0, 0.0), Vec3::new(0.0, 5.0, 0.0), down, 10.0)
+            .unwrap();
+        assert!((distance - 4.0).abs() < 1e-5);
+        assert!(normal.abs_diff_eq(Vec3::Y, 1e-5));
+        assert!(sphere
+            .cast_ray(&at(0.0, 0.0, 0.0), Vec3::new(0.0, 5.0, 0.0), down, 3.0)
+            .is_none());
+
+        let cuboid = ColliderShape::Cuboid {
+            half_extents: Vec3::new(1.0, 2.0, 1.0),
+        };
+        let rotated = Transform {
+            rotation: Quat::from_rotation_z(std::f32::consts::FRAC_PI_2),
+            ..at(0.0, 0.0, 0.0)
+        };
+        let (distance, normal) = cuboid
+            .cast_ray(&rotated, Vec3::new(0.0, 5.0, 0.0), down, 10.0)
+            .unwrap();
+        assert!((distance - 4.0).abs() < 1e-5);
+        assert!(normal.abs_diff_eq(Vec3::Y, 1e-5));
+        // starting inside
+        let (distance, normal) = cuboid.cast_ray(&rotated, Vec3::ZERO, down, 10.0).unwrap();
+        assert_eq!(distance, 0.0);
+        assert!(normal.abs_diff_eq(Vec3::Y, 1e-5));
+
+        let capsule = ColliderShape::Capsule {
+            half_height: 1.0,
+            radius: 0.5,
+        };
+        let (distance, normal) = capsule
+            .cast_ray(&at(0.0, 0.0, 0.0), Vec3::new(-5.0, 0.5, 0.0), Vec3::X, 10.0)
+            .unwrap();
+        assert!((distance - 4.5).abs() < 1e-5);
+        assert!(normal.abs_diff_eq(-Vec3::X, 1e-5));
+        let (distance, _) = capsule
+            .cast_ray(&at(0.0, 0.0, 0.0), Vec3::new(0.0, 5.0, 0.0), down, 10.0)
+            .unwrap();
+        assert!((distance - 3.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn casts_shapes() {
+        let sphere = ColliderShape::Sphere { radius: 0.5 };
+        let ground = ColliderShape::Cuboid {
+            half_extents: Vec3::new(10.0, 1.0, 10.0),
+        };
+        let (distance, manifold) = cast_shape(
+            &sphere,
+            &at(0.0, 5.0, 0.0),
+            -Vec3::Y,
+            10.0,
+            &ground,
+            &at(0.0, 0.0, 0.0),
+        )
+        .unwrap();
+        assert!((distance - 3.5).abs() < 1e-3);
+        assert!(manifold.normal.abs_diff_eq(-Vec3::Y, 1e-5));
+        assert!(cast_shape(
+            &sphere,
+            &at(0.0, 5.0, 0.0),
+            -Vec3::Y,
+            3.0,
+            &ground,
+            &at(0.0, 0.0, 0.0),
+        )
+        .is_none());
+    }
+}
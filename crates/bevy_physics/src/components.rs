@@ -0,0 +1,192 @@
+use bevy_ecs::reflect::ReflectComponent;
+use bevy_math::Vec3;
+use bevy_reflect::{Reflect, ReflectDeserialize};
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+
+/// How an entity with a [Collider] is moved by the simulation. Entities with a [Collider] but
+/// no [RigidBody] are static.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Reflect)]
+#[reflect_value(PartialEq, Serialize, Deserialize, Component)]
+pub enum RigidBody {
+    /// Moved by gravity, forces and collisions
+    Dynamic,
+    /// Never moves, and pushes dynamic bodies out of itself
+    Static,
+    /// Moved only by its [Velocity] or by writing its transform, and pushes dynamic bodies out of
+    /// its way
+    Kinematic,
+}
+
+impl Default for RigidBody {
+    fn default() -> Self {
+        RigidBody::Dynamic
+    }
+}
+
+impl RigidBody {
+    pub fn is_dynamic(&self) -> bool {
+        matches!(self, RigidBody::Dynamic)
+    }
+}
+
+/// The velocity of a [RigidBody], in world space. Updated by the simulation for dynamic bodies,
+/// and set by gameplay code for kinematic bodies.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Reflect)]
+#[reflect(Component, PartialEq)]
+pub struct Velocity {
+    pub linear: Vec3,
+    /// The rotation axis scaled by the rotation speed, in radians per second
+    pub angular: Vec3,
+}
+
+impl Velocity {
+    pub fn from_linear(linear: Vec3) -> Self {
+        Velocity {
+            linear,
+            angular: Vec3::ZERO,
+        }
+    }
+}
+
+/// A force and torque applied to a dynamic [RigidBody] at every step, in world space
+#[derive(Debug, Clone, Copy, Default, PartialEq, Reflect)]
+#[reflect(Component, PartialEq)]
+pub struct ExternalForce {
+    pub force: Vec3,
+    pub torque: Vec3,
+}
+
+/// The shape of a [Collider], centered on the translation of its entity and rotated with it. The
+/// scale of the entity is ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Reflect)]
+#[reflect_value(PartialEq, Serialize, Deserialize)]
+pub enum ColliderShape {
+    Sphere {
+        radius: f32,
+    },
+    Cuboid {
+        half_extents: Vec3,
+    },
+    /// A cylinder along the local y axis capped by two half spheres
+    Capsule {
+        /// Half the distance between the centers of the two half spheres
+        half_height: f32,
+        radius: f32,
+    },
+}
+
+impl Default for ColliderShape {
+    fn default() -> Self {
+        ColliderShape::Sphere { radius: 0.5 }
+    }
+}
+
+impl ColliderShape {
+    pub fn volume(&self) -> f32 {
+        match *self {
+            ColliderShape::Sphere { radius } => 4.0 / 3.0 * PI * radius.powi(3),
+            ColliderShape::Cuboid { half_extents } => {
+                8.0 * half_extents.x * half_extents.y * half_extents.z
+            }
+            ColliderShape::Capsule {
+                half_height,
+                radius,
+            } => PI * radius * radius * (2.0 * half_height + 4.0 / 3.0 * radius),
+        }
+    }
+
+    /// The principal moments of inertia of the shape around its local axes, for a given `mass`
+    pub fn principal_inertia(&self, mass: f32) -> Vec3 {
+        match *self {
+            ColliderShape::Sphere { radius } => Vec3::splat(0.4 * mass * radius * radius),
+            ColliderShape::Cuboid { half_extents } => {
+                let size = half_extents * 2.0;
+                let squared = size * size;
+                Vec3::new(
+                    squared.y + squared.z,
+                    squared.x + squared.z,
+                    squared.x + squared.y,
+                ) * (mass / 12.0)
+            }
+            ColliderShape::Capsule {
+                half_height,
+                radius,
+            } => {
+                // split the mass between the cylinder and the two half spheres by volume
+                let cylinder_volume = PI * radius * radius * 2.0 * half_height;
+                let sphere_volume = 4.0 / 3.0 * PI * radius.powi(3);
+                let total_volume = cylinder_volume + sphere_volume;
+                let cylinder_mass = mass * cylinder_volume / total_volume;
+                let sphere_mass = mass * sphere_volume / total_volume;
+                let radius_squared = radius * radius;
+                let axial =
+                    cylinder_mass * radius_squared / 2.0 + sphere_mass * 0.4 * radius_squared;
+                let transverse = cylinder_mass
+                    * (half_height * half_height / 3.0 + radius_squared / 4.0)
+                    + sphere_mass
+                        * (0.4 * radius_squared
+                            + half_height * half_height
+                            + 0.75 * half_height * radius);
+                Vec3::new(transverse, axial, transverse)
+            }
+        }
+    }
+}
+
+/// Makes an entity collide with the other colliders. Its mass is derived from its shape and
+/// `density` when it is a dynamic [RigidBody].
+#[derive(Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct Collider {
+    pub shape: ColliderShape,
+    pub density: f32,
+    /// The friction coefficient, combined with the one of the other collider by their geometric
+    /// mean
+    pub friction: f32,
+    /// How much of the velocity along the contact normal is kept when bouncing, combined with the
+    /// one of the other collider by taking the largest
+    pub restitution: f32,
+    /// Sensors report collisions but don't push other colliders
+    pub is_sensor: bool,
+}
+
+impl Default for Collider {
+    fn default() -> Self {
+        Collider {
+            shape: Default::default(),
+            density: 1.0,
+            friction: 0.5,
+            restitution: 0.0,
+            is_sensor: false,
+        }
+    }
+}
+
+impl Collider {
+    pub fn new(shape: ColliderShape) -> Self {
+        Collider {
+            shape,
+            ..Default::default()
+        }
+    }
+
+    pub fn sphere(radius: f32) -> Self {
+        Self::new(ColliderShape::Sphere { radius })
+    }
+
+    pub fn cuboid(half_extents: Vec3) -> Self {
+        Self::new(ColliderShape::Cuboid { half_extents })
+    }
+
+    pub fn capsule(half_height: f32, radius: f32) -> Self {
+        Self::new(ColliderShape::Capsule {
+            half_height,
+            radius,
+        })
+    }
+
+    pub fn mass(&self) -> f32 {
+        self.shape.volume() * self.density
+    }
+}
@@ -0,0 +1,175 @@
+use crate::ContactManifold;
+use bevy_ecs::entity::Entity;
+use bevy_utils::HashMap;
+
+/// Sent at the first step two colliders overlap
+#[derive(Debug, Clone)]
+pub struct CollisionStarted {
+    pub entity_a: Entity,
+    pub entity_b: Entity,
+    /// The contact points, with a normal going from `entity_a` to `entity_b`
+    pub manifold: ContactManifold,
+}
+
+/// Sent at the first step two colliders stop overlapping, or after one of them was despawned or
+/// lost its [Collider](crate::Collider)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CollisionEnded {
+    pub entity_a: Entity,
+    pub entity_b: Entity,
+}
+
+/// The colliders that overlapped at the last step of the simulation
+#[derive(Debug, Default)]
+pub struct Contacts {
+    /// The manifolds of each pair, keyed by the smallest entity first with a normal going from it
+    /// to the other entity
+    pairs: HashMap<(Entity, Entity), ContactManifold>,
+}
+
+fn key(entity_a: Entity, entity_b: Entity) -> ((Entity, Entity), bool) {
+    if entity_a <= entity_b {
+        ((entity_a, entity_b), false)
+    } else {
+        ((entity_b, entity_a), true)
+    }
+}
+
+impl Contacts {
+    pub fn contains(&self, entity_a: Entity, entity_b: Entity) -> bool {
+        self.pairs.contains_key(&key(entity_a, entity_b).0)
+    }
+
+    /// The contact of two colliders, with a normal going from `entity_a` to `entity_b`
+    pub fn get(&self, entity_a: Entity, entity_b: Entity) -> Option<ContactManifold> {
+        let (key, flipped) = key(entity_a, entity_b);
+        let manifold = self.pairs.get(&key)?.clone();
+        Some(if flipped {
+            manifold.flipped()
+        } else {
+            manifold
+        })
+    }
+
+    /// The colliders touching `entity`
+    pub fn colliding_with(&self, entity: Entity) -> impl Iterator<Item = Entity> + '_ {
+        self.pairs.keys().filter_map(move |(entity_a, entity_b)| {
+            if *entity_a == entity {
+                Some(*entity_b)
+            } else if *entity_b == entity {
+                Some(*entity_a)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Every overlapping pair, with a normal going from the first entity to the second one
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, Entity, &ContactManifold)> {
+        self.pairs
+            .iter()
+            .map(|((entity_a, entity_b), manifold)| (*entity_a, *entity_b, manifold))
+    }
+
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+
+    /// Replaces the contacts with the ones of a new step, and returns the pairs that started and
+    /// stopped overlapping
+    pub fn update(
+        &mut self,
+        contacts: impl Iterator<Item = (Entity, Entity, ContactManifold)>,
+    ) -> (Vec<CollisionStarted>, Vec<CollisionEnded>) {
+        let mut pairs = HashMap::default();
+        for (entity_a, entity_b, manifold) in contacts {
+            let (key, flipped) = key(entity_a, entity_b);
+            let manifold = if flipped {
+                manifold.flipped()
+            } else {
+                manifold
+            };
+            pairs.insert(key, manifold);
+        }
+
+        let mut started = pairs
+            .iter()
+            .filter(|(key, _)| !self.pairs.contains_key(key))
+            .map(|((entity_a, entity_b), manifold)| CollisionStarted {
+                entity_a: *entity_a,
+                entity_b: *entity_b,
+                manifold: manifold.clone(),
+            })
+            .collect::<Vec<_>>();
+        let mut ended = self
+            .pairs
+            .keys()
+            .filter(|key| !pairs.contains_key(key))
+            .map(|(entity_a, entity_b)| CollisionEnded {
+                entity_a: *entity_a,
+                entity_b: *entity_b,
+            })
+            .collect::<Vec<_>>();
+        // keep the events in a stable order, independent of the hash map
+        started.sort_by_key(|event| (event.entity_a, event.entity_b));
+        ended.sort_by_key(|event| (event.entity_a, event.entity_b));
+
+        self.pairs = pairs;
+        (started, ended)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ContactPoint;
+    use bevy_math::Vec3;
+
+    fn manifold() -> ContactManifold {
+        ContactManifold {
+            normal: Vec3::X,
+            points: vec![ContactPoint {
+                point: Vec3::ZERO,
+                depth: 0.1,
+            }],
+        }
+    }
+
+    #[test]
+    fn reports_started_and_ended_pairs() {
+        let (a, b, c) = (Entity::new(0), Entity::new(1), Entity::new(2));
+        let mut contacts = Contacts::default();
+
+        let (started, ended) = contacts.update(vec![(b, a, manifold())].into_iter());
+        assert_eq!(started.len(), 1);
+        assert_eq!((started[0].entity_a, started[0].entity_b), (a, b));
+        assert_eq!(started[0].manifold.normal, -Vec3::X);
+        assert!(ended.is_empty());
+        assert_eq!(contacts.get(b, a).unwrap().normal, Vec3::X);
+
+        let (started, ended) =
+            contacts.update(vec![(a, b, manifold()), (c, a, manifold())].into_iter());
+        assert_eq!(started.len(), 1);
+        assert_eq!((started[0].entity_a, started[0].entity_b), (a, c));
+        assert!(ended.is_empty());
+        let mut colliding = contacts.colliding_with(a).collect::<Vec<_>>();
+        colliding.sort();
+        assert_eq!(colliding, vec![b, c]);
+
+        let (started, ended) = contacts.update(vec![(a, c, manifold())].into_iter());
+        assert!(started.is_empty());
+        assert_eq!(
+            ended,
+            vec![CollisionEnded {
+                entity_a: a,
+                entity_b: b,
+            }]
+        );
+        assert!(!contacts.contains(a, b));
+        assert!(contacts.contains(c, a));
+    }
+}
@@ -0,0 +1,136 @@
+mod character_controller;
+mod collision;
+mod components;
+mod contacts;
+mod scene_query;
+mod simulation;
+
+pub use character_controller::*;
+pub use collision::*;
+pub use components::*;
+pub use contacts::*;
+pub use scene_query::*;
+pub use simulation::physics_step_system;
+
+pub mod prelude {
+    pub use crate::{
+        CharacterController, Collider, ColliderShape, Collision, CollisionEnded, CollisionStarted,
+        Contacts, ExternalForce, PhysicsPlugin, PhysicsSettings, RigidBody, RigidBodyBundle,
+        SceneQuery, SceneQueryFilter, Velocity,
+    };
+}
+
+use bevy_app::prelude::*;
+use bevy_core::FixedTimestep;
+use bevy_ecs::{
+    bundle::Bundle,
+    entity::Entity,
+    schedule::{ParallelSystemDescriptorCoercion, StageLabel, SystemLabel, SystemStage},
+    system::IntoSystem,
+};
+use bevy_math::Vec3;
+use bevy_transform::{
+    components::{GlobalTransform, Transform},
+    TransformSystem,
+};
+
+/// The label of the fixed timestep of the physics stage, to get its state from
+/// [FixedTimesteps](bevy_core::FixedTimesteps)
+pub const PHYSICS_TIMESTEP: &str = "physics";
+
+/// The names of physics stages in an App Schedule
+#[derive(Debug, Hash, PartialEq, Eq, Clone, StageLabel)]
+pub enum PhysicsStage {
+    /// Runs after [CoreStage::Update] once per step of the physics timestep, so it can run several
+    /// times or not at all in a frame
+    Step,
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub enum PhysicsSystem {
+    Step,
+    CharacterController,
+}
+
+/// Settings of the simulation. The timestep is read when [PhysicsPlugin] is added, so it has to be
+/// inserted before it to be changed.
+#[derive(Debug, Clone)]
+pub struct PhysicsSettings {
+    /// The duration of a step, in seconds
+    pub timestep: f64,
+    pub gravity: Vec3,
+    /// How many times the contacts are solved at each step. More iterations make stacks of bodies
+    /// more stable.
+    pub solver_iterations: u32,
+}
+
+impl Default for PhysicsSettings {
+    fn default() -> Self {
+        PhysicsSettings {
+            timestep: 1.0 / 60.0,
+            gravity: Vec3::new(0.0, -9.81, 0.0),
+            solver_iterations: 8,
+        }
+    }
+}
+
+/// Sent for every pair of overlapping colliders at each step
+#[derive(Debug, Clone)]
+pub struct Collision {
+    pub entity_a: Entity,
+    pub entity_b: Entity,
+    /// The contact points, with a normal going from `entity_a` to `entity_b`
+    pub manifold: ContactManifold,
+}
+
+/// A component bundle for rigid bodies. Dynamic bodies need a [Velocity] to keep moving from one
+/// step to the next.
+#[derive(Bundle, Default)]
+pub struct RigidBodyBundle {
+    pub rigid_body: RigidBody,
+    pub collider: Collider,
+    pub velocity: Velocity,
+    pub transform: Transform,
+    pub global_transform: GlobalTransform,
+}
+
+/// Simulates entities with a [Collider] at a fixed timestep.
+///
+/// Their [Transform] is read at each step and written back when they move, so gameplay systems
+/// can teleport bodies and move kinematic bodies by changing their transform. Bodies are
+/// simulated in the space of their transform, so they should not have a parent.
+#[derive(Default)]
+pub struct PhysicsPlugin;
+
+impl Plugin for PhysicsPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        let timestep = app
+            .world_mut()
+            .get_resource_or_insert_with(PhysicsSettings::default)
+            .timestep;
+        app.register_type::<RigidBody>()
+            .register_type::<Velocity>()
+            .register_type::<ExternalForce>()
+            .register_type::<ColliderShape>()
+            .register_type::<Collider>()
+            .register_type::<CharacterController>()
+            .init_resource::<Contacts>()
+            .add_event::<Collision>()
+            .add_event::<CollisionStarted>()
+            .add_event::<CollisionEnded>()
+            .add_stage_after(
+                CoreStage::Update,
+                PhysicsStage::Step,
+                SystemStage::parallel()
+                    .with_run_criteria(FixedTimestep::step(timestep).with_label(PHYSICS_TIMESTEP))
+                    .with_system(physics_step_system.system().label(PhysicsSystem::Step)),
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                character_controller_system
+                    .system()
+                    .label(PhysicsSystem::CharacterController)
+                    .before(TransformSystem::TransformPropagate),
+            );
+    }
+}
@@ -0,0 +1,305 @@
+use crate::{cast_shape, collide, Collider, ColliderShape, ContactManifold};
+use bevy_ecs::{
+    entity::Entity,
+    system::{Query, SystemParam},
+};
+use bevy_math::Vec3;
+use bevy_transform::components::{GlobalTransform, Transform};
+
+/// The colliders a [SceneQuery] can hit
+#[derive(Debug, Clone, Default)]
+pub struct SceneQueryFilter {
+    /// An entity to skip, usually the one making the query
+    pub excluded: Option<Entity>,
+    pub include_sensors: bool,
+}
+
+impl SceneQueryFilter {
+    pub fn excluding(entity: Entity) -> Self {
+        SceneQueryFilter {
+            excluded: Some(entity),
+            ..Default::default()
+        }
+    }
+
+    fn allows(&self, entity: Entity, collider: &Collider) -> bool {
+        self.excluded != Some(entity) && (self.include_sensors || !collider.is_sensor)
+    }
+}
+
+/// A collider hit by a ray
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayHit {
+    pub entity: Entity,
+    /// The distance along the ray, in world units
+    pub distance: f32,
+    /// The world space position of the hit
+    pub position: Vec3,
+    /// The normal of the surface of the collider at the hit
+    pub normal: Vec3,
+}
+
+/// A collider hit by a moving shape
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShapeHit {
+    pub entity: Entity,
+    /// How far the shape moved before touching the collider
+    pub distance: f32,
+    /// The contact with the collider once they touch, with a normal going from the shape to the
+    /// collider
+    pub manifold: ContactManifold,
+}
+
+/// Casts rays and shapes against colliders, and finds the colliders overlapping a shape. Can be
+/// used from any system, and sees colliders where they were at the last transform propagation.
+#[derive(SystemParam)]
+pub struct SceneQuery<'a> {
+    colliders: Query<'a, (Entity, &'static Collider, &'static GlobalTransform)>,
+}
+
+impl<'a> SceneQuery<'a> {
+    fn colliders(&self) -> impl Iterator<Item = (Entity, &Collider, Transform)> {
+        self.colliders
+            .iter()
+            .map(|(entity, collider, global_transform)| {
+                (entity, collider, Transform::from(*global_transform))
+            })
+    }
+
+    /// Returns the closest collider hit by the ray from `origin` along `direction`. Returns
+    /// `None` if `direction` is zero.
+    pub fn cast_ray(
+        &self,
+        origin: Vec3,
+        direction: Vec3,
+        max_distance: f32,
+        filter: &SceneQueryFilter,
+    ) -> Option<RayHit> {
+        cast_ray(self.colliders(), origin, direction, max_distance, filter)
+    }
+
+    /// Returns the first collider hit by `shape` moving from `transform` along `direction`.
+    /// Returns `None` if `direction` is zero.
+    pub fn cast_shape(
+        &self,
+        shape: &ColliderShape,
+        transform: &Transform,
+        direction: Vec3,
+        max_distance: f32,
+        filter: &SceneQueryFilter,
+    ) -> Option<ShapeHit> {
+        cast_shape_against(
+            self.colliders(),
+            shape,
+            transform,
+            direction,
+            max_distance,
+            filter,
+        )
+    }
+
+    /// Returns the colliders overlapping `shape` placed at `transform`, with contact normals
+    /// going from the shape to the colliders
+    pub fn overlaps(
+        &self,
+        shape: &ColliderShape,
+        transform: &Transform,
+        filter: &SceneQueryFilter,
+    ) -> Vec<(Entity, ContactManifold)> {
+        overlaps(self.colliders(), shape, transform, filter)
+    }
+}
+
+pub(crate) fn cast_ray<'c>(
+    colliders: impl Iterator<Item = (Entity, &'c Collider, Transform)>,
+    origin: Vec3,
+    direction: Vec3,
+    max_distance: f32,
+    filter: &SceneQueryFilter,
+) -> Option<RayHit> {
+    let direction = direction.try_normalize()?;
+    let mut closest: Option<RayHit> = None;
+    for (entity, collider, transform) in colliders {
+        if !filter.allows(entity, collider) {
+            continue;
+        }
+        let max_distance = closest.map_or(max_distance, |closest| closest.distance);
+        if let Some((distance, normal)) =
+            collider
+                .shape
+                .cast_ray(&transform, origin, direction, max_distance)
+        {
+            if closest.map_or(true, |closest| distance < closest.distance) {
+                closest = Some(RayHit {
+                    entity,
+                    distance,
+                    position: origin + direction * distance,
+                    normal,
+                });
+            }
+        }
+    }
+    closest
+}
+
+pub(crate) fn cast_shape_against<'c>(
+    colliders: impl Iterator<Item = (Entity, &'c Collider, Transform)>,
+    shape: &ColliderShape,
+    transform: &Transform,
+    direction: Vec3,
+    max_distance: f32,
+    filter: &SceneQueryFilter,
+) -> Option<ShapeHit> {
+    let direction = direction.try_normalize()?;
+    let start = shape.aabb(transform);
+    let end = shape.aabb(&Transform {
+        translation: transform.translation + direction * max_distance,
+        ..*transform
+    });
+    let swept = start.merge(&end);
+
+    let mut closest: Option<ShapeHit> = None;
+    for (entity, collider, collider_transform) in colliders {
+        if !filter.allows(entity, collider)
+            || !swept.intersects(&collider.shape.aabb(&collider_transform))
+        {
+            continue;
+        }
+        let max_distance = closest
+            .as_ref()
+            .map_or(max_distance, |closest| closest.distance);
+        if let Some((distance, manifold)) = cast_shape(
+            shape,
+            transform,
+            direction,
+            max_distance,
+            &collider.shape,
+            &collider_transform,
+        ) {
+            if closest
+                .as_ref()
+                .map_or(true, |closest| distance < closest.distance)
+            {
+                closest = Some(ShapeHit {
+                    entity,
+                    distance,
+                    manifold,
+                });
+            }
+        }
+    }
+    closest
+}
+
+pub(crate) fn overlaps<'c>(
+    colliders: impl Iterator<Item = (Entity, &'c Collider, Transform)>,
+    shape: &ColliderShape,
+    transform: &Transform,
+    filter: &SceneQueryFilter,
+) -> Vec<(Entity, ContactManifold)> {
+    let aabb = shape.aabb(transform);
+    colliders
+        .filter(|(entity, collider, collider_transform)| {
+            filter.allows(*entity, collider)
+                && aabb.intersects(&collider.shape.aabb(collider_transform))
+        })
+        .filter_map(|(entity, collider, collider_transform)| {
+            collide(shape, transform, &collider.shape, &collider_transform)
+                .map(|manifold| (entity, manifold))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scene() -> Vec<(Entity, Collider, Transform)> {
+        let mut sensor = Collider::sphere(1.0);
+        sensor.is_sensor = true;
+        vec![
+            (
+                Entity::new(0),
+                Collider::cuboid(Vec3::new(10.0, 1.0, 10.0)),
+                Transform::identity(),
+            ),
+            (
+                Entity::new(1),
+                Collider::sphere(0.5),
+                Transform::from_xyz(0.0, 3.0, 0.0),
+            ),
+            (Entity::new(2), sensor, Transform::from_xyz(0.0, 6.0, 0.0)),
+        ]
+    }
+
+    fn colliders(
+        scene: &[(Entity, Collider, Transform)],
+    ) -> impl Iterator<Item = (Entity, &Collider, Transform)> {
+        scene
+            .iter()
+            .map(|(entity, collider, transform)| (*entity, collider, *transform))
+    }
+
+    #[test]
+    fn casts_rays_at_the_closest_collider() {
+        let scene = scene();
+        let origin = Vec3::new(0.0, 10.0, 0.0);
+        let filter = SceneQueryFilter::default();
+
+        let hit = cast_ray(colliders(&scene), origin, -Vec3::Y, 100.0, &filter).unwrap();
+        assert_eq!(hit.entity, Entity::new(1));
+        assert!((hit.distance - 6.5).abs() < 1e-5);
+        assert!(hit.position.abs_diff_eq(Vec3::new(0.0, 3.5, 0.0), 1e-5));
+
+        let with_sensors = SceneQueryFilter {
+            include_sensors: true,
+            ..Default::default()
+        };
+        let hit = cast_ray(colliders(&scene), origin, -Vec3::Y, 100.0, &with_sensors).unwrap();
+        assert_eq!(hit.entity, Entity::new(2));
+
+        let excluding = SceneQueryFilter::excluding(Entity::new(1));
+        let hit = cast_ray(colliders(&scene), origin, -Vec3::Y, 100.0, &excluding).unwrap();
+        assert_eq!(hit.entity, Entity::new(0));
+        assert!(cast_ray(colliders(&scene), origin, Vec3::Y, 100.0, &filter).is_none());
+        assert!(cast_ray(colliders(&scene), origin, Vec3::ZERO, 100.0, &filter).is_none());
+    }
+
+    #[test]
+    fn casts_shapes_and_finds_overlaps() {
+        let scene = scene();
+        let filter = SceneQueryFilter::default();
+        let cuboid = ColliderShape::Cuboid {
+            half_extents: Vec3::splat(0.5),
+        };
+
+        let hit = cast_shape_against(
+            colliders(&scene),
+            &cuboid,
+            &Transform::from_xyz(3.0, 2.6, 0.0),
+            -Vec3::X,
+            10.0,
+            &filter,
+        )
+        .unwrap();
+        assert_eq!(hit.entity, Entity::new(1));
+        assert!((hit.distance - 2.0).abs() < 1e-3);
+        assert!(hit.manifold.normal.abs_diff_eq(-Vec3::X, 1e-5));
+
+        let tall_cuboid = ColliderShape::Cuboid {
+            half_extents: Vec3::new(0.5, 1.0, 0.5),
+        };
+        let overlapping = overlaps(
+            colliders(&scene),
+            &tall_cuboid,
+            &Transform::from_xyz(0.0, 1.8, 0.0),
+            &filter,
+        );
+        let mut entities = overlapping
+            .iter()
+            .map(|(entity, _)| *entity)
+            .collect::<Vec<_>>();
+        entities.sort();
+        assert_eq!(entities, vec![Entity::new(0), Entity::new(1)]);
+    }
+}
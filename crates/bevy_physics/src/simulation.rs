@@ -0,0 +1,452 @@
+use crate::{
+    collide, Collider, Collision, CollisionEnded, CollisionStarted, ContactManifold, Contacts,
+    ExternalForce, PhysicsSettings, RigidBody, Velocity, PHYSICS_TIMESTEP,
+};
+use bevy_core::{FixedTimesteps, FloatOrd};
+use bevy_ecs::{
+    entity::Entity,
+    event::EventWriter,
+    system::{Query, Res, ResMut},
+};
+use bevy_math::{Aabb, Mat3, Quat, Vec3};
+use bevy_transform::components::Transform;
+
+/// Contacts shallower than this are left alone by the position correction, which keeps resting
+/// contacts from jittering
+const PENETRATION_SLOP: f32 = 0.005;
+/// The fraction of the penetration removed at each step
+const POSITION_CORRECTION: f32 = 0.8;
+/// Colliders approaching each other slower than this don't bounce
+const RESTITUTION_THRESHOLD: f32 = 1.0;
+
+/// The state of a collider during a step of the simulation
+#[derive(Debug, Clone)]
+pub(crate) struct Body {
+    pub entity: Entity,
+    pub rigid_body: RigidBody,
+    pub collider: Collider,
+    pub transform: Transform,
+    pub velocity: Velocity,
+    pub force: ExternalForce,
+    inverse_mass: f32,
+    /// The inverse inertia tensor in world space
+    inverse_inertia: Mat3,
+}
+
+impl Body {
+    pub fn new(
+        entity: Entity,
+        rigid_body: RigidBody,
+        collider: &Collider,
+        transform: Transform,
+        velocity: Velocity,
+        force: ExternalForce,
+    ) -> Self {
+        let mass = collider.mass();
+        let (inverse_mass, inverse_inertia) = if rigid_body.is_dynamic() && mass > 0.0 {
+            let inertia = collider.shape.principal_inertia(mass);
+            let inverse = |moment: f32| if moment > 0.0 { 1.0 / moment } else { 0.0 };
+            let rotation = Mat3::from_quat(transform.rotation);
+            let local_inverse_inertia = Mat3::from_diagonal(Vec3::new(
+                inverse(inertia.x),
+                inverse(inertia.y),
+                inverse(inertia.z),
+            ));
+            (
+                1.0 / mass,
+                rotation * local_inverse_inertia * rotation.transpose(),
+            )
+        } else {
+            (0.0, Mat3::ZERO)
+        };
+        Body {
+            entity,
+            rigid_body,
+            collider: collider.clone(),
+            transform,
+            velocity,
+            force,
+            inverse_mass,
+            inverse_inertia,
+        }
+    }
+
+    fn aabb(&self) -> Aabb {
+        self.collider.shape.aabb(&self.transform)
+    }
+
+    fn velocity_at(&self, point: Vec3) -> Vec3 {
+        self.velocity.linear
+            + self
+                .velocity
+                .angular
+                .cross(point - self.transform.translation)
+    }
+
+    fn apply_impulse(&mut self, impulse: Vec3, point: Vec3) {
+        self.velocity.linear += impulse * self.inverse_mass;
+        self.velocity.angular +=
+            self.inverse_inertia * (point - self.transform.translation).cross(impulse);
+    }
+
+    /// The inverse of the mass felt when pushing at `point` along `direction`
+    fn inverse_effective_mass(&self, point: Vec3, direction: Vec3) -> f32 {
+        let arm = point - self.transform.translation;
+        self.inverse_mass + direction.dot((self.inverse_inertia * arm.cross(direction)).cross(arm))
+    }
+
+    fn is_solid(&self) -> bool {
+        !self.collider.is_sensor
+    }
+}
+
+/// Two overlapping colliders, given by their index in the bodies of a step
+pub(crate) struct Contact {
+    pub a: usize,
+    pub b: usize,
+    pub manifold: ContactManifold,
+}
+
+/// A point of a [Contact] pushing two bodies apart
+struct ContactConstraint {
+    a: usize,
+    b: usize,
+    point: Vec3,
+    normal: Vec3,
+    tangents: [Vec3; 2],
+    normal_mass: f32,
+    tangent_masses: [f32; 2],
+    friction: f32,
+    target_normal_velocity: f32,
+    normal_impulse: f32,
+    tangent_impulses: [f32; 2],
+}
+
+/// Advances `bodies` by `delta` seconds, and returns the contacts found along the way
+pub(crate) fn step(bodies: &mut [Body], settings: &PhysicsSettings, delta: f32) -> Vec<Contact> {
+    for body in bodies.iter_mut() {
+        if body.rigid_body.is_dynamic() {
+            body.velocity.linear +=
+                (settings.gravity + body.force.force * body.inverse_mass) * delta;
+            body.velocity.angular += body.inverse_inertia * body.force.torque * delta;
+        }
+    }
+
+    let contacts = find_contacts(bodies);
+    solve_velocities(bodies, &contacts, settings.solver_iterations);
+
+    for body in bodies.iter_mut() {
+        if matches!(body.rigid_body, RigidBody::Static) {
+            continue;
+        }
+        body.transform.translation += body.velocity.linear * delta;
+        let angular_speed = body.velocity.angular.length();
+        if angular_speed > 0.0 {
+            let rotation =
+                Quat::from_axis_angle(body.velocity.angular / angular_speed, angular_speed * delta);
+            body.transform.rotation = (rotation * body.transform.rotation).normalize();
+        }
+    }
+
+    correct_positions(bodies, &contacts);
+    contacts
+}
+
+/// Finds the overlapping colliders, by sweeping their bounding boxes along the x axis before
+/// testing their shapes
+fn find_contacts(bodies: &[Body]) -> Vec<Contact> {
+    let aabbs = bodies.iter().map(Body::aabb).collect::<Vec<_>>();
+    let mut order = (0..bodies.len()).collect::<Vec<_>>();
+    order.sort_by_key(|i| FloatOrd(aabbs[*i].min.x));
+
+    let mut contacts = Vec::new();
+    for (i, &first) in order.iter().enumerate() {
+        for &second in order[i + 1..].iter() {
+            if aabbs[second].min.x > aabbs[first].max.x {
+                break;
+            }
+            if !aabbs[first].intersects(&aabbs[second]) {
+                continue;
+            }
+            let (a, b) = (first.min(second), first.max(second));
+            if matches!(bodies[a].rigid_body, RigidBody::Static)
+                && matches!(bodies[b].rigid_body, RigidBody::Static)
+            {
+                continue;
+            }
+            if let Some(manifold) = collide(
+                &bodies[a].collider.shape,
+                &bodies[a].transform,
+                &bodies[b].collider.shape,
+                &bodies[b].transform,
+            ) {
+                contacts.push(Contact { a, b, manifold });
+            }
+        }
+    }
+    contacts.sort_by_key(|contact| (contact.a, contact.b));
+    contacts
+}
+
+fn pair_mut(bodies: &mut [Body], a: usize, b: usize) -> (&mut Body, &mut Body) {
+    debug_assert!(a < b);
+    let (first, second) = bodies.split_at_mut(b);
+    (&mut first[a], &mut second[0])
+}
+
+/// Two directions perpendicular to `normal` and to each other
+fn tangents(normal: Vec3) -> [Vec3; 2] {
+    let tangent = if normal.x.abs() > 0.57 {
+        Vec3::new(normal.y, -normal.x, 0.0)
+    } else {
+        Vec3::new(0.0, normal.z, -normal.y)
+    }
+    .normalize();
+    [tangent, normal.cross(tangent)]
+}
+
+/// Applies impulses at the contact points until the bodies stop moving into each other, with
+/// sequential impulses
+fn solve_velocities(bodies: &mut [Body], contacts: &[Contact], iterations: u32) {
+    let mut constraints = Vec::new();
+    for contact in contacts.iter() {
+        let (a, b) = (&bodies[contact.a], &bodies[contact.b]);
+        if !a.is_solid()
+            || !b.is_solid()
+            || (!a.rigid_body.is_dynamic() && !b.rigid_body.is_dynamic())
+        {
+            continue;
+        }
+        let normal = contact.manifold.normal;
+        let friction = (a.collider.friction * b.collider.friction).sqrt();
+        let restitution = a.collider.restitution.max(b.collider.restitution);
+        let tangents = tangents(normal);
+        for contact_point in contact.manifold.points.iter() {
+            let point = contact_point.point;
+            let effective_mass = |direction: Vec3| {
+                let inverse = a.inverse_effective_mass(point, direction)
+                    + b.inverse_effective_mass(point, direction);
+                if inverse > 0.0 {
+                    1.0 / inverse
+                } else {
+                    0.0
+                }
+            };
+            let normal_velocity = (b.velocity_at(point) - a.velocity_at(point)).dot(normal);
+            constraints.push(ContactConstraint {
+                a: contact.a,
+                b: contact.b,
+                point,
+                normal,
+                tangents,
+                normal_mass: effective_mass(normal),
+                tangent_masses: [effective_mass(tangents[0]), effective_mass(tangents[1])],
+                friction,
+                target_normal_velocity: if normal_velocity < -RESTITUTION_THRESHOLD {
+                    -restitution * normal_velocity
+                } else {
+                    0.0
+                },
+                normal_impulse: 0.0,
+                tangent_impulses: [0.0; 2],
+            });
+        }
+    }
+
+    for _ in 0..iterations {
+        for constraint in constraints.iter_mut() {
+            let (a, b) = pair_mut(bodies, constraint.a, constraint.b);
+            let point = constraint.point;
+
+            let relative_velocity = b.velocity_at(point) - a.velocity_at(point);
+            let impulse = constraint.normal_mass
+                * (constraint.target_normal_velocity - relative_velocity.dot(constraint.normal));
+            let normal_impulse = (constraint.normal_impulse + impulse).max(0.0);
+            let impulse = constraint.normal * (normal_impulse - constraint.normal_impulse);
+            constraint.normal_impulse = normal_impulse;
+            a.apply_impulse(-impulse, point);
+            b.apply_impulse(impulse, point);
+
+            let max_friction_impulse = constraint.friction * constraint.normal_impulse;
+            for i in 0..2 {
+                let tangent = constraint.tangents[i];
+                let relative_velocity = b.velocity_at(point) - a.velocity_at(point);
+                let impulse = -constraint.tangent_masses[i] * relative_velocity.dot(tangent);
+                let tangent_impulse = (constraint.tangent_impulses[i] + impulse)
+                    .max(-max_friction_impulse)
+                    .min(max_friction_impulse);
+                let impulse = tangent * (tangent_impulse - constraint.tangent_impulses[i]);
+                constraint.tangent_impulses[i] = tangent_impulse;
+                a.apply_impulse(-impulse, point);
+                b.apply_impulse(impulse, point);
+            }
+        }
+    }
+}
+
+/// Moves dynamic bodies out of the colliders they went into, in proportion to their inverse mass
+fn correct_positions(bodies: &mut [Body], contacts: &[Contact]) {
+    for contact in contacts.iter() {
+        let (a, b) = pair_mut(bodies, contact.a, contact.b);
+        let inverse_mass = a.inverse_mass + b.inverse_mass;
+        if !a.is_solid() || !b.is_solid() || inverse_mass <= 0.0 {
+            continue;
+        }
+        let depth = (contact.manifold.max_depth() - PENETRATION_SLOP).max(0.0);
+        let correction = contact.manifold.normal * (depth * POSITION_CORRECTION / inverse_mass);
+        a.transform.translation -= correction * a.inverse_mass;
+        b.transform.translation += correction * b.inverse_mass;
+    }
+}
+
+/// Steps the simulation, reading the transforms and velocities of the colliders and writing back
+/// the ones of the bodies that moved. Runs once per step of the physics timestep.
+#[allow(clippy::type_complexity)]
+pub fn physics_step_system(
+    fixed_timesteps: Res<FixedTimesteps>,
+    settings: Res<PhysicsSettings>,
+    mut contacts: ResMut<Contacts>,
+    mut collisions: EventWriter<Collision>,
+    mut collisions_started: EventWriter<CollisionStarted>,
+    mut collisions_ended: EventWriter<CollisionEnded>,
+    mut query: Query<(
+        Entity,
+        &Collider,
+        &mut Transform,
+        Option<&RigidBody>,
+        Option<&mut Velocity>,
+        Option<&ExternalForce>,
+    )>,
+) {
+    let delta = fixed_timesteps
+        .get(PHYSICS_TIMESTEP)
+        .map_or(1.0 / 60.0, |timestep| timestep.step() as f32);
+
+    let mut bodies = query
+        .iter_mut()
+        .map(
+            |(entity, collider, transform, rigid_body, velocity, force)| {
+                Body::new(
+                    entity,
+                    rigid_body.copied().unwrap_or(RigidBody::Static),
+                    collider,
+                    *transform,
+                    velocity.map_or(Velocity::default(), |velocity| *velocity),
+                    force.copied().unwrap_or_default(),
+                )
+            },
+        )
+        .collect::<Vec<_>>();
+
+    let step_contacts = step(&mut bodies, &settings, delta);
+
+    for body in bodies.iter() {
+        if matches!(body.rigid_body, RigidBody::Static) {
+            continue;
+        }
+        let (_, _, mut transform, _, velocity, _) = query.get_mut(body.entity).unwrap();
+        // only write on change, so change detection only picks up the bodies that moved
+        if *transform != body.transform {
+            *transform = body.transform;
+        }
+        if let Some(mut velocity) = velocity {
+            if *velocity != body.velocity {
+                *velocity = body.velocity;
+            }
+        }
+    }
+
+    let (started, ended) = contacts.update(step_contacts.iter().map(|contact| {
+        (
+            bodies[contact.a].entity,
+            bodies[contact.b].entity,
+            contact.manifold.clone(),
+        )
+    }));
+    collisions_started.send_batch(started.into_iter());
+    collisions_ended.send_batch(ended.into_iter());
+    collisions.send_batch(step_contacts.into_iter().map(|contact| Collision {
+        entity_a: bodies[contact.a].entity,
+        entity_b: bodies[contact.b].entity,
+        manifold: contact.manifold,
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn body(id: u32, rigid_body: RigidBody, collider: Collider, translation: Vec3) -> Body {
+        Body::new(
+            Entity::new(id),
+            rigid_body,
+            &collider,
+            Transform::from_translation(translation),
+            Velocity::default(),
+            ExternalForce::default(),
+        )
+    }
+
+    #[test]
+    fn bodies_come_to_rest_on_the_ground() {
+        let settings = PhysicsSettings::default();
+        let mut bodies = vec![
+            body(
+                0,
+                RigidBody::Static,
+                Collider::cuboid(Vec3::new(10.0, 1.0, 10.0)),
+                Vec3::ZERO,
+            ),
+            body(
+                1,
+                RigidBody::Dynamic,
+                Collider::sphere(0.5),
+                Vec3::new(-2.0, 3.0, 0.0),
+            ),
+            body(
+                2,
+                RigidBody::Dynamic,
+                Collider::cuboid(Vec3::splat(0.5)),
+                Vec3::new(2.0, 3.0, 0.0),
+            ),
+        ];
+
+        let mut contacts = Vec::new();
+        for _ in 0..300 {
+            contacts = step(&mut bodies, &settings, 1.0 / 60.0);
+        }
+
+        assert_eq!(bodies[0].transform.translation, Vec3::ZERO);
+        for body in bodies[1..].iter() {
+            assert!((body.transform.translation.y - 1.5).abs() < 0.02);
+            assert!(body.velocity.linear.length() < 0.05);
+        }
+        assert_eq!(
+            contacts
+                .iter()
+                .map(|contact| (contact.a, contact.b))
+                .collect::<Vec<_>>(),
+            vec![(0, 1), (0, 2)]
+        );
+    }
+
+    #[test]
+    fn sensors_report_contacts_without_pushing() {
+        let settings = PhysicsSettings::default();
+        let mut sensor = Collider::cuboid(Vec3::splat(1.0));
+        sensor.is_sensor = true;
+        let mut bodies = vec![
+            body(0, RigidBody::Static, sensor, Vec3::ZERO),
+            body(
+                1,
+                RigidBody::Dynamic,
+                Collider::sphere(0.5),
+                Vec3::new(0.0, 0.5, 0.0),
+            ),
+        ];
+
+        let contacts = step(&mut bodies, &settings, 1.0 / 60.0);
+        assert_eq!(contacts.len(), 1);
+        assert!(bodies[1].velocity.linear.y < 0.0);
+    }
+}
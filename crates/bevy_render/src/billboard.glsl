@@ -0,0 +1,25 @@
+// Replaces the rotation of a model matrix with one facing the camera. `view` holds the camera's
+// right, up and back axes. Defining BILLBOARD_LOCK_Y keeps the billboard upright.
+mat4 billboard(mat4 model, mat4 view) {
+#ifdef BILLBOARD_LOCK_Y
+    vec3 back = view[2].xyz;
+    // a camera looking straight up or down has no horizontal back axis, its up axis points the
+    // other way in that case
+    if (abs(back.x) + abs(back.z) > 1.1920929e-7) {
+        back = normalize(vec3(back.x, 0.0, back.z));
+    } else {
+        back = normalize(vec3(-view[1].x, 0.0, -view[1].z));
+    }
+    vec3 up = vec3(0.0, 1.0, 0.0);
+    vec3 right = cross(up, back);
+#else
+    vec3 right = normalize(view[0].xyz);
+    vec3 up = normalize(view[1].xyz);
+    vec3 back = normalize(view[2].xyz);
+#endif
+    return mat4(
+        vec4(right * length(model[0].xyz), 0.0),
+        vec4(up * length(model[1].xyz), 0.0),
+        vec4(back * length(model[2].xyz), 0.0),
+        model[3]);
+}
@@ -0,0 +1,129 @@
+use crate::{draw::OutsideFrustum, pipeline::RenderPipelines};
+use bevy_ecs::{query::Without, reflect::ReflectComponent, system::Query};
+use bevy_math::{Mat3, Quat, Vec3};
+use bevy_reflect::{Reflect, ReflectDeserialize};
+use bevy_transform::components::GlobalTransform;
+use serde::{Deserialize, Serialize};
+
+/// The shader def set on the pipelines of every [Billboard]
+pub const BILLBOARD_SHADER_DEF: &str = "BILLBOARD";
+/// The shader def set on the pipelines of [BillboardMode::LockY] billboards, in addition to
+/// [BILLBOARD_SHADER_DEF]
+pub const BILLBOARD_LOCK_Y_SHADER_DEF: &str = "BILLBOARD_LOCK_Y";
+/// The path and source of the `billboard` GLSL function, which vertex shaders drawing
+/// [Billboard]s include with `#include "bevy_render/billboard.glsl"`
+pub const BILLBOARD_GLSL: (&str, &str) =
+    ("bevy_render/billboard.glsl", include_str!("billboard.glsl"));
+
+/// How a [Billboard] turns to face the camera
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+#[reflect_value(PartialEq, Serialize, Deserialize)]
+pub enum BillboardMode {
+    /// The billboard is parallel to the image plane of the camera
+    Full,
+    /// The billboard stays upright and only turns around the world Y axis, which suits trees,
+    /// characters and health bars
+    LockY,
+}
+
+impl Default for BillboardMode {
+    fn default() -> Self {
+        BillboardMode::Full
+    }
+}
+
+/// Turns an entity towards the camera it is drawn by. Its local X and Y axes follow the right and
+/// up axes of the camera and its local Z axis points back towards the camera. The rotation of the
+/// entity's [GlobalTransform] is replaced, its translation and scale are kept.
+///
+/// Billboards are rotated in the vertex shader, which the `StandardMaterial`, sprite and sprite
+/// sheet pipelines support. Custom pipelines can support them by checking the
+/// [BILLBOARD_SHADER_DEF] and [BILLBOARD_LOCK_Y_SHADER_DEF] shader defs and reading the
+/// `CameraView` uniform, the camera to world matrix.
+#[derive(Debug, Clone, Default, Reflect)]
+#[reflect(Component)]
+pub struct Billboard {
+    pub mode: BillboardMode,
+}
+
+impl Billboard {
+    pub fn lock_y() -> Self {
+        Billboard {
+            mode: BillboardMode::LockY,
+        }
+    }
+
+    /// The world space rotation the billboard is drawn with by a camera at `camera_transform`
+    pub fn rotation(&self, camera_transform: &GlobalTransform) -> Quat {
+        match self.mode {
+            BillboardMode::Full => camera_transform.rotation,
+            BillboardMode::LockY => {
+                let back = camera_transform.back();
+                // a camera looking straight up or down has no horizontal back axis, its up axis
+                // points the other way in that case
+                let back = if back.x.abs() + back.z.abs() > f32::EPSILON {
+                    Vec3::new(back.x, 0.0, back.z)
+                } else {
+                    let up = camera_transform.up();
+                    Vec3::new(-up.x, 0.0, -up.z)
+                }
+                .normalize();
+                Quat::from_rotation_mat3(&Mat3::from_cols(Vec3::Y.cross(back), Vec3::Y, back))
+            }
+        }
+    }
+
+    fn shader_defs(&self) -> &'static [&'static str] {
+        match self.mode {
+            BillboardMode::Full => &[BILLBOARD_SHADER_DEF],
+            BillboardMode::LockY => &[BILLBOARD_SHADER_DEF, BILLBOARD_LOCK_Y_SHADER_DEF],
+        }
+    }
+}
+
+pub fn billboard_shader_defs_system(
+    mut query: Query<(&Billboard, &mut RenderPipelines), Without<OutsideFrustum>>,
+) {
+    for (billboard, mut render_pipelines) in query.iter_mut() {
+        for render_pipeline in render_pipelines.pipelines.iter_mut() {
+            for shader_def in billboard.shader_defs() {
+                render_pipeline
+                    .specialization
+                    .shader_specialization
+                    .shader_defs
+                    .insert(shader_def.to_string());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(a: Vec3, b: Vec3) {
+        assert!((a - b).abs().max_element() < 1e-5, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn faces_the_camera() {
+        let camera = GlobalTransform::from_xyz(4.0, 3.0, 4.0).looking_at(Vec3::ZERO, Vec3::Y);
+
+        let rotation = Billboard::default().rotation(&camera);
+        assert_near(rotation * Vec3::Z, camera.back());
+        assert_near(rotation * Vec3::Y, camera.up());
+
+        let rotation = Billboard::lock_y().rotation(&camera);
+        assert_near(rotation * Vec3::Y, Vec3::Y);
+        assert_near(rotation * Vec3::Z, Vec3::new(1.0, 0.0, 1.0).normalize());
+        assert_near(rotation * Vec3::X, Vec3::new(1.0, 0.0, -1.0).normalize());
+    }
+
+    #[test]
+    fn stays_upright_under_a_top_down_camera() {
+        let camera = GlobalTransform::from_xyz(0.0, 10.0, 0.0).looking_at(Vec3::ZERO, -Vec3::Z);
+        let rotation = Billboard::lock_y().rotation(&camera);
+        assert_near(rotation * Vec3::Y, Vec3::Y);
+        assert_near(rotation * Vec3::Z, Vec3::Z);
+    }
+}
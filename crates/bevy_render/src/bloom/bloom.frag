@@ -0,0 +1,63 @@
+#version 450
+
+layout(location = 0) in vec2 v_Uv;
+
+layout(location = 0) out vec4 o_Target;
+
+layout(set = 0, binding = 0) uniform texture2D BloomSource;
+layout(set = 0, binding = 1) uniform sampler BloomSource_sampler;
+
+layout(set = 0, binding = 2) uniform Bloom {
+    float Threshold;
+    float Knee;
+    float Intensity;
+};
+
+vec3 sample_source(vec2 uv) {
+    return texture(sampler2D(BloomSource, BloomSource_sampler), uv).rgb;
+}
+
+// each bilinear tap averages 2x2 texels, so this averages the surrounding 4x4 texels
+vec3 downsample(vec2 texel_size) {
+    vec4 offset = texel_size.xyxy * vec4(-1.0, -1.0, 1.0, 1.0);
+    return (sample_source(v_Uv + offset.xy) + sample_source(v_Uv + offset.zy) +
+            sample_source(v_Uv + offset.xw) + sample_source(v_Uv + offset.zw)) * 0.25;
+}
+
+// 3x3 tent filter, which blurs as it upsamples
+vec3 upsample(vec2 texel_size) {
+    vec4 offset = texel_size.xyxy * vec4(1.0, 1.0, -1.0, 0.0);
+    vec3 color = sample_source(v_Uv - offset.xy);
+    color += sample_source(v_Uv - offset.wy) * 2.0;
+    color += sample_source(v_Uv - offset.zy);
+    color += sample_source(v_Uv + offset.zw) * 2.0;
+    color += sample_source(v_Uv) * 4.0;
+    color += sample_source(v_Uv + offset.xw) * 2.0;
+    color += sample_source(v_Uv + offset.zy);
+    color += sample_source(v_Uv + offset.wy) * 2.0;
+    color += sample_source(v_Uv + offset.xy);
+    return color / 16.0;
+}
+
+// keeps the parts of the color brighter than Threshold, with a quadratic falloff over Knee
+vec3 threshold(vec3 color) {
+    float brightness = max(color.r, max(color.g, color.b));
+    float soft = clamp(brightness - Threshold + Knee, 0.0, 2.0 * Knee);
+    soft = soft * soft / (4.0 * Knee + 0.00001);
+    float contribution = max(soft, brightness - Threshold) / max(brightness, 0.00001);
+    return color * contribution;
+}
+
+void main() {
+    vec2 texel_size = 1.0 / vec2(textureSize(sampler2D(BloomSource, BloomSource_sampler), 0));
+#if defined(BLOOM_THRESHOLD)
+    o_Target = vec4(threshold(downsample(texel_size)), 1.0);
+#elif defined(BLOOM_DOWNSAMPLE)
+    o_Target = vec4(downsample(texel_size), 1.0);
+#elif defined(BLOOM_UPSAMPLE)
+    o_Target = vec4(upsample(texel_size), 1.0);
+#else
+    // composite
+    o_Target = vec4(upsample(texel_size) * Intensity, 1.0);
+#endif
+}
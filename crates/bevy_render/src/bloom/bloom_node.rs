@@ -0,0 +1,123 @@
+use super::{Bloom, BLOOM_ADDITIVE_PIPELINE_HANDLE, BLOOM_PIPELINE_HANDLE};
+use crate::{
+    camera::ActiveCameras,
+    pass::{FullscreenPass, LoadOp, Operations},
+    render_graph::{
+        base::{main_pass_camera, Hdr},
+        Node, ResourceSlotInfo, ResourceSlots,
+    },
+    renderer::{RenderContext, RenderResourceType},
+    Color,
+};
+use bevy_core::AsBytes;
+use bevy_ecs::world::World;
+use std::borrow::Cow;
+
+/// The passes that make up the bloom effect
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BloomPass {
+    /// Downsamples the parts of the source that are brighter than [Bloom::threshold]
+    Threshold,
+    /// Downsamples the source into a target half its size
+    Downsample,
+    /// Blurs the source into a target twice its size, adding to the target
+    Upsample,
+    /// Like [Upsample](Self::Upsample), but scales the blurred source by [Bloom::intensity]
+    Composite,
+}
+
+impl BloomPass {
+    fn shader_def(&self) -> Option<&'static str> {
+        match self {
+            BloomPass::Threshold => Some("BLOOM_THRESHOLD"),
+            BloomPass::Downsample => Some("BLOOM_DOWNSAMPLE"),
+            BloomPass::Upsample => Some("BLOOM_UPSAMPLE"),
+            BloomPass::Composite => None,
+        }
+    }
+
+    fn is_additive(&self) -> bool {
+        matches!(self, BloomPass::Upsample | BloomPass::Composite)
+    }
+}
+
+/// Runs one [BloomPass] from its source texture into its target texture, if the main pass camera
+/// has a [Bloom] component
+pub struct BloomNode {
+    pass: FullscreenPass,
+    bloom: Option<Bloom>,
+}
+
+impl BloomNode {
+    pub const IN_SOURCE: &'static str = "source";
+    pub const IN_TARGET: &'static str = "target";
+
+    pub fn new(bloom_pass: BloomPass) -> Self {
+        let (pipeline, load) = if bloom_pass.is_additive() {
+            (BLOOM_ADDITIVE_PIPELINE_HANDLE, LoadOp::Load)
+        } else {
+            (BLOOM_PIPELINE_HANDLE, LoadOp::Clear(Color::BLACK))
+        };
+        let mut pass = FullscreenPass::new(pipeline.typed(), Operations { load, store: true });
+        pass.specialization.color_target_format = Some(Hdr::TEXTURE_FORMAT);
+        if let Some(shader_def) = bloom_pass.shader_def() {
+            pass.specialization
+                .shader_specialization
+                .shader_defs
+                .insert(shader_def.to_string());
+        }
+        BloomNode { pass, bloom: None }
+    }
+}
+
+impl Node for BloomNode {
+    fn input(&self) -> &[ResourceSlotInfo] {
+        static INPUT: &[ResourceSlotInfo] = &[
+            ResourceSlotInfo {
+                name: Cow::Borrowed(BloomNode::IN_SOURCE),
+                resource_type: RenderResourceType::Texture,
+            },
+            ResourceSlotInfo {
+                name: Cow::Borrowed(BloomNode::IN_TARGET),
+                resource_type: RenderResourceType::Texture,
+            },
+        ];
+        INPUT
+    }
+
+    fn prepare(&mut self, world: &mut World) {
+        let active_cameras = world.get_resource::<ActiveCameras>().unwrap();
+        self.bloom = main_pass_camera(&active_cameras)
+            .and_then(|entity| world.get::<Bloom>(entity))
+            .cloned();
+        if self.bloom.is_some() {
+            self.pass.prepare(world);
+        }
+    }
+
+    fn update(
+        &mut self,
+        world: &World,
+        render_context: &mut dyn RenderContext,
+        input: &ResourceSlots,
+        _output: &mut ResourceSlots,
+    ) {
+        const IN_SOURCE: usize = 0;
+        const IN_TARGET: usize = 1;
+        let bloom = match self.bloom {
+            Some(ref bloom) => bloom,
+            None => return,
+        };
+        let source = input.get(IN_SOURCE).unwrap().get_texture().unwrap();
+        let target = input.get(IN_TARGET).unwrap().get_texture().unwrap();
+
+        self.pass
+            .set_texture(render_context.resources(), "BloomSource", source);
+        self.pass.set_uniform(
+            render_context,
+            "Bloom",
+            [bloom.threshold, bloom.knee, bloom.intensity].as_bytes(),
+        );
+        self.pass.draw(world, render_context, target);
+    }
+}
@@ -0,0 +1,190 @@
+use crate::{
+    pass::FullscreenPass,
+    pipeline::{BlendMode, PipelineDescriptor},
+    render_graph::{
+        base::{self, Hdr},
+        NodeLabel, RenderGraph, TransientTextureNode, WindowTextureNode,
+    },
+    shader::{Shader, ShaderStage},
+    texture::{Extent3d, TextureDescriptor, TextureDimension, TextureUsage},
+};
+use bevy_app::prelude::*;
+use bevy_asset::{Assets, Handle, HandleUntyped};
+use bevy_ecs::reflect::ReflectComponent;
+use bevy_reflect::{Reflect, TypeUuid};
+use bevy_utils::tracing::warn;
+use bevy_window::WindowId;
+
+mod bloom_node;
+
+pub use bloom_node::*;
+
+pub const BLOOM_PIPELINE_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(PipelineDescriptor::TYPE_UUID, 0x4a81e6f2c09d3b57);
+pub const BLOOM_ADDITIVE_PIPELINE_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(PipelineDescriptor::TYPE_UUID, 0x91c5d7308e2fa46b);
+
+/// The number of textures in the bloom chain, each half the size of the previous one. The first
+/// is half the size of the window.
+pub const BLOOM_LEVELS: usize = 6;
+
+pub mod node {
+    pub const BLOOM_THRESHOLD: &str = "bloom_threshold";
+    pub const BLOOM_COMPOSITE: &str = "bloom_composite";
+
+    pub fn bloom_texture(level: usize) -> String {
+        format!("bloom_texture_{}", level)
+    }
+
+    pub fn bloom_downsample(level: usize) -> String {
+        format!("bloom_downsample_{}", level)
+    }
+
+    pub fn bloom_upsample(level: usize) -> String {
+        format!("bloom_upsample_{}", level)
+    }
+}
+
+/// Makes bright parts of the main pass bleed into their surroundings, for cameras with a [Bloom]
+/// component.
+///
+/// The bright parts of the HDR main pass texture are downsampled into a chain of
+/// [BLOOM_LEVELS] transient textures, which are then blurred back up the chain and added to the
+/// main pass texture before it is tonemapped. Requires [Hdr] to be enabled, and must be added
+/// after [RenderPlugin](crate::RenderPlugin).
+#[derive(Debug, Default)]
+pub struct BloomPlugin;
+
+impl Plugin for BloomPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.register_type::<Bloom>();
+        let world = app.world_mut().cell();
+        if !world.get_resource::<Hdr>().unwrap().enabled {
+            warn!("BloomPlugin requires Hdr to be enabled before RenderPlugin is added");
+            return;
+        }
+
+        let mut shaders = world.get_resource_mut::<Assets<Shader>>().unwrap();
+        let mut pipelines = world
+            .get_resource_mut::<Assets<PipelineDescriptor>>()
+            .unwrap();
+        let fragment = shaders.add(Shader::from_glsl(
+            ShaderStage::Fragment,
+            include_str!("bloom.frag"),
+        ));
+        pipelines.set_untracked(
+            BLOOM_PIPELINE_HANDLE,
+            build_bloom_pipeline(fragment.clone_weak(), false),
+        );
+        pipelines.set_untracked(
+            BLOOM_ADDITIVE_PIPELINE_HANDLE,
+            build_bloom_pipeline(fragment, true),
+        );
+
+        let mut graph = world.get_resource_mut::<RenderGraph>().unwrap();
+        let hdr_texture = (
+            NodeLabel::from(base::node::MAIN_HDR_TEXTURE),
+            WindowTextureNode::OUT_TEXTURE,
+        );
+        let bloom_textures = (0..BLOOM_LEVELS)
+            .map(|level| {
+                let texture_node = TransientTextureNode::window_fraction(
+                    WindowId::primary(),
+                    2 << level,
+                    TextureDescriptor {
+                        size: Extent3d::new(1, 1, 1),
+                        mip_level_count: 1,
+                        sample_count: 1,
+                        dimension: TextureDimension::D2,
+                        format: Hdr::TEXTURE_FORMAT,
+                        usage: TextureUsage::OUTPUT_ATTACHMENT | TextureUsage::SAMPLED,
+                        view_dimension: None,
+                    },
+                );
+                let id = graph.add_node(node::bloom_texture(level), texture_node);
+                (NodeLabel::from(id), TransientTextureNode::OUT_TEXTURE)
+            })
+            .collect::<Vec<_>>();
+
+        // (name, pass, source, target) of every pass, in execution order
+        let mut passes = vec![(
+            node::BLOOM_THRESHOLD.to_string(),
+            BloomPass::Threshold,
+            hdr_texture.clone(),
+            bloom_textures[0].clone(),
+        )];
+        for level in 1..BLOOM_LEVELS {
+            passes.push((
+                node::bloom_downsample(level),
+                BloomPass::Downsample,
+                bloom_textures[level - 1].clone(),
+                bloom_textures[level].clone(),
+            ));
+        }
+        for level in (0..BLOOM_LEVELS - 1).rev() {
+            passes.push((
+                node::bloom_upsample(level),
+                BloomPass::Upsample,
+                bloom_textures[level + 1].clone(),
+                bloom_textures[level].clone(),
+            ));
+        }
+        passes.push((
+            node::BLOOM_COMPOSITE.to_string(),
+            BloomPass::Composite,
+            bloom_textures[0].clone(),
+            hdr_texture,
+        ));
+
+        let mut previous_pass = NodeLabel::from(base::node::MAIN_PASS);
+        for (name, pass, (source, source_slot), (target, target_slot)) in passes {
+            let id = graph.add_node(name, BloomNode::new(pass));
+            graph
+                .add_slot_edge(source, source_slot, id, BloomNode::IN_SOURCE)
+                .unwrap();
+            graph
+                .add_slot_edge(target, target_slot, id, BloomNode::IN_TARGET)
+                .unwrap();
+            graph.add_node_edge(previous_pass, id).unwrap();
+            previous_pass = NodeLabel::from(id);
+        }
+        graph
+            .add_node_edge(previous_pass, base::node::TONEMAPPING)
+            .unwrap();
+    }
+}
+
+/// Configures the bloom of the [main_pass_camera](crate::render_graph::base::main_pass_camera)
+/// when [BloomPlugin] is added. Cameras without this component have no bloom.
+#[derive(Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct Bloom {
+    /// How much of the blurred highlights is added to the image
+    pub intensity: f32,
+    /// Colors whose brightest channel is below this don't bloom
+    pub threshold: f32,
+    /// Softens the cutoff at `threshold`: colors from `threshold - knee` to `threshold + knee`
+    /// partially bloom
+    pub knee: f32,
+}
+
+impl Default for Bloom {
+    fn default() -> Self {
+        Bloom {
+            intensity: 0.3,
+            threshold: 1.0,
+            knee: 0.5,
+        }
+    }
+}
+
+fn build_bloom_pipeline(fragment: Handle<Shader>, additive: bool) -> PipelineDescriptor {
+    let mut descriptor = PipelineDescriptor {
+        name: Some("bloom".into()),
+        ..FullscreenPass::pipeline_descriptor(fragment)
+    };
+    if additive {
+        descriptor.set_blend_mode(BlendMode::Additive);
+    }
+    descriptor
+}
@@ -1,4 +1,5 @@
 use super::CameraProjection;
+use crate::render_graph::base::Depth;
 use bevy_ecs::{
     component::Component,
     entity::Entity,
@@ -40,6 +41,12 @@ impl Default for DepthCalculation {
 }
 
 impl Camera {
+    /// The matrix transforming world space positions to clip space for a camera at
+    /// `camera_transform`
+    pub fn view_projection(&self, camera_transform: &GlobalTransform) -> Mat4 {
+        self.projection_matrix * camera_transform.compute_matrix().inverse()
+    }
+
     /// Given a position in world space, use the camera to compute the screen space coordinates.
     pub fn world_to_screen(
         &self,
@@ -50,8 +57,7 @@ impl Camera {
         let window = windows.get(self.window)?;
         let window_size = Vec2::new(window.width(), window.height());
         // Build a transform to convert from world to NDC using camera data
-        let world_to_ndc: Mat4 =
-            self.projection_matrix * camera_transform.compute_matrix().inverse();
+        let world_to_ndc = self.view_projection(camera_transform);
         let ndc_space_coords: Vec3 = world_to_ndc.project_point3(world_position);
         // NDC z-values outside of 0 < z < 1 are behind the camera and are thus not in screen space
         if ndc_space_coords.z < 0.0 || ndc_space_coords.z > 1.0 {
@@ -67,6 +73,7 @@ pub fn camera_system<T: CameraProjection + Component>(
     mut window_resized_events: EventReader<WindowResized>,
     mut window_created_events: EventReader<WindowCreated>,
     windows: Res<Windows>,
+    depth: Res<Depth>,
     mut queries: QuerySet<(
         Query<(Entity, &mut Camera, &mut T)>,
         Query<Entity, Added<Camera>>,
@@ -99,9 +106,13 @@ pub fn camera_system<T: CameraProjection + Component>(
     }
     for (entity, mut camera, mut camera_projection) in queries.q0_mut().iter_mut() {
         if let Some(window) = windows.get(camera.window) {
-            if changed_window_ids.contains(&window.id()) || added_cameras.contains(&entity) {
+            if changed_window_ids.contains(&window.id())
+                || added_cameras.contains(&entity)
+                || camera_projection.is_changed()
+            {
                 camera_projection.update(window.width(), window.height());
-                camera.projection_matrix = camera_projection.get_projection_matrix();
+                camera.projection_matrix =
+                    depth.projection(camera_projection.get_projection_matrix());
                 camera.depth_calculation = camera_projection.depth_calculation();
             }
         }
@@ -0,0 +1,236 @@
+use bevy_app::prelude::*;
+use bevy_core::Time;
+use bevy_ecs::{
+    event::EventReader,
+    reflect::ReflectComponent,
+    system::{IntoSystem, Query, Res, ResMut},
+};
+use bevy_input::{
+    keyboard::KeyCode,
+    mouse::{MouseButton, MouseMotion, MouseScrollUnit, MouseWheel},
+    Input,
+};
+use bevy_math::{Quat, Vec2, Vec3};
+use bevy_reflect::Reflect;
+use bevy_transform::components::Transform;
+use bevy_window::Windows;
+use std::f32::consts::FRAC_PI_2;
+
+/// Keeps the camera from flipping over when looking straight up or down
+const MAX_PITCH: f32 = FRAC_PI_2 - 0.01;
+
+/// Adds the [FlyCamera] and [OrbitCamera] controllers. Add either component to an entity with a
+/// [Transform], such as a camera, to control it.
+#[derive(Default)]
+pub struct CameraControllerPlugin;
+
+impl Plugin for CameraControllerPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.register_type::<FlyCamera>()
+            .register_type::<OrbitCamera>()
+            .add_system(fly_camera_system.system())
+            .add_system(orbit_camera_system.system());
+    }
+}
+
+/// A free flying first person controller.
+///
+/// Clicking the primary window grabs the cursor and enables mouse look, `Escape` releases it.
+/// `W`/`A`/`S`/`D` move, `Space` and `LShift` move up and down, and holding `LControl` moves
+/// faster.
+#[derive(Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct FlyCamera {
+    pub enabled: bool,
+    /// Movement speed in units per second
+    pub speed: f32,
+    /// Multiplies `speed` while sprinting
+    pub sprint_multiplier: f32,
+    /// Radians of rotation per pixel of mouse motion
+    pub sensitivity: f32,
+    /// Rotation around the y axis. Derived from the [Transform] when the component is added.
+    pub yaw: f32,
+    /// Rotation around the local x axis. Derived from the [Transform] when the component is added.
+    pub pitch: f32,
+}
+
+impl Default for FlyCamera {
+    fn default() -> Self {
+        FlyCamera {
+            enabled: true,
+            speed: 5.0,
+            sprint_multiplier: 3.0,
+            sensitivity: 0.002,
+            yaw: 0.0,
+            pitch: 0.0,
+        }
+    }
+}
+
+/// Rotates around and zooms towards a focus point.
+///
+/// Dragging with the left mouse button rotates around `focus`, scrolling changes `radius`.
+#[derive(Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct OrbitCamera {
+    pub enabled: bool,
+    pub focus: Vec3,
+    /// Distance from `focus`. Derived from the [Transform] when the component is added.
+    pub radius: f32,
+    pub min_radius: f32,
+    pub max_radius: f32,
+    /// Radians of rotation per pixel of mouse motion
+    pub sensitivity: f32,
+    /// How much one line of scrolling zooms in, as a fraction of `radius`
+    pub zoom_sensitivity: f32,
+    /// Rotation around the y axis. Derived from the [Transform] when the component is added.
+    pub yaw: f32,
+    /// Rotation around the local x axis. Derived from the [Transform] when the component is added.
+    pub pitch: f32,
+}
+
+impl Default for OrbitCamera {
+    fn default() -> Self {
+        OrbitCamera {
+            enabled: true,
+            focus: Vec3::ZERO,
+            radius: 5.0,
+            min_radius: 0.1,
+            max_radius: 1000.0,
+            sensitivity: 0.005,
+            zoom_sensitivity: 0.1,
+            yaw: 0.0,
+            pitch: 0.0,
+        }
+    }
+}
+
+fn yaw_pitch_rotation(yaw: f32, pitch: f32) -> Quat {
+    Quat::from_rotation_y(yaw) * Quat::from_rotation_x(pitch)
+}
+
+pub fn fly_camera_system(
+    time: Res<Time>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_button_input: Res<Input<MouseButton>>,
+    mut mouse_motion_events: EventReader<MouseMotion>,
+    mut windows: ResMut<Windows>,
+    mut query: Query<(&mut FlyCamera, &mut Transform)>,
+) {
+    let mouse_delta = mouse_motion_events
+        .iter()
+        .fold(Vec2::ZERO, |delta, event| delta + event.delta);
+    let cursor_locked = match windows.get_primary_mut() {
+        Some(window) => {
+            if mouse_button_input.just_pressed(MouseButton::Left) {
+                window.set_cursor_lock_mode(true);
+                window.set_cursor_visibility(false);
+            } else if keyboard_input.just_pressed(KeyCode::Escape) {
+                window.set_cursor_lock_mode(false);
+                window.set_cursor_visibility(true);
+            }
+            window.cursor_locked()
+        }
+        None => false,
+    };
+
+    let mut direction = Vec3::ZERO;
+    for (key, key_direction) in [
+        (KeyCode::W, -Vec3::Z),
+        (KeyCode::S, Vec3::Z),
+        (KeyCode::A, -Vec3::X),
+        (KeyCode::D, Vec3::X),
+    ]
+    .iter()
+    {
+        if keyboard_input.pressed(*key) {
+            direction += *key_direction;
+        }
+    }
+    let mut vertical = 0.0;
+    if keyboard_input.pressed(KeyCode::Space) {
+        vertical += 1.0;
+    }
+    if keyboard_input.pressed(KeyCode::LShift) {
+        vertical -= 1.0;
+    }
+
+    for (mut fly_camera, mut transform) in query.iter_mut() {
+        if fly_camera.is_added() {
+            let forward = transform.rotation * -Vec3::Z;
+            fly_camera.pitch = forward.y.max(-1.0).min(1.0).asin();
+            fly_camera.yaw = (-forward.x).atan2(-forward.z);
+        }
+        if !fly_camera.enabled {
+            continue;
+        }
+
+        if cursor_locked {
+            fly_camera.yaw -= mouse_delta.x * fly_camera.sensitivity;
+            fly_camera.pitch = (fly_camera.pitch - mouse_delta.y * fly_camera.sensitivity)
+                .max(-MAX_PITCH)
+                .min(MAX_PITCH);
+        }
+        transform.rotation = yaw_pitch_rotation(fly_camera.yaw, fly_camera.pitch);
+
+        let mut speed = fly_camera.speed * time.delta_seconds();
+        if keyboard_input.pressed(KeyCode::LControl) {
+            speed *= fly_camera.sprint_multiplier;
+        }
+        let mut movement = Vec3::Y * vertical;
+        if direction != Vec3::ZERO {
+            movement += transform.rotation * direction.normalize();
+        }
+        transform.translation += movement * speed;
+    }
+}
+
+pub fn orbit_camera_system(
+    mouse_button_input: Res<Input<MouseButton>>,
+    mut mouse_motion_events: EventReader<MouseMotion>,
+    mut mouse_wheel_events: EventReader<MouseWheel>,
+    mut query: Query<(&mut OrbitCamera, &mut Transform)>,
+) {
+    let mouse_delta = mouse_motion_events
+        .iter()
+        .fold(Vec2::ZERO, |delta, event| delta + event.delta);
+    let scroll = mouse_wheel_events
+        .iter()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y,
+            // roughly one line per 16 pixels
+            MouseScrollUnit::Pixel => event.y / 16.0,
+        })
+        .sum::<f32>();
+    let dragging = mouse_button_input.pressed(MouseButton::Left);
+
+    for (mut orbit_camera, mut transform) in query.iter_mut() {
+        if orbit_camera.is_added() {
+            let offset = transform.translation - orbit_camera.focus;
+            let radius = offset.length();
+            if radius > 0.0 {
+                let direction = offset / radius;
+                orbit_camera.radius = radius;
+                orbit_camera.pitch = (-direction.y).max(-1.0).min(1.0).asin();
+                orbit_camera.yaw = direction.x.atan2(direction.z);
+            }
+        }
+        if !orbit_camera.enabled {
+            continue;
+        }
+
+        if dragging {
+            orbit_camera.yaw -= mouse_delta.x * orbit_camera.sensitivity;
+            orbit_camera.pitch = (orbit_camera.pitch - mouse_delta.y * orbit_camera.sensitivity)
+                .max(-MAX_PITCH)
+                .min(MAX_PITCH);
+        }
+        orbit_camera.radius = (orbit_camera.radius
+            * (1.0 - scroll * orbit_camera.zoom_sensitivity).max(0.0))
+        .max(orbit_camera.min_radius)
+        .min(orbit_camera.max_radius);
+
+        transform.rotation = yaw_pitch_rotation(orbit_camera.yaw, orbit_camera.pitch);
+        transform.translation = orbit_camera.focus + transform.back() * orbit_camera.radius;
+    }
+}
@@ -64,6 +64,10 @@ pub enum ScalingMode {
     FixedVertical,
     /// Keep horizontal axis constant; resize vertical with aspect ratio.
     FixedHorizontal,
+    /// Show at least `width` x `height` world units, with 1 world unit = an integer number of
+    /// pixels. The largest integer scale that fits the window is used, and any extra space shows
+    /// more of the world, so pixel art stays crisp at every resolution.
+    PixelPerfect { width: u32, height: u32 },
 }
 
 #[derive(Debug, Clone, Reflect)]
@@ -137,6 +141,24 @@ impl CameraProjection for OrthographicProjection {
                 self.top = aspect_ratio;
                 self.bottom = 0.0;
             }
+            (
+                ScalingMode::PixelPerfect {
+                    width: min_width,
+                    height: min_height,
+                },
+                origin,
+            ) => {
+                let pixel_scale = pixel_perfect_scale(width, height, *min_width, *min_height);
+                let (left, bottom) = match origin {
+                    // offset by whole pixels so texels line up with pixels
+                    WindowOrigin::Center => ((width / 2.0).floor(), (height / 2.0).floor()),
+                    WindowOrigin::BottomLeft => (0.0, 0.0),
+                };
+                self.left = -left / pixel_scale;
+                self.right = (width - left) / pixel_scale;
+                self.top = (height - bottom) / pixel_scale;
+                self.bottom = -bottom / pixel_scale;
+            }
             (ScalingMode::None, _) => {}
         }
     }
@@ -146,6 +168,14 @@ impl CameraProjection for OrthographicProjection {
     }
 }
 
+/// The largest integer number of pixels per world unit that fits `min_width` x `min_height` world
+/// units in the window, and at least 1
+fn pixel_perfect_scale(width: f32, height: f32, min_width: u32, min_height: u32) -> f32 {
+    let horizontal = (width / min_width.max(1) as f32).floor();
+    let vertical = (height / min_height.max(1) as f32).floor();
+    horizontal.min(vertical).max(1.0)
+}
+
 impl Default for OrthographicProjection {
     fn default() -> Self {
         OrthographicProjection {
@@ -162,3 +192,34 @@ impl Default for OrthographicProjection {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pixel_perfect_scaling() {
+        let mut projection = OrthographicProjection {
+            scaling_mode: ScalingMode::PixelPerfect {
+                width: 320,
+                height: 180,
+            },
+            ..Default::default()
+        };
+
+        // 3x fits vertically, 4x horizontally
+        projection.update(1281.0, 600.0);
+        assert_eq!(projection.left, -640.0 / 3.0);
+        assert_eq!(projection.right, 641.0 / 3.0);
+        assert_eq!(projection.bottom, -100.0);
+        assert_eq!(projection.top, 100.0);
+
+        // windows smaller than the minimum size still get 1 pixel per world unit
+        projection.window_origin = WindowOrigin::BottomLeft;
+        projection.update(200.0, 100.0);
+        assert_eq!(projection.left, 0.0);
+        assert_eq!(projection.right, 200.0);
+        assert_eq!(projection.bottom, 0.0);
+        assert_eq!(projection.top, 100.0);
+    }
+}
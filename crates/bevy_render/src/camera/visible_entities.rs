@@ -1,5 +1,5 @@
 use super::{Camera, DepthCalculation};
-use crate::{draw::OutsideFrustum, prelude::Visible};
+use crate::{draw::OutsideFrustum, material::MaterialBatch, occlusion::Occluded, prelude::Visible};
 use bevy_core::FloatOrd;
 use bevy_ecs::{entity::Entity, query::Without, reflect::ReflectComponent, system::Query};
 use bevy_reflect::Reflect;
@@ -24,6 +24,15 @@ impl VisibleEntities {
     }
 }
 
+/// Overrides the sort key an entity is drawn with, which is otherwise derived from its distance to
+/// the camera.
+///
+/// Opaque entities are drawn in ascending order and transparent entities in descending order, so
+/// lower values behave like entities that are closer to the camera.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct DrawOrder(pub f32);
+
 type LayerMask = u32;
 
 /// An identifier for a rendering layer.
@@ -197,6 +206,15 @@ mod rendering_mask_tests {
     }
 }
 
+/// Rounds `depth` towards zero to a power of two, so depths whose magnitudes are within a factor of
+/// two of each other share a bucket. Buckets keep the order of their depths.
+fn depth_bucket(depth: f32) -> FloatOrd {
+    if depth == 0.0 || !depth.is_finite() {
+        return FloatOrd(depth);
+    }
+    FloatOrd(depth.signum() * depth.abs().log2().floor().exp2())
+}
+
 pub fn visible_entities_system(
     mut camera_query: Query<(
         &Camera,
@@ -204,8 +222,13 @@ pub fn visible_entities_system(
         &mut VisibleEntities,
         Option<&RenderLayers>,
     )>,
-    visible_query: Query<(Entity, &Visible, Option<&RenderLayers>), Without<OutsideFrustum>>,
+    visible_query: Query<
+        (Entity, &Visible, Option<&RenderLayers>),
+        (Without<OutsideFrustum>, Without<Occluded>),
+    >,
     visible_transform_query: Query<&GlobalTransform, Without<OutsideFrustum>>,
+    batch_query: Query<&MaterialBatch>,
+    draw_order_query: Query<&DrawOrder>,
 ) {
     for (camera, camera_global_transform, mut visible_entities, maybe_camera_mask) in
         camera_query.iter_mut()
@@ -215,6 +238,7 @@ pub fn visible_entities_system(
         let camera_mask = maybe_camera_mask.copied().unwrap_or_default();
 
         let mut no_transform_order = 0.0;
+        let mut opaque_entities = Vec::new();
         let mut transparent_entities = Vec::new();
         for (entity, visible, maybe_entity_mask) in visible_query.iter() {
             if !visible.is_visible {
@@ -226,7 +250,11 @@ pub fn visible_entities_system(
                 continue;
             }
 
-            let order = if let Ok(global_transform) = visible_transform_query.get(entity) {
+            let mut explicit_order = false;
+            let order = if let Ok(draw_order) = draw_order_query.get(entity) {
+                explicit_order = true;
+                FloatOrd(draw_order.0)
+            } else if let Ok(global_transform) = visible_transform_query.get(entity) {
                 let position = global_transform.translation;
                 // smaller distances are sorted to lower indices by using the distance from the
                 // camera
@@ -243,12 +271,22 @@ pub fn visible_entities_system(
             if visible.is_transparent {
                 transparent_entities.push(VisibleEntity { entity, order })
             } else {
-                visible_entities.value.push(VisibleEntity { entity, order })
+                let batch = batch_query.get(entity).copied().unwrap_or_default();
+                // entities with a draw order are never batched out of it
+                let bucket = if explicit_order {
+                    order
+                } else {
+                    depth_bucket(order.0)
+                };
+                opaque_entities.push((bucket, batch, VisibleEntity { entity, order }))
             }
         }
 
-        // sort opaque entities front-to-back
-        visible_entities.value.sort_by_key(|e| e.order);
+        // sort opaque entities front-to-back, grouping those at similar depths by material
+        opaque_entities.sort_by_key(|(bucket, batch, e)| (*bucket, *batch, e.order));
+        visible_entities
+            .value
+            .extend(opaque_entities.into_iter().map(|(_, _, e)| e));
 
         // sort transparent entities front-to-back
         transparent_entities.sort_by_key(|e| -e.order);
@@ -258,3 +296,21 @@ pub fn visible_entities_system(
         // to prevent holding unneeded memory
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buckets_depths_in_order() {
+        let depths = [-9.0, -3.0, -2.5, -1.0, 0.0, 0.75, 1.0, 1.5, 3.0, 4.0, 100.0];
+        let buckets = depths
+            .iter()
+            .map(|depth| depth_bucket(*depth).0)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            buckets,
+            vec![-8.0, -2.0, -2.0, -1.0, 0.0, 0.5, 1.0, 1.0, 2.0, 4.0, 64.0]
+        );
+    }
+}
@@ -319,7 +319,7 @@ impl Color {
                 green,
                 blue,
                 alpha,
-            } => Color::Rgba {
+            } => Color::RgbaLinear {
                 red: red.nonlinear_to_linear_srgb(),
                 green: green.nonlinear_to_linear_srgb(),
                 blue: blue.nonlinear_to_linear_srgb(),
@@ -489,6 +489,66 @@ impl Color {
             } => [hue, saturation, lightness, alpha],
         }
     }
+
+    /// Returns this color with the given alpha
+    pub fn with_alpha(mut self, alpha: f32) -> Color {
+        self.set_a(alpha);
+        self
+    }
+
+    /// Interpolates towards `other` in linear RGB, which blends like light does instead of
+    /// producing the darker midpoints of blending sRGB values. The result keeps the
+    /// representation of `self`.
+    pub fn mix(self, other: Color, t: f32) -> Color {
+        let from = Vec4::from(self.as_linear_rgba_f32());
+        let to = Vec4::from(other.as_linear_rgba_f32());
+        let [red, green, blue, alpha]: [f32; 4] = from.lerp(to, t).into();
+        Color::rgba_linear(red, green, blue, alpha).in_representation_of(self)
+    }
+
+    /// Increases the HSL lightness by `amount`, keeping the representation of `self`
+    pub fn lighten(self, amount: f32) -> Color {
+        let [hue, saturation, lightness, alpha] = self.as_hlsa_f32();
+        Color::hsla(hue, saturation, (lightness + amount).clamp(0.0, 1.0), alpha)
+            .in_representation_of(self)
+    }
+
+    /// Decreases the HSL lightness by `amount`, keeping the representation of `self`
+    pub fn darken(self, amount: f32) -> Color {
+        self.lighten(-amount)
+    }
+
+    /// Rotates the hue by `degrees`, keeping the representation of `self`
+    pub fn rotate_hue(self, degrees: f32) -> Color {
+        let [hue, saturation, lightness, alpha] = self.as_hlsa_f32();
+        Color::hsla(
+            (hue + degrees).rem_euclid(360.0),
+            saturation,
+            lightness,
+            alpha,
+        )
+        .in_representation_of(self)
+    }
+
+    /// The color on the opposite side of the color wheel
+    pub fn complementary(self) -> Color {
+        self.rotate_hue(180.0)
+    }
+
+    /// `count` colors with hues evenly spaced around the color wheel, starting with `self`
+    pub fn palette(self, count: usize) -> Vec<Color> {
+        (0..count)
+            .map(|i| self.rotate_hue(360.0 * i as f32 / count as f32))
+            .collect()
+    }
+
+    fn in_representation_of(self, other: Color) -> Color {
+        match other {
+            Color::Rgba { .. } => self.as_rgba(),
+            Color::RgbaLinear { .. } => self.as_rgba_linear(),
+            Color::Hsla { .. } => self.as_hsla(),
+        }
+    }
 }
 
 impl Default for Color {
@@ -1227,4 +1287,28 @@ mod tests {
 
         assert_eq!(starting_color * transformation, mutated_color,);
     }
+
+    #[test]
+    fn srgb_converts_to_linear() {
+        let linear = Color::rgb(0.5, 0.5, 0.5).as_rgba_linear();
+        assert!(matches!(linear, Color::RgbaLinear { .. }));
+        assert!((linear.as_linear_rgba_f32()[0] - 0.214).abs() < 0.001);
+    }
+
+    #[test]
+    fn palette_utilities() {
+        let gray = Color::BLACK.mix(Color::WHITE, 0.5);
+        assert!(matches!(gray, Color::Rgba { .. }));
+        assert!((gray.r() - 0.735).abs() < 0.001);
+        assert_eq!(Color::RED.with_alpha(0.5).a(), 0.5);
+
+        let [hue, _, lightness, _] = Color::hsl(90.0, 1.0, 0.5).lighten(0.25).as_hlsa_f32();
+        assert_eq!((hue, lightness), (90.0, 0.75));
+        let [hue, ..] = Color::hsl(270.0, 1.0, 0.5).complementary().as_hlsa_f32();
+        assert_eq!(hue, 90.0);
+
+        let palette = Color::hsl(0.0, 1.0, 0.5).palette(3);
+        let hues = palette.iter().map(|color| color.as_hlsa_f32()[0]);
+        assert_eq!(hues.collect::<Vec<_>>(), vec![0.0, 120.0, 240.0]);
+    }
 }
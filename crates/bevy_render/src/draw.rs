@@ -1,4 +1,5 @@
 use crate::{
+    color::Color,
     pipeline::{
         IndexFormat, PipelineCompiler, PipelineDescriptor, PipelineLayout, PipelineSpecialization,
     },
@@ -18,7 +19,7 @@ use std::{ops::Range, sync::Arc};
 use thiserror::Error;
 
 /// A queued command for the renderer
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum RenderCommand {
     SetPipeline {
         pipeline: Handle<PipelineDescriptor>,
@@ -47,6 +48,38 @@ pub enum RenderCommand {
         vertices: Range<u32>,
         instances: Range<u32>,
     },
+    /// Sets the value compared against and written to the stencil buffer by pipelines with a
+    /// [StencilState](crate::pipeline::StencilState)
+    SetStencilReference { reference: u32 },
+    /// Sets the constant blended with by pipelines using
+    /// [BlendState::CONSTANT](crate::pipeline::BlendState::CONSTANT) or the blend color factors
+    SetBlendColor { color: Color },
+    /// Restricts the following draws to a rectangle of the target, in physical pixels from its
+    /// top left corner
+    SetScissorRect {
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    },
+    /// Draws `count` times with the [DrawIndexedIndirectArgs] stored one after the other in
+    /// `buffer`, starting at `offset`
+    ///
+    /// [DrawIndexedIndirectArgs]: crate::renderer::DrawIndexedIndirectArgs
+    DrawIndexedIndirect {
+        buffer: BufferId,
+        offset: u64,
+        count: u32,
+    },
+    /// Draws `count` times with the [DrawIndirectArgs] stored one after the other in `buffer`,
+    /// starting at `offset`
+    ///
+    /// [DrawIndirectArgs]: crate::renderer::DrawIndirectArgs
+    DrawIndirect {
+        buffer: BufferId,
+        offset: u64,
+        count: u32,
+    },
 }
 
 #[derive(Debug, Clone, Reflect)]
@@ -143,6 +176,42 @@ impl Draw {
         });
     }
 
+    /// Opaque draws are reordered by the pass, so a draw testing the stencil should set the
+    /// reference itself instead of relying on the one set by a previous draw
+    pub fn set_stencil_reference(&mut self, reference: u32) {
+        self.render_command(RenderCommand::SetStencilReference { reference });
+    }
+
+    /// Like the stencil reference, a draw blending with the constant should set it itself
+    pub fn set_blend_color(&mut self, color: Color) {
+        self.render_command(RenderCommand::SetBlendColor { color });
+    }
+
+    pub fn set_scissor_rect(&mut self, x: u32, y: u32, width: u32, height: u32) {
+        self.render_command(RenderCommand::SetScissorRect {
+            x,
+            y,
+            width,
+            height,
+        });
+    }
+
+    pub fn draw_indexed_indirect(&mut self, buffer: BufferId, offset: u64, count: u32) {
+        self.render_command(RenderCommand::DrawIndexedIndirect {
+            buffer,
+            offset,
+            count,
+        });
+    }
+
+    pub fn draw_indirect(&mut self, buffer: BufferId, offset: u64, count: u32) {
+        self.render_command(RenderCommand::DrawIndirect {
+            buffer,
+            offset,
+            count,
+        });
+    }
+
     #[inline]
     pub fn render_command(&mut self, render_command: RenderCommand) {
         self.render_commands.push(render_command);
@@ -155,6 +224,8 @@ pub enum DrawError {
     NonExistentPipeline,
     #[error("no pipeline set")]
     NoPipelineSet,
+    #[error("a shader of the pipeline isn't loaded yet")]
+    ShaderNotLoaded,
     #[error("pipeline has no layout")]
     PipelineHasNoLayout,
     #[error("failed to get a buffer for the given `RenderResource`")]
@@ -197,13 +268,15 @@ impl<'a> DrawContext<'a> {
         {
             specialized_pipeline
         } else {
-            self.pipeline_compiler.compile_pipeline(
-                &**self.render_resource_context,
-                &mut self.pipelines,
-                &mut self.shaders,
-                pipeline_handle,
-                specialization,
-            )
+            self.pipeline_compiler
+                .compile_pipeline(
+                    &**self.render_resource_context,
+                    &mut self.pipelines,
+                    &mut self.shaders,
+                    pipeline_handle,
+                    specialization,
+                )
+                .ok_or(DrawError::ShaderNotLoaded)?
         };
 
         draw.set_pipeline(&specialized_pipeline);
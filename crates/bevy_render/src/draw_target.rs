@@ -0,0 +1,214 @@
+use crate::pass::RenderCommands;
+use bevy_ecs::{
+    entity::Entity,
+    query::{Fetch, FilterFetch, QueryState, ReadOnlyFetch, WorldQuery},
+    world::World,
+};
+use bevy_utils::HashMap;
+use std::any::{Any, TypeId};
+
+/// The order a [DrawTarget] draws its entities in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrawSortOrder {
+    /// Entities closest to the camera are drawn first, which avoids overdraw of opaque entities
+    FrontToBack,
+    /// Entities farthest from the camera are drawn first, which transparent entities need to blend
+    /// correctly
+    BackToFront,
+}
+
+/// Custom drawing logic for the visible entities matching its query.
+///
+/// Draw targets are registered in the [DrawTargets] resource. Every pass node draws the visible
+/// entities matching both its own query and a target's query with that target instead of their
+/// [Draw](crate::draw::Draw) component. Targets drawing [DrawSortOrder::FrontToBack] are drawn
+/// with the opaque entities, and targets drawing [DrawSortOrder::BackToFront] are drawn in depth
+/// order with the transparent entities.
+pub trait DrawTarget: Send + Sync + 'static {
+    /// The components this target reads from the entities it draws
+    type Query: WorldQuery;
+    /// Filters the entities this target draws
+    type Filter: WorldQuery;
+
+    /// The order this target draws its entities in. Entities with a
+    /// [DrawOrder](crate::camera::DrawOrder) are sorted by it instead of their depth
+    fn sort_order(&self) -> DrawSortOrder;
+
+    /// Records the commands drawing a single entity. Camera bind groups are set by the pass node
+    /// whenever a pipeline is set.
+    fn draw<'w>(
+        &self,
+        world: &'w World,
+        item: <<Self::Query as WorldQuery>::Fetch as Fetch<'w>>::Item,
+        commands: &mut RenderCommands,
+    );
+}
+
+/// A [DrawTarget] with its query state, with the target's types erased
+pub(crate) trait DynDrawTarget: Send + Sync {
+    fn init(&mut self, world: &mut World);
+    fn sort_order(&self) -> DrawSortOrder;
+    fn matches(&mut self, world: &World, entity: Entity) -> bool;
+    fn draw(&mut self, world: &World, entity: Entity, commands: &mut RenderCommands);
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+struct DrawTargetState<T: DrawTarget> {
+    target: T,
+    query_state: Option<QueryState<T::Query, T::Filter>>,
+}
+
+impl<T: DrawTarget> DynDrawTarget for DrawTargetState<T>
+where
+    <T::Query as WorldQuery>::Fetch: ReadOnlyFetch,
+    <T::Filter as WorldQuery>::Fetch: FilterFetch,
+{
+    fn init(&mut self, world: &mut World) {
+        if self.query_state.is_none() {
+            self.query_state = Some(world.query_filtered());
+        }
+    }
+
+    fn sort_order(&self) -> DrawSortOrder {
+        self.target.sort_order()
+    }
+
+    fn matches(&mut self, world: &World, entity: Entity) -> bool {
+        self.query_state
+            .as_mut()
+            .map_or(false, |query_state| query_state.get(world, entity).is_ok())
+    }
+
+    fn draw(&mut self, world: &World, entity: Entity, commands: &mut RenderCommands) {
+        if let Some(query_state) = self.query_state.as_mut() {
+            if let Ok(item) = query_state.get(world, entity) {
+                self.target.draw(world, item, commands);
+            }
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        &self.target
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        &mut self.target
+    }
+}
+
+/// The registered [DrawTarget]s, at most one of each type. Entities matching several targets are
+/// drawn by the one that was registered first.
+#[derive(Default)]
+pub struct DrawTargets {
+    targets: Vec<Box<dyn DynDrawTarget>>,
+    indices: HashMap<TypeId, usize>,
+}
+
+impl DrawTargets {
+    /// Registers `target`, replacing the registered target of the same type if there is one
+    pub fn add<T: DrawTarget>(&mut self, target: T)
+    where
+        <T::Query as WorldQuery>::Fetch: ReadOnlyFetch,
+        <T::Filter as WorldQuery>::Fetch: FilterFetch,
+    {
+        let state = Box::new(DrawTargetState {
+            target,
+            query_state: None,
+        });
+        if let Some(index) = self.indices.get(&TypeId::of::<T>()) {
+            self.targets[*index] = state;
+        } else {
+            self.indices.insert(TypeId::of::<T>(), self.targets.len());
+            self.targets.push(state);
+        }
+    }
+
+    pub fn get<T: DrawTarget>(&self) -> Option<&T> {
+        let index = *self.indices.get(&TypeId::of::<T>())?;
+        self.targets[index].as_any().downcast_ref()
+    }
+
+    pub fn get_mut<T: DrawTarget>(&mut self) -> Option<&mut T> {
+        let index = *self.indices.get(&TypeId::of::<T>())?;
+        self.targets[index].as_any_mut().downcast_mut()
+    }
+
+    pub fn len(&self) -> usize {
+        self.targets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.targets.is_empty()
+    }
+
+    pub(crate) fn iter_mut(&mut self) -> impl Iterator<Item = &mut Box<dyn DynDrawTarget>> {
+        self.targets.iter_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{draw::RenderCommand, renderer::BindGroupId};
+
+    struct Marker(u32);
+
+    struct MarkerTarget {
+        sort_order: DrawSortOrder,
+    }
+
+    impl DrawTarget for MarkerTarget {
+        type Query = &'static Marker;
+        type Filter = ();
+
+        fn sort_order(&self) -> DrawSortOrder {
+            self.sort_order
+        }
+
+        fn draw<'w>(&self, _world: &'w World, marker: &'w Marker, commands: &mut RenderCommands) {
+            commands.push(RenderCommand::SetBindGroup {
+                index: 0,
+                bind_group: BindGroupId(marker.0 as u64),
+                dynamic_uniform_indices: None,
+            });
+        }
+    }
+
+    #[test]
+    fn draws_entities_matching_the_target_query() {
+        let mut world = World::new();
+        let marked = world.spawn().insert(Marker(7)).id();
+        let unmarked = world.spawn().id();
+
+        let mut draw_targets = DrawTargets::default();
+        draw_targets.add(MarkerTarget {
+            sort_order: DrawSortOrder::FrontToBack,
+        });
+        draw_targets.add(MarkerTarget {
+            sort_order: DrawSortOrder::BackToFront,
+        });
+        assert_eq!(draw_targets.len(), 1);
+        assert_eq!(
+            draw_targets.get::<MarkerTarget>().unwrap().sort_order,
+            DrawSortOrder::BackToFront
+        );
+
+        let mut commands = RenderCommands::default();
+        for target in draw_targets.iter_mut() {
+            target.init(&mut world);
+            assert!(target.matches(&world, marked));
+            assert!(!target.matches(&world, unmarked));
+            target.draw(&world, marked, &mut commands);
+            target.draw(&world, unmarked, &mut commands);
+        }
+        assert_eq!(
+            commands.iter().collect::<Vec<_>>(),
+            vec![&RenderCommand::SetBindGroup {
+                index: 0,
+                bind_group: BindGroupId(7),
+                dynamic_uniform_indices: None,
+            }]
+        );
+    }
+}
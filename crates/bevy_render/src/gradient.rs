@@ -0,0 +1,76 @@
+use crate::color::Color;
+use bevy_math::{Curve, Interpolate, Keyframe};
+use bevy_reflect::{Reflect, TypeUuid};
+use serde::{Deserialize, Serialize};
+
+impl Interpolate for Color {
+    /// Interpolates in linear space, see [Color::mix]
+    fn interpolate(self, other: Self, t: f32) -> Self {
+        self.mix(other, t)
+    }
+}
+
+/// Colors placed at positions along a line, usually in `0.0..=1.0`, and blended in linear space
+/// between them
+#[derive(Debug, Clone, PartialEq, Reflect, Serialize, Deserialize, TypeUuid)]
+#[uuid = "3f1c6a2e-8d47-4b59-a0e3-71c25d9b4e86"]
+pub struct Gradient {
+    stops: Curve<Color>,
+}
+
+impl Gradient {
+    /// Creates a gradient from `(position, color)` stops, in any order. Panics if there are no
+    /// stops.
+    pub fn new(stops: impl IntoIterator<Item = (f32, Color)>) -> Self {
+        Gradient {
+            stops: Curve::linear(stops),
+        }
+    }
+
+    /// A gradient from `start` at 0.0 to `end` at 1.0
+    pub fn from_to(start: Color, end: Color) -> Self {
+        Gradient::new(vec![(0.0, start), (1.0, end)])
+    }
+
+    /// A gradient that is `color` everywhere
+    pub fn solid(color: Color) -> Self {
+        Gradient {
+            stops: Curve::constant(color),
+        }
+    }
+
+    pub fn stops(&self) -> &[Keyframe<Color>] {
+        self.stops.keyframes()
+    }
+
+    /// The color at `position`, which is the color of the closest stop outside of the stops
+    pub fn sample(&self, position: f32) -> Color {
+        self.stops.sample(position)
+    }
+
+    /// The stops as a curve, to sample alongside other curves
+    pub fn as_curve(&self) -> &Curve<Color> {
+        &self.stops
+    }
+}
+
+impl From<Curve<Color>> for Gradient {
+    fn from(stops: Curve<Color>) -> Self {
+        Gradient { stops }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blends_stops_in_linear_space() {
+        let gradient = Gradient::new(vec![(1.0, Color::WHITE), (0.0, Color::BLACK)]);
+        assert_eq!(gradient.sample(-1.0), Color::BLACK);
+        assert_eq!(gradient.sample(2.0), Color::WHITE);
+        let middle = gradient.sample(0.5).as_linear_rgba_f32();
+        assert!((middle[0] - 0.5).abs() < 1e-4);
+        assert_eq!(Gradient::solid(Color::RED).sample(0.3), Color::RED);
+    }
+}
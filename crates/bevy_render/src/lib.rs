@@ -1,15 +1,25 @@
+pub mod billboard;
+pub mod bloom;
 pub mod camera;
 pub mod color;
 pub mod colorspace;
 pub mod draw;
+pub mod draw_target;
 pub mod entity;
+pub mod gradient;
+pub mod lod;
+pub mod material;
 pub mod mesh;
+pub mod occlusion;
+pub mod particles;
 pub mod pass;
+pub mod picking;
 pub mod pipeline;
 pub mod render_graph;
 pub mod renderer;
 pub mod shader;
 pub mod texture;
+pub mod tonemapping;
 pub mod wireframe;
 
 use bevy_ecs::{
@@ -24,12 +34,15 @@ pub use once_cell;
 
 pub mod prelude {
     pub use crate::{
-        base::Msaa,
+        base::{Depth, Hdr, Msaa},
+        billboard::Billboard,
         color::Color,
         draw::{Draw, Visible},
         entity::*,
+        gradient::Gradient,
+        lod::{Lod, LodLevel},
         mesh::{shape, Mesh},
-        pass::ClearColor,
+        pass::{ClearColor, ClearColorConfig},
         pipeline::RenderPipelines,
         shader::Shader,
         texture::Texture,
@@ -37,32 +50,48 @@ pub mod prelude {
 }
 
 use crate::prelude::*;
-use base::Msaa;
+use base::{Depth, Hdr, Msaa};
 use bevy_app::prelude::*;
-use bevy_asset::{AddAsset, AssetStage};
+use bevy_asset::{AddAsset, AssetStage, Assets};
 use bevy_ecs::schedule::{StageLabel, SystemLabel};
+use bevy_math::Interpolation;
+use billboard::{Billboard, BillboardMode};
 use camera::{
-    ActiveCameras, Camera, DepthCalculation, OrthographicProjection, PerspectiveProjection,
-    RenderLayers, ScalingMode, VisibleEntities, WindowOrigin,
+    ActiveCameras, Camera, DepthCalculation, DrawOrder, OrthographicProjection,
+    PerspectiveProjection, RenderLayers, ScalingMode, VisibleEntities, WindowOrigin,
 };
+use draw_target::DrawTargets;
+use lod::{Lod, LodLevel};
+use pass::FULLSCREEN_VERTEX_SHADER_HANDLE;
 use pipeline::{
-    IndexFormat, PipelineCompiler, PipelineDescriptor, PipelineSpecialization, PrimitiveTopology,
-    ShaderSpecialization, VertexBufferLayout,
+    ComputePipelineDescriptor, IndexFormat, IndirectDraw, PipelineCompiler, PipelineDescriptor,
+    PipelineSpecialization, PrimitiveTopology, ShaderSpecialization, VertexBufferLayout,
 };
 use render_graph::{
     base::{self, BaseRenderGraphConfig, MainPass},
     RenderGraph,
 };
-use renderer::{AssetRenderResourceBindings, RenderResourceBindings, RenderResourceContext};
-use shader::ShaderLoader;
+use renderer::{
+    gpu_capture_key_system, AssetRenderResourceBindings, FramesInFlight, GpuCapture,
+    RenderResourceBindings, RenderResourceContext,
+};
+use shader::{ShaderCompileError, ShaderLoader, ShaderStage};
+#[cfg(feature = "dds")]
+use texture::DdsTextureLoader;
 #[cfg(feature = "hdr")]
 use texture::HdrTextureLoader;
 #[cfg(feature = "png")]
 use texture::ImageTextureLoader;
+#[cfg(feature = "ktx2")]
+use texture::{ImageTextureProcessor, Ktx2TextureLoader};
+use tonemapping::{Exposure, Tonemapping, TonemappingOperator, TONEMAPPING_PIPELINE_HANDLE};
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
 pub enum RenderSystem {
     VisibleEntities,
+    /// Records the [Draw](draw::Draw) commands of entities with
+    /// [RenderPipelines](pipeline::RenderPipelines)
+    Draw,
 }
 
 /// The names of "render" App stages
@@ -104,6 +133,15 @@ impl Plugin for RenderPlugin {
         {
             app.init_asset_loader::<HdrTextureLoader>();
         }
+        #[cfg(feature = "dds")]
+        {
+            app.init_asset_loader::<DdsTextureLoader>();
+        }
+        #[cfg(feature = "ktx2")]
+        {
+            app.init_asset_loader::<Ktx2TextureLoader>()
+                .add_asset_processor(ImageTextureProcessor);
+        }
 
         app.add_stage_after(
             AssetStage::AssetEvents,
@@ -135,17 +173,27 @@ impl Plugin for RenderPlugin {
         .add_asset::<Texture>()
         .add_asset::<Shader>()
         .add_asset::<PipelineDescriptor>()
+        .add_asset::<ComputePipelineDescriptor>()
+        .add_asset::<Gradient>()
+        .register_type::<Billboard>()
+        .register_type::<BillboardMode>()
         .register_type::<Camera>()
         .register_type::<DepthCalculation>()
         .register_type::<Draw>()
+        .register_type::<Lod>()
+        .register_type::<LodLevel>()
         .register_type::<Visible>()
         .register_type::<OutsideFrustum>()
         .register_type::<RenderPipelines>()
+        .register_type::<IndirectDraw>()
         .register_type::<OrthographicProjection>()
         .register_type::<PerspectiveProjection>()
         .register_type::<MainPass>()
         .register_type::<VisibleEntities>()
+        .register_type::<DrawOrder>()
         .register_type::<Color>()
+        .register_type::<Gradient>()
+        .register_type::<Interpolation>()
         .register_type::<ShaderSpecialization>()
         .register_type::<PrimitiveTopology>()
         .register_type::<IndexFormat>()
@@ -154,10 +202,19 @@ impl Plugin for RenderPlugin {
         .register_type::<ScalingMode>()
         .register_type::<VertexBufferLayout>()
         .register_type::<WindowOrigin>()
+        .register_type::<Tonemapping>()
+        .register_type::<Exposure>()
+        .register_type::<TonemappingOperator>()
         .init_resource::<ClearColor>()
+        .init_resource::<DrawTargets>()
         .init_resource::<RenderGraph>()
         .init_resource::<PipelineCompiler>()
         .init_resource::<Msaa>()
+        .init_resource::<Hdr>()
+        .init_resource::<Depth>()
+        .init_resource::<FramesInFlight>()
+        .init_resource::<GpuCapture>()
+        .add_event::<ShaderCompileError>()
         .init_resource::<RenderResourceBindings>()
         .init_resource::<AssetRenderResourceBindings>()
         .init_resource::<ActiveCameras>()
@@ -166,6 +223,7 @@ impl Plugin for RenderPlugin {
             check_for_render_resource_context.system(),
         )
         .add_system_to_stage(CoreStage::PreUpdate, draw::clear_draw_system.system())
+        .add_system_to_stage(CoreStage::PostUpdate, gpu_capture_key_system.system())
         .add_system_to_stage(
             CoreStage::PostUpdate,
             camera::active_cameras_system.system(),
@@ -189,10 +247,24 @@ impl Plugin for RenderPlugin {
                 .label(RenderSystem::VisibleEntities)
                 .after(TransformSystem::TransformPropagate),
         )
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            lod::lod_system
+                .system()
+                .after(RenderSystem::VisibleEntities),
+        )
         .add_system_to_stage(
             RenderStage::RenderResource,
             shader::shader_update_system.system(),
         )
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            mesh::mesh_shader_defs_system.system(),
+        )
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            billboard::billboard_shader_defs_system.system(),
+        )
         .add_system_to_stage(
             RenderStage::RenderResource,
             mesh::mesh_resource_provider_system.system(),
@@ -207,13 +279,34 @@ impl Plugin for RenderPlugin {
         )
         .add_system_to_stage(
             RenderStage::Draw,
-            pipeline::draw_render_pipelines_system.system(),
+            pipeline::draw_render_pipelines_system
+                .system()
+                .label(RenderSystem::Draw),
         )
         .add_system_to_stage(
             RenderStage::PostRender,
             shader::clear_shader_defs_system.system(),
         );
 
+        {
+            let world = app.world_mut().cell();
+            let mut shaders = world.get_resource_mut::<Assets<Shader>>().unwrap();
+            let mut pipelines = world
+                .get_resource_mut::<Assets<PipelineDescriptor>>()
+                .unwrap();
+            shaders.set_untracked(
+                FULLSCREEN_VERTEX_SHADER_HANDLE,
+                Shader::from_glsl(ShaderStage::Vertex, include_str!("pass/fullscreen.vert")),
+            );
+            pipeline::build_error_shaders(&mut shaders);
+            pipelines.set_untracked(
+                TONEMAPPING_PIPELINE_HANDLE,
+                tonemapping::build_tonemapping_pipeline(&mut shaders),
+            );
+            let mut textures = world.get_resource_mut::<Assets<Texture>>().unwrap();
+            textures.set_untracked(texture::ERROR_TEXTURE_HANDLE, Texture::error_checkerboard());
+        }
+
         if let Some(ref config) = self.base_render_graph_config {
             crate::base::add_base_graph(config, app.world_mut());
             let mut active_cameras = app.world_mut().get_resource_mut::<ActiveCameras>().unwrap();
@@ -0,0 +1,220 @@
+use crate::{
+    camera::{Camera, DepthCalculation, VisibleEntities},
+    draw::RenderCommand,
+    mesh::{Indices, Mesh, INDEX_BUFFER_ASSET_INDEX, VERTEX_ATTRIBUTE_BUFFER_ID},
+    pipeline::IndexFormat,
+    renderer::RenderResourceContext,
+};
+use bevy_asset::{Assets, Handle};
+use bevy_ecs::{entity::Entity, reflect::ReflectComponent, system::Query, world::World};
+use bevy_reflect::Reflect;
+use bevy_transform::components::GlobalTransform;
+use bevy_utils::HashMap;
+
+/// A level of detail of a [Lod]
+#[derive(Debug, Clone, Default, Reflect)]
+pub struct LodLevel {
+    pub mesh: Handle<Mesh>,
+    /// The distance from the camera at which this level starts being used
+    pub min_distance: f32,
+}
+
+/// Draws an entity with simpler meshes as it gets further from a camera. The level is selected
+/// for each camera that sees the entity, so every camera draws the level matching its own
+/// distance. The entity's [Handle<Mesh>] should be the most detailed level: it is drawn by cameras
+/// that didn't select a level, and the other levels are drawn with its pipelines, so they need
+/// the same vertex layout and must either all have indices or all have none.
+///
+/// To avoid popping back and forth when the camera hovers around the distance between two
+/// levels, the camera has to move `hysteresis` times that distance past it before the level
+/// changes.
+#[derive(Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct Lod {
+    /// The levels of detail, from the most detailed one to the least detailed one. Their
+    /// `min_distance` should increase from one level to the next.
+    pub levels: Vec<LodLevel>,
+    pub hysteresis: f32,
+    /// The selected level, by camera
+    #[reflect(ignore)]
+    current: HashMap<Entity, usize>,
+}
+
+impl Default for Lod {
+    fn default() -> Self {
+        Lod {
+            levels: Vec::new(),
+            hysteresis: 0.1,
+            current: HashMap::default(),
+        }
+    }
+}
+
+impl Lod {
+    pub fn new(levels: Vec<LodLevel>) -> Self {
+        Lod {
+            levels,
+            ..Default::default()
+        }
+    }
+
+    /// The index of the level that was last selected for `camera`, if any
+    pub fn current_level(&self, camera: Entity) -> Option<usize> {
+        self.current.get(&camera).copied()
+    }
+
+    /// Selects the level for `camera`, which is `distance` away, taking the level previously
+    /// selected for it and the hysteresis into account
+    pub fn select_level(&mut self, camera: Entity, distance: f32) -> Option<usize> {
+        if self.levels.is_empty() {
+            self.current.clear();
+            return None;
+        }
+
+        let mut level = match self.current.get(&camera) {
+            Some(level) => (*level).min(self.levels.len() - 1),
+            None => {
+                let level = self
+                    .levels
+                    .iter()
+                    .rposition(|level| distance >= level.min_distance)
+                    .unwrap_or(0);
+                self.current.insert(camera, level);
+                return Some(level);
+            }
+        };
+        while level + 1 < self.levels.len()
+            && distance > self.levels[level + 1].min_distance * (1.0 + self.hysteresis)
+        {
+            level += 1;
+        }
+        while level > 0 && distance < self.levels[level].min_distance * (1.0 - self.hysteresis) {
+            level -= 1;
+        }
+        self.current.insert(camera, level);
+        Some(level)
+    }
+}
+
+/// Selects the level of detail of every [Lod] entity for each 3d camera that sees it
+pub fn lod_system(
+    cameras: Query<(Entity, &Camera, &GlobalTransform, &VisibleEntities)>,
+    mut lods: Query<(&mut Lod, &GlobalTransform)>,
+) {
+    for (camera_entity, camera, camera_transform, visible_entities) in cameras.iter() {
+        if !matches!(camera.depth_calculation, DepthCalculation::Distance) {
+            continue;
+        }
+        for visible_entity in visible_entities.iter() {
+            if let Ok((mut lod, global_transform)) = lods.get_mut(visible_entity.entity) {
+                let distance = camera_transform
+                    .translation
+                    .distance(global_transform.translation);
+                lod.select_level(camera_entity, distance);
+            }
+        }
+    }
+}
+
+/// The commands drawing `entity` for `camera` with the level of its [Lod] selected for that
+/// camera, which replace the mesh buffers and draw ranges of the entity's `render_commands`.
+/// Returns `None` if the entity is drawn with its own mesh.
+pub(crate) fn lod_render_commands(
+    world: &World,
+    entity: Entity,
+    camera: Entity,
+    render_commands: &[RenderCommand],
+) -> Option<Vec<RenderCommand>> {
+    let lod = world.get::<Lod>(entity)?;
+    let mesh_handle = &lod.levels.get(lod.current_level(camera)?)?.mesh;
+    if world.get::<Handle<Mesh>>(entity) == Some(mesh_handle) {
+        return None;
+    }
+    let mesh = world.get_resource::<Assets<Mesh>>()?.get(mesh_handle)?;
+    let render_resource_context = world.get_resource::<Box<dyn RenderResourceContext>>()?;
+    // the level is drawn once its buffers exist
+    let vertex_buffer = render_resource_context
+        .get_asset_resource(mesh_handle, VERTEX_ATTRIBUTE_BUFFER_ID)?
+        .get_buffer()?;
+    let index_buffer = match mesh.indices() {
+        Some(indices) => Some((
+            render_resource_context
+                .get_asset_resource(mesh_handle, INDEX_BUFFER_ASSET_INDEX)?
+                .get_buffer()?,
+            IndexFormat::from(indices),
+            match indices {
+                Indices::U32(indices) => indices.len() as u32,
+                Indices::U16(indices) => indices.len() as u32,
+            },
+        )),
+        None => None,
+    };
+
+    render_commands
+        .iter()
+        .map(|render_command| {
+            Some(match render_command {
+                RenderCommand::SetVertexBuffer {
+                    slot: 0, offset, ..
+                } => RenderCommand::SetVertexBuffer {
+                    slot: 0,
+                    buffer: vertex_buffer,
+                    offset: *offset,
+                },
+                RenderCommand::SetIndexBuffer { offset, .. } => {
+                    let (buffer, index_format, _) = index_buffer?;
+                    RenderCommand::SetIndexBuffer {
+                        buffer,
+                        offset: *offset,
+                        index_format,
+                    }
+                }
+                RenderCommand::DrawIndexed {
+                    base_vertex,
+                    instances,
+                    ..
+                } => RenderCommand::DrawIndexed {
+                    indices: 0..index_buffer?.2,
+                    base_vertex: *base_vertex,
+                    instances: instances.clone(),
+                },
+                RenderCommand::Draw { instances, .. } if index_buffer.is_none() => {
+                    RenderCommand::Draw {
+                        vertices: 0..mesh.count_vertices() as u32,
+                        instances: instances.clone(),
+                    }
+                }
+                // the level's indexing doesn't match the entity's mesh
+                RenderCommand::Draw { .. } => return None,
+                render_command => render_command.clone(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selects_levels_with_hysteresis() {
+        let level = |min_distance| LodLevel {
+            mesh: Default::default(),
+            min_distance,
+        };
+        let (camera, other_camera) = (Entity::new(0), Entity::new(1));
+        let mut lod = Lod::new(vec![level(0.0), level(10.0), level(20.0)]);
+        assert_eq!(lod.select_level(camera, 15.0), Some(1));
+        assert_eq!(lod.select_level(camera, 9.5), Some(1));
+        assert_eq!(lod.select_level(camera, 8.5), Some(0));
+        assert_eq!(lod.select_level(camera, 10.5), Some(0));
+        assert_eq!(lod.select_level(camera, 50.0), Some(2));
+        assert_eq!(lod.select_level(camera, 1.0), Some(0));
+        assert_eq!(Lod::default().select_level(camera, 1.0), None);
+
+        // every camera keeps its own level
+        assert_eq!(lod.select_level(other_camera, 25.0), Some(2));
+        assert_eq!(lod.current_level(camera), Some(0));
+        assert_eq!(lod.current_level(other_camera), Some(2));
+    }
+}
@@ -0,0 +1,99 @@
+use crate::{
+    render_graph::{add_main_pass_node, AssetRenderResourcesNode},
+    renderer::RenderResources,
+    shader::{asset_shader_defs_system, ShaderDefs},
+    RenderSystem,
+};
+use bevy_app::prelude::*;
+use bevy_asset::{AddAsset, Asset, Handle};
+use bevy_ecs::{
+    entity::Entity,
+    query::{Changed, With, Without},
+    reflect::ReflectComponent,
+    schedule::ParallelSystemDescriptorCoercion,
+    system::{Commands, IntoSystem, Query, RemovedComponents},
+};
+use bevy_reflect::Reflect;
+use bevy_utils::AHasher;
+use std::{
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+};
+
+/// An asset whose fields are bound to shaders and whose shader defs specialize the pipelines it is
+/// drawn with.
+///
+/// Textures, colors and scalars are mapped to bind group entries by
+/// `#[derive(RenderResources)]`, and shader defs by `#[derive(ShaderDefs)]`. Adding a
+/// [MaterialPlugin] for the type takes care of the rest.
+pub trait Material: RenderResources + ShaderDefs + Asset {}
+
+/// Identifies the material an entity is drawn with. Opaque entities at similar depths are grouped
+/// by their batch, which lets consecutive draws reuse the material's bind group while still drawing
+/// roughly front-to-back.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Reflect)]
+#[reflect(Component)]
+pub struct MaterialBatch(pub u64);
+
+/// Registers a [Material] asset type and binds it to shaders drawn in the main pass
+pub struct MaterialPlugin<M: Material> {
+    dynamic_uniforms: bool,
+    marker: PhantomData<M>,
+}
+
+impl<M: Material> MaterialPlugin<M> {
+    pub fn new(dynamic_uniforms: bool) -> Self {
+        MaterialPlugin {
+            dynamic_uniforms,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<M: Material> Default for MaterialPlugin<M> {
+    fn default() -> Self {
+        Self::new(false)
+    }
+}
+
+impl<M: Material> Plugin for MaterialPlugin<M> {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_asset::<M>()
+            .register_type::<MaterialBatch>()
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                asset_shader_defs_system::<M>.system(),
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                material_batch_system::<M>
+                    .system()
+                    .before(RenderSystem::VisibleEntities),
+            );
+        add_main_pass_node(
+            app,
+            AssetRenderResourcesNode::<M>::new(self.dynamic_uniforms),
+        );
+    }
+}
+
+pub fn material_batch_system<M: Material>(
+    mut commands: Commands,
+    query: Query<(Entity, &Handle<M>), Changed<Handle<M>>>,
+    removed: RemovedComponents<Handle<M>>,
+    without_material: Query<(), (With<MaterialBatch>, Without<Handle<M>>)>,
+) {
+    for (entity, handle) in query.iter() {
+        let mut hasher = AHasher::default();
+        handle.id.hash(&mut hasher);
+        commands
+            .entity(entity)
+            .insert(MaterialBatch(hasher.finish()));
+    }
+    // entities that no longer have the material must not be batched with it
+    for entity in removed.iter() {
+        if without_material.get(entity).is_ok() {
+            commands.entity(entity).remove::<MaterialBatch>();
+        }
+    }
+}
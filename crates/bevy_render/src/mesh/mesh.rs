@@ -1,6 +1,7 @@
 mod conversions;
 
 use crate::{
+    draw::OutsideFrustum,
     pipeline::{IndexFormat, PrimitiveTopology, RenderPipelines, VertexFormat},
     renderer::{BufferInfo, BufferUsage, RenderResourceContext, RenderResourceId},
 };
@@ -9,7 +10,7 @@ use bevy_core::AsBytes;
 use bevy_ecs::{
     entity::Entity,
     event::EventReader,
-    query::{Changed, With},
+    query::{Changed, With, Without},
     system::{Local, Query, QuerySet, Res},
     world::Mut,
 };
@@ -23,6 +24,13 @@ use bevy_utils::{HashMap, HashSet};
 pub const INDEX_BUFFER_ASSET_INDEX: u64 = 0;
 pub const VERTEX_ATTRIBUTE_BUFFER_ID: u64 = 10;
 
+/// Shader defs that are defined for entities whose mesh has the corresponding optional attribute
+pub const MESH_ATTRIBUTE_SHADER_DEFS: &[(&str, &str)] = &[
+    (Mesh::ATTRIBUTE_COLOR, "VERTEX_COLORS"),
+    (Mesh::ATTRIBUTE_TANGENT, "VERTEX_TANGENTS"),
+    (Mesh::ATTRIBUTE_UV_1, "VERTEX_UV_1"),
+];
+
 /// An array where each entry describes a property of a single vertex.
 #[derive(Clone, Debug)]
 pub enum VertexAttributeValues {
@@ -186,7 +194,7 @@ pub enum Indices {
 }
 
 impl Indices {
-    fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+    pub(crate) fn iter(&self) -> impl Iterator<Item = usize> + '_ {
         match self {
             Indices::U16(vec) => IndicesIter::U16(vec.iter()),
             Indices::U32(vec) => IndicesIter::U32(vec.iter()),
@@ -253,13 +261,17 @@ impl Mesh {
     /// The direction the vertex normal is facing in.
     /// Use in conjunction with [`Mesh::set_attribute`]
     pub const ATTRIBUTE_NORMAL: &'static str = "Vertex_Normal";
-    /// The direction of the vertex tangent. Used for normal mapping
+    /// The direction of the vertex tangent. Used for normal mapping, which the PBR shader skips for
+    /// meshes without tangents
     pub const ATTRIBUTE_TANGENT: &'static str = "Vertex_Tangent";
 
     /// Where the vertex is located in space. Use in conjunction with [`Mesh::set_attribute`]
     pub const ATTRIBUTE_POSITION: &'static str = "Vertex_Position";
     /// Texture coordinates for the vertex. Use in conjunction with [`Mesh::set_attribute`]
     pub const ATTRIBUTE_UV_0: &'static str = "Vertex_Uv";
+    /// A second set of texture coordinates, commonly used for lightmaps. The PBR shader samples
+    /// the occlusion texture with it. Use in conjunction with [`Mesh::set_attribute`]
+    pub const ATTRIBUTE_UV_1: &'static str = "Vertex_Uv_1";
 
     /// Construct a new mesh. You need to provide a PrimitiveTopology so that the
     /// renderer knows how to treat the vertex data. Most of the time this will be
@@ -591,6 +603,32 @@ pub fn mesh_resource_provider_system(
     }
 }
 
+/// Defines the [MESH_ATTRIBUTE_SHADER_DEFS] of the optional attributes each entity's mesh has, so
+/// shaders can make use of them without requiring them
+pub fn mesh_shader_defs_system(
+    meshes: Res<Assets<Mesh>>,
+    mut query: Query<(&Handle<Mesh>, &mut RenderPipelines), Without<OutsideFrustum>>,
+) {
+    for (handle, mut render_pipelines) in query.iter_mut() {
+        let mesh = match meshes.get(handle) {
+            Some(mesh) => mesh,
+            None => continue,
+        };
+        for (attribute, shader_def) in MESH_ATTRIBUTE_SHADER_DEFS.iter() {
+            if mesh.attribute(*attribute).is_none() {
+                continue;
+            }
+            for render_pipeline in render_pipelines.pipelines.iter_mut() {
+                render_pipeline
+                    .specialization
+                    .shader_specialization
+                    .shader_defs
+                    .insert(shader_def.to_string());
+            }
+        }
+    }
+}
+
 fn update_entity_mesh(
     render_resource_context: &dyn RenderResourceContext,
     mesh: &Mesh,
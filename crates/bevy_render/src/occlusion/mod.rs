@@ -0,0 +1,243 @@
+use crate::{
+    pipeline::ComputePipelineDescriptor,
+    render_graph::{base, RenderGraph, WindowTextureNode},
+    shader::{Shader, ShaderStage},
+};
+use bevy_app::prelude::*;
+use bevy_asset::{Assets, HandleUntyped};
+use bevy_ecs::{
+    entity::Entity,
+    query::With,
+    reflect::ReflectComponent,
+    system::{Commands, IntoSystem, Query, Res},
+};
+use bevy_math::Aabb;
+use bevy_reflect::{Reflect, TypeUuid};
+use bevy_transform::components::GlobalTransform;
+use bevy_utils::HashSet;
+
+mod occlusion_culling_node;
+
+pub use occlusion_culling_node::*;
+
+pub const HI_Z_PIPELINE_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(ComputePipelineDescriptor::TYPE_UUID, 0x2a7c5e91f04b6d38);
+pub const OCCLUSION_CULLING_PIPELINE_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(ComputePipelineDescriptor::TYPE_UUID, 0x93e1b04d7f25a6c8);
+
+/// The shader def of the Hi-Z pipeline that builds the first level from the depth texture
+pub const HI_Z_FROM_DEPTH_SHADER_DEF: &str = "HI_Z_FROM_DEPTH";
+/// The shader def of the Hi-Z pipeline set when the depth texture is multisampled
+pub const DEPTH_MULTISAMPLED_SHADER_DEF: &str = "DEPTH_MULTISAMPLED";
+
+/// The maximum number of levels of the Hi-Z pyramid, enough for 65536x65536 depth textures
+pub const MAX_HI_Z_LEVELS: usize = 16;
+
+pub mod node {
+    pub const OCCLUSION_CULLING: &str = "occlusion_culling";
+}
+
+/// The names of the occlusion culling render resources
+pub mod binding {
+    pub const HI_Z: &str = "HiZ";
+    pub const HI_Z_LEVEL: &str = "HiZLevel";
+    pub const DEPTH_TEXTURE: &str = "DepthTexture";
+    pub const DEPTH_TEXTURE_SAMPLER: &str = "DepthTexture_sampler";
+    pub const OCCLUSION_AABBS: &str = "OcclusionAabbs";
+    pub const OCCLUSION_VISIBILITY: &str = "OcclusionVisibility";
+    pub const OCCLUSION_CAMERA: &str = "OcclusionCamera";
+}
+
+/// Hides [OcclusionCullable] entities that are behind other geometry from the 3d camera.
+///
+/// After the main pass, the depth texture is reduced to a hierarchical-Z pyramid, where each
+/// texel holds the farthest depth of the texels it covers. A compute pass then compares the
+/// nearest depth of the bounding box of each entity with the pyramid level at which the box
+/// covers at most 2x2 texels. Entities that are entirely behind that depth are marked
+/// [Occluded] and left out of the [VisibleEntities](crate::camera::VisibleEntities) of every
+/// camera.
+///
+/// The result is read back from the gpu without blocking while the next frames render, and
+/// [Occluded] is updated at the start of the first frame that finds the readback complete,
+/// usually two frames later. Visibility therefore lags at least two frames behind the depth it
+/// was tested against: entities that come into view are drawn two frames late. Must be added after
+/// [RenderPlugin](crate::RenderPlugin), with the 3d camera and main depth texture of the base
+/// render graph enabled.
+#[derive(Debug, Default)]
+pub struct OcclusionCullingPlugin;
+
+impl Plugin for OcclusionCullingPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.register_type::<OcclusionCullable>()
+            .register_type::<Occluded>()
+            .init_resource::<OcclusionCulling>()
+            .add_system_to_stage(CoreStage::PreUpdate, occluded_system.system());
+
+        let world = app.world_mut().cell();
+        let mut shaders = world.get_resource_mut::<Assets<Shader>>().unwrap();
+        let mut compute_pipelines = world
+            .get_resource_mut::<Assets<ComputePipelineDescriptor>>()
+            .unwrap();
+        compute_pipelines.set_untracked(
+            HI_Z_PIPELINE_HANDLE,
+            ComputePipelineDescriptor::new(shaders.add(Shader::from_glsl(
+                ShaderStage::Compute,
+                include_str!("hi_z.comp"),
+            )))
+            .with_depth_texture(binding::DEPTH_TEXTURE),
+        );
+        compute_pipelines.set_untracked(
+            OCCLUSION_CULLING_PIPELINE_HANDLE,
+            ComputePipelineDescriptor::new(shaders.add(Shader::from_glsl(
+                ShaderStage::Compute,
+                include_str!("occlusion_cull.comp"),
+            ))),
+        );
+
+        let mut graph = world.get_resource_mut::<RenderGraph>().unwrap();
+        graph.add_node(node::OCCLUSION_CULLING, OcclusionCullingNode::default());
+        graph
+            .add_slot_edge(
+                base::node::MAIN_DEPTH_TEXTURE,
+                WindowTextureNode::OUT_TEXTURE,
+                node::OCCLUSION_CULLING,
+                OcclusionCullingNode::IN_DEPTH,
+            )
+            .unwrap();
+        graph
+            .add_node_edge(base::node::MAIN_PASS, node::OCCLUSION_CULLING)
+            .unwrap();
+    }
+}
+
+/// Marks an entity with a [Mesh](crate::mesh::Mesh) as tested by [OcclusionCullingPlugin]. The
+/// bounding box of its mesh is tested, so it should only be added to entities that are drawn
+/// within it.
+#[derive(Debug, Clone, Default, Reflect)]
+#[reflect(Component)]
+pub struct OcclusionCullable;
+
+/// Inserted on [OcclusionCullable] entities that were hidden behind other geometry the last time
+/// they were tested, which is against the depth of the frame two frames before the current one
+#[derive(Debug, Clone, Default, Reflect)]
+#[reflect(Component)]
+pub struct Occluded;
+
+/// The result of the last occlusion culling pass read back from the gpu. It is updated while the
+/// render graph runs, once the readback of the pass completes.
+#[derive(Debug, Default)]
+pub struct OcclusionCulling {
+    occluded: HashSet<Entity>,
+}
+
+impl OcclusionCulling {
+    pub fn is_occluded(&self, entity: Entity) -> bool {
+        self.occluded.contains(&entity)
+    }
+}
+
+/// Inserts or removes the [Occluded] marker of [OcclusionCullable] entities
+pub fn occluded_system(
+    mut commands: Commands,
+    occlusion_culling: Res<OcclusionCulling>,
+    query: Query<(Entity, Option<&Occluded>), With<OcclusionCullable>>,
+) {
+    for (entity, occluded) in query.iter() {
+        match (occlusion_culling.is_occluded(entity), occluded.is_some()) {
+            (true, false) => {
+                commands.entity(entity).insert(Occluded);
+            }
+            (false, true) => {
+                commands.entity(entity).remove::<Occluded>();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// A level of the Hi-Z pyramid, stored in a buffer after the levels before it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HiZLevel {
+    /// The index of the first texel of the level in the buffer
+    pub offset: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl HiZLevel {
+    pub fn texel_count(&self) -> u32 {
+        self.width * self.height
+    }
+}
+
+/// The levels of the Hi-Z pyramid of a `width` by `height` depth texture. The first level is half
+/// the size of the depth texture, rounded up, and each following level halves the previous one
+/// until a single texel is left.
+pub fn hi_z_levels(width: u32, height: u32) -> Vec<HiZLevel> {
+    let mut levels = Vec::new();
+    let (mut width, mut height, mut offset) = (width.max(1), height.max(1), 0);
+    while levels.len() < MAX_HI_Z_LEVELS {
+        width = width / 2 + width % 2;
+        height = height / 2 + height % 2;
+        let level = HiZLevel {
+            offset,
+            width,
+            height,
+        };
+        offset += level.texel_count();
+        levels.push(level);
+        if width == 1 && height == 1 {
+            break;
+        }
+    }
+    levels
+}
+
+/// The world space bounding box of a local space `aabb` moved by `global_transform`
+pub fn transform_aabb(aabb: &Aabb, global_transform: &GlobalTransform) -> Aabb {
+    aabb.transformed(&global_transform.compute_matrix())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_math::{Quat, Vec3};
+
+    #[test]
+    fn halves_levels_down_to_one_texel() {
+        let level = |offset, width, height| HiZLevel {
+            offset,
+            width,
+            height,
+        };
+        assert_eq!(
+            hi_z_levels(10, 3),
+            vec![
+                level(0, 5, 2),
+                level(10, 3, 1),
+                level(13, 2, 1),
+                level(15, 1, 1)
+            ]
+        );
+        assert_eq!(hi_z_levels(1, 1), vec![level(0, 1, 1)]);
+        assert_eq!(hi_z_levels(u32::MAX, 1).len(), MAX_HI_Z_LEVELS);
+    }
+
+    #[test]
+    fn transforms_aabbs() {
+        let aabb = Aabb {
+            min: Vec3::new(-1.0, 0.0, -2.0),
+            max: Vec3::new(1.0, 1.0, 2.0),
+        };
+        let global_transform = GlobalTransform {
+            translation: Vec3::new(0.0, 5.0, 0.0),
+            rotation: Quat::from_rotation_y(std::f32::consts::FRAC_PI_2),
+            scale: Vec3::splat(2.0),
+        };
+        let transformed = transform_aabb(&aabb, &global_transform);
+        assert!(transformed
+            .min
+            .abs_diff_eq(Vec3::new(-4.0, 5.0, -2.0), 1e-5));
+        assert!(transformed.max.abs_diff_eq(Vec3::new(4.0, 7.0, 2.0), 1e-5));
+    }
+}
@@ -0,0 +1,831 @@
+use super::{
+    binding, hi_z_levels, transform_aabb, HiZLevel, OcclusionCullable, OcclusionCulling,
+    DEPTH_MULTISAMPLED_SHADER_DEF, HI_Z_FROM_DEPTH_SHADER_DEF, HI_Z_PIPELINE_HANDLE,
+    MAX_HI_Z_LEVELS, OCCLUSION_CULLING_PIPELINE_HANDLE,
+};
+use crate::{
+    camera::{ActiveCameras, Camera},
+    draw::{OutsideFrustum, Visible},
+    mesh::{Mesh, VertexAttributeValues},
+    pipeline::{
+        BindGroupDescriptorId, ComputePipelineDescriptor, PipelineCompiler, ShaderSpecialization,
+    },
+    prelude::{Depth, Msaa},
+    render_graph::{base::camera, CommandQueue, Node, ResourceSlotInfo, ResourceSlots},
+    renderer::{
+        BindGroupId, BufferId, BufferInfo, BufferMapMode, BufferMapState, BufferUsage,
+        FramesInFlight, PerFrame, RenderContext, RenderResourceBinding, RenderResourceBindings,
+        RenderResourceContext, RenderResourceType, SamplerId,
+    },
+    shader::Shader,
+    texture::SamplerDescriptor,
+};
+use bevy_app::{Events, ManualEventReader};
+use bevy_asset::{AssetEvent, Assets, Handle};
+use bevy_core::{AsBytes, Byteable};
+use bevy_ecs::{
+    entity::Entity,
+    query::{With, Without},
+    world::{Mut, World},
+};
+use bevy_math::{Aabb, Mat4, Vec3};
+use bevy_transform::prelude::GlobalTransform;
+use bevy_utils::{HashMap, HashSet};
+use bevy_window::Windows;
+use std::{borrow::Cow, cell::RefCell};
+
+/// The width and height of the Hi-Z workgroups
+const HI_Z_WORKGROUP_SIZE: u32 = 8;
+
+/// The number of entities tested by each culling workgroup
+const CULLING_WORKGROUP_SIZE: u32 = 64;
+
+/// The size of an entity's bounding box in the bounding box buffer, its min and max corners
+const AABB_SIZE: usize = std::mem::size_of::<[[f32; 4]; 2]>();
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+struct HiZLevelUniform {
+    source: [u32; 4],
+    destination: [u32; 4],
+}
+
+unsafe impl Byteable for HiZLevelUniform {}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+struct OcclusionCameraUniform {
+    view_proj: [[f32; 4]; 4],
+    counts: [u32; 4],
+    levels: [[u32; 4]; MAX_HI_Z_LEVELS],
+}
+
+unsafe impl Byteable for OcclusionCameraUniform {}
+
+/// The Hi-Z pyramid of a depth texture
+#[derive(Debug)]
+struct HiZ {
+    width: u32,
+    height: u32,
+    sample_count: u32,
+    buffer: BufferId,
+    sampler: SamplerId,
+    levels: Vec<HiZLevel>,
+    /// The bindings of the dispatch that builds each level
+    level_bindings: Vec<RenderResourceBindings>,
+    level_buffers: Vec<BufferId>,
+}
+
+impl HiZ {
+    fn new(
+        width: u32,
+        height: u32,
+        sample_count: u32,
+        render_resource_context: &dyn RenderResourceContext,
+    ) -> Self {
+        let levels = hi_z_levels(width, height);
+        let texel_count = levels.iter().map(|level| level.texel_count()).sum::<u32>();
+        let buffer_size = texel_count as usize * std::mem::size_of::<f32>();
+        let buffer = render_resource_context.create_buffer(BufferInfo {
+            size: buffer_size,
+            buffer_usage: BufferUsage::STORAGE,
+            ..Default::default()
+        });
+        let sampler = render_resource_context.create_sampler(&SamplerDescriptor::default());
+
+        let mut level_bindings = Vec::with_capacity(levels.len());
+        let mut level_buffers = Vec::with_capacity(levels.len());
+        for (i, level) in levels.iter().enumerate() {
+            let source = match i {
+                0 => [0, width, height, 0],
+                _ => {
+                    let source = levels[i - 1];
+                    [source.offset, source.width, source.height, 0]
+                }
+            };
+            let uniform = HiZLevelUniform {
+                source,
+                destination: [level.offset, level.width, level.height, 0],
+            };
+            let level_buffer = render_resource_context.create_buffer_with_data(
+                BufferInfo {
+                    size: std::mem::size_of::<HiZLevelUniform>(),
+                    buffer_usage: BufferUsage::UNIFORM,
+                    ..Default::default()
+                },
+                uniform.as_bytes(),
+            );
+
+            let mut bindings = RenderResourceBindings::default();
+            bindings.set(
+                binding::HI_Z,
+                RenderResourceBinding::Buffer {
+                    buffer,
+                    range: 0..buffer_size as u64,
+                    dynamic_index: None,
+                },
+            );
+            bindings.set(
+                binding::HI_Z_LEVEL,
+                RenderResourceBinding::Buffer {
+                    buffer: level_buffer,
+                    range: 0..std::mem::size_of::<HiZLevelUniform>() as u64,
+                    dynamic_index: None,
+                },
+            );
+            if i == 0 {
+                bindings.set(
+                    binding::DEPTH_TEXTURE_SAMPLER,
+                    RenderResourceBinding::Sampler(sampler),
+                );
+            }
+            level_bindings.push(bindings);
+            level_buffers.push(level_buffer);
+        }
+
+        HiZ {
+            width,
+            height,
+            sample_count,
+            buffer,
+            sampler,
+            levels,
+            level_bindings,
+            level_buffers,
+        }
+    }
+
+    fn size(&self) -> u64 {
+        let texel_count = self
+            .levels
+            .iter()
+            .map(|level| level.texel_count())
+            .sum::<u32>();
+        texel_count as u64 * std::mem::size_of::<f32>() as u64
+    }
+
+    fn remove_buffers(&self, render_resource_context: &dyn RenderResourceContext) {
+        render_resource_context.remove_buffer(self.buffer);
+        render_resource_context.remove_sampler(self.sampler);
+        for buffer in self.level_buffers.iter() {
+            render_resource_context.remove_buffer(*buffer);
+        }
+    }
+}
+
+/// The buffers holding the bounding boxes and visibility of the tested entities
+#[derive(Debug)]
+struct EntityBuffers {
+    capacity: usize,
+    aabb_buffer: BufferId,
+    visibility_buffer: BufferId,
+    camera_buffer: BufferId,
+}
+
+impl EntityBuffers {
+    fn new(capacity: usize, render_resource_context: &dyn RenderResourceContext) -> Self {
+        let camera_size = std::mem::size_of::<OcclusionCameraUniform>();
+        EntityBuffers {
+            capacity,
+            aabb_buffer: render_resource_context.create_buffer(BufferInfo {
+                size: capacity * AABB_SIZE,
+                buffer_usage: BufferUsage::STORAGE | BufferUsage::COPY_DST,
+                ..Default::default()
+            }),
+            visibility_buffer: render_resource_context.create_buffer(BufferInfo {
+                size: capacity * std::mem::size_of::<u32>(),
+                buffer_usage: BufferUsage::STORAGE | BufferUsage::COPY_SRC,
+                ..Default::default()
+            }),
+            camera_buffer: render_resource_context.create_buffer(BufferInfo {
+                size: camera_size,
+                buffer_usage: BufferUsage::UNIFORM | BufferUsage::COPY_DST,
+                ..Default::default()
+            }),
+        }
+    }
+
+    fn set_bindings(&self, bindings: &mut RenderResourceBindings) {
+        let buffers = [
+            (binding::OCCLUSION_AABBS, self.aabb_buffer, AABB_SIZE),
+            (
+                binding::OCCLUSION_VISIBILITY,
+                self.visibility_buffer,
+                std::mem::size_of::<u32>(),
+            ),
+        ];
+        for (name, buffer, element_size) in buffers.iter() {
+            bindings.set(
+                name,
+                RenderResourceBinding::Buffer {
+                    buffer: *buffer,
+                    range: 0..(self.capacity * element_size) as u64,
+                    dynamic_index: None,
+                },
+            );
+        }
+        bindings.set(
+            binding::OCCLUSION_CAMERA,
+            RenderResourceBinding::Buffer {
+                buffer: self.camera_buffer,
+                range: 0..std::mem::size_of::<OcclusionCameraUniform>() as u64,
+                dynamic_index: None,
+            },
+        );
+    }
+
+    fn remove_buffers(&self, render_resource_context: &dyn RenderResourceContext) {
+        for buffer in [self.aabb_buffer, self.visibility_buffer, self.camera_buffer].iter() {
+            render_resource_context.remove_buffer(*buffer);
+        }
+    }
+}
+
+/// The staging buffer of one frame in flight, holding the camera uniform followed by the bounding
+/// boxes, so the CPU never writes to a buffer the GPU may still copy from
+#[derive(Debug, Default)]
+struct StagingBuffer {
+    buffer: Option<BufferId>,
+    capacity: usize,
+}
+
+/// The readback buffer of one frame in flight, which receives the visibility of the entities
+/// tested in that frame
+#[derive(Debug, Default)]
+struct Readback {
+    buffer: Option<BufferId>,
+    capacity: usize,
+    /// The frame and entities whose visibility the buffer receives, until it is read
+    pending: Option<(u64, Vec<Entity>)>,
+}
+
+#[derive(Debug)]
+struct Pipelines {
+    hi_z_from_depth: Handle<ComputePipelineDescriptor>,
+    hi_z: Handle<ComputePipelineDescriptor>,
+    culling: Handle<ComputePipelineDescriptor>,
+}
+
+#[derive(Debug)]
+struct Dispatch {
+    pipeline: Handle<ComputePipelineDescriptor>,
+    workgroups: (u32, u32),
+    bind_groups: Vec<(u32, BindGroupDescriptorId, BindGroupId)>,
+}
+
+/// A Render Graph [Node] that builds the Hi-Z pyramid of the main depth texture and tests the
+/// bounding boxes of [OcclusionCullable] entities against it in a compute pass. The visibility
+/// of the entities is mapped without blocking once the frame is submitted, and stored in the
+/// [OcclusionCulling] resource in the first frame that finds the mapping complete.
+#[derive(Default)]
+pub struct OcclusionCullingNode {
+    command_queue: CommandQueue,
+    pipelines: Option<Pipelines>,
+    hi_z: Option<HiZ>,
+    entity_buffers: Option<EntityBuffers>,
+    culling_bindings: RenderResourceBindings,
+    mesh_aabbs: HashMap<Handle<Mesh>, Option<Aabb>>,
+    mesh_event_reader: ManualEventReader<AssetEvent<Mesh>>,
+    staging_buffers: PerFrame<StagingBuffer>,
+    readbacks: PerFrame<Readback>,
+    /// The entities tested this frame
+    entities: Vec<Entity>,
+    /// The number of prepared frames
+    frame: u64,
+    /// The frame of the visibility in the [OcclusionCulling] resource
+    read_frame: u64,
+}
+
+impl OcclusionCullingNode {
+    pub const IN_DEPTH: &'static str = "depth";
+
+    fn compile_pipeline(
+        world: &mut World,
+        pipeline: Handle<ComputePipelineDescriptor>,
+        shader_defs: &[&str],
+    ) -> Handle<ComputePipelineDescriptor> {
+        let world = world.cell();
+        let render_resource_context = world
+            .get_resource::<Box<dyn RenderResourceContext>>()
+            .unwrap();
+        let mut pipeline_compiler = world.get_resource_mut::<PipelineCompiler>().unwrap();
+        let mut pipelines = world
+            .get_resource_mut::<Assets<ComputePipelineDescriptor>>()
+            .unwrap();
+        let mut shaders = world.get_resource_mut::<Assets<Shader>>().unwrap();
+        let specialization = ShaderSpecialization {
+            shader_defs: shader_defs.iter().map(|def| def.to_string()).collect(),
+        };
+        pipeline_compiler
+            .get_specialized_compute_pipeline(&pipeline, &specialization)
+            .unwrap_or_else(|| {
+                pipeline_compiler.compile_compute_pipeline(
+                    &**render_resource_context,
+                    &mut pipelines,
+                    &mut shaders,
+                    &pipeline,
+                    &specialization,
+                )
+            })
+    }
+
+    /// Stores the visibility of the newest frame whose readback completed, and starts mapping the
+    /// readbacks of the frames submitted since the last call
+    fn read_back(&mut self, world: &mut World) {
+        let render_resource_context = world
+            .get_resource::<Box<dyn RenderResourceContext>>()
+            .unwrap();
+        let mut newest = None;
+        let mut keep_newest = |frame: u64, occluded: HashSet<Entity>| {
+            if newest
+                .as_ref()
+                .map_or(true, |(newest_frame, _)| frame > *newest_frame)
+            {
+                newest = Some((frame, occluded));
+            }
+        };
+        for readback in self.readbacks.iter_mut() {
+            let (frame, entities) = match &readback.pending {
+                Some(pending) => pending,
+                None => continue,
+            };
+            let buffer = match readback.buffer {
+                Some(buffer) if !entities.is_empty() => buffer,
+                // nothing was tested that frame, so nothing is occluded
+                _ => {
+                    keep_newest(*frame, HashSet::default());
+                    readback.pending = None;
+                    continue;
+                }
+            };
+            match render_resource_context.get_buffer_map_state(buffer) {
+                BufferMapState::Unmapped => {
+                    render_resource_context.map_buffer_async(buffer, BufferMapMode::Read);
+                    continue;
+                }
+                BufferMapState::Pending => continue,
+                BufferMapState::Mapped => {
+                    let occluded = RefCell::new(HashSet::default());
+                    let size = (entities.len() * std::mem::size_of::<u32>()) as u64;
+                    render_resource_context.read_mapped_buffer(buffer, 0..size, &|data, _| {
+                        let mut occluded = occluded.borrow_mut();
+                        for (entity, visibility) in entities.iter().zip(data.chunks_exact(4)) {
+                            let visibility =
+                                [visibility[0], visibility[1], visibility[2], visibility[3]];
+                            if u32::from_le_bytes(visibility) == 0 {
+                                occluded.insert(*entity);
+                            }
+                        }
+                    });
+                    render_resource_context.unmap_buffer(buffer);
+                    keep_newest(*frame, occluded.into_inner());
+                }
+                BufferMapState::Failed => render_resource_context.unmap_buffer(buffer),
+            }
+            readback.pending = None;
+        }
+        if let Some((frame, occluded)) = newest {
+            if frame > self.read_frame {
+                self.read_frame = frame;
+                world
+                    .get_resource_mut::<OcclusionCulling>()
+                    .unwrap()
+                    .occluded = occluded;
+            }
+        }
+    }
+
+    /// Returns the view projection matrix of the 3d camera and the size and sample count of the
+    /// main depth texture
+    fn camera(world: &World) -> Option<(Mat4, u32, u32, u32)> {
+        let active_cameras = world.get_resource::<ActiveCameras>().unwrap();
+        let entity = active_cameras.get(camera::CAMERA_3D)?.entity?;
+        let camera = world.get::<Camera>(entity)?;
+        let global_transform = world.get::<GlobalTransform>(entity)?;
+        let window = world
+            .get_resource::<Windows>()
+            .unwrap()
+            .get(camera.window)?;
+        let sample_count = world.get_resource::<Msaa>().unwrap().samples;
+        let view_proj = camera.view_projection(global_transform);
+        Some((
+            view_proj,
+            window.physical_width(),
+            window.physical_height(),
+            sample_count,
+        ))
+    }
+
+    fn update_mesh_aabbs(&mut self, world: &World) {
+        let mesh_events = world.get_resource::<Events<AssetEvent<Mesh>>>().unwrap();
+        for event in self.mesh_event_reader.iter(&mesh_events) {
+            match event {
+                AssetEvent::Modified { handle } | AssetEvent::Removed { handle } => {
+                    self.mesh_aabbs.remove(handle);
+                }
+                AssetEvent::Created { .. } => {}
+            }
+        }
+    }
+}
+
+fn mesh_aabb(mesh: &Mesh) -> Option<Aabb> {
+    match mesh.attribute(Mesh::ATTRIBUTE_POSITION)? {
+        VertexAttributeValues::Float3(positions) if !positions.is_empty() => Some(
+            Aabb::from_points(positions.iter().map(|position| Vec3::from(*position))),
+        ),
+        _ => None,
+    }
+}
+
+fn bind_groups(
+    pipelines: &Assets<ComputePipelineDescriptor>,
+    pipeline: &Handle<ComputePipelineDescriptor>,
+    bindings: &mut RenderResourceBindings,
+    render_resource_context: &dyn RenderResourceContext,
+) -> Option<Vec<(u32, BindGroupDescriptorId, BindGroupId)>> {
+    let layout = pipelines.get(pipeline)?.get_layout()?;
+    layout
+        .bind_groups
+        .iter()
+        .map(|bind_group_descriptor| {
+            bindings
+                .update_bind_group(bind_group_descriptor, render_resource_context)
+                .map(|bind_group| {
+                    (
+                        bind_group_descriptor.index,
+                        bind_group_descriptor.id,
+                        bind_group.id,
+                    )
+                })
+        })
+        .collect()
+}
+
+impl Node for OcclusionCullingNode {
+    fn input(&self) -> &[ResourceSlotInfo] {
+        static INPUT: &[ResourceSlotInfo] = &[ResourceSlotInfo {
+            name: Cow::Borrowed(OcclusionCullingNode::IN_DEPTH),
+            resource_type: RenderResourceType::Texture,
+        }];
+        INPUT
+    }
+
+    fn prepare(&mut self, world: &mut World) {
+        self.frame += 1;
+        self.read_back(world);
+        self.entities.clear();
+
+        let (view_proj, width, height, sample_count) = match Self::camera(world) {
+            Some(camera) if camera.1 > 0 && camera.2 > 0 => camera,
+            _ => {
+                // nothing is tested without a camera, and readbacks still in flight are outdated
+                self.pipelines = None;
+                self.read_frame = self.frame;
+                world
+                    .get_resource_mut::<OcclusionCulling>()
+                    .unwrap()
+                    .occluded
+                    .clear();
+                return;
+            }
+        };
+        let mut hi_z_defs = vec![HI_Z_FROM_DEPTH_SHADER_DEF];
+        if sample_count > 1 {
+            hi_z_defs.push(DEPTH_MULTISAMPLED_SHADER_DEF);
+        }
+        let mut culling_defs = Vec::new();
+        if world.get_resource::<Depth>().unwrap().reversed_z {
+            hi_z_defs.push(Depth::REVERSED_Z_SHADER_DEF);
+            culling_defs.push(Depth::REVERSED_Z_SHADER_DEF);
+        }
+        self.pipelines = Some(Pipelines {
+            hi_z_from_depth: Self::compile_pipeline(
+                world,
+                HI_Z_PIPELINE_HANDLE.typed(),
+                &hi_z_defs,
+            ),
+            hi_z: Self::compile_pipeline(world, HI_Z_PIPELINE_HANDLE.typed(), &[]),
+            culling: Self::compile_pipeline(
+                world,
+                OCCLUSION_CULLING_PIPELINE_HANDLE.typed(),
+                &culling_defs,
+            ),
+        });
+        self.update_mesh_aabbs(world);
+
+        let mut query = world.query_filtered::<
+            (Entity, &Handle<Mesh>, &GlobalTransform, &Visible),
+            (With<OcclusionCullable>, Without<OutsideFrustum>),
+        >();
+        let OcclusionCullingNode {
+            command_queue,
+            hi_z,
+            entity_buffers,
+            culling_bindings,
+            mesh_aabbs,
+            staging_buffers,
+            entities,
+            ..
+        } = self;
+        world.resource_scope(
+            |world, render_resource_context: Mut<Box<dyn RenderResourceContext>>| {
+                let render_resource_context = &**render_resource_context;
+                let hi_z_matches = hi_z.as_ref().map_or(false, |hi_z| {
+                    (hi_z.width, hi_z.height, hi_z.sample_count) == (width, height, sample_count)
+                });
+                if !hi_z_matches {
+                    if let Some(hi_z) = hi_z.take() {
+                        hi_z.remove_buffers(render_resource_context);
+                    }
+                    let new_hi_z = HiZ::new(width, height, sample_count, render_resource_context);
+                    culling_bindings.set(
+                        binding::HI_Z,
+                        RenderResourceBinding::Buffer {
+                            buffer: new_hi_z.buffer,
+                            range: 0..new_hi_z.size(),
+                            dynamic_index: None,
+                        },
+                    );
+                    *hi_z = Some(new_hi_z);
+                }
+
+                let meshes = world.get_resource::<Assets<Mesh>>().unwrap();
+                let mut aabbs = Vec::<[[f32; 4]; 2]>::new();
+                for (entity, mesh_handle, global_transform, visible) in query.iter(world) {
+                    if !visible.is_visible {
+                        continue;
+                    }
+                    let aabb = match mesh_aabbs.get(mesh_handle) {
+                        Some(aabb) => *aabb,
+                        // don't test the entity until its mesh is loaded
+                        None => match meshes.get(mesh_handle) {
+                            Some(mesh) => *mesh_aabbs
+                                .entry(mesh_handle.clone_weak())
+                                .or_insert(mesh_aabb(mesh)),
+                            None => continue,
+                        },
+                    };
+                    if let Some(aabb) = aabb {
+                        let aabb = transform_aabb(&aabb, global_transform);
+                        entities.push(entity);
+                        aabbs.push([aabb.min.extend(1.0).into(), aabb.max.extend(1.0).into()]);
+                    }
+                }
+                if entities.is_empty() {
+                    return;
+                }
+
+                let buffers = match entity_buffers.take() {
+                    Some(buffers) if buffers.capacity >= entities.len() => buffers,
+                    buffers => {
+                        if let Some(buffers) = buffers {
+                            buffers.remove_buffers(render_resource_context);
+                        }
+                        let buffers = EntityBuffers::new(
+                            entities.len().next_power_of_two(),
+                            render_resource_context,
+                        );
+                        buffers.set_bindings(culling_bindings);
+                        buffers
+                    }
+                };
+
+                let levels = &hi_z.as_ref().unwrap().levels;
+                let mut uniform = OcclusionCameraUniform {
+                    view_proj: view_proj.to_cols_array_2d(),
+                    counts: [entities.len() as u32, levels.len() as u32, 0, 0],
+                    levels: [[0; 4]; MAX_HI_Z_LEVELS],
+                };
+                for (uniform_level, level) in uniform.levels.iter_mut().zip(levels.iter()) {
+                    *uniform_level = [level.offset, level.width, level.height, 0];
+                }
+                let camera_size = std::mem::size_of::<OcclusionCameraUniform>();
+                let aabbs_size = aabbs.len() * AABB_SIZE;
+                // the GPU is done with the staging buffer of this frame index, so mapping it
+                // doesn't wait
+                let frames_in_flight = world.get_resource::<FramesInFlight>().unwrap();
+                let staging = staging_buffers.get_mut(frames_in_flight);
+                let staging_buffer = match staging.buffer {
+                    Some(buffer) if staging.capacity >= buffers.capacity => {
+                        render_resource_context.map_buffer(buffer, BufferMapMode::Write);
+                        buffer
+                    }
+                    buffer => {
+                        if let Some(buffer) = buffer {
+                            render_resource_context.remove_buffer(buffer);
+                        }
+                        let buffer = render_resource_context.create_buffer(BufferInfo {
+                            size: camera_size + buffers.capacity * AABB_SIZE,
+                            buffer_usage: BufferUsage::COPY_SRC | BufferUsage::MAP_WRITE,
+                            mapped_at_creation: true,
+                        });
+                        staging.buffer = Some(buffer);
+                        staging.capacity = buffers.capacity;
+                        buffer
+                    }
+                };
+                render_resource_context.write_mapped_buffer(
+                    staging_buffer,
+                    0..(camera_size + aabbs_size) as u64,
+                    &mut |data, _renderer| {
+                        data[0..camera_size].copy_from_slice(uniform.as_bytes());
+                        data[camera_size..].copy_from_slice(aabbs.as_bytes());
+                    },
+                );
+                render_resource_context.unmap_buffer(staging_buffer);
+                command_queue.copy_buffer_to_buffer(
+                    staging_buffer,
+                    0,
+                    buffers.camera_buffer,
+                    0,
+                    camera_size as u64,
+                );
+                command_queue.copy_buffer_to_buffer(
+                    staging_buffer,
+                    camera_size as u64,
+                    buffers.aabb_buffer,
+                    0,
+                    aabbs_size as u64,
+                );
+                *entity_buffers = Some(buffers);
+            },
+        );
+    }
+
+    fn update(
+        &mut self,
+        world: &World,
+        render_context: &mut dyn RenderContext,
+        input: &ResourceSlots,
+        _output: &mut ResourceSlots,
+    ) {
+        const IN_DEPTH: usize = 0;
+        self.command_queue.execute(render_context);
+        let (pipelines, hi_z) = match (&self.pipelines, &mut self.hi_z) {
+            (Some(pipelines), Some(hi_z)) => (pipelines, hi_z),
+            _ => return,
+        };
+        let depth_texture = input.get(IN_DEPTH).unwrap().get_texture().unwrap();
+        hi_z.level_bindings[0].set(
+            binding::DEPTH_TEXTURE,
+            RenderResourceBinding::Texture(depth_texture),
+        );
+
+        let compute_pipelines = world
+            .get_resource::<Assets<ComputePipelineDescriptor>>()
+            .unwrap();
+        let mut dispatches = Vec::with_capacity(hi_z.levels.len() + 1);
+        {
+            let render_resource_context = render_context.resources();
+            for (i, (level, bindings)) in hi_z
+                .levels
+                .iter()
+                .zip(hi_z.level_bindings.iter_mut())
+                .enumerate()
+            {
+                let pipeline = match i {
+                    0 => &pipelines.hi_z_from_depth,
+                    _ => &pipelines.hi_z,
+                };
+                let bind_groups = match bind_groups(
+                    &compute_pipelines,
+                    pipeline,
+                    bindings,
+                    render_resource_context,
+                ) {
+                    Some(bind_groups) => bind_groups,
+                    None => return,
+                };
+                dispatches.push(Dispatch {
+                    pipeline: pipeline.clone_weak(),
+                    workgroups: (
+                        (level.width + HI_Z_WORKGROUP_SIZE - 1) / HI_Z_WORKGROUP_SIZE,
+                        (level.height + HI_Z_WORKGROUP_SIZE - 1) / HI_Z_WORKGROUP_SIZE,
+                    ),
+                    bind_groups,
+                });
+            }
+
+            if !self.entities.is_empty() {
+                if let Some(bind_groups) = bind_groups(
+                    &compute_pipelines,
+                    &pipelines.culling,
+                    &mut self.culling_bindings,
+                    render_resource_context,
+                ) {
+                    let entity_count = self.entities.len() as u32;
+                    dispatches.push(Dispatch {
+                        pipeline: pipelines.culling.clone_weak(),
+                        workgroups: (
+                            (entity_count + CULLING_WORKGROUP_SIZE - 1) / CULLING_WORKGROUP_SIZE,
+                            1,
+                        ),
+                        bind_groups,
+                    });
+                } else {
+                    self.entities.clear();
+                }
+            }
+        }
+
+        render_context.begin_compute_pass(&mut |compute_pass| {
+            for dispatch in dispatches.iter() {
+                compute_pass.set_pipeline(&dispatch.pipeline);
+                for (index, bind_group_descriptor, bind_group) in dispatch.bind_groups.iter() {
+                    compute_pass.set_bind_group(*index, *bind_group_descriptor, *bind_group, None);
+                }
+                compute_pass.dispatch(dispatch.workgroups.0, dispatch.workgroups.1, 1);
+            }
+        });
+
+        let frames_in_flight = world.get_resource::<FramesInFlight>().unwrap();
+        let readback = self.readbacks.get_mut(frames_in_flight);
+        if readback.pending.is_some() {
+            // the readback of this frame index hasn't completed yet, so this frame isn't read back
+            return;
+        }
+        let tested_entities = !self.entities.is_empty();
+        let entity_buffers = self.entity_buffers.as_ref().filter(|_| tested_entities);
+        if let Some(entity_buffers) = entity_buffers {
+            let buffer = match readback.buffer {
+                Some(buffer) if readback.capacity >= entity_buffers.capacity => buffer,
+                buffer => {
+                    let render_resource_context = render_context.resources();
+                    if let Some(buffer) = buffer {
+                        render_resource_context.remove_buffer(buffer);
+                    }
+                    let buffer = render_resource_context.create_buffer(BufferInfo {
+                        size: entity_buffers.capacity * std::mem::size_of::<u32>(),
+                        buffer_usage: BufferUsage::MAP_READ | BufferUsage::COPY_DST,
+                        mapped_at_creation: false,
+                    });
+                    readback.buffer = Some(buffer);
+                    readback.capacity = entity_buffers.capacity;
+                    buffer
+                }
+            };
+            render_context.copy_buffer_to_buffer(
+                entity_buffers.visibility_buffer,
+                0,
+                buffer,
+                0,
+                (self.entities.len() * std::mem::size_of::<u32>()) as u64,
+            );
+        }
+        readback.pending = Some((self.frame, std::mem::take(&mut self.entities)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::HeadlessRenderResourceContext;
+
+    #[test]
+    fn reads_back_the_newest_completed_frame_without_blocking() {
+        let mut world = World::new();
+        let render_resource_context = HeadlessRenderResourceContext::default();
+        let readback = |frame, entities: Vec<Entity>| Readback {
+            buffer: Some(render_resource_context.create_buffer(BufferInfo {
+                size: entities.len() * std::mem::size_of::<u32>(),
+                buffer_usage: BufferUsage::MAP_READ | BufferUsage::COPY_DST,
+                mapped_at_creation: false,
+            })),
+            capacity: entities.len(),
+            pending: Some((frame, entities)),
+        };
+        let old = world.spawn().id();
+        let new = world.spawn().id();
+        let (old_readback, new_readback) = (readback(1, vec![old]), readback(2, vec![new]));
+        world.insert_resource::<Box<dyn RenderResourceContext>>(Box::new(render_resource_context));
+        world.insert_resource(OcclusionCulling::default());
+
+        let mut node = OcclusionCullingNode::default();
+        let mut frames_in_flight = FramesInFlight::new(2);
+        *node.readbacks.get_mut(&frames_in_flight) = new_readback;
+        frames_in_flight.advance();
+        *node.readbacks.get_mut(&frames_in_flight) = old_readback;
+
+        // the first call only starts mapping the buffers
+        node.read_back(&mut world);
+        assert!(world
+            .get_resource::<OcclusionCulling>()
+            .unwrap()
+            .occluded
+            .is_empty());
+        assert!(node
+            .readbacks
+            .iter()
+            .all(|readback| readback.pending.is_some()));
+
+        // the headless context reads zeros, which mark every entity occluded
+        node.read_back(&mut world);
+        let occluded = &world.get_resource::<OcclusionCulling>().unwrap().occluded;
+        assert_eq!(occluded.iter().collect::<Vec<_>>(), vec![&new]);
+        assert!(node
+            .readbacks
+            .iter()
+            .all(|readback| readback.pending.is_none()));
+    }
+}
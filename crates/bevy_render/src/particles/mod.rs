@@ -0,0 +1,276 @@
+use crate::{
+    color::Color,
+    draw::{Draw, DrawContext, Visible},
+    gradient::Gradient,
+    pipeline::{
+        ComputePipelineDescriptor, CullMode, PipelineDescriptor, RenderPipeline, RenderPipelines,
+    },
+    prelude::{Depth, Hdr, Msaa},
+    render_graph::{base, base::MainPass, RenderGraph},
+    renderer::RenderResourceBindings,
+    shader::{Shader, ShaderStage, ShaderStages},
+    RenderStage,
+};
+use bevy_app::prelude::*;
+use bevy_asset::{Assets, HandleUntyped};
+use bevy_core::Byteable;
+use bevy_ecs::{
+    bundle::Bundle,
+    reflect::ReflectComponent,
+    system::{IntoSystem, Query, Res, ResMut},
+};
+use bevy_math::{Curve, Interpolate, Vec3};
+use bevy_reflect::{Reflect, TypeUuid};
+use bevy_transform::prelude::{GlobalTransform, Transform};
+
+mod particle_simulation_node;
+
+pub use particle_simulation_node::*;
+
+pub const PARTICLE_PIPELINE_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(PipelineDescriptor::TYPE_UUID, 0x6f3d92a1c48e07b5);
+pub const PARTICLE_SIMULATION_PIPELINE_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(ComputePipelineDescriptor::TYPE_UUID, 0xd5207b8e3a6c914f);
+
+/// The number of samples of the curves of a [ParticleEmitter] the shaders interpolate between
+pub const PARTICLE_CURVE_SAMPLES: usize = 8;
+
+pub mod node {
+    pub const PARTICLE_SIMULATION: &str = "particle_simulation";
+}
+
+/// The names of the particle render resources
+pub mod binding {
+    pub const PARTICLES: &str = "Particles";
+    pub const PARTICLE_EMITTER: &str = "ParticleEmitter";
+}
+
+/// Simulates the particles of every [ParticleEmitter] in a compute pass, and draws them as
+/// camera facing quads in the main pass. Must be added after [RenderPlugin](crate::RenderPlugin).
+#[derive(Debug, Default)]
+pub struct ParticlePlugin;
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.register_type::<ParticleEmitter>()
+            .register_type::<Curve<f32>>()
+            .add_system_to_stage(RenderStage::Draw, draw_particles_system.system());
+
+        let world = app.world_mut().cell();
+        let mut shaders = world.get_resource_mut::<Assets<Shader>>().unwrap();
+        let mut pipelines = world
+            .get_resource_mut::<Assets<PipelineDescriptor>>()
+            .unwrap();
+        pipelines.set_untracked(
+            PARTICLE_PIPELINE_HANDLE,
+            build_particle_pipeline(&mut shaders),
+        );
+        let mut compute_pipelines = world
+            .get_resource_mut::<Assets<ComputePipelineDescriptor>>()
+            .unwrap();
+        compute_pipelines.set_untracked(
+            PARTICLE_SIMULATION_PIPELINE_HANDLE,
+            ComputePipelineDescriptor::new(shaders.add(Shader::from_glsl(
+                ShaderStage::Compute,
+                include_str!("particles.comp"),
+            ))),
+        );
+
+        let mut graph = world.get_resource_mut::<RenderGraph>().unwrap();
+        graph.add_node(node::PARTICLE_SIMULATION, ParticleSimulationNode::default());
+        graph
+            .add_node_edge(node::PARTICLE_SIMULATION, base::node::MAIN_PASS)
+            .unwrap();
+    }
+}
+
+fn build_particle_pipeline(shaders: &mut Assets<Shader>) -> PipelineDescriptor {
+    let mut descriptor = PipelineDescriptor::default_config(ShaderStages {
+        vertex: shaders.add(Shader::from_glsl(
+            ShaderStage::Vertex,
+            include_str!("particle.vert"),
+        )),
+        fragment: Some(shaders.add(Shader::from_glsl(
+            ShaderStage::Fragment,
+            include_str!("particle.frag"),
+        ))),
+    });
+    // particles are transparent and aren't sorted, so they don't hide each other
+    if let Some(depth_stencil) = descriptor.depth_stencil.as_mut() {
+        depth_stencil.depth_write_enabled = false;
+    }
+    descriptor.primitive.cull_mode = CullMode::None;
+    descriptor
+}
+
+/// [PARTICLE_CURVE_SAMPLES] evenly spaced samples of `curve` over the life of a particle, from
+/// 0.0 to 1.0
+pub fn bake_particle_curve<T: Reflect + Interpolate>(
+    curve: &Curve<T>,
+) -> [T; PARTICLE_CURVE_SAMPLES] {
+    let mut samples = [curve.sample(0.0); PARTICLE_CURVE_SAMPLES];
+    for (i, sample) in samples.iter_mut().enumerate() {
+        *sample = curve.sample(i as f32 / (PARTICLE_CURVE_SAMPLES - 1) as f32);
+    }
+    samples
+}
+
+/// Continuously spawns particles at the position of its entity. Particles live in a gpu buffer
+/// of `capacity` particles and are simulated by a compute shader, so they can't be accessed from
+/// the ECS.
+#[derive(Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct ParticleEmitter {
+    /// The number of particles spawned per second
+    pub rate: f32,
+    /// The maximum number of particles alive at once. Once reached, the oldest particles are
+    /// replaced.
+    pub capacity: u32,
+    /// The number of seconds a particle lives
+    pub lifetime: f32,
+    /// The initial velocity of particles, in world space
+    pub velocity: Vec3,
+    /// The radius of the sphere of random velocities added to the initial velocity
+    pub velocity_spread: f32,
+    /// The acceleration of particles, in world space
+    pub acceleration: Vec3,
+    /// The width and height of a particle quad
+    pub size: f32,
+    /// Scales the velocity of particles over their life, from 0.0 to 1.0
+    pub speed_over_life: Curve<f32>,
+    /// The color of particles over their life, from 0.0 to 1.0
+    pub color_over_life: Gradient,
+}
+
+impl Default for ParticleEmitter {
+    fn default() -> Self {
+        ParticleEmitter {
+            rate: 100.0,
+            capacity: 1024,
+            lifetime: 2.0,
+            velocity: Vec3::new(0.0, 2.0, 0.0),
+            velocity_spread: 0.5,
+            acceleration: Vec3::new(0.0, -1.0, 0.0),
+            size: 0.1,
+            speed_over_life: Curve::constant(1.0),
+            color_over_life: Gradient::from_to(Color::WHITE, Color::WHITE.with_alpha(0.0)),
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ParticleEmitterUniform {
+    pub position: [f32; 4],
+    pub velocity: [f32; 4],
+    pub acceleration: [f32; 4],
+    pub spawn: [u32; 4],
+    pub time: [f32; 4],
+    pub speed_over_life: [f32; PARTICLE_CURVE_SAMPLES],
+    pub color_over_life: [[f32; 4]; PARTICLE_CURVE_SAMPLES],
+}
+
+unsafe impl Byteable for ParticleEmitterUniform {}
+
+/// A component bundle for particle emitter entities
+#[derive(Bundle)]
+pub struct ParticleEmitterBundle {
+    pub emitter: ParticleEmitter,
+    pub draw: Draw,
+    pub visible: Visible,
+    pub render_pipelines: RenderPipelines,
+    pub main_pass: MainPass,
+    pub transform: Transform,
+    pub global_transform: GlobalTransform,
+}
+
+impl Default for ParticleEmitterBundle {
+    fn default() -> Self {
+        ParticleEmitterBundle {
+            emitter: Default::default(),
+            draw: Default::default(),
+            visible: Visible {
+                is_transparent: true,
+                ..Default::default()
+            },
+            render_pipelines: RenderPipelines::from_pipelines(vec![RenderPipeline::new(
+                PARTICLE_PIPELINE_HANDLE.typed(),
+            )]),
+            main_pass: MainPass,
+            transform: Default::default(),
+            global_transform: Default::default(),
+        }
+    }
+}
+
+pub fn draw_particles_system(
+    mut draw_context: DrawContext,
+    mut render_resource_bindings: ResMut<RenderResourceBindings>,
+    msaa: Res<Msaa>,
+    hdr: Res<Hdr>,
+    depth: Res<Depth>,
+    mut query: Query<(
+        &mut Draw,
+        &mut RenderPipelines,
+        &ParticleEmitter,
+        &Visible,
+        Option<&MainPass>,
+    )>,
+) {
+    for (mut draw, mut render_pipelines, emitter, visible, main_pass) in query.iter_mut() {
+        // the particle buffer is created by the simulation node the first time it sees the
+        // emitter
+        if !visible.is_visible || render_pipelines.bindings.get(binding::PARTICLES).is_none() {
+            continue;
+        }
+
+        let render_pipelines = &mut *render_pipelines;
+        for render_pipeline in render_pipelines.pipelines.iter_mut() {
+            render_pipeline.specialization.sample_count = msaa.samples;
+            depth.specialize(&mut render_pipeline.specialization);
+            render_pipeline.specialization.color_target_format =
+                main_pass.and(hdr.main_pass_format());
+        }
+
+        for render_pipeline in render_pipelines.pipelines.iter() {
+            // don't render if the pipeline's shaders aren't loaded yet
+            if draw_context
+                .set_pipeline(
+                    &mut draw,
+                    &render_pipeline.pipeline,
+                    &render_pipeline.specialization,
+                )
+                .is_err()
+            {
+                continue;
+            }
+            draw_context
+                .set_bind_groups_from_bindings(
+                    &mut draw,
+                    &mut [
+                        &mut render_pipelines.bindings,
+                        &mut render_resource_bindings,
+                    ],
+                )
+                .unwrap();
+            draw.draw(0..6, 0..emitter.capacity);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bakes_curves() {
+        let curve = Curve::linear(vec![(0.0, 0.0), (1.0, 7.0)]);
+        let baked = bake_particle_curve::<f32>(&curve);
+        assert_eq!(baked[0], 0.0);
+        assert!((baked[3] - 3.0).abs() < 1e-5);
+        assert_eq!(baked[PARTICLE_CURVE_SAMPLES - 1], 7.0);
+
+        let baked = bake_particle_curve(&Curve::constant(3.0f32));
+        assert_eq!(baked, [3.0; PARTICLE_CURVE_SAMPLES]);
+    }
+}
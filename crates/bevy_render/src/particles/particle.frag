@@ -0,0 +1,12 @@
+#version 450
+
+layout(location = 0) in vec2 v_Uv;
+layout(location = 1) in vec4 v_Color;
+
+layout(location = 0) out vec4 o_Target;
+
+void main() {
+    // round particles with a soft edge
+    float alpha = 1.0 - smoothstep(0.3, 0.5, length(v_Uv - 0.5));
+    o_Target = vec4(v_Color.rgb, v_Color.a * alpha);
+}
@@ -0,0 +1,56 @@
+#version 450
+
+struct Particle {
+    // xyz: position, w: age
+    vec4 Position;
+    // xyz: velocity, w: lifetime
+    vec4 Velocity;
+};
+
+layout(location = 0) out vec2 v_Uv;
+layout(location = 1) out vec4 v_Color;
+
+layout(set = 0, binding = 0) uniform CameraViewProj {
+    mat4 ViewProj;
+};
+layout(set = 0, binding = 1) uniform CameraView {
+    mat4 View;
+};
+
+layout(std430, set = 1, binding = 0) readonly buffer Particles {
+    Particle particles[];
+};
+
+layout(std140, set = 1, binding = 1) uniform ParticleEmitter {
+    vec4 EmitterPosition;
+    vec4 EmitterVelocity;
+    vec4 EmitterAcceleration;
+    uvec4 EmitterSpawn;
+    vec4 EmitterTime;
+    vec4 SpeedOverLife[2];
+    vec4 ColorOverLife[8];
+};
+
+const vec2 CORNERS[6] = vec2[6](
+    vec2(-0.5, -0.5), vec2(0.5, -0.5), vec2(0.5, 0.5),
+    vec2(-0.5, -0.5), vec2(0.5, 0.5), vec2(-0.5, 0.5)
+);
+
+vec4 sample_color(float t) {
+    float x = clamp(t, 0.0, 1.0) * 7.0;
+    int i = min(int(x), 6);
+    return mix(ColorOverLife[i], ColorOverLife[i + 1], x - float(i));
+}
+
+void main() {
+    Particle particle = particles[gl_InstanceIndex];
+    vec2 corner = CORNERS[gl_VertexIndex];
+    // dead particles collapse to a point
+    float size = particle.Position.w < particle.Velocity.w ? EmitterAcceleration.w : 0.0;
+    // the quad faces the camera, View holds the camera's right and up axes
+    vec3 position = particle.Position.xyz + (View[0].xyz * corner.x + View[1].xyz * corner.y) * size;
+
+    v_Uv = corner + 0.5;
+    v_Color = sample_color(particle.Position.w / max(particle.Velocity.w, 1e-5));
+    gl_Position = ViewProj * vec4(position, 1.0);
+}
@@ -0,0 +1,312 @@
+use super::{
+    bake_particle_curve, binding, ParticleEmitter, ParticleEmitterUniform, PARTICLE_CURVE_SAMPLES,
+    PARTICLE_SIMULATION_PIPELINE_HANDLE,
+};
+use crate::{
+    pipeline::{
+        BindGroupDescriptorId, ComputePipelineDescriptor, PipelineCompiler, RenderPipelines,
+        ShaderSpecialization,
+    },
+    render_graph::{CommandQueue, Node, ResourceSlots},
+    renderer::{
+        BindGroupId, BufferId, BufferInfo, BufferMapMode, BufferUsage, RenderContext,
+        RenderResourceBinding, RenderResourceBindings, RenderResourceContext,
+    },
+    shader::Shader,
+};
+use bevy_asset::{Assets, Handle};
+use bevy_core::{AsBytes, Time};
+use bevy_ecs::{
+    entity::Entity,
+    world::{Mut, World},
+};
+use bevy_transform::prelude::GlobalTransform;
+use bevy_utils::{HashMap, HashSet};
+
+/// The number of particles simulated by each compute shader workgroup
+const WORKGROUP_SIZE: u32 = 64;
+
+/// The size of a particle in the particle buffer, a position and age followed by a velocity and
+/// lifetime
+const PARTICLE_SIZE: usize = 2 * std::mem::size_of::<[f32; 4]>();
+
+#[derive(Debug, Default)]
+struct EmitterState {
+    particle_buffer: Option<BufferId>,
+    emitter_buffer: Option<BufferId>,
+    staging_buffer: Option<BufferId>,
+    capacity: u32,
+    spawn_accumulator: f32,
+    next_particle: u32,
+    seed: u32,
+}
+
+impl EmitterState {
+    /// Recreates the buffers of the emitter if its capacity changed, then queues a write of its
+    /// [ParticleEmitterUniform] for this frame
+    fn update(
+        &mut self,
+        emitter: &ParticleEmitter,
+        global_transform: &GlobalTransform,
+        delta: f32,
+        bindings: &mut RenderResourceBindings,
+        render_resource_context: &dyn RenderResourceContext,
+        command_queue: &mut CommandQueue,
+    ) {
+        let capacity = emitter.capacity.max(1);
+        let uniform_size = std::mem::size_of::<ParticleEmitterUniform>();
+        if self.particle_buffer.is_none() || self.capacity != capacity {
+            self.remove_buffers(render_resource_context);
+            self.capacity = capacity;
+            self.next_particle = 0;
+
+            // zeroed particles have reached their lifetime of 0, so they start out dead
+            let particle_buffer_size = capacity as usize * PARTICLE_SIZE;
+            let particle_buffer = render_resource_context.create_buffer_with_data(
+                BufferInfo {
+                    size: particle_buffer_size,
+                    buffer_usage: BufferUsage::STORAGE,
+                    ..Default::default()
+                },
+                &vec![0; particle_buffer_size],
+            );
+            let emitter_buffer = render_resource_context.create_buffer(BufferInfo {
+                size: uniform_size,
+                buffer_usage: BufferUsage::UNIFORM | BufferUsage::COPY_DST,
+                ..Default::default()
+            });
+            bindings.set(
+                binding::PARTICLES,
+                RenderResourceBinding::Buffer {
+                    buffer: particle_buffer,
+                    range: 0..particle_buffer_size as u64,
+                    dynamic_index: None,
+                },
+            );
+            bindings.set(
+                binding::PARTICLE_EMITTER,
+                RenderResourceBinding::Buffer {
+                    buffer: emitter_buffer,
+                    range: 0..uniform_size as u64,
+                    dynamic_index: None,
+                },
+            );
+            self.particle_buffer = Some(particle_buffer);
+            self.emitter_buffer = Some(emitter_buffer);
+            self.staging_buffer = Some(render_resource_context.create_buffer(BufferInfo {
+                size: uniform_size,
+                buffer_usage: BufferUsage::COPY_SRC | BufferUsage::MAP_WRITE,
+                mapped_at_creation: true,
+            }));
+        } else {
+            render_resource_context.map_buffer(self.staging_buffer.unwrap(), BufferMapMode::Write);
+        }
+
+        self.spawn_accumulator += emitter.rate.max(0.0) * delta;
+        let spawn_count = (self.spawn_accumulator as u32).min(capacity);
+        self.spawn_accumulator -= spawn_count as f32;
+        let first_particle = self.next_particle;
+        self.next_particle = (self.next_particle + spawn_count) % capacity;
+        self.seed = self.seed.wrapping_add(0x9e37_79b9);
+
+        let mut color_over_life = [[0.0; 4]; PARTICLE_CURVE_SAMPLES];
+        for (color, sample) in color_over_life
+            .iter_mut()
+            .zip(bake_particle_curve(emitter.color_over_life.as_curve()).iter())
+        {
+            *color = sample.as_linear_rgba_f32();
+        }
+        let uniform = ParticleEmitterUniform {
+            position: global_transform.translation.extend(emitter.lifetime).into(),
+            velocity: emitter.velocity.extend(emitter.velocity_spread).into(),
+            acceleration: emitter.acceleration.extend(emitter.size).into(),
+            spawn: [first_particle, spawn_count, capacity, self.seed],
+            time: [delta, 0.0, 0.0, 0.0],
+            speed_over_life: bake_particle_curve(&emitter.speed_over_life),
+            color_over_life,
+        };
+
+        let staging_buffer = self.staging_buffer.unwrap();
+        render_resource_context.write_mapped_buffer(
+            staging_buffer,
+            0..uniform_size as u64,
+            &mut |data, _renderer| {
+                data[0..uniform_size].copy_from_slice(uniform.as_bytes());
+            },
+        );
+        render_resource_context.unmap_buffer(staging_buffer);
+        command_queue.copy_buffer_to_buffer(
+            staging_buffer,
+            0,
+            self.emitter_buffer.unwrap(),
+            0,
+            uniform_size as u64,
+        );
+    }
+
+    fn remove_buffers(&mut self, render_resource_context: &dyn RenderResourceContext) {
+        for buffer in [
+            self.particle_buffer.take(),
+            self.emitter_buffer.take(),
+            self.staging_buffer.take(),
+        ]
+        .iter()
+        .flatten()
+        {
+            render_resource_context.remove_buffer(*buffer);
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Dispatch {
+    workgroups: u32,
+    bind_groups: Vec<(u32, BindGroupDescriptorId, BindGroupId)>,
+}
+
+/// A Render Graph [Node] that spawns and moves the particles of every [ParticleEmitter] in a
+/// compute pass
+#[derive(Debug, Default)]
+pub struct ParticleSimulationNode {
+    command_queue: CommandQueue,
+    pipeline: Option<Handle<ComputePipelineDescriptor>>,
+    emitters: HashMap<Entity, EmitterState>,
+    dispatches: Vec<Dispatch>,
+}
+
+impl ParticleSimulationNode {
+    fn compile_pipeline(world: &mut World) -> Handle<ComputePipelineDescriptor> {
+        let world = world.cell();
+        let render_resource_context = world
+            .get_resource::<Box<dyn RenderResourceContext>>()
+            .unwrap();
+        let mut pipeline_compiler = world.get_resource_mut::<PipelineCompiler>().unwrap();
+        let mut pipelines = world
+            .get_resource_mut::<Assets<ComputePipelineDescriptor>>()
+            .unwrap();
+        let mut shaders = world.get_resource_mut::<Assets<Shader>>().unwrap();
+        let pipeline = PARTICLE_SIMULATION_PIPELINE_HANDLE.typed();
+        let specialization = ShaderSpecialization::default();
+        pipeline_compiler
+            .get_specialized_compute_pipeline(&pipeline, &specialization)
+            .unwrap_or_else(|| {
+                pipeline_compiler.compile_compute_pipeline(
+                    &**render_resource_context,
+                    &mut pipelines,
+                    &mut shaders,
+                    &pipeline,
+                    &specialization,
+                )
+            })
+    }
+}
+
+impl Node for ParticleSimulationNode {
+    fn prepare(&mut self, world: &mut World) {
+        self.dispatches.clear();
+        if self.pipeline.is_none() {
+            self.pipeline = Some(Self::compile_pipeline(world));
+        }
+        let layout = match world
+            .get_resource::<Assets<ComputePipelineDescriptor>>()
+            .unwrap()
+            .get(self.pipeline.as_ref().unwrap())
+            .and_then(|pipeline| pipeline.get_layout())
+        {
+            Some(layout) => layout.clone(),
+            None => return,
+        };
+        let delta = world
+            .get_resource::<Time>()
+            .map_or(0.0, |time| time.delta_seconds());
+
+        let ParticleSimulationNode {
+            command_queue,
+            emitters,
+            dispatches,
+            ..
+        } = self;
+        world.resource_scope(
+            |world, render_resource_context: Mut<Box<dyn RenderResourceContext>>| {
+                let render_resource_context = &**render_resource_context;
+                let mut query = world.query::<(
+                    Entity,
+                    &ParticleEmitter,
+                    &GlobalTransform,
+                    &mut RenderPipelines,
+                )>();
+                let mut live_emitters = HashSet::default();
+                for (entity, emitter, global_transform, mut render_pipelines) in
+                    query.iter_mut(world)
+                {
+                    live_emitters.insert(entity);
+                    let bindings = &mut render_pipelines.bindings;
+                    emitters.entry(entity).or_default().update(
+                        emitter,
+                        global_transform,
+                        delta,
+                        bindings,
+                        render_resource_context,
+                        command_queue,
+                    );
+
+                    let bind_groups = layout
+                        .bind_groups
+                        .iter()
+                        .map(|bind_group_descriptor| {
+                            bindings
+                                .update_bind_group(bind_group_descriptor, render_resource_context)
+                                .map(|bind_group| {
+                                    (
+                                        bind_group_descriptor.index,
+                                        bind_group_descriptor.id,
+                                        bind_group.id,
+                                    )
+                                })
+                        })
+                        .collect::<Option<Vec<_>>>();
+                    if let Some(bind_groups) = bind_groups {
+                        dispatches.push(Dispatch {
+                            workgroups: (emitter.capacity.max(1) + WORKGROUP_SIZE - 1)
+                                / WORKGROUP_SIZE,
+                            bind_groups,
+                        });
+                    }
+                }
+
+                emitters.retain(|entity, state| {
+                    let live = live_emitters.contains(entity);
+                    if !live {
+                        state.remove_buffers(render_resource_context);
+                    }
+                    live
+                });
+            },
+        );
+    }
+
+    fn update(
+        &mut self,
+        _world: &World,
+        render_context: &mut dyn RenderContext,
+        _input: &ResourceSlots,
+        _output: &mut ResourceSlots,
+    ) {
+        self.command_queue.execute(render_context);
+        if self.dispatches.is_empty() {
+            return;
+        }
+
+        let pipeline = self.pipeline.as_ref().unwrap();
+        let dispatches = &self.dispatches;
+        render_context.begin_compute_pass(&mut |compute_pass| {
+            compute_pass.set_pipeline(pipeline);
+            for dispatch in dispatches.iter() {
+                for (index, bind_group_descriptor, bind_group) in dispatch.bind_groups.iter() {
+                    compute_pass.set_bind_group(*index, *bind_group_descriptor, *bind_group, None);
+                }
+                compute_pass.dispatch(dispatch.workgroups, 1, 1);
+            }
+        });
+    }
+}
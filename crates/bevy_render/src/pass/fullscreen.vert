@@ -0,0 +1,9 @@
+#version 450
+
+layout(location = 0) out vec2 v_Uv;
+
+void main() {
+    // a single triangle that covers the whole target, with uvs (0, 0), (2, 0) and (0, 2)
+    v_Uv = vec2((gl_VertexIndex << 1) & 2, gl_VertexIndex & 2);
+    gl_Position = vec4(v_Uv * vec2(2.0, -2.0) + vec2(-1.0, 1.0), 0.0, 1.0);
+}
@@ -0,0 +1,237 @@
+use super::{
+    Operations, PassDescriptor, RenderCommands, RenderPassColorAttachmentDescriptor,
+    TextureAttachment,
+};
+use crate::{
+    pipeline::{
+        BlendState, ColorTargetState, ColorWrite, CullMode, PipelineCompiler, PipelineDescriptor,
+        PipelineSpecialization,
+    },
+    renderer::{
+        BufferInfo, BufferUsage, RenderContext, RenderResourceBinding, RenderResourceBindings,
+        RenderResourceContext, SamplerId, TextureId,
+    },
+    shader::{Shader, ShaderStages},
+    texture::{FilterMode, SamplerDescriptor, TextureFormat},
+    Color,
+};
+use bevy_asset::{Assets, Handle, HandleUntyped};
+use bevy_ecs::world::World;
+use bevy_reflect::TypeUuid;
+use bevy_utils::HashMap;
+
+/// The vertex shader of every [FullscreenPass] pipeline. It outputs the uv of the pixel at
+/// `layout(location = 0) in vec2 v_Uv`, with (0, 0) at the top left of the target.
+pub const FULLSCREEN_VERTEX_SHADER_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(Shader::TYPE_UUID, 0x2c1f7a6e90b4d853);
+
+/// Runs a fragment shader over every pixel of a color target by drawing a single triangle that
+/// covers it. This is the building block of post processing nodes: they bind their input
+/// textures and uniforms with [FullscreenPass::set_texture] and [FullscreenPass::set_uniform],
+/// call [FullscreenPass::prepare] from [Node::prepare](crate::render_graph::Node::prepare) and
+/// [FullscreenPass::draw] from [Node::update](crate::render_graph::Node::update).
+#[derive(Debug)]
+pub struct FullscreenPass {
+    pipeline: Handle<PipelineDescriptor>,
+    /// The specialization the pipeline is compiled with by [FullscreenPass::prepare]
+    pub specialization: PipelineSpecialization,
+    specialized_pipeline: Option<Handle<PipelineDescriptor>>,
+    descriptor: PassDescriptor,
+    bindings: RenderResourceBindings,
+    sampler: Option<SamplerId>,
+    uniform_data: HashMap<String, Vec<u8>>,
+}
+
+impl FullscreenPass {
+    pub fn new(pipeline: Handle<PipelineDescriptor>, ops: Operations<Color>) -> Self {
+        FullscreenPass {
+            pipeline,
+            specialization: PipelineSpecialization::default(),
+            specialized_pipeline: None,
+            descriptor: PassDescriptor {
+                color_attachments: vec![RenderPassColorAttachmentDescriptor {
+                    attachment: TextureAttachment::Input("target".to_string()),
+                    resolve_target: None,
+                    ops,
+                }],
+                depth_stencil_attachment: None,
+                sample_count: 1,
+            },
+            bindings: RenderResourceBindings::default(),
+            sampler: None,
+            uniform_data: HashMap::default(),
+        }
+    }
+
+    /// A pipeline that runs `fragment` over a swap chain format target without blending. The
+    /// fragment shader can read the uv of the pixel from `layout(location = 0) in vec2 v_Uv`.
+    pub fn pipeline_descriptor(fragment: Handle<Shader>) -> PipelineDescriptor {
+        let mut descriptor = PipelineDescriptor {
+            color_target_states: vec![ColorTargetState {
+                format: TextureFormat::default(),
+                color_blend: BlendState::REPLACE,
+                alpha_blend: BlendState::REPLACE,
+                write_mask: ColorWrite::ALL,
+            }],
+            ..PipelineDescriptor::new(ShaderStages {
+                vertex: FULLSCREEN_VERTEX_SHADER_HANDLE.typed(),
+                fragment: Some(fragment),
+            })
+        };
+        // the winding of the triangle doesn't matter
+        descriptor.primitive.cull_mode = CullMode::None;
+        descriptor
+    }
+
+    /// Binds `texture` to the shader texture called `name`, and a linear sampler to the sampler
+    /// called `{name}_sampler`
+    pub fn set_texture(
+        &mut self,
+        render_resource_context: &dyn RenderResourceContext,
+        name: &str,
+        texture: TextureId,
+    ) {
+        let sampler = *self.sampler.get_or_insert_with(|| {
+            render_resource_context.create_sampler(&SamplerDescriptor {
+                mag_filter: FilterMode::Linear,
+                min_filter: FilterMode::Linear,
+                ..Default::default()
+            })
+        });
+        self.bindings
+            .set(name, RenderResourceBinding::Texture(texture));
+        self.bindings.set(
+            &format!("{}_sampler", name),
+            RenderResourceBinding::Sampler(sampler),
+        );
+    }
+
+    /// Binds `data` to the uniform block called `name`. The buffer is only written when `data`
+    /// changes.
+    pub fn set_uniform(&mut self, render_context: &mut dyn RenderContext, name: &str, data: &[u8]) {
+        if self
+            .uniform_data
+            .get(name)
+            .map(|current| current.as_slice())
+            == Some(data)
+        {
+            return;
+        }
+
+        let render_resource_context = render_context.resources();
+        match self.bindings.get(name) {
+            Some(RenderResourceBinding::Buffer { buffer, range, .. })
+                if range.end == data.len() as u64 =>
+            {
+                let buffer = *buffer;
+                let staging_buffer = render_resource_context.create_buffer_with_data(
+                    BufferInfo {
+                        size: data.len(),
+                        buffer_usage: BufferUsage::COPY_SRC,
+                        ..Default::default()
+                    },
+                    data,
+                );
+                render_context.copy_buffer_to_buffer(
+                    staging_buffer,
+                    0,
+                    buffer,
+                    0,
+                    data.len() as u64,
+                );
+                render_context.resources().remove_buffer(staging_buffer);
+            }
+            current => {
+                if let Some(buffer) = current.and_then(|binding| binding.get_buffer()) {
+                    render_resource_context.remove_buffer(buffer);
+                }
+                let buffer = render_resource_context.create_buffer_with_data(
+                    BufferInfo {
+                        size: data.len(),
+                        buffer_usage: BufferUsage::UNIFORM | BufferUsage::COPY_DST,
+                        ..Default::default()
+                    },
+                    data,
+                );
+                self.bindings.set(
+                    name,
+                    RenderResourceBinding::Buffer {
+                        buffer,
+                        range: 0..data.len() as u64,
+                        dynamic_index: None,
+                    },
+                );
+                self.bindings.set_buffer_byte_len(name, data.len() as u64);
+            }
+        }
+        self.uniform_data.insert(name.to_string(), data.to_vec());
+    }
+
+    /// Compiles the pipeline with the current [FullscreenPass::specialization], if it hasn't been
+    /// compiled yet and its shaders are loaded
+    pub fn prepare(&mut self, world: &mut World) {
+        let world = world.cell();
+        let mut pipeline_compiler = world.get_resource_mut::<PipelineCompiler>().unwrap();
+        let specialized_pipeline =
+            pipeline_compiler.get_specialized_pipeline(&self.pipeline, &self.specialization);
+        self.specialized_pipeline = specialized_pipeline.or_else(|| {
+            let render_resource_context = world
+                .get_resource::<Box<dyn RenderResourceContext>>()
+                .unwrap();
+            let mut pipelines = world
+                .get_resource_mut::<Assets<PipelineDescriptor>>()
+                .unwrap();
+            let mut shaders = world.get_resource_mut::<Assets<Shader>>().unwrap();
+            pipeline_compiler.compile_pipeline(
+                &**render_resource_context,
+                &mut pipelines,
+                &mut shaders,
+                &self.pipeline,
+                &self.specialization,
+            )
+        });
+    }
+
+    /// Draws into `target`. Does nothing if the pass hasn't been prepared or a binding the shader
+    /// uses hasn't been set.
+    pub fn draw(
+        &mut self,
+        world: &World,
+        render_context: &mut dyn RenderContext,
+        target: TextureId,
+    ) {
+        let pipelines = world.get_resource::<Assets<PipelineDescriptor>>().unwrap();
+        let (pipeline, layout) = match self.specialized_pipeline.as_ref().and_then(|pipeline| {
+            pipelines
+                .get(pipeline)
+                .and_then(|descriptor| descriptor.get_layout())
+                .map(|layout| (pipeline, layout))
+        }) {
+            Some(pipeline) => pipeline,
+            None => return,
+        };
+
+        let mut commands = RenderCommands::default();
+        commands.set_pipeline(pipeline);
+        for bind_group_descriptor in layout.bind_groups.iter() {
+            match self
+                .bindings
+                .update_bind_group(bind_group_descriptor, render_context.resources())
+            {
+                Some(bind_group) => {
+                    commands.set_bind_group(bind_group_descriptor.index, &bind_group)
+                }
+                None => return,
+            }
+        }
+        commands.draw(0..3, 0..1);
+
+        self.descriptor.color_attachments[0].attachment = TextureAttachment::Id(target);
+        render_context.execute_render_commands(
+            &self.descriptor,
+            &self.bindings,
+            &pipelines,
+            &mut commands,
+        );
+    }
+}
@@ -1,8 +1,14 @@
+mod compute_pass;
+mod fullscreen_pass;
 mod ops;
 #[allow(clippy::module_inception)]
 mod pass;
+mod render_commands;
 mod render_pass;
 
+pub use compute_pass::*;
+pub use fullscreen_pass::*;
 pub use ops::*;
 pub use pass::*;
+pub use render_commands::*;
 pub use render_pass::*;
@@ -27,6 +27,25 @@ impl Default for ClearColor {
     }
 }
 
+/// Chooses how a camera's passes clear their color target. Add it to a camera entity to
+/// override the [ClearColor] resource for that camera.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClearColorConfig {
+    /// Clears with the [ClearColor] resource
+    Default,
+    /// Clears with the given color
+    Custom(Color),
+    /// Keeps the previous contents, for example when a skybox or another camera covers the
+    /// whole target
+    None,
+}
+
+impl Default for ClearColorConfig {
+    fn default() -> Self {
+        ClearColorConfig::Default
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct RenderPassColorAttachmentDescriptor {
     /// The actual color attachment.
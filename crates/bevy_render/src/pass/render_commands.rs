@@ -0,0 +1,438 @@
+use super::RenderPass;
+use crate::{
+    color::Color,
+    draw::RenderCommand,
+    pipeline::{IndexFormat, PipelineDescriptor},
+    renderer::{BindGroup, BindGroupId, BufferId, DrawIndexedIndirectArgs, DrawIndirectArgs},
+};
+use bevy_asset::{Assets, Handle};
+use bevy_utils::tracing::debug;
+use std::ops::Range;
+
+/// A list of [RenderCommand]s recorded for a pass, independent of the backend that executes them.
+///
+/// Lists can be recorded separately, for example on multiple threads, sorted and appended to each
+/// other, before a backend replays them with [RenderContext::execute_render_commands].
+///
+/// [RenderContext::execute_render_commands]: crate::renderer::RenderContext::execute_render_commands
+#[derive(Debug, Default, Clone)]
+pub struct RenderCommands {
+    commands: Vec<RenderCommand>,
+}
+
+impl RenderCommands {
+    pub fn push(&mut self, render_command: RenderCommand) {
+        self.commands.push(render_command);
+    }
+
+    pub fn set_pipeline(&mut self, pipeline: &Handle<PipelineDescriptor>) {
+        self.push(RenderCommand::SetPipeline {
+            pipeline: pipeline.clone_weak(),
+        });
+    }
+
+    pub fn set_bind_group(&mut self, index: u32, bind_group: &BindGroup) {
+        self.push(RenderCommand::SetBindGroup {
+            index,
+            bind_group: bind_group.id,
+            dynamic_uniform_indices: bind_group.dynamic_uniform_indices.clone(),
+        });
+    }
+
+    pub fn set_stencil_reference(&mut self, reference: u32) {
+        self.push(RenderCommand::SetStencilReference { reference });
+    }
+
+    pub fn set_blend_color(&mut self, color: Color) {
+        self.push(RenderCommand::SetBlendColor { color });
+    }
+
+    pub fn set_scissor_rect(&mut self, x: u32, y: u32, width: u32, height: u32) {
+        self.push(RenderCommand::SetScissorRect {
+            x,
+            y,
+            width,
+            height,
+        });
+    }
+
+    pub fn draw(&mut self, vertices: Range<u32>, instances: Range<u32>) {
+        self.push(RenderCommand::Draw {
+            vertices,
+            instances,
+        });
+    }
+
+    /// Moves the commands of `other` to the end of this list
+    pub fn append(&mut self, other: &mut RenderCommands) {
+        self.commands.append(&mut other.commands);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &RenderCommand> {
+        self.commands.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.commands.clear();
+    }
+
+    /// Replays the commands into `render_pass`, leaving the list empty. Redundant state changes
+    /// and draws whose pipeline layout hasn't been fully bound are skipped. Indirect draws that
+    /// read consecutive arguments of the same buffer without state changes in between are issued
+    /// as a single multi draw.
+    pub fn replay(
+        &mut self,
+        render_pass: &mut dyn RenderPass,
+        pipelines: &Assets<PipelineDescriptor>,
+    ) {
+        run_render_commands(render_pass, pipelines, self.commands.drain(..));
+    }
+}
+
+impl Extend<RenderCommand> for RenderCommands {
+    fn extend<T: IntoIterator<Item = RenderCommand>>(&mut self, iter: T) {
+        self.commands.extend(iter);
+    }
+}
+
+impl From<Vec<RenderCommand>> for RenderCommands {
+    fn from(commands: Vec<RenderCommand>) -> Self {
+        RenderCommands { commands }
+    }
+}
+
+fn run_render_commands(
+    render_pass: &mut dyn RenderPass,
+    pipelines: &Assets<PipelineDescriptor>,
+    commands: impl Iterator<Item = RenderCommand>,
+) {
+    let mut draw_state = DrawState::default();
+    let mut indirect_draws: Option<IndirectDraws> = None;
+    for render_command in commands {
+        match render_command {
+            RenderCommand::DrawIndexedIndirect {
+                buffer,
+                offset,
+                count,
+            } => {
+                if draw_state.can_draw_indexed() {
+                    queue_indirect_draws(
+                        render_pass,
+                        &mut indirect_draws,
+                        IndirectDraws {
+                            indexed: true,
+                            buffer,
+                            offset,
+                            count,
+                        },
+                    );
+                } else {
+                    debug!("Could not draw indexed indirect because the pipeline layout wasn't fully set for pipeline: {:?}", draw_state.pipeline);
+                }
+            }
+            RenderCommand::DrawIndirect {
+                buffer,
+                offset,
+                count,
+            } => {
+                if draw_state.can_draw() {
+                    queue_indirect_draws(
+                        render_pass,
+                        &mut indirect_draws,
+                        IndirectDraws {
+                            indexed: false,
+                            buffer,
+                            offset,
+                            count,
+                        },
+                    );
+                } else {
+                    debug!("Could not draw indirect because the pipeline layout wasn't fully set for pipeline: {:?}", draw_state.pipeline);
+                }
+            }
+            RenderCommand::SetPipeline { pipeline } => {
+                if draw_state.is_pipeline_set(pipeline.clone_weak()) {
+                    continue;
+                }
+                flush_indirect_draws(render_pass, &mut indirect_draws);
+                render_pass.set_pipeline(&pipeline);
+                let descriptor = pipelines.get(&pipeline).unwrap();
+                draw_state.set_pipeline(&pipeline, descriptor);
+            }
+            RenderCommand::DrawIndexed {
+                base_vertex,
+                indices,
+                instances,
+            } => {
+                if draw_state.can_draw_indexed() {
+                    flush_indirect_draws(render_pass, &mut indirect_draws);
+                    render_pass.draw_indexed(indices.clone(), base_vertex, instances.clone());
+                } else {
+                    debug!("Could not draw indexed because the pipeline layout wasn't fully set for pipeline: {:?}", draw_state.pipeline);
+                }
+            }
+            RenderCommand::Draw {
+                vertices,
+                instances,
+            } => {
+                if draw_state.can_draw() {
+                    flush_indirect_draws(render_pass, &mut indirect_draws);
+                    render_pass.draw(vertices.clone(), instances.clone());
+                } else {
+                    debug!("Could not draw because the pipeline layout wasn't fully set for pipeline: {:?}", draw_state.pipeline);
+                }
+            }
+            RenderCommand::SetStencilReference { reference } => {
+                if draw_state.stencil_reference == Some(reference) {
+                    continue;
+                }
+                flush_indirect_draws(render_pass, &mut indirect_draws);
+                render_pass.set_stencil_reference(reference);
+                draw_state.stencil_reference = Some(reference);
+            }
+            RenderCommand::SetBlendColor { color } => {
+                if draw_state.blend_color == Some(color) {
+                    continue;
+                }
+                flush_indirect_draws(render_pass, &mut indirect_draws);
+                render_pass.set_blend_color(color);
+                draw_state.blend_color = Some(color);
+            }
+            RenderCommand::SetScissorRect {
+                x,
+                y,
+                width,
+                height,
+            } => {
+                let scissor_rect = (x, y, width, height);
+                if draw_state.scissor_rect == Some(scissor_rect) {
+                    continue;
+                }
+                flush_indirect_draws(render_pass, &mut indirect_draws);
+                render_pass.set_scissor_rect(x, y, width, height);
+                draw_state.scissor_rect = Some(scissor_rect);
+            }
+            RenderCommand::SetVertexBuffer {
+                buffer,
+                offset,
+                slot,
+            } => {
+                if draw_state.is_vertex_buffer_set(slot, buffer, offset) {
+                    continue;
+                }
+                flush_indirect_draws(render_pass, &mut indirect_draws);
+                render_pass.set_vertex_buffer(slot, buffer, offset);
+                draw_state.set_vertex_buffer(slot, buffer, offset);
+            }
+            RenderCommand::SetIndexBuffer {
+                buffer,
+                offset,
+                index_format,
+            } => {
+                if draw_state.is_index_buffer_set(buffer, offset, index_format) {
+                    continue;
+                }
+                flush_indirect_draws(render_pass, &mut indirect_draws);
+                render_pass.set_index_buffer(buffer, offset, index_format);
+                draw_state.set_index_buffer(buffer, offset, index_format);
+            }
+            RenderCommand::SetBindGroup {
+                index,
+                bind_group,
+                dynamic_uniform_indices,
+            } => {
+                if dynamic_uniform_indices.is_none()
+                    && draw_state.is_bind_group_set(index, bind_group)
+                {
+                    continue;
+                }
+                let pipeline = pipelines
+                    .get(draw_state.pipeline.as_ref().unwrap())
+                    .unwrap();
+                let layout = pipeline.get_layout().unwrap();
+                let bind_group_descriptor = layout.get_bind_group(index).unwrap();
+                flush_indirect_draws(render_pass, &mut indirect_draws);
+                render_pass.set_bind_group(
+                    index,
+                    bind_group_descriptor.id,
+                    bind_group,
+                    dynamic_uniform_indices.as_deref(),
+                );
+                draw_state.set_bind_group(index, bind_group);
+            }
+        }
+    }
+    flush_indirect_draws(render_pass, &mut indirect_draws);
+}
+
+/// Indirect draws reading `count` consecutive arguments from `buffer`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct IndirectDraws {
+    indexed: bool,
+    buffer: BufferId,
+    offset: u64,
+    count: u32,
+}
+
+impl IndirectDraws {
+    fn stride(&self) -> u64 {
+        if self.indexed {
+            std::mem::size_of::<DrawIndexedIndirectArgs>() as u64
+        } else {
+            std::mem::size_of::<DrawIndirectArgs>() as u64
+        }
+    }
+
+    /// Appends `next` to these draws if it reads the arguments that follow them
+    fn merge(&mut self, next: &IndirectDraws) -> bool {
+        if self.indexed == next.indexed
+            && self.buffer == next.buffer
+            && self.offset + self.count as u64 * self.stride() == next.offset
+        {
+            self.count += next.count;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn draw(&self, render_pass: &mut dyn RenderPass) {
+        if self.indexed {
+            render_pass.draw_indexed_indirect(self.buffer, self.offset, self.count);
+        } else {
+            render_pass.draw_indirect(self.buffer, self.offset, self.count);
+        }
+    }
+}
+
+fn queue_indirect_draws(
+    render_pass: &mut dyn RenderPass,
+    indirect_draws: &mut Option<IndirectDraws>,
+    next: IndirectDraws,
+) {
+    if let Some(draws) = indirect_draws {
+        if draws.merge(&next) {
+            return;
+        }
+    }
+    flush_indirect_draws(render_pass, indirect_draws);
+    *indirect_draws = Some(next);
+}
+
+fn flush_indirect_draws(
+    render_pass: &mut dyn RenderPass,
+    indirect_draws: &mut Option<IndirectDraws>,
+) {
+    if let Some(draws) = indirect_draws.take() {
+        draws.draw(render_pass);
+    }
+}
+
+/// Tracks the current pipeline state to ensure draw calls are valid.
+#[derive(Debug, Default)]
+struct DrawState {
+    pipeline: Option<Handle<PipelineDescriptor>>,
+    bind_groups: Vec<Option<BindGroupId>>,
+    vertex_buffers: Vec<Option<(BufferId, u64)>>,
+    index_buffer: Option<(BufferId, u64, IndexFormat)>,
+    stencil_reference: Option<u32>,
+    blend_color: Option<Color>,
+    scissor_rect: Option<(u32, u32, u32, u32)>,
+}
+
+impl DrawState {
+    pub fn set_bind_group(&mut self, index: u32, bind_group: BindGroupId) {
+        self.bind_groups[index as usize] = Some(bind_group);
+    }
+
+    pub fn is_bind_group_set(&self, index: u32, bind_group: BindGroupId) -> bool {
+        self.bind_groups[index as usize] == Some(bind_group)
+    }
+
+    pub fn set_vertex_buffer(&mut self, index: u32, buffer: BufferId, offset: u64) {
+        self.vertex_buffers[index as usize] = Some((buffer, offset));
+    }
+
+    pub fn is_vertex_buffer_set(&self, index: u32, buffer: BufferId, offset: u64) -> bool {
+        self.vertex_buffers[index as usize] == Some((buffer, offset))
+    }
+
+    pub fn set_index_buffer(&mut self, buffer: BufferId, offset: u64, index_format: IndexFormat) {
+        self.index_buffer = Some((buffer, offset, index_format));
+    }
+
+    pub fn is_index_buffer_set(
+        &self,
+        buffer: BufferId,
+        offset: u64,
+        index_format: IndexFormat,
+    ) -> bool {
+        self.index_buffer == Some((buffer, offset, index_format))
+    }
+
+    pub fn can_draw(&self) -> bool {
+        self.bind_groups.iter().all(|b| b.is_some())
+            && self.vertex_buffers.iter().all(|v| v.is_some())
+    }
+
+    pub fn can_draw_indexed(&self) -> bool {
+        self.can_draw() && self.index_buffer.is_some()
+    }
+
+    pub fn is_pipeline_set(&self, pipeline: Handle<PipelineDescriptor>) -> bool {
+        self.pipeline == Some(pipeline)
+    }
+
+    pub fn set_pipeline(
+        &mut self,
+        handle: &Handle<PipelineDescriptor>,
+        descriptor: &PipelineDescriptor,
+    ) {
+        self.bind_groups.clear();
+        self.vertex_buffers.clear();
+        self.index_buffer = None;
+
+        self.pipeline = Some(handle.clone_weak());
+        let layout = descriptor.get_layout().unwrap();
+        self.bind_groups.resize(layout.bind_groups.len(), None);
+        self.vertex_buffers
+            .resize(layout.vertex_buffer_descriptors.len(), None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merges_consecutive_indirect_draws() {
+        let buffer = BufferId::new();
+        let draws = |indexed, offset, count| IndirectDraws {
+            indexed,
+            buffer,
+            offset,
+            count,
+        };
+        let mut merged = draws(true, 0, 2);
+        assert!(merged.merge(&draws(true, 40, 1)));
+        assert_eq!(merged, draws(true, 0, 3));
+        assert!(!merged.merge(&draws(true, 80, 1)));
+        assert!(!merged.merge(&draws(false, 60, 1)));
+        assert!(!merged.merge(&IndirectDraws {
+            buffer: BufferId::new(),
+            ..draws(true, 60, 1)
+        }));
+
+        let mut merged = draws(false, 16, 1);
+        assert!(merged.merge(&draws(false, 32, 4)));
+        assert_eq!(merged, draws(false, 16, 5));
+    }
+}
@@ -1,4 +1,5 @@
 use crate::{
+    color::Color,
     pipeline::{BindGroupDescriptorId, IndexFormat, PipelineDescriptor},
     renderer::{BindGroupId, BufferId, RenderContext},
 };
@@ -13,8 +14,22 @@ pub trait RenderPass {
     fn set_viewport(&mut self, x: f32, y: f32, w: f32, h: f32, min_depth: f32, max_depth: f32);
     fn set_scissor_rect(&mut self, x: u32, y: u32, w: u32, h: u32);
     fn set_stencil_reference(&mut self, reference: u32);
+    /// Sets the constant used by the [BlendFactor::BlendColor] and
+    /// [BlendFactor::OneMinusBlendColor] factors of the pipeline
+    ///
+    /// [BlendFactor::BlendColor]: crate::pipeline::BlendFactor::BlendColor
+    /// [BlendFactor::OneMinusBlendColor]: crate::pipeline::BlendFactor::OneMinusBlendColor
+    fn set_blend_color(&mut self, color: Color);
     fn draw(&mut self, vertices: Range<u32>, instances: Range<u32>);
     fn draw_indexed(&mut self, indices: Range<u32>, base_vertex: i32, instances: Range<u32>);
+    /// Draws `count` times with the
+    /// [DrawIndirectArgs](crate::renderer::DrawIndirectArgs) stored one after the other in
+    /// `buffer`, starting at `offset`
+    fn draw_indirect(&mut self, buffer: BufferId, offset: u64, count: u32);
+    /// Draws `count` times with the
+    /// [DrawIndexedIndirectArgs](crate::renderer::DrawIndexedIndirectArgs) stored one after the
+    /// other in `buffer`, starting at `offset`
+    fn draw_indexed_indirect(&mut self, buffer: BufferId, offset: u64, count: u32);
     fn set_bind_group(
         &mut self,
         index: u32,
@@ -22,4 +37,10 @@ pub trait RenderPass {
         bind_group: BindGroupId,
         dynamic_uniform_indices: Option<&[u32]>,
     );
+    /// Starts a group of draws named `label` in GPU debuggers, until the matching
+    /// [RenderPass::pop_debug_group]
+    fn push_debug_group(&mut self, label: &str);
+    fn pop_debug_group(&mut self);
+    /// Names the next draws in GPU debuggers
+    fn insert_debug_marker(&mut self, label: &str);
 }
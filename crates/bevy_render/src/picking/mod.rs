@@ -0,0 +1,231 @@
+use crate::{
+    draw::{DrawContext, OutsideFrustum},
+    mesh::Indices,
+    pipeline::{PipelineDescriptor, PipelineSpecialization, RenderPipeline},
+    prelude::*,
+    render_graph::{base, RenderGraph, WindowTextureNode},
+    texture::{Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsage},
+    RenderStage,
+};
+use bevy_app::prelude::*;
+use bevy_asset::{Assets, Handle, HandleUntyped};
+use bevy_ecs::{
+    entity::Entity,
+    query::{With, Without},
+    reflect::ReflectComponent,
+    system::{IntoSystem, Query, Res, ResMut},
+};
+use bevy_math::Vec2;
+use bevy_reflect::{Reflect, TypeUuid};
+use bevy_utils::HashSet;
+use bevy_window::{WindowId, Windows};
+
+mod picking_node;
+mod pipeline;
+mod raycast;
+
+pub use picking_node::*;
+pub use raycast::*;
+
+pub const PICKING_PIPELINE_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(PipelineDescriptor::TYPE_UUID, 0x5b6f0d1e83a2c947);
+
+pub mod node {
+    pub const PICKING_TEXTURE: &str = "picking_texture";
+    pub const PICKING_DEPTH_TEXTURE: &str = "picking_depth_texture";
+    pub const PICKING_PASS: &str = "picking_pass";
+}
+
+/// Adds an offscreen pass that lets [Pickable] entities be selected with the cursor. The entity
+/// under the cursor is available in the [Picking] resource.
+///
+/// Must be added after [RenderPlugin](crate::RenderPlugin), with the 3d camera of the base render
+/// graph enabled.
+#[derive(Debug, Default)]
+pub struct PickingPlugin;
+
+impl Plugin for PickingPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.register_type::<Pickable>()
+            .init_resource::<Picking>()
+            .init_resource::<PickingDraw>()
+            .add_system_to_stage(CoreStage::PreUpdate, picking_cursor_system.system())
+            .add_system_to_stage(RenderStage::Draw, draw_picking_system.system());
+        let world = app.world_mut().cell();
+        let mut shaders = world.get_resource_mut::<Assets<Shader>>().unwrap();
+        let mut pipelines = world
+            .get_resource_mut::<Assets<PipelineDescriptor>>()
+            .unwrap();
+        pipelines.set_untracked(
+            PICKING_PIPELINE_HANDLE,
+            pipeline::build_picking_pipeline(&mut shaders),
+        );
+
+        let mut graph = world.get_resource_mut::<RenderGraph>().unwrap();
+        let texture_node = |format, usage| {
+            WindowTextureNode::new(
+                WindowId::primary(),
+                TextureDescriptor {
+                    size: Extent3d::new(1, 1, 1),
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: TextureDimension::D2,
+                    format,
+                    usage,
+                    view_dimension: None,
+                },
+            )
+        };
+        graph.add_node(
+            node::PICKING_TEXTURE,
+            texture_node(
+                TextureFormat::R32Uint,
+                TextureUsage::OUTPUT_ATTACHMENT | TextureUsage::COPY_SRC,
+            ),
+        );
+        graph.add_node(
+            node::PICKING_DEPTH_TEXTURE,
+            texture_node(TextureFormat::Depth32Float, TextureUsage::OUTPUT_ATTACHMENT),
+        );
+        graph.add_node(node::PICKING_PASS, PickingNode::new());
+        graph
+            .add_slot_edge(
+                node::PICKING_TEXTURE,
+                WindowTextureNode::OUT_TEXTURE,
+                node::PICKING_PASS,
+                PickingNode::IN_COLOR_ATTACHMENT,
+            )
+            .unwrap();
+        graph
+            .add_slot_edge(
+                node::PICKING_DEPTH_TEXTURE,
+                WindowTextureNode::OUT_TEXTURE,
+                node::PICKING_PASS,
+                PickingNode::IN_DEPTH,
+            )
+            .unwrap();
+        graph
+            .add_node_edge(base::node::CAMERA_3D, node::PICKING_PASS)
+            .unwrap();
+        graph
+            .add_node_edge(base::node::SHARED_BUFFERS, node::PICKING_PASS)
+            .unwrap();
+    }
+}
+
+/// Marks an entity with a [Mesh] as selectable by [PickingPlugin]
+#[derive(Debug, Clone, Default, Reflect)]
+#[reflect(Component)]
+pub struct Pickable;
+
+/// The result of the picking pass
+#[derive(Debug, Default)]
+pub struct Picking {
+    /// The position to pick at, in the same coordinates as
+    /// [Window::cursor_position](bevy_window::Window::cursor_position). Follows the cursor of
+    /// the primary window.
+    pub cursor_position: Option<Vec2>,
+    picked: Option<Entity>,
+}
+
+impl Picking {
+    /// Returns the [Pickable] entity under `cursor_position`, if any.
+    ///
+    /// Reading the picking texture back from the gpu takes a frame, so this is the result from
+    /// the previous frame.
+    pub fn picked(&self) -> Option<Entity> {
+        self.picked
+    }
+}
+
+/// The render commands of every visible [Pickable] entity. The picking id of an entity is its
+/// index in `entities` + 1.
+#[derive(Debug, Default)]
+pub struct PickingDraw {
+    draw: Draw,
+    entities: Vec<Entity>,
+}
+
+pub fn picking_cursor_system(windows: Res<Windows>, mut picking: ResMut<Picking>) {
+    picking.cursor_position = windows
+        .get_primary()
+        .and_then(|window| window.cursor_position());
+}
+
+pub fn draw_picking_system(
+    mut draw_context: DrawContext,
+    mut picking_draw: ResMut<PickingDraw>,
+    depth: Res<Depth>,
+    meshes: Res<Assets<Mesh>>,
+    mut query: Query<
+        (Entity, &mut RenderPipelines, &Handle<Mesh>, &Visible),
+        (With<Pickable>, Without<OutsideFrustum>),
+    >,
+) {
+    let picking_draw = &mut *picking_draw;
+    picking_draw.draw.clear_render_commands();
+    picking_draw.entities.clear();
+    for (entity, mut render_pipelines, mesh_handle, visible) in query.iter_mut() {
+        if !visible.is_visible {
+            continue;
+        }
+
+        // don't render if the mesh isn't loaded yet
+        let mesh = if let Some(mesh) = meshes.get(mesh_handle) {
+            mesh
+        } else {
+            continue;
+        };
+
+        let mut render_pipeline = RenderPipeline::specialized(
+            PICKING_PIPELINE_HANDLE.typed(),
+            PipelineSpecialization {
+                sample_count: 1,
+                strip_index_format: None,
+                shader_specialization: Default::default(),
+                primitive_topology: mesh.primitive_topology(),
+                dynamic_bindings: render_pipelines
+                    .bindings
+                    .iter_dynamic_bindings()
+                    .map(|name| name.to_string())
+                    .collect::<HashSet<String>>(),
+                vertex_buffer_layout: mesh.get_vertex_buffer_layout(),
+                color_target_format: None,
+                blend_mode: None,
+                // the picking depth texture keeps its own format
+                depth_format: None,
+                reversed_z: depth.reversed_z,
+            },
+        );
+        render_pipeline.dynamic_bindings_generation =
+            render_pipelines.bindings.dynamic_bindings_generation();
+
+        let draw = &mut picking_draw.draw;
+        draw_context
+            .set_pipeline(
+                draw,
+                &render_pipeline.pipeline,
+                &render_pipeline.specialization,
+            )
+            .unwrap();
+        draw_context
+            .set_bind_groups_from_bindings(draw, &mut [&mut render_pipelines.bindings])
+            .unwrap();
+        draw_context
+            .set_vertex_buffers_from_bindings(draw, &[&render_pipelines.bindings])
+            .unwrap();
+
+        picking_draw.entities.push(entity);
+        // the picking id is passed to the shader as the instance index
+        let id = picking_draw.entities.len() as u32;
+        match mesh.indices() {
+            Some(Indices::U32(indices)) => {
+                draw.draw_indexed(0..indices.len() as u32, 0, id..id + 1)
+            }
+            Some(Indices::U16(indices)) => {
+                draw.draw_indexed(0..indices.len() as u32, 0, id..id + 1)
+            }
+            None => draw.draw(0..mesh.count_vertices() as u32, id..id + 1),
+        };
+    }
+}
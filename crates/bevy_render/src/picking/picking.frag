@@ -0,0 +1,9 @@
+#version 450
+
+layout(location = 0) flat in uint v_PickingId;
+
+layout(location = 0) out uint o_Target;
+
+void main() {
+    o_Target = v_PickingId;
+}
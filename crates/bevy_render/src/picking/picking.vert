@@ -0,0 +1,19 @@
+#version 450
+
+layout(location = 0) in vec3 Vertex_Position;
+
+layout(location = 0) flat out uint v_PickingId;
+
+layout(set = 0, binding = 0) uniform CameraViewProj {
+    mat4 ViewProj;
+};
+
+layout(set = 1, binding = 0) uniform Transform {
+    mat4 Model;
+};
+
+void main() {
+    // the picking id of each entity is passed in as its instance index
+    v_PickingId = uint(gl_InstanceIndex);
+    gl_Position = ViewProj * Model * vec4(Vertex_Position, 1.0);
+}
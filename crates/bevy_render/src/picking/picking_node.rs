@@ -0,0 +1,270 @@
+use super::{Picking, PickingDraw};
+use crate::{
+    camera::ActiveCameras,
+    draw::RenderCommand,
+    pass::{
+        LoadOp, Operations, PassDescriptor, RenderCommands, RenderPassColorAttachmentDescriptor,
+        RenderPassDepthStencilAttachmentDescriptor, TextureAttachment,
+    },
+    pipeline::PipelineDescriptor,
+    render_graph::{
+        base::{camera, Depth},
+        Node, ResourceSlotInfo, ResourceSlots,
+    },
+    renderer::{
+        BufferId, BufferInfo, BufferMapMode, BufferMapState, BufferUsage, RenderContext,
+        RenderResourceBindings, RenderResourceContext, RenderResourceType,
+    },
+    texture::Extent3d,
+    Color,
+};
+use bevy_asset::Assets;
+use bevy_ecs::{
+    entity::Entity,
+    world::{Mut, World},
+};
+use bevy_window::Windows;
+use std::{borrow::Cow, cell::Cell};
+
+// texture to buffer copies must be padded to this many bytes per row
+const READBACK_BUFFER_SIZE: usize = 256;
+
+/// Renders the entity ids of [Pickable](super::Pickable) entities, as seen by the 3d camera, into
+/// an integer texture and copies the texel under the cursor into a readback buffer. The buffer is
+/// mapped without blocking the frame, and the result is stored in the [Picking] resource once the
+/// mapping completes. No new texel is copied while a readback is in flight.
+pub struct PickingNode {
+    descriptor: PassDescriptor,
+    commands: RenderCommands,
+    entities: Vec<Entity>,
+    readback_buffer: Option<BufferId>,
+    /// The entities drawn in the frame whose readback is in flight
+    pending_readback: Option<Vec<Entity>>,
+}
+
+impl PickingNode {
+    pub const IN_COLOR_ATTACHMENT: &'static str = "color_attachment";
+    pub const IN_DEPTH: &'static str = "depth";
+
+    pub fn new() -> Self {
+        PickingNode {
+            descriptor: PassDescriptor {
+                color_attachments: vec![RenderPassColorAttachmentDescriptor {
+                    attachment: TextureAttachment::Input(Self::IN_COLOR_ATTACHMENT.to_string()),
+                    resolve_target: None,
+                    // 0 is reserved for "no entity"
+                    ops: Operations {
+                        load: LoadOp::Clear(Color::NONE),
+                        store: true,
+                    },
+                }],
+                depth_stencil_attachment: Some(RenderPassDepthStencilAttachmentDescriptor {
+                    attachment: TextureAttachment::Input(Self::IN_DEPTH.to_string()),
+                    depth_ops: Some(Operations {
+                        load: LoadOp::Clear(1.0),
+                        store: true,
+                    }),
+                    stencil_ops: None,
+                }),
+                sample_count: 1,
+            },
+            commands: RenderCommands::default(),
+            entities: Vec::new(),
+            readback_buffer: None,
+            pending_readback: None,
+        }
+    }
+
+    fn read_back(&mut self, world: &mut World) {
+        let (entities, buffer) = match (self.pending_readback.as_ref(), self.readback_buffer) {
+            (Some(entities), Some(buffer)) => (entities, buffer),
+            _ => {
+                world.get_resource_mut::<Picking>().unwrap().picked = None;
+                return;
+            }
+        };
+        let render_resource_context = world
+            .get_resource::<Box<dyn RenderResourceContext>>()
+            .unwrap();
+        let picked = match render_resource_context.get_buffer_map_state(buffer) {
+            BufferMapState::Unmapped => {
+                render_resource_context.map_buffer_async(buffer, BufferMapMode::Read);
+                return;
+            }
+            BufferMapState::Pending => return,
+            BufferMapState::Mapped => {
+                let id = Cell::new(0);
+                render_resource_context.read_mapped_buffer(buffer, 0..4, &|data, _| {
+                    id.set(u32::from_le_bytes([data[0], data[1], data[2], data[3]]));
+                });
+                render_resource_context.unmap_buffer(buffer);
+                (id.get() as usize)
+                    .checked_sub(1)
+                    .and_then(|index| entities.get(index).copied())
+            }
+            BufferMapState::Failed => {
+                render_resource_context.unmap_buffer(buffer);
+                None
+            }
+        };
+        self.pending_readback = None;
+        world.get_resource_mut::<Picking>().unwrap().picked = picked;
+    }
+}
+
+impl Default for PickingNode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Node for PickingNode {
+    fn input(&self) -> &[ResourceSlotInfo] {
+        static INPUT: &[ResourceSlotInfo] = &[
+            ResourceSlotInfo {
+                name: Cow::Borrowed(PickingNode::IN_COLOR_ATTACHMENT),
+                resource_type: RenderResourceType::Texture,
+            },
+            ResourceSlotInfo {
+                name: Cow::Borrowed(PickingNode::IN_DEPTH),
+                resource_type: RenderResourceType::Texture,
+            },
+        ];
+        INPUT
+    }
+
+    fn prepare(&mut self, world: &mut World) {
+        self.read_back(world);
+        let clear_depth = world.get_resource::<Depth>().unwrap().clear_depth();
+        if let Some(depth_ops) = self
+            .descriptor
+            .depth_stencil_attachment
+            .as_mut()
+            .and_then(|attachment| attachment.depth_ops.as_mut())
+        {
+            depth_ops.load = LoadOp::Clear(clear_depth);
+        }
+
+        let (draw_commands, entities) = {
+            let mut picking_draw = world.get_resource_mut::<PickingDraw>().unwrap();
+            let picking_draw = &mut *picking_draw;
+            (
+                std::mem::take(&mut picking_draw.draw.render_commands),
+                std::mem::take(&mut picking_draw.entities),
+            )
+        };
+        self.entities = entities;
+
+        let commands = &mut self.commands;
+        commands.clear();
+        world.resource_scope(|world, mut active_cameras: Mut<ActiveCameras>| {
+            let active_camera = match active_cameras.get_mut(camera::CAMERA_3D) {
+                Some(active_camera) if active_camera.entity.is_some() => active_camera,
+                _ => return,
+            };
+            let pipelines = world.get_resource::<Assets<PipelineDescriptor>>().unwrap();
+            let render_resource_context = &**world
+                .get_resource::<Box<dyn RenderResourceContext>>()
+                .unwrap();
+            for render_command in draw_commands {
+                // the camera bind groups aren't known to the draw system, so set them whenever
+                // the pipeline changes
+                let camera_pipeline = match render_command {
+                    RenderCommand::SetPipeline { ref pipeline } => Some(pipeline.clone_weak()),
+                    _ => None,
+                };
+                commands.push(render_command);
+                let layout = match camera_pipeline
+                    .and_then(|pipeline| pipelines.get(pipeline))
+                    .and_then(|descriptor| descriptor.get_layout())
+                {
+                    Some(layout) => layout,
+                    None => continue,
+                };
+                for bind_group_descriptor in layout.bind_groups.iter() {
+                    if let Some(bind_group) = active_camera
+                        .bindings
+                        .update_bind_group(bind_group_descriptor, render_resource_context)
+                    {
+                        commands.push(RenderCommand::SetBindGroup {
+                            index: bind_group_descriptor.index,
+                            bind_group: bind_group.id,
+                            dynamic_uniform_indices: bind_group.dynamic_uniform_indices.clone(),
+                        });
+                    }
+                }
+            }
+        });
+    }
+
+    fn update(
+        &mut self,
+        world: &World,
+        render_context: &mut dyn RenderContext,
+        input: &ResourceSlots,
+        _output: &mut ResourceSlots,
+    ) {
+        const IN_COLOR_ATTACHMENT: usize = 0;
+        const IN_DEPTH: usize = 1;
+        let picking_texture = input
+            .get(IN_COLOR_ATTACHMENT)
+            .unwrap()
+            .get_texture()
+            .unwrap();
+        self.descriptor.color_attachments[0].attachment = TextureAttachment::Id(picking_texture);
+        self.descriptor
+            .depth_stencil_attachment
+            .as_mut()
+            .unwrap()
+            .attachment =
+            TextureAttachment::Id(input.get(IN_DEPTH).unwrap().get_texture().unwrap());
+
+        let render_resource_bindings = world.get_resource::<RenderResourceBindings>().unwrap();
+        let pipelines = world.get_resource::<Assets<PipelineDescriptor>>().unwrap();
+        render_context.execute_render_commands(
+            &self.descriptor,
+            &render_resource_bindings,
+            &pipelines,
+            &mut self.commands,
+        );
+
+        if self.pending_readback.is_some() {
+            return;
+        }
+        let picking = world.get_resource::<Picking>().unwrap();
+        let windows = world.get_resource::<Windows>().unwrap();
+        let (cursor_position, window) = match (picking.cursor_position, windows.get_primary()) {
+            (Some(cursor_position), Some(window)) => (cursor_position, window),
+            _ => return,
+        };
+        // the cursor is in logical pixels with the origin at the bottom left, textures have
+        // their origin at the top left
+        let scale_factor = window.scale_factor() as f32;
+        let x = (cursor_position.x * scale_factor) as u32;
+        let y = window
+            .physical_height()
+            .checked_sub((cursor_position.y * scale_factor) as u32 + 1);
+        let y = match y {
+            Some(y) if x < window.physical_width() => y,
+            _ => return,
+        };
+
+        let readback_buffer = *self.readback_buffer.get_or_insert_with(|| {
+            render_context.resources().create_buffer(BufferInfo {
+                size: READBACK_BUFFER_SIZE,
+                buffer_usage: BufferUsage::MAP_READ | BufferUsage::COPY_DST,
+                mapped_at_creation: false,
+            })
+        });
+        render_context.copy_texture_to_buffer(
+            picking_texture,
+            [x, y, 0],
+            0,
+            readback_buffer,
+            0,
+            READBACK_BUFFER_SIZE as u32,
+            Extent3d::new(1, 1, 1),
+        );
+        self.pending_readback = Some(std::mem::take(&mut self.entities));
+    }
+}
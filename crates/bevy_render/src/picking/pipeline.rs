@@ -0,0 +1,29 @@
+use crate::{
+    pipeline::{BlendState, ColorTargetState, ColorWrite, PipelineDescriptor},
+    shader::{Shader, ShaderStage, ShaderStages},
+    texture::TextureFormat,
+};
+use bevy_asset::Assets;
+
+pub(crate) fn build_picking_pipeline(shaders: &mut Assets<Shader>) -> PipelineDescriptor {
+    PipelineDescriptor {
+        name: Some("picking".into()),
+        // integer formats can't be blended
+        color_target_states: vec![ColorTargetState {
+            format: TextureFormat::R32Uint,
+            color_blend: BlendState::REPLACE,
+            alpha_blend: BlendState::REPLACE,
+            write_mask: ColorWrite::ALL,
+        }],
+        ..PipelineDescriptor::default_config(ShaderStages {
+            vertex: shaders.add(Shader::from_glsl(
+                ShaderStage::Vertex,
+                include_str!("picking.vert"),
+            )),
+            fragment: Some(shaders.add(Shader::from_glsl(
+                ShaderStage::Fragment,
+                include_str!("picking.frag"),
+            ))),
+        })
+    }
+}
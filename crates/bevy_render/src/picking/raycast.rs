@@ -0,0 +1,483 @@
+use super::Pickable;
+use crate::{
+    camera::Camera,
+    mesh::{Mesh, VertexAttributeValues},
+    pipeline::PrimitiveTopology,
+};
+use bevy_app::prelude::*;
+use bevy_asset::{AssetEvent, Assets, Handle};
+use bevy_ecs::{
+    entity::Entity,
+    event::EventReader,
+    query::With,
+    system::{IntoSystem, Query, Res, ResMut, SystemParam},
+};
+use bevy_math::{Aabb, Vec2, Vec3};
+use bevy_transform::components::GlobalTransform;
+use bevy_utils::HashMap;
+use bevy_window::Windows;
+
+/// The maximum number of triangles in a leaf of a [MeshBvh]
+const MAX_LEAF_TRIANGLES: usize = 4;
+
+/// Lets [RayCast] test rays against the meshes of [Pickable] entities. Unlike
+/// [PickingPlugin](super::PickingPlugin) this runs on the cpu, so results are available
+/// immediately and any ray can be cast.
+#[derive(Debug, Default)]
+pub struct RaycastPlugin;
+
+impl Plugin for RaycastPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.register_type::<Pickable>()
+            .init_resource::<MeshBvhs>()
+            .add_system_to_stage(CoreStage::PreUpdate, mesh_bvh_system.system());
+    }
+}
+
+/// A half-line in 3d space
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+    pub origin: Vec3,
+    /// Normalized direction of the ray
+    pub direction: Vec3,
+}
+
+impl Ray {
+    pub fn new(origin: Vec3, direction: Vec3) -> Self {
+        Ray {
+            origin,
+            direction: direction.normalize(),
+        }
+    }
+
+    /// Returns the ray through the given screen space position of the camera's window, in the
+    /// same coordinates as [Window::cursor_position](bevy_window::Window::cursor_position).
+    pub fn from_screen(
+        camera: &Camera,
+        camera_transform: &GlobalTransform,
+        windows: &Windows,
+        screen_position: Vec2,
+    ) -> Option<Ray> {
+        let window = windows.get(camera.window)?;
+        let window_size = Vec2::new(window.width(), window.height());
+        let ndc = screen_position / window_size * 2.0 - Vec2::ONE;
+        let ndc_to_view = camera.projection_matrix.inverse();
+        let mut near = ndc_to_view.project_point3(ndc.extend(0.0));
+        let mut far = ndc_to_view.project_point3(ndc.extend(1.0));
+        // with reversed-Z the far plane is at a depth of 0.0
+        if near.z < far.z {
+            std::mem::swap(&mut near, &mut far);
+        }
+        let view_to_world = camera_transform.compute_matrix();
+        let near = view_to_world.transform_point3(near);
+        let far = view_to_world.transform_point3(far);
+        let direction = far - near;
+        if !direction.length_squared().is_finite() || direction.length_squared() <= f32::EPSILON {
+            return None;
+        }
+        Some(Ray::new(near, direction))
+    }
+
+    pub fn at(&self, distance: f32) -> Vec3 {
+        self.origin + self.direction * distance
+    }
+
+    /// Returns the distance along the ray at which it enters `aabb`, or 0.0 if the ray starts
+    /// inside of it
+    pub fn intersect_aabb(&self, aabb: &Aabb) -> Option<f32> {
+        let inverse_direction = Vec3::ONE / self.direction;
+        let t1 = (aabb.min - self.origin) * inverse_direction;
+        let t2 = (aabb.max - self.origin) * inverse_direction;
+        let t_min = t1.min(t2).max_element().max(0.0);
+        let t_max = t1.max(t2).min_element();
+        if t_min <= t_max {
+            Some(t_min)
+        } else {
+            None
+        }
+    }
+}
+
+/// Where a ray hit a triangle
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TriangleHit {
+    pub distance: f32,
+    /// The weights of the triangle's vertices at the hit position
+    pub barycentric: Vec3,
+}
+
+/// Intersects a ray with a triangle using the Möller–Trumbore algorithm. Both faces of the
+/// triangle are hit.
+pub fn ray_triangle_intersection(ray: &Ray, triangle: &[Vec3; 3]) -> Option<TriangleHit> {
+    let edge_1 = triangle[1] - triangle[0];
+    let edge_2 = triangle[2] - triangle[0];
+    let p = ray.direction.cross(edge_2);
+    let determinant = edge_1.dot(p);
+    if determinant.abs() < f32::EPSILON {
+        // the ray is parallel to the triangle
+        return None;
+    }
+    let inverse_determinant = 1.0 / determinant;
+    let t = ray.origin - triangle[0];
+    let u = t.dot(p) * inverse_determinant;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = t.cross(edge_1);
+    let v = ray.direction.dot(q) * inverse_determinant;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let distance = edge_2.dot(q) * inverse_determinant;
+    if distance < 0.0 {
+        return None;
+    }
+    Some(TriangleHit {
+        distance,
+        barycentric: Vec3::new(1.0 - u - v, u, v),
+    })
+}
+
+#[derive(Debug, Clone)]
+enum BvhNode {
+    Leaf {
+        aabb: Aabb,
+        first_triangle: usize,
+        triangle_count: usize,
+    },
+    Branch {
+        aabb: Aabb,
+        left: usize,
+        right: usize,
+    },
+}
+
+impl BvhNode {
+    fn aabb(&self) -> &Aabb {
+        match self {
+            BvhNode::Leaf { aabb, .. } | BvhNode::Branch { aabb, .. } => aabb,
+        }
+    }
+}
+
+/// A bounding volume hierarchy over the triangles of a [Mesh], used to cast rays against it
+#[derive(Debug, Clone)]
+pub struct MeshBvh {
+    triangles: Vec<[Vec3; 3]>,
+    /// The index of each triangle in the source mesh
+    triangle_indices: Vec<usize>,
+    nodes: Vec<BvhNode>,
+}
+
+impl MeshBvh {
+    /// Builds a bvh from the positions of a triangle list mesh. Returns `None` for other
+    /// topologies or meshes without positions.
+    pub fn from_mesh(mesh: &Mesh) -> Option<MeshBvh> {
+        if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+            return None;
+        }
+        let positions = match mesh.attribute(Mesh::ATTRIBUTE_POSITION)? {
+            VertexAttributeValues::Float3(positions) => positions,
+            _ => return None,
+        };
+        let vertex = |index: usize| Vec3::from(positions[index]);
+        let triangles = match mesh.indices() {
+            Some(indices) => {
+                let indices = indices.iter().collect::<Vec<_>>();
+                indices
+                    .chunks_exact(3)
+                    .map(|triangle| {
+                        [
+                            vertex(triangle[0]),
+                            vertex(triangle[1]),
+                            vertex(triangle[2]),
+                        ]
+                    })
+                    .collect()
+            }
+            None => (0..positions.len() / 3)
+                .map(|triangle| {
+                    [
+                        vertex(triangle * 3),
+                        vertex(triangle * 3 + 1),
+                        vertex(triangle * 3 + 2),
+                    ]
+                })
+                .collect(),
+        };
+        Some(MeshBvh::new(triangles))
+    }
+
+    pub fn new(triangles: Vec<[Vec3; 3]>) -> MeshBvh {
+        let mut bvh = MeshBvh {
+            triangle_indices: (0..triangles.len()).collect(),
+            triangles,
+            nodes: Vec::new(),
+        };
+        if !bvh.triangles.is_empty() {
+            bvh.build(0, bvh.triangles.len());
+        }
+        bvh
+    }
+
+    /// Builds the node for `triangles[start..end]` and returns its index
+    fn build(&mut self, start: usize, end: usize) -> usize {
+        let aabb = Aabb::from_points(self.triangles[start..end].iter().flatten().copied());
+        let node_index = self.nodes.len();
+        if end - start <= MAX_LEAF_TRIANGLES {
+            self.nodes.push(BvhNode::Leaf {
+                aabb,
+                first_triangle: start,
+                triangle_count: end - start,
+            });
+            return node_index;
+        }
+
+        // split at the median centroid along the longest axis
+        let extent = aabb.max - aabb.min;
+        let axis = if extent.x >= extent.y && extent.x >= extent.z {
+            0
+        } else if extent.y >= extent.z {
+            1
+        } else {
+            2
+        };
+        let centroid = |triangle: &[Vec3; 3]| {
+            let centroid = <[f32; 3]>::from(triangle[0] + triangle[1] + triangle[2]);
+            centroid[axis]
+        };
+        let mut order = (start..end).collect::<Vec<_>>();
+        order.sort_by(|a, b| {
+            centroid(&self.triangles[*a])
+                .partial_cmp(&centroid(&self.triangles[*b]))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        let triangles = order.iter().map(|i| self.triangles[*i]).collect::<Vec<_>>();
+        let triangle_indices = order
+            .iter()
+            .map(|i| self.triangle_indices[*i])
+            .collect::<Vec<_>>();
+        self.triangles[start..end].copy_from_slice(&triangles);
+        self.triangle_indices[start..end].copy_from_slice(&triangle_indices);
+
+        // reserve this node's slot before building the children
+        self.nodes.push(BvhNode::Leaf {
+            aabb,
+            first_triangle: start,
+            triangle_count: 0,
+        });
+        let middle = start + (end - start) / 2;
+        let left = self.build(start, middle);
+        let right = self.build(middle, end);
+        self.nodes[node_index] = BvhNode::Branch { aabb, left, right };
+        node_index
+    }
+
+    pub fn aabb(&self) -> Option<&Aabb> {
+        self.nodes.first().map(|node| node.aabb())
+    }
+
+    /// Returns the closest hit and the index of the triangle that was hit
+    pub fn cast_ray(&self, ray: &Ray) -> Option<(TriangleHit, usize)> {
+        let mut closest: Option<(TriangleHit, usize)> = None;
+        let mut stack = Vec::new();
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+            match ray.intersect_aabb(node.aabb()) {
+                Some(distance)
+                    if closest.map_or(true, |(closest, _)| distance <= closest.distance) => {}
+                _ => continue,
+            }
+            match *node {
+                BvhNode::Leaf {
+                    first_triangle,
+                    triangle_count,
+                    ..
+                } => {
+                    for i in first_triangle..first_triangle + triangle_count {
+                        if let Some(hit) = ray_triangle_intersection(ray, &self.triangles[i]) {
+                            if closest.map_or(true, |(closest, _)| hit.distance < closest.distance)
+                            {
+                                closest = Some((hit, self.triangle_indices[i]));
+                            }
+                        }
+                    }
+                }
+                BvhNode::Branch { left, right, .. } => {
+                    stack.push(left);
+                    stack.push(right);
+                }
+            }
+        }
+        closest
+    }
+}
+
+/// The [MeshBvh] of every loaded [Mesh], kept up to date by [mesh_bvh_system]
+#[derive(Debug, Default)]
+pub struct MeshBvhs {
+    bvhs: HashMap<Handle<Mesh>, MeshBvh>,
+}
+
+impl MeshBvhs {
+    pub fn get(&self, mesh: &Handle<Mesh>) -> Option<&MeshBvh> {
+        self.bvhs.get(mesh)
+    }
+}
+
+pub fn mesh_bvh_system(
+    mut mesh_bvhs: ResMut<MeshBvhs>,
+    meshes: Res<Assets<Mesh>>,
+    mut mesh_events: EventReader<AssetEvent<Mesh>>,
+) {
+    for event in mesh_events.iter() {
+        match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
+                match meshes.get(handle).and_then(MeshBvh::from_mesh) {
+                    Some(bvh) => mesh_bvhs.bvhs.insert(handle.clone_weak(), bvh),
+                    None => mesh_bvhs.bvhs.remove(handle),
+                };
+            }
+            AssetEvent::Removed { handle } => {
+                mesh_bvhs.bvhs.remove(handle);
+            }
+        }
+    }
+}
+
+/// An entity hit by a ray
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayHit {
+    pub entity: Entity,
+    /// The distance along the ray, in world units
+    pub distance: f32,
+    /// The world space position of the hit
+    pub position: Vec3,
+    /// The index of the triangle that was hit in the entity's mesh
+    pub triangle: usize,
+    /// The weights of the triangle's vertices at the hit position
+    pub barycentric: Vec3,
+}
+
+/// Casts rays against the meshes of [Pickable] entities on the cpu
+#[derive(SystemParam)]
+pub struct RayCast<'a> {
+    mesh_bvhs: Res<'a, MeshBvhs>,
+    query: Query<'a, (Entity, &'static Handle<Mesh>, &'static GlobalTransform), With<Pickable>>,
+}
+
+impl<'a> RayCast<'a> {
+    /// Returns the closest entity hit by the ray
+    pub fn cast_ray(&self, ray: &Ray) -> Option<RayHit> {
+        let mut closest: Option<RayHit> = None;
+        for (entity, mesh, transform) in self.query.iter() {
+            let bvh = match self.mesh_bvhs.get(mesh) {
+                Some(bvh) => bvh,
+                None => continue,
+            };
+            // casting in mesh space avoids transforming every triangle. the direction isn't
+            // renormalized so distances stay in world units.
+            let world_to_mesh = transform.compute_matrix().inverse();
+            let local_ray = Ray {
+                origin: world_to_mesh.transform_point3(ray.origin),
+                direction: world_to_mesh.transform_vector3(ray.direction),
+            };
+            let (hit, triangle) = match bvh.cast_ray(&local_ray) {
+                Some(hit) => hit,
+                None => continue,
+            };
+            if closest.map_or(true, |closest| hit.distance < closest.distance) {
+                closest = Some(RayHit {
+                    entity,
+                    distance: hit.distance,
+                    position: ray.at(hit.distance),
+                    triangle,
+                    barycentric: hit.barycentric,
+                });
+            }
+        }
+        closest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::shape;
+
+    #[test]
+    fn ray_triangle() {
+        let triangle = [
+            Vec3::new(-1.0, -1.0, 0.0),
+            Vec3::new(1.0, -1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        ];
+        let hit =
+            ray_triangle_intersection(&Ray::new(Vec3::new(0.0, 0.0, 5.0), -Vec3::Z), &triangle)
+                .unwrap();
+        assert!((hit.distance - 5.0).abs() < 1e-5);
+        assert!((hit.barycentric.x + hit.barycentric.y + hit.barycentric.z - 1.0).abs() < 1e-5);
+
+        // pointing away
+        assert!(
+            ray_triangle_intersection(&Ray::new(Vec3::new(0.0, 0.0, 5.0), Vec3::Z), &triangle)
+                .is_none()
+        );
+        // missing to the side
+        assert!(ray_triangle_intersection(
+            &Ray::new(Vec3::new(3.0, 0.0, 5.0), -Vec3::Z),
+            &triangle
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn ray_aabb() {
+        let aabb = Aabb::from_points(vec![-Vec3::ONE, Vec3::ONE]);
+        assert_eq!(
+            Ray::new(Vec3::new(0.0, 0.0, -5.0), Vec3::Z).intersect_aabb(&aabb),
+            Some(4.0)
+        );
+        assert_eq!(
+            Ray::new(Vec3::ZERO, Vec3::Z).intersect_aabb(&aabb),
+            Some(0.0)
+        );
+        assert_eq!(
+            Ray::new(Vec3::new(0.0, 0.0, -5.0), -Vec3::Z).intersect_aabb(&aabb),
+            None
+        );
+    }
+
+    #[test]
+    fn bvh_matches_brute_force() {
+        let mesh = Mesh::from(shape::Icosphere {
+            radius: 1.0,
+            subdivisions: 3,
+        });
+        let bvh = MeshBvh::from_mesh(&mesh).unwrap();
+        let brute_force = MeshBvh {
+            nodes: vec![BvhNode::Leaf {
+                aabb: *bvh.aabb().unwrap(),
+                first_triangle: 0,
+                triangle_count: bvh.triangles.len(),
+            }],
+            ..bvh.clone()
+        };
+        for i in 0..32 {
+            let angle = i as f32 * 0.4;
+            let origin = Vec3::new(angle.cos() * 3.0, (i as f32 * 0.1) - 1.5, angle.sin() * 3.0);
+            let ray = Ray::new(origin, -origin + Vec3::new(0.0, 0.3, 0.0));
+            let hit = bvh.cast_ray(&ray).map(|(hit, _)| hit.distance);
+            let expected = brute_force.cast_ray(&ray).map(|(hit, _)| hit.distance);
+            assert_eq!(hit, expected);
+        }
+
+        let hit = bvh
+            .cast_ray(&Ray::new(Vec3::new(0.0, 0.0, 5.0), -Vec3::Z))
+            .unwrap();
+        assert!((hit.0.distance - 4.0).abs() < 0.05);
+    }
+}
@@ -0,0 +1,39 @@
+use super::PipelineLayout;
+use crate::shader::Shader;
+use bevy_asset::Handle;
+use bevy_reflect::TypeUuid;
+
+/// A pipeline that runs a compute shader. Compile it with
+/// [PipelineCompiler::compile_compute_pipeline](super::PipelineCompiler::compile_compute_pipeline)
+/// and run it in a [ComputePass](crate::pass::ComputePass).
+#[derive(Clone, Debug, TypeUuid)]
+#[uuid = "2e8b3c7d-5f0a-4a61-9c1e-6d4b8f2a7e35"]
+pub struct ComputePipelineDescriptor {
+    pub name: Option<String>,
+    pub layout: Option<PipelineLayout>,
+    pub shader: Handle<Shader>,
+    /// The names of the texture bindings sampled from depth textures. GLSL can't declare depth
+    /// textures that aren't compared against, so reflection gives them a float sample type.
+    pub depth_textures: Vec<String>,
+}
+
+impl ComputePipelineDescriptor {
+    pub fn new(shader: Handle<Shader>) -> Self {
+        ComputePipelineDescriptor {
+            name: None,
+            layout: None,
+            shader,
+            depth_textures: Vec::new(),
+        }
+    }
+
+    /// Binds the texture named `name` as a depth texture
+    pub fn with_depth_texture(mut self, name: &str) -> Self {
+        self.depth_textures.push(name.to_string());
+        self
+    }
+
+    pub fn get_layout(&self) -> Option<&PipelineLayout> {
+        self.layout.as_ref()
+    }
+}
@@ -0,0 +1,7 @@
+#version 450
+
+layout(location = 0) out vec4 o_Target;
+
+void main() {
+    o_Target = vec4(1.0, 0.0, 1.0, 1.0);
+}
@@ -1,14 +1,11 @@
 use super::{
-    state_descriptors::{
-        BlendFactor, BlendOperation, ColorWrite, CompareFunction, CullMode, FrontFace,
-        PrimitiveTopology,
-    },
+    state_descriptors::{CompareFunction, CullMode, FrontFace, PrimitiveTopology},
     PipelineLayout,
 };
 use crate::{
     pipeline::{
-        BlendState, ColorTargetState, DepthBiasState, DepthStencilState, MultisampleState,
-        PolygonMode, PrimitiveState, StencilFaceState, StencilState,
+        BlendMode, ColorTargetState, DepthBiasState, DepthStencilState, MultisampleState,
+        PolygonMode, PrimitiveState, StencilState,
     },
     shader::ShaderStages,
     texture::TextureFormat,
@@ -67,12 +64,7 @@ impl PipelineDescriptor {
                 format: TextureFormat::Depth32Float,
                 depth_write_enabled: true,
                 depth_compare: CompareFunction::Less,
-                stencil: StencilState {
-                    front: StencilFaceState::IGNORE,
-                    back: StencilFaceState::IGNORE,
-                    read_mask: 0,
-                    write_mask: 0,
-                },
+                stencil: StencilState::IGNORE,
                 bias: DepthBiasState {
                     constant: 0,
                     slope_scale: 0.0,
@@ -80,20 +72,10 @@ impl PipelineDescriptor {
                 },
                 clamp_depth: false,
             }),
-            color_target_states: vec![ColorTargetState {
-                format: TextureFormat::default(),
-                color_blend: BlendState {
-                    src_factor: BlendFactor::SrcAlpha,
-                    dst_factor: BlendFactor::OneMinusSrcAlpha,
-                    operation: BlendOperation::Add,
-                },
-                alpha_blend: BlendState {
-                    src_factor: BlendFactor::One,
-                    dst_factor: BlendFactor::One,
-                    operation: BlendOperation::Add,
-                },
-                write_mask: ColorWrite::ALL,
-            }],
+            color_target_states: vec![ColorTargetState::new(
+                TextureFormat::default(),
+                BlendMode::Alpha,
+            )],
             multisample: MultisampleState {
                 count: 1,
                 mask: !0,
@@ -103,6 +85,13 @@ impl PipelineDescriptor {
         }
     }
 
+    /// Blends every color target with `blend_mode`
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) {
+        for color_target_state in self.color_target_states.iter_mut() {
+            color_target_state.set_blend_mode(blend_mode);
+        }
+    }
+
     pub fn get_layout(&self) -> Option<&PipelineLayout> {
         self.layout.as_ref()
     }
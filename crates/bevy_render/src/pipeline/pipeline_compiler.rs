@@ -1,15 +1,40 @@
 use super::{state_descriptors::PrimitiveTopology, IndexFormat, PipelineDescriptor};
 use crate::{
-    pipeline::{BindType, InputStepMode, VertexBufferLayout},
+    pipeline::{
+        BindType, BlendMode, ComputePipelineDescriptor, InputStepMode, PipelineLayout,
+        VertexBufferLayout,
+    },
     renderer::RenderResourceContext,
-    shader::{Shader, ShaderError},
+    shader::{Shader, ShaderCompileError, ShaderError, ShaderStage},
+    texture::{TextureFormat, TextureSampleType},
 };
-use bevy_asset::{Assets, Handle};
-use bevy_reflect::{Reflect, ReflectDeserialize};
-use bevy_utils::{HashMap, HashSet};
+use bevy_asset::{Assets, Handle, HandleUntyped};
+use bevy_reflect::{Reflect, ReflectDeserialize, TypeUuid};
+use bevy_utils::{tracing::error, HashMap, HashSet};
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 
+/// Replaces the vertex shader of pipelines whose vertex shader failed to compile. It only reads
+/// `Vertex_Position`, `CameraViewProj` and `Transform`, which nearly every entity provides.
+pub const ERROR_VERTEX_SHADER_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(Shader::TYPE_UUID, 0x5a0e3c9d17f26b48);
+
+/// Replaces the fragment shader of pipelines with a shader that failed to compile. It draws solid
+/// magenta, so the broken entities stand out where they are.
+pub const ERROR_FRAGMENT_SHADER_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(Shader::TYPE_UUID, 0x93b7d41e26c0a5f1);
+
+pub(crate) fn build_error_shaders(shaders: &mut Assets<Shader>) {
+    shaders.set_untracked(
+        ERROR_VERTEX_SHADER_HANDLE,
+        Shader::from_glsl(ShaderStage::Vertex, include_str!("error.vert")),
+    );
+    shaders.set_untracked(
+        ERROR_FRAGMENT_SHADER_HANDLE,
+        Shader::from_glsl(ShaderStage::Fragment, include_str!("error.frag")),
+    );
+}
+
 #[derive(Clone, Eq, PartialEq, Debug, Reflect)]
 #[reflect(PartialEq)]
 pub struct PipelineSpecialization {
@@ -19,6 +44,18 @@ pub struct PipelineSpecialization {
     pub strip_index_format: Option<IndexFormat>,
     pub vertex_buffer_layout: VertexBufferLayout,
     pub sample_count: u32,
+    /// Overrides the format of every color target, for passes that don't render to the swap chain
+    /// format
+    #[reflect(ignore)]
+    pub color_target_format: Option<TextureFormat>,
+    /// Overrides the blending of every color target, so entities sharing a pipeline can pick a
+    /// [BlendMode] per material
+    pub blend_mode: Option<BlendMode>,
+    /// Overrides the format of the depth stencil state, for passes using the main depth texture
+    #[reflect(ignore)]
+    pub depth_format: Option<TextureFormat>,
+    /// Flips the depth test, see [Depth](crate::render_graph::base::Depth)
+    pub reversed_z: bool,
 }
 
 impl Default for PipelineSpecialization {
@@ -30,6 +67,10 @@ impl Default for PipelineSpecialization {
             primitive_topology: Default::default(),
             dynamic_bindings: Default::default(),
             vertex_buffer_layout: Default::default(),
+            color_target_format: None,
+            blend_mode: None,
+            depth_format: None,
+            reversed_z: false,
         }
     }
 }
@@ -59,11 +100,23 @@ struct SpecializedPipeline {
     specialization: PipelineSpecialization,
 }
 
+#[derive(Debug)]
+struct SpecializedComputePipeline {
+    pipeline: Handle<ComputePipelineDescriptor>,
+    specialization: ShaderSpecialization,
+}
+
 #[derive(Debug, Default)]
 pub struct PipelineCompiler {
     specialized_shaders: HashMap<Handle<Shader>, Vec<SpecializedShader>>,
     specialized_shader_pipelines: HashMap<Handle<Shader>, Vec<Handle<PipelineDescriptor>>>,
     specialized_pipelines: HashMap<Handle<PipelineDescriptor>, Vec<SpecializedPipeline>>,
+    specialized_compute_pipelines:
+        HashMap<Handle<ComputePipelineDescriptor>, Vec<SpecializedComputePipeline>>,
+    /// The source pipelines drawn with the error shaders, by the shader that failed
+    failed_shader_pipelines: HashMap<Handle<Shader>, Vec<Handle<PipelineDescriptor>>>,
+    /// The shaders that failed since the errors were last taken
+    compile_errors: Vec<ShaderCompileError>,
 }
 
 impl PipelineCompiler {
@@ -79,7 +132,7 @@ impl PipelineCompiler {
             .entry(shader_handle.clone_weak())
             .or_insert_with(Vec::new);
 
-        let shader = shaders.get(shader_handle).unwrap();
+        let shader = shaders.get(shader_handle).ok_or(ShaderError::NotLoaded)?;
 
         if let Some(specialized_shader) =
             specialized_shaders
@@ -109,6 +162,46 @@ impl PipelineCompiler {
         }
     }
 
+    /// Compiles a shader of `source_pipeline`, returning None and recording the error if it fails
+    fn compile_shader_or_record_error(
+        &mut self,
+        render_resource_context: &dyn RenderResourceContext,
+        shaders: &mut Assets<Shader>,
+        shader_handle: &Handle<Shader>,
+        shader_specialization: &ShaderSpecialization,
+        source_pipeline: &Handle<PipelineDescriptor>,
+    ) -> Option<Handle<Shader>> {
+        match self.compile_shader(
+            render_resource_context,
+            shaders,
+            shader_handle,
+            shader_specialization,
+        ) {
+            Ok(shader) => Some(shader),
+            Err(err) => {
+                let message = trim_shader_error(&err);
+                error!(
+                    "Failed to compile a shader, its pipeline draws with the error shaders. {}",
+                    message
+                );
+                self.failed_shader_pipelines
+                    .entry(shader_handle.clone_weak())
+                    .or_insert_with(Vec::new)
+                    .push(source_pipeline.clone_weak());
+                self.compile_errors.push(ShaderCompileError {
+                    shader: shader_handle.clone_weak(),
+                    message,
+                });
+                None
+            }
+        }
+    }
+
+    /// Returns the errors of the shaders that failed to compile since the last call
+    pub fn take_compile_errors(&mut self) -> Vec<ShaderCompileError> {
+        std::mem::take(&mut self.compile_errors)
+    }
+
     pub fn get_specialized_pipeline(
         &self,
         pipeline: &Handle<PipelineDescriptor>,
@@ -126,6 +219,9 @@ impl PipelineCompiler {
             .map(|specialized_pipeline| specialized_pipeline.pipeline.clone_weak())
     }
 
+    /// Compiles `source_pipeline` with `pipeline_specialization`. Returns None while one of its
+    /// shaders isn't loaded, like meshes that aren't loaded yet, so the pipeline is compiled by
+    /// the first draw after the shader is created.
     pub fn compile_pipeline(
         &mut self,
         render_resource_context: &dyn RenderResourceContext,
@@ -133,35 +229,71 @@ impl PipelineCompiler {
         shaders: &mut Assets<Shader>,
         source_pipeline: &Handle<PipelineDescriptor>,
         pipeline_specialization: &PipelineSpecialization,
-    ) -> Handle<PipelineDescriptor> {
+    ) -> Option<Handle<PipelineDescriptor>> {
         let source_descriptor = pipelines.get(source_pipeline).unwrap();
+        if source_descriptor
+            .shader_stages
+            .iter()
+            .any(|shader| !shaders.contains(&shader))
+        {
+            return None;
+        }
         let mut specialized_descriptor = source_descriptor.clone();
-        let specialized_vertex_shader = self
-            .compile_shader(
-                render_resource_context,
-                shaders,
-                &specialized_descriptor.shader_stages.vertex,
-                &pipeline_specialization.shader_specialization,
-            )
-            .unwrap_or_else(|e| panic_shader_error(e));
-        specialized_descriptor.shader_stages.vertex = specialized_vertex_shader.clone_weak();
-        let mut specialized_fragment_shader = None;
-        specialized_descriptor.shader_stages.fragment = specialized_descriptor
+        let shader_specialization = &pipeline_specialization.shader_specialization;
+        let vertex = self.compile_shader_or_record_error(
+            render_resource_context,
+            shaders,
+            &specialized_descriptor.shader_stages.vertex,
+            shader_specialization,
+            source_pipeline,
+        );
+        let fragment = specialized_descriptor
             .shader_stages
             .fragment
             .as_ref()
             .map(|fragment| {
-                let shader = self
-                    .compile_shader(
+                self.compile_shader_or_record_error(
+                    render_resource_context,
+                    shaders,
+                    fragment,
+                    shader_specialization,
+                    source_pipeline,
+                )
+            });
+        // a failed fragment shader keeps the vertex shader, but the error fragment shader can't
+        // read the outputs of an arbitrary vertex shader
+        let (vertex, fragment) = match (vertex, fragment) {
+            (Some(vertex), fragment) => (
+                vertex,
+                fragment.map(|fragment| {
+                    fragment.unwrap_or_else(|| {
+                        self.compile_error_shader(
+                            render_resource_context,
+                            shaders,
+                            ERROR_FRAGMENT_SHADER_HANDLE,
+                        )
+                    })
+                }),
+            ),
+            (None, fragment) => (
+                self.compile_error_shader(
+                    render_resource_context,
+                    shaders,
+                    ERROR_VERTEX_SHADER_HANDLE,
+                ),
+                fragment.map(|_| {
+                    self.compile_error_shader(
                         render_resource_context,
                         shaders,
-                        fragment,
-                        &pipeline_specialization.shader_specialization,
+                        ERROR_FRAGMENT_SHADER_HANDLE,
                     )
-                    .unwrap_or_else(|e| panic_shader_error(e));
-                specialized_fragment_shader = Some(shader.clone_weak());
-                shader
-            });
+                }),
+            ),
+        };
+        let specialized_vertex_shader = vertex.clone_weak();
+        let specialized_fragment_shader = fragment.as_ref().map(Handle::clone_weak);
+        specialized_descriptor.shader_stages.vertex = vertex;
+        specialized_descriptor.shader_stages.fragment = fragment;
 
         let mut layout = render_resource_context.reflect_pipeline_layout(
             &shaders,
@@ -247,6 +379,24 @@ impl PipelineCompiler {
         specialized_descriptor.primitive.topology = pipeline_specialization.primitive_topology;
         specialized_descriptor.primitive.strip_index_format =
             pipeline_specialization.strip_index_format;
+        if let Some(format) = pipeline_specialization.color_target_format {
+            for color_target_state in specialized_descriptor.color_target_states.iter_mut() {
+                color_target_state.format = format;
+            }
+        }
+        if let Some(blend_mode) = pipeline_specialization.blend_mode {
+            specialized_descriptor.set_blend_mode(blend_mode);
+        }
+        if let Some(depth_stencil) = specialized_descriptor.depth_stencil.as_mut() {
+            if let Some(format) = pipeline_specialization.depth_format {
+                depth_stencil.format = format;
+            }
+            if pipeline_specialization.reversed_z {
+                depth_stencil.depth_compare = depth_stencil.depth_compare.reversed();
+                depth_stencil.bias.constant = -depth_stencil.bias.constant;
+                depth_stencil.bias.slope_scale = -depth_stencil.bias.slope_scale;
+            }
+        }
 
         let specialized_pipeline_handle = pipelines.add(specialized_descriptor);
         render_resource_context.create_render_pipeline(
@@ -277,6 +427,104 @@ impl PipelineCompiler {
             specialization: pipeline_specialization.clone(),
         });
 
+        Some(weak_specialized_pipeline_handle)
+    }
+
+    fn compile_error_shader(
+        &mut self,
+        render_resource_context: &dyn RenderResourceContext,
+        shaders: &mut Assets<Shader>,
+        error_shader: HandleUntyped,
+    ) -> Handle<Shader> {
+        self.compile_shader(
+            render_resource_context,
+            shaders,
+            &error_shader.typed(),
+            &ShaderSpecialization::default(),
+        )
+        .unwrap_or_else(|e| panic_shader_error(e))
+    }
+
+    pub fn get_specialized_compute_pipeline(
+        &self,
+        pipeline: &Handle<ComputePipelineDescriptor>,
+        specialization: &ShaderSpecialization,
+    ) -> Option<Handle<ComputePipelineDescriptor>> {
+        self.specialized_compute_pipelines
+            .get(pipeline)
+            .and_then(|specialized_pipelines| {
+                specialized_pipelines
+                    .iter()
+                    .find(|current_specialized_pipeline| {
+                        &current_specialized_pipeline.specialization == specialization
+                    })
+            })
+            .map(|specialized_pipeline| specialized_pipeline.pipeline.clone_weak())
+    }
+
+    pub fn compile_compute_pipeline(
+        &mut self,
+        render_resource_context: &dyn RenderResourceContext,
+        pipelines: &mut Assets<ComputePipelineDescriptor>,
+        shaders: &mut Assets<Shader>,
+        source_pipeline: &Handle<ComputePipelineDescriptor>,
+        shader_specialization: &ShaderSpecialization,
+    ) -> Handle<ComputePipelineDescriptor> {
+        let mut specialized_descriptor = pipelines.get(source_pipeline).unwrap().clone();
+        specialized_descriptor.shader = self
+            .compile_shader(
+                render_resource_context,
+                shaders,
+                &specialized_descriptor.shader,
+                shader_specialization,
+            )
+            .unwrap_or_else(|e| panic_shader_error(e));
+        let shader_layout = shaders
+            .get(&specialized_descriptor.shader)
+            .unwrap()
+            .reflect_layout(false)
+            .unwrap();
+        let mut layout = PipelineLayout::from_shader_layouts(&mut [shader_layout]);
+        for bind_group in layout.bind_groups.iter_mut() {
+            let mut binding_changed = false;
+            for binding in bind_group.bindings.iter_mut() {
+                if specialized_descriptor
+                    .depth_textures
+                    .contains(&binding.name)
+                {
+                    if let BindType::Texture {
+                        ref mut sample_type,
+                        ..
+                    } = binding.bind_type
+                    {
+                        *sample_type = TextureSampleType::Depth;
+                        binding_changed = true;
+                    }
+                }
+            }
+
+            if binding_changed {
+                bind_group.update_id();
+            }
+        }
+        specialized_descriptor.layout = Some(layout);
+
+        let specialized_pipeline_handle = pipelines.add(specialized_descriptor);
+        render_resource_context.create_compute_pipeline(
+            specialized_pipeline_handle.clone_weak(),
+            pipelines.get(&specialized_pipeline_handle).unwrap(),
+            &shaders,
+        );
+
+        let weak_specialized_pipeline_handle = specialized_pipeline_handle.clone_weak();
+        self.specialized_compute_pipelines
+            .entry(source_pipeline.clone_weak())
+            .or_insert_with(Vec::new)
+            .push(SpecializedComputePipeline {
+                pipeline: specialized_pipeline_handle,
+                specialization: shader_specialization.clone(),
+            });
+
         weak_specialized_pipeline_handle
     }
 
@@ -313,6 +561,18 @@ impl PipelineCompiler {
         shaders: &mut Assets<Shader>,
         render_resource_context: &dyn RenderResourceContext,
     ) -> Result<(), ShaderError> {
+        // pipelines drawn with the error shaders are compiled again on their next draw
+        if let Some(source_pipelines) = self.failed_shader_pipelines.remove(shader) {
+            for source_pipeline in source_pipelines {
+                if let Some(specialized_pipelines) =
+                    self.specialized_pipelines.remove(&source_pipeline)
+                {
+                    for p in specialized_pipelines {
+                        pipelines.remove(p.pipeline);
+                    }
+                }
+            }
+        }
         if let Some(specialized_shaders) = self.specialized_shaders.get_mut(shader) {
             for specialized_shader in specialized_shaders {
                 // Recompile specialized shader. If it fails, we bail immediately.
@@ -357,11 +617,125 @@ impl PipelineCompiler {
     }
 }
 
-fn panic_shader_error(error: ShaderError) -> ! {
+fn trim_shader_error(error: &ShaderError) -> String {
     let msg = error.to_string();
-    let msg = msg
-        .trim_end()
+    msg.trim_end()
         .trim_end_matches("Debug log:") // if this matches, then there wasn't a debug log anyways
-        .trim_end();
-    panic!("{}\n", msg);
+        .trim_end()
+        .to_string()
+}
+
+fn panic_shader_error(error: ShaderError) -> ! {
+    panic!("{}\n", trim_shader_error(&error));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        pipeline::{VertexAttribute, VertexFormat},
+        renderer::HeadlessRenderResourceContext,
+        shader::ShaderStages,
+    };
+    use bevy_app::App;
+    use bevy_asset::{AddAsset, AssetPlugin, HandleId};
+    use bevy_core::CorePlugin;
+
+    const VERTEX_SHADER: &str = r#"
+#version 450
+layout(location = 0) in vec3 Vertex_Position;
+void main() {
+    gl_Position = vec4(Vertex_Position, 1.0);
+}
+"#;
+
+    fn assets() -> (Assets<PipelineDescriptor>, Assets<Shader>) {
+        let mut app = App::build();
+        app.add_plugin(CorePlugin)
+            .add_plugin(AssetPlugin)
+            .add_asset::<PipelineDescriptor>()
+            .add_asset::<Shader>();
+        let world = app.world_mut();
+        (
+            world.remove_resource().unwrap(),
+            world.remove_resource().unwrap(),
+        )
+    }
+
+    fn specialization() -> PipelineSpecialization {
+        PipelineSpecialization {
+            vertex_buffer_layout: VertexBufferLayout {
+                name: "Mesh".into(),
+                stride: 12,
+                step_mode: InputStepMode::Vertex,
+                attributes: vec![VertexAttribute {
+                    name: "Vertex_Position".into(),
+                    format: VertexFormat::Float3,
+                    offset: 0,
+                    shader_location: 0,
+                }],
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn compiles_pipelines_once_their_shaders_load() {
+        let (mut pipelines, mut shaders) = assets();
+        let context = HeadlessRenderResourceContext::default();
+        let mut compiler = PipelineCompiler::default();
+        let vertex = Handle::<Shader>::weak(HandleId::random::<Shader>());
+        let pipeline = pipelines.add(PipelineDescriptor::default_config(ShaderStages {
+            vertex: vertex.clone(),
+            fragment: None,
+        }));
+
+        let mut compile = |pipelines: &mut Assets<PipelineDescriptor>,
+                           shaders: &mut Assets<Shader>| {
+            compiler.compile_pipeline(&context, pipelines, shaders, &pipeline, &specialization())
+        };
+        assert!(compile(&mut pipelines, &mut shaders).is_none());
+        shaders.set_untracked(
+            vertex,
+            Shader::from_glsl(ShaderStage::Vertex, VERTEX_SHADER),
+        );
+        assert!(compile(&mut pipelines, &mut shaders).is_some());
+        assert!(compiler.take_compile_errors().is_empty());
+    }
+
+    #[test]
+    fn draws_failed_shaders_with_the_error_shaders() {
+        let (mut pipelines, mut shaders) = assets();
+        build_error_shaders(&mut shaders);
+        let context = HeadlessRenderResourceContext::default();
+        let mut compiler = PipelineCompiler::default();
+        let fragment = shaders.add(Shader::from_glsl(
+            ShaderStage::Fragment,
+            "#version 450\nvoid main() { undefined_function(); }",
+        ));
+        let pipeline = pipelines.add(PipelineDescriptor::default_config(ShaderStages {
+            vertex: shaders.add(Shader::from_glsl(ShaderStage::Vertex, VERTEX_SHADER)),
+            fragment: Some(fragment.clone()),
+        }));
+
+        let specialized = compiler
+            .compile_pipeline(
+                &context,
+                &mut pipelines,
+                &mut shaders,
+                &pipeline,
+                &specialization(),
+            )
+            .unwrap();
+        let error_fragment = &compiler.specialized_shaders
+            [&ERROR_FRAGMENT_SHADER_HANDLE.typed::<Shader>()][0]
+            .shader;
+        assert_eq!(
+            pipelines.get(specialized).unwrap().shader_stages.fragment,
+            Some(error_fragment.clone_weak())
+        );
+        let errors = compiler.take_compile_errors();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].shader, fragment);
+    }
 }
@@ -1,7 +1,7 @@
 use super::{BindGroupDescriptor, VertexBufferLayout};
 use crate::shader::ShaderLayout;
 use bevy_utils::HashMap;
-use std::hash::Hash;
+use std::{error::Error, fmt, hash::Hash};
 
 #[derive(Clone, Debug, Default)]
 pub struct PipelineLayout {
@@ -102,4 +102,162 @@ impl UniformProperty {
             UniformProperty::Array(property, length) => property.get_size() * *length as u64,
         }
     }
+
+    /// The alignment of the property under the std140 layout rules used by uniform blocks
+    pub fn get_std140_alignment(&self) -> u64 {
+        match self {
+            UniformProperty::UInt | UniformProperty::Int | UniformProperty::Float => 4,
+            UniformProperty::IVec2 | UniformProperty::Vec2 => 8,
+            UniformProperty::Vec3
+            | UniformProperty::Vec4
+            | UniformProperty::UVec4
+            | UniformProperty::Mat3
+            | UniformProperty::Mat4 => 16,
+            UniformProperty::Struct(properties) => round_up(
+                properties
+                    .iter()
+                    .map(|property| property.get_std140_alignment())
+                    .max()
+                    .unwrap_or(0),
+                16,
+            ),
+            UniformProperty::Array(property, _) => round_up(property.get_std140_alignment(), 16),
+        }
+    }
+
+    /// The size of the property under the std140 layout rules, including any padding it requires
+    /// when nested in a struct or array
+    pub fn get_std140_size(&self) -> u64 {
+        match self {
+            UniformProperty::Struct(properties) => {
+                round_up(std140_block_size(properties), self.get_std140_alignment())
+            }
+            UniformProperty::Array(property, length) => {
+                round_up(property.get_std140_size(), 16) * *length as u64
+            }
+            _ => self.get_size(),
+        }
+    }
+}
+
+fn round_up(value: u64, alignment: u64) -> u64 {
+    if alignment == 0 {
+        value
+    } else {
+        (value + alignment - 1) / alignment * alignment
+    }
+}
+
+/// Returns the std140 offsets of each property in a struct
+pub fn std140_offsets(properties: &[UniformProperty]) -> Vec<u64> {
+    let mut offset = 0;
+    properties
+        .iter()
+        .map(|property| {
+            let property_offset = round_up(offset, property.get_std140_alignment());
+            offset = property_offset + property.get_std140_size();
+            property_offset
+        })
+        .collect()
+}
+
+/// The number of bytes a uniform block with the given members reads. Unlike nested structs, the
+/// end of a block is not padded.
+pub fn std140_block_size(properties: &[UniformProperty]) -> u64 {
+    std140_offsets(properties).last().map_or(0, |offset| {
+        offset + properties.last().unwrap().get_std140_size()
+    })
+}
+
+/// The std140 layout of a uniform block doesn't match the bytes written for it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UniformLayoutError {
+    pub name: String,
+    /// The number of bytes written for the uniform
+    pub actual_size: u64,
+    /// The number of bytes the shader reads
+    pub expected_size: u64,
+    /// The reflected members of the uniform block, along with their std140 offsets
+    pub members: Vec<(UniformProperty, u64)>,
+}
+
+impl fmt::Display for UniformLayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Uniform `{}` is {} bytes, but the shader expects {} bytes under std140 layout rules. \
+            vec3s, mat3s and arrays are padded to 16 bytes in uniform blocks. Expected layout:",
+            self.name, self.actual_size, self.expected_size
+        )?;
+        for (property, offset) in self.members.iter() {
+            write!(f, " {:?} at offset {};", property, offset)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for UniformLayoutError {}
+
+/// Validates that `actual_size` bytes match the std140 layout of the reflected uniform `property`
+pub fn validate_uniform_layout(
+    name: &str,
+    actual_size: u64,
+    property: &UniformProperty,
+) -> Result<(), UniformLayoutError> {
+    let members = match property {
+        UniformProperty::Struct(properties) => properties.clone(),
+        property => vec![property.clone()],
+    };
+    let expected_size = std140_block_size(&members);
+    // trailing padding is allowed, as long as it doesn't amount to another member
+    if actual_size >= expected_size && actual_size <= round_up(expected_size, 16) {
+        return Ok(());
+    }
+
+    let offsets = std140_offsets(&members);
+    Err(UniformLayoutError {
+        name: name.to_string(),
+        actual_size,
+        expected_size,
+        members: members.into_iter().zip(offsets).collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn std140_layout() {
+        let properties = vec![
+            UniformProperty::Vec3,
+            UniformProperty::Float,
+            UniformProperty::Vec2,
+            UniformProperty::Vec3,
+            UniformProperty::Mat4,
+        ];
+        assert_eq!(std140_offsets(&properties), vec![0, 12, 16, 32, 48]);
+        assert_eq!(std140_block_size(&properties), 112);
+        assert_eq!(
+            UniformProperty::Array(Box::new(UniformProperty::Float), 4).get_std140_size(),
+            64
+        );
+    }
+
+    #[test]
+    fn uniform_layout_validation() {
+        let float = UniformProperty::Struct(vec![UniformProperty::Float]);
+        assert!(validate_uniform_layout("a", 4, &float).is_ok());
+        assert!(validate_uniform_layout("a", 16, &float).is_ok());
+
+        // two tightly packed vec3s
+        let vec3s = UniformProperty::Struct(vec![UniformProperty::Vec3, UniformProperty::Vec3]);
+        let error = validate_uniform_layout("b", 24, &vec3s).unwrap_err();
+        assert_eq!(error.expected_size, 28);
+        assert_eq!(
+            error.members,
+            vec![(UniformProperty::Vec3, 0), (UniformProperty::Vec3, 16)]
+        );
+        assert!(validate_uniform_layout("b", 32, &vec3s).is_ok());
+    }
 }
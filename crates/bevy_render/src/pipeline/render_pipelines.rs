@@ -2,8 +2,11 @@ use super::{PipelineDescriptor, PipelineSpecialization};
 use crate::{
     draw::{Draw, DrawContext, OutsideFrustum},
     mesh::{Indices, Mesh},
-    prelude::{Msaa, Visible},
-    renderer::RenderResourceBindings,
+    prelude::{Depth, Hdr, Msaa, Visible},
+    render_graph::base::MainPass,
+    renderer::{
+        DrawIndexedIndirectArgs, DrawIndirectArgs, IndirectBuffers, RenderResourceBindings,
+    },
 };
 use bevy_asset::{Assets, Handle};
 use bevy_ecs::{
@@ -18,6 +21,9 @@ use bevy_utils::HashSet;
 pub struct RenderPipeline {
     pub pipeline: Handle<PipelineDescriptor>,
     pub specialization: PipelineSpecialization,
+    /// The reference the pipeline's [StencilState](super::StencilState) compares against and
+    /// writes, set before every draw with this pipeline
+    pub stencil_reference: Option<u32>,
     /// used to track if PipelineSpecialization::dynamic_bindings is in sync with
     /// RenderResourceBindings
     pub dynamic_bindings_generation: usize,
@@ -28,6 +34,7 @@ impl RenderPipeline {
         RenderPipeline {
             specialization: Default::default(),
             pipeline,
+            stencil_reference: None,
             dynamic_bindings_generation: std::usize::MAX,
         }
     }
@@ -39,6 +46,7 @@ impl RenderPipeline {
         RenderPipeline {
             pipeline,
             specialization,
+            stencil_reference: None,
             dynamic_bindings_generation: std::usize::MAX,
         }
     }
@@ -82,17 +90,38 @@ impl Default for RenderPipelines {
     }
 }
 
+/// A marker component that draws an entity's mesh with arguments pushed to the
+/// [IndirectBuffers] of its pipelines instead of with direct draws
+///
+/// Consecutive indirect draws that bind the same state are issued as a single multi draw, and
+/// compute passes can rewrite the arguments before the main pass, for example to cull the draw.
+#[derive(Debug, Default, Clone, Reflect)]
+#[reflect(Component)]
+pub struct IndirectDraw;
+
 pub fn draw_render_pipelines_system(
     mut draw_context: DrawContext,
     mut render_resource_bindings: ResMut<RenderResourceBindings>,
     msaa: Res<Msaa>,
+    hdr: Res<Hdr>,
+    depth: Res<Depth>,
     meshes: Res<Assets<Mesh>>,
+    mut indirect_buffers: Option<ResMut<IndirectBuffers>>,
     mut query: Query<
-        (&mut Draw, &mut RenderPipelines, &Handle<Mesh>, &Visible),
+        (
+            &mut Draw,
+            &mut RenderPipelines,
+            &Handle<Mesh>,
+            &Visible,
+            Option<&MainPass>,
+            Option<&IndirectDraw>,
+        ),
         Without<OutsideFrustum>,
     >,
 ) {
-    for (mut draw, mut render_pipelines, mesh_handle, visible) in query.iter_mut() {
+    for (mut draw, mut render_pipelines, mesh_handle, visible, main_pass, indirect_draw) in
+        query.iter_mut()
+    {
         if !visible.is_visible {
             continue;
         }
@@ -113,6 +142,16 @@ pub fn draw_render_pipelines_system(
         let render_pipelines = &mut *render_pipelines;
         for pipeline in render_pipelines.pipelines.iter_mut() {
             pipeline.specialization.sample_count = msaa.samples;
+            depth.specialize(&mut pipeline.specialization);
+            pipeline.specialization.color_target_format = main_pass.and(hdr.main_pass_format());
+            if pipeline.specialization.color_target_format.is_some() {
+                // shader defs are cleared after every frame
+                pipeline
+                    .specialization
+                    .shader_specialization
+                    .shader_defs
+                    .insert(Hdr::SHADER_DEF.to_string());
+            }
             if pipeline.dynamic_bindings_generation
                 != render_pipelines.bindings.dynamic_bindings_generation()
             {
@@ -144,24 +183,60 @@ pub fn draw_render_pipelines_system(
                 &mut render_pipelines.bindings,
                 &mut render_resource_bindings,
             ];
-            draw_context
+            // don't render if the pipeline's shaders aren't loaded yet
+            if draw_context
                 .set_pipeline(
                     &mut draw,
                     &render_pipeline.pipeline,
                     &render_pipeline.specialization,
                 )
-                .unwrap();
+                .is_err()
+            {
+                continue;
+            }
             draw_context
                 .set_bind_groups_from_bindings(&mut draw, render_resource_bindings)
                 .unwrap();
             draw_context
                 .set_vertex_buffers_from_bindings(&mut draw, &[&render_pipelines.bindings])
                 .unwrap();
+            if let Some(reference) = render_pipeline.stencil_reference {
+                draw.set_stencil_reference(reference);
+            }
 
-            if let Some(indices) = index_range.clone() {
-                draw.draw_indexed(indices, 0, 0..1);
-            } else {
-                draw.draw(0..mesh.count_vertices() as u32, 0..1)
+            let indirect_buffers = indirect_buffers
+                .as_deref_mut()
+                .filter(|_| indirect_draw.is_some());
+            match (indirect_buffers, index_range.clone()) {
+                (Some(indirect_buffers), Some(indices)) => {
+                    let (buffer, offset) = indirect_buffers.push_draw_indexed(
+                        &**draw_context.render_resource_context,
+                        draw_context.current_pipeline.as_ref().unwrap(),
+                        &DrawIndexedIndirectArgs {
+                            index_count: indices.end - indices.start,
+                            instance_count: 1,
+                            first_index: indices.start,
+                            base_vertex: 0,
+                            first_instance: 0,
+                        },
+                    );
+                    draw.draw_indexed_indirect(buffer, offset, 1);
+                }
+                (Some(indirect_buffers), None) => {
+                    let (buffer, offset) = indirect_buffers.push_draw(
+                        &**draw_context.render_resource_context,
+                        draw_context.current_pipeline.as_ref().unwrap(),
+                        &DrawIndirectArgs {
+                            vertex_count: mesh.count_vertices() as u32,
+                            instance_count: 1,
+                            first_vertex: 0,
+                            first_instance: 0,
+                        },
+                    );
+                    draw.draw_indirect(buffer, offset, 1);
+                }
+                (None, Some(indices)) => draw.draw_indexed(indices, 0, 0..1),
+                (None, None) => draw.draw(0..mesh.count_vertices() as u32, 0..1),
             }
         }
     }
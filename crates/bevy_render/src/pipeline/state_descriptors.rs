@@ -12,13 +12,66 @@ pub struct DepthStencilState {
     pub clamp_depth: bool,
 }
 
-#[derive(Clone, Debug)]
+/// Tests and updates the stencil buffer of a depth stencil attachment, against the reference
+/// set by [RenderPass::set_stencil_reference](crate::pass::RenderPass::set_stencil_reference).
+/// Requires a depth stencil format with a stencil aspect, such as
+/// [TextureFormat::Depth24PlusStencil8].
+#[derive(Clone, Debug, PartialEq)]
 pub struct StencilState {
     pub front: StencilFaceState,
     pub back: StencilFaceState,
+    /// The bits of the stencil buffer and the reference compared by the test
     pub read_mask: u32,
+    /// The bits of the stencil buffer the operations can change
     pub write_mask: u32,
 }
+
+impl StencilState {
+    /// Leaves the stencil buffer untouched and lets every fragment through
+    pub const IGNORE: Self = StencilState {
+        front: StencilFaceState::IGNORE,
+        back: StencilFaceState::IGNORE,
+        read_mask: 0,
+        write_mask: 0,
+    };
+
+    /// Writes the reference wherever a fragment passes the depth test, such as to mark the
+    /// pixels covered by an outlined object, a portal or a UI clipping mask
+    pub fn write_reference() -> Self {
+        let face = StencilFaceState {
+            pass_op: StencilOperation::Replace,
+            ..StencilFaceState::IGNORE
+        };
+        StencilState {
+            front: face.clone(),
+            back: face,
+            read_mask: !0,
+            write_mask: !0,
+        }
+    }
+
+    /// Only lets fragments through where `reference compare stencil` holds, without changing
+    /// the stencil buffer. [CompareFunction::Equal] draws inside of a mask written by
+    /// [StencilState::write_reference], and [CompareFunction::NotEqual] outside of it.
+    pub fn compare_reference(compare: CompareFunction) -> Self {
+        let face = StencilFaceState {
+            compare,
+            ..StencilFaceState::IGNORE
+        };
+        StencilState {
+            front: face.clone(),
+            back: face,
+            read_mask: !0,
+            write_mask: 0,
+        }
+    }
+
+    /// Returns true if the stencil buffer is tested or written
+    pub fn is_enabled(&self) -> bool {
+        self.front != StencilFaceState::IGNORE || self.back != StencilFaceState::IGNORE
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct MultisampleState {
     /// The number of samples calculated per pixel (for MSAA). For non-multisampled textures,
@@ -87,6 +140,20 @@ pub enum CompareFunction {
     Always = 7,
 }
 
+impl CompareFunction {
+    /// The comparison that gives the same result with the operands swapped, used to flip depth
+    /// tests for reversed-Z
+    pub fn reversed(self) -> Self {
+        match self {
+            CompareFunction::Less => CompareFunction::Greater,
+            CompareFunction::LessEqual => CompareFunction::GreaterEqual,
+            CompareFunction::Greater => CompareFunction::Less,
+            CompareFunction::GreaterEqual => CompareFunction::LessEqual,
+            compare => compare,
+        }
+    }
+}
+
 /// Describes how the VertexAttributes should be interpreted while rendering
 #[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize, Reflect)]
 #[reflect_value(Serialize, Deserialize, PartialEq, Hash)]
@@ -162,6 +229,31 @@ pub struct ColorTargetState {
     pub write_mask: ColorWrite,
 }
 
+impl ColorTargetState {
+    /// A target of `format` blended with `blend_mode`, writing every channel
+    pub fn new(format: TextureFormat, blend_mode: BlendMode) -> Self {
+        ColorTargetState {
+            format,
+            alpha_blend: blend_mode.alpha_blend(),
+            color_blend: blend_mode.color_blend(),
+            write_mask: ColorWrite::ALL,
+        }
+    }
+
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) {
+        self.color_blend = blend_mode.color_blend();
+        self.alpha_blend = blend_mode.alpha_blend();
+    }
+
+    /// Returns the preset matching the blend states of this target, if there is one
+    pub fn blend_mode(&self) -> Option<BlendMode> {
+        BlendMode::ALL.iter().copied().find(|blend_mode| {
+            self.color_blend == blend_mode.color_blend()
+                && self.alpha_blend == blend_mode.alpha_blend()
+        })
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct BlendState {
     pub src_factor: BlendFactor,
@@ -175,6 +267,102 @@ impl BlendState {
         dst_factor: BlendFactor::Zero,
         operation: BlendOperation::Add,
     };
+
+    /// Leaves the target unchanged
+    pub const KEEP: Self = BlendState {
+        src_factor: BlendFactor::Zero,
+        dst_factor: BlendFactor::One,
+        operation: BlendOperation::Add,
+    };
+
+    /// Blends the source over the target by the source alpha
+    pub const ALPHA_BLENDING: Self = BlendState {
+        src_factor: BlendFactor::SrcAlpha,
+        dst_factor: BlendFactor::OneMinusSrcAlpha,
+        operation: BlendOperation::Add,
+    };
+
+    /// Blends a source already multiplied by its alpha over the target
+    pub const PREMULTIPLIED_ALPHA_BLENDING: Self = BlendState {
+        src_factor: BlendFactor::One,
+        dst_factor: BlendFactor::OneMinusSrcAlpha,
+        operation: BlendOperation::Add,
+    };
+
+    /// Adds the source to the target
+    pub const ADDITIVE: Self = BlendState {
+        src_factor: BlendFactor::One,
+        dst_factor: BlendFactor::One,
+        operation: BlendOperation::Add,
+    };
+
+    /// Multiplies the target by the source
+    pub const MULTIPLY: Self = BlendState {
+        src_factor: BlendFactor::DstColor,
+        dst_factor: BlendFactor::Zero,
+        operation: BlendOperation::Add,
+    };
+
+    /// Blends the source over the target by the constant set with
+    /// [RenderPass::set_blend_color](crate::pass::RenderPass::set_blend_color), such as to fade
+    /// a whole draw in or out
+    pub const CONSTANT: Self = BlendState {
+        src_factor: BlendFactor::BlendColor,
+        dst_factor: BlendFactor::OneMinusBlendColor,
+        operation: BlendOperation::Add,
+    };
+}
+
+/// Common pairs of color and alpha [BlendState]s of a [ColorTargetState]
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize, Reflect)]
+#[reflect_value(Hash, PartialEq, Serialize, Deserialize)]
+pub enum BlendMode {
+    /// Overwrites the target
+    Replace,
+    /// Blends the color by the source alpha and accumulates alpha
+    Alpha,
+    /// Blends colors already multiplied by their alpha, which filter correctly and can mix
+    /// additive and alpha blended content in a single draw
+    PremultipliedAlpha,
+    /// Adds the color to the target, keeping the alpha of the target
+    Additive,
+    /// Multiplies the color of the target, keeping the alpha of the target
+    Multiply,
+}
+
+impl Default for BlendMode {
+    fn default() -> Self {
+        BlendMode::Alpha
+    }
+}
+
+impl BlendMode {
+    pub const ALL: [BlendMode; 5] = [
+        BlendMode::Replace,
+        BlendMode::Alpha,
+        BlendMode::PremultipliedAlpha,
+        BlendMode::Additive,
+        BlendMode::Multiply,
+    ];
+
+    pub fn color_blend(self) -> BlendState {
+        match self {
+            BlendMode::Replace => BlendState::REPLACE,
+            BlendMode::Alpha => BlendState::ALPHA_BLENDING,
+            BlendMode::PremultipliedAlpha => BlendState::PREMULTIPLIED_ALPHA_BLENDING,
+            BlendMode::Additive => BlendState::ADDITIVE,
+            BlendMode::Multiply => BlendState::MULTIPLY,
+        }
+    }
+
+    pub fn alpha_blend(self) -> BlendState {
+        match self {
+            BlendMode::Replace => BlendState::REPLACE,
+            BlendMode::Alpha => BlendState::ADDITIVE,
+            BlendMode::PremultipliedAlpha => BlendState::PREMULTIPLIED_ALPHA_BLENDING,
+            BlendMode::Additive | BlendMode::Multiply => BlendState::KEEP,
+        }
+    }
 }
 
 bitflags::bitflags! {
@@ -239,3 +427,32 @@ impl Default for IndexFormat {
         IndexFormat::Uint32
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stencil_presets() {
+        assert!(!StencilState::IGNORE.is_enabled());
+        let write = StencilState::write_reference();
+        assert!(write.is_enabled());
+        assert_eq!(write.front.pass_op, StencilOperation::Replace);
+        assert_eq!(write.front.compare, CompareFunction::Always);
+        let test = StencilState::compare_reference(CompareFunction::NotEqual);
+        assert!(test.is_enabled());
+        assert_eq!(test.write_mask, 0);
+        assert_eq!(test.back.pass_op, StencilOperation::Keep);
+    }
+
+    #[test]
+    fn blend_mode_presets() {
+        for blend_mode in BlendMode::ALL.iter().copied() {
+            let target = ColorTargetState::new(TextureFormat::default(), blend_mode);
+            assert_eq!(target.blend_mode(), Some(blend_mode));
+        }
+        let mut target = ColorTargetState::new(TextureFormat::default(), BlendMode::Replace);
+        target.color_blend = BlendState::CONSTANT;
+        assert_eq!(target.blend_mode(), None);
+    }
+}
@@ -1,16 +1,20 @@
 use super::{
-    CameraNode, PassNode, RenderGraph, SharedBuffersNode, TextureCopyNode, WindowSwapChainNode,
-    WindowTextureNode,
+    CameraNode, IndirectBuffersNode, PassNode, RenderGraph, SharedBuffersNode, TextureCopyNode,
+    WindowSwapChainNode, WindowTextureNode,
 };
 use crate::{
+    camera::ActiveCameras,
     pass::{
         LoadOp, Operations, PassDescriptor, RenderPassColorAttachmentDescriptor,
         RenderPassDepthStencilAttachmentDescriptor, TextureAttachment,
     },
+    pipeline::PipelineSpecialization,
     texture::{Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsage},
+    tonemapping::TonemappingNode,
     Color,
 };
-use bevy_ecs::{reflect::ReflectComponent, world::World};
+use bevy_ecs::{entity::Entity, reflect::ReflectComponent, world::World};
+use bevy_math::{Mat4, Vec4};
 use bevy_reflect::Reflect;
 use bevy_window::WindowId;
 
@@ -53,6 +57,110 @@ impl Msaa {
     }
 }
 
+/// Renders the main pass into an [Hdr::TEXTURE_FORMAT] texture instead of the swap chain, so
+/// colors brighter than 1.0 aren't clipped. The texture is mapped to the swap chain by a
+/// tonemapping pass, configured by the [Tonemapping](crate::tonemapping::Tonemapping) component of
+/// the active 3d or 2d camera.
+///
+/// Like [Msaa], this is read when the base render graph is built, so it must be inserted before
+/// the [RenderPlugin](crate::RenderPlugin) is added.
+#[derive(Debug, Default)]
+pub struct Hdr {
+    pub enabled: bool,
+}
+
+impl Hdr {
+    pub const TEXTURE_FORMAT: TextureFormat = TextureFormat::Rgba16Float;
+    /// The shader def set on [MainPass] pipelines when HDR is enabled
+    pub const SHADER_DEF: &'static str = "HDR";
+
+    /// The color target format of pipelines drawn in the main pass, or `None` if they render to
+    /// the swap chain format
+    pub fn main_pass_format(&self) -> Option<TextureFormat> {
+        if self.enabled {
+            Some(Self::TEXTURE_FORMAT)
+        } else {
+            None
+        }
+    }
+}
+
+/// Configures the depth buffer of the main pass, which the passes drawn on top of it share.
+///
+/// With `reversed_z`, cameras map the near plane to a depth of 1.0 and the far plane to 0.0,
+/// depth buffers are cleared to 0.0 and depth tests are flipped. Combined with a float depth
+/// format this spreads the precision evenly over the view distance, which avoids z-fighting on
+/// distant geometry in large scenes.
+///
+/// Like [Msaa], this is read when the base render graph is built, so it must be inserted before
+/// the [RenderPlugin](crate::RenderPlugin) is added.
+#[derive(Debug, Clone)]
+pub struct Depth {
+    /// [TextureFormat::Depth32Float], or [TextureFormat::Depth24PlusStencil8] for stencil tests
+    pub format: TextureFormat,
+    pub reversed_z: bool,
+}
+
+impl Default for Depth {
+    fn default() -> Self {
+        Depth {
+            format: TextureFormat::Depth32Float,
+            reversed_z: false,
+        }
+    }
+}
+
+impl Depth {
+    /// The shader def set on compute pipelines reading depth buffers when reversed-Z is enabled
+    pub const REVERSED_Z_SHADER_DEF: &'static str = "REVERSED_Z";
+
+    /// The depth of the far plane, which depth buffers are cleared to
+    pub fn clear_depth(&self) -> f32 {
+        if self.reversed_z {
+            0.0
+        } else {
+            1.0
+        }
+    }
+
+    /// The stencil operations of passes clearing the depth buffer, which clear the stencil values
+    /// to 0 if the format stores them
+    pub fn stencil_ops(&self) -> Option<Operations<u32>> {
+        if self.format.has_stencil() {
+            Some(Operations {
+                load: LoadOp::Clear(0),
+                store: true,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Remaps a projection matrix with a depth range from 0.0 at the near plane to 1.0 at the
+    /// far plane to the configured depth range
+    pub fn projection(&self, projection: Mat4) -> Mat4 {
+        if self.reversed_z {
+            // z' = w - z
+            let reverse = Mat4::from_cols(
+                Vec4::new(1.0, 0.0, 0.0, 0.0),
+                Vec4::new(0.0, 1.0, 0.0, 0.0),
+                Vec4::new(0.0, 0.0, -1.0, 0.0),
+                Vec4::new(0.0, 0.0, 1.0, 1.0),
+            );
+            reverse * projection
+        } else {
+            projection
+        }
+    }
+
+    /// Sets the depth format and depth test direction of a pipeline drawn with the main depth
+    /// texture
+    pub fn specialize(&self, specialization: &mut PipelineSpecialization) {
+        specialization.depth_format = Some(self.format);
+        specialization.reversed_z = self.reversed_z;
+    }
+}
+
 #[derive(Debug)]
 pub struct BaseRenderGraphConfig {
     pub add_2d_camera: bool,
@@ -70,8 +178,12 @@ pub mod node {
     pub const TEXTURE_COPY: &str = "texture_copy";
     pub const MAIN_DEPTH_TEXTURE: &str = "main_pass_depth_texture";
     pub const MAIN_SAMPLED_COLOR_ATTACHMENT: &str = "main_pass_sampled_color_attachment";
+    pub const MAIN_HDR_TEXTURE: &str = "main_pass_hdr_texture";
+    pub const MAIN_SAMPLED_HDR_ATTACHMENT: &str = "main_pass_sampled_hdr_attachment";
     pub const MAIN_PASS: &str = "main_pass";
+    pub const TONEMAPPING: &str = "tonemapping";
     pub const SHARED_BUFFERS: &str = "shared_buffers";
+    pub const INDIRECT_BUFFERS: &str = "indirect_buffers";
 }
 
 pub mod camera {
@@ -79,6 +191,14 @@ pub mod camera {
     pub const CAMERA_2D: &str = "Camera2d";
 }
 
+/// The camera whose components configure per camera settings of the main pass and its post
+/// processing: the active 3d camera, or the active 2d camera if there is no 3d camera
+pub fn main_pass_camera(active_cameras: &ActiveCameras) -> Option<Entity> {
+    [camera::CAMERA_3D, camera::CAMERA_2D]
+        .iter()
+        .find_map(|name| active_cameras.get(name).and_then(|camera| camera.entity))
+}
+
 impl Default for BaseRenderGraphConfig {
     fn default() -> Self {
         BaseRenderGraphConfig {
@@ -99,6 +219,8 @@ pub(crate) fn add_base_graph(config: &BaseRenderGraphConfig, world: &mut World)
     let world = world.cell();
     let mut graph = world.get_resource_mut::<RenderGraph>().unwrap();
     let msaa = world.get_resource::<Msaa>().unwrap();
+    let hdr = world.get_resource::<Hdr>().unwrap();
+    let depth = world.get_resource::<Depth>().unwrap();
 
     graph.add_node(node::TEXTURE_COPY, TextureCopyNode::default());
     if config.add_3d_camera {
@@ -110,6 +232,7 @@ pub(crate) fn add_base_graph(config: &BaseRenderGraphConfig, world: &mut World)
     }
 
     graph.add_node(node::SHARED_BUFFERS, SharedBuffersNode::default());
+    graph.add_node(node::INDIRECT_BUFFERS, IndirectBuffersNode::default());
     if config.add_main_depth_texture {
         graph.add_node(
             node::MAIN_DEPTH_TEXTURE,
@@ -124,9 +247,10 @@ pub(crate) fn add_base_graph(config: &BaseRenderGraphConfig, world: &mut World)
                     mip_level_count: 1,
                     sample_count: msaa.samples,
                     dimension: TextureDimension::D2,
-                    format: TextureFormat::Depth32Float, /* PERF: vulkan docs recommend using 24
-                                                          * bit depth for better performance */
-                    usage: TextureUsage::OUTPUT_ATTACHMENT,
+                    format: depth.format,
+                    // sampled by occlusion culling
+                    usage: TextureUsage::OUTPUT_ATTACHMENT | TextureUsage::SAMPLED,
+                    view_dimension: None,
                 },
             ),
         );
@@ -145,10 +269,10 @@ pub(crate) fn add_base_graph(config: &BaseRenderGraphConfig, world: &mut World)
             depth_stencil_attachment: Some(RenderPassDepthStencilAttachmentDescriptor {
                 attachment: TextureAttachment::Input("depth".to_string()),
                 depth_ops: Some(Operations {
-                    load: LoadOp::Clear(1.0),
+                    load: LoadOp::Clear(depth.clear_depth()),
                     store: true,
                 }),
-                stencil_ops: None,
+                stencil_ops: depth.stencil_ops(),
             }),
             sample_count: msaa.samples,
         });
@@ -171,6 +295,9 @@ pub(crate) fn add_base_graph(config: &BaseRenderGraphConfig, world: &mut World)
         graph
             .add_node_edge(node::SHARED_BUFFERS, node::MAIN_PASS)
             .unwrap();
+        graph
+            .add_node_edge(node::INDIRECT_BUFFERS, node::MAIN_PASS)
+            .unwrap();
 
         if config.add_3d_camera {
             graph
@@ -190,49 +317,121 @@ pub(crate) fn add_base_graph(config: &BaseRenderGraphConfig, world: &mut World)
         WindowSwapChainNode::new(WindowId::primary()),
     );
 
-    if config.connect_main_pass_to_swapchain {
+    let main_pass_color_slot = if msaa.samples > 1 {
+        "color_resolve_target"
+    } else {
+        "color_attachment"
+    };
+    let window_texture_node = |format, sample_count, usage| {
+        WindowTextureNode::new(
+            WindowId::primary(),
+            TextureDescriptor {
+                size: Extent3d {
+                    depth: 1,
+                    width: 1,
+                    height: 1,
+                },
+                mip_level_count: 1,
+                sample_count,
+                dimension: TextureDimension::D2,
+                format,
+                usage,
+                view_dimension: None,
+            },
+        )
+    };
+
+    if hdr.enabled && config.add_main_pass {
+        graph.add_node(
+            node::MAIN_HDR_TEXTURE,
+            window_texture_node(
+                Hdr::TEXTURE_FORMAT,
+                1,
+                TextureUsage::OUTPUT_ATTACHMENT | TextureUsage::SAMPLED,
+            ),
+        );
+        graph.add_node(node::TONEMAPPING, TonemappingNode::default());
         graph
             .add_slot_edge(
-                node::PRIMARY_SWAP_CHAIN,
-                WindowSwapChainNode::OUT_TEXTURE,
-                node::MAIN_PASS,
-                if msaa.samples > 1 {
-                    "color_resolve_target"
-                } else {
-                    "color_attachment"
-                },
+                node::MAIN_HDR_TEXTURE,
+                WindowTextureNode::OUT_TEXTURE,
+                node::TONEMAPPING,
+                TonemappingNode::IN_HDR_TEXTURE,
             )
             .unwrap();
+        graph
+            .add_node_edge(node::MAIN_PASS, node::TONEMAPPING)
+            .unwrap();
+    }
+
+    if config.connect_main_pass_to_swapchain {
+        if hdr.enabled {
+            graph
+                .add_slot_edge(
+                    node::MAIN_HDR_TEXTURE,
+                    WindowTextureNode::OUT_TEXTURE,
+                    node::MAIN_PASS,
+                    main_pass_color_slot,
+                )
+                .unwrap();
+            graph
+                .add_slot_edge(
+                    node::PRIMARY_SWAP_CHAIN,
+                    WindowSwapChainNode::OUT_TEXTURE,
+                    node::TONEMAPPING,
+                    TonemappingNode::IN_TARGET,
+                )
+                .unwrap();
+        } else {
+            graph
+                .add_slot_edge(
+                    node::PRIMARY_SWAP_CHAIN,
+                    WindowSwapChainNode::OUT_TEXTURE,
+                    node::MAIN_PASS,
+                    main_pass_color_slot,
+                )
+                .unwrap();
+        }
     }
 
     if msaa.samples > 1 {
+        // also used by passes that render to the swap chain after tonemapping, such as the ui
         graph.add_node(
             node::MAIN_SAMPLED_COLOR_ATTACHMENT,
-            WindowTextureNode::new(
-                WindowId::primary(),
-                TextureDescriptor {
-                    size: Extent3d {
-                        depth: 1,
-                        width: 1,
-                        height: 1,
-                    },
-                    mip_level_count: 1,
-                    sample_count: msaa.samples,
-                    dimension: TextureDimension::D2,
-                    format: TextureFormat::default(),
-                    usage: TextureUsage::OUTPUT_ATTACHMENT,
-                },
+            window_texture_node(
+                TextureFormat::default(),
+                msaa.samples,
+                TextureUsage::OUTPUT_ATTACHMENT,
             ),
         );
 
-        graph
-            .add_slot_edge(
-                node::MAIN_SAMPLED_COLOR_ATTACHMENT,
-                WindowSwapChainNode::OUT_TEXTURE,
-                node::MAIN_PASS,
-                "color_attachment",
-            )
-            .unwrap();
+        if hdr.enabled && config.add_main_pass {
+            graph.add_node(
+                node::MAIN_SAMPLED_HDR_ATTACHMENT,
+                window_texture_node(
+                    Hdr::TEXTURE_FORMAT,
+                    msaa.samples,
+                    TextureUsage::OUTPUT_ATTACHMENT,
+                ),
+            );
+            graph
+                .add_slot_edge(
+                    node::MAIN_SAMPLED_HDR_ATTACHMENT,
+                    WindowTextureNode::OUT_TEXTURE,
+                    node::MAIN_PASS,
+                    "color_attachment",
+                )
+                .unwrap();
+        } else {
+            graph
+                .add_slot_edge(
+                    node::MAIN_SAMPLED_COLOR_ATTACHMENT,
+                    WindowSwapChainNode::OUT_TEXTURE,
+                    node::MAIN_PASS,
+                    "color_attachment",
+                )
+                .unwrap();
+        }
     }
 
     if config.connect_main_pass_to_main_depth_texture {
@@ -246,3 +445,38 @@ pub(crate) fn add_base_graph(config: &BaseRenderGraphConfig, world: &mut World)
             .unwrap();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reverses_depth_range() {
+        let depth = Depth {
+            reversed_z: true,
+            ..Default::default()
+        };
+        let projection = depth.projection(Mat4::perspective_rh(1.0, 1.0, 1.0, 100.0));
+        let near = projection.project_point3(bevy_math::Vec3::new(0.0, 0.0, -1.0));
+        let far = projection.project_point3(bevy_math::Vec3::new(0.0, 0.0, -100.0));
+        assert!((near.z - 1.0).abs() < 1e-6);
+        assert!(far.z.abs() < 1e-6);
+        assert_eq!(depth.clear_depth(), 0.0);
+    }
+
+    #[test]
+    fn clears_stencil_of_depth_stencil_formats() {
+        assert_eq!(Depth::default().stencil_ops(), None);
+        let depth = Depth {
+            format: TextureFormat::Depth24PlusStencil8,
+            ..Default::default()
+        };
+        assert_eq!(
+            depth.stencil_ops(),
+            Some(Operations {
+                load: LoadOp::Clear(0),
+                store: true,
+            })
+        );
+    }
+}
@@ -5,8 +5,10 @@ mod graph;
 mod node;
 mod node_slot;
 mod nodes;
+mod render_resources_plugin;
 mod schedule;
 mod system;
+mod transient_textures;
 
 pub use command::*;
 pub use edge::*;
@@ -14,8 +16,10 @@ pub use graph::*;
 pub use node::*;
 pub use node_slot::*;
 pub use nodes::*;
+pub use render_resources_plugin::*;
 pub use schedule::*;
 pub use system::*;
+pub use transient_textures::*;
 
 use thiserror::Error;
 
@@ -180,7 +180,7 @@ pub fn camera_node_system(
     }
 
     if let Some(RenderResourceBinding::Buffer { buffer, .. }) = bindings.get(CAMERA_VIEW_PROJ) {
-        let view_proj = camera.projection_matrix * view.inverse();
+        let view_proj = camera.view_projection(global_transform);
         render_resource_context.write_mapped_buffer(
             staging_buffer,
             offset..(offset + MATRIX_SIZE as u64),
@@ -0,0 +1,21 @@
+use crate::{
+    render_graph::{Node, ResourceSlots},
+    renderer::{IndirectBuffers, RenderContext},
+};
+use bevy_ecs::world::World;
+
+#[derive(Default)]
+pub struct IndirectBuffersNode;
+
+impl Node for IndirectBuffersNode {
+    fn update(
+        &mut self,
+        world: &World,
+        render_context: &mut dyn RenderContext,
+        _input: &ResourceSlots,
+        _output: &mut ResourceSlots,
+    ) {
+        let indirect_buffers = world.get_resource::<IndirectBuffers>().unwrap();
+        indirect_buffers.apply(render_context);
+    }
+}
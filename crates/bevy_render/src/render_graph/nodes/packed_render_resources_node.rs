@@ -0,0 +1,341 @@
+use crate::{
+    draw::OutsideFrustum,
+    pipeline::RenderPipelines,
+    prelude::Visible,
+    render_graph::{CommandQueue, Node, ResourceSlots, SystemNode},
+    renderer::{
+        BufferId, BufferInfo, BufferMapMode, BufferUsage, RenderContext, RenderResourceBinding,
+        RenderResourceContext, RenderResourceType, RenderResources,
+    },
+};
+use bevy_ecs::{
+    entity::Entity,
+    query::Without,
+    system::{BoxedSystem, IntoSystem, Local, Query, Res},
+    world::World,
+};
+use std::{marker::PhantomData, ops::DerefMut};
+
+/// A uniform buffer that holds one item per drawn entity, packed without gaps
+#[derive(Debug)]
+struct PackedBuffer {
+    /// The size of one item, aligned so it can be used as a dynamic offset
+    item_size: usize,
+    capacity: usize,
+    buffer: Option<BufferId>,
+}
+
+impl PackedBuffer {
+    /// Grows the buffer to fit `len` items. Returns true if a new buffer was allocated.
+    fn reserve(&mut self, len: usize, render_resource_context: &dyn RenderResourceContext) -> bool {
+        if len <= self.capacity {
+            return false;
+        }
+
+        if let Some(old_buffer) = self.buffer.take() {
+            render_resource_context.remove_buffer(old_buffer);
+        }
+
+        self.capacity = len.max(self.capacity * 2);
+        self.buffer = Some(render_resource_context.create_buffer(BufferInfo {
+            size: self.capacity * self.item_size,
+            buffer_usage: BufferUsage::COPY_DST | BufferUsage::UNIFORM,
+            ..Default::default()
+        }));
+        true
+    }
+}
+
+/// A fast path for small per-entity uniforms, such as a model matrix or a color, that are
+/// rewritten every frame.
+///
+/// Unlike [RenderResourcesNode](super::RenderResourcesNode) with dynamic uniforms, which keeps a
+/// stable slot per entity in a hash map, this node packs the `T` of every visible entity into a
+/// single buffer per uniform, in query order. The whole buffer is uploaded with one copy and each
+/// entity is drawn with one dynamic offset. An entity's binding is only touched when its slot
+/// changes, which doesn't happen while the set of visible entities is stable.
+///
+/// Only buffer render resources of `T` are bound. Use
+/// [RenderResourcesNode](super::RenderResourcesNode) for types that contain textures.
+pub struct PackedRenderResourcesNode<T>
+where
+    T: RenderResources,
+{
+    command_queue: CommandQueue,
+    _marker: PhantomData<T>,
+}
+
+impl<T> PackedRenderResourcesNode<T>
+where
+    T: RenderResources,
+{
+    pub fn new() -> Self {
+        PackedRenderResourcesNode {
+            command_queue: CommandQueue::default(),
+            _marker: PhantomData::default(),
+        }
+    }
+}
+
+impl<T> Default for PackedRenderResourcesNode<T>
+where
+    T: RenderResources,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Node for PackedRenderResourcesNode<T>
+where
+    T: RenderResources,
+{
+    fn update(
+        &mut self,
+        _world: &World,
+        render_context: &mut dyn RenderContext,
+        _input: &ResourceSlots,
+        _output: &mut ResourceSlots,
+    ) {
+        self.command_queue.execute(render_context);
+    }
+}
+
+impl<T> SystemNode for PackedRenderResourcesNode<T>
+where
+    T: RenderResources,
+{
+    fn get_system(&self) -> BoxedSystem {
+        let system = packed_render_resources_node_system::<T>
+            .system()
+            .config(|config| {
+                config.0 = Some(PackedRenderResourcesNodeState {
+                    command_queue: self.command_queue.clone(),
+                    ..Default::default()
+                })
+            });
+
+        Box::new(system)
+    }
+}
+
+struct PackedRenderResourcesNodeState<T: RenderResources> {
+    command_queue: CommandQueue,
+    /// One buffer per render resource of `T`, `None` for render resources that aren't buffers
+    buffers: Vec<Option<PackedBuffer>>,
+    staging_buffer: Option<BufferId>,
+    staging_buffer_size: usize,
+    /// The entity drawn from each slot on the last update. The binding of each of these entities
+    /// points at its slot.
+    slots: Vec<Entity>,
+    _marker: PhantomData<T>,
+}
+
+impl<T: RenderResources> Default for PackedRenderResourcesNodeState<T> {
+    fn default() -> Self {
+        Self {
+            command_queue: Default::default(),
+            buffers: Default::default(),
+            staging_buffer: Default::default(),
+            staging_buffer_size: Default::default(),
+            slots: Default::default(),
+            _marker: Default::default(),
+        }
+    }
+}
+
+fn packed_render_resources_node_system<T: RenderResources>(
+    mut state: Local<PackedRenderResourcesNodeState<T>>,
+    render_resource_context: Res<Box<dyn RenderResourceContext>>,
+    mut query: Query<(Entity, &T, &Visible, &mut RenderPipelines), Without<OutsideFrustum>>,
+) {
+    let state = state.deref_mut();
+    let render_resource_context = &**render_resource_context;
+
+    // initialize the buffers using the first RenderResources
+    if state.buffers.is_empty() {
+        if let Some((_, first, _, _)) = query.iter_mut().next() {
+            state.buffers = first
+                .iter()
+                .map(|render_resource| match render_resource.resource_type() {
+                    Some(RenderResourceType::Buffer) => {
+                        let size = render_resource.buffer_byte_len().unwrap();
+                        Some(PackedBuffer {
+                            item_size: render_resource_context.get_aligned_uniform_size(size, true),
+                            capacity: 0,
+                            buffer: None,
+                        })
+                    }
+                    _ => None,
+                })
+                .collect();
+        }
+    }
+
+    let len = query
+        .iter_mut()
+        .filter(|(_, _, visible, _)| visible.is_visible)
+        .count();
+    if len == 0 {
+        state.slots.clear();
+        return;
+    }
+
+    let mut reallocated = false;
+    for buffer in state.buffers.iter_mut().flatten() {
+        reallocated |= buffer.reserve(len, render_resource_context);
+    }
+    // bindings pointing into the old buffers are stale, so every entity needs a new binding
+    if reallocated {
+        state.slots.clear();
+    }
+
+    let staging_buffer_size = state
+        .buffers
+        .iter()
+        .flatten()
+        .map(|buffer| buffer.item_size * len)
+        .sum::<usize>();
+    if staging_buffer_size > state.staging_buffer_size {
+        if let Some(staging_buffer) = state.staging_buffer.take() {
+            render_resource_context.remove_buffer(staging_buffer);
+        }
+        state.staging_buffer = Some(render_resource_context.create_buffer(BufferInfo {
+            buffer_usage: BufferUsage::COPY_SRC | BufferUsage::MAP_WRITE,
+            size: staging_buffer_size,
+            ..Default::default()
+        }));
+        state.staging_buffer_size = staging_buffer_size;
+    }
+    let staging_buffer = state.staging_buffer.unwrap();
+
+    let previous_slots = std::mem::take(&mut state.slots);
+    render_resource_context.map_buffer(staging_buffer, BufferMapMode::Write);
+    render_resource_context.write_mapped_buffer(
+        staging_buffer,
+        0..staging_buffer_size as u64,
+        &mut |staging_buffer, _render_resource_context| {
+            for (entity, uniforms, visible, mut render_pipelines) in query.iter_mut() {
+                if !visible.is_visible {
+                    continue;
+                }
+
+                let slot = state.slots.len();
+                let rebind = previous_slots.get(slot) != Some(&entity);
+                // each buffer's items are stored contiguously in the staging buffer, in the same
+                // order as in the final buffer
+                let mut staging_offset = 0;
+                for (i, render_resource) in uniforms.iter().enumerate() {
+                    let buffer = match &state.buffers[i] {
+                        Some(buffer) => buffer,
+                        None => continue,
+                    };
+                    let size = render_resource.buffer_byte_len().unwrap();
+                    let offset = slot * buffer.item_size;
+                    render_resource.write_buffer_bytes(
+                        &mut staging_buffer
+                            [(staging_offset + offset)..(staging_offset + offset + size)],
+                    );
+                    staging_offset += buffer.item_size * len;
+
+                    if rebind {
+                        let render_resource_name = uniforms.get_render_resource_name(i).unwrap();
+                        let bindings = &mut render_pipelines.bindings;
+                        bindings.set_buffer_byte_len(render_resource_name, size as u64);
+                        bindings.set(
+                            render_resource_name,
+                            RenderResourceBinding::Buffer {
+                                buffer: buffer.buffer.unwrap(),
+                                range: 0..buffer.item_size as u64,
+                                dynamic_index: Some(offset as u32),
+                            },
+                        );
+                    }
+                }
+                state.slots.push(entity);
+            }
+        },
+    );
+    render_resource_context.unmap_buffer(staging_buffer);
+
+    let mut staging_offset = 0;
+    for buffer in state.buffers.iter().flatten() {
+        let size = buffer.item_size * len;
+        state.command_queue.copy_buffer_to_buffer(
+            staging_buffer,
+            staging_offset as u64,
+            buffer.buffer.unwrap(),
+            0,
+            size as u64,
+        );
+        staging_offset += size;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::{HeadlessRenderResourceContext, RenderResource};
+    use bevy_transform::prelude::GlobalTransform;
+
+    fn dynamic_index(world: &World, entity: Entity) -> Option<u32> {
+        match world
+            .get::<RenderPipelines>(entity)
+            .unwrap()
+            .bindings
+            .get("Transform")
+        {
+            Some(RenderResourceBinding::Buffer { dynamic_index, .. }) => *dynamic_index,
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn packs_visible_entities_into_consecutive_slots() {
+        let mut world = World::new();
+        let context: Box<dyn RenderResourceContext> =
+            Box::new(HeadlessRenderResourceContext::default());
+        world.insert_resource(context);
+        let mut spawn = |is_visible| {
+            world
+                .spawn()
+                .insert_bundle((
+                    GlobalTransform::default(),
+                    Visible {
+                        is_visible,
+                        ..Default::default()
+                    },
+                    RenderPipelines::default(),
+                ))
+                .id()
+        };
+        let a = spawn(true);
+        let hidden = spawn(false);
+        let b = spawn(true);
+        let c = spawn(true);
+
+        let node = PackedRenderResourcesNode::<GlobalTransform>::new();
+        let mut system = node.get_system();
+        system.initialize(&mut world);
+        system.run((), &mut world);
+
+        let item_size = GlobalTransform::default().buffer_byte_len().unwrap() as u32;
+        let mut indices = [a, b, c]
+            .iter()
+            .map(|entity| dynamic_index(&world, *entity).unwrap())
+            .collect::<Vec<_>>();
+        indices.sort_unstable();
+        assert_eq!(indices, vec![0, item_size, 2 * item_size]);
+        assert_eq!(dynamic_index(&world, hidden), None);
+
+        // removing an entity packs the remaining ones without a gap
+        world.despawn(a);
+        system.run((), &mut world);
+        let mut indices = [b, c]
+            .iter()
+            .map(|entity| dynamic_index(&world, *entity).unwrap())
+            .collect::<Vec<_>>();
+        indices.sort_unstable();
+        assert_eq!(indices, vec![0, item_size]);
+    }
+}
@@ -1,8 +1,12 @@
 use crate::{
     camera::{ActiveCameras, VisibleEntities},
     draw::{Draw, RenderCommand},
-    pass::{ClearColor, LoadOp, PassDescriptor, TextureAttachment},
-    pipeline::{IndexFormat, PipelineDescriptor},
+    draw_target::{DrawSortOrder, DrawTargets},
+    lod::lod_render_commands,
+    pass::{
+        ClearColor, ClearColorConfig, LoadOp, PassDescriptor, RenderCommands, TextureAttachment,
+    },
+    pipeline::PipelineDescriptor,
     prelude::Visible,
     render_graph::{Node, ResourceSlotInfo, ResourceSlots},
     renderer::{
@@ -10,13 +14,14 @@ use crate::{
         RenderResourceType,
     },
 };
-use bevy_asset::{Assets, Handle};
+use bevy_asset::{Assets, HandleId};
+use bevy_core::FloatOrd;
 use bevy_ecs::{
     query::{QueryState, ReadOnlyFetch, WorldQuery},
     world::{Mut, World},
 };
-use bevy_utils::{tracing::debug, HashMap};
-use std::fmt;
+use bevy_utils::HashMap;
+use std::{borrow::Cow, fmt};
 
 pub struct PassNode<Q: WorldQuery> {
     descriptor: PassDescriptor,
@@ -27,7 +32,8 @@ pub struct PassNode<Q: WorldQuery> {
     depth_stencil_attachment_input_index: Option<usize>,
     default_clear_color_inputs: Vec<usize>,
     query_state: Option<QueryState<Q>>,
-    commands: Vec<RenderCommand>,
+    commands: RenderCommands,
+    sort_draws: bool,
 }
 
 impl<Q: WorldQuery> fmt::Debug for PassNode<Q> {
@@ -52,6 +58,7 @@ impl<Q: WorldQuery> fmt::Debug for PassNode<Q> {
                 "default_clear_color_inputs",
                 &self.default_clear_color_inputs,
             )
+            .field("sort_draws", &self.sort_draws)
             .finish()
     }
 }
@@ -103,7 +110,8 @@ impl<Q: WorldQuery> PassNode<Q> {
             depth_stencil_attachment_input_index,
             default_clear_color_inputs: Vec::new(),
             query_state: None,
-            commands: Vec::new(),
+            commands: RenderCommands::default(),
+            sort_draws: true,
         }
     }
 
@@ -111,9 +119,18 @@ impl<Q: WorldQuery> PassNode<Q> {
         self.cameras.push(camera_name.to_string());
     }
 
+    /// Clears the color attachment with the [ClearColor] resource, or the [ClearColorConfig] of
+    /// the first of the pass' cameras that has one
     pub fn use_default_clear_color(&mut self, color_attachment_index: usize) {
         self.default_clear_color_inputs.push(color_attachment_index);
     }
+
+    /// Sets whether opaque draws are sorted by pipeline, bind groups and depth before they are
+    /// recorded, which reduces state changes. Transparent draws always keep their back-to-front
+    /// order. Enabled by default.
+    pub fn set_sort_draws(&mut self, sort_draws: bool) {
+        self.sort_draws = sort_draws;
+    }
 }
 
 impl<Q: WorldQuery + Send + Sync + 'static> Node for PassNode<Q>
@@ -128,6 +145,13 @@ where
         let query_state = self.query_state.get_or_insert_with(|| world.query());
         let cameras = &self.cameras;
         let commands = &mut self.commands;
+        let sort_draws = self.sort_draws;
+        let mut draw_targets = world.remove_resource::<DrawTargets>();
+        if let Some(draw_targets) = draw_targets.as_mut() {
+            for draw_target in draw_targets.iter_mut() {
+                draw_target.init(world);
+            }
+        }
         world.resource_scope(|world, mut active_cameras: Mut<ActiveCameras>| {
             let mut pipeline_camera_commands = HashMap::default();
             let pipelines = world.get_resource::<Assets<PipelineDescriptor>>().unwrap();
@@ -143,63 +167,148 @@ where
                     continue;
                 };
 
-                let visible_entities = if let Some(entity) = active_camera.entity {
-                    world.get::<VisibleEntities>(entity).unwrap()
+                let (camera_entity, visible_entities) = if let Some(entity) = active_camera.entity {
+                    (entity, world.get::<VisibleEntities>(entity).unwrap())
                 } else {
                     continue;
                 };
-                for visible_entity in visible_entities.iter() {
+                let mut opaque_draws = Vec::new();
+                let mut transparent_draws = Vec::new();
+                let mut target_entities =
+                    vec![Vec::new(); draw_targets.as_ref().map_or(0, |t| t.len())];
+                'visible_entities: for visible_entity in visible_entities.iter() {
                     if query_state.get(world, visible_entity.entity).is_err() {
                         // visible entity does not match the Pass query
                         continue;
                     }
 
+                    if let Some(draw_targets) = draw_targets.as_mut() {
+                        for (i, draw_target) in draw_targets.iter_mut().enumerate() {
+                            if draw_target.matches(world, visible_entity.entity) {
+                                target_entities[i]
+                                    .push((visible_entity.order, visible_entity.entity));
+                                continue 'visible_entities;
+                            }
+                        }
+                    }
+
                     let draw = if let Some(draw) = world.get::<Draw>(visible_entity.entity) {
                         draw
                     } else {
                         continue;
                     };
 
+                    let mut transparent = true;
                     if let Some(visible) = world.get::<Visible>(visible_entity.entity) {
                         if !visible.is_visible {
                             continue;
                         }
+                        transparent = visible.is_transparent;
                     }
-                    for render_command in draw.render_commands.iter() {
-                        commands.push(render_command.clone());
-                        // whenever a new pipeline is set, ensure the relevant camera bind groups are set
-                        if let RenderCommand::SetPipeline { pipeline } = render_command {
-                            let bind_groups = pipeline_camera_commands
-                                .entry(pipeline.clone_weak())
-                                .or_insert_with(|| {
-                                    let descriptor = pipelines.get(pipeline).unwrap();
-                                    let layout = descriptor.get_layout().unwrap();
-                                    let mut commands = Vec::new();
-                                    for bind_group_descriptor in layout.bind_groups.iter() {
-                                        if let Some(bind_group) =
-                                            active_camera.bindings.update_bind_group(
-                                                bind_group_descriptor,
-                                                render_resource_context,
-                                            )
-                                        {
-                                            commands.push(RenderCommand::SetBindGroup {
-                                                index: bind_group_descriptor.index,
-                                                bind_group: bind_group.id,
-                                                dynamic_uniform_indices: bind_group
-                                                    .dynamic_uniform_indices
-                                                    .clone(),
-                                            })
-                                        }
-                                    }
-                                    commands
-                                });
+                    // the camera draws its own level of detail of Lod entities
+                    let render_commands = match lod_render_commands(
+                        world,
+                        visible_entity.entity,
+                        camera_entity,
+                        &draw.render_commands,
+                    ) {
+                        Some(render_commands) => Cow::Owned(render_commands),
+                        None => Cow::Borrowed(&draw.render_commands[..]),
+                    };
+                    if transparent {
+                        transparent_draws.push((visible_entity.order, render_commands));
+                    } else {
+                        let sort_key = if sort_draws {
+                            Some(DrawSortKey::new(&render_commands, visible_entity.order))
+                        } else {
+                            None
+                        };
+                        opaque_draws.push((sort_key, render_commands));
+                    }
+                }
+                if sort_draws {
+                    opaque_draws.sort_by(|(a, _), (b, _)| a.cmp(b));
+                }
 
-                            commands.extend(bind_groups.iter().cloned());
+                // target draws join the opaque or transparent entities depending on their order.
+                // Transparent entities are already back to front, so a stable sort keeps the order
+                // of equal depths.
+                let mut opaque_draws = opaque_draws
+                    .into_iter()
+                    .map(|(_, render_commands)| render_commands)
+                    .collect::<Vec<_>>();
+                if let Some(draw_targets) = draw_targets.as_mut() {
+                    for (draw_target, mut entities) in draw_targets.iter_mut().zip(target_entities)
+                    {
+                        let sort_order = draw_target.sort_order();
+                        match sort_order {
+                            DrawSortOrder::FrontToBack => entities.sort_by_key(|(order, _)| *order),
+                            DrawSortOrder::BackToFront => {
+                                entities.sort_by_key(|(order, _)| -*order)
+                            }
+                        }
+                        for (order, entity) in entities {
+                            let mut target_commands = RenderCommands::default();
+                            draw_target.draw(world, entity, &mut target_commands);
+                            let render_commands =
+                                Cow::Owned(target_commands.iter().cloned().collect());
+                            match sort_order {
+                                DrawSortOrder::FrontToBack => opaque_draws.push(render_commands),
+                                DrawSortOrder::BackToFront => {
+                                    transparent_draws.push((order, render_commands))
+                                }
+                            }
                         }
                     }
                 }
+                transparent_draws.sort_by_key(|(order, _)| -*order);
+
+                let mut record = |render_command: &RenderCommand| {
+                    commands.push(render_command.clone());
+                    // whenever a new pipeline is set, ensure the relevant camera bind groups are set
+                    if let RenderCommand::SetPipeline { pipeline } = render_command {
+                        let bind_groups = pipeline_camera_commands
+                            .entry(pipeline.clone_weak())
+                            .or_insert_with(|| {
+                                let descriptor = pipelines.get(pipeline).unwrap();
+                                let layout = descriptor.get_layout().unwrap();
+                                let mut commands = Vec::new();
+                                for bind_group_descriptor in layout.bind_groups.iter() {
+                                    if let Some(bind_group) =
+                                        active_camera.bindings.update_bind_group(
+                                            bind_group_descriptor,
+                                            render_resource_context,
+                                        )
+                                    {
+                                        commands.push(RenderCommand::SetBindGroup {
+                                            index: bind_group_descriptor.index,
+                                            bind_group: bind_group.id,
+                                            dynamic_uniform_indices: bind_group
+                                                .dynamic_uniform_indices
+                                                .clone(),
+                                        })
+                                    }
+                                }
+                                commands
+                            });
+
+                        commands.extend(bind_groups.iter().cloned());
+                    }
+                };
+
+                let transparent_draws = transparent_draws
+                    .into_iter()
+                    .map(|(_, render_commands)| render_commands);
+                for render_commands in opaque_draws.into_iter().chain(transparent_draws) {
+                    for render_command in render_commands.iter() {
+                        record(render_command);
+                    }
+                }
             }
         });
+        if let Some(draw_targets) = draw_targets {
+            world.insert_resource(draw_targets);
+        }
     }
 
     fn update(
@@ -209,10 +318,26 @@ where
         input: &ResourceSlots,
         _output: &mut ResourceSlots,
     ) {
+        let active_cameras = world.get_resource::<ActiveCameras>().unwrap();
+        let clear_color_config = self
+            .cameras
+            .iter()
+            .filter_map(|name| active_cameras.get(name)?.entity)
+            .find_map(|entity| world.get::<ClearColorConfig>(entity))
+            .copied()
+            .unwrap_or_default();
         for (i, color_attachment) in self.descriptor.color_attachments.iter_mut().enumerate() {
             if self.default_clear_color_inputs.contains(&i) {
-                if let Some(default_clear_color) = world.get_resource::<ClearColor>() {
-                    color_attachment.ops.load = LoadOp::Clear(default_clear_color.0);
+                match clear_color_config {
+                    ClearColorConfig::Default => {
+                        if let Some(default_clear_color) = world.get_resource::<ClearColor>() {
+                            color_attachment.ops.load = LoadOp::Clear(default_clear_color.0);
+                        }
+                    }
+                    ClearColorConfig::Custom(color) => {
+                        color_attachment.ops.load = LoadOp::Clear(color)
+                    }
+                    ClearColorConfig::None => color_attachment.ops.load = LoadOp::Load,
                 }
             }
             if let Some(input_index) = self.color_attachment_input_indices[i] {
@@ -238,152 +363,219 @@ where
         let render_resource_bindings = world.get_resource::<RenderResourceBindings>().unwrap();
         let pipelines = world.get_resource::<Assets<PipelineDescriptor>>().unwrap();
 
-        let mut draw_state = DrawState::default();
-        let commands = &mut self.commands;
-        render_context.begin_pass(
+        render_context.execute_render_commands(
             &self.descriptor,
             &render_resource_bindings,
-            &mut |render_pass| {
-            for render_command in commands.drain(..) {
-                match render_command {
-                    RenderCommand::SetPipeline { pipeline } => {
-                        if draw_state.is_pipeline_set(pipeline.clone_weak()) {
-                            continue;
-                        }
-                        render_pass.set_pipeline(&pipeline);
-                        let descriptor = pipelines.get(&pipeline).unwrap();
-                        draw_state.set_pipeline(&pipeline, descriptor);
-                    }
-                    RenderCommand::DrawIndexed {
-                        base_vertex,
-                        indices,
-                        instances,
-                    } => {
-                        if draw_state.can_draw_indexed() {
-                            render_pass.draw_indexed(
-                                indices.clone(),
-                                base_vertex,
-                                instances.clone(),
-                            );
-                        } else {
-                            debug!("Could not draw indexed because the pipeline layout wasn't fully set for pipeline: {:?}", draw_state.pipeline);
-                        }
-                    }
-                    RenderCommand::Draw { vertices, instances } => {
-                        if draw_state.can_draw() {
-                            render_pass.draw(vertices.clone(), instances.clone());
-                        } else {
-                            debug!("Could not draw because the pipeline layout wasn't fully set for pipeline: {:?}", draw_state.pipeline);
-                        }
-                    }
-                    RenderCommand::SetVertexBuffer {
-                        buffer,
-                        offset,
-                        slot,
-                    } => {
-                        if draw_state.is_vertex_buffer_set(slot, buffer, offset) {
-                            continue;
-                        }
-                        render_pass.set_vertex_buffer(slot, buffer, offset);
-                        draw_state.set_vertex_buffer(slot, buffer, offset);
-                    }
-                    RenderCommand::SetIndexBuffer { buffer, offset, index_format } => {
-                        if draw_state.is_index_buffer_set(buffer, offset, index_format) {
-                            continue;
-                        }
-                        render_pass.set_index_buffer(buffer, offset, index_format);
-                        draw_state.set_index_buffer(buffer, offset, index_format);
-                    }
-                    RenderCommand::SetBindGroup {
-                        index,
-                        bind_group,
-                        dynamic_uniform_indices,
-                    } => {
-                        if dynamic_uniform_indices.is_none() && draw_state.is_bind_group_set(index, bind_group) {
-                            continue;
-                        }
-                        let pipeline = pipelines.get(draw_state.pipeline.as_ref().unwrap()).unwrap();
-                        let layout = pipeline.get_layout().unwrap();
-                        let bind_group_descriptor = layout.get_bind_group(index).unwrap();
-                        render_pass.set_bind_group(
-                            index,
-                            bind_group_descriptor.id,
-                            bind_group,
-                            dynamic_uniform_indices.as_deref()
-                        );
-                        draw_state.set_bind_group(index, bind_group);
-                    }
-                }
-            }
-        });
+            &pipelines,
+            &mut self.commands,
+        );
     }
 }
 
-/// Tracks the current pipeline state to ensure draw calls are valid.
-#[derive(Debug, Default)]
-struct DrawState {
-    pipeline: Option<Handle<PipelineDescriptor>>,
-    bind_groups: Vec<Option<BindGroupId>>,
-    vertex_buffers: Vec<Option<(BufferId, u64)>>,
-    index_buffer: Option<(BufferId, u64, IndexFormat)>,
+/// Orders an entity's draw by the state it binds, so that draws sharing a pipeline and bind
+/// groups are recorded together, then by the indirect arguments it reads, so that consecutive
+/// arguments are issued as one multi draw, and then front-to-back by depth
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct DrawSortKey {
+    pipeline: Option<HandleId>,
+    bind_groups: Vec<(u32, BindGroupId)>,
+    indirect: Option<(BufferId, u64)>,
+    depth: FloatOrd,
 }
 
-impl DrawState {
-    pub fn set_bind_group(&mut self, index: u32, bind_group: BindGroupId) {
-        self.bind_groups[index as usize] = Some(bind_group);
+impl DrawSortKey {
+    fn new(render_commands: &[RenderCommand], depth: FloatOrd) -> Self {
+        let mut key = DrawSortKey {
+            pipeline: None,
+            bind_groups: Vec::new(),
+            indirect: None,
+            depth,
+        };
+        // only the state bound before the first draw is considered
+        for render_command in render_commands {
+            match render_command {
+                RenderCommand::SetPipeline { pipeline } if key.pipeline.is_none() => {
+                    key.pipeline = Some(pipeline.id);
+                }
+                RenderCommand::SetBindGroup {
+                    index, bind_group, ..
+                } => key.bind_groups.push((*index, *bind_group)),
+                RenderCommand::DrawIndirect { buffer, offset, .. }
+                | RenderCommand::DrawIndexedIndirect { buffer, offset, .. } => {
+                    key.indirect = Some((*buffer, *offset));
+                    break;
+                }
+                RenderCommand::Draw { .. } | RenderCommand::DrawIndexed { .. } => break,
+                _ => {}
+            }
+        }
+        key.bind_groups.sort_unstable();
+        key
     }
+}
 
-    pub fn is_bind_group_set(&self, index: u32, bind_group: BindGroupId) -> bool {
-        self.bind_groups[index as usize] == Some(bind_group)
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        camera::VisibleEntity,
+        draw_target::DrawTarget,
+        renderer::{HeadlessRenderResourceContext, RenderResourceContext},
+    };
+    use bevy_asset::Handle;
+    use bevy_ecs::entity::Entity;
+    use bevy_utils::Uuid;
 
-    pub fn set_vertex_buffer(&mut self, index: u32, buffer: BufferId, offset: u64) {
-        self.vertex_buffers[index as usize] = Some((buffer, offset));
+    fn bind_group(id: u64) -> RenderCommand {
+        RenderCommand::SetBindGroup {
+            index: 0,
+            bind_group: BindGroupId(id),
+            dynamic_uniform_indices: None,
+        }
     }
 
-    pub fn is_vertex_buffer_set(&self, index: u32, buffer: BufferId, offset: u64) -> bool {
-        self.vertex_buffers[index as usize] == Some((buffer, offset))
-    }
+    struct TargetMarker;
 
-    pub fn set_index_buffer(&mut self, buffer: BufferId, offset: u64, index_format: IndexFormat) {
-        self.index_buffer = Some((buffer, offset, index_format));
-    }
+    struct TransparentTarget;
 
-    pub fn is_index_buffer_set(
-        &self,
-        buffer: BufferId,
-        offset: u64,
-        index_format: IndexFormat,
-    ) -> bool {
-        self.index_buffer == Some((buffer, offset, index_format))
-    }
+    impl DrawTarget for TransparentTarget {
+        type Query = Entity;
+        type Filter = bevy_ecs::query::With<TargetMarker>;
+
+        fn sort_order(&self) -> DrawSortOrder {
+            DrawSortOrder::BackToFront
+        }
 
-    pub fn can_draw(&self) -> bool {
-        self.bind_groups.iter().all(|b| b.is_some())
-            && self.vertex_buffers.iter().all(|v| v.is_some())
+        fn draw<'w>(&self, _world: &'w World, entity: Entity, commands: &mut RenderCommands) {
+            commands.push(bind_group(entity.id() as u64 + 100));
+        }
     }
 
-    pub fn can_draw_indexed(&self) -> bool {
-        self.can_draw() && self.index_buffer.is_some()
+    #[test]
+    fn sorts_draws_by_pipeline_and_bind_groups() {
+        let pipeline = |id| Handle::<PipelineDescriptor>::weak(HandleId::new(Uuid::nil(), id));
+        let draw = |pipeline_id, bind_group| {
+            vec![
+                RenderCommand::SetPipeline {
+                    pipeline: pipeline(pipeline_id),
+                },
+                RenderCommand::SetBindGroup {
+                    index: 1,
+                    bind_group: BindGroupId(bind_group),
+                    dynamic_uniform_indices: None,
+                },
+                RenderCommand::Draw {
+                    vertices: 0..3,
+                    instances: 0..1,
+                },
+                RenderCommand::SetBindGroup {
+                    index: 0,
+                    bind_group: BindGroupId(bind_group + 1),
+                    dynamic_uniform_indices: None,
+                },
+            ]
+        };
+        let mut keys = vec![
+            DrawSortKey::new(&draw(2, 10), FloatOrd(1.0)),
+            DrawSortKey::new(&draw(1, 20), FloatOrd(2.0)),
+            DrawSortKey::new(&draw(1, 10), FloatOrd(3.0)),
+            DrawSortKey::new(&draw(1, 10), FloatOrd(0.5)),
+        ];
+        assert_eq!(keys[0].bind_groups, vec![(1, BindGroupId(10))]);
+
+        keys.sort();
+        let order = keys.iter().map(|key| key.depth.0).collect::<Vec<_>>();
+        assert_eq!(order, vec![0.5, 3.0, 2.0, 1.0]);
     }
 
-    pub fn is_pipeline_set(&self, pipeline: Handle<PipelineDescriptor>) -> bool {
-        self.pipeline == Some(pipeline)
+    #[test]
+    fn sorts_indirect_draws_by_argument_offset() {
+        let buffer = BufferId::new();
+        let draw = |offset| {
+            vec![
+                bind_group(1),
+                RenderCommand::DrawIndexedIndirect {
+                    buffer,
+                    offset,
+                    count: 1,
+                },
+            ]
+        };
+        let mut keys = vec![
+            DrawSortKey::new(&draw(40), FloatOrd(1.0)),
+            DrawSortKey::new(&draw(0), FloatOrd(3.0)),
+            DrawSortKey::new(&draw(20), FloatOrd(2.0)),
+        ];
+
+        keys.sort();
+        let offsets = keys
+            .iter()
+            .map(|key| key.indirect.unwrap().1)
+            .collect::<Vec<_>>();
+        assert_eq!(offsets, vec![0, 20, 40]);
     }
 
-    pub fn set_pipeline(
-        &mut self,
-        handle: &Handle<PipelineDescriptor>,
-        descriptor: &PipelineDescriptor,
-    ) {
-        self.bind_groups.clear();
-        self.vertex_buffers.clear();
-        self.index_buffer = None;
-
-        self.pipeline = Some(handle.clone_weak());
-        let layout = descriptor.get_layout().unwrap();
-        self.bind_groups.resize(layout.bind_groups.len(), None);
-        self.vertex_buffers
-            .resize(layout.vertex_buffer_descriptors.len(), None);
+    #[test]
+    fn draws_transparent_targets_in_depth_order_with_transparent_entities() {
+        let mut world = World::new();
+        world.insert_resource(Assets::<PipelineDescriptor>::default());
+        world.insert_resource::<Box<dyn RenderResourceContext>>(Box::new(
+            HeadlessRenderResourceContext::default(),
+        ));
+        let mut draw_targets = DrawTargets::default();
+        draw_targets.add(TransparentTarget);
+        world.insert_resource(draw_targets);
+
+        let mut spawn = |id, transparent, target| {
+            let mut entity = world.spawn();
+            entity
+                .insert(Draw {
+                    render_commands: vec![bind_group(id)],
+                })
+                .insert(Visible {
+                    is_visible: true,
+                    is_transparent: transparent,
+                });
+            if target {
+                entity.insert(TargetMarker);
+            }
+            entity.id()
+        };
+        let opaque = spawn(1, false, false);
+        let far = spawn(2, true, false);
+        let target = spawn(3, true, true);
+        let near = spawn(4, true, false);
+        let visible_entities = VisibleEntities {
+            value: vec![(opaque, 1.0), (far, 5.0), (target, 3.0), (near, 2.0)]
+                .into_iter()
+                .map(|(entity, order)| VisibleEntity {
+                    entity,
+                    order: FloatOrd(order),
+                })
+                .collect(),
+        };
+        let camera = world.spawn().insert(visible_entities).id();
+        let mut active_cameras = ActiveCameras::default();
+        active_cameras.add("camera");
+        active_cameras.get_mut("camera").unwrap().entity = Some(camera);
+        world.insert_resource(active_cameras);
+
+        let mut node = PassNode::<&Draw>::new(PassDescriptor {
+            color_attachments: Vec::new(),
+            depth_stencil_attachment: None,
+            sample_count: 1,
+        });
+        node.add_camera("camera");
+        node.prepare(&mut world);
+        let target_id = target.id() as u64 + 100;
+        assert_eq!(
+            node.commands.iter().cloned().collect::<Vec<_>>(),
+            vec![
+                bind_group(1),
+                bind_group(2),
+                bind_group(target_id),
+                bind_group(4)
+            ]
+        );
     }
 }
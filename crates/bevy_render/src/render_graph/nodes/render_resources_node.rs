@@ -3,23 +3,29 @@ use crate::{
     prelude::Visible,
     render_graph::{CommandQueue, Node, ResourceSlots, SystemNode},
     renderer::{
-        self, BufferInfo, BufferMapMode, BufferUsage, RenderContext, RenderResourceBinding,
-        RenderResourceBindings, RenderResourceContext, RenderResourceHints,
+        self, BufferInfo, BufferMapMode, BufferUsage, FramesInFlight, PerFrame, RenderContext,
+        RenderResourceBinding, RenderResourceBindings, RenderResourceContext, RenderResourceHints,
     },
     texture,
 };
 
 use bevy_app::EventReader;
-use bevy_asset::{Asset, AssetEvent, Assets, Handle, HandleId};
+use bevy_asset::{Asset, AssetEvent, AssetServer, Assets, Handle, HandleId, LoadState};
 use bevy_ecs::{
-    entity::Entity,
+    archetype::{Archetype, Archetypes},
+    component::Components,
+    entity::{Entities, Entity, EntityLocation},
     query::{Changed, Or, With},
     system::{BoxedSystem, IntoSystem, Local, Query, QuerySet, RemovedComponents, Res, ResMut},
     world::World,
 };
 use bevy_utils::HashMap;
 use renderer::{AssetRenderResourceBindings, BufferId, RenderResourceType, RenderResources};
-use std::{any::TypeId, hash::Hash, marker::PhantomData, ops::DerefMut};
+use std::{
+    any::TypeId,
+    marker::PhantomData,
+    ops::{DerefMut, Range},
+};
 
 #[derive(Debug)]
 struct QueuedBufferWrite {
@@ -27,84 +33,248 @@ struct QueuedBufferWrite {
     target_offset: usize,
     source_offset: usize,
     size: usize,
+    /// The space the write occupies in the target buffer, including alignment padding
+    padded_size: usize,
+}
+
+/// Sorts the writes by their target and lays them out in the staging buffer so that writes to
+/// neighboring slots of a buffer become a single contiguous copy. Returns the merged writes,
+/// with their offsets into the staging buffer, and the source and staging ranges to copy each
+/// write's data from and to.
+fn coalesce_buffer_writes(
+    mut writes: Vec<QueuedBufferWrite>,
+) -> (Vec<QueuedBufferWrite>, Vec<(Range<usize>, Range<usize>)>) {
+    writes.sort_unstable_by_key(|write| (write.buffer, write.target_offset));
+    let mut merged: Vec<QueuedBufferWrite> = Vec::new();
+    let mut data_ranges = Vec::with_capacity(writes.len());
+    let mut staging_offset = 0;
+    for write in writes {
+        let contiguous = merged.last().map_or(false, |range| {
+            range.buffer == write.buffer
+                && range.target_offset + range.padded_size == write.target_offset
+        });
+        if !contiguous {
+            merged.push(QueuedBufferWrite {
+                buffer: write.buffer,
+                target_offset: write.target_offset,
+                source_offset: staging_offset,
+                size: 0,
+                padded_size: 0,
+            });
+        }
+        let range = merged.last_mut().unwrap();
+        let offset = write.target_offset - range.target_offset;
+        data_ranges.push((
+            write.source_offset..write.source_offset + write.size,
+            range.source_offset + offset..range.source_offset + offset + write.size,
+        ));
+        // the padding between writes is copied too, which is never read by shaders
+        range.size = offset + write.size;
+        range.padded_size = offset + write.padded_size;
+        staging_offset = range.source_offset + range.padded_size;
+    }
+
+    (merged, data_ranges)
+}
+
+/// The slots of an archetype's entities, which are contiguous and in archetype order
+struct ArchetypeRegion {
+    start: usize,
+    capacity: usize,
+    /// The archetype's entities when the region was last updated
+    entities: Vec<Entity>,
+}
+
+/// Assigns buffer array slots to entities per archetype. Each archetype gets one contiguous
+/// region, so the slot of an entity is the start of its archetype's region offset by its index in
+/// the archetype, and the uniforms of an archetype's entities are copied to their buffers
+/// together. Only the regions are stored, not a slot per entity.
+#[derive(Default)]
+struct EntitySlots {
+    /// The regions of archetypes, by archetype index
+    regions: Vec<Option<ArchetypeRegion>>,
+    free_regions: Vec<Range<usize>>,
+    len: usize,
+}
+
+impl EntitySlots {
+    const MIN_REGION_CAPACITY: usize = 8;
+
+    fn get(&self, location: EntityLocation) -> Option<usize> {
+        let region = self.regions.get(location.archetype_id.index())?.as_ref()?;
+        if location.index < region.capacity {
+            Some(region.start + location.index)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the start of the first free range of `capacity` slots, growing the slots if there
+    /// is none
+    fn allocate_region(&mut self, capacity: usize) -> usize {
+        if let Some(free_index) = self
+            .free_regions
+            .iter()
+            .position(|free_region| free_region.len() >= capacity)
+        {
+            let free_region = &mut self.free_regions[free_index];
+            let start = free_region.start;
+            free_region.start += capacity;
+            if free_region.is_empty() {
+                self.free_regions.swap_remove(free_index);
+            }
+            start
+        } else {
+            self.len += capacity;
+            self.len - capacity
+        }
+    }
+
+    /// Updates the regions of the archetypes `matches` accepts. Returns the entities whose
+    /// uniforms have to be written: the `changed` ones and the ones whose slot changed because
+    /// they moved within their archetype or their archetype's region was reallocated.
+    fn update(
+        &mut self,
+        archetypes: &Archetypes,
+        matches: impl Fn(&Archetype) -> bool,
+        changed: Vec<Entity>,
+    ) -> Vec<Entity> {
+        let mut entities_to_write = changed;
+        for archetype in archetypes.iter().filter(|archetype| matches(archetype)) {
+            let index = archetype.id().index();
+            if index >= self.regions.len() {
+                self.regions.resize_with(index + 1, || None);
+            }
+            let archetype_entities = archetype.entities();
+            if archetype_entities.is_empty() {
+                if let Some(region) = self.regions[index].take() {
+                    self.free_regions
+                        .push(region.start..region.start + region.capacity);
+                }
+                continue;
+            }
+
+            let too_small = self.regions[index]
+                .as_ref()
+                .map_or(true, |region| region.capacity < archetype_entities.len());
+            if too_small {
+                if let Some(region) = self.regions[index].take() {
+                    self.free_regions
+                        .push(region.start..region.start + region.capacity);
+                }
+                let capacity = archetype_entities
+                    .len()
+                    .next_power_of_two()
+                    .max(Self::MIN_REGION_CAPACITY);
+                self.regions[index] = Some(ArchetypeRegion {
+                    start: self.allocate_region(capacity),
+                    capacity,
+                    entities: Vec::new(),
+                });
+            }
+
+            let region = self.regions[index].as_mut().unwrap();
+            for (i, entity) in archetype_entities.iter().enumerate() {
+                if region.entities.get(i) != Some(entity) {
+                    entities_to_write.push(*entity);
+                }
+            }
+            region.entities.clear();
+            region.entities.extend_from_slice(archetype_entities);
+        }
+
+        entities_to_write.sort_unstable();
+        entities_to_write.dedup();
+        entities_to_write
+    }
+}
+
+/// Assigns buffer array slots to assets, reusing the slots of removed assets
+#[derive(Default)]
+struct AssetSlots {
+    indices: HashMap<HandleId, usize>,
+    free_indices: Vec<usize>,
+    len: usize,
+}
+
+impl AssetSlots {
+    fn get(&self, handle: HandleId) -> Option<usize> {
+        self.indices.get(&handle).copied()
+    }
+
+    fn get_or_assign(&mut self, handle: HandleId) -> usize {
+        if let Some(index) = self.get(handle) {
+            index
+        } else {
+            let index = self.free_indices.pop().unwrap_or_else(|| {
+                self.len += 1;
+                self.len - 1
+            });
+            self.indices.insert(handle, index);
+            index
+        }
+    }
+
+    fn remove(&mut self, handle: HandleId) {
+        if let Some(index) = self.indices.remove(&handle) {
+            self.free_indices.push(index);
+        }
+    }
 }
 
 /// Used to track items in a gpu buffer in an "array" style
-#[derive(Debug)]
-struct BufferArray<I> {
+struct BufferArray {
     item_size: usize,
     buffer_capacity: usize,
     min_capacity: usize,
-    len: usize,
     buffer: Option<BufferId>,
-    free_indices: Vec<usize>,
-    indices: HashMap<I, usize>,
 }
 
-impl<I: Hash + Eq> BufferArray<I> {
+impl BufferArray {
     pub fn new(item_size: usize, min_capacity: usize) -> Self {
         BufferArray {
             item_size,
-            len: 0,
             buffer_capacity: 0,
             min_capacity,
             buffer: None,
-            free_indices: Vec::new(),
-            indices: HashMap::default(),
         }
     }
 
-    fn get_or_assign_index(&mut self, id: I) -> usize {
-        if let Some(index) = self.indices.get(&id) {
-            *index
-        } else if let Some(index) = self.free_indices.pop() {
-            self.indices.insert(id, index);
-            self.len += 1;
-            index
-        } else {
-            let index = self.len;
-            self.indices.insert(id, index);
-            self.len += 1;
-            index
+    pub fn get_binding(&self, index: usize) -> RenderResourceBinding {
+        RenderResourceBinding::Buffer {
+            buffer: self.buffer.unwrap(),
+            dynamic_index: Some((index * self.item_size) as u32),
+            range: 0..self.item_size as u64,
         }
     }
 
-    pub fn get_binding(&self, id: I) -> Option<RenderResourceBinding> {
-        self.indices
-            .get(&id)
-            .map(|index| RenderResourceBinding::Buffer {
-                buffer: self.buffer.unwrap(),
-                dynamic_index: Some((index * self.item_size) as u32),
-                range: 0..self.item_size as u64,
-            })
-    }
-
-    pub fn remove_binding(&mut self, id: I) {
-        if let Some(index) = self.indices.remove(&id) {
-            self.free_indices.push(index);
-            self.len -= 1;
-        }
-    }
-
-    pub fn resize(&mut self, render_resource_context: &dyn RenderResourceContext) -> bool {
-        if self.len <= self.buffer_capacity {
+    pub fn resize(
+        &mut self,
+        len: usize,
+        render_resource_context: &dyn RenderResourceContext,
+    ) -> bool {
+        if len <= self.buffer_capacity {
             return false;
         }
 
-        self.allocate_buffer(render_resource_context);
+        self.allocate_buffer(len, render_resource_context);
         // TODO: allow shrinking
         true
     }
 
-    pub fn allocate_buffer(&mut self, render_resource_context: &dyn RenderResourceContext) {
+    pub fn allocate_buffer(
+        &mut self,
+        len: usize,
+        render_resource_context: &dyn RenderResourceContext,
+    ) {
         if let Some(old_buffer) = self.buffer.take() {
             render_resource_context.remove_buffer(old_buffer);
         }
 
         let new_len = if self.buffer_capacity == 0 {
-            self.min_capacity.max(self.len)
+            self.min_capacity.max(len)
         } else {
-            self.min_capacity.max(self.len * 2)
+            self.min_capacity.max(len * 2)
         };
 
         let size = new_len * self.item_size;
@@ -119,39 +289,47 @@ impl<I: Hash + Eq> BufferArray<I> {
     }
 }
 
-struct UniformBufferArrays<I, T>
+/// The staging buffer of one frame in flight, so the CPU never writes to a buffer the GPU may
+/// still copy from
+#[derive(Debug, Default, Clone, Copy)]
+struct StagingBuffer {
+    buffer: Option<BufferId>,
+    size: usize,
+}
+
+struct UniformBufferArrays<T>
 where
     T: renderer::RenderResources,
 {
-    buffer_arrays: Vec<Option<BufferArray<I>>>,
-    staging_buffer: Option<BufferId>,
-    staging_buffer_size: usize,
+    buffer_arrays: Vec<Option<BufferArray>>,
+    staging_buffers: PerFrame<StagingBuffer>,
     required_staging_buffer_size: usize,
-    current_staging_buffer_offset: usize,
+    current_uniform_data_offset: usize,
     queued_buffer_writes: Vec<QueuedBufferWrite>,
+    /// Uniform data written this update, before it is laid out in the staging buffer
+    uniform_data: Vec<u8>,
     _marker: PhantomData<T>,
 }
 
-impl<I, T> Default for UniformBufferArrays<I, T>
+impl<T> Default for UniformBufferArrays<T>
 where
     T: renderer::RenderResources,
 {
     fn default() -> Self {
         Self {
             buffer_arrays: Default::default(),
-            staging_buffer: Default::default(),
-            staging_buffer_size: 0,
-            current_staging_buffer_offset: 0,
+            staging_buffers: Default::default(),
+            current_uniform_data_offset: 0,
             queued_buffer_writes: Vec::new(),
+            uniform_data: Vec::new(),
             required_staging_buffer_size: 0,
             _marker: Default::default(),
         }
     }
 }
 
-impl<I, T> UniformBufferArrays<I, T>
+impl<T> UniformBufferArrays<T>
 where
-    I: Hash + Eq + Copy,
     T: renderer::RenderResources,
 {
     /// Initialize this UniformBufferArrays using information from a RenderResources value.
@@ -179,40 +357,40 @@ where
     /// Resets staging buffer tracking information
     fn begin_update(&mut self) {
         self.required_staging_buffer_size = 0;
-        self.current_staging_buffer_offset = 0;
+        self.current_uniform_data_offset = 0;
+        self.uniform_data.clear();
     }
 
-    /// Find a spot for the given RenderResources in each uniform's BufferArray and prepare space in
-    /// the staging buffer
-    fn prepare_uniform_buffers(&mut self, id: I, render_resources: &T) {
+    /// Prepare space in the staging buffer for the given RenderResources
+    fn prepare_uniform_buffers(&mut self, render_resources: &T) {
         for (i, render_resource) in render_resources.iter().enumerate() {
             if let Some(RenderResourceType::Buffer) = render_resource.resource_type() {
-                let size = render_resource.buffer_byte_len().unwrap();
-                if let Some(buffer_array) = &mut self.buffer_arrays[i] {
-                    buffer_array.get_or_assign_index(id);
-                    self.required_staging_buffer_size += size;
+                if let Some(buffer_array) = &self.buffer_arrays[i] {
+                    // coalesced writes keep the alignment padding between items
+                    self.required_staging_buffer_size += buffer_array.item_size;
                 }
             }
         }
     }
 
-    /// Resize BufferArray buffers if they aren't large enough
+    /// Resize BufferArray buffers if they can't hold `len` items
     fn resize_buffer_arrays(
         &mut self,
+        len: usize,
         render_resource_context: &dyn RenderResourceContext,
     ) -> bool {
         let mut resized = false;
         for buffer_array in self.buffer_arrays.iter_mut().flatten() {
-            resized |= buffer_array.resize(render_resource_context);
+            resized |= buffer_array.resize(len, render_resource_context);
         }
 
         resized
     }
 
-    fn set_required_staging_buffer_size_to_max(&mut self) {
+    fn set_required_staging_buffer_size_to_max(&mut self, len: usize) {
         let mut new_size = 0;
         for buffer_array in self.buffer_arrays.iter().flatten() {
-            new_size += buffer_array.item_size * buffer_array.len;
+            new_size += buffer_array.item_size * len;
         }
 
         if new_size > self.required_staging_buffer_size {
@@ -220,43 +398,43 @@ where
         }
     }
 
-    /// Update the staging buffer to provide enough space to copy data to target buffers.
-    fn resize_staging_buffer(&mut self, render_resource_context: &dyn RenderResourceContext) {
+    /// Update the staging buffer of the current frame to provide enough space to copy data to
+    /// target buffers.
+    fn resize_staging_buffer(
+        &mut self,
+        render_resource_context: &dyn RenderResourceContext,
+        frames_in_flight: &FramesInFlight,
+    ) -> StagingBuffer {
+        let staging_buffer = self.staging_buffers.get_mut(frames_in_flight);
         // TODO: allow staging buffer to scale down
-        if self.required_staging_buffer_size > self.staging_buffer_size {
-            if let Some(staging_buffer) = self.staging_buffer {
-                render_resource_context.remove_buffer(staging_buffer);
+        if self.required_staging_buffer_size > staging_buffer.size {
+            if let Some(buffer) = staging_buffer.buffer {
+                render_resource_context.remove_buffer(buffer);
             }
 
             if self.required_staging_buffer_size > 0 {
-                let staging_buffer = render_resource_context.create_buffer(BufferInfo {
+                let buffer = render_resource_context.create_buffer(BufferInfo {
                     buffer_usage: BufferUsage::COPY_SRC | BufferUsage::MAP_WRITE,
                     size: self.required_staging_buffer_size,
                     ..Default::default()
                 });
-                self.staging_buffer = Some(staging_buffer);
+                staging_buffer.buffer = Some(buffer);
             } else {
-                self.staging_buffer = None;
+                staging_buffer.buffer = None;
             }
 
-            self.staging_buffer_size = self.required_staging_buffer_size;
-        }
-    }
-
-    fn remove_bindings(&mut self, id: I) {
-        for buffer_array in self.buffer_arrays.iter_mut().flatten() {
-            buffer_array.remove_binding(id);
+            staging_buffer.size = self.required_staging_buffer_size;
         }
+        *staging_buffer
     }
 
     fn write_uniform_buffers(
         &mut self,
-        id: I,
+        index: usize,
         uniforms: &T,
         dynamic_uniforms: bool,
         render_resource_context: &dyn RenderResourceContext,
         render_resource_bindings: &mut RenderResourceBindings,
-        staging_buffer: &mut [u8],
     ) {
         for (i, render_resource) in uniforms.iter().enumerate() {
             if let Some(RenderResourceType::Buffer) = render_resource.resource_type() {
@@ -265,8 +443,9 @@ where
                 let aligned_size = render_resource_context.get_aligned_uniform_size(size, false);
                 let buffer_array = self.buffer_arrays[i].as_mut().unwrap();
                 let range = 0..aligned_size as u64;
-                let (target_buffer, target_offset) = if dynamic_uniforms {
-                    let binding = buffer_array.get_binding(id).unwrap();
+                render_resource_bindings.set_buffer_byte_len(render_resource_name, size as u64);
+                let (target_buffer, target_offset, padded_size) = if dynamic_uniforms {
+                    let binding = buffer_array.get_binding(index);
                     let dynamic_index = if let RenderResourceBinding::Buffer {
                         dynamic_index: Some(dynamic_index),
                         ..
@@ -277,7 +456,11 @@ where
                         panic!("Dynamic index should always be set.");
                     };
                     render_resource_bindings.set(render_resource_name, binding);
-                    (buffer_array.buffer.unwrap(), dynamic_index)
+                    (
+                        buffer_array.buffer.unwrap(),
+                        dynamic_index,
+                        buffer_array.item_size,
+                    )
                 } else {
                     let mut matching_buffer = None;
                     if let Some(binding) = render_resource_bindings.get(render_resource_name) {
@@ -321,25 +504,39 @@ where
                         buffer
                     };
 
-                    (resource, 0)
+                    (resource, 0, size)
                 };
 
+                self.uniform_data
+                    .resize(self.current_uniform_data_offset + size, 0);
                 render_resource.write_buffer_bytes(
-                    &mut staging_buffer[self.current_staging_buffer_offset
-                        ..(self.current_staging_buffer_offset + size)],
+                    &mut self.uniform_data[self.current_uniform_data_offset
+                        ..(self.current_uniform_data_offset + size)],
                 );
 
                 self.queued_buffer_writes.push(QueuedBufferWrite {
                     buffer: target_buffer,
                     target_offset: target_offset as usize,
-                    source_offset: self.current_staging_buffer_offset,
+                    source_offset: self.current_uniform_data_offset,
                     size,
+                    padded_size,
                 });
-                self.current_staging_buffer_offset += size;
+                self.current_uniform_data_offset += size;
             }
         }
     }
 
+    /// Writes the uniform data to the staging buffer, merging writes to neighboring slots of a
+    /// buffer array into a single copy
+    fn write_staging_buffer(&mut self, staging_buffer: &mut [u8]) {
+        let writes = std::mem::take(&mut self.queued_buffer_writes);
+        let (merged, data_ranges) = coalesce_buffer_writes(writes);
+        for (source, target) in data_ranges {
+            staging_buffer[target].copy_from_slice(&self.uniform_data[source]);
+        }
+        self.queued_buffer_writes = merged;
+    }
+
     fn copy_staging_buffer_to_final_buffers(
         &mut self,
         command_queue: &mut CommandQueue,
@@ -403,7 +600,8 @@ where
         let system = render_resources_node_system::<T>.system().config(|config| {
             config.0 = Some(RenderResourcesNodeState {
                 command_queue: self.command_queue.clone(),
-                uniform_buffer_arrays: UniformBufferArrays::<Entity, T>::default(),
+                uniform_buffer_arrays: UniformBufferArrays::<T>::default(),
+                slots: EntitySlots::default(),
                 dynamic_uniforms: self.dynamic_uniforms,
             })
         });
@@ -412,27 +610,34 @@ where
     }
 }
 
-struct RenderResourcesNodeState<I, T: RenderResources> {
+struct RenderResourcesNodeState<S, T: RenderResources> {
     command_queue: CommandQueue,
-    uniform_buffer_arrays: UniformBufferArrays<I, T>,
+    uniform_buffer_arrays: UniformBufferArrays<T>,
+    slots: S,
     dynamic_uniforms: bool,
 }
 
-impl<I, T: RenderResources> Default for RenderResourcesNodeState<I, T> {
+impl<S: Default, T: RenderResources> Default for RenderResourcesNodeState<S, T> {
     fn default() -> Self {
         Self {
             command_queue: Default::default(),
             uniform_buffer_arrays: Default::default(),
+            slots: Default::default(),
             dynamic_uniforms: Default::default(),
         }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn render_resources_node_system<T: RenderResources>(
-    mut state: Local<RenderResourcesNodeState<Entity, T>>,
+    mut state: Local<RenderResourcesNodeState<EntitySlots, T>>,
     mut entities_waiting_for_textures: Local<Vec<Entity>>,
     render_resource_context: Res<Box<dyn RenderResourceContext>>,
-    removed: RemovedComponents<T>,
+    asset_server: Res<AssetServer>,
+    frames_in_flight: Res<FramesInFlight>,
+    archetypes: &Archetypes,
+    components: &Components,
+    entities: &Entities,
     mut queries: QuerySet<(
         Query<(Entity, &T, &Visible, &mut RenderPipelines), Or<(Changed<T>, Changed<Visible>)>>,
         Query<(Entity, &T, &Visible, &mut RenderPipelines)>,
@@ -447,10 +652,6 @@ fn render_resources_node_system<T: RenderResources>(
         uniform_buffer_arrays.initialize(first, render_resource_context);
     }
 
-    for entity in removed.iter() {
-        uniform_buffer_arrays.remove_bindings(entity);
-    }
-
     // handle entities that were waiting for texture loads on the last update
     for entity in std::mem::take(&mut *entities_waiting_for_textures) {
         if let Ok((entity, uniforms, _visible, mut render_pipelines)) =
@@ -459,6 +660,7 @@ fn render_resources_node_system<T: RenderResources>(
             if !setup_uniform_texture_resources::<T>(
                 &uniforms,
                 render_resource_context,
+                &asset_server,
                 &mut render_pipelines.bindings,
             ) {
                 entities_waiting_for_textures.push(entity);
@@ -466,79 +668,127 @@ fn render_resources_node_system<T: RenderResources>(
         }
     }
 
+    let mut changed_entities = Vec::new();
     for (entity, uniforms, visible, mut render_pipelines) in queries.q0_mut().iter_mut() {
         if !visible.is_visible {
             continue;
         }
-        uniform_buffer_arrays.prepare_uniform_buffers(entity, uniforms);
+        changed_entities.push(entity);
         if !setup_uniform_texture_resources::<T>(
             &uniforms,
             render_resource_context,
+            &asset_server,
             &mut render_pipelines.bindings,
         ) {
             entities_waiting_for_textures.push(entity);
         }
     }
 
-    let resized = uniform_buffer_arrays.resize_buffer_arrays(render_resource_context);
-    if resized {
-        uniform_buffer_arrays.set_required_staging_buffer_size_to_max()
-    }
-    uniform_buffer_arrays.resize_staging_buffer(render_resource_context);
-
-    if let Some(staging_buffer) = state.uniform_buffer_arrays.staging_buffer {
-        render_resource_context.map_buffer(staging_buffer, BufferMapMode::Write);
-        render_resource_context.write_mapped_buffer(
-            staging_buffer,
-            0..state.uniform_buffer_arrays.staging_buffer_size as u64,
-            &mut |mut staging_buffer, _render_resource_context| {
-                // if the buffer array was resized, write all entities to the new buffer, otherwise
-                // only write changes
-                if resized {
-                    for (entity, uniforms, visible, mut render_pipelines) in
-                        queries.q1_mut().iter_mut()
-                    {
-                        if !visible.is_visible {
-                            continue;
-                        }
-
-                        state.uniform_buffer_arrays.write_uniform_buffers(
-                            entity,
-                            &uniforms,
-                            state.dynamic_uniforms,
-                            render_resource_context,
-                            &mut render_pipelines.bindings,
-                            &mut staging_buffer,
-                        );
-                    }
-                } else {
-                    for (entity, uniforms, visible, mut render_pipelines) in
-                        queries.q0_mut().iter_mut()
-                    {
-                        if !visible.is_visible {
-                            continue;
-                        }
+    // the archetypes matching the queries
+    let component_ids = [
+        TypeId::of::<T>(),
+        TypeId::of::<Visible>(),
+        TypeId::of::<RenderPipelines>(),
+    ]
+    .iter()
+    .map(|type_id| components.get_id(*type_id))
+    .collect::<Option<Vec<_>>>()
+    .unwrap_or_default();
+    let matches = |archetype: &Archetype| {
+        !component_ids.is_empty() && component_ids.iter().all(|id| archetype.contains(*id))
+    };
+    let entities_to_write = state.slots.update(archetypes, matches, changed_entities);
+    for entity in entities_to_write.iter() {
+        if let Ok((_, uniforms, visible, _)) = queries.q1_mut().get_mut(*entity) {
+            if visible.is_visible {
+                uniform_buffer_arrays.prepare_uniform_buffers(uniforms);
+            }
+        }
+    }
 
-                        state.uniform_buffer_arrays.write_uniform_buffers(
-                            entity,
-                            &uniforms,
-                            state.dynamic_uniforms,
-                            render_resource_context,
-                            &mut render_pipelines.bindings,
-                            &mut staging_buffer,
-                        );
+    let resized =
+        uniform_buffer_arrays.resize_buffer_arrays(state.slots.len, render_resource_context);
+    if resized {
+        uniform_buffer_arrays.set_required_staging_buffer_size_to_max(state.slots.len)
+    }
+    let staging_buffer =
+        uniform_buffer_arrays.resize_staging_buffer(render_resource_context, &frames_in_flight);
+
+    if let Some(buffer) = staging_buffer.buffer {
+        let slots = &state.slots;
+        let uniform_buffer_arrays = &mut state.uniform_buffer_arrays;
+        let dynamic_uniforms = state.dynamic_uniforms;
+        let mut write_uniform_buffers =
+            |entity: Entity, uniforms: &T, bindings: &mut RenderResourceBindings| {
+                if let Some(index) = entities
+                    .get(entity)
+                    .and_then(|location| slots.get(location))
+                {
+                    uniform_buffer_arrays.write_uniform_buffers(
+                        index,
+                        uniforms,
+                        dynamic_uniforms,
+                        render_resource_context,
+                        bindings,
+                    );
+                }
+            };
+        // if the buffer array was resized, write all entities to the new buffer, otherwise
+        // only write changed and moved entities
+        if resized {
+            for (entity, uniforms, visible, mut render_pipelines) in queries.q1_mut().iter_mut() {
+                if visible.is_visible {
+                    write_uniform_buffers(entity, uniforms, &mut render_pipelines.bindings);
+                }
+            }
+        } else {
+            for entity in entities_to_write {
+                if let Ok((entity, uniforms, visible, mut render_pipelines)) =
+                    queries.q1_mut().get_mut(entity)
+                {
+                    if visible.is_visible {
+                        write_uniform_buffers(entity, uniforms, &mut render_pipelines.bindings);
                     }
                 }
-            },
+            }
+        }
+
+        write_staging_buffer(
+            &mut state.uniform_buffer_arrays,
+            buffer,
+            staging_buffer.size,
+            render_resource_context,
         );
-        render_resource_context.unmap_buffer(staging_buffer);
 
         state
             .uniform_buffer_arrays
-            .copy_staging_buffer_to_final_buffers(&mut state.command_queue, staging_buffer);
+            .copy_staging_buffer_to_final_buffers(&mut state.command_queue, buffer);
     }
 }
 
+fn write_staging_buffer<T>(
+    uniform_buffer_arrays: &mut UniformBufferArrays<T>,
+    staging_buffer: BufferId,
+    staging_buffer_size: usize,
+    render_resource_context: &dyn RenderResourceContext,
+) where
+    T: RenderResources,
+{
+    if uniform_buffer_arrays.queued_buffer_writes.is_empty() {
+        return;
+    }
+
+    render_resource_context.map_buffer(staging_buffer, BufferMapMode::Write);
+    render_resource_context.write_mapped_buffer(
+        staging_buffer,
+        0..staging_buffer_size as u64,
+        &mut |staging_buffer, _render_resource_context| {
+            uniform_buffer_arrays.write_staging_buffer(staging_buffer);
+        },
+    );
+    render_resource_context.unmap_buffer(staging_buffer);
+}
+
 #[derive(Default)]
 pub struct AssetRenderResourcesNode<T>
 where
@@ -587,7 +837,8 @@ where
             .config(|config| {
                 config.0 = Some(RenderResourcesNodeState {
                     command_queue: self.command_queue.clone(),
-                    uniform_buffer_arrays: UniformBufferArrays::<HandleId, T>::default(),
+                    uniform_buffer_arrays: UniformBufferArrays::<T>::default(),
+                    slots: AssetSlots::default(),
                     dynamic_uniforms: self.dynamic_uniforms,
                 })
             });
@@ -612,12 +863,14 @@ impl<T: Asset> Default for AssetRenderNodeState<T> {
 
 #[allow(clippy::too_many_arguments)]
 fn asset_render_resources_node_system<T: RenderResources + Asset>(
-    mut state: Local<RenderResourcesNodeState<HandleId, T>>,
+    mut state: Local<RenderResourcesNodeState<AssetSlots, T>>,
     mut asset_state: Local<AssetRenderNodeState<T>>,
     assets: Res<Assets<T>>,
     mut asset_events: EventReader<AssetEvent<T>>,
     mut asset_render_resource_bindings: ResMut<AssetRenderResourceBindings>,
     render_resource_context: Res<Box<dyn RenderResourceContext>>,
+    asset_server: Res<AssetServer>,
+    frames_in_flight: Res<FramesInFlight>,
     removed_handles: RemovedComponents<Handle<T>>,
     mut queries: QuerySet<(
         Query<(&Handle<T>, &mut RenderPipelines), Changed<Handle<T>>>,
@@ -642,7 +895,7 @@ fn asset_render_resources_node_system<T: RenderResources + Asset>(
                 }
             }
             AssetEvent::Removed { ref handle } => {
-                uniform_buffer_arrays.remove_bindings(handle.id);
+                state.slots.remove(handle.id);
                 // if asset was modified and removed in the same update, ignore the modification
                 // events are ordered so future modification events are ok
                 changed_assets.remove(&handle.id);
@@ -655,8 +908,12 @@ fn asset_render_resources_node_system<T: RenderResources + Asset>(
         if let Some(asset) = assets.get(asset_handle) {
             let mut bindings =
                 asset_render_resource_bindings.get_or_insert_mut(&Handle::<T>::weak(asset_handle));
-            if !setup_uniform_texture_resources::<T>(&asset, render_resource_context, &mut bindings)
-            {
+            if !setup_uniform_texture_resources::<T>(
+                &asset,
+                render_resource_context,
+                &asset_server,
+                &mut bindings,
+            ) {
                 asset_state.assets_waiting_for_textures.push(asset_handle);
             }
         }
@@ -685,68 +942,73 @@ fn asset_render_resources_node_system<T: RenderResources + Asset>(
     }
 
     for (asset_handle, asset) in changed_assets.iter() {
-        uniform_buffer_arrays.prepare_uniform_buffers(*asset_handle, asset);
+        state.slots.get_or_assign(*asset_handle);
+        uniform_buffer_arrays.prepare_uniform_buffers(asset);
         let mut bindings =
             asset_render_resource_bindings.get_or_insert_mut(&Handle::<T>::weak(*asset_handle));
-        if !setup_uniform_texture_resources::<T>(&asset, render_resource_context, &mut bindings) {
+        if !setup_uniform_texture_resources::<T>(
+            &asset,
+            render_resource_context,
+            &asset_server,
+            &mut bindings,
+        ) {
             asset_state.assets_waiting_for_textures.push(*asset_handle);
         }
     }
 
-    let resized = uniform_buffer_arrays.resize_buffer_arrays(render_resource_context);
+    let resized =
+        uniform_buffer_arrays.resize_buffer_arrays(state.slots.len, render_resource_context);
     if resized {
         // full asset copy needed, make sure there is also space for unchanged assets
         for (asset_handle, asset) in assets.iter() {
             if !changed_assets.contains_key(&asset_handle) {
-                uniform_buffer_arrays.prepare_uniform_buffers(asset_handle, asset);
+                uniform_buffer_arrays.prepare_uniform_buffers(asset);
             }
         }
-        uniform_buffer_arrays.set_required_staging_buffer_size_to_max()
-    }
-    uniform_buffer_arrays.resize_staging_buffer(render_resource_context);
-
-    if let Some(staging_buffer) = state.uniform_buffer_arrays.staging_buffer {
-        render_resource_context.map_buffer(staging_buffer, BufferMapMode::Write);
-        render_resource_context.write_mapped_buffer(
-            staging_buffer,
-            0..state.uniform_buffer_arrays.staging_buffer_size as u64,
-            &mut |mut staging_buffer, _render_resource_context| {
-                if resized {
-                    for (asset_handle, asset) in assets.iter() {
-                        let mut render_resource_bindings = asset_render_resource_bindings
-                            .get_or_insert_mut(&Handle::<T>::weak(asset_handle));
-                        // TODO: only setup buffer if we haven't seen this handle before
-                        state.uniform_buffer_arrays.write_uniform_buffers(
-                            asset_handle,
-                            &asset,
-                            state.dynamic_uniforms,
-                            render_resource_context,
-                            &mut render_resource_bindings,
-                            &mut staging_buffer,
-                        );
-                    }
-                } else {
-                    for (asset_handle, asset) in changed_assets.iter() {
-                        let mut render_resource_bindings = asset_render_resource_bindings
-                            .get_or_insert_mut(&Handle::<T>::weak(*asset_handle));
-                        // TODO: only setup buffer if we haven't seen this handle before
-                        state.uniform_buffer_arrays.write_uniform_buffers(
-                            *asset_handle,
-                            &asset,
-                            state.dynamic_uniforms,
-                            render_resource_context,
-                            &mut render_resource_bindings,
-                            &mut staging_buffer,
-                        );
-                    }
-                }
-            },
+        uniform_buffer_arrays.set_required_staging_buffer_size_to_max(state.slots.len)
+    }
+    let staging_buffer =
+        uniform_buffer_arrays.resize_staging_buffer(render_resource_context, &frames_in_flight);
+
+    if let Some(buffer) = staging_buffer.buffer {
+        if resized {
+            for (asset_handle, asset) in assets.iter() {
+                let mut render_resource_bindings = asset_render_resource_bindings
+                    .get_or_insert_mut(&Handle::<T>::weak(asset_handle));
+                // TODO: only setup buffer if we haven't seen this handle before
+                state.uniform_buffer_arrays.write_uniform_buffers(
+                    state.slots.get_or_assign(asset_handle),
+                    &asset,
+                    state.dynamic_uniforms,
+                    render_resource_context,
+                    &mut render_resource_bindings,
+                );
+            }
+        } else {
+            for (asset_handle, asset) in changed_assets.iter() {
+                let mut render_resource_bindings = asset_render_resource_bindings
+                    .get_or_insert_mut(&Handle::<T>::weak(*asset_handle));
+                // TODO: only setup buffer if we haven't seen this handle before
+                state.uniform_buffer_arrays.write_uniform_buffers(
+                    state.slots.get_or_assign(*asset_handle),
+                    &asset,
+                    state.dynamic_uniforms,
+                    render_resource_context,
+                    &mut render_resource_bindings,
+                );
+            }
+        }
+
+        write_staging_buffer(
+            &mut state.uniform_buffer_arrays,
+            buffer,
+            staging_buffer.size,
+            render_resource_context,
         );
-        render_resource_context.unmap_buffer(staging_buffer);
 
         state
             .uniform_buffer_arrays
-            .copy_staging_buffer_to_final_buffers(&mut state.command_queue, staging_buffer);
+            .copy_staging_buffer_to_final_buffers(&mut state.command_queue, buffer);
     }
 
     // update removed entity asset mapping
@@ -772,6 +1034,7 @@ fn asset_render_resources_node_system<T: RenderResources + Asset>(
 fn setup_uniform_texture_resources<T>(
     uniforms: &T,
     render_resource_context: &dyn RenderResourceContext,
+    asset_server: &AssetServer,
     render_resource_bindings: &mut RenderResourceBindings,
 ) -> bool
 where
@@ -781,26 +1044,24 @@ where
     for (i, render_resource) in uniforms.iter().enumerate() {
         if let Some(RenderResourceType::Texture) = render_resource.resource_type() {
             let render_resource_name = uniforms.get_render_resource_name(i).unwrap();
-            let sampler_name = format!("{}_sampler", render_resource_name);
             if let Some(texture_handle) = render_resource.texture() {
-                if let Some(texture_resource) = render_resource_context
-                    .get_asset_resource(texture_handle, texture::TEXTURE_ASSET_INDEX)
-                {
-                    let sampler_resource = render_resource_context
-                        .get_asset_resource(texture_handle, texture::SAMPLER_ASSET_INDEX)
-                        .unwrap();
-
-                    render_resource_bindings.set(
+                if set_texture_bindings(
+                    render_resource_name,
+                    texture_handle,
+                    render_resource_context,
+                    render_resource_bindings,
+                ) {
+                    continue;
+                }
+                success = false;
+                // the entity keeps waiting, so the texture is bound once it loads again
+                if asset_server.get_load_state(texture_handle) == LoadState::Failed {
+                    set_texture_bindings(
                         render_resource_name,
-                        RenderResourceBinding::Texture(texture_resource.get_texture().unwrap()),
+                        &texture::ERROR_TEXTURE_HANDLE.typed(),
+                        render_resource_context,
+                        render_resource_bindings,
                     );
-                    render_resource_bindings.set(
-                        &sampler_name,
-                        RenderResourceBinding::Sampler(sampler_resource.get_sampler().unwrap()),
-                    );
-                    continue;
-                } else {
-                    success = false;
                 }
             }
         }
@@ -808,3 +1069,265 @@ where
 
     success
 }
+
+/// Binds the texture and sampler of `texture_handle`, returning false if they don't exist yet
+fn set_texture_bindings(
+    render_resource_name: &str,
+    texture_handle: &Handle<texture::Texture>,
+    render_resource_context: &dyn RenderResourceContext,
+    render_resource_bindings: &mut RenderResourceBindings,
+) -> bool {
+    let texture_resource = match render_resource_context
+        .get_asset_resource(texture_handle, texture::TEXTURE_ASSET_INDEX)
+    {
+        Some(texture_resource) => texture_resource,
+        None => return false,
+    };
+    let sampler_resource = render_resource_context
+        .get_asset_resource(texture_handle, texture::SAMPLER_ASSET_INDEX)
+        .unwrap();
+
+    render_resource_bindings.set(
+        render_resource_name,
+        RenderResourceBinding::Texture(texture_resource.get_texture().unwrap()),
+    );
+    render_resource_bindings.set(
+        &format!("{}_sampler", render_resource_name),
+        RenderResourceBinding::Sampler(sampler_resource.get_sampler().unwrap()),
+    );
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reuses_freed_asset_slots() {
+        let mut slots = AssetSlots::default();
+        let (a, b, c) = (
+            HandleId::random::<texture::Texture>(),
+            HandleId::random::<texture::Texture>(),
+            HandleId::random::<texture::Texture>(),
+        );
+        assert_eq!(slots.get_or_assign(a), 0);
+        assert_eq!(slots.get_or_assign(b), 1);
+        assert_eq!(slots.get_or_assign(a), 0);
+
+        slots.remove(a);
+        assert_eq!(slots.get_or_assign(c), 0);
+        assert_eq!(slots.get_or_assign(a), 2);
+        assert_eq!(slots.len, 3);
+    }
+
+    #[test]
+    fn assigns_contiguous_slots_per_archetype() {
+        struct A;
+        struct B;
+
+        let mut world = World::new();
+        let a = world.spawn().insert(A).id();
+        let b = world.spawn().insert(A).id();
+        let c = world.spawn().insert(A).insert(B).id();
+        let slots_of = |world: &World, slots: &EntitySlots, entities: &[Entity]| {
+            entities
+                .iter()
+                .map(|entity| slots.get(world.entities().get(*entity).unwrap()))
+                .collect::<Vec<_>>()
+        };
+
+        let mut slots = EntitySlots::default();
+        let a_id = world.components().get_id(TypeId::of::<A>()).unwrap();
+        let matches = |archetype: &Archetype| archetype.contains(a_id);
+        // every entity is written when its archetype first gets a region
+        let written = slots.update(world.archetypes(), matches, vec![a]);
+        assert_eq!(written, vec![a, b, c]);
+        assert_eq!(
+            slots_of(&world, &slots, &[a, b, c]),
+            vec![Some(0), Some(1), Some(EntitySlots::MIN_REGION_CAPACITY)]
+        );
+        assert_eq!(slots.len, 2 * EntitySlots::MIN_REGION_CAPACITY);
+
+        // despawning moves the last entity of the archetype into the freed slot
+        world.despawn(a);
+        let written = slots.update(world.archetypes(), matches, Vec::new());
+        assert_eq!(written, vec![b]);
+        assert_eq!(slots_of(&world, &slots, &[b]), vec![Some(0)]);
+
+        // entities moving to another archetype are written to the slot of their new index
+        world.entity_mut(c).remove::<B>();
+        let d = world.spawn().insert(A).insert(B).id();
+        let written = slots.update(world.archetypes(), matches, Vec::new());
+        assert_eq!(written, vec![c, d]);
+        assert_eq!(
+            slots_of(&world, &slots, &[b, c, d]),
+            vec![Some(0), Some(1), Some(EntitySlots::MIN_REGION_CAPACITY)]
+        );
+
+        // a grown region no longer fits into its old range and is moved to the end
+        let entities = (0..EntitySlots::MIN_REGION_CAPACITY)
+            .map(|_| world.spawn().insert(A).id())
+            .collect::<Vec<_>>();
+        slots.update(world.archetypes(), matches, Vec::new());
+        assert_eq!(
+            slots_of(&world, &slots, &[b, entities[0]]),
+            vec![
+                Some(2 * EntitySlots::MIN_REGION_CAPACITY),
+                Some(2 * EntitySlots::MIN_REGION_CAPACITY + 2)
+            ]
+        );
+
+        // emptied archetypes free their region, which is reused by the first one fitting
+        world.despawn(d);
+        slots.update(world.archetypes(), matches, Vec::new());
+        let e = world.spawn().insert(A).insert(B).id();
+        let written = slots.update(world.archetypes(), matches, Vec::new());
+        assert_eq!(written, vec![e]);
+        assert_eq!(slots_of(&world, &slots, &[e]), vec![Some(0)]);
+        assert_eq!(slots.len, 4 * EntitySlots::MIN_REGION_CAPACITY);
+    }
+
+    #[test]
+    fn coalesces_neighboring_writes() {
+        let buffer = BufferId::new();
+        let write = |target_offset, source_offset| QueuedBufferWrite {
+            buffer,
+            target_offset,
+            source_offset,
+            size: 64,
+            padded_size: 256,
+        };
+        // slots 2, 0 and 1 are contiguous once sorted, slot 4 follows an unchanged slot
+        let writes = vec![
+            write(512, 0),
+            write(0, 64),
+            write(1024, 128),
+            write(256, 192),
+        ];
+        let (merged, data_ranges) = coalesce_buffer_writes(writes);
+
+        let offsets =
+            |write: &QueuedBufferWrite| (write.target_offset, write.source_offset, write.size);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(offsets(&merged[0]), (0, 0, 576));
+        assert_eq!(offsets(&merged[1]), (1024, 768, 64));
+        assert_eq!(
+            data_ranges,
+            vec![
+                (64..128, 0..64),
+                (192..256, 256..320),
+                (0..64, 512..576),
+                (128..192, 768..832),
+            ]
+        );
+    }
+
+    #[test]
+    fn binds_the_error_texture_while_a_texture_failed_to_load() {
+        use crate::{
+            renderer::{HeadlessRenderResourceContext, RenderResource, RenderResourceIterator},
+            texture::{SamplerDescriptor, Texture, TextureDescriptor},
+        };
+        use bevy_app::App;
+        use bevy_asset::{AssetLoader, AssetPlugin, LoadContext};
+        use bevy_core::CorePlugin;
+        use bevy_utils::BoxedFuture;
+
+        struct EmptyLoader;
+
+        impl AssetLoader for EmptyLoader {
+            fn load<'a>(
+                &'a self,
+                _bytes: &'a [u8],
+                _load_context: &'a mut LoadContext,
+            ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+                Box::pin(async { Ok(()) })
+            }
+
+            fn extensions(&self) -> &[&str] {
+                &["empty"]
+            }
+        }
+
+        struct TestMaterial {
+            texture: Handle<Texture>,
+        }
+
+        impl RenderResources for TestMaterial {
+            fn render_resources_len(&self) -> usize {
+                1
+            }
+
+            fn get_render_resource(&self, index: usize) -> Option<&dyn RenderResource> {
+                if index == 0 {
+                    Some(&self.texture)
+                } else {
+                    None
+                }
+            }
+
+            fn get_render_resource_name(&self, index: usize) -> Option<&str> {
+                if index == 0 {
+                    Some("TestMaterial_texture")
+                } else {
+                    None
+                }
+            }
+
+            fn iter(&self) -> RenderResourceIterator {
+                RenderResourceIterator::new(self)
+            }
+        }
+
+        let mut app = App::build();
+        app.add_plugin(CorePlugin).add_plugin(AssetPlugin);
+        let asset_server = app.world().get_resource::<AssetServer>().unwrap().clone();
+        asset_server.add_loader(EmptyLoader);
+        let material = TestMaterial {
+            texture: asset_server.load("missing.empty"),
+        };
+        for _ in 0..500 {
+            if asset_server.get_load_state(&material.texture) == LoadState::Failed {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert_eq!(
+            asset_server.get_load_state(&material.texture),
+            LoadState::Failed
+        );
+
+        let context: Box<dyn RenderResourceContext> =
+            Box::new(HeadlessRenderResourceContext::default());
+        let error_texture = texture::ERROR_TEXTURE_HANDLE.typed::<Texture>();
+        let texture_id = context.create_texture(TextureDescriptor::default());
+        let sampler_id = context.create_sampler(&SamplerDescriptor::default());
+        context.set_asset_resource(
+            &error_texture,
+            renderer::RenderResourceId::Texture(texture_id),
+            texture::TEXTURE_ASSET_INDEX,
+        );
+        context.set_asset_resource(
+            &error_texture,
+            renderer::RenderResourceId::Sampler(sampler_id),
+            texture::SAMPLER_ASSET_INDEX,
+        );
+
+        let mut bindings = RenderResourceBindings::default();
+        // the material keeps waiting for its texture
+        assert!(!setup_uniform_texture_resources(
+            &material,
+            &*context,
+            &asset_server,
+            &mut bindings
+        ));
+        assert_eq!(
+            bindings.get("TestMaterial_texture"),
+            Some(&RenderResourceBinding::Texture(texture_id))
+        );
+        assert_eq!(
+            bindings.get("TestMaterial_texture_sampler"),
+            Some(&RenderResourceBinding::Sampler(sampler_id))
+        );
+    }
+}
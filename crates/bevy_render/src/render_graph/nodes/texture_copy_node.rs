@@ -1,7 +1,7 @@
 use crate::{
     render_graph::{Node, ResourceSlots},
     renderer::{BufferInfo, BufferUsage, RenderContext},
-    texture::{Texture, TextureDescriptor, TEXTURE_ASSET_INDEX},
+    texture::{Extent3d, Texture, TEXTURE_ASSET_INDEX},
 };
 use bevy_app::{Events, ManualEventReader};
 use bevy_asset::{AssetEvent, Assets};
@@ -32,27 +32,54 @@ impl Node for TextureCopyNode {
                             continue;
                         }
 
-                        let texture_descriptor: TextureDescriptor = texture.into();
-                        let width = texture.size.width as usize;
-                        let aligned_width =
-                            render_context.resources().get_aligned_texture_size(width);
+                        let supported_compression =
+                            render_context.resources().supported_texture_compression();
+                        let decompressed;
+                        let texture = if supported_compression
+                            .contains(texture.format.required_compression())
+                        {
+                            texture
+                        } else if let Some(texture) = texture.decompress() {
+                            decompressed = texture;
+                            &decompressed
+                        } else {
+                            // the texture resource system didn't create a texture to copy to
+                            continue;
+                        };
+
+                        // compressed formats are copied a row of blocks at a time
+                        let (block_width, block_height) = texture.format.block_dimensions();
                         let format_size = texture.format.pixel_size();
-                        let mut aligned_data = vec![
-                            0;
-                            format_size
-                                * aligned_width
-                                * texture.size.height as usize
-                                * texture.size.depth as usize
-                        ];
-                        texture
-                            .data
-                            .chunks_exact(format_size * width)
-                            .enumerate()
-                            .for_each(|(index, row)| {
-                                let offset = index * aligned_width * format_size;
-                                aligned_data[offset..(offset + width * format_size)]
-                                    .copy_from_slice(row);
-                            });
+                        let mut aligned_data = Vec::new();
+                        let mut levels = Vec::new();
+                        for level in 0..texture.mip_level_count {
+                            let size = texture.mip_level_size(level);
+                            let width = ((size.width + block_width - 1) / block_width) as usize;
+                            let height = ((size.height + block_height - 1) / block_height) as usize;
+                            let aligned_width =
+                                render_context.resources().get_aligned_texture_size(width);
+                            let offset = aligned_data.len();
+                            aligned_data.resize(
+                                offset + format_size * aligned_width * height * size.depth as usize,
+                                0,
+                            );
+                            texture
+                                .mip_level_data(level)
+                                .chunks_exact(format_size * width)
+                                .enumerate()
+                                .for_each(|(index, row)| {
+                                    let row_offset = offset + index * aligned_width * format_size;
+                                    aligned_data[row_offset..(row_offset + width * format_size)]
+                                        .copy_from_slice(row);
+                                });
+                            // copies cover whole blocks, even where they extend past the texture
+                            let copy_size = Extent3d::new(
+                                width as u32 * block_width,
+                                height as u32 * block_height,
+                                size.depth,
+                            );
+                            levels.push((offset, format_size * aligned_width, copy_size));
+                        }
                         let texture_buffer = render_context.resources().create_buffer_with_data(
                             BufferInfo {
                                 buffer_usage: BufferUsage::COPY_SRC,
@@ -66,15 +93,18 @@ impl Node for TextureCopyNode {
                             .get_asset_resource(handle, TEXTURE_ASSET_INDEX)
                             .unwrap();
 
-                        render_context.copy_buffer_to_texture(
-                            texture_buffer,
-                            0,
-                            (format_size * aligned_width) as u32,
-                            texture_resource.get_texture().unwrap(),
-                            [0, 0, 0],
-                            0,
-                            texture_descriptor.size,
-                        );
+                        for (level, (offset, bytes_per_row, size)) in levels.into_iter().enumerate()
+                        {
+                            render_context.copy_buffer_to_texture(
+                                texture_buffer,
+                                offset as u64,
+                                bytes_per_row as u32,
+                                texture_resource.get_texture().unwrap(),
+                                [0, 0, 0],
+                                level as u32,
+                                size,
+                            );
+                        }
                         render_context.resources().remove_buffer(texture_buffer);
 
                         copied_textures.insert(&handle.id);
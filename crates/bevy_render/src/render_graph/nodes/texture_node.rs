@@ -1,16 +1,24 @@
 use bevy_asset::HandleUntyped;
 use bevy_ecs::world::World;
+use bevy_window::{WindowId, Windows};
 use std::borrow::Cow;
 
 use crate::{
-    render_graph::{Node, ResourceSlotInfo, ResourceSlots},
+    render_graph::{Node, ResourceSlotInfo, ResourceSlots, WindowRelativeSize},
     renderer::{RenderContext, RenderResourceId, RenderResourceType},
-    texture::{SamplerDescriptor, TextureDescriptor, SAMPLER_ASSET_INDEX, TEXTURE_ASSET_INDEX},
+    texture::{
+        Extent3d, SamplerDescriptor, TextureDescriptor, SAMPLER_ASSET_INDEX, TEXTURE_ASSET_INDEX,
+    },
 };
 pub struct TextureNode {
     pub texture_descriptor: TextureDescriptor,
     pub sampler_descriptor: Option<SamplerDescriptor>,
     pub handle: Option<HandleUntyped>,
+    /// Sizes the texture relative to a window instead of `texture_descriptor.size`. Can't be
+    /// combined with `handle`: materials sampling the handle would keep the texture removed by a
+    /// resize.
+    pub window_size: Option<(WindowId, WindowRelativeSize)>,
+    created_size: Option<Extent3d>,
 }
 
 impl TextureNode {
@@ -25,6 +33,23 @@ impl TextureNode {
             texture_descriptor,
             sampler_descriptor,
             handle,
+            window_size: None,
+            created_size: None,
+        }
+    }
+
+    /// Creates a texture node whose size is `size` relative to the physical size of the window.
+    /// The texture is recreated when the window is resized, so it is only passed through the
+    /// node's output slot and has no asset handle.
+    pub fn window_relative(
+        window_id: WindowId,
+        size: WindowRelativeSize,
+        texture_descriptor: TextureDescriptor,
+        sampler_descriptor: Option<SamplerDescriptor>,
+    ) -> Self {
+        Self {
+            window_size: Some((window_id, size)),
+            ..Self::new(texture_descriptor, sampler_descriptor, None)
         }
     }
 }
@@ -38,6 +63,23 @@ impl Node for TextureNode {
         OUTPUT
     }
 
+    fn prepare(&mut self, world: &mut World) {
+        let (window_id, size) = match self.window_size {
+            Some(window_size) => window_size,
+            None => return,
+        };
+        assert!(
+            self.handle.is_none(),
+            "Window relative texture nodes can't have a handle, as they are recreated on resize."
+        );
+        let windows = world.get_resource::<Windows>().unwrap();
+        if let Some(window) = windows.get(window_id) {
+            let (width, height) = size.apply(window.physical_width(), window.physical_height());
+            self.texture_descriptor.size.width = width;
+            self.texture_descriptor.size.height = height;
+        }
+    }
+
     fn update(
         &mut self,
         _world: &World,
@@ -45,25 +87,33 @@ impl Node for TextureNode {
         _input: &ResourceSlots,
         output: &mut ResourceSlots,
     ) {
-        if output.get(0).is_none() {
-            let render_resource_context = render_context.resources_mut();
-            let texture_id = render_resource_context.create_texture(self.texture_descriptor);
-            if let Some(handle) = &self.handle {
+        let old_texture = output.get(0);
+        if old_texture.is_some() && self.created_size == Some(self.texture_descriptor.size) {
+            return;
+        }
+
+        let render_resource_context = render_context.resources_mut();
+        if let Some(RenderResourceId::Texture(old_texture)) = old_texture {
+            render_resource_context.remove_texture(old_texture);
+        }
+        let texture_id = render_resource_context.create_texture(self.texture_descriptor);
+        if let Some(handle) = &self.handle {
+            render_resource_context.set_asset_resource_untyped(
+                handle.clone(),
+                RenderResourceId::Texture(texture_id),
+                TEXTURE_ASSET_INDEX,
+            );
+            // the sampler doesn't depend on the size, so it is kept when the texture is recreated
+            if let (Some(sampler_descriptor), None) = (self.sampler_descriptor, old_texture) {
+                let sampler_id = render_resource_context.create_sampler(&sampler_descriptor);
                 render_resource_context.set_asset_resource_untyped(
                     handle.clone(),
-                    RenderResourceId::Texture(texture_id),
-                    TEXTURE_ASSET_INDEX,
+                    RenderResourceId::Sampler(sampler_id),
+                    SAMPLER_ASSET_INDEX,
                 );
-                if let Some(sampler_descriptor) = self.sampler_descriptor {
-                    let sampler_id = render_resource_context.create_sampler(&sampler_descriptor);
-                    render_resource_context.set_asset_resource_untyped(
-                        handle.clone(),
-                        RenderResourceId::Sampler(sampler_id),
-                        SAMPLER_ASSET_INDEX,
-                    );
-                }
             }
-            output.set(0, RenderResourceId::Texture(texture_id));
         }
+        output.set(0, RenderResourceId::Texture(texture_id));
+        self.created_size = Some(self.texture_descriptor.size);
     }
 }
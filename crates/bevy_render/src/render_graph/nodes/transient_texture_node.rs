@@ -0,0 +1,104 @@
+use crate::{
+    render_graph::{Node, ResourceSlotInfo, ResourceSlots, WindowRelativeSize},
+    renderer::{RenderContext, RenderResourceType},
+    texture::TextureDescriptor,
+};
+use bevy_ecs::world::World;
+use bevy_window::{WindowId, Windows};
+use std::borrow::Cow;
+
+/// Provides a texture that only lives for part of a graph run, such as an intermediate
+/// post-processing target. The texture is valid from this node until the last node that takes it
+/// as an input. Transient textures with identical descriptors and non-overlapping lifetimes share
+/// the same GPU texture.
+///
+/// The texture is assigned by the render graph executor, see
+/// [TransientTexturePool](crate::render_graph::TransientTexturePool).
+#[derive(Debug)]
+pub struct TransientTextureNode {
+    descriptor: TextureDescriptor,
+    window_id: Option<WindowId>,
+    window_size: WindowRelativeSize,
+}
+
+impl TransientTextureNode {
+    pub const OUT_TEXTURE: &'static str = "texture";
+
+    pub fn new(descriptor: TextureDescriptor) -> Self {
+        TransientTextureNode {
+            descriptor,
+            window_id: None,
+            window_size: WindowRelativeSize::FULL,
+        }
+    }
+
+    /// Creates a texture node whose size follows the physical size of the given window
+    pub fn window_sized(window_id: WindowId, descriptor: TextureDescriptor) -> Self {
+        Self::window_relative(window_id, WindowRelativeSize::FULL, descriptor)
+    }
+
+    /// Creates a texture node whose size is the physical size of the given window divided by
+    /// `divisor`, rounded up. Useful for downsampled post-processing targets.
+    pub fn window_fraction(
+        window_id: WindowId,
+        divisor: u32,
+        descriptor: TextureDescriptor,
+    ) -> Self {
+        Self::window_relative(window_id, WindowRelativeSize::new(1, divisor), descriptor)
+    }
+
+    /// Creates a texture node whose size is `size` relative to the physical size of the window
+    pub fn window_relative(
+        window_id: WindowId,
+        size: WindowRelativeSize,
+        descriptor: TextureDescriptor,
+    ) -> Self {
+        TransientTextureNode {
+            descriptor,
+            window_id: Some(window_id),
+            window_size: size,
+        }
+    }
+
+    pub fn descriptor(&self) -> &TextureDescriptor {
+        &self.descriptor
+    }
+}
+
+impl Node for TransientTextureNode {
+    fn output(&self) -> &[ResourceSlotInfo] {
+        static OUTPUT: &[ResourceSlotInfo] = &[ResourceSlotInfo {
+            name: Cow::Borrowed(TransientTextureNode::OUT_TEXTURE),
+            resource_type: RenderResourceType::Texture,
+        }];
+        OUTPUT
+    }
+
+    fn prepare(&mut self, world: &mut World) {
+        let window_id = match self.window_id {
+            Some(window_id) => window_id,
+            None => return,
+        };
+        let windows = world.get_resource::<Windows>().unwrap();
+        if let Some(window) = windows.get(window_id) {
+            let (width, height) = self
+                .window_size
+                .apply(window.physical_width(), window.physical_height());
+            self.descriptor.size.width = width;
+            self.descriptor.size.height = height;
+        }
+    }
+
+    fn update(
+        &mut self,
+        _world: &World,
+        _render_context: &mut dyn RenderContext,
+        _input: &ResourceSlots,
+        output: &mut ResourceSlots,
+    ) {
+        debug_assert!(
+            output.get(0).is_some(),
+            "Transient textures must be assigned before the graph runs"
+        );
+    }
+}
@@ -8,8 +8,45 @@ use bevy_ecs::world::World;
 use bevy_window::{WindowCreated, WindowId, WindowResized, Windows};
 use std::borrow::Cow;
 
+/// A texture size relative to the physical size of a window, such as full, half or quarter
+/// resolution. Textures with a relative size are recreated when the window is resized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WindowRelativeSize {
+    pub numerator: u32,
+    pub denominator: u32,
+}
+
+impl Default for WindowRelativeSize {
+    fn default() -> Self {
+        WindowRelativeSize::FULL
+    }
+}
+
+impl WindowRelativeSize {
+    pub const FULL: WindowRelativeSize = WindowRelativeSize::new(1, 1);
+    pub const HALF: WindowRelativeSize = WindowRelativeSize::new(1, 2);
+    pub const QUARTER: WindowRelativeSize = WindowRelativeSize::new(1, 4);
+
+    pub const fn new(numerator: u32, denominator: u32) -> Self {
+        WindowRelativeSize {
+            numerator,
+            denominator,
+        }
+    }
+
+    /// Returns the size for a window of the given physical size, rounded up and at least 1
+    pub fn apply(&self, width: u32, height: u32) -> (u32, u32) {
+        let numerator = self.numerator.max(1) as u64;
+        let denominator = self.denominator.max(1) as u64;
+        let scale =
+            |value: u32| ((value as u64 * numerator + denominator - 1) / denominator).max(1) as u32;
+        (scale(width), scale(height))
+    }
+}
+
 pub struct WindowTextureNode {
     window_id: WindowId,
+    size: WindowRelativeSize,
     descriptor: TextureDescriptor,
     window_created_event_reader: ManualEventReader<WindowCreated>,
     window_resized_event_reader: ManualEventReader<WindowResized>,
@@ -19,8 +56,18 @@ impl WindowTextureNode {
     pub const OUT_TEXTURE: &'static str = "texture";
 
     pub fn new(window_id: WindowId, descriptor: TextureDescriptor) -> Self {
+        Self::relative(window_id, WindowRelativeSize::FULL, descriptor)
+    }
+
+    /// Creates a texture node whose size is `size` relative to the physical size of the window
+    pub fn relative(
+        window_id: WindowId,
+        size: WindowRelativeSize,
+        descriptor: TextureDescriptor,
+    ) -> Self {
         WindowTextureNode {
             window_id,
+            size,
             descriptor,
             window_created_event_reader: Default::default(),
             window_resized_event_reader: Default::default(),
@@ -67,10 +114,26 @@ impl Node for WindowTextureNode {
                 render_resource_context.remove_texture(old_texture);
             }
 
-            self.descriptor.size.width = window.physical_width();
-            self.descriptor.size.height = window.physical_height();
+            let (width, height) = self
+                .size
+                .apply(window.physical_width(), window.physical_height());
+            self.descriptor.size.width = width;
+            self.descriptor.size.height = height;
             let texture_resource = render_resource_context.create_texture(self.descriptor);
             output.set(WINDOW_TEXTURE, RenderResourceId::Texture(texture_resource));
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rounds_relative_sizes_up() {
+        assert_eq!(WindowRelativeSize::FULL.apply(1280, 720), (1280, 720));
+        assert_eq!(WindowRelativeSize::HALF.apply(1281, 721), (641, 361));
+        assert_eq!(WindowRelativeSize::QUARTER.apply(2, 0), (1, 1));
+        assert_eq!(WindowRelativeSize::new(3, 2).apply(5, 4), (8, 6));
+    }
+}
@@ -0,0 +1,103 @@
+use super::{base, AssetRenderResourcesNode, RenderGraph, RenderResourcesNode};
+use crate::renderer::RenderResources;
+use bevy_app::{AppBuilder, Plugin};
+use bevy_asset::Asset;
+pub use bevy_derive::Uniform;
+use std::{any::type_name, marker::PhantomData};
+
+/// A component whose [RenderResources] are bound to shaders drawn in the main pass once it is
+/// registered with [AddUniform::add_uniform]. Usually implemented with `#[derive(Uniform)]`.
+pub trait Uniform: RenderResources {
+    /// Whether all entities share one dynamic uniform buffer. `#[uniform(per_entity_buffers)]`
+    /// sets this to `false`.
+    const DYNAMIC_UNIFORMS: bool;
+}
+
+pub trait AddUniform {
+    /// Adds a [RenderResourcesPlugin] for `T` configured by its [Uniform] impl.
+    fn add_uniform<T: Uniform>(&mut self) -> &mut Self;
+}
+
+impl AddUniform for AppBuilder {
+    fn add_uniform<T: Uniform>(&mut self) -> &mut Self {
+        self.add_plugin(RenderResourcesPlugin::<T>::new(T::DYNAMIC_UNIFORMS))
+    }
+}
+
+/// Binds the [RenderResources] of every entity with a `T` component to shaders drawn in the main
+/// pass. Together with `#[derive(RenderResources)]` this is all that is needed to make a custom
+/// component available to shaders.
+///
+/// Must be added after [RenderPlugin](crate::RenderPlugin).
+pub struct RenderResourcesPlugin<T: RenderResources> {
+    dynamic_uniforms: bool,
+    marker: PhantomData<T>,
+}
+
+impl<T: RenderResources> RenderResourcesPlugin<T> {
+    /// Per-entity data is usually best stored in a single dynamic uniform buffer. Pass `false` to
+    /// give each entity its own buffer instead.
+    pub fn new(dynamic_uniforms: bool) -> Self {
+        RenderResourcesPlugin {
+            dynamic_uniforms,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T: RenderResources> Default for RenderResourcesPlugin<T> {
+    fn default() -> Self {
+        Self::new(true)
+    }
+}
+
+impl<T: RenderResources> Plugin for RenderResourcesPlugin<T> {
+    fn build(&self, app: &mut AppBuilder) {
+        add_main_pass_node(app, RenderResourcesNode::<T>::new(self.dynamic_uniforms));
+    }
+}
+
+/// Binds the [RenderResources] of every `T` asset to shaders drawn in the main pass, for entities
+/// with a `Handle<T>` component.
+///
+/// Must be added after [RenderPlugin](crate::RenderPlugin).
+pub struct AssetRenderResourcesPlugin<T: RenderResources + Asset> {
+    dynamic_uniforms: bool,
+    marker: PhantomData<T>,
+}
+
+impl<T: RenderResources + Asset> AssetRenderResourcesPlugin<T> {
+    pub fn new(dynamic_uniforms: bool) -> Self {
+        AssetRenderResourcesPlugin {
+            dynamic_uniforms,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T: RenderResources + Asset> Default for AssetRenderResourcesPlugin<T> {
+    fn default() -> Self {
+        Self::new(false)
+    }
+}
+
+impl<T: RenderResources + Asset> Plugin for AssetRenderResourcesPlugin<T> {
+    fn build(&self, app: &mut AppBuilder) {
+        add_main_pass_node(
+            app,
+            AssetRenderResourcesNode::<T>::new(self.dynamic_uniforms),
+        );
+    }
+}
+
+pub(crate) fn add_main_pass_node<N: super::SystemNode + 'static>(app: &mut AppBuilder, node: N) {
+    let mut render_graph = app
+        .world_mut()
+        .get_resource_mut::<RenderGraph>()
+        .expect("Plugins that add render graph nodes must be added after RenderPlugin");
+    let name = type_name::<N>();
+    render_graph.add_system_node(name, node);
+    render_graph
+        .add_node_edge(name, base::node::MAIN_PASS)
+        .unwrap();
+}
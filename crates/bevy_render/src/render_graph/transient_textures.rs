@@ -0,0 +1,178 @@
+use super::{Edge, NodeId, StageBorrow, TransientTextureNode};
+use crate::{
+    renderer::{RenderResourceContext, RenderResourceId, TextureId},
+    texture::TextureDescriptor,
+};
+use bevy_utils::HashMap;
+
+/// Owns the textures of [TransientTextureNode]s. Before each graph run the lifetime of every
+/// transient texture is derived from the execution order of the staged graph, and textures with
+/// identical descriptors and non-overlapping lifetimes are assigned the same GPU texture.
+#[derive(Debug, Default)]
+pub struct TransientTexturePool {
+    textures: Vec<(TextureDescriptor, TextureId)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TransientLifetime {
+    descriptor: TextureDescriptor,
+    /// Execution index of the node that provides the texture
+    first: usize,
+    /// Execution index of the last node that takes the texture as an input
+    last: usize,
+}
+
+impl TransientTexturePool {
+    /// The number of GPU textures currently backing transient textures
+    pub fn texture_count(&self) -> usize {
+        self.textures.len()
+    }
+
+    /// Assigns textures to the outputs of all [TransientTextureNode]s in `stages`. Nodes are
+    /// assumed to be recorded and submitted in stage, job and node order.
+    pub fn assign(
+        &mut self,
+        stages: &mut [StageBorrow],
+        render_resource_context: &dyn RenderResourceContext,
+    ) {
+        let mut execution_order = HashMap::<NodeId, usize>::default();
+        for node_state in stages
+            .iter()
+            .flat_map(|stage| stage.jobs.iter())
+            .flat_map(|job| job.node_states.iter())
+        {
+            let index = execution_order.len();
+            execution_order.insert(node_state.id, index);
+        }
+
+        let mut transient_nodes = Vec::new();
+        let mut lifetimes = Vec::new();
+        for node_state in stages
+            .iter()
+            .flat_map(|stage| stage.jobs.iter())
+            .flat_map(|job| job.node_states.iter())
+        {
+            let node = match node_state.node.downcast_ref::<TransientTextureNode>() {
+                Some(node) => node,
+                None => continue,
+            };
+            let first = execution_order[&node_state.id];
+            let last = node_state
+                .edges
+                .output_edges
+                .iter()
+                .filter_map(|edge| match edge {
+                    Edge::SlotEdge { input_node, .. } => execution_order.get(input_node).copied(),
+                    Edge::NodeEdge { .. } => None,
+                })
+                .fold(first, usize::max);
+            transient_nodes.push(node_state.id);
+            lifetimes.push(TransientLifetime {
+                descriptor: *node.descriptor(),
+                first,
+                last,
+            });
+        }
+
+        let (slots, slot_descriptors) = alias_lifetimes(&lifetimes);
+
+        // reuse last run's textures where the descriptors match
+        let mut unused = std::mem::take(&mut self.textures);
+        for descriptor in slot_descriptors {
+            let texture = match unused.iter().position(|(d, _)| *d == descriptor) {
+                Some(index) => unused.swap_remove(index).1,
+                None => render_resource_context.create_texture(descriptor),
+            };
+            self.textures.push((descriptor, texture));
+        }
+        for (_, texture) in unused {
+            render_resource_context.remove_texture(texture);
+        }
+
+        let textures = transient_nodes
+            .into_iter()
+            .zip(slots)
+            .map(|(node, slot)| (node, self.textures[slot].1))
+            .collect::<HashMap<_, _>>();
+        for node_state in stages
+            .iter_mut()
+            .flat_map(|stage| stage.jobs.iter_mut())
+            .flat_map(|job| job.node_states.iter_mut())
+        {
+            if let Some(texture) = textures.get(&node_state.id) {
+                node_state
+                    .output_slots
+                    .set(0, RenderResourceId::Texture(*texture));
+            }
+        }
+    }
+}
+
+/// Greedily packs lifetimes into as few slots as possible. Returns the slot of each lifetime and
+/// the descriptor of each slot.
+fn alias_lifetimes(lifetimes: &[TransientLifetime]) -> (Vec<usize>, Vec<TextureDescriptor>) {
+    let mut order = (0..lifetimes.len()).collect::<Vec<_>>();
+    order.sort_by_key(|i| lifetimes[*i].first);
+
+    let mut slots = vec![0; lifetimes.len()];
+    // the descriptor of each slot and the last execution index it is used at
+    let mut slot_uses: Vec<(TextureDescriptor, usize)> = Vec::new();
+    for i in order {
+        let lifetime = &lifetimes[i];
+        let free_slot = slot_uses.iter().position(|(descriptor, last)| {
+            *descriptor == lifetime.descriptor && *last < lifetime.first
+        });
+        slots[i] = match free_slot {
+            Some(slot) => {
+                slot_uses[slot].1 = lifetime.last;
+                slot
+            }
+            None => {
+                slot_uses.push((lifetime.descriptor, lifetime.last));
+                slot_uses.len() - 1
+            }
+        };
+    }
+    (
+        slots,
+        slot_uses
+            .into_iter()
+            .map(|(descriptor, _)| descriptor)
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::texture::{Extent3d, TextureFormat};
+
+    fn lifetime(descriptor: TextureDescriptor, first: usize, last: usize) -> TransientLifetime {
+        TransientLifetime {
+            descriptor,
+            first,
+            last,
+        }
+    }
+
+    #[test]
+    fn aliases_non_overlapping_lifetimes() {
+        let a = TextureDescriptor::default();
+        let b = TextureDescriptor {
+            size: Extent3d::new(2, 2, 1),
+            format: TextureFormat::Rgba16Float,
+            ..Default::default()
+        };
+        let (slots, descriptors) = alias_lifetimes(&[
+            // a post processing chain: each target is read by the next pass only
+            lifetime(a, 0, 2),
+            lifetime(a, 1, 3),
+            lifetime(a, 3, 4),
+            // a different descriptor never shares a slot
+            lifetime(b, 5, 6),
+            lifetime(a, 5, 5),
+        ]);
+        assert_eq!(slots, vec![0, 1, 0, 2, 0]);
+        assert_eq!(descriptors, vec![a, a, b]);
+    }
+}
@@ -0,0 +1,104 @@
+/// The number of frames the CPU may prepare while the GPU still renders the previous ones, from 1
+/// to 3. More frames keep the GPU busier at the cost of input latency. Insert it before the
+/// RenderPlugin is added to change the default of 2.
+#[derive(Debug, Clone)]
+pub struct FramesInFlight {
+    count: usize,
+    frame: u64,
+}
+
+impl Default for FramesInFlight {
+    fn default() -> Self {
+        FramesInFlight::new(2)
+    }
+}
+
+impl FramesInFlight {
+    pub const MAX: usize = 3;
+
+    pub fn new(count: usize) -> Self {
+        FramesInFlight {
+            count: count.max(1).min(FramesInFlight::MAX),
+            frame: 0,
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn set_count(&mut self, count: usize) {
+        self.count = count.max(1).min(FramesInFlight::MAX);
+    }
+
+    /// The number of frames started since the app started
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// The bucket of the current frame in [PerFrame] values
+    pub fn index(&self) -> usize {
+        (self.frame % self.count as u64) as usize
+    }
+
+    /// Starts the next frame. The render backend calls this once the GPU has finished the frame
+    /// that last used the same index.
+    pub fn advance(&mut self) {
+        self.frame += 1;
+    }
+}
+
+/// One value per frame in flight, for transient resources the CPU writes while the GPU may still
+/// read the ones of the previous frames, like staging buffers
+#[derive(Debug, Clone)]
+pub struct PerFrame<T> {
+    buckets: Vec<T>,
+}
+
+impl<T> Default for PerFrame<T> {
+    fn default() -> Self {
+        PerFrame {
+            buckets: Vec::new(),
+        }
+    }
+}
+
+impl<T: Default> PerFrame<T> {
+    /// The value of the current frame
+    pub fn get_mut(&mut self, frames_in_flight: &FramesInFlight) -> &mut T {
+        if self.buckets.len() < frames_in_flight.count() {
+            self.buckets
+                .resize_with(frames_in_flight.count(), Default::default);
+        }
+        &mut self.buckets[frames_in_flight.index()]
+    }
+}
+
+impl<T> PerFrame<T> {
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.buckets.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.buckets.iter_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotates_buckets() {
+        let mut frames_in_flight = FramesInFlight::new(5);
+        assert_eq!(frames_in_flight.count(), FramesInFlight::MAX);
+        let mut values = PerFrame::<Vec<u64>>::default();
+        for _ in 0..5 {
+            frames_in_flight.advance();
+            let frame = frames_in_flight.frame();
+            values.get_mut(&frames_in_flight).push(frame);
+        }
+        let buckets = values.iter().cloned().collect::<Vec<_>>();
+        assert_eq!(buckets, vec![vec![3], vec![1, 4], vec![2, 5]]);
+    }
+}
@@ -0,0 +1,80 @@
+use bevy_ecs::system::{Res, ResMut};
+use bevy_input::{keyboard::KeyCode, Input};
+
+/// Captures frames with a GPU debugger, like RenderDoc, when the render backend found one.
+/// Every pass and draw of a captured frame can then be inspected in the debugger.
+#[derive(Debug, Clone)]
+pub struct GpuCapture {
+    /// Captures the next frame when pressed
+    pub key: Option<KeyCode>,
+    requested_frames: u32,
+    available: bool,
+}
+
+impl Default for GpuCapture {
+    fn default() -> Self {
+        GpuCapture {
+            key: Some(KeyCode::F10),
+            requested_frames: 0,
+            available: false,
+        }
+    }
+}
+
+impl GpuCapture {
+    pub fn capture_next_frame(&mut self) {
+        self.capture_frames(1);
+    }
+
+    /// Captures the next `count` frames, each into its own capture
+    pub fn capture_frames(&mut self, count: u32) {
+        self.requested_frames += count;
+    }
+
+    /// Returns true if the render backend found a GPU debugger. Captures are skipped otherwise.
+    pub fn is_available(&self) -> bool {
+        self.available
+    }
+
+    /// Called by the render backend once it has looked for a GPU debugger
+    pub fn set_available(&mut self, available: bool) {
+        self.available = available;
+    }
+
+    /// Returns true if the current frame should be captured. The render backend calls this once
+    /// per frame.
+    pub fn take_request(&mut self) -> bool {
+        if self.requested_frames > 0 {
+            self.requested_frames -= 1;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+pub fn gpu_capture_key_system(
+    keyboard: Option<Res<Input<KeyCode>>>,
+    mut gpu_capture: ResMut<GpuCapture>,
+) {
+    if let (Some(keyboard), Some(key)) = (keyboard, gpu_capture.key) {
+        if keyboard.just_pressed(key) {
+            gpu_capture.capture_next_frame();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn takes_requested_frames() {
+        let mut gpu_capture = GpuCapture::default();
+        assert!(!gpu_capture.take_request());
+        gpu_capture.capture_frames(2);
+        assert!(gpu_capture.take_request());
+        assert!(gpu_capture.take_request());
+        assert!(!gpu_capture.take_request());
+    }
+}
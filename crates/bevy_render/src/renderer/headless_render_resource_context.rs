@@ -1,14 +1,15 @@
 use super::RenderResourceContext;
 use crate::{
-    pipeline::{BindGroupDescriptorId, PipelineDescriptor},
+    pipeline::{BindGroupDescriptorId, ComputePipelineDescriptor, PipelineDescriptor},
     renderer::{
-        BindGroup, BufferId, BufferInfo, BufferMapMode, RenderResourceId, SamplerId, TextureId,
+        BindGroup, BufferId, BufferInfo, BufferMapMode, BufferMapState, RenderResourceId,
+        SamplerId, TextureId,
     },
     shader::{Shader, ShaderError},
-    texture::{SamplerDescriptor, TextureDescriptor},
+    texture::{SamplerDescriptor, TextureCompression, TextureDescriptor},
 };
 use bevy_asset::{Assets, Handle, HandleUntyped};
-use bevy_utils::HashMap;
+use bevy_utils::{HashMap, HashSet};
 use bevy_window::Window;
 use parking_lot::RwLock;
 use std::{ops::Range, sync::Arc};
@@ -17,6 +18,7 @@ use std::{ops::Range, sync::Arc};
 pub struct HeadlessRenderResourceContext {
     buffer_info: Arc<RwLock<HashMap<BufferId, BufferInfo>>>,
     texture_descriptors: Arc<RwLock<HashMap<TextureId, TextureDescriptor>>>,
+    mapped_buffers: Arc<RwLock<HashSet<BufferId>>>,
     pub asset_resources: Arc<RwLock<HashMap<(HandleUntyped, u64), RenderResourceId>>>,
 }
 
@@ -81,7 +83,21 @@ impl RenderResourceContext for HeadlessRenderResourceContext {
 
     fn map_buffer(&self, _id: BufferId, _mode: BufferMapMode) {}
 
-    fn unmap_buffer(&self, _id: BufferId) {}
+    fn map_buffer_async(&self, id: BufferId, _mode: BufferMapMode) {
+        self.mapped_buffers.write().insert(id);
+    }
+
+    fn get_buffer_map_state(&self, id: BufferId) -> BufferMapState {
+        if self.mapped_buffers.read().contains(&id) {
+            BufferMapState::Mapped
+        } else {
+            BufferMapState::Unmapped
+        }
+    }
+
+    fn unmap_buffer(&self, id: BufferId) {
+        self.mapped_buffers.write().remove(&id);
+    }
 
     fn create_buffer_with_data(&self, buffer_info: BufferInfo, _data: &[u8]) -> BufferId {
         let buffer = BufferId::new();
@@ -93,6 +109,7 @@ impl RenderResourceContext for HeadlessRenderResourceContext {
 
     fn remove_buffer(&self, buffer: BufferId) {
         self.buffer_info.write().remove(&buffer);
+        self.mapped_buffers.write().remove(&buffer);
     }
 
     fn remove_texture(&self, texture: TextureId) {
@@ -128,6 +145,14 @@ impl RenderResourceContext for HeadlessRenderResourceContext {
     ) {
     }
 
+    fn create_compute_pipeline(
+        &self,
+        _pipeline_handle: Handle<ComputePipelineDescriptor>,
+        _pipeline_descriptor: &ComputePipelineDescriptor,
+        _shaders: &Assets<Shader>,
+    ) {
+    }
+
     fn create_bind_group(
         &self,
         _bind_group_descriptor_id: BindGroupDescriptorId,
@@ -162,11 +187,19 @@ impl RenderResourceContext for HeadlessRenderResourceContext {
         size
     }
 
+    fn supported_texture_compression(&self) -> TextureCompression {
+        TextureCompression::all()
+    }
+
     fn get_specialized_shader(
         &self,
         shader: &Shader,
         _macros: Option<&[String]>,
     ) -> Result<Shader, ShaderError> {
+        // compiled like the GPU backends do, so pipelines can be reflected without a GPU
+        #[cfg(not(target_arch = "wasm32"))]
+        return shader.get_spirv_shader(_macros);
+        #[cfg(target_arch = "wasm32")]
         Ok(shader.clone())
     }
 
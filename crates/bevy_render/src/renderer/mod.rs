@@ -1,8 +1,12 @@
+mod frames_in_flight;
+mod gpu_capture;
 mod headless_render_resource_context;
 mod render_context;
 mod render_resource;
 mod render_resource_context;
 
+pub use frames_in_flight::*;
+pub use gpu_capture::*;
 pub use headless_render_resource_context::*;
 pub use render_context::*;
 pub use render_resource::*;
@@ -1,11 +1,14 @@
 use super::RenderResourceContext;
 use crate::{
-    pass::{PassDescriptor, RenderPass},
+    pass::{ComputePass, PassDescriptor, RenderCommands, RenderPass},
+    pipeline::PipelineDescriptor,
     renderer::{BufferId, RenderResourceBindings, TextureId},
     texture::Extent3d,
 };
+use bevy_asset::Assets;
+use downcast_rs::{impl_downcast, Downcast};
 
-pub trait RenderContext {
+pub trait RenderContext: Downcast {
     fn resources(&self) -> &dyn RenderResourceContext;
     fn resources_mut(&mut self) -> &mut dyn RenderResourceContext;
     fn copy_buffer_to_buffer(
@@ -55,4 +58,28 @@ pub trait RenderContext {
         render_resource_bindings: &RenderResourceBindings,
         run_pass: &mut dyn FnMut(&mut dyn RenderPass),
     );
+    /// Runs a pass that replays the recorded `render_commands`, leaving them empty. Backends can
+    /// override this to translate the commands without going through [RenderPass].
+    fn execute_render_commands(
+        &mut self,
+        pass_descriptor: &PassDescriptor,
+        render_resource_bindings: &RenderResourceBindings,
+        pipelines: &Assets<PipelineDescriptor>,
+        render_commands: &mut RenderCommands,
+    ) {
+        self.begin_pass(
+            pass_descriptor,
+            render_resource_bindings,
+            &mut |render_pass| render_commands.replay(render_pass, pipelines),
+        );
+    }
+    fn begin_compute_pass(&mut self, run_pass: &mut dyn FnMut(&mut dyn ComputePass));
+    /// Starts a group of commands named `label` in GPU debuggers, until the matching
+    /// [RenderContext::pop_debug_group]
+    fn push_debug_group(&mut self, label: &str);
+    fn pop_debug_group(&mut self);
+    /// Names the next commands in GPU debuggers
+    fn insert_debug_marker(&mut self, label: &str);
 }
+
+impl_downcast!(RenderContext);
@@ -1,6 +1,6 @@
 use bevy_utils::Uuid;
 
-#[derive(Copy, Clone, Hash, Eq, PartialEq, Debug)]
+#[derive(Copy, Clone, Hash, Eq, PartialEq, Ord, PartialOrd, Debug)]
 pub struct BufferId(Uuid);
 
 impl BufferId {
@@ -49,3 +49,15 @@ pub enum BufferMapMode {
     Read,
     Write,
 }
+
+/// The progress of mapping a buffer with `map_buffer_async` of the
+/// [RenderResourceContext](crate::renderer::RenderResourceContext)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BufferMapState {
+    Unmapped,
+    /// The buffer is waiting for the GPU to finish using it
+    Pending,
+    /// The buffer can be read or written until it is unmapped
+    Mapped,
+    Failed,
+}
@@ -0,0 +1,26 @@
+use bevy_core::Byteable;
+
+/// The arguments of a non-indexed indirect draw, as laid out in an indirect buffer
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DrawIndirectArgs {
+    pub vertex_count: u32,
+    pub instance_count: u32,
+    pub first_vertex: u32,
+    pub first_instance: u32,
+}
+
+unsafe impl Byteable for DrawIndirectArgs {}
+
+/// The arguments of an indexed indirect draw, as laid out in an indirect buffer
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DrawIndexedIndirectArgs {
+    pub index_count: u32,
+    pub instance_count: u32,
+    pub first_index: u32,
+    pub base_vertex: i32,
+    pub first_instance: u32,
+}
+
+unsafe impl Byteable for DrawIndexedIndirectArgs {}
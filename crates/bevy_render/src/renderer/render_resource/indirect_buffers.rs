@@ -0,0 +1,209 @@
+use super::{BufferId, BufferInfo, DrawIndexedIndirectArgs, DrawIndirectArgs};
+use crate::{
+    pipeline::PipelineDescriptor,
+    render_graph::CommandQueue,
+    renderer::{BufferMapMode, BufferUsage, RenderContext, RenderResourceContext},
+};
+use bevy_asset::Handle;
+use bevy_core::AsBytes;
+use bevy_ecs::system::{Res, ResMut};
+use bevy_utils::HashMap;
+
+#[derive(Debug, Default)]
+struct IndirectBuffer {
+    staging_buffer: Option<BufferId>,
+    indirect_buffer: Option<BufferId>,
+    buffer_size: usize,
+    current_offset: usize,
+}
+
+/// Per pipeline buffers of indirect draw arguments, written by draw systems and uploaded before
+/// the passes that draw them.
+///
+/// The arguments pushed for a pipeline within a frame are laid out one after the other, so
+/// consecutive indirect draws of a pipeline are issued as a single multi draw when the
+/// `MultiDrawIndirect` feature is enabled. Indirect buffers are also storage buffers: compute
+/// shaders run after the upload can rewrite the arguments, for example to zero the instance count
+/// of culled draws.
+pub struct IndirectBuffers {
+    buffers: HashMap<Handle<PipelineDescriptor>, IndirectBuffer>,
+    buffers_to_free: Vec<BufferId>,
+    initial_size: usize,
+    command_queue: CommandQueue,
+}
+
+impl IndirectBuffers {
+    pub fn new(initial_size: usize) -> Self {
+        Self {
+            buffers: Default::default(),
+            buffers_to_free: Default::default(),
+            initial_size,
+            command_queue: Default::default(),
+        }
+    }
+
+    /// Queues the upload of `args` to the indirect buffer of `pipeline`. Returns the buffer and
+    /// the offset to pass to [Draw::draw_indirect](crate::draw::Draw::draw_indirect).
+    pub fn push_draw(
+        &mut self,
+        render_resource_context: &dyn RenderResourceContext,
+        pipeline: &Handle<PipelineDescriptor>,
+        args: &DrawIndirectArgs,
+    ) -> (BufferId, u64) {
+        self.push(render_resource_context, pipeline, args.as_bytes())
+    }
+
+    /// Queues the upload of `args` to the indirect buffer of `pipeline`. Returns the buffer and
+    /// the offset to pass to [Draw::draw_indexed_indirect].
+    ///
+    /// [Draw::draw_indexed_indirect]: crate::draw::Draw::draw_indexed_indirect
+    pub fn push_draw_indexed(
+        &mut self,
+        render_resource_context: &dyn RenderResourceContext,
+        pipeline: &Handle<PipelineDescriptor>,
+        args: &DrawIndexedIndirectArgs,
+    ) -> (BufferId, u64) {
+        self.push(render_resource_context, pipeline, args.as_bytes())
+    }
+
+    fn push(
+        &mut self,
+        render_resource_context: &dyn RenderResourceContext,
+        pipeline: &Handle<PipelineDescriptor>,
+        bytes: &[u8],
+    ) -> (BufferId, u64) {
+        let initial_size = self.initial_size;
+        let buffer = self.buffers.entry(pipeline.clone_weak()).or_default();
+        if buffer.current_offset + bytes.len() > buffer.buffer_size {
+            // draws that were already pushed keep using the old buffers until the end of the frame
+            while buffer.buffer_size < buffer.current_offset + bytes.len() {
+                buffer.buffer_size = if buffer.buffer_size == 0 {
+                    initial_size.max(1)
+                } else {
+                    buffer.buffer_size * 2
+                };
+            }
+            buffer.current_offset = 0;
+            if let Some(staging_buffer) = buffer.staging_buffer.take() {
+                render_resource_context.unmap_buffer(staging_buffer);
+                self.buffers_to_free.push(staging_buffer);
+            }
+            if let Some(indirect_buffer) = buffer.indirect_buffer.take() {
+                self.buffers_to_free.push(indirect_buffer);
+            }
+            buffer.staging_buffer = Some(render_resource_context.create_buffer(BufferInfo {
+                size: buffer.buffer_size,
+                buffer_usage: BufferUsage::MAP_WRITE | BufferUsage::COPY_SRC,
+                mapped_at_creation: true,
+            }));
+            buffer.indirect_buffer = Some(render_resource_context.create_buffer(BufferInfo {
+                size: buffer.buffer_size,
+                buffer_usage: BufferUsage::COPY_DST | BufferUsage::INDIRECT | BufferUsage::STORAGE,
+                mapped_at_creation: false,
+            }));
+        }
+
+        let offset = buffer.current_offset as u64;
+        let staging_buffer = buffer.staging_buffer.unwrap();
+        let indirect_buffer = buffer.indirect_buffer.unwrap();
+        render_resource_context.write_mapped_buffer(
+            staging_buffer,
+            offset..offset + bytes.len() as u64,
+            &mut |data, _renderer| {
+                data[..bytes.len()].copy_from_slice(bytes);
+            },
+        );
+        self.command_queue.copy_buffer_to_buffer(
+            staging_buffer,
+            offset,
+            indirect_buffer,
+            offset,
+            bytes.len() as u64,
+        );
+        buffer.current_offset += bytes.len();
+        (indirect_buffer, offset)
+    }
+
+    /// The indirect buffer currently used by `pipeline`, if any
+    pub fn get_indirect_buffer(&self, pipeline: &Handle<PipelineDescriptor>) -> Option<BufferId> {
+        self.buffers
+            .get(pipeline)
+            .and_then(|buffer| buffer.indirect_buffer)
+    }
+
+    pub fn update(&mut self, render_resource_context: &dyn RenderResourceContext) {
+        for buffer in self.buffers_to_free.drain(..) {
+            render_resource_context.remove_buffer(buffer)
+        }
+
+        for buffer in self.buffers.values_mut() {
+            buffer.current_offset = 0;
+            if let Some(staging_buffer) = buffer.staging_buffer {
+                render_resource_context.map_buffer(staging_buffer, BufferMapMode::Write);
+            }
+        }
+    }
+
+    pub fn apply(&self, render_context: &mut dyn RenderContext) {
+        for buffer in self.buffers.values() {
+            if let Some(staging_buffer) = buffer.staging_buffer {
+                render_context.resources().unmap_buffer(staging_buffer);
+            }
+        }
+        self.command_queue.execute(render_context);
+    }
+}
+
+pub fn indirect_buffers_update_system(
+    mut indirect_buffers: ResMut<IndirectBuffers>,
+    render_resource_context: Res<Box<dyn RenderResourceContext>>,
+) {
+    indirect_buffers.update(&**render_resource_context);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::HeadlessRenderResourceContext;
+
+    #[test]
+    fn lays_out_draws_per_pipeline() {
+        let render_resource_context = HeadlessRenderResourceContext::default();
+        let mut indirect_buffers = IndirectBuffers::new(32);
+        let pipeline = Handle::<PipelineDescriptor>::default();
+        let args = DrawIndirectArgs::default();
+        let indexed_args = DrawIndexedIndirectArgs::default();
+
+        let (buffer, offset) =
+            indirect_buffers.push_draw(&render_resource_context, &pipeline, &args);
+        assert_eq!(offset, 0);
+        let (second_buffer, offset) =
+            indirect_buffers.push_draw(&render_resource_context, &pipeline, &args);
+        assert_eq!((second_buffer, offset), (buffer, 16));
+
+        // the buffer is full, so a bigger one is created
+        let (grown_buffer, offset) =
+            indirect_buffers.push_draw_indexed(&render_resource_context, &pipeline, &indexed_args);
+        assert_ne!(grown_buffer, buffer);
+        assert_eq!(offset, 0);
+        assert_eq!(
+            render_resource_context
+                .get_buffer_info(grown_buffer)
+                .unwrap()
+                .size,
+            64
+        );
+        assert_eq!(
+            indirect_buffers.get_indirect_buffer(&pipeline),
+            Some(grown_buffer)
+        );
+
+        indirect_buffers.update(&render_resource_context);
+        let (buffer, offset) =
+            indirect_buffers.push_draw(&render_resource_context, &pipeline, &args);
+        assert_eq!((buffer, offset), (grown_buffer, 0));
+        assert!(render_resource_context
+            .get_buffer_info(second_buffer)
+            .is_none());
+    }
+}
@@ -1,5 +1,7 @@
 mod bind_group;
 mod buffer;
+mod indirect_args;
+mod indirect_buffers;
 #[allow(clippy::module_inception)]
 mod render_resource;
 mod render_resource_bindings;
@@ -8,6 +10,8 @@ mod texture;
 
 pub use bind_group::*;
 pub use buffer::*;
+pub use indirect_args::*;
+pub use indirect_buffers::*;
 pub use render_resource::*;
 pub use render_resource_bindings::*;
 pub use shared_buffers::*;
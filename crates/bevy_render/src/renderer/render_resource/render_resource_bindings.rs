@@ -1,10 +1,13 @@
 use super::{BindGroup, BindGroupId, BufferId, SamplerId, TextureId};
 use crate::{
-    pipeline::{BindGroupDescriptor, BindGroupDescriptorId, IndexFormat, PipelineDescriptor},
+    pipeline::{
+        validate_uniform_layout, BindGroupDescriptor, BindGroupDescriptorId, BindType, IndexFormat,
+        PipelineDescriptor,
+    },
     renderer::RenderResourceContext,
 };
 use bevy_asset::{Asset, Handle, HandleUntyped};
-use bevy_utils::{HashMap, HashSet};
+use bevy_utils::{tracing::warn, HashMap, HashSet};
 use std::{any::TypeId, ops::Range};
 
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -72,6 +75,9 @@ pub struct RenderResourceBindings {
     /// but undefined by the mesh.
     pub vertex_fallback_buffer: Option<BufferId>,
     pub index_buffer: Option<(BufferId, IndexFormat)>,
+    /// The number of bytes written to each uniform buffer binding, used to validate them against
+    /// the shader's uniform layouts
+    buffer_byte_lens: HashMap<String, u64>,
     assets: HashSet<(HandleUntyped, TypeId)>,
     bind_groups: HashMap<BindGroupId, BindGroup>,
     bind_group_descriptors: HashMap<BindGroupDescriptorId, Option<BindGroupId>>,
@@ -89,6 +95,14 @@ impl RenderResourceBindings {
         self.bindings.insert(name.to_string(), binding);
     }
 
+    /// Records the number of bytes written to the buffer bound to `name`. Bind groups using the
+    /// binding are validated against the shader's uniform layout when they are created.
+    pub fn set_buffer_byte_len(&mut self, name: &str, byte_len: u64) {
+        if self.buffer_byte_lens.get(name) != Some(&byte_len) {
+            self.buffer_byte_lens.insert(name.to_string(), byte_len);
+        }
+    }
+
     /// The current "generation" of dynamic bindings. This number increments every time a dynamic
     /// binding changes
     pub fn dynamic_bindings_generation(&self) -> usize {
@@ -117,6 +131,9 @@ impl RenderResourceBindings {
         for (name, binding) in render_resource_bindings.bindings.iter() {
             self.set(name, binding.clone());
         }
+        for (name, byte_len) in render_resource_bindings.buffer_byte_lens.iter() {
+            self.set_buffer_byte_len(name, *byte_len);
+        }
     }
 
     pub fn set_index_buffer(&mut self, index_buffer: BufferId, index_format: IndexFormat) {
@@ -178,6 +195,7 @@ impl RenderResourceBindings {
         let status = self.update_bind_group_status(bind_group_descriptor);
         match status {
             BindGroupStatus::Changed(id) => {
+                self.validate_uniform_layouts(bind_group_descriptor);
                 let bind_group = self
                     .get_bind_group(id)
                     .expect("`RenderResourceSet` was just changed, so it should exist.");
@@ -201,6 +219,21 @@ impl RenderResourceBindings {
         }
     }
 
+    /// Mismatched uniform layouts silently corrupt rendering, so they are reported here
+    fn validate_uniform_layouts(&self, bind_group_descriptor: &BindGroupDescriptor) {
+        for binding_descriptor in bind_group_descriptor.bindings.iter() {
+            if let BindType::Uniform { property, .. } = &binding_descriptor.bind_type {
+                if let Some(byte_len) = self.buffer_byte_lens.get(&binding_descriptor.name) {
+                    if let Err(err) =
+                        validate_uniform_layout(&binding_descriptor.name, *byte_len, property)
+                    {
+                        warn!("{}", err);
+                    }
+                }
+            }
+        }
+    }
+
     pub fn update_bind_groups(
         &mut self,
         pipeline: &PipelineDescriptor,
@@ -1,10 +1,13 @@
 use crate::{
-    pipeline::{BindGroupDescriptorId, PipelineDescriptor, PipelineLayout},
+    pipeline::{
+        BindGroupDescriptorId, ComputePipelineDescriptor, PipelineDescriptor, PipelineLayout,
+    },
     renderer::{
-        BindGroup, BufferId, BufferInfo, BufferMapMode, RenderResourceId, SamplerId, TextureId,
+        BindGroup, BufferId, BufferInfo, BufferMapMode, BufferMapState, RenderResourceId,
+        SamplerId, TextureId,
     },
     shader::{Shader, ShaderError, ShaderLayout, ShaderStages},
-    texture::{SamplerDescriptor, TextureDescriptor},
+    texture::{SamplerDescriptor, TextureCompression, TextureDescriptor},
 };
 use bevy_asset::{Asset, Assets, Handle, HandleUntyped};
 use bevy_window::Window;
@@ -32,7 +35,13 @@ pub trait RenderResourceContext: Downcast + Send + Sync + 'static {
         range: Range<u64>,
         read: &dyn Fn(&[u8], &dyn RenderResourceContext),
     );
+    /// Maps the buffer, blocking until the GPU has finished using it
     fn map_buffer(&self, id: BufferId, mode: BufferMapMode);
+    /// Starts mapping the buffer without blocking. The mapping completes during a later frame,
+    /// once [RenderResourceContext::get_buffer_map_state] returns [BufferMapState::Mapped]. The
+    /// buffer must not be used by the GPU until it is unmapped.
+    fn map_buffer_async(&self, id: BufferId, mode: BufferMapMode);
+    fn get_buffer_map_state(&self, id: BufferId) -> BufferMapState;
     fn unmap_buffer(&self, id: BufferId);
     fn create_buffer_with_data(&self, buffer_info: BufferInfo, data: &[u8]) -> BufferId;
     fn create_shader_module(&self, shader_handle: &Handle<Shader>, shaders: &Assets<Shader>);
@@ -48,6 +57,8 @@ pub trait RenderResourceContext: Downcast + Send + Sync + 'static {
     fn get_buffer_info(&self, buffer: BufferId) -> Option<BufferInfo>;
     fn get_aligned_uniform_size(&self, size: usize, dynamic: bool) -> usize;
     fn get_aligned_texture_size(&self, data_size: usize) -> usize;
+    /// The block compressed texture formats that textures can be created with
+    fn supported_texture_compression(&self) -> TextureCompression;
     fn set_asset_resource_untyped(
         &self,
         handle: HandleUntyped,
@@ -66,6 +77,12 @@ pub trait RenderResourceContext: Downcast + Send + Sync + 'static {
         pipeline_descriptor: &PipelineDescriptor,
         shaders: &Assets<Shader>,
     );
+    fn create_compute_pipeline(
+        &self,
+        pipeline_handle: Handle<ComputePipelineDescriptor>,
+        pipeline_descriptor: &ComputePipelineDescriptor,
+        shaders: &Assets<Shader>,
+    );
     fn bind_group_descriptor_exists(&self, bind_group_descriptor_id: BindGroupDescriptorId)
         -> bool;
     fn create_bind_group(
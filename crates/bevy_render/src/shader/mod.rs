@@ -1,12 +1,14 @@
 #[allow(clippy::module_inception)]
 mod shader;
 mod shader_defs;
+mod shader_preprocessor;
 
 #[cfg(not(target_arch = "wasm32"))]
 mod shader_reflect;
 
 pub use shader::*;
 pub use shader_defs::*;
+pub use shader_preprocessor::*;
 
 #[cfg(not(target_arch = "wasm32"))]
 pub use shader_reflect::*;
@@ -3,13 +3,18 @@ use crate::{
     renderer::RenderResourceContext,
 };
 
-use super::ShaderLayout;
-use bevy_app::EventReader;
+use super::{
+    annotate_compiler_message, glsl_includes, preprocess_glsl, ShaderLayout, ShaderSourceMap,
+};
+use bevy_app::{EventReader, EventWriter};
 use bevy_asset::{AssetEvent, AssetLoader, Assets, Handle, LoadContext, LoadedAsset};
 use bevy_ecs::system::{Res, ResMut};
 use bevy_reflect::TypeUuid;
-use bevy_utils::{tracing::error, BoxedFuture};
-use std::marker::Copy;
+use bevy_utils::{tracing::error, BoxedFuture, HashMap};
+use std::{
+    marker::Copy,
+    path::{Path, PathBuf},
+};
 use thiserror::Error;
 
 /// The stage of a shader
@@ -27,6 +32,10 @@ pub enum ShaderError {
     #[error("Shader compilation error:\n{0}")]
     Compilation(String),
 
+    /// The shader failed to load or hasn't loaded yet.
+    #[error("The shader isn't loaded")]
+    NotLoaded,
+
     #[cfg(not(any(
         target_arch = "wasm32",
         all(target_arch = "x86_64", target_os = "linux", target_env = "gnu"),
@@ -62,6 +71,14 @@ pub enum ShaderError {
     ErrorInitializingShadercCompileOptions,
 }
 
+/// Sent when a shader fails to compile. The message shows the failing lines of the shader and of
+/// the files it includes.
+#[derive(Debug, Clone)]
+pub struct ShaderCompileError {
+    pub shader: Handle<Shader>,
+    pub message: String,
+}
+
 #[cfg(any(
     all(target_arch = "x86_64", target_os = "linux", target_env = "gnu"),
     all(target_arch = "x86_64", target_os = "macos"),
@@ -177,11 +194,17 @@ impl ShaderSource {
 pub struct Shader {
     pub source: ShaderSource,
     pub stage: ShaderStage,
+    /// Maps lines of a GLSL source with resolved `#include`s back to the files they came from
+    pub source_map: Option<ShaderSourceMap>,
 }
 
 impl Shader {
     pub fn new(stage: ShaderStage, source: ShaderSource) -> Shader {
-        Shader { source, stage }
+        Shader {
+            source,
+            stage,
+            source_map: None,
+        }
     }
 
     #[cfg(not(target_arch = "wasm32"))]
@@ -199,6 +222,7 @@ impl Shader {
         Ok(Shader {
             source: ShaderSource::spirv_from_bytes(spirv),
             stage,
+            source_map: None,
         })
     }
 
@@ -206,6 +230,32 @@ impl Shader {
         Shader {
             source: ShaderSource::Glsl(glsl.to_string()),
             stage,
+            source_map: None,
+        }
+    }
+
+    /// Creates a GLSL shader whose `#include "path"` directives are resolved from `includes`, which
+    /// pairs include paths with their sources. Meant for shaders built into crates, which can't
+    /// read their includes from the asset directory. `path` names the shader in compiler
+    /// messages.
+    ///
+    /// Panics if an include is missing or the includes form a cycle.
+    pub fn from_glsl_with_includes(
+        stage: ShaderStage,
+        path: &str,
+        glsl: &str,
+        includes: &[(&str, &str)],
+    ) -> Shader {
+        let sources: HashMap<PathBuf, String> = includes
+            .iter()
+            .map(|(path, source)| (PathBuf::from(path), source.to_string()))
+            .collect();
+        let preprocessed = preprocess_glsl(Path::new(path), glsl, &sources)
+            .unwrap_or_else(|error| panic!("{}", error));
+        Shader {
+            source: ShaderSource::Glsl(preprocessed.source),
+            stage,
+            source_map: Some(preprocessed.source_map),
         }
     }
 
@@ -213,7 +263,14 @@ impl Shader {
     pub fn get_spirv(&self, macros: Option<&[String]>) -> Result<Vec<u32>, ShaderError> {
         match self.source {
             ShaderSource::Spirv(ref bytes) => Ok(bytes.clone()),
-            ShaderSource::Glsl(ref source) => glsl_to_spirv(&source, self.stage, macros),
+            ShaderSource::Glsl(ref source) => {
+                glsl_to_spirv(&source, self.stage, macros).map_err(|error| match error {
+                    ShaderError::Compilation(message) => ShaderError::Compilation(
+                        annotate_compiler_message(&message, source, self.source_map.as_ref()),
+                    ),
+                    error => error,
+                })
+            }
         }
     }
 
@@ -222,6 +279,7 @@ impl Shader {
         Ok(Shader {
             source: ShaderSource::Spirv(self.get_spirv(macros)?),
             stage: self.stage,
+            source_map: None,
         })
     }
 
@@ -307,8 +365,8 @@ impl AssetLoader for ShaderLoader {
             let ext = load_context.path().extension().unwrap().to_str().unwrap();
 
             let shader = match ext {
-                "vert" => Shader::from_glsl(ShaderStage::Vertex, std::str::from_utf8(bytes)?),
-                "frag" => Shader::from_glsl(ShaderStage::Fragment, std::str::from_utf8(bytes)?),
+                "vert" => load_glsl(ShaderStage::Vertex, bytes, load_context).await?,
+                "frag" => load_glsl(ShaderStage::Fragment, bytes, load_context).await?,
                 #[cfg(not(target_arch = "wasm32"))]
                 "spv" => Shader::from_spirv(bytes)?,
                 #[cfg(target_arch = "wasm32")]
@@ -326,16 +384,52 @@ impl AssetLoader for ShaderLoader {
     }
 }
 
+/// Loads a GLSL shader and resolves its `#include "path"` directives. Include paths are relative to
+/// the asset directory.
+async fn load_glsl(
+    stage: ShaderStage,
+    bytes: &[u8],
+    load_context: &mut LoadContext<'_>,
+) -> Result<Shader, anyhow::Error> {
+    let source = std::str::from_utf8(bytes)?;
+    let mut sources = HashMap::<PathBuf, String>::default();
+    let mut pending = glsl_includes(source).collect::<Vec<_>>();
+    if pending.is_empty() {
+        return Ok(Shader::from_glsl(stage, source));
+    }
+    while let Some(path) = pending.pop() {
+        if sources.contains_key(&path) {
+            continue;
+        }
+        // missing files are reported by the preprocessor, along with the line that included them
+        let include = match load_context.read_asset_bytes(&path).await {
+            Ok(include) => String::from_utf8(include)?,
+            Err(_) => continue,
+        };
+        pending.extend(glsl_includes(&include));
+        sources.insert(path, include);
+    }
+
+    let preprocessed = preprocess_glsl(load_context.path(), source, &sources)?;
+    Ok(Shader {
+        source: ShaderSource::Glsl(preprocessed.source),
+        stage,
+        source_map: Some(preprocessed.source_map),
+    })
+}
+
 pub fn shader_update_system(
     mut shaders: ResMut<Assets<Shader>>,
     mut pipelines: ResMut<Assets<PipelineDescriptor>>,
     mut shader_events: EventReader<AssetEvent<Shader>>,
+    mut compile_errors: EventWriter<ShaderCompileError>,
     mut pipeline_compiler: ResMut<PipelineCompiler>,
     render_resource_context: Res<Box<dyn RenderResourceContext>>,
 ) {
     for event in shader_events.iter() {
         match event {
-            AssetEvent::Modified { handle } => {
+            // a created shader may replace one that failed to load
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
                 if let Err(e) = pipeline_compiler.update_shader(
                     handle,
                     &mut pipelines,
@@ -343,13 +437,18 @@ pub fn shader_update_system(
                     &**render_resource_context,
                 ) {
                     error!("Failed to update shader: {}", e);
+                    compile_errors.send(ShaderCompileError {
+                        shader: handle.clone_weak(),
+                        message: e.to_string(),
+                    });
                 }
             }
-            // Creating shaders on the fly is unhandled since they
-            // have to exist already when assigned to a pipeline. If a
-            // shader is removed the pipeline keeps using its
+            // If a shader is removed the pipeline keeps using its
             // specialized version. Maybe this should be a warning?
-            AssetEvent::Created { .. } | AssetEvent::Removed { .. } => (),
+            AssetEvent::Removed { .. } => (),
         }
     }
+    for compile_error in pipeline_compiler.take_compile_errors() {
+        compile_errors.send(compile_error);
+    }
 }
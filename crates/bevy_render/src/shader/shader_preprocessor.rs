@@ -0,0 +1,346 @@
+use bevy_utils::HashMap;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// An error that occurs while resolving `#include` directives
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ShaderPreprocessorError {
+    #[error(
+        "{}:{}: malformed include directive, expected `#include \"path\"`",
+        .path.display(),
+        .line
+    )]
+    MalformedInclude { path: PathBuf, line: usize },
+    #[error(
+        "{}:{}: included file {:?} could not be found",
+        .path.display(),
+        .line,
+        .include
+    )]
+    MissingInclude {
+        path: PathBuf,
+        line: usize,
+        include: PathBuf,
+    },
+    #[error("include cycle: {}", format_cycle(.0))]
+    IncludeCycle(Vec<PathBuf>),
+}
+
+fn format_cycle(cycle: &[PathBuf]) -> String {
+    cycle
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(" -> ")
+}
+
+/// Maps the lines of a preprocessed shader back to the files and lines they came from
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShaderSourceMap {
+    files: Vec<PathBuf>,
+    /// The file index and 1-based line of each line in the preprocessed source
+    lines: Vec<(usize, usize)>,
+}
+
+impl ShaderSourceMap {
+    /// Returns the file and 1-based line that the given 1-based line of the preprocessed source
+    /// came from
+    pub fn get(&self, line: usize) -> Option<(&Path, usize)> {
+        let (file, line) = *self.lines.get(line.checked_sub(1)?)?;
+        Some((&self.files[file], line))
+    }
+
+    /// Rewrites compiler messages of the form `<name>:<line>: ...` to point at the file and line
+    /// the code was included from
+    pub fn remap_message(&self, message: &str) -> String {
+        message
+            .lines()
+            .map(|message_line| {
+                self.remap_message_line(message_line)
+                    .unwrap_or_else(|| message_line.to_string())
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn remap_message_line(&self, message_line: &str) -> Option<String> {
+        let (line, offset) = message_line_number(message_line)?;
+        let (path, line) = self.get(line)?;
+        Some(format!(
+            "{}:{}:{}",
+            path.display(),
+            line,
+            &message_line[offset..]
+        ))
+    }
+}
+
+/// Returns the line number of a compiler message of the form `<name>:<line>: ...`, and the offset
+/// of the rest of the message
+fn message_line_number(message_line: &str) -> Option<(usize, usize)> {
+    // find the first `:<digits>:`, which is where compilers put the line number
+    let mut offset = 0;
+    for part in message_line.split(':') {
+        let start = offset;
+        offset += part.len() + 1;
+        if start == 0 || part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()) {
+            continue;
+        }
+        if offset > message_line.len() {
+            return None;
+        }
+        return Some((part.parse().ok()?, offset));
+    }
+    None
+}
+
+/// The number of source lines shown before and after the line a compiler message points at
+const SNIPPET_CONTEXT: usize = 1;
+
+/// Follows each line of a compiler message that points at a line of `source` with that line and
+/// the ones around it. Lines are mapped back to the included files through `source_map`.
+pub fn annotate_compiler_message(
+    message: &str,
+    source: &str,
+    source_map: Option<&ShaderSourceMap>,
+) -> String {
+    let source_lines = source.lines().collect::<Vec<_>>();
+    let mut annotated = String::new();
+    for message_line in message.lines() {
+        let remapped =
+            source_map.and_then(|source_map| source_map.remap_message_line(message_line));
+        annotated.push_str(remapped.as_deref().unwrap_or(message_line));
+        annotated.push('\n');
+        let line = match message_line_number(message_line) {
+            Some((line, _)) if line >= 1 && line <= source_lines.len() => line,
+            _ => continue,
+        };
+        let file = source_map.and_then(|source_map| source_map.get(line).map(|(file, _)| file));
+        let first = line.saturating_sub(SNIPPET_CONTEXT).max(1);
+        let last = (line + SNIPPET_CONTEXT).min(source_lines.len());
+        for snippet_line in first..=last {
+            let number = match source_map.and_then(|source_map| source_map.get(snippet_line)) {
+                // context from other files would be numbered as if it was in the same file
+                Some((snippet_file, _)) if Some(snippet_file) != file => continue,
+                Some((_, number)) => number,
+                None => snippet_line,
+            };
+            let marker = if snippet_line == line { '>' } else { ' ' };
+            annotated.push_str(&format!(
+                "{} {:>4} | {}\n",
+                marker,
+                number,
+                source_lines[snippet_line - 1]
+            ));
+        }
+    }
+    annotated
+}
+
+/// A GLSL source with all `#include` directives resolved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreprocessedGlsl {
+    pub source: String,
+    pub source_map: ShaderSourceMap,
+}
+
+/// Returns the path of an `#include "path"` directive, `Ok(None)` if `line` isn't an include
+/// directive, or `Err(())` if it is malformed.
+fn parse_include(line: &str) -> Result<Option<&str>, ()> {
+    let directive = match line.trim_start().strip_prefix('#') {
+        Some(directive) => directive.trim_start(),
+        None => return Ok(None),
+    };
+    let path = match directive.strip_prefix("include") {
+        Some(path) => path.trim(),
+        None => return Ok(None),
+    };
+    path.strip_prefix('"')
+        .and_then(|path| path.strip_suffix('"'))
+        .filter(|path| !path.is_empty())
+        .map(Some)
+        .ok_or(())
+}
+
+/// Returns the paths included by `source`, in the order they appear
+pub fn glsl_includes(source: &str) -> impl Iterator<Item = PathBuf> + '_ {
+    source
+        .lines()
+        .filter_map(|line| parse_include(line).ok().flatten())
+        .map(PathBuf::from)
+}
+
+/// Replaces each `#include "path"` directive in the shader at `path` with the contents of the
+/// included file. Include paths are relative to the asset directory and are looked up in
+/// `sources`, which must contain every file that is included, directly or indirectly.
+pub fn preprocess_glsl(
+    path: &Path,
+    source: &str,
+    sources: &HashMap<PathBuf, String>,
+) -> Result<PreprocessedGlsl, ShaderPreprocessorError> {
+    let mut preprocessed = PreprocessedGlsl {
+        source: String::with_capacity(source.len()),
+        source_map: ShaderSourceMap::default(),
+    };
+    let mut stack = Vec::new();
+    append_glsl(path, source, sources, &mut stack, &mut preprocessed)?;
+    Ok(preprocessed)
+}
+
+fn append_glsl(
+    path: &Path,
+    source: &str,
+    sources: &HashMap<PathBuf, String>,
+    stack: &mut Vec<PathBuf>,
+    preprocessed: &mut PreprocessedGlsl,
+) -> Result<(), ShaderPreprocessorError> {
+    if stack.iter().any(|included| included == path) {
+        let mut cycle = stack.clone();
+        cycle.push(path.to_path_buf());
+        return Err(ShaderPreprocessorError::IncludeCycle(cycle));
+    }
+    stack.push(path.to_path_buf());
+
+    let source_map = &mut preprocessed.source_map;
+    let file = match source_map.files.iter().position(|file| file == path) {
+        Some(file) => file,
+        None => {
+            source_map.files.push(path.to_path_buf());
+            source_map.files.len() - 1
+        }
+    };
+    for (i, line) in source.lines().enumerate() {
+        let line_number = i + 1;
+        match parse_include(line) {
+            Ok(Some(include)) => {
+                let include = PathBuf::from(include);
+                let include_source = sources.get(&include).ok_or_else(|| {
+                    ShaderPreprocessorError::MissingInclude {
+                        path: path.to_path_buf(),
+                        line: line_number,
+                        include: include.clone(),
+                    }
+                })?;
+                append_glsl(&include, include_source, sources, stack, preprocessed)?;
+            }
+            Ok(None) => {
+                preprocessed.source.push_str(line);
+                preprocessed.source.push('\n');
+                preprocessed.source_map.lines.push((file, line_number));
+            }
+            Err(()) => {
+                return Err(ShaderPreprocessorError::MalformedInclude {
+                    path: path.to_path_buf(),
+                    line: line_number,
+                })
+            }
+        }
+    }
+
+    stack.pop();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sources(files: &[(&str, &str)]) -> HashMap<PathBuf, String> {
+        files
+            .iter()
+            .map(|(path, source)| (PathBuf::from(path), source.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn resolves_includes() {
+        let sources = sources(&[
+            (
+                "shaders/lighting.glsl",
+                "#include \"shaders/common.glsl\"\nfloat light;",
+            ),
+            ("shaders/common.glsl", "float common;"),
+        ]);
+        let preprocessed = preprocess_glsl(
+            Path::new("shaders/pbr.frag"),
+            "#version 450\n  # include \"shaders/lighting.glsl\"\nvoid main() {}",
+            &sources,
+        )
+        .unwrap();
+        assert_eq!(
+            preprocessed.source,
+            "#version 450\nfloat common;\nfloat light;\nvoid main() {}\n"
+        );
+        assert_eq!(
+            preprocessed.source_map.get(3),
+            Some((Path::new("shaders/lighting.glsl"), 2))
+        );
+        assert_eq!(
+            preprocessed.source_map.get(4),
+            Some((Path::new("shaders/pbr.frag"), 3))
+        );
+        assert_eq!(
+            preprocessed
+                .source_map
+                .remap_message("shader.glsl:2: error: 'common' : redefinition"),
+            "shaders/common.glsl:1: error: 'common' : redefinition"
+        );
+    }
+
+    #[test]
+    fn annotates_messages() {
+        let sources = sources(&[("common.glsl", "float a;\nfloat b;")]);
+        let preprocessed = preprocess_glsl(
+            Path::new("main.frag"),
+            "#version 450\n#include \"common.glsl\"\nvoid main() {}",
+            &sources,
+        )
+        .unwrap();
+        assert_eq!(
+            annotate_compiler_message(
+                "ERROR: 0:3: 'b' : redefinition\n1 error",
+                &preprocessed.source,
+                Some(&preprocessed.source_map),
+            ),
+            [
+                "common.glsl:2: 'b' : redefinition",
+                "     1 | float a;",
+                ">    2 | float b;",
+                "1 error\n",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn detects_errors() {
+        let sources = sources(&[
+            ("a.glsl", "#include \"b.glsl\""),
+            ("b.glsl", "#include \"a.glsl\""),
+        ]);
+        assert_eq!(
+            preprocess_glsl(Path::new("main.vert"), "#include \"a.glsl\"", &sources),
+            Err(ShaderPreprocessorError::IncludeCycle(vec![
+                PathBuf::from("main.vert"),
+                PathBuf::from("a.glsl"),
+                PathBuf::from("b.glsl"),
+                PathBuf::from("a.glsl"),
+            ]))
+        );
+        assert_eq!(
+            preprocess_glsl(Path::new("main.vert"), "\n#include \"c.glsl\"", &sources),
+            Err(ShaderPreprocessorError::MissingInclude {
+                path: PathBuf::from("main.vert"),
+                line: 2,
+                include: PathBuf::from("c.glsl"),
+            })
+        );
+        assert_eq!(
+            preprocess_glsl(Path::new("main.vert"), "#include <c.glsl>", &sources),
+            Err(ShaderPreprocessorError::MalformedInclude {
+                path: PathBuf::from("main.vert"),
+                line: 1,
+            })
+        );
+    }
+}
@@ -31,7 +31,9 @@ impl ShaderLayout {
                 // obtain attribute descriptors from reflection
                 let mut vertex_attributes = Vec::new();
                 for input_variable in module.enumerate_input_variables(None).unwrap() {
-                    if input_variable.name == GL_VERTEX_INDEX
+                    // compute shaders only have builtin inputs
+                    if shader_stage == ReflectShaderStageFlags::COMPUTE
+                        || input_variable.name == GL_VERTEX_INDEX
                         || input_variable.name == GL_INSTANCE_INDEX
                         || input_variable.name == GL_FRONT_FACING
                     {
@@ -105,12 +107,19 @@ fn reflect_bind_group(
 }
 
 fn reflect_dimension(type_description: &ReflectTypeDescription) -> TextureViewDimension {
-    match type_description.traits.image.dim {
-        ReflectDimension::Type1d => TextureViewDimension::D1,
-        ReflectDimension::Type2d => TextureViewDimension::D2,
-        ReflectDimension::Type3d => TextureViewDimension::D3,
-        ReflectDimension::Cube => TextureViewDimension::Cube,
-        dimension => panic!("Unsupported image dimension: {:?}.", dimension),
+    let arrayed = type_description.traits.image.arrayed != 0;
+    match (type_description.traits.image.dim, arrayed) {
+        (ReflectDimension::Type1d, false) => TextureViewDimension::D1,
+        (ReflectDimension::Type2d, false) => TextureViewDimension::D2,
+        (ReflectDimension::Type2d, true) => TextureViewDimension::D2Array,
+        (ReflectDimension::Type3d, false) => TextureViewDimension::D3,
+        (ReflectDimension::Cube, false) => TextureViewDimension::Cube,
+        (ReflectDimension::Cube, true) => TextureViewDimension::CubeArray,
+        (dimension, arrayed) => panic!(
+            "Unsupported image dimension: {:?}{}.",
+            dimension,
+            if arrayed { " array" } else { "" }
+        ),
     }
 }
 
@@ -127,19 +136,31 @@ fn reflect_binding(
                 property: reflect_uniform(type_description),
             },
         ),
-        ReflectDescriptorType::SampledImage => (
-            &binding.name,
-            BindType::Texture {
-                view_dimension: reflect_dimension(type_description),
-                sample_type: TextureSampleType::Float { filterable: true },
-                multisampled: false,
-            },
-        ),
+        ReflectDescriptorType::SampledImage => {
+            // multisampled textures can only be loaded from, not filtered
+            let multisampled = type_description.traits.image.ms != 0;
+            let sample_type = if type_description.traits.image.depth == 1 {
+                TextureSampleType::Depth
+            } else {
+                TextureSampleType::Float {
+                    filterable: !multisampled,
+                }
+            };
+            (
+                &binding.name,
+                BindType::Texture {
+                    view_dimension: reflect_dimension(type_description),
+                    sample_type,
+                    multisampled,
+                },
+            )
+        }
         ReflectDescriptorType::StorageBuffer => (
             &type_description.type_name,
             BindType::StorageBuffer {
                 has_dynamic_offset: false,
-                readonly: true,
+                // only compute shaders can write to storage buffers
+                readonly: shader_stage != ReflectShaderStageFlags::COMPUTE,
             },
         ),
         // TODO: detect comparison "true" case: https://github.com/gpuweb/gpuweb/issues/552
@@ -401,4 +422,40 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn reflects_array_and_3d_textures() {
+        let fragment_shader = Shader::from_glsl(
+            ShaderStage::Fragment,
+            r#"
+            #version 450
+            layout(location = 0) out vec4 o_Target;
+            layout(set = 0, binding = 0) uniform texture2DArray Layers;
+            layout(set = 0, binding = 1) uniform texture3D Volume;
+            layout(set = 0, binding = 2) uniform sampler Sampler;
+
+            void main() {
+                o_Target = texture(sampler2DArray(Layers, Sampler), vec3(0.0))
+                    + texture(sampler3D(Volume, Sampler), vec3(0.0));
+            }
+        "#,
+        )
+        .get_spirv_shader(None)
+        .unwrap();
+
+        let layout = fragment_shader.reflect_layout(true).unwrap();
+        let view_dimension = |index: u32| {
+            let binding = layout.bind_groups[0]
+                .bindings
+                .iter()
+                .find(|binding| binding.index == index)
+                .unwrap();
+            match binding.bind_type {
+                BindType::Texture { view_dimension, .. } => view_dimension,
+                _ => panic!("expected a texture binding"),
+            }
+        };
+        assert_eq!(view_dimension(0), TextureViewDimension::D2Array);
+        assert_eq!(view_dimension(1), TextureViewDimension::D3);
+    }
 }
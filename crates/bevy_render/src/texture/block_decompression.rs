@@ -0,0 +1,239 @@
+use super::{Extent3d, TextureFormat};
+
+/// The format [decompress] converts `format` to, or `None` if it can't be decompressed on the cpu
+pub(crate) fn decompressed_format(format: TextureFormat) -> Option<TextureFormat> {
+    match format {
+        TextureFormat::Bc1RgbaUnorm | TextureFormat::Bc2RgbaUnorm | TextureFormat::Bc3RgbaUnorm => {
+            Some(TextureFormat::Rgba8Unorm)
+        }
+        TextureFormat::Bc1RgbaUnormSrgb
+        | TextureFormat::Bc2RgbaUnormSrgb
+        | TextureFormat::Bc3RgbaUnormSrgb => Some(TextureFormat::Rgba8UnormSrgb),
+        TextureFormat::Bc4RUnorm => Some(TextureFormat::R8Unorm),
+        TextureFormat::Bc4RSnorm => Some(TextureFormat::R8Snorm),
+        TextureFormat::Bc5RgUnorm => Some(TextureFormat::Rg8Unorm),
+        TextureFormat::Bc5RgSnorm => Some(TextureFormat::Rg8Snorm),
+        _ => None,
+    }
+}
+
+/// Decodes a single mip level of block compressed `data` into the format returned by
+/// [decompressed_format]
+pub(crate) fn decompress(data: &[u8], size: Extent3d, format: TextureFormat) -> Option<Vec<u8>> {
+    let decompressed_format = decompressed_format(format)?;
+    let block_size = format.pixel_size();
+    if data.len() < format.data_size(size) {
+        return None;
+    }
+
+    let pixel_size = decompressed_format.pixel_size();
+    let width = size.width as usize;
+    let height = size.height as usize;
+    let blocks_wide = (width + 3) / 4;
+    let blocks_high = (height + 3) / 4;
+    let mut decompressed = vec![0; width * height * size.depth as usize * pixel_size];
+    let mut pixels = [[0u8; 4]; 16];
+    for (i, block) in data
+        .chunks_exact(block_size)
+        .take(blocks_wide * blocks_high * size.depth as usize)
+        .enumerate()
+    {
+        decode_block(block, format, &mut pixels);
+
+        let layer = i / (blocks_wide * blocks_high);
+        let block_x = i % blocks_wide * 4;
+        let block_y = i / blocks_wide % blocks_high * 4;
+        for (j, pixel) in pixels.iter().enumerate() {
+            let x = block_x + j % 4;
+            let y = block_y + j / 4;
+            // blocks on the right and bottom edges can extend past the texture
+            if x >= width || y >= height {
+                continue;
+            }
+            let offset = ((layer * height + y) * width + x) * pixel_size;
+            decompressed[offset..offset + pixel_size].copy_from_slice(&pixel[..pixel_size]);
+        }
+    }
+    Some(decompressed)
+}
+
+fn decode_block(block: &[u8], format: TextureFormat, pixels: &mut [[u8; 4]; 16]) {
+    match format {
+        TextureFormat::Bc1RgbaUnorm | TextureFormat::Bc1RgbaUnormSrgb => {
+            decode_color_block(block, true, pixels);
+        }
+        TextureFormat::Bc2RgbaUnorm | TextureFormat::Bc2RgbaUnormSrgb => {
+            decode_color_block(&block[8..], false, pixels);
+            let alpha = u64::from_le_bytes(read_8(&block[..8]));
+            for (i, pixel) in pixels.iter_mut().enumerate() {
+                pixel[3] = ((alpha >> (i * 4)) & 0xf) as u8 * 17;
+            }
+        }
+        TextureFormat::Bc3RgbaUnorm | TextureFormat::Bc3RgbaUnormSrgb => {
+            decode_color_block(&block[8..], false, pixels);
+            decode_channel_block(&block[..8], false, 3, pixels);
+        }
+        TextureFormat::Bc4RUnorm | TextureFormat::Bc4RSnorm => {
+            decode_channel_block(block, format == TextureFormat::Bc4RSnorm, 0, pixels);
+        }
+        TextureFormat::Bc5RgUnorm | TextureFormat::Bc5RgSnorm => {
+            let signed = format == TextureFormat::Bc5RgSnorm;
+            decode_channel_block(&block[..8], signed, 0, pixels);
+            decode_channel_block(&block[8..], signed, 1, pixels);
+        }
+        _ => unreachable!("{:?} can't be decompressed", format),
+    }
+}
+
+fn read_8(bytes: &[u8]) -> [u8; 8] {
+    let mut array = [0; 8];
+    array.copy_from_slice(&bytes[..8]);
+    array
+}
+
+fn rgb565(color: u16) -> [u32; 3] {
+    let r = (color >> 11) as u32 & 0x1f;
+    let g = (color >> 5) as u32 & 0x3f;
+    let b = color as u32 & 0x1f;
+    [
+        (r << 3) | (r >> 2),
+        (g << 2) | (g >> 4),
+        (b << 3) | (b >> 2),
+    ]
+}
+
+/// Decodes the 8 byte color block shared by BC1, BC2 and BC3. Only BC1 has a 3 color mode with
+/// transparency, chosen by the order of the endpoints.
+fn decode_color_block(block: &[u8], bc1: bool, pixels: &mut [[u8; 4]; 16]) {
+    let color0 = u16::from_le_bytes([block[0], block[1]]);
+    let color1 = u16::from_le_bytes([block[2], block[3]]);
+    let c0 = rgb565(color0);
+    let c1 = rgb565(color1);
+    let mut palette = [[0u8; 4]; 4];
+    for channel in 0..3 {
+        palette[0][channel] = c0[channel] as u8;
+        palette[1][channel] = c1[channel] as u8;
+        if bc1 && color0 <= color1 {
+            palette[2][channel] = ((c0[channel] + c1[channel]) / 2) as u8;
+            palette[3][channel] = 0;
+        } else {
+            palette[2][channel] = ((2 * c0[channel] + c1[channel]) / 3) as u8;
+            palette[3][channel] = ((c0[channel] + 2 * c1[channel]) / 3) as u8;
+        }
+    }
+    palette[0][3] = 255;
+    palette[1][3] = 255;
+    palette[2][3] = 255;
+    palette[3][3] = if bc1 && color0 <= color1 { 0 } else { 255 };
+
+    let indices = u32::from_le_bytes([block[4], block[5], block[6], block[7]]);
+    for (i, pixel) in pixels.iter_mut().enumerate() {
+        *pixel = palette[((indices >> (i * 2)) & 0x3) as usize];
+    }
+}
+
+/// Decodes an 8 byte BC4 style block into one channel of `pixels`. Signed values are stored as
+/// their two's complement bytes.
+fn decode_channel_block(block: &[u8], signed: bool, channel: usize, pixels: &mut [[u8; 4]; 16]) {
+    let (e0, e1, min, max) = if signed {
+        // -128 is treated as -127 so the range is symmetric
+        let e0 = (block[0] as i8).max(-127) as i32;
+        let e1 = (block[1] as i8).max(-127) as i32;
+        (e0, e1, -127, 127)
+    } else {
+        (block[0] as i32, block[1] as i32, 0, 255)
+    };
+    let mut palette = [0i32; 8];
+    palette[0] = e0;
+    palette[1] = e1;
+    if e0 > e1 {
+        for i in 1..7 {
+            palette[i + 1] = ((7 - i as i32) * e0 + i as i32 * e1) / 7;
+        }
+    } else {
+        for i in 1..5 {
+            palette[i + 1] = ((5 - i as i32) * e0 + i as i32 * e1) / 5;
+        }
+        palette[6] = min;
+        palette[7] = max;
+    }
+
+    let indices = u64::from_le_bytes(read_8(block)) >> 16;
+    for (i, pixel) in pixels.iter_mut().enumerate() {
+        pixel[channel] = palette[((indices >> (i * 3)) & 0x7) as usize] as u8;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decompresses_bc1() {
+        // endpoints red and blue in 4 color mode, the first row uses each palette entry
+        let block = [0x00, 0xf8, 0x1f, 0x00, 0b1110_0100, 0, 0, 0];
+        let pixels = decompress(&block, Extent3d::new(2, 1, 1), TextureFormat::Bc1RgbaUnorm);
+        assert_eq!(pixels, Some(vec![255, 0, 0, 255, 0, 0, 255, 255]));
+
+        // swapped endpoints select the 3 color mode, where index 3 is transparent black
+        let block = [0x1f, 0x00, 0x00, 0xf8, 0b1110_0100, 0, 0, 0];
+        let pixels = decompress(&block, Extent3d::new(4, 1, 1), TextureFormat::Bc1RgbaUnorm);
+        assert_eq!(
+            pixels,
+            Some(vec![
+                0, 0, 255, 255, 255, 0, 0, 255, 127, 0, 127, 255, 0, 0, 0, 0
+            ])
+        );
+    }
+
+    #[test]
+    fn decompresses_bc4() {
+        // in 8 value mode the second pixel is 1/7th of the way from endpoint 0 to endpoint 1
+        let block = [140, 0, 0b0001_0000, 0, 0, 0, 0, 0];
+        let pixels = decompress(&block, Extent3d::new(3, 1, 1), TextureFormat::Bc4RUnorm);
+        assert_eq!(pixels, Some(vec![140, 120, 140]));
+
+        // in 6 value mode it is 1/5th of the way
+        let block = [0, 140, 0b0001_0000, 0, 0, 0, 0, 0];
+        let pixels = decompress(&block, Extent3d::new(3, 1, 1), TextureFormat::Bc4RUnorm);
+        assert_eq!(pixels, Some(vec![0, 28, 0]));
+
+        // signed 6 value mode ends with the minimum and maximum values
+        let block = [0x81, 0x7f, 0b0011_0111, 0, 0, 0, 0, 0];
+        let pixels = decompress(&block, Extent3d::new(2, 1, 1), TextureFormat::Bc4RSnorm);
+        assert_eq!(pixels, Some(vec![127, 0x81]));
+    }
+
+    #[test]
+    fn skips_pixels_outside_the_texture() {
+        let block = [0xff; 8];
+        let blocks = [block, block].concat();
+        let pixels = decompress(&blocks, Extent3d::new(5, 1, 1), TextureFormat::Bc4RUnorm);
+        assert_eq!(pixels.map(|pixels| pixels.len()), Some(5));
+        assert_eq!(
+            decompress(&block, Extent3d::new(5, 1, 1), TextureFormat::Bc4RUnorm),
+            None
+        );
+    }
+
+    #[test]
+    fn has_no_device_format_without_a_decompressor() {
+        use crate::texture::{Texture, TextureCompression};
+
+        let texture = |format| Texture {
+            format,
+            ..Default::default()
+        };
+        let bc4 = texture(TextureFormat::Bc4RUnorm);
+        assert_eq!(
+            bc4.device_format(TextureCompression::empty()),
+            Some(TextureFormat::R8Unorm)
+        );
+        assert_eq!(
+            bc4.device_format(TextureCompression::BC),
+            Some(TextureFormat::Bc4RUnorm)
+        );
+        let bc7 = texture(TextureFormat::Bc7RgbaUnorm);
+        assert_eq!(bc7.device_format(TextureCompression::empty()), None);
+    }
+}
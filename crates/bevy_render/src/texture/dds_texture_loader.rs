@@ -0,0 +1,354 @@
+use super::{Extent3d, Texture, TextureDimension, TextureFormat};
+use anyhow::Result;
+use bevy_asset::{AssetLoader, LoadContext, LoadedAsset};
+use bevy_utils::BoxedFuture;
+use std::convert::TryInto;
+use thiserror::Error;
+
+/// Loads DDS textures as Texture assets, including their mip levels, array layers and cube faces
+#[derive(Clone, Default)]
+pub struct DdsTextureLoader;
+
+impl AssetLoader for DdsTextureLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let texture = dds_buffer_to_texture(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(texture));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["dds"]
+    }
+}
+
+/// An error that occurs when reading a DDS file
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum DdsError {
+    #[error("not a DDS file")]
+    InvalidMagic,
+    #[error("the file ends before the data it describes")]
+    UnexpectedEnd,
+    #[error("unsupported pixel format with FourCC {0:?}")]
+    UnsupportedFourCc([u8; 4]),
+    #[error("unsupported uncompressed pixel format")]
+    UnsupportedPixelFormat,
+    #[error("unsupported DXGI format {0}")]
+    UnsupportedDxgiFormat(u32),
+    #[error("the texture has no pixels")]
+    Empty,
+    #[error("the texture is too large to load")]
+    TooLarge,
+    #[error("{0} mip levels don't fit the size of the texture")]
+    InvalidMipLevelCount(u32),
+}
+
+const MAGIC: &[u8; 4] = b"DDS ";
+/// The size of the magic number and the header
+const HEADER_END: usize = 128;
+const DX10_HEADER_END: usize = HEADER_END + 20;
+
+const DDSD_MIPMAPCOUNT: u32 = 0x2_0000;
+const DDPF_FOURCC: u32 = 0x4;
+const DDPF_RGB: u32 = 0x40;
+const DDSCAPS2_CUBEMAP: u32 = 0x200;
+const DDSCAPS2_VOLUME: u32 = 0x20_0000;
+const D3D10_RESOURCE_DIMENSION_TEXTURE3D: u32 = 4;
+const D3D10_RESOURCE_MISC_TEXTURECUBE: u32 = 0x4;
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, DdsError> {
+    bytes
+        .get(offset..offset + 4)
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or(DdsError::UnexpectedEnd)
+}
+
+/// Reads a DDS file. Array layers and cube faces are stored as the layers of a 2D texture, in
+/// the order they appear in the file.
+pub fn dds_buffer_to_texture(bytes: &[u8]) -> Result<Texture, DdsError> {
+    if bytes.get(..4) != Some(&MAGIC[..]) {
+        return Err(DdsError::InvalidMagic);
+    }
+    let flags = read_u32(bytes, 8)?;
+    let height = read_u32(bytes, 12)?;
+    let width = read_u32(bytes, 16)?;
+    let depth = read_u32(bytes, 24)?;
+    let mip_map_count = read_u32(bytes, 28)?;
+    let pixel_format_flags = read_u32(bytes, 80)?;
+    let four_cc = read_u32(bytes, 84)?.to_le_bytes();
+    let caps2 = read_u32(bytes, 112)?;
+
+    let mip_level_count = if flags & DDSD_MIPMAPCOUNT != 0 {
+        mip_map_count.max(1)
+    } else {
+        1
+    };
+    let faces = if caps2 & DDSCAPS2_CUBEMAP != 0 { 6 } else { 1 };
+    let (format, dimension, layers, data_offset) = if pixel_format_flags & DDPF_FOURCC != 0 {
+        if &four_cc == b"DX10" {
+            let dxgi_format = read_u32(bytes, HEADER_END)?;
+            let resource_dimension = read_u32(bytes, HEADER_END + 4)?;
+            let misc_flag = read_u32(bytes, HEADER_END + 8)?;
+            let array_size = read_u32(bytes, HEADER_END + 12)?.max(1);
+            let format = dxgi_format_to_texture_format(dxgi_format)
+                .ok_or(DdsError::UnsupportedDxgiFormat(dxgi_format))?;
+            let dimension = if resource_dimension == D3D10_RESOURCE_DIMENSION_TEXTURE3D {
+                TextureDimension::D3
+            } else {
+                TextureDimension::D2
+            };
+            let faces = if misc_flag & D3D10_RESOURCE_MISC_TEXTURECUBE != 0 {
+                6
+            } else {
+                1
+            };
+            let layers = array_size.checked_mul(faces).ok_or(DdsError::TooLarge)?;
+            (format, dimension, layers, DX10_HEADER_END)
+        } else {
+            let format =
+                four_cc_to_texture_format(&four_cc).ok_or(DdsError::UnsupportedFourCc(four_cc))?;
+            (format, legacy_dimension(caps2), faces, HEADER_END)
+        }
+    } else if pixel_format_flags & DDPF_RGB != 0 {
+        let format = masks_to_texture_format(bytes)?.ok_or(DdsError::UnsupportedPixelFormat)?;
+        (format, legacy_dimension(caps2), faces, HEADER_END)
+    } else {
+        return Err(DdsError::UnsupportedPixelFormat);
+    };
+
+    let size = match dimension {
+        TextureDimension::D3 => Extent3d::new(width, height, depth.max(1)),
+        _ => Extent3d::new(width, height, layers),
+    };
+    if size.width == 0 || size.height == 0 {
+        return Err(DdsError::Empty);
+    }
+    let mut texture = Texture {
+        size,
+        format,
+        dimension,
+        mip_level_count,
+        ..Default::default()
+    };
+    if mip_level_count > texture.max_mip_level_count() {
+        return Err(DdsError::InvalidMipLevelCount(mip_level_count));
+    }
+
+    // DDS stores every mip level of a layer before the next layer, but textures store every
+    // layer of a mip level before the next level
+    let level_sizes = (0..mip_level_count)
+        .map(|level| {
+            let mut size = texture.mip_level_size(level);
+            if dimension != TextureDimension::D3 {
+                size.depth = 1;
+            }
+            format.checked_data_size(size)
+        })
+        .collect::<Option<Vec<_>>>()
+        .ok_or(DdsError::TooLarge)?;
+    let file_layers = if dimension == TextureDimension::D3 {
+        1
+    } else {
+        layers as usize
+    };
+    let layer_size = level_sizes
+        .iter()
+        .try_fold(0usize, |sum, size| sum.checked_add(*size))
+        .ok_or(DdsError::TooLarge)?;
+    let data_size = layer_size
+        .checked_mul(file_layers)
+        .ok_or(DdsError::TooLarge)?;
+    let data = bytes
+        .get(data_offset..)
+        .and_then(|data| data.get(..data_size))
+        .ok_or(DdsError::UnexpectedEnd)?;
+    for (level, level_size) in level_sizes.iter().enumerate() {
+        let level_offset = level_sizes[..level].iter().sum::<usize>();
+        for layer in 0..file_layers {
+            let offset = layer * layer_size + level_offset;
+            texture
+                .data
+                .extend_from_slice(&data[offset..offset + level_size]);
+        }
+    }
+    Ok(texture)
+}
+
+fn legacy_dimension(caps2: u32) -> TextureDimension {
+    if caps2 & DDSCAPS2_VOLUME != 0 {
+        TextureDimension::D3
+    } else {
+        TextureDimension::D2
+    }
+}
+
+fn four_cc_to_texture_format(four_cc: &[u8; 4]) -> Option<TextureFormat> {
+    Some(match four_cc {
+        b"DXT1" => TextureFormat::Bc1RgbaUnorm,
+        b"DXT2" | b"DXT3" => TextureFormat::Bc2RgbaUnorm,
+        b"DXT4" | b"DXT5" => TextureFormat::Bc3RgbaUnorm,
+        b"ATI1" | b"BC4U" => TextureFormat::Bc4RUnorm,
+        b"BC4S" => TextureFormat::Bc4RSnorm,
+        b"ATI2" | b"BC5U" => TextureFormat::Bc5RgUnorm,
+        b"BC5S" => TextureFormat::Bc5RgSnorm,
+        _ => return None,
+    })
+}
+
+/// Finds the format of uncompressed legacy files from their bit count and channel masks
+fn masks_to_texture_format(bytes: &[u8]) -> Result<Option<TextureFormat>, DdsError> {
+    let bit_count = read_u32(bytes, 88)?;
+    let masks = [
+        read_u32(bytes, 92)?,
+        read_u32(bytes, 96)?,
+        read_u32(bytes, 100)?,
+        read_u32(bytes, 104)?,
+    ];
+    Ok(match (bit_count, masks) {
+        (32, [0xff, 0xff00, 0xff_0000, 0xff00_0000]) => Some(TextureFormat::Rgba8Unorm),
+        (32, [0xff_0000, 0xff00, 0xff, 0xff00_0000]) => Some(TextureFormat::Bgra8Unorm),
+        _ => None,
+    })
+}
+
+fn dxgi_format_to_texture_format(dxgi_format: u32) -> Option<TextureFormat> {
+    Some(match dxgi_format {
+        2 => TextureFormat::Rgba32Float,
+        3 => TextureFormat::Rgba32Uint,
+        4 => TextureFormat::Rgba32Sint,
+        10 => TextureFormat::Rgba16Float,
+        12 => TextureFormat::Rgba16Uint,
+        14 => TextureFormat::Rgba16Sint,
+        16 => TextureFormat::Rg32Float,
+        17 => TextureFormat::Rg32Uint,
+        18 => TextureFormat::Rg32Sint,
+        24 => TextureFormat::Rgb10a2Unorm,
+        26 => TextureFormat::Rg11b10Float,
+        28 => TextureFormat::Rgba8Unorm,
+        29 => TextureFormat::Rgba8UnormSrgb,
+        30 => TextureFormat::Rgba8Uint,
+        31 => TextureFormat::Rgba8Snorm,
+        32 => TextureFormat::Rgba8Sint,
+        34 => TextureFormat::Rg16Float,
+        36 => TextureFormat::Rg16Uint,
+        38 => TextureFormat::Rg16Sint,
+        41 => TextureFormat::R32Float,
+        42 => TextureFormat::R32Uint,
+        43 => TextureFormat::R32Sint,
+        49 => TextureFormat::Rg8Unorm,
+        50 => TextureFormat::Rg8Uint,
+        51 => TextureFormat::Rg8Snorm,
+        52 => TextureFormat::Rg8Sint,
+        54 => TextureFormat::R16Float,
+        57 => TextureFormat::R16Uint,
+        59 => TextureFormat::R16Sint,
+        61 => TextureFormat::R8Unorm,
+        62 => TextureFormat::R8Uint,
+        63 => TextureFormat::R8Snorm,
+        64 => TextureFormat::R8Sint,
+        71 => TextureFormat::Bc1RgbaUnorm,
+        72 => TextureFormat::Bc1RgbaUnormSrgb,
+        74 => TextureFormat::Bc2RgbaUnorm,
+        75 => TextureFormat::Bc2RgbaUnormSrgb,
+        77 => TextureFormat::Bc3RgbaUnorm,
+        78 => TextureFormat::Bc3RgbaUnormSrgb,
+        80 => TextureFormat::Bc4RUnorm,
+        81 => TextureFormat::Bc4RSnorm,
+        83 => TextureFormat::Bc5RgUnorm,
+        84 => TextureFormat::Bc5RgSnorm,
+        87 => TextureFormat::Bgra8Unorm,
+        91 => TextureFormat::Bgra8UnormSrgb,
+        95 => TextureFormat::Bc6hRgbUfloat,
+        96 => TextureFormat::Bc6hRgbSfloat,
+        98 => TextureFormat::Bc7RgbaUnorm,
+        99 => TextureFormat::Bc7RgbaUnormSrgb,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dds_file(width: u32, height: u32, mips: u32, four_cc: &[u8; 4], caps2: u32) -> Vec<u8> {
+        let mut file = vec![0; HEADER_END];
+        file[..4].copy_from_slice(MAGIC);
+        let mut write = |offset: usize, value: u32| {
+            file[offset..offset + 4].copy_from_slice(&value.to_le_bytes())
+        };
+        write(4, 124);
+        write(8, DDSD_MIPMAPCOUNT);
+        write(12, height);
+        write(16, width);
+        write(28, mips);
+        write(76, 32);
+        write(80, DDPF_FOURCC);
+        write(84, u32::from_le_bytes(*four_cc));
+        write(112, caps2);
+        file
+    }
+
+    #[test]
+    fn reorders_cubemap_mip_levels() {
+        let mut file = dds_file(4, 4, 2, b"DXT1", DDSCAPS2_CUBEMAP);
+        // each face has an 8 byte block for both mip levels, filled with the face and level
+        for face in 0..6u8 {
+            file.extend_from_slice(&[face * 2; 8]);
+            file.extend_from_slice(&[face * 2 + 1; 8]);
+        }
+        let texture = dds_buffer_to_texture(&file).unwrap();
+        assert_eq!(texture.format, TextureFormat::Bc1RgbaUnorm);
+        assert_eq!(texture.size, Extent3d::new(4, 4, 6));
+        assert_eq!(texture.mip_level_count, 2);
+        let level_0 = (0..6)
+            .flat_map(|face| vec![face * 2; 8])
+            .collect::<Vec<_>>();
+        let level_1 = (0..6)
+            .flat_map(|face| vec![face * 2 + 1; 8])
+            .collect::<Vec<_>>();
+        assert_eq!(texture.mip_level_data(0), &level_0[..]);
+        assert_eq!(texture.mip_level_data(1), &level_1[..]);
+    }
+
+    #[test]
+    fn rejects_invalid_files() {
+        assert_eq!(
+            dds_buffer_to_texture(b"not a texture").unwrap_err(),
+            DdsError::InvalidMagic
+        );
+        assert_eq!(
+            dds_buffer_to_texture(&dds_file(4, 4, 1, b"DXT1", 0)).unwrap_err(),
+            DdsError::UnexpectedEnd
+        );
+        assert_eq!(
+            dds_buffer_to_texture(&dds_file(4, 4, 1, b"ABCD", 0)).unwrap_err(),
+            DdsError::UnsupportedFourCc(*b"ABCD")
+        );
+        assert_eq!(
+            dds_buffer_to_texture(&dds_file(4, 4, 4, b"DXT1", 0)).unwrap_err(),
+            DdsError::InvalidMipLevelCount(4)
+        );
+        assert_eq!(
+            dds_buffer_to_texture(&dds_file(4, 0, 1, b"DXT1", 0)).unwrap_err(),
+            DdsError::Empty
+        );
+        assert_eq!(
+            dds_buffer_to_texture(&dds_file(0, 4, 1, b"DXT1", 0)).unwrap_err(),
+            DdsError::Empty
+        );
+        // a cubemap array with more faces than a u32 can count
+        let mut file = dds_file(4, 4, 1, b"DX10", 0);
+        for value in &[71, 3, D3D10_RESOURCE_MISC_TEXTURECUBE, u32::MAX, 0] {
+            file.extend_from_slice(&value.to_le_bytes());
+        }
+        assert_eq!(
+            dds_buffer_to_texture(&file).unwrap_err(),
+            DdsError::TooLarge
+        );
+    }
+}
@@ -8,7 +8,7 @@ use thiserror::Error;
 #[derive(Clone, Default)]
 pub struct ImageTextureLoader;
 
-const FILE_EXTENSIONS: &[&str] = &["png", "dds", "tga", "jpg", "jpeg", "bmp"];
+const FILE_EXTENSIONS: &[&str] = &["png", "tga", "jpg", "jpeg", "bmp"];
 
 impl AssetLoader for ImageTextureLoader {
     fn load<'a>(
@@ -0,0 +1,200 @@
+use super::{texture_to_ktx2_buffer, Extent3d, Texture, TextureDimension, TextureFormat};
+use anyhow::Result;
+use bevy_asset::{AssetProcessor, ImportSettings};
+
+/// Imports images as KTX2 textures, which load without decoding.
+///
+/// [ImportSettings::generate_mipmaps] stores a full mip chain, [ImportSettings::compression]
+/// compresses the texture with BC3 and [ImportSettings::srgb] decides whether the colors are
+/// sRGB encoded. Compressed textures are decompressed when loaded on devices without BC support.
+#[derive(Clone, Default)]
+pub struct ImageTextureProcessor;
+
+impl AssetProcessor for ImageTextureProcessor {
+    fn process(&self, bytes: &[u8], settings: &ImportSettings) -> Result<Vec<u8>> {
+        let image = image::load_from_memory(bytes)?.into_rgba8();
+        let (width, height) = image.dimensions();
+        let format = if settings.srgb {
+            TextureFormat::Rgba8UnormSrgb
+        } else {
+            TextureFormat::Rgba8Unorm
+        };
+        let mut texture = Texture::new(
+            Extent3d::new(width, height, 1),
+            TextureDimension::D2,
+            image.into_raw(),
+            format,
+        );
+        if settings.generate_mipmaps {
+            texture.generate_mipmaps();
+        }
+        if settings.compression {
+            texture = compress_bc3(&texture);
+        }
+        Ok(texture_to_ktx2_buffer(&texture).expect("RGBA8 and BC3 textures can be written"))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["png", "tga", "jpg", "jpeg", "bmp"]
+    }
+
+    fn artifact_extension(&self) -> &str {
+        "ktx2"
+    }
+}
+
+/// Compresses every mip level of an RGBA8 texture with BC3
+fn compress_bc3(texture: &Texture) -> Texture {
+    let format = match texture.format {
+        TextureFormat::Rgba8UnormSrgb => TextureFormat::Bc3RgbaUnormSrgb,
+        _ => TextureFormat::Bc3RgbaUnorm,
+    };
+    let mut data = Vec::new();
+    for level in 0..texture.mip_level_count {
+        let size = texture.mip_level_size(level);
+        let pixels = texture.mip_level_data(level);
+        let (width, height) = (size.width as usize, size.height as usize);
+        for layer in 0..size.depth as usize {
+            for block_y in (0..height).step_by(4) {
+                for block_x in (0..width).step_by(4) {
+                    let mut block = [[0u8; 4]; 16];
+                    for (i, pixel) in block.iter_mut().enumerate() {
+                        // blocks on the right and bottom edges repeat the last pixels
+                        let x = (block_x + i % 4).min(width - 1);
+                        let y = (block_y + i / 4).min(height - 1);
+                        let offset = ((layer * height + y) * width + x) * 4;
+                        pixel.copy_from_slice(&pixels[offset..offset + 4]);
+                    }
+                    encode_alpha_block(&block, &mut data);
+                    encode_color_block(&block, &mut data);
+                }
+            }
+        }
+    }
+    Texture {
+        data,
+        format,
+        ..texture.clone()
+    }
+}
+
+fn to_rgb565(color: [u8; 3]) -> u16 {
+    ((color[0] as u16 >> 3) << 11) | ((color[1] as u16 >> 2) << 5) | (color[2] as u16 >> 3)
+}
+
+fn from_rgb565(color: u16) -> [i32; 3] {
+    let r = (color >> 11) as i32 & 0x1f;
+    let g = (color >> 5) as i32 & 0x3f;
+    let b = color as i32 & 0x1f;
+    [
+        (r << 3) | (r >> 2),
+        (g << 2) | (g >> 4),
+        (b << 3) | (b >> 2),
+    ]
+}
+
+/// Encodes the colors of a block between the corners of their bounding box, in the 4 color mode
+/// used by BC3
+fn encode_color_block(block: &[[u8; 4]; 16], data: &mut Vec<u8>) {
+    let mut min = [255u8; 3];
+    let mut max = [0u8; 3];
+    for pixel in block.iter() {
+        for channel in 0..3 {
+            min[channel] = min[channel].min(pixel[channel]);
+            max[channel] = max[channel].max(pixel[channel]);
+        }
+    }
+    let color0 = to_rgb565(max);
+    let color1 = to_rgb565(min);
+    let c0 = from_rgb565(color0);
+    let c1 = from_rgb565(color1);
+    let mut palette = [[0i32; 3]; 4];
+    for channel in 0..3 {
+        palette[0][channel] = c0[channel];
+        palette[1][channel] = c1[channel];
+        palette[2][channel] = (2 * c0[channel] + c1[channel]) / 3;
+        palette[3][channel] = (c0[channel] + 2 * c1[channel]) / 3;
+    }
+
+    let mut indices = 0u32;
+    for (i, pixel) in block.iter().enumerate() {
+        let distance = |color: &[i32; 3]| {
+            (0..3)
+                .map(|channel| (color[channel] - pixel[channel] as i32).pow(2))
+                .sum::<i32>()
+        };
+        let index = (0..4)
+            .min_by_key(|index| distance(&palette[*index]))
+            .unwrap();
+        indices |= (index as u32) << (i * 2);
+    }
+    data.extend_from_slice(&color0.to_le_bytes());
+    data.extend_from_slice(&color1.to_le_bytes());
+    data.extend_from_slice(&indices.to_le_bytes());
+}
+
+/// Encodes the alphas of a block between their minimum and maximum, in the 8 value mode
+fn encode_alpha_block(block: &[[u8; 4]; 16], data: &mut Vec<u8>) {
+    let max = block.iter().map(|pixel| pixel[3]).max().unwrap() as i32;
+    let min = block.iter().map(|pixel| pixel[3]).min().unwrap() as i32;
+    let mut palette = [max, min, 0, 0, 0, 0, 0, 0];
+    for i in 1..7 {
+        palette[i + 1] = ((7 - i as i32) * max + i as i32 * min) / 7;
+    }
+
+    let mut indices = 0u64;
+    for (i, pixel) in block.iter().enumerate() {
+        // with equal endpoints, the decoder's 6 value mode also starts with them
+        let index = (0..8)
+            .min_by_key(|index| (palette[*index] - pixel[3] as i32).abs())
+            .unwrap();
+        indices |= (index as u64) << (i * 3);
+    }
+    data.push(max as u8);
+    data.push(min as u8);
+    data.extend_from_slice(&indices.to_le_bytes()[..6]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generates_mipmaps() {
+        let mut texture = Texture::new(
+            Extent3d::new(4, 2, 1),
+            TextureDimension::D2,
+            [[0, 0, 0, 255], [255, 255, 255, 255]].repeat(4).concat(),
+            TextureFormat::Rgba8Unorm,
+        );
+        assert!(texture.generate_mipmaps());
+        assert_eq!(texture.mip_level_count, 3);
+        assert_eq!(texture.mip_level_size(2), Extent3d::new(1, 1, 1));
+        assert_eq!(
+            texture.mip_level_data(1),
+            &[128, 128, 128, 255, 128, 128, 128, 255]
+        );
+        assert_eq!(texture.mip_level_data(2), &[128, 128, 128, 255]);
+    }
+
+    #[test]
+    fn compressed_textures_decompress_to_their_source() {
+        let mut source = Vec::new();
+        for y in 0..6u8 {
+            for x in 0..6u8 {
+                let value = if (x + y) % 2 == 0 { 255 } else { 0 };
+                source.extend_from_slice(&[value, value, value, 255 - value]);
+            }
+        }
+        let texture = Texture::new(
+            Extent3d::new(6, 6, 1),
+            TextureDimension::D2,
+            source.clone(),
+            TextureFormat::Rgba8Unorm,
+        );
+        let compressed = compress_bc3(&texture);
+        assert_eq!(compressed.format, TextureFormat::Bc3RgbaUnorm);
+        assert_eq!(compressed.data.len(), 4 * 16);
+        assert_eq!(compressed.decompress().unwrap().data, source);
+    }
+}
@@ -0,0 +1,403 @@
+use super::{Extent3d, Texture, TextureDimension, TextureFormat};
+use anyhow::Result;
+use bevy_asset::{AssetLoader, LoadContext, LoadedAsset};
+use bevy_utils::BoxedFuture;
+use std::convert::TryInto;
+use thiserror::Error;
+
+/// Loads KTX2 textures as Texture assets, including their mip levels, array layers and cube
+/// faces. Supercompressed files are not supported.
+#[derive(Clone, Default)]
+pub struct Ktx2TextureLoader;
+
+impl AssetLoader for Ktx2TextureLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let texture = ktx2_buffer_to_texture(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(texture));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ktx2"]
+    }
+}
+
+/// An error that occurs when reading a KTX2 file
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum Ktx2Error {
+    #[error("not a KTX2 file")]
+    InvalidIdentifier,
+    #[error("the file ends before the data it describes")]
+    UnexpectedEnd,
+    #[error("unsupported Vulkan format {0}")]
+    UnsupportedFormat(u32),
+    #[error("unsupported supercompression scheme {0}")]
+    UnsupportedSupercompression(u32),
+    #[error("mip level {0} has {1} bytes, expected {2}")]
+    InvalidLevelLength(u32, usize, usize),
+    #[error("the texture has no pixels")]
+    Empty,
+    #[error("the texture is too large to load")]
+    TooLarge,
+    #[error("{0} mip levels don't fit the size of the texture")]
+    InvalidMipLevelCount(u32),
+}
+
+const IDENTIFIER: [u8; 12] = [
+    0xab, 0x4b, 0x54, 0x58, 0x20, 0x32, 0x30, 0xbb, 0x0d, 0x0a, 0x1a, 0x0a,
+];
+/// The size of the identifier, header and index that come before the level index
+const LEVEL_INDEX_OFFSET: usize = 80;
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, Ktx2Error> {
+    bytes
+        .get(offset..offset + 4)
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or(Ktx2Error::UnexpectedEnd)
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> Result<usize, Ktx2Error> {
+    bytes
+        .get(offset..offset + 8)
+        .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()) as usize)
+        .ok_or(Ktx2Error::UnexpectedEnd)
+}
+
+/// Reads a KTX2 file. Array layers and cube faces are stored as the layers of a 2D texture, in
+/// the order they appear in the file.
+pub fn ktx2_buffer_to_texture(bytes: &[u8]) -> Result<Texture, Ktx2Error> {
+    if bytes.get(..IDENTIFIER.len()) != Some(&IDENTIFIER[..]) {
+        return Err(Ktx2Error::InvalidIdentifier);
+    }
+    let vk_format = read_u32(bytes, 12)?;
+    let width = read_u32(bytes, 20)?;
+    let height = read_u32(bytes, 24)?;
+    let depth = read_u32(bytes, 28)?;
+    let layer_count = read_u32(bytes, 32)?;
+    let face_count = read_u32(bytes, 36)?;
+    let level_count = read_u32(bytes, 40)?;
+    let supercompression = read_u32(bytes, 44)?;
+
+    let format =
+        vk_format_to_texture_format(vk_format).ok_or(Ktx2Error::UnsupportedFormat(vk_format))?;
+    if supercompression != 0 {
+        return Err(Ktx2Error::UnsupportedSupercompression(supercompression));
+    }
+
+    // dimensions that don't apply are 0, but a 3D texture has a height
+    if width == 0 || (depth > 0 && height == 0) {
+        return Err(Ktx2Error::Empty);
+    }
+    let (dimension, depth) = if depth > 0 {
+        (TextureDimension::D3, depth)
+    } else if height > 0 {
+        let layers = layer_count
+            .max(1)
+            .checked_mul(face_count.max(1))
+            .ok_or(Ktx2Error::TooLarge)?;
+        (TextureDimension::D2, layers)
+    } else {
+        (TextureDimension::D1, layer_count.max(1))
+    };
+    let mut texture = Texture {
+        size: Extent3d::new(width, height.max(1), depth),
+        format,
+        dimension,
+        // 0 asks for a full mip chain to be generated, which isn't supported, so only the base
+        // level is loaded
+        mip_level_count: level_count.max(1),
+        ..Default::default()
+    };
+    if texture.mip_level_count > texture.max_mip_level_count() {
+        return Err(Ktx2Error::InvalidMipLevelCount(texture.mip_level_count));
+    }
+
+    for level in 0..texture.mip_level_count {
+        let index = LEVEL_INDEX_OFFSET + level as usize * 24;
+        let offset = read_u64(bytes, index)?;
+        let length = read_u64(bytes, index + 8)?;
+        let expected_length = format
+            .checked_data_size(texture.mip_level_size(level))
+            .ok_or(Ktx2Error::TooLarge)?;
+        if length != expected_length {
+            return Err(Ktx2Error::InvalidLevelLength(
+                level,
+                length,
+                expected_length,
+            ));
+        }
+        let level_data = offset
+            .checked_add(length)
+            .and_then(|end| bytes.get(offset..end))
+            .ok_or(Ktx2Error::UnexpectedEnd)?;
+        texture.data.extend_from_slice(level_data);
+    }
+    Ok(texture)
+}
+
+/// Writes a texture as a KTX2 file readable by [ktx2_buffer_to_texture]. The data format
+/// descriptor, which that loader doesn't read, is left out. Returns `None` if the format has no
+/// Vulkan equivalent.
+pub fn texture_to_ktx2_buffer(texture: &Texture) -> Option<Vec<u8>> {
+    let vk_format = (0..=184)
+        .find(|vk_format| vk_format_to_texture_format(*vk_format) == Some(texture.format))?;
+    let size = texture.size;
+    let (height, depth, layer_count) = match texture.dimension {
+        TextureDimension::D1 => (0, 0, size.depth),
+        TextureDimension::D2 => (size.height, 0, size.depth),
+        TextureDimension::D3 => (size.height, size.depth, 1),
+    };
+    let layer_count = if layer_count > 1 { layer_count } else { 0 };
+
+    let mut bytes = IDENTIFIER.to_vec();
+    for value in &[
+        vk_format,
+        1,
+        size.width,
+        height,
+        depth,
+        layer_count,
+        1,
+        texture.mip_level_count,
+        0,
+    ] {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    bytes.resize(LEVEL_INDEX_OFFSET, 0);
+
+    // levels are stored from the smallest to the largest, aligned to their block size
+    let alignment = texture.format.pixel_size().max(4);
+    let mut level_index = vec![(0, 0); texture.mip_level_count as usize];
+    let mut data = Vec::new();
+    let data_offset = LEVEL_INDEX_OFFSET + level_index.len() * 24;
+    for level in (0..texture.mip_level_count).rev() {
+        while (data_offset + data.len()) % alignment != 0 {
+            data.push(0);
+        }
+        let level_data = texture.mip_level_data(level);
+        level_index[level as usize] = (data_offset + data.len(), level_data.len());
+        data.extend_from_slice(level_data);
+    }
+    for (offset, length) in level_index {
+        bytes.extend_from_slice(&(offset as u64).to_le_bytes());
+        bytes.extend_from_slice(&(length as u64).to_le_bytes());
+        bytes.extend_from_slice(&(length as u64).to_le_bytes());
+    }
+    bytes.extend(data);
+    Some(bytes)
+}
+
+fn vk_format_to_texture_format(vk_format: u32) -> Option<TextureFormat> {
+    Some(match vk_format {
+        9 => TextureFormat::R8Unorm,
+        10 => TextureFormat::R8Snorm,
+        13 => TextureFormat::R8Uint,
+        14 => TextureFormat::R8Sint,
+        16 => TextureFormat::Rg8Unorm,
+        17 => TextureFormat::Rg8Snorm,
+        20 => TextureFormat::Rg8Uint,
+        21 => TextureFormat::Rg8Sint,
+        37 => TextureFormat::Rgba8Unorm,
+        38 => TextureFormat::Rgba8Snorm,
+        41 => TextureFormat::Rgba8Uint,
+        42 => TextureFormat::Rgba8Sint,
+        43 => TextureFormat::Rgba8UnormSrgb,
+        44 => TextureFormat::Bgra8Unorm,
+        50 => TextureFormat::Bgra8UnormSrgb,
+        64 => TextureFormat::Rgb10a2Unorm,
+        74 => TextureFormat::R16Uint,
+        75 => TextureFormat::R16Sint,
+        76 => TextureFormat::R16Float,
+        81 => TextureFormat::Rg16Uint,
+        82 => TextureFormat::Rg16Sint,
+        83 => TextureFormat::Rg16Float,
+        95 => TextureFormat::Rgba16Uint,
+        96 => TextureFormat::Rgba16Sint,
+        97 => TextureFormat::Rgba16Float,
+        98 => TextureFormat::R32Uint,
+        99 => TextureFormat::R32Sint,
+        100 => TextureFormat::R32Float,
+        101 => TextureFormat::Rg32Uint,
+        102 => TextureFormat::Rg32Sint,
+        103 => TextureFormat::Rg32Float,
+        107 => TextureFormat::Rgba32Uint,
+        108 => TextureFormat::Rgba32Sint,
+        109 => TextureFormat::Rgba32Float,
+        122 => TextureFormat::Rg11b10Float,
+        133 => TextureFormat::Bc1RgbaUnorm,
+        134 => TextureFormat::Bc1RgbaUnormSrgb,
+        135 => TextureFormat::Bc2RgbaUnorm,
+        136 => TextureFormat::Bc2RgbaUnormSrgb,
+        137 => TextureFormat::Bc3RgbaUnorm,
+        138 => TextureFormat::Bc3RgbaUnormSrgb,
+        139 => TextureFormat::Bc4RUnorm,
+        140 => TextureFormat::Bc4RSnorm,
+        141 => TextureFormat::Bc5RgUnorm,
+        142 => TextureFormat::Bc5RgSnorm,
+        143 => TextureFormat::Bc6hRgbUfloat,
+        144 => TextureFormat::Bc6hRgbSfloat,
+        145 => TextureFormat::Bc7RgbaUnorm,
+        146 => TextureFormat::Bc7RgbaUnormSrgb,
+        147 => TextureFormat::Etc2RgbUnorm,
+        148 => TextureFormat::Etc2RgbUnormSrgb,
+        149 => TextureFormat::Etc2RgbA1Unorm,
+        150 => TextureFormat::Etc2RgbA1UnormSrgb,
+        151 => TextureFormat::Etc2RgbA8Unorm,
+        152 => TextureFormat::Etc2RgbA8UnormSrgb,
+        153 => TextureFormat::EacRUnorm,
+        154 => TextureFormat::EacRSnorm,
+        155 => TextureFormat::EtcRgUnorm,
+        156 => TextureFormat::EtcRgSnorm,
+        157 => TextureFormat::Astc4x4RgbaUnorm,
+        158 => TextureFormat::Astc4x4RgbaUnormSrgb,
+        159 => TextureFormat::Astc5x4RgbaUnorm,
+        160 => TextureFormat::Astc5x4RgbaUnormSrgb,
+        161 => TextureFormat::Astc5x5RgbaUnorm,
+        162 => TextureFormat::Astc5x5RgbaUnormSrgb,
+        163 => TextureFormat::Astc6x5RgbaUnorm,
+        164 => TextureFormat::Astc6x5RgbaUnormSrgb,
+        165 => TextureFormat::Astc6x6RgbaUnorm,
+        166 => TextureFormat::Astc6x6RgbaUnormSrgb,
+        167 => TextureFormat::Astc8x5RgbaUnorm,
+        168 => TextureFormat::Astc8x5RgbaUnormSrgb,
+        169 => TextureFormat::Astc8x6RgbaUnorm,
+        170 => TextureFormat::Astc8x6RgbaUnormSrgb,
+        171 => TextureFormat::Astc8x8RgbaUnorm,
+        172 => TextureFormat::Astc8x8RgbaUnormSrgb,
+        173 => TextureFormat::Astc10x5RgbaUnorm,
+        174 => TextureFormat::Astc10x5RgbaUnormSrgb,
+        175 => TextureFormat::Astc10x6RgbaUnorm,
+        176 => TextureFormat::Astc10x6RgbaUnormSrgb,
+        177 => TextureFormat::Astc10x8RgbaUnorm,
+        178 => TextureFormat::Astc10x8RgbaUnormSrgb,
+        179 => TextureFormat::Astc10x10RgbaUnorm,
+        180 => TextureFormat::Astc10x10RgbaUnormSrgb,
+        181 => TextureFormat::Astc12x10RgbaUnorm,
+        182 => TextureFormat::Astc12x10RgbaUnormSrgb,
+        183 => TextureFormat::Astc12x12RgbaUnorm,
+        184 => TextureFormat::Astc12x12RgbaUnormSrgb,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ktx2_file(
+        vk_format: u32,
+        size: [u32; 3],
+        layers: u32,
+        faces: u32,
+        levels: &[&[u8]],
+    ) -> Vec<u8> {
+        let mut file = IDENTIFIER.to_vec();
+        for value in &[
+            vk_format,
+            1,
+            size[0],
+            size[1],
+            size[2],
+            layers,
+            faces,
+            levels.len() as u32,
+            0,
+        ] {
+            file.extend_from_slice(&value.to_le_bytes());
+        }
+        // data format descriptor, key/value data and supercompression global data are left empty
+        file.resize(LEVEL_INDEX_OFFSET, 0);
+        let mut offset = LEVEL_INDEX_OFFSET + levels.len() * 24;
+        for level in levels {
+            file.extend_from_slice(&(offset as u64).to_le_bytes());
+            file.extend_from_slice(&(level.len() as u64).to_le_bytes());
+            file.extend_from_slice(&(level.len() as u64).to_le_bytes());
+            offset += level.len();
+        }
+        for level in levels {
+            file.extend_from_slice(level);
+        }
+        file
+    }
+
+    #[test]
+    fn loads_mip_levels_and_layers() {
+        // a 2x2 R8 texture with two array layers and two mip levels
+        let file = ktx2_file(9, [2, 2, 0], 2, 1, &[&[0, 1, 2, 3, 4, 5, 6, 7], &[8, 9]]);
+        let texture = ktx2_buffer_to_texture(&file).unwrap();
+        assert_eq!(texture.format, TextureFormat::R8Unorm);
+        assert_eq!(texture.dimension, TextureDimension::D2);
+        assert_eq!(texture.size, Extent3d::new(2, 2, 2));
+        assert_eq!(texture.mip_level_count, 2);
+        assert_eq!(texture.mip_level_size(1), Extent3d::new(1, 1, 2));
+        assert_eq!(texture.mip_level_data(1), &[8, 9]);
+
+        // a 4x4 BC1 cubemap
+        let face = [0u8; 8];
+        let file = ktx2_file(133, [4, 4, 0], 0, 6, &[&face.repeat(6)]);
+        let texture = ktx2_buffer_to_texture(&file).unwrap();
+        assert_eq!(texture.size, Extent3d::new(4, 4, 6));
+        assert_eq!(texture.data.len(), 48);
+    }
+
+    #[test]
+    fn writes_loadable_files() {
+        let mut texture = Texture::new(
+            Extent3d::new(2, 2, 1),
+            TextureDimension::D2,
+            vec![0, 1, 2, 3],
+            TextureFormat::R8Unorm,
+        );
+        texture.mip_level_count = 2;
+        texture.data.push(4);
+        let file = texture_to_ktx2_buffer(&texture).unwrap();
+        let loaded = ktx2_buffer_to_texture(&file).unwrap();
+        assert_eq!(loaded.size, texture.size);
+        assert_eq!(loaded.mip_level_count, 2);
+        assert_eq!(loaded.data, texture.data);
+    }
+
+    #[test]
+    fn rejects_invalid_files() {
+        assert_eq!(
+            ktx2_buffer_to_texture(b"not a texture").unwrap_err(),
+            Ktx2Error::InvalidIdentifier
+        );
+        let file = ktx2_file(9, [2, 2, 0], 0, 1, &[&[0, 1, 2]]);
+        assert_eq!(
+            ktx2_buffer_to_texture(&file).unwrap_err(),
+            Ktx2Error::InvalidLevelLength(0, 3, 4)
+        );
+        let file = ktx2_file(1, [2, 2, 0], 0, 1, &[&[0, 1, 2, 3]]);
+        assert_eq!(
+            ktx2_buffer_to_texture(&file).unwrap_err(),
+            Ktx2Error::UnsupportedFormat(1)
+        );
+        let file = ktx2_file(9, [2, 2, 0], 0, 1, &[&[0, 1, 2, 3], &[4], &[5]]);
+        assert_eq!(
+            ktx2_buffer_to_texture(&file).unwrap_err(),
+            Ktx2Error::InvalidMipLevelCount(3)
+        );
+        let file = ktx2_file(9, [0, 2, 0], 0, 1, &[&[]]);
+        assert_eq!(ktx2_buffer_to_texture(&file).unwrap_err(), Ktx2Error::Empty);
+        let file = ktx2_file(9, [2, 0, 2], 0, 1, &[&[]]);
+        assert_eq!(ktx2_buffer_to_texture(&file).unwrap_err(), Ktx2Error::Empty);
+        let file = ktx2_file(9, [2, 2, 0], u32::MAX, 6, &[&[0, 1, 2, 3]]);
+        assert_eq!(
+            ktx2_buffer_to_texture(&file).unwrap_err(),
+            Ktx2Error::TooLarge
+        );
+        let mut file = ktx2_file(9, [2, 2, 0], 0, 1, &[&[0, 1, 2, 3]]);
+        file.truncate(file.len() - 1);
+        assert_eq!(
+            ktx2_buffer_to_texture(&file).unwrap_err(),
+            Ktx2Error::UnexpectedEnd
+        );
+    }
+}
@@ -1,6 +1,13 @@
+mod block_decompression;
+#[cfg(feature = "dds")]
+mod dds_texture_loader;
 #[cfg(feature = "hdr")]
 mod hdr_texture_loader;
 mod image_texture_loader;
+#[cfg(feature = "ktx2")]
+mod image_texture_processor;
+#[cfg(feature = "ktx2")]
+mod ktx2_texture_loader;
 mod sampler_descriptor;
 #[allow(clippy::module_inception)]
 mod texture;
@@ -9,9 +16,15 @@ mod texture_dimension;
 
 pub(crate) mod image_texture_conversion;
 
+#[cfg(feature = "dds")]
+pub use dds_texture_loader::*;
 #[cfg(feature = "hdr")]
 pub use hdr_texture_loader::*;
 pub use image_texture_loader::*;
+#[cfg(feature = "ktx2")]
+pub use image_texture_processor::*;
+#[cfg(feature = "ktx2")]
+pub use ktx2_texture_loader::*;
 pub use sampler_descriptor::*;
 pub use texture::*;
 pub use texture_descriptor::*;
@@ -1,7 +1,11 @@
 use crate::pipeline::CompareFunction;
-use std::num::NonZeroU8;
+use std::{
+    hash::{Hash, Hasher},
+    num::NonZeroU8,
+};
 
-/// Describes a sampler
+/// Describes a sampler. Every [Texture](super::Texture) has its own, and render backends share
+/// a single sampler between equal descriptors.
 #[derive(Debug, Copy, Clone)]
 pub struct SamplerDescriptor {
     pub address_mode_u: AddressMode,
@@ -24,6 +28,47 @@ impl SamplerDescriptor {
         self.address_mode_v = address_mode;
         self.address_mode_w = address_mode;
     }
+
+    /// Sets the filter used when magnifying, minifying and between mip levels.
+    pub fn set_filter(&mut self, filter_mode: FilterMode) {
+        self.mag_filter = filter_mode;
+        self.min_filter = filter_mode;
+        self.mipmap_filter = filter_mode;
+    }
+}
+
+impl PartialEq for SamplerDescriptor {
+    fn eq(&self, other: &Self) -> bool {
+        self.address_mode_u == other.address_mode_u
+            && self.address_mode_v == other.address_mode_v
+            && self.address_mode_w == other.address_mode_w
+            && self.mag_filter == other.mag_filter
+            && self.min_filter == other.min_filter
+            && self.mipmap_filter == other.mipmap_filter
+            && self.lod_min_clamp.to_bits() == other.lod_min_clamp.to_bits()
+            && self.lod_max_clamp.to_bits() == other.lod_max_clamp.to_bits()
+            && self.compare_function == other.compare_function
+            && self.anisotropy_clamp == other.anisotropy_clamp
+            && self.border_color == other.border_color
+    }
+}
+
+impl Eq for SamplerDescriptor {}
+
+impl Hash for SamplerDescriptor {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.address_mode_u.hash(state);
+        self.address_mode_v.hash(state);
+        self.address_mode_w.hash(state);
+        self.mag_filter.hash(state);
+        self.min_filter.hash(state);
+        self.mipmap_filter.hash(state);
+        self.lod_min_clamp.to_bits().hash(state);
+        self.lod_max_clamp.to_bits().hash(state);
+        self.compare_function.hash(state);
+        self.anisotropy_clamp.hash(state);
+        self.border_color.hash(state);
+    }
 }
 
 impl Default for SamplerDescriptor {
@@ -77,3 +122,24 @@ pub enum SamplerBorderColor {
     OpaqueBlack,
     OpaqueWhite,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_utils::HashSet;
+
+    #[test]
+    fn equal_descriptors_share_a_key() {
+        let mut linear = SamplerDescriptor::default();
+        linear.set_filter(FilterMode::Linear);
+        let mut biased = linear;
+        biased.lod_min_clamp = 1.0;
+
+        let mut descriptors = HashSet::default();
+        descriptors.insert(SamplerDescriptor::default());
+        descriptors.insert(linear);
+        descriptors.insert(biased);
+        assert!(!descriptors.insert(linear));
+        assert_eq!(descriptors.len(), 3);
+    }
+}
@@ -1,26 +1,34 @@
 use super::{
-    image_texture_conversion::image_to_texture, Extent3d, SamplerDescriptor, TextureDescriptor,
-    TextureDimension, TextureFormat,
+    block_decompression, image_texture_conversion::image_to_texture, Extent3d, FilterMode,
+    SamplerDescriptor, TextureCompression, TextureDescriptor, TextureDimension, TextureFormat,
 };
 use crate::renderer::{
     RenderResource, RenderResourceContext, RenderResourceId, RenderResourceType,
 };
-use bevy_asset::{AssetEvent, Assets, Handle};
+use bevy_asset::{AssetEvent, Assets, Handle, HandleUntyped};
 use bevy_ecs::{event::EventReader, system::Res};
 use bevy_reflect::TypeUuid;
-use bevy_utils::HashSet;
+use bevy_utils::{tracing::warn, HashSet};
 use thiserror::Error;
 
 pub const TEXTURE_ASSET_INDEX: u64 = 0;
 pub const SAMPLER_ASSET_INDEX: u64 = 1;
 
+/// Sampled instead of the textures that failed to load, until they load again. It is a
+/// [Texture::error_checkerboard].
+pub const ERROR_TEXTURE_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(Texture::TYPE_UUID, 0x7d2b05e6c8a1f394);
+
 #[derive(Debug, Clone, TypeUuid)]
 #[uuid = "6ea26da6-6cf8-4ea2-9986-1d7bf6c17d6f"]
 pub struct Texture {
+    /// The mip levels of the texture one after another, starting with the full size level. Each
+    /// level contains every array layer or cube face.
     pub data: Vec<u8>,
     pub size: Extent3d,
     pub format: TextureFormat,
     pub dimension: TextureDimension,
+    pub mip_level_count: u32,
     pub sampler: SamplerDescriptor,
 }
 
@@ -35,6 +43,7 @@ impl Default for Texture {
             },
             format: TextureFormat::Rgba8UnormSrgb,
             dimension: TextureDimension::D2,
+            mip_level_count: 1,
             sampler: Default::default(),
         }
     }
@@ -48,7 +57,7 @@ impl Texture {
         format: TextureFormat,
     ) -> Self {
         debug_assert_eq!(
-            size.volume() * format.pixel_size(),
+            format.data_size(size),
             data.len(),
             "Pixel data, size and format have to match",
         );
@@ -61,6 +70,30 @@ impl Texture {
         }
     }
 
+    /// An 8x8 checkerboard of magenta and black, which stands out in any scene
+    pub fn error_checkerboard() -> Self {
+        const SIZE: u32 = 8;
+        let mut data = Vec::with_capacity((SIZE * SIZE * 4) as usize);
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                let pixel = if (x + y) % 2 == 0 {
+                    [255, 0, 255, 255]
+                } else {
+                    [0, 0, 0, 255]
+                };
+                data.extend_from_slice(&pixel);
+            }
+        }
+        let mut texture = Texture::new(
+            Extent3d::new(SIZE, SIZE, 1),
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba8UnormSrgb,
+        );
+        texture.sampler.set_filter(FilterMode::Nearest);
+        texture
+    }
+
     pub fn new_fill(
         size: Extent3d,
         dimension: TextureDimension,
@@ -94,10 +127,43 @@ impl Texture {
         self.size.height as f32 / self.size.width as f32
     }
 
+    /// Resizes the texture to `size`, dropping every mip level but the first
     pub fn resize(&mut self, size: Extent3d) {
         self.size = size;
-        self.data
-            .resize(size.volume() * self.format.pixel_size(), 0);
+        self.mip_level_count = 1;
+        self.data.resize(self.format.data_size(size), 0);
+    }
+
+    /// The number of mip levels of a full mip chain, down to a 1x1 level
+    pub fn max_mip_level_count(&self) -> u32 {
+        let mut largest = self.size.width.max(self.size.height);
+        if self.dimension == TextureDimension::D3 {
+            largest = largest.max(self.size.depth);
+        }
+        32 - largest.leading_zeros()
+    }
+
+    /// The size of the given mip level. The depth of 2D textures is the number of array layers,
+    /// which stays the same for every level.
+    pub fn mip_level_size(&self, level: u32) -> Extent3d {
+        let depth = match self.dimension {
+            TextureDimension::D3 => (self.size.depth >> level).max(1),
+            TextureDimension::D1 | TextureDimension::D2 => self.size.depth,
+        };
+        Extent3d::new(
+            (self.size.width >> level).max(1),
+            (self.size.height >> level).max(1),
+            depth,
+        )
+    }
+
+    /// The data of the given mip level
+    pub fn mip_level_data(&self, level: u32) -> &[u8] {
+        let offset = (0..level)
+            .map(|level| self.format.data_size(self.mip_level_size(level)))
+            .sum::<usize>();
+        let size = self.format.data_size(self.mip_level_size(level));
+        &self.data[offset..offset + size]
     }
 
     /// Changes the `size`, asserting that the total number of data elements (pixels) remains the
@@ -153,6 +219,106 @@ impl Texture {
             .map(super::image_texture_conversion::image_to_texture)
     }
 
+    /// Decodes a block compressed texture to an uncompressed format, for devices that don't
+    /// support its format. Returns `None` if the texture isn't compressed with BC1 to BC5.
+    pub fn decompress(&self) -> Option<Texture> {
+        let format = block_decompression::decompressed_format(self.format)?;
+        let mut data = Vec::new();
+        for level in 0..self.mip_level_count {
+            data.extend(block_decompression::decompress(
+                self.mip_level_data(level),
+                self.mip_level_size(level),
+                self.format,
+            )?);
+        }
+        Some(Texture {
+            data,
+            format,
+            ..self.clone()
+        })
+    }
+
+    /// Replaces the mip levels of a 2D texture with a full mip chain, each level averaging 2x2
+    /// pixels of the previous one. sRGB colors are averaged in linear space. Returns `false`,
+    /// leaving the texture unchanged, if its format doesn't have 8 bit unsigned normalized
+    /// channels.
+    pub fn generate_mipmaps(&mut self) -> bool {
+        let srgb = match self.format {
+            TextureFormat::R8Unorm
+            | TextureFormat::Rg8Unorm
+            | TextureFormat::Rgba8Unorm
+            | TextureFormat::Bgra8Unorm => false,
+            TextureFormat::Rgba8UnormSrgb | TextureFormat::Bgra8UnormSrgb => true,
+            _ => return false,
+        };
+        if self.dimension != TextureDimension::D2 {
+            return false;
+        }
+
+        let channels = self.format.pixel_size();
+        let to_linear = |channel: usize, value: u8| {
+            let value = value as f32 / 255.0;
+            if srgb && channel < 3 {
+                value.powf(2.2)
+            } else {
+                value
+            }
+        };
+        let from_linear = |channel: usize, value: f32| {
+            let value = if srgb && channel < 3 {
+                value.powf(1.0 / 2.2)
+            } else {
+                value
+            };
+            (value * 255.0).round() as u8
+        };
+
+        self.data.truncate(self.format.data_size(self.size));
+        self.mip_level_count = 1;
+        let level_count = 32 - self.size.width.max(self.size.height).leading_zeros();
+        for level in 1..level_count {
+            let source = self.mip_level_data(level - 1).to_vec();
+            let source_size = self.mip_level_size(level - 1);
+            let size = self.mip_level_size(level);
+            let (source_width, source_height) =
+                (source_size.width as usize, source_size.height as usize);
+            for layer in 0..size.depth as usize {
+                for y in 0..size.height as usize {
+                    for x in 0..size.width as usize {
+                        for channel in 0..channels {
+                            let mut sum = 0.0;
+                            for (dx, dy) in &[(0, 0), (1, 0), (0, 1), (1, 1)] {
+                                // odd sizes repeat the last row or column
+                                let sx = (x * 2 + dx).min(source_width - 1);
+                                let sy = (y * 2 + dy).min(source_height - 1);
+                                let offset = ((layer * source_height + sy) * source_width + sx)
+                                    * channels
+                                    + channel;
+                                sum += to_linear(channel, source[offset]);
+                            }
+                            self.data.push(from_linear(channel, sum / 4.0));
+                        }
+                    }
+                }
+            }
+            self.mip_level_count += 1;
+        }
+        true
+    }
+
+    /// The format the texture is stored in on a device that supports `supported_compression`,
+    /// or `None` if the device supports neither its format nor the format it decompresses to
+    pub(crate) fn device_format(
+        &self,
+        supported_compression: TextureCompression,
+    ) -> Option<TextureFormat> {
+        if supported_compression.contains(self.format.required_compression()) {
+            Some(self.format)
+        } else {
+            block_decompression::decompressed_format(self.format)
+        }
+    }
+
     pub fn texture_resource_system(
         render_resource_context: Res<Box<dyn RenderResourceContext>>,
         textures: Res<Assets<Texture>>,
@@ -181,7 +347,20 @@ impl Texture {
 
         for texture_handle in changed_textures.iter() {
             if let Some(texture) = textures.get(*texture_handle) {
-                let texture_descriptor: TextureDescriptor = texture.into();
+                let format = match texture
+                    .device_format(render_resource_context.supported_texture_compression())
+                {
+                    Some(format) => format,
+                    None => {
+                        warn!(
+                            "The texture format {:?} is not supported by this device",
+                            texture.format
+                        );
+                        continue;
+                    }
+                };
+                let mut texture_descriptor: TextureDescriptor = texture.into();
+                texture_descriptor.format = format;
                 let texture_resource = render_resource_context.create_texture(texture_descriptor);
 
                 let sampler_resource = render_resource_context.create_sampler(&texture.sampler);
@@ -1,6 +1,11 @@
-use super::{Extent3d, Texture, TextureDimension, TextureFormat, TextureUsage};
+use super::{
+    Extent3d, Texture, TextureDimension, TextureFormat, TextureUsage, TextureViewDimension,
+};
 
 /// Describes a texture
+///
+/// The `depth` of the `size` of 1D and 2D textures is their number of array layers, while 3D
+/// textures have that many slices.
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub struct TextureDescriptor {
     pub size: Extent3d,
@@ -9,17 +14,33 @@ pub struct TextureDescriptor {
     pub dimension: TextureDimension,
     pub format: TextureFormat,
     pub usage: TextureUsage,
+    /// How shaders see the texture. When `None`, 2D textures with more than one layer are
+    /// bound as arrays, which needs to be set explicitly for arrays with a single layer and for
+    /// cube maps.
+    pub view_dimension: Option<TextureViewDimension>,
+}
+
+impl TextureDescriptor {
+    pub fn view_dimension(&self) -> TextureViewDimension {
+        self.view_dimension.unwrap_or(match self.dimension {
+            TextureDimension::D1 => TextureViewDimension::D1,
+            TextureDimension::D2 if self.size.depth > 1 => TextureViewDimension::D2Array,
+            TextureDimension::D2 => TextureViewDimension::D2,
+            TextureDimension::D3 => TextureViewDimension::D3,
+        })
+    }
 }
 
 impl From<&Texture> for TextureDescriptor {
     fn from(texture: &Texture) -> Self {
         TextureDescriptor {
             size: texture.size,
-            mip_level_count: 1,
+            mip_level_count: texture.mip_level_count,
             sample_count: 1,
             dimension: texture.dimension,
             format: texture.format,
             usage: TextureUsage::SAMPLED | TextureUsage::COPY_DST,
+            view_dimension: None,
         }
     }
 }
@@ -37,6 +58,7 @@ impl Default for TextureDescriptor {
             dimension: TextureDimension::D2,
             format: TextureFormat::Rgba8UnormSrgb,
             usage: TextureUsage::SAMPLED | TextureUsage::COPY_DST,
+            view_dimension: None,
         }
     }
 }
@@ -58,8 +80,7 @@ pub enum StorageTextureAccess {
     /// ```
     WriteOnly,
     /// The texture can be both read and written in the shader.
-    /// `wgpu::Features::STORAGE_TEXTURE_ACCESS_READ_WRITE` must be enabled to use this access
-    /// mode.
+    /// The render backend must support read-write storage textures to use this access mode.
     ///
     /// Example GLSL syntax:
     /// ```cpp,ignore
@@ -67,3 +88,32 @@ pub enum StorageTextureAccess {
     /// ```
     ReadWrite,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn infers_array_views_from_layers() {
+        let descriptor = |dimension, depth| TextureDescriptor {
+            size: Extent3d::new(4, 4, depth),
+            dimension,
+            ..Default::default()
+        };
+        let layers = descriptor(TextureDimension::D2, 4);
+        assert_eq!(layers.view_dimension(), TextureViewDimension::D2Array);
+        assert_eq!(
+            descriptor(TextureDimension::D2, 1).view_dimension(),
+            TextureViewDimension::D2
+        );
+        assert_eq!(
+            descriptor(TextureDimension::D3, 4).view_dimension(),
+            TextureViewDimension::D3
+        );
+        let cube = TextureDescriptor {
+            view_dimension: Some(TextureViewDimension::Cube),
+            ..descriptor(TextureDimension::D2, 6)
+        };
+        assert_eq!(cube.view_dimension(), TextureViewDimension::Cube);
+    }
+}
@@ -150,9 +150,72 @@ pub enum TextureFormat {
     Depth32Float = 35,
     Depth24Plus = 36,
     Depth24PlusStencil8 = 37,
+
+    // BC compressed formats, require the TEXTURE_COMPRESSION_BC feature
+    Bc1RgbaUnorm = 38,
+    Bc1RgbaUnormSrgb = 39,
+    Bc2RgbaUnorm = 40,
+    Bc2RgbaUnormSrgb = 41,
+    Bc3RgbaUnorm = 42,
+    Bc3RgbaUnormSrgb = 43,
+    Bc4RUnorm = 44,
+    Bc4RSnorm = 45,
+    Bc5RgUnorm = 46,
+    Bc5RgSnorm = 47,
+    Bc6hRgbUfloat = 48,
+    Bc6hRgbSfloat = 49,
+    Bc7RgbaUnorm = 50,
+    Bc7RgbaUnormSrgb = 51,
+
+    // ETC2 and EAC compressed formats, require the TEXTURE_COMPRESSION_ETC2 feature
+    Etc2RgbUnorm = 52,
+    Etc2RgbUnormSrgb = 53,
+    Etc2RgbA1Unorm = 54,
+    Etc2RgbA1UnormSrgb = 55,
+    Etc2RgbA8Unorm = 56,
+    Etc2RgbA8UnormSrgb = 57,
+    EacRUnorm = 58,
+    EacRSnorm = 59,
+    EtcRgUnorm = 60,
+    EtcRgSnorm = 61,
+
+    // ASTC compressed formats, require the TEXTURE_COMPRESSION_ASTC_LDR feature
+    Astc4x4RgbaUnorm = 62,
+    Astc4x4RgbaUnormSrgb = 63,
+    Astc5x4RgbaUnorm = 64,
+    Astc5x4RgbaUnormSrgb = 65,
+    Astc5x5RgbaUnorm = 66,
+    Astc5x5RgbaUnormSrgb = 67,
+    Astc6x5RgbaUnorm = 68,
+    Astc6x5RgbaUnormSrgb = 69,
+    Astc6x6RgbaUnorm = 70,
+    Astc6x6RgbaUnormSrgb = 71,
+    Astc8x5RgbaUnorm = 72,
+    Astc8x5RgbaUnormSrgb = 73,
+    Astc8x6RgbaUnorm = 74,
+    Astc8x6RgbaUnormSrgb = 75,
+    Astc8x8RgbaUnorm = 76,
+    Astc8x8RgbaUnormSrgb = 77,
+    Astc10x5RgbaUnorm = 78,
+    Astc10x5RgbaUnormSrgb = 79,
+    Astc10x6RgbaUnorm = 80,
+    Astc10x6RgbaUnormSrgb = 81,
+    Astc10x8RgbaUnorm = 82,
+    Astc10x8RgbaUnormSrgb = 83,
+    Astc10x10RgbaUnorm = 84,
+    Astc10x10RgbaUnormSrgb = 85,
+    Astc12x10RgbaUnorm = 86,
+    Astc12x10RgbaUnormSrgb = 87,
+    Astc12x12RgbaUnorm = 88,
+    Astc12x12RgbaUnormSrgb = 89,
 }
 
 impl TextureFormat {
+    /// Returns true for depth formats that also store stencil values
+    pub fn has_stencil(&self) -> bool {
+        matches!(self, TextureFormat::Depth24PlusStencil8)
+    }
+
     pub fn pixel_info(&self) -> PixelInfo {
         let type_size = match self {
             // 8bit
@@ -200,6 +263,60 @@ impl TextureFormat {
             TextureFormat::Rg11b10Float => 4,
             TextureFormat::Depth24Plus => 3, // FIXME is this correct?
             TextureFormat::Depth24PlusStencil8 => 4,
+
+            // block compressed formats, the size of one block
+            TextureFormat::Bc1RgbaUnorm
+            | TextureFormat::Bc1RgbaUnormSrgb
+            | TextureFormat::Bc4RUnorm
+            | TextureFormat::Bc4RSnorm
+            | TextureFormat::Etc2RgbUnorm
+            | TextureFormat::Etc2RgbUnormSrgb
+            | TextureFormat::Etc2RgbA1Unorm
+            | TextureFormat::Etc2RgbA1UnormSrgb
+            | TextureFormat::EacRUnorm
+            | TextureFormat::EacRSnorm => 8,
+            TextureFormat::Bc2RgbaUnorm
+            | TextureFormat::Bc2RgbaUnormSrgb
+            | TextureFormat::Bc3RgbaUnorm
+            | TextureFormat::Bc3RgbaUnormSrgb
+            | TextureFormat::Bc5RgUnorm
+            | TextureFormat::Bc5RgSnorm
+            | TextureFormat::Bc6hRgbUfloat
+            | TextureFormat::Bc6hRgbSfloat
+            | TextureFormat::Bc7RgbaUnorm
+            | TextureFormat::Bc7RgbaUnormSrgb
+            | TextureFormat::Etc2RgbA8Unorm
+            | TextureFormat::Etc2RgbA8UnormSrgb
+            | TextureFormat::EtcRgUnorm
+            | TextureFormat::EtcRgSnorm
+            | TextureFormat::Astc4x4RgbaUnorm
+            | TextureFormat::Astc4x4RgbaUnormSrgb
+            | TextureFormat::Astc5x4RgbaUnorm
+            | TextureFormat::Astc5x4RgbaUnormSrgb
+            | TextureFormat::Astc5x5RgbaUnorm
+            | TextureFormat::Astc5x5RgbaUnormSrgb
+            | TextureFormat::Astc6x5RgbaUnorm
+            | TextureFormat::Astc6x5RgbaUnormSrgb
+            | TextureFormat::Astc6x6RgbaUnorm
+            | TextureFormat::Astc6x6RgbaUnormSrgb
+            | TextureFormat::Astc8x5RgbaUnorm
+            | TextureFormat::Astc8x5RgbaUnormSrgb
+            | TextureFormat::Astc8x6RgbaUnorm
+            | TextureFormat::Astc8x6RgbaUnormSrgb
+            | TextureFormat::Astc8x8RgbaUnorm
+            | TextureFormat::Astc8x8RgbaUnormSrgb
+            | TextureFormat::Astc10x5RgbaUnorm
+            | TextureFormat::Astc10x5RgbaUnormSrgb
+            | TextureFormat::Astc10x6RgbaUnorm
+            | TextureFormat::Astc10x6RgbaUnormSrgb
+            | TextureFormat::Astc10x8RgbaUnorm
+            | TextureFormat::Astc10x8RgbaUnormSrgb
+            | TextureFormat::Astc10x10RgbaUnorm
+            | TextureFormat::Astc10x10RgbaUnormSrgb
+            | TextureFormat::Astc12x10RgbaUnorm
+            | TextureFormat::Astc12x10RgbaUnormSrgb
+            | TextureFormat::Astc12x12RgbaUnorm
+            | TextureFormat::Astc12x12RgbaUnormSrgb => 16,
         };
 
         let components = match self {
@@ -245,6 +362,60 @@ impl TextureFormat {
             | TextureFormat::Depth32Float
             | TextureFormat::Depth24Plus
             | TextureFormat::Depth24PlusStencil8 => 1,
+
+            // block compressed formats
+            TextureFormat::Bc1RgbaUnorm
+            | TextureFormat::Bc1RgbaUnormSrgb
+            | TextureFormat::Bc2RgbaUnorm
+            | TextureFormat::Bc2RgbaUnormSrgb
+            | TextureFormat::Bc3RgbaUnorm
+            | TextureFormat::Bc3RgbaUnormSrgb
+            | TextureFormat::Bc4RUnorm
+            | TextureFormat::Bc4RSnorm
+            | TextureFormat::Bc5RgUnorm
+            | TextureFormat::Bc5RgSnorm
+            | TextureFormat::Bc6hRgbUfloat
+            | TextureFormat::Bc6hRgbSfloat
+            | TextureFormat::Bc7RgbaUnorm
+            | TextureFormat::Bc7RgbaUnormSrgb
+            | TextureFormat::Etc2RgbUnorm
+            | TextureFormat::Etc2RgbUnormSrgb
+            | TextureFormat::Etc2RgbA1Unorm
+            | TextureFormat::Etc2RgbA1UnormSrgb
+            | TextureFormat::Etc2RgbA8Unorm
+            | TextureFormat::Etc2RgbA8UnormSrgb
+            | TextureFormat::EacRUnorm
+            | TextureFormat::EacRSnorm
+            | TextureFormat::EtcRgUnorm
+            | TextureFormat::EtcRgSnorm
+            | TextureFormat::Astc4x4RgbaUnorm
+            | TextureFormat::Astc4x4RgbaUnormSrgb
+            | TextureFormat::Astc5x4RgbaUnorm
+            | TextureFormat::Astc5x4RgbaUnormSrgb
+            | TextureFormat::Astc5x5RgbaUnorm
+            | TextureFormat::Astc5x5RgbaUnormSrgb
+            | TextureFormat::Astc6x5RgbaUnorm
+            | TextureFormat::Astc6x5RgbaUnormSrgb
+            | TextureFormat::Astc6x6RgbaUnorm
+            | TextureFormat::Astc6x6RgbaUnormSrgb
+            | TextureFormat::Astc8x5RgbaUnorm
+            | TextureFormat::Astc8x5RgbaUnormSrgb
+            | TextureFormat::Astc8x6RgbaUnorm
+            | TextureFormat::Astc8x6RgbaUnormSrgb
+            | TextureFormat::Astc8x8RgbaUnorm
+            | TextureFormat::Astc8x8RgbaUnormSrgb
+            | TextureFormat::Astc10x5RgbaUnorm
+            | TextureFormat::Astc10x5RgbaUnormSrgb
+            | TextureFormat::Astc10x6RgbaUnorm
+            | TextureFormat::Astc10x6RgbaUnormSrgb
+            | TextureFormat::Astc10x8RgbaUnorm
+            | TextureFormat::Astc10x8RgbaUnormSrgb
+            | TextureFormat::Astc10x10RgbaUnorm
+            | TextureFormat::Astc10x10RgbaUnormSrgb
+            | TextureFormat::Astc12x10RgbaUnorm
+            | TextureFormat::Astc12x10RgbaUnormSrgb
+            | TextureFormat::Astc12x12RgbaUnorm
+            | TextureFormat::Astc12x12RgbaUnormSrgb => 1,
         };
 
         PixelInfo {
@@ -253,10 +424,116 @@ impl TextureFormat {
         }
     }
 
+    /// The size of one pixel, or of one block for block compressed formats
     pub fn pixel_size(&self) -> usize {
         let info = self.pixel_info();
         info.type_size * info.num_components
     }
+
+    /// The width and height in pixels of one block. Uncompressed formats have 1x1 blocks.
+    pub fn block_dimensions(&self) -> (u32, u32) {
+        match self {
+            TextureFormat::Astc5x4RgbaUnorm | TextureFormat::Astc5x4RgbaUnormSrgb => (5, 4),
+            TextureFormat::Astc5x5RgbaUnorm | TextureFormat::Astc5x5RgbaUnormSrgb => (5, 5),
+            TextureFormat::Astc6x5RgbaUnorm | TextureFormat::Astc6x5RgbaUnormSrgb => (6, 5),
+            TextureFormat::Astc6x6RgbaUnorm | TextureFormat::Astc6x6RgbaUnormSrgb => (6, 6),
+            TextureFormat::Astc8x5RgbaUnorm | TextureFormat::Astc8x5RgbaUnormSrgb => (8, 5),
+            TextureFormat::Astc8x6RgbaUnorm | TextureFormat::Astc8x6RgbaUnormSrgb => (8, 6),
+            TextureFormat::Astc8x8RgbaUnorm | TextureFormat::Astc8x8RgbaUnormSrgb => (8, 8),
+            TextureFormat::Astc10x5RgbaUnorm | TextureFormat::Astc10x5RgbaUnormSrgb => (10, 5),
+            TextureFormat::Astc10x6RgbaUnorm | TextureFormat::Astc10x6RgbaUnormSrgb => (10, 6),
+            TextureFormat::Astc10x8RgbaUnorm | TextureFormat::Astc10x8RgbaUnormSrgb => (10, 8),
+            TextureFormat::Astc10x10RgbaUnorm | TextureFormat::Astc10x10RgbaUnormSrgb => (10, 10),
+            TextureFormat::Astc12x10RgbaUnorm | TextureFormat::Astc12x10RgbaUnormSrgb => (12, 10),
+            TextureFormat::Astc12x12RgbaUnorm | TextureFormat::Astc12x12RgbaUnormSrgb => (12, 12),
+            _ if self.is_compressed() => (4, 4),
+            _ => (1, 1),
+        }
+    }
+
+    pub fn is_compressed(&self) -> bool {
+        !self.required_compression().is_empty()
+    }
+
+    /// The device feature needed to use this format
+    pub fn required_compression(&self) -> TextureCompression {
+        match self {
+            TextureFormat::Bc1RgbaUnorm
+            | TextureFormat::Bc1RgbaUnormSrgb
+            | TextureFormat::Bc2RgbaUnorm
+            | TextureFormat::Bc2RgbaUnormSrgb
+            | TextureFormat::Bc3RgbaUnorm
+            | TextureFormat::Bc3RgbaUnormSrgb
+            | TextureFormat::Bc4RUnorm
+            | TextureFormat::Bc4RSnorm
+            | TextureFormat::Bc5RgUnorm
+            | TextureFormat::Bc5RgSnorm
+            | TextureFormat::Bc6hRgbUfloat
+            | TextureFormat::Bc6hRgbSfloat
+            | TextureFormat::Bc7RgbaUnorm
+            | TextureFormat::Bc7RgbaUnormSrgb => TextureCompression::BC,
+            TextureFormat::Etc2RgbUnorm
+            | TextureFormat::Etc2RgbUnormSrgb
+            | TextureFormat::Etc2RgbA1Unorm
+            | TextureFormat::Etc2RgbA1UnormSrgb
+            | TextureFormat::Etc2RgbA8Unorm
+            | TextureFormat::Etc2RgbA8UnormSrgb
+            | TextureFormat::EacRUnorm
+            | TextureFormat::EacRSnorm
+            | TextureFormat::EtcRgUnorm
+            | TextureFormat::EtcRgSnorm => TextureCompression::ETC2,
+            TextureFormat::Astc4x4RgbaUnorm
+            | TextureFormat::Astc4x4RgbaUnormSrgb
+            | TextureFormat::Astc5x4RgbaUnorm
+            | TextureFormat::Astc5x4RgbaUnormSrgb
+            | TextureFormat::Astc5x5RgbaUnorm
+            | TextureFormat::Astc5x5RgbaUnormSrgb
+            | TextureFormat::Astc6x5RgbaUnorm
+            | TextureFormat::Astc6x5RgbaUnormSrgb
+            | TextureFormat::Astc6x6RgbaUnorm
+            | TextureFormat::Astc6x6RgbaUnormSrgb
+            | TextureFormat::Astc8x5RgbaUnorm
+            | TextureFormat::Astc8x5RgbaUnormSrgb
+            | TextureFormat::Astc8x6RgbaUnorm
+            | TextureFormat::Astc8x6RgbaUnormSrgb
+            | TextureFormat::Astc8x8RgbaUnorm
+            | TextureFormat::Astc8x8RgbaUnormSrgb
+            | TextureFormat::Astc10x5RgbaUnorm
+            | TextureFormat::Astc10x5RgbaUnormSrgb
+            | TextureFormat::Astc10x6RgbaUnorm
+            | TextureFormat::Astc10x6RgbaUnormSrgb
+            | TextureFormat::Astc10x8RgbaUnorm
+            | TextureFormat::Astc10x8RgbaUnormSrgb
+            | TextureFormat::Astc10x10RgbaUnorm
+            | TextureFormat::Astc10x10RgbaUnormSrgb
+            | TextureFormat::Astc12x10RgbaUnorm
+            | TextureFormat::Astc12x10RgbaUnormSrgb
+            | TextureFormat::Astc12x12RgbaUnorm
+            | TextureFormat::Astc12x12RgbaUnormSrgb => TextureCompression::ASTC_LDR,
+            _ => TextureCompression::empty(),
+        }
+    }
+
+    /// The number of bytes taken by a single mip level of this format with the given size
+    pub fn data_size(&self, size: Extent3d) -> usize {
+        let (block_width, block_height) = self.block_dimensions();
+        let blocks_wide = (size.width + block_width - 1) / block_width;
+        let blocks_high = (size.height + block_height - 1) / block_height;
+        blocks_wide as usize * blocks_high as usize * size.depth as usize * self.pixel_size()
+    }
+
+    /// Like [data_size](TextureFormat::data_size), but returns `None` if the size doesn't fit in
+    /// a `usize`. Use it for sizes read from untrusted files.
+    pub fn checked_data_size(&self, size: Extent3d) -> Option<usize> {
+        let (block_width, block_height) = self.block_dimensions();
+        let blocks_wide = (size.width as usize + block_width as usize - 1) / block_width as usize;
+        let blocks_high =
+            (size.height as usize + block_height as usize - 1) / block_height as usize;
+        blocks_wide
+            .checked_mul(blocks_high)?
+            .checked_mul(size.depth as usize)?
+            .checked_mul(self.pixel_size())
+    }
 }
 
 impl Default for TextureFormat {
@@ -270,6 +547,16 @@ impl Default for TextureFormat {
     }
 }
 
+bitflags::bitflags! {
+    /// Families of block compressed [TextureFormat]s. Each is an optional device feature.
+    #[repr(transparent)]
+    pub struct TextureCompression: u32 {
+        const BC = 1;
+        const ETC2 = 2;
+        const ASTC_LDR = 4;
+    }
+}
+
 bitflags::bitflags! {
     #[repr(transparent)]
     pub struct TextureUsage: u32 {
@@ -0,0 +1,93 @@
+use bevy_ecs::reflect::ReflectComponent;
+use bevy_reflect::Reflect;
+
+/// The settings of a physical camera, from which an [Exposure] can be computed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PhysicalCameraParameters {
+    /// The f-number of the aperture, such as 1.8 for f/1.8
+    pub aperture_f_stops: f32,
+    /// The time the sensor is exposed for, in seconds
+    pub shutter_speed_s: f32,
+    /// The sensitivity of the sensor in ISO
+    pub sensitivity_iso: f32,
+}
+
+impl PhysicalCameraParameters {
+    /// The exposure value at ISO 100 of these settings
+    pub fn ev100(&self) -> f32 {
+        (self.aperture_f_stops * self.aperture_f_stops * 100.0
+            / (self.shutter_speed_s * self.sensitivity_iso))
+            .log2()
+    }
+}
+
+impl Default for PhysicalCameraParameters {
+    fn default() -> Self {
+        PhysicalCameraParameters {
+            aperture_f_stops: 1.0,
+            shutter_speed_s: 1.0 / 125.0,
+            sensitivity_iso: 100.0,
+        }
+    }
+}
+
+/// Scales the light reaching the camera before tonemapping, so scenes can be lit with physical
+/// light intensities, from a dim room to direct sunlight. Higher exposure values let less light
+/// through. Applied by the [TonemappingNode](super::TonemappingNode) to the
+/// [main_pass_camera](crate::render_graph::base::main_pass_camera), whose colors are left as they
+/// are if it doesn't have this component.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct Exposure {
+    /// The exposure value at ISO 100
+    pub ev100: f32,
+}
+
+impl Exposure {
+    pub const SUNLIGHT: Exposure = Exposure::from_ev100(15.0);
+    pub const OVERCAST: Exposure = Exposure::from_ev100(12.0);
+    pub const INDOOR: Exposure = Exposure::from_ev100(7.0);
+
+    pub const fn from_ev100(ev100: f32) -> Self {
+        Exposure { ev100 }
+    }
+
+    pub fn from_physical_camera(parameters: PhysicalCameraParameters) -> Self {
+        Exposure::from_ev100(parameters.ev100())
+    }
+
+    /// The factor colors are multiplied by: the inverse of the luminance that saturates the
+    /// sensor, as defined by the standard output sensitivity
+    pub fn exposure(&self) -> f32 {
+        (-self.ev100).exp2() / 1.2
+    }
+}
+
+impl Default for Exposure {
+    fn default() -> Self {
+        Exposure::from_physical_camera(PhysicalCameraParameters::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn physical_camera_exposure() {
+        // f/16, 1/100s and ISO 100 is the "sunny 16" rule
+        let sunny_16 = Exposure::from_physical_camera(PhysicalCameraParameters {
+            aperture_f_stops: 16.0,
+            shutter_speed_s: 1.0 / 100.0,
+            sensitivity_iso: 100.0,
+        });
+        assert!((sunny_16.ev100 - 14.64).abs() < 0.01);
+        // doubling the sensitivity lets twice as much light through
+        let doubled = Exposure::from_physical_camera(PhysicalCameraParameters {
+            sensitivity_iso: 200.0,
+            ..Default::default()
+        });
+        let ratio = doubled.exposure() / Exposure::default().exposure();
+        assert!((ratio - 2.0).abs() < 1e-4);
+    }
+}
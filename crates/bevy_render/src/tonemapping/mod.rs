@@ -0,0 +1,70 @@
+use crate::{
+    pass::FullscreenPass,
+    pipeline::PipelineDescriptor,
+    shader::{Shader, ShaderStage},
+};
+use bevy_asset::{Assets, HandleUntyped};
+use bevy_ecs::reflect::ReflectComponent;
+use bevy_reflect::{Reflect, ReflectDeserialize, TypeUuid};
+use serde::{Deserialize, Serialize};
+
+mod exposure;
+mod tonemapping_node;
+
+pub use exposure::*;
+pub use tonemapping_node::*;
+
+pub const TONEMAPPING_PIPELINE_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(PipelineDescriptor::TYPE_UUID, 0x7d3e19c4a5f0b268);
+
+/// The curve used to map HDR colors to the displayable 0.0 - 1.0 range
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+#[reflect_value(PartialEq, Serialize, Deserialize)]
+pub enum TonemappingOperator {
+    /// Clips colors brighter than 1.0
+    None,
+    /// Reinhard applied to the luminance, which compresses highlights without shifting hues
+    Reinhard,
+    /// An approximation of the ACES filmic curve, with more contrast than
+    /// [Reinhard](Self::Reinhard)
+    Aces,
+}
+
+impl TonemappingOperator {
+    pub(crate) fn shader_def(&self) -> Option<&'static str> {
+        match self {
+            TonemappingOperator::None => None,
+            TonemappingOperator::Reinhard => Some("TONEMAPPING_REINHARD"),
+            TonemappingOperator::Aces => Some("TONEMAPPING_ACES"),
+        }
+    }
+}
+
+impl Default for TonemappingOperator {
+    fn default() -> Self {
+        TonemappingOperator::Reinhard
+    }
+}
+
+/// Configures the tonemapping pass that maps the main pass to the swap chain when
+/// [Hdr](crate::render_graph::base::Hdr) is enabled. It is read from the
+/// [main_pass_camera](crate::render_graph::base::main_pass_camera), which uses the defaults if it
+/// doesn't have this component.
+#[derive(Debug, Clone, Default, Reflect)]
+#[reflect(Component)]
+pub struct Tonemapping {
+    pub operator: TonemappingOperator,
+    /// Exposure compensation in stops: colors are multiplied by `2^exposure` before tonemapping,
+    /// on top of the [Exposure] of the camera
+    pub exposure: f32,
+}
+
+pub(crate) fn build_tonemapping_pipeline(shaders: &mut Assets<Shader>) -> PipelineDescriptor {
+    PipelineDescriptor {
+        name: Some("tonemapping".into()),
+        ..FullscreenPass::pipeline_descriptor(shaders.add(Shader::from_glsl(
+            ShaderStage::Fragment,
+            include_str!("tonemapping.frag"),
+        )))
+    }
+}
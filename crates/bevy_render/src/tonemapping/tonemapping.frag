@@ -0,0 +1,37 @@
+#version 450
+
+layout(location = 0) in vec2 v_Uv;
+
+layout(location = 0) out vec4 o_Target;
+
+layout(set = 0, binding = 0) uniform texture2D HdrTexture;
+layout(set = 0, binding = 1) uniform sampler HdrTexture_sampler;
+
+layout(set = 0, binding = 2) uniform Tonemapping {
+    float Exposure;
+};
+
+// Krzysztof Narkowicz's fit of the ACES filmic curve
+// https://knarkowicz.wordpress.com/2016/01/06/aces-filmic-tone-mapping-curve/
+vec3 aces(vec3 color) {
+    return clamp((color * (2.51 * color + 0.03)) / (color * (2.43 * color + 0.59) + 0.14), 0.0, 1.0);
+}
+
+// reinhard on RGB oversaturates colors, so it is applied to the luminance
+vec3 reinhard_luminance(vec3 color) {
+    float l_old = dot(color, vec3(0.2126, 0.7152, 0.0722));
+    float l_new = l_old / (1.0 + l_old);
+    return color * (l_new / max(l_old, 0.0001));
+}
+
+void main() {
+    vec4 hdr = texture(sampler2D(HdrTexture, HdrTexture_sampler), v_Uv);
+    vec3 color = hdr.rgb * Exposure;
+#if defined(TONEMAPPING_REINHARD)
+    color = reinhard_luminance(color);
+#elif defined(TONEMAPPING_ACES)
+    color = aces(color);
+#endif
+    // the swap chain is srgb, so no gamma correction is needed
+    o_Target = vec4(color, hdr.a);
+}
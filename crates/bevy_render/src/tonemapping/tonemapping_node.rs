@@ -0,0 +1,93 @@
+use super::{Exposure, Tonemapping, TONEMAPPING_PIPELINE_HANDLE};
+use crate::{
+    camera::ActiveCameras,
+    pass::{FullscreenPass, LoadOp, Operations},
+    render_graph::{base::main_pass_camera, Node, ResourceSlotInfo, ResourceSlots},
+    renderer::{RenderContext, RenderResourceType},
+    Color,
+};
+use bevy_core::AsBytes;
+use bevy_ecs::world::World;
+use std::borrow::Cow;
+
+/// Maps the HDR main pass texture to the swap chain, as configured by the [Tonemapping] and
+/// [Exposure] components of the active camera
+pub struct TonemappingNode {
+    pass: FullscreenPass,
+    exposure: f32,
+}
+
+impl TonemappingNode {
+    pub const IN_HDR_TEXTURE: &'static str = "hdr_texture";
+    pub const IN_TARGET: &'static str = "target";
+}
+
+impl Default for TonemappingNode {
+    fn default() -> Self {
+        TonemappingNode {
+            pass: FullscreenPass::new(
+                TONEMAPPING_PIPELINE_HANDLE.typed(),
+                Operations {
+                    load: LoadOp::Clear(Color::BLACK),
+                    store: true,
+                },
+            ),
+            exposure: 1.0,
+        }
+    }
+}
+
+impl Node for TonemappingNode {
+    fn input(&self) -> &[ResourceSlotInfo] {
+        static INPUT: &[ResourceSlotInfo] = &[
+            ResourceSlotInfo {
+                name: Cow::Borrowed(TonemappingNode::IN_HDR_TEXTURE),
+                resource_type: RenderResourceType::Texture,
+            },
+            ResourceSlotInfo {
+                name: Cow::Borrowed(TonemappingNode::IN_TARGET),
+                resource_type: RenderResourceType::Texture,
+            },
+        ];
+        INPUT
+    }
+
+    fn prepare(&mut self, world: &mut World) {
+        let active_cameras = world.get_resource::<ActiveCameras>().unwrap();
+        let camera = main_pass_camera(&active_cameras);
+        let tonemapping = camera
+            .and_then(|entity| world.get::<Tonemapping>(entity))
+            .cloned()
+            .unwrap_or_default();
+        let camera_exposure = camera
+            .and_then(|entity| world.get::<Exposure>(entity))
+            .map_or(1.0, |exposure| exposure.exposure());
+
+        let shader_defs = &mut self.pass.specialization.shader_specialization.shader_defs;
+        shader_defs.clear();
+        if let Some(shader_def) = tonemapping.operator.shader_def() {
+            shader_defs.insert(shader_def.to_string());
+        }
+        self.exposure = camera_exposure * 2.0f32.powf(tonemapping.exposure);
+        self.pass.prepare(world);
+    }
+
+    fn update(
+        &mut self,
+        world: &World,
+        render_context: &mut dyn RenderContext,
+        input: &ResourceSlots,
+        _output: &mut ResourceSlots,
+    ) {
+        const IN_HDR_TEXTURE: usize = 0;
+        const IN_TARGET: usize = 1;
+        let hdr_texture = input.get(IN_HDR_TEXTURE).unwrap().get_texture().unwrap();
+        let target = input.get(IN_TARGET).unwrap().get_texture().unwrap();
+
+        self.pass
+            .set_texture(render_context.resources(), "HdrTexture", hdr_texture);
+        self.pass
+            .set_uniform(render_context, "Tonemapping", self.exposure.as_bytes());
+        self.pass.draw(world, render_context, target);
+    }
+}
@@ -58,6 +58,8 @@ impl Default for WireframeConfig {
 pub fn draw_wireframes_system(
     mut draw_context: DrawContext,
     msaa: Res<Msaa>,
+    hdr: Res<Hdr>,
+    depth: Res<Depth>,
     meshes: Res<Assets<Mesh>>,
     wireframe_config: Res<WireframeConfig>,
     mut query: QuerySet<(
@@ -95,6 +97,10 @@ pub fn draw_wireframes_system(
                     .map(|name| name.to_string())
                     .collect::<HashSet<String>>(),
                 vertex_buffer_layout: mesh.get_vertex_buffer_layout(),
+                color_target_format: hdr.main_pass_format(),
+                blend_mode: None,
+                depth_format: Some(depth.format),
+                reversed_z: depth.reversed_z,
             },
         );
         render_pipeline.dynamic_bindings_generation =
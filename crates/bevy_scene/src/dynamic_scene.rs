@@ -1,6 +1,8 @@
 use crate::{serde::SceneSerializer, Scene, SceneSpawnError};
 use anyhow::Result;
 use bevy_ecs::{
+    archetype::Archetype,
+    component::ComponentId,
     entity::EntityMap,
     reflect::{ReflectComponent, ReflectMapEntities},
     world::World,
@@ -25,9 +27,23 @@ impl DynamicScene {
     }
 
     pub fn from_world(world: &World, type_registry: &TypeRegistryArc) -> Self {
+        Self::from_world_filtered(world, type_registry, |_| true, |_| true)
+    }
+
+    /// Creates a scene from the entities of the archetypes matching `archetype_filter`, with
+    /// their registered components matching `component_filter`
+    pub(crate) fn from_world_filtered(
+        world: &World,
+        type_registry: &TypeRegistryArc,
+        archetype_filter: impl Fn(&Archetype) -> bool,
+        component_filter: impl Fn(ComponentId) -> bool,
+    ) -> Self {
         let mut scene = DynamicScene::default();
         let type_registry = type_registry.read();
         for archetype in world.archetypes().iter() {
+            if !archetype_filter(archetype) {
+                continue;
+            }
             let entities_offset = scene.entities.len();
             for entity in archetype.entities() {
                 scene.entities.push(Entity {
@@ -37,6 +53,9 @@ impl DynamicScene {
             }
 
             for component_id in archetype.components() {
+                if !component_filter(component_id) {
+                    continue;
+                }
                 let reflect_component = world
                     .components()
                     .get_info(component_id)
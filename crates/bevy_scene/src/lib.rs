@@ -4,16 +4,19 @@ mod scene;
 mod scene_loader;
 mod scene_spawner;
 pub mod serde;
+mod snapshot;
 
 pub use command::*;
 pub use dynamic_scene::*;
 pub use scene::*;
 pub use scene_loader::*;
 pub use scene_spawner::*;
+pub use snapshot::*;
 
 pub mod prelude {
     pub use crate::{
-        DynamicScene, Scene, SceneSpawner, SpawnSceneAsChildCommands, SpawnSceneCommands,
+        DynamicScene, Scene, SceneSpawner, SnapshotFilter, SnapshotFormat,
+        SpawnSceneAsChildCommands, SpawnSceneCommands, WorldSnapshot,
     };
 }
 
@@ -1,4 +1,4 @@
-use crate::{DynamicScene, Entity};
+use crate::{DynamicScene, Entity, WorldSnapshot, SNAPSHOT_VERSION};
 use anyhow::Result;
 use bevy_reflect::{
     serde::{ReflectDeserializer, ReflectSerializer},
@@ -249,3 +249,135 @@ impl<'a, 'de> Visitor<'de> for ComponentSeqVisitor<'a> {
         Ok(dynamic_properties)
     }
 }
+
+pub struct SnapshotSerializer<'a> {
+    pub snapshot: &'a WorldSnapshot,
+    pub registry: &'a TypeRegistryArc,
+}
+
+impl<'a> SnapshotSerializer<'a> {
+    pub fn new(snapshot: &'a WorldSnapshot, registry: &'a TypeRegistryArc) -> Self {
+        SnapshotSerializer { snapshot, registry }
+    }
+}
+
+impl<'a> Serialize for SnapshotSerializer<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct(SNAPSHOT_STRUCT, 3)?;
+        state.serialize_field(SNAPSHOT_FIELD_VERSION, &SNAPSHOT_VERSION)?;
+        state.serialize_field(
+            SNAPSHOT_FIELD_ENTITIES,
+            &SceneSerializer::new(&self.snapshot.scene, self.registry),
+        )?;
+        state.serialize_field(
+            SNAPSHOT_FIELD_RESOURCES,
+            &ComponentsSerializer {
+                components: &self.snapshot.resources,
+                registry: self.registry,
+            },
+        )?;
+        state.end()
+    }
+}
+
+pub struct SnapshotDeserializer<'a> {
+    pub type_registry: &'a TypeRegistry,
+}
+
+impl<'a, 'de> DeserializeSeed<'de> for SnapshotDeserializer<'a> {
+    type Value = WorldSnapshot;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_struct(
+            SNAPSHOT_STRUCT,
+            &[
+                SNAPSHOT_FIELD_VERSION,
+                SNAPSHOT_FIELD_ENTITIES,
+                SNAPSHOT_FIELD_RESOURCES,
+            ],
+            SnapshotVisitor {
+                type_registry: self.type_registry,
+            },
+        )
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "lowercase")]
+enum SnapshotField {
+    Version,
+    Entities,
+    Resources,
+}
+
+pub const SNAPSHOT_STRUCT: &str = "Snapshot";
+pub const SNAPSHOT_FIELD_VERSION: &str = "version";
+pub const SNAPSHOT_FIELD_ENTITIES: &str = "entities";
+pub const SNAPSHOT_FIELD_RESOURCES: &str = "resources";
+
+struct SnapshotVisitor<'a> {
+    pub type_registry: &'a TypeRegistry,
+}
+
+impl<'a, 'de> Visitor<'de> for SnapshotVisitor<'a> {
+    type Value = WorldSnapshot;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("snapshot")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut version = None;
+        let mut scene = None;
+        let mut resources = None;
+        while let Some(key) = map.next_key()? {
+            match key {
+                SnapshotField::Version => {
+                    if version.is_some() {
+                        return Err(Error::duplicate_field(SNAPSHOT_FIELD_VERSION));
+                    }
+                    let snapshot_version = map.next_value::<u32>()?;
+                    // the fields after the version can't be read with the format of a newer one
+                    if snapshot_version > SNAPSHOT_VERSION {
+                        return Err(Error::custom(format_args!(
+                            "unsupported snapshot version {}, the latest supported is {}",
+                            snapshot_version, SNAPSHOT_VERSION
+                        )));
+                    }
+                    version = Some(snapshot_version);
+                }
+                SnapshotField::Entities => {
+                    if scene.is_some() {
+                        return Err(Error::duplicate_field(SNAPSHOT_FIELD_ENTITIES));
+                    }
+                    scene = Some(map.next_value_seed(SceneDeserializer {
+                        type_registry: self.type_registry,
+                    })?);
+                }
+                SnapshotField::Resources => {
+                    if resources.is_some() {
+                        return Err(Error::duplicate_field(SNAPSHOT_FIELD_RESOURCES));
+                    }
+                    resources = Some(map.next_value_seed(ComponentVecDeserializer {
+                        registry: self.type_registry,
+                    })?);
+                }
+            }
+        }
+
+        version.ok_or_else(|| Error::missing_field(SNAPSHOT_FIELD_VERSION))?;
+        Ok(WorldSnapshot {
+            scene: scene.ok_or_else(|| Error::missing_field(SNAPSHOT_FIELD_ENTITIES))?,
+            resources: resources.ok_or_else(|| Error::missing_field(SNAPSHOT_FIELD_RESOURCES))?,
+        })
+    }
+}
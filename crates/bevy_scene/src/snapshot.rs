@@ -0,0 +1,296 @@
+use crate::{
+    serde::{SnapshotDeserializer, SnapshotSerializer},
+    serialize_ron, DynamicScene, SceneSpawnError,
+};
+use bevy_ecs::{component::Component, entity::EntityMap, reflect::ReflectResource, world::World};
+use bevy_reflect::{Reflect, TypeRegistryArc};
+use serde::{de::DeserializeSeed, Serialize};
+use std::{any::TypeId, fs, io, path::Path};
+use thiserror::Error;
+
+/// The version of the snapshot format written by this version of Bevy. Snapshots written by newer
+/// versions fail to load.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// The first bytes of the snapshots saved in [SnapshotFormat::Binary]
+const BINARY_SNAPSHOT_MAGIC: &[u8] = b"BEVYSNAP";
+
+#[derive(Error, Debug)]
+pub enum SnapshotError {
+    #[error("failed to read or write the snapshot: {0}")]
+    Io(#[from] io::Error),
+    #[error("invalid RON snapshot: {0}")]
+    Ron(#[from] ron::Error),
+    #[error("failed to encode the binary snapshot: {0}")]
+    Encode(#[from] rmp_serde::encode::Error),
+    #[error("invalid binary snapshot: {0}")]
+    Decode(#[from] rmp_serde::decode::Error),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotFormat {
+    /// Human readable, and close to the scene format
+    Ron,
+    /// Smaller and faster to read and write ([MessagePack](https://msgpack.org))
+    Binary,
+}
+
+/// Selects the entities, components and resources saved in a [WorldSnapshot]
+#[derive(Debug, Clone, Default)]
+pub struct SnapshotFilter {
+    required_components: Vec<TypeId>,
+    excluded_components: Vec<TypeId>,
+    resources: Vec<(TypeId, &'static str)>,
+}
+
+impl SnapshotFilter {
+    /// Only saves the entities with a `T` component, such as a marker of the saved entities. All
+    /// the entities are saved when no component is required.
+    pub fn with<T: Component>(mut self) -> Self {
+        self.required_components.push(TypeId::of::<T>());
+        self
+    }
+
+    /// Doesn't save the `T` components, such as the ones rebuilt from the others when loading
+    pub fn without_component<T: Component>(mut self) -> Self {
+        self.excluded_components.push(TypeId::of::<T>());
+        self
+    }
+
+    /// Saves the `T` resource, whose type must be registered with `#[reflect(Resource)]`
+    pub fn with_resource<T: Component>(mut self) -> Self {
+        self.resources
+            .push((TypeId::of::<T>(), std::any::type_name::<T>()));
+        self
+    }
+}
+
+/// The registered components of the entities of a [World], and some of its resources, to save a
+/// game and restore it later.
+///
+/// Entities are saved with their ids, and restored as new entities: [WorldSnapshot::write_to_world]
+/// fills an [EntityMap] from the saved ids to the new entities, and maps the entities referenced
+/// by the components implementing `MapEntities`. Entities referenced by resources aren't mapped.
+#[derive(Default)]
+pub struct WorldSnapshot {
+    pub scene: DynamicScene,
+    pub resources: Vec<Box<dyn Reflect>>,
+}
+
+impl WorldSnapshot {
+    /// Saves the registered components of every entity, without any resource
+    pub fn from_world(world: &World, type_registry: &TypeRegistryArc) -> Self {
+        WorldSnapshot {
+            scene: DynamicScene::from_world(world, type_registry),
+            resources: Vec::new(),
+        }
+    }
+
+    pub fn from_world_filtered(
+        world: &World,
+        type_registry: &TypeRegistryArc,
+        filter: &SnapshotFilter,
+    ) -> Result<Self, SceneSpawnError> {
+        let components = world.components();
+        let required_components = filter
+            .required_components
+            .iter()
+            .map(|type_id| components.get_id(*type_id))
+            .collect::<Option<Vec<_>>>();
+        let required_components = match required_components {
+            Some(required_components) => required_components,
+            // no entity has a component that was never added
+            None => return Ok(WorldSnapshot::default()),
+        };
+        let excluded_components = filter
+            .excluded_components
+            .iter()
+            .filter_map(|type_id| components.get_id(*type_id))
+            .collect::<Vec<_>>();
+        let scene = DynamicScene::from_world_filtered(
+            world,
+            type_registry,
+            |archetype| {
+                required_components
+                    .iter()
+                    .all(|component_id| archetype.contains(*component_id))
+            },
+            |component_id| !excluded_components.contains(&component_id),
+        );
+
+        let type_registry = type_registry.read();
+        let mut resources = Vec::new();
+        for (type_id, type_name) in filter.resources.iter() {
+            let reflect_resource = type_registry
+                .get(*type_id)
+                .and_then(|registration| registration.data::<ReflectResource>())
+                .ok_or_else(|| SceneSpawnError::UnregisteredResource {
+                    type_name: type_name.to_string(),
+                })?;
+            if let Some(resource) = reflect_resource.reflect_resource(world) {
+                resources.push(resource.clone_value());
+            }
+        }
+
+        Ok(WorldSnapshot { scene, resources })
+    }
+
+    /// Spawns the saved entities, or updates the ones they are already mapped to in
+    /// `entity_map`, and inserts the saved resources
+    pub fn write_to_world(
+        &self,
+        world: &mut World,
+        entity_map: &mut EntityMap,
+    ) -> Result<(), SceneSpawnError> {
+        self.scene.write_to_world(world, entity_map)?;
+
+        let registry = world.get_resource::<TypeRegistryArc>().unwrap().clone();
+        let type_registry = registry.read();
+        for resource in self.resources.iter() {
+            let reflect_resource = type_registry
+                .get_with_name(resource.type_name())
+                .and_then(|registration| registration.data::<ReflectResource>())
+                .ok_or_else(|| SceneSpawnError::UnregisteredResource {
+                    type_name: resource.type_name().to_string(),
+                })?;
+            if reflect_resource.reflect_resource(world).is_some() {
+                reflect_resource.apply_resource(world, &**resource);
+            } else {
+                reflect_resource.insert_resource(world, &**resource);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn serialize(
+        &self,
+        format: SnapshotFormat,
+        registry: &TypeRegistryArc,
+    ) -> Result<Vec<u8>, SnapshotError> {
+        let serializer = SnapshotSerializer::new(self, registry);
+        match format {
+            SnapshotFormat::Ron => Ok(serialize_ron(serializer)?.into_bytes()),
+            SnapshotFormat::Binary => {
+                let mut bytes = BINARY_SNAPSHOT_MAGIC.to_vec();
+                serializer
+                    .serialize(&mut rmp_serde::Serializer::new(&mut bytes).with_struct_map())?;
+                Ok(bytes)
+            }
+        }
+    }
+
+    /// Reads a snapshot in either format
+    pub fn deserialize(bytes: &[u8], registry: &TypeRegistryArc) -> Result<Self, SnapshotError> {
+        let type_registry = registry.read();
+        let snapshot_deserializer = SnapshotDeserializer {
+            type_registry: &*type_registry,
+        };
+        if let Some(bytes) = bytes.strip_prefix(BINARY_SNAPSHOT_MAGIC) {
+            let mut deserializer = rmp_serde::Deserializer::new(bytes);
+            Ok(snapshot_deserializer.deserialize(&mut deserializer)?)
+        } else {
+            let mut deserializer = ron::de::Deserializer::from_bytes(bytes)?;
+            Ok(snapshot_deserializer.deserialize(&mut deserializer)?)
+        }
+    }
+
+    pub fn save(
+        &self,
+        path: impl AsRef<Path>,
+        format: SnapshotFormat,
+        registry: &TypeRegistryArc,
+    ) -> Result<(), SnapshotError> {
+        fs::write(path, self.serialize(format, registry)?)?;
+        Ok(())
+    }
+
+    pub fn load(path: impl AsRef<Path>, registry: &TypeRegistryArc) -> Result<Self, SnapshotError> {
+        Self::deserialize(&fs::read(path)?, registry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_ecs::reflect::ReflectComponent;
+
+    #[derive(Reflect, Default, Debug, PartialEq)]
+    #[reflect(Component)]
+    struct Health(u32);
+
+    #[derive(Reflect, Default, Debug, PartialEq)]
+    #[reflect(Component)]
+    struct Saved;
+
+    #[derive(Reflect, Default, Debug, PartialEq)]
+    #[reflect(Resource)]
+    struct Score {
+        points: u32,
+    }
+
+    fn world() -> World {
+        let registry = TypeRegistryArc::default();
+        {
+            let mut registry = registry.write();
+            registry.register::<u32>();
+            registry.register::<Health>();
+            registry.register::<Saved>();
+            registry.register::<Score>();
+        }
+        let mut world = World::new();
+        world.insert_resource(registry);
+        world
+    }
+
+    #[test]
+    fn saves_and_restores_filtered_entities_and_resources() {
+        let mut world = world();
+        world.spawn().insert_bundle((Health(3), Saved));
+        world.spawn().insert(Health(5));
+        world.insert_resource(Score { points: 12 });
+        let registry = world.get_resource::<TypeRegistryArc>().unwrap().clone();
+        let filter = SnapshotFilter::default()
+            .with::<Saved>()
+            .with_resource::<Score>();
+        let snapshot = WorldSnapshot::from_world_filtered(&world, &registry, &filter).unwrap();
+        assert_eq!(snapshot.scene.entities.len(), 1);
+
+        for format in [SnapshotFormat::Ron, SnapshotFormat::Binary].iter() {
+            let bytes = snapshot.serialize(*format, &registry).unwrap();
+            let loaded = WorldSnapshot::deserialize(&bytes, &registry).unwrap();
+
+            let mut restored = self::world();
+            restored.insert_resource(Score { points: 0 });
+            let mut entity_map = EntityMap::default();
+            loaded
+                .write_to_world(&mut restored, &mut entity_map)
+                .unwrap();
+            let mut query = restored.query::<(&Health, &Saved)>();
+            assert_eq!(
+                query.iter(&restored).collect::<Vec<_>>(),
+                vec![(&Health(3), &Saved)]
+            );
+            assert_eq!(
+                restored.get_resource::<Score>(),
+                Some(&Score { points: 12 })
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_newer_versions_and_unregistered_resources() {
+        let world = world();
+        let registry = world.get_resource::<TypeRegistryArc>().unwrap().clone();
+        let ron = format!(
+            "(version: {}, entities: [], resources: [])",
+            SNAPSHOT_VERSION + 1
+        );
+        assert!(WorldSnapshot::deserialize(ron.as_bytes(), &registry).is_err());
+
+        let filter = SnapshotFilter::default().with_resource::<String>();
+        assert!(matches!(
+            WorldSnapshot::from_world_filtered(&world, &registry, &filter),
+            Err(SceneSpawnError::UnregisteredResource { .. })
+        ));
+    }
+}
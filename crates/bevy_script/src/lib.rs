@@ -0,0 +1,85 @@
+mod runtime;
+mod script;
+mod world_api;
+
+pub mod prelude {
+    pub use crate::{AddScriptSystem, Script, ScriptPlugin};
+}
+
+pub use runtime::*;
+pub use script::*;
+pub use world_api::{apply_lua, reflect_to_lua};
+
+use bevy_app::prelude::*;
+use bevy_asset::{AddAsset, AssetPath, AssetServer};
+use bevy_ecs::{
+    schedule::StageLabel,
+    system::{IntoExclusiveSystem, IntoSystem},
+    world::World,
+};
+
+/// Adds support for systems written as Lua scripts to an App.
+///
+/// A script defines an `update(world)` function, called each time its system runs. The `world`
+/// table accesses the registered components and resources through reflection, naming types by
+/// their short or full name and entities by integers:
+///
+/// ```lua
+/// function update(world)
+///     for _, entity in ipairs(world.query({"Transform", "Spinning"})) do
+///         local transform = world.get(entity, "Transform")
+///         transform.translation.x = transform.translation.x + world.delta_seconds()
+///         world.set(entity, "Transform", transform)
+///     end
+/// end
+/// ```
+///
+/// It also has `spawn()`, `despawn(entity)`, `remove(entity, name)`, `resource(name)` and
+/// `set_resource(name, value)`. Components and resources need `#[reflect(Component)]` or
+/// `#[reflect(Resource)]`. Scripts are reloaded when their file changes, with asset hot reloading
+/// enabled.
+#[derive(Default)]
+pub struct ScriptPlugin;
+
+impl Plugin for ScriptPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_asset::<Script>()
+            .init_asset_loader::<ScriptLoader>()
+            .init_non_send_resource::<ScriptRuntime>()
+            .add_system_to_stage(CoreStage::PreUpdate, script_reload_system.system());
+    }
+}
+
+/// Adds systems running Lua [Script]s, loaded through the [AssetServer]
+pub trait AddScriptSystem {
+    /// Runs the script in [CoreStage::Update]
+    fn add_script_system<'a>(&mut self, path: impl Into<AssetPath<'a>>) -> &mut Self;
+
+    fn add_script_system_to_stage<'a>(
+        &mut self,
+        stage_label: impl StageLabel,
+        path: impl Into<AssetPath<'a>>,
+    ) -> &mut Self;
+}
+
+impl AddScriptSystem for AppBuilder {
+    fn add_script_system<'a>(&mut self, path: impl Into<AssetPath<'a>>) -> &mut Self {
+        self.add_script_system_to_stage(CoreStage::Update, path)
+    }
+
+    fn add_script_system_to_stage<'a>(
+        &mut self,
+        stage_label: impl StageLabel,
+        path: impl Into<AssetPath<'a>>,
+    ) -> &mut Self {
+        let script = self
+            .world()
+            .get_resource::<AssetServer>()
+            .expect("the ScriptPlugin needs the AssetPlugin")
+            .load(path);
+        self.add_system_to_stage(
+            stage_label,
+            (move |world: &mut World| run_script(world, &script)).exclusive_system(),
+        )
+    }
+}
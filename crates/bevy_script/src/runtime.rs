@@ -0,0 +1,113 @@
+use crate::{world_api::create_world_table, Script};
+use bevy_app::EventReader;
+use bevy_asset::{AssetEvent, Assets, Handle, HandleId};
+use bevy_ecs::{system::NonSendMut, world::World};
+use bevy_reflect::TypeRegistryArc;
+use bevy_utils::{tracing::warn, HashMap};
+use mlua::{Lua, RegistryKey, Table, Value};
+use std::cell::RefCell;
+
+/// The Lua state running the scripts, stored as a non-send resource by the
+/// [ScriptPlugin](crate::ScriptPlugin).
+///
+/// Each script runs in its own environment, created by loading the script the first time it
+/// runs and again each time it is modified, so that the globals of a script don't leak into the
+/// others. The environments fall back to the shared globals, such as the Lua standard library.
+pub struct ScriptRuntime {
+    lua: Lua,
+    environments: HashMap<HandleId, RegistryKey>,
+}
+
+impl Default for ScriptRuntime {
+    fn default() -> Self {
+        ScriptRuntime {
+            lua: Lua::new(),
+            environments: HashMap::default(),
+        }
+    }
+}
+
+impl ScriptRuntime {
+    pub fn lua(&self) -> &Lua {
+        &self.lua
+    }
+
+    fn load(&mut self, script: &Script, id: HandleId) -> mlua::Result<()> {
+        if !self.environments.contains_key(&id) {
+            let environment = self.lua.create_table()?;
+            let metatable = self.lua.create_table()?;
+            metatable.set("__index", self.lua.globals())?;
+            environment.set_metatable(Some(metatable));
+            self.lua
+                .load(&script.source)
+                .set_name(&script.name)?
+                .set_environment(environment.clone())?
+                .exec()?;
+            let key = self.lua.create_registry_value(environment)?;
+            self.environments.insert(id, key);
+        }
+        Ok(())
+    }
+
+    /// Calls the `update(world)` function of a script. Does nothing while the script is loading.
+    pub fn run(&mut self, world: &mut World, script: &Handle<Script>) -> mlua::Result<()> {
+        let id = script.id;
+        let script = match world
+            .get_resource::<Assets<Script>>()
+            .and_then(|scripts| scripts.get(script))
+        {
+            Some(script) => script.clone(),
+            None => return Ok(()),
+        };
+        self.load(&script, id)?;
+        let environment: Table = self.lua.registry_value(&self.environments[&id])?;
+        let update = match environment.get::<_, Value>("update")? {
+            Value::Function(update) => update,
+            _ => return Ok(()),
+        };
+        let registry = world.get_resource::<TypeRegistryArc>().unwrap().clone();
+        let world = RefCell::new(world);
+        self.lua.scope(|scope| {
+            let world_table = create_world_table(&self.lua, scope, &world, &registry)?;
+            update.call::<_, ()>(world_table)
+        })
+    }
+
+    /// Drops the environment of a script, so that it's loaded again the next time it runs
+    pub fn unload(&mut self, id: HandleId) {
+        if let Some(key) = self.environments.remove(&id) {
+            let _ = self.lua.remove_registry_value(key);
+        }
+    }
+}
+
+/// Runs a script through the [ScriptRuntime], logging its errors
+pub fn run_script(world: &mut World, script: &Handle<Script>) {
+    let mut runtime = match world.remove_non_send::<ScriptRuntime>() {
+        Some(runtime) => runtime,
+        None => return,
+    };
+    if let Err(err) = runtime.run(world, script) {
+        let name = world
+            .get_resource::<Assets<Script>>()
+            .and_then(|scripts| scripts.get(script))
+            .map_or_else(|| format!("{:?}", script.id), |script| script.name.clone());
+        warn!("script {} failed: {}", name, err);
+    }
+    world.insert_non_send(runtime);
+}
+
+/// Reloads the scripts modified on disk the next time they run
+pub fn script_reload_system(
+    mut runtime: NonSendMut<ScriptRuntime>,
+    mut events: EventReader<AssetEvent<Script>>,
+) {
+    for event in events.iter() {
+        match event {
+            AssetEvent::Modified { handle } | AssetEvent::Removed { handle } => {
+                runtime.unload(handle.id)
+            }
+            AssetEvent::Created { .. } => {}
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use anyhow::Result;
+use bevy_asset::{AssetLoader, LoadContext, LoadedAsset};
+use bevy_reflect::TypeUuid;
+use bevy_utils::BoxedFuture;
+
+/// The source of a Lua script, run as a system added with
+/// [AddScriptSystem](crate::AddScriptSystem)
+#[derive(Debug, Clone, TypeUuid)]
+#[uuid = "3c0d36f5-92b4-4e0a-a0c9-35f2b4e1c7a8"]
+pub struct Script {
+    pub name: String,
+    pub source: String,
+}
+
+/// Loads lua files as [Script] [Assets](bevy_asset::Assets)
+#[derive(Default)]
+pub struct ScriptLoader;
+
+impl AssetLoader for ScriptLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let script = Script {
+                name: load_context.path().display().to_string(),
+                source: String::from_utf8(bytes.to_vec())?,
+            };
+            load_context.set_default_asset(LoadedAsset::new(script));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["lua"]
+    }
+}
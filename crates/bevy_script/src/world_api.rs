@@ -0,0 +1,392 @@
+use bevy_core::Time;
+use bevy_ecs::{
+    entity::Entity,
+    reflect::{ReflectComponent, ReflectResource},
+    world::World,
+};
+use bevy_reflect::{
+    DynamicStruct, DynamicTupleStruct, Reflect, ReflectDeserialize, ReflectMut, ReflectRef,
+    TypeRegistration, TypeRegistry, TypeRegistryArc,
+};
+use mlua::{Error, Lua, LuaSerdeExt, Result, Scope, Table, Value};
+use std::cell::RefCell;
+
+fn runtime_error(message: String) -> Error {
+    Error::RuntimeError(message)
+}
+
+/// Scripts refer to entities by the integer of [Entity::to_bits]
+fn entity_from_lua(bits: i64) -> Entity {
+    Entity::from_bits(bits as u64)
+}
+
+fn entity_to_lua(entity: Entity) -> i64 {
+    entity.to_bits() as i64
+}
+
+/// Finds a type by its full name, or by its short name like `Transform`
+fn registration<'a>(registry: &'a TypeRegistry, name: &str) -> Result<&'a TypeRegistration> {
+    registry
+        .get_with_name(name)
+        .or_else(|| registry.get_with_short_name(name))
+        .ok_or_else(|| runtime_error(format!("unregistered type `{}`", name)))
+}
+
+fn reflect_component<'a>(registry: &'a TypeRegistry, name: &str) -> Result<&'a ReflectComponent> {
+    registration(registry, name)?
+        .data::<ReflectComponent>()
+        .ok_or_else(|| {
+            runtime_error(format!(
+                "`{}` isn't a component, consider adding `#[reflect(Component)]` to it",
+                name
+            ))
+        })
+}
+
+fn reflect_resource<'a>(registry: &'a TypeRegistry, name: &str) -> Result<&'a ReflectResource> {
+    registration(registry, name)?
+        .data::<ReflectResource>()
+        .ok_or_else(|| {
+            runtime_error(format!(
+                "`{}` isn't a resource, consider adding `#[reflect(Resource)]` to it",
+                name
+            ))
+        })
+}
+
+fn sequence<'lua, 'a>(
+    lua: &'lua Lua,
+    values: impl Iterator<Item = &'a dyn Reflect>,
+) -> Result<Value<'lua>> {
+    let table = lua.create_table()?;
+    for (i, value) in values.enumerate() {
+        table.raw_set(i + 1, reflect_to_lua(lua, value)?)?;
+    }
+    Ok(Value::Table(table))
+}
+
+/// Converts structs to tables of their fields, lists and tuples to sequences, and values like
+/// numbers, strings and vectors through their serialization
+pub fn reflect_to_lua<'lua>(lua: &'lua Lua, value: &dyn Reflect) -> Result<Value<'lua>> {
+    match value.reflect_ref() {
+        ReflectRef::Struct(value) => {
+            let table = lua.create_table()?;
+            for (i, field) in value.iter_fields().enumerate() {
+                table.raw_set(value.name_at(i).unwrap(), reflect_to_lua(lua, field)?)?;
+            }
+            Ok(Value::Table(table))
+        }
+        ReflectRef::TupleStruct(value) => sequence(lua, value.iter_fields()),
+        ReflectRef::Tuple(value) => sequence(lua, value.iter_fields()),
+        ReflectRef::List(value) => sequence(lua, value.iter()),
+        ReflectRef::Map(value) => {
+            let table = lua.create_table()?;
+            for (key, value) in value.iter() {
+                table.raw_set(reflect_to_lua(lua, key)?, reflect_to_lua(lua, value)?)?;
+            }
+            Ok(Value::Table(table))
+        }
+        ReflectRef::Value(value) => match value.serializable() {
+            Some(serializable) => lua.to_value(serializable.borrow()),
+            None => Err(runtime_error(format!(
+                "`{}` can't be converted to a Lua value",
+                value.type_name()
+            ))),
+        },
+    }
+}
+
+fn table(value: Value, type_name: &str) -> Result<Table> {
+    match value {
+        Value::Table(table) => Ok(table),
+        _ => Err(runtime_error(format!(
+            "expected a table for `{}`",
+            type_name
+        ))),
+    }
+}
+
+/// Sets the fields present in a Lua value, converted like [reflect_to_lua] does, and leaves
+/// the others unchanged
+pub fn apply_lua(registry: &TypeRegistry, target: &mut dyn Reflect, value: Value) -> Result<()> {
+    let type_name = target.type_name().to_string();
+    match target.reflect_mut() {
+        ReflectMut::Struct(target) => {
+            for pair in table(value, &type_name)?.pairs::<String, Value>() {
+                let (name, value) = pair?;
+                let field = target.field_mut(&name).ok_or_else(|| {
+                    runtime_error(format!("`{}` has no field `{}`", type_name, name))
+                })?;
+                apply_lua(registry, field, value)?;
+            }
+            Ok(())
+        }
+        ReflectMut::TupleStruct(target) => {
+            for (i, value) in table(value, &type_name)?
+                .sequence_values::<Value>()
+                .enumerate()
+            {
+                if let Some(field) = target.field_mut(i) {
+                    apply_lua(registry, field, value?)?;
+                }
+            }
+            Ok(())
+        }
+        ReflectMut::Tuple(target) => {
+            for (i, value) in table(value, &type_name)?
+                .sequence_values::<Value>()
+                .enumerate()
+            {
+                if let Some(field) = target.field_mut(i) {
+                    apply_lua(registry, field, value?)?;
+                }
+            }
+            Ok(())
+        }
+        ReflectMut::List(target) => {
+            for (i, value) in table(value, &type_name)?
+                .sequence_values::<Value>()
+                .enumerate()
+            {
+                if let Some(item) = target.get_mut(i) {
+                    apply_lua(registry, item, value?)?;
+                }
+            }
+            Ok(())
+        }
+        ReflectMut::Map(_) => Err(runtime_error(format!(
+            "maps like `{}` can't be set from Lua",
+            type_name
+        ))),
+        ReflectMut::Value(target) => {
+            let reflect_deserialize = registry
+                .get_with_name(&type_name)
+                .and_then(|registration| registration.data::<ReflectDeserialize>())
+                .ok_or_else(|| {
+                    runtime_error(format!("`{}` can't be converted from Lua", type_name))
+                })?;
+            let value = reflect_deserialize.deserialize(mlua::serde::Deserializer::new(value))?;
+            target.apply(&*value);
+            Ok(())
+        }
+    }
+}
+
+/// An empty value of the right kind to build a component from before applying a Lua value to it
+fn empty_value(value: &Value, type_name: &str) -> Box<dyn Reflect> {
+    match value {
+        Value::Table(table) if table.raw_len() > 0 => {
+            let mut tuple_struct = DynamicTupleStruct::default();
+            tuple_struct.set_name(type_name.to_string());
+            Box::new(tuple_struct)
+        }
+        _ => {
+            let mut dynamic_struct = DynamicStruct::default();
+            dynamic_struct.set_name(type_name.to_string());
+            Box::new(dynamic_struct)
+        }
+    }
+}
+
+/// Creates the `world` table passed to scripts, whose functions access the [World] through
+/// reflection for the duration of the scope
+pub fn create_world_table<'lua, 'scope>(
+    lua: &'lua Lua,
+    scope: &Scope<'lua, 'scope>,
+    world: &'scope RefCell<&mut World>,
+    registry: &'scope TypeRegistryArc,
+) -> Result<Table<'lua>> {
+    let table = lua.create_table()?;
+
+    table.set(
+        "spawn",
+        scope.create_function(move |_, ()| Ok(entity_to_lua(world.borrow_mut().spawn().id())))?,
+    )?;
+
+    table.set(
+        "despawn",
+        scope.create_function(move |_, entity: i64| {
+            Ok(world.borrow_mut().despawn(entity_from_lua(entity)))
+        })?,
+    )?;
+
+    table.set(
+        "query",
+        scope.create_function(move |_, names: Vec<String>| {
+            let world = world.borrow();
+            let registry = registry.read();
+            let mut component_ids = Vec::with_capacity(names.len());
+            for name in names.iter() {
+                let type_id = registration(&registry, name)?.type_id();
+                match world.components().get_id(type_id) {
+                    Some(component_id) => component_ids.push(component_id),
+                    // no entity has a component that was never added
+                    None => return Ok(Vec::new()),
+                }
+            }
+            let entities = world
+                .archetypes()
+                .iter()
+                .filter(|archetype| {
+                    component_ids
+                        .iter()
+                        .all(|component_id| archetype.contains(*component_id))
+                })
+                .flat_map(|archetype| archetype.entities().iter())
+                .map(|entity| entity_to_lua(*entity))
+                .collect::<Vec<_>>();
+            Ok(entities)
+        })?,
+    )?;
+
+    table.set(
+        "get",
+        scope.create_function(move |lua, (entity, name): (i64, String)| {
+            let world = world.borrow();
+            let registry = registry.read();
+            match reflect_component(&registry, &name)?
+                .reflect_component(&world, entity_from_lua(entity))
+            {
+                Some(component) => reflect_to_lua(lua, component),
+                None => Ok(Value::Nil),
+            }
+        })?,
+    )?;
+
+    table.set(
+        "set",
+        scope.create_function(move |_, (entity, name, value): (i64, String, Value)| {
+            let mut world = world.borrow_mut();
+            let world: &mut World = &mut world;
+            let registry = registry.read();
+            let entity = entity_from_lua(entity);
+            if world.get_entity(entity).is_none() {
+                return Err(runtime_error(format!("entity {:?} doesn't exist", entity)));
+            }
+            let reflect_component = reflect_component(&registry, &name)?;
+            if reflect_component.reflect_component(world, entity).is_none() {
+                let type_name = registration(&registry, &name)?.name();
+                reflect_component.add_component(world, entity, &*empty_value(&value, type_name));
+            }
+            let mut component = reflect_component
+                .reflect_component_mut(world, entity)
+                .unwrap();
+            apply_lua(&registry, &mut *component, value)
+        })?,
+    )?;
+
+    table.set(
+        "remove",
+        scope.create_function(move |_, (entity, name): (i64, String)| {
+            let mut world = world.borrow_mut();
+            let entity = entity_from_lua(entity);
+            if world.get_entity(entity).is_some() {
+                let registry = registry.read();
+                reflect_component(&registry, &name)?.remove_component(&mut world, entity);
+            }
+            Ok(())
+        })?,
+    )?;
+
+    table.set(
+        "resource",
+        scope.create_function(move |lua, name: String| {
+            let world = world.borrow();
+            let registry = registry.read();
+            match reflect_resource(&registry, &name)?.reflect_resource(&world) {
+                Some(resource) => reflect_to_lua(lua, resource),
+                None => Ok(Value::Nil),
+            }
+        })?,
+    )?;
+
+    table.set(
+        "set_resource",
+        scope.create_function(move |_, (name, value): (String, Value)| {
+            let mut world = world.borrow_mut();
+            let world: &mut World = &mut world;
+            let registry = registry.read();
+            let reflect_resource = reflect_resource(&registry, &name)?;
+            let mut resource = match reflect_resource.reflect_resource(world) {
+                Some(resource) => resource.clone_value(),
+                None => {
+                    let type_name = registration(&registry, &name)?.name();
+                    empty_value(&value, type_name)
+                }
+            };
+            apply_lua(&registry, &mut *resource, value)?;
+            reflect_resource.insert_resource(world, &*resource);
+            Ok(())
+        })?,
+    )?;
+
+    table.set(
+        "delta_seconds",
+        scope.create_function(move |_, ()| {
+            Ok(world
+                .borrow()
+                .get_resource::<Time>()
+                .map_or(0.0, |time| time.delta_seconds()))
+        })?,
+    )?;
+
+    Ok(table)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Reflect, Default, Debug, PartialEq)]
+    #[reflect(Component)]
+    struct Speed {
+        value: f32,
+    }
+
+    #[derive(Reflect, Default, Debug, PartialEq)]
+    #[reflect(Resource)]
+    struct Score(u32);
+
+    #[test]
+    fn scripts_read_and_write_components_and_resources() {
+        let registry = TypeRegistryArc::default();
+        {
+            let mut registry = registry.write();
+            registry.register::<f32>();
+            registry.register::<u32>();
+            registry.register::<Speed>();
+            registry.register::<Score>();
+        }
+        let mut world = World::new();
+        world.spawn().insert(Speed { value: 1.0 });
+        world.insert_resource(Score(2));
+
+        let lua = Lua::new();
+        let world_cell = RefCell::new(&mut world);
+        lua.scope(|scope| {
+            let world_table = create_world_table(&lua, scope, &world_cell, &registry)?;
+            lua.globals().set("world", world_table)?;
+            lua.load(
+                r#"
+                for _, entity in ipairs(world.query({"Speed"})) do
+                    local speed = world.get(entity, "Speed")
+                    world.set(entity, "Speed", { value = speed.value * 3 })
+                end
+                world.set(world.spawn(), "Speed", { value = 5 })
+                world.set_resource("Score", { world.resource("Score")[1] + 1 })
+                "#,
+            )
+            .exec()
+        })
+        .unwrap();
+
+        let mut speeds = world
+            .query::<&Speed>()
+            .iter(&world)
+            .map(|speed| speed.value)
+            .collect::<Vec<_>>();
+        speeds.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(speeds, vec![3.0, 5.0]);
+        assert_eq!(world.get_resource::<Score>(), Some(&Score(3)));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::NineSlice;
 use bevy_app::{EventReader, Events, ManualEventReader};
 use bevy_asset::{self, AssetEvent, Assets, Handle};
 use bevy_ecs::system::{Local, Res, ResMut};
@@ -11,6 +12,9 @@ pub struct ColorMaterial {
     pub color: Color,
     #[shader_def]
     pub texture: Option<Handle<Texture>>,
+    /// Keeps the borders of the texture from stretching when it is drawn at a different size
+    #[shader_def]
+    pub nine_slice: NineSlice,
 }
 
 impl ColorMaterial {
@@ -18,6 +22,7 @@ impl ColorMaterial {
         ColorMaterial {
             color,
             texture: None,
+            nine_slice: NineSlice::NONE,
         }
     }
 
@@ -25,6 +30,7 @@ impl ColorMaterial {
         ColorMaterial {
             color: Color::WHITE,
             texture: Some(texture),
+            nine_slice: NineSlice::NONE,
         }
     }
 
@@ -32,8 +38,14 @@ impl ColorMaterial {
         ColorMaterial {
             color,
             texture: Some(texture),
+            nine_slice: NineSlice::NONE,
         }
     }
+
+    pub fn with_nine_slice(mut self, nine_slice: NineSlice) -> Self {
+        self.nine_slice = nine_slice;
+        self
+    }
 }
 
 impl Default for ColorMaterial {
@@ -41,6 +53,7 @@ impl Default for ColorMaterial {
         ColorMaterial {
             color: Color::rgb(1.0, 1.0, 1.0),
             texture: None,
+            nine_slice: NineSlice::NONE,
         }
     }
 }
@@ -1,6 +1,6 @@
 use bevy_asset::{Assets, Handle};
 use bevy_ecs::prelude::{Commands, Entity, Query, Res, With};
-use bevy_math::Vec2;
+use bevy_math::{Aabb2d, Vec2};
 use bevy_render::{
     camera::{ActiveCameras, Camera},
     draw::OutsideFrustum,
@@ -10,22 +10,9 @@ use bevy_window::Windows;
 
 use crate::{Sprite, TextureAtlas, TextureAtlasSprite};
 
-struct Rect {
-    position: Vec2,
-    size: Vec2,
-}
-
-impl Rect {
-    #[inline]
-    pub fn is_intersecting(&self, other: Rect) -> bool {
-        self.position.distance(other.position) < (self.get_radius() + other.get_radius())
-    }
-
-    #[inline]
-    pub fn get_radius(&self) -> f32 {
-        let half_size = self.size / Vec2::splat(2.0);
-        (half_size.x.powf(2.0) + half_size.y.powf(2.0)).sqrt()
-    }
+/// The bounds of a sprite of `size` at `transform`, large enough to contain it at any rotation
+fn sprite_bounds(transform: &Transform, size: Vec2) -> Aabb2d {
+    Aabb2d::from_center_size(transform.translation.truncate(), Vec2::splat(size.length()))
 }
 
 pub fn sprite_frustum_culling_system(
@@ -46,18 +33,13 @@ pub fn sprite_frustum_culling_system(
         if let Ok(camera_transform) = camera_transforms.get(active_camera_entity) {
             let camera_size = window_size * camera_transform.scale.truncate();
 
-            let rect = Rect {
-                position: camera_transform.translation.truncate(),
-                size: camera_size,
-            };
+            let rect =
+                Aabb2d::from_center_size(camera_transform.translation.truncate(), camera_size);
 
             for (entity, drawable_transform, sprite) in sprites.iter() {
-                let sprite_rect = Rect {
-                    position: drawable_transform.translation.truncate(),
-                    size: sprite.size,
-                };
+                let sprite_rect = sprite_bounds(drawable_transform, sprite.size);
 
-                if rect.is_intersecting(sprite_rect) {
+                if rect.intersects(&sprite_rect) {
                     if culled_sprites.get(entity).is_ok() {
                         commands.entity(entity).remove::<OutsideFrustum>();
                     }
@@ -90,22 +72,17 @@ pub fn atlas_frustum_culling_system(
         if let Ok(camera_transform) = camera_transforms.get(active_camera_entity) {
             let camera_size = window_size * camera_transform.scale.truncate();
 
-            let rect = Rect {
-                position: camera_transform.translation.truncate(),
-                size: camera_size,
-            };
+            let rect =
+                Aabb2d::from_center_size(camera_transform.translation.truncate(), camera_size);
 
             for (entity, drawable_transform, sprite, atlas_handle) in sprites.iter() {
                 if let Some(atlas) = textures.get(atlas_handle) {
                     if let Some(sprite) = atlas.textures.get(sprite.index as usize) {
                         let size = Vec2::new(sprite.width(), sprite.height());
 
-                        let sprite_rect = Rect {
-                            position: drawable_transform.translation.truncate(),
-                            size,
-                        };
+                        let sprite_rect = sprite_bounds(drawable_transform, size);
 
-                        if rect.is_intersecting(sprite_rect) {
+                        if rect.intersects(&sprite_rect) {
                             if culled_sprites.get(entity).is_ok() {
                                 commands.entity(entity).remove::<OutsideFrustum>();
                             }
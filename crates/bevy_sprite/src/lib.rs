@@ -4,6 +4,7 @@ pub mod entity;
 mod color_material;
 mod dynamic_texture_atlas_builder;
 mod frustum_culling;
+mod nine_slice;
 mod rect;
 mod render;
 mod sprite;
@@ -13,12 +14,13 @@ mod texture_atlas_builder;
 pub mod prelude {
     pub use crate::{
         entity::{SpriteBundle, SpriteSheetBundle},
-        ColorMaterial, Sprite, SpriteResizeMode, TextureAtlas, TextureAtlasSprite,
+        ColorMaterial, NineSlice, Sprite, SpriteResizeMode, TextureAtlas, TextureAtlasSprite,
     };
 }
 
 pub use color_material::*;
 pub use dynamic_texture_atlas_builder::*;
+pub use nine_slice::*;
 pub use rect::*;
 pub use render::*;
 pub use sprite::*;
@@ -69,9 +71,14 @@ impl Plugin for SpritePlugin {
     fn build(&self, app: &mut AppBuilder) {
         app.add_asset::<ColorMaterial>()
             .add_asset::<TextureAtlas>()
+            .register_type::<NineSlice>()
             .register_type::<Sprite>()
             .register_type::<SpriteResizeMode>()
             .add_system_to_stage(CoreStage::PostUpdate, sprite_system.system())
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                texture_atlas_nine_slices_system.system(),
+            )
             .add_system_to_stage(
                 CoreStage::PostUpdate,
                 material_texture_detection_system.system(),
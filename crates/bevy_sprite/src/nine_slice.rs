@@ -0,0 +1,112 @@
+use bevy_asset::Handle;
+use bevy_core::{Byteable, Bytes};
+use bevy_reflect::{Reflect, ReflectDeserialize};
+use bevy_render::{
+    renderer::{RenderResource, RenderResourceType},
+    shader::ShaderDef,
+    texture::Texture,
+};
+use serde::{Deserialize, Serialize};
+
+/// Insets from the edges of a texture, in texels, splitting it into a 3x3 grid.
+///
+/// When the texture is drawn at a different size, the corners keep their size, the edges only
+/// stretch along their border and the center stretches both ways, so buttons and panels can be
+/// scaled without distorting their frame. The borders shrink if the texture is drawn smaller than
+/// them.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize, Reflect)]
+#[reflect_value(PartialEq, Serialize, Deserialize)]
+pub struct NineSlice {
+    pub left: f32,
+    pub right: f32,
+    pub top: f32,
+    pub bottom: f32,
+}
+
+impl NineSlice {
+    /// Stretches the whole texture, like a texture without insets
+    pub const NONE: NineSlice = NineSlice {
+        left: 0.0,
+        right: 0.0,
+        top: 0.0,
+        bottom: 0.0,
+    };
+
+    pub fn new(left: f32, right: f32, top: f32, bottom: f32) -> Self {
+        NineSlice {
+            left,
+            right,
+            top,
+            bottom,
+        }
+    }
+
+    /// The same inset from every edge
+    pub fn uniform(inset: f32) -> Self {
+        NineSlice::new(inset, inset, inset, inset)
+    }
+
+    /// Returns true if any inset is set
+    pub fn is_enabled(&self) -> bool {
+        *self != NineSlice::NONE
+    }
+
+    /// Maps `position`, from `0.0` to `size` along an axis of the drawn texture, to the texels of
+    /// an axis of `texture_size` texels with the insets `start` and `end`. This is the mapping the
+    /// sprite and ui shaders apply.
+    pub fn map_axis(position: f32, size: f32, texture_size: f32, start: f32, end: f32) -> f32 {
+        let scale = (size / (start + end).max(f32::EPSILON)).min(1.0);
+        if position < start * scale {
+            position / scale
+        } else if position > size - end * scale {
+            texture_size - (size - position) / scale
+        } else {
+            let middle = (size - (start + end) * scale).max(f32::EPSILON);
+            start + (position - start * scale) / middle * (texture_size - start - end)
+        }
+    }
+}
+
+unsafe impl Byteable for NineSlice {}
+
+impl RenderResource for NineSlice {
+    fn resource_type(&self) -> Option<RenderResourceType> {
+        Some(RenderResourceType::Buffer)
+    }
+
+    fn buffer_byte_len(&self) -> Option<usize> {
+        Some(std::mem::size_of::<NineSlice>())
+    }
+
+    fn write_buffer_bytes(&self, buffer: &mut [u8]) {
+        self.write_bytes(buffer);
+    }
+
+    fn texture(&self) -> Option<&Handle<Texture>> {
+        None
+    }
+}
+
+impl ShaderDef for NineSlice {
+    fn is_defined(&self) -> bool {
+        self.is_enabled()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_corners_and_stretches_center() {
+        // a 30 texel texture with 10 texel borders drawn 100 units wide
+        assert_eq!(NineSlice::map_axis(5.0, 100.0, 30.0, 10.0, 10.0), 5.0);
+        assert_eq!(NineSlice::map_axis(95.0, 100.0, 30.0, 10.0, 10.0), 25.0);
+        assert_eq!(NineSlice::map_axis(50.0, 100.0, 30.0, 10.0, 10.0), 15.0);
+        // drawn smaller than its borders, the borders shrink to fit
+        assert_eq!(NineSlice::map_axis(5.0, 10.0, 30.0, 10.0, 10.0), 10.0);
+        assert!(!NineSlice::NONE.is_enabled());
+        assert!(NineSlice::uniform(4.0).is_enabled());
+    }
+}
@@ -2,10 +2,11 @@ use crate::{ColorMaterial, Sprite, TextureAtlas, TextureAtlasSprite};
 use bevy_asset::{Assets, HandleUntyped};
 use bevy_reflect::TypeUuid;
 use bevy_render::{
+    billboard::BILLBOARD_GLSL,
     pipeline::{
-        BlendFactor, BlendOperation, BlendState, ColorTargetState, ColorWrite, CompareFunction,
-        CullMode, DepthBiasState, DepthStencilState, FrontFace, PipelineDescriptor, PolygonMode,
-        PrimitiveState, PrimitiveTopology, StencilFaceState, StencilState,
+        BlendMode, ColorTargetState, CompareFunction, CullMode, DepthBiasState, DepthStencilState,
+        FrontFace, PipelineDescriptor, PolygonMode, PrimitiveState, PrimitiveTopology,
+        StencilState,
     },
     render_graph::{base, AssetRenderResourcesNode, RenderGraph, RenderResourcesNode},
     shader::{Shader, ShaderStage, ShaderStages},
@@ -18,18 +19,20 @@ pub const SPRITE_PIPELINE_HANDLE: HandleUntyped =
 pub const SPRITE_SHEET_PIPELINE_HANDLE: HandleUntyped =
     HandleUntyped::weak_from_u64(PipelineDescriptor::TYPE_UUID, 9016885805180281612);
 
+/// The path and source of the `nine_slice_axis` GLSL function, which shaders drawing
+/// [NineSlice](crate::NineSlice)s include with `#include "bevy_sprite/nine_slice.glsl"`
+pub const NINE_SLICE_GLSL: (&str, &str) = (
+    "bevy_sprite/nine_slice.glsl",
+    include_str!("nine_slice.glsl"),
+);
+
 pub fn build_sprite_sheet_pipeline(shaders: &mut Assets<Shader>) -> PipelineDescriptor {
     PipelineDescriptor {
         depth_stencil: Some(DepthStencilState {
             format: TextureFormat::Depth32Float,
             depth_write_enabled: true,
             depth_compare: CompareFunction::LessEqual,
-            stencil: StencilState {
-                front: StencilFaceState::IGNORE,
-                back: StencilFaceState::IGNORE,
-                read_mask: 0,
-                write_mask: 0,
-            },
+            stencil: StencilState::IGNORE,
             bias: DepthBiasState {
                 constant: 0,
                 slope_scale: 0.0,
@@ -37,20 +40,10 @@ pub fn build_sprite_sheet_pipeline(shaders: &mut Assets<Shader>) -> PipelineDesc
             },
             clamp_depth: false,
         }),
-        color_target_states: vec![ColorTargetState {
-            format: TextureFormat::default(),
-            color_blend: BlendState {
-                src_factor: BlendFactor::SrcAlpha,
-                dst_factor: BlendFactor::OneMinusSrcAlpha,
-                operation: BlendOperation::Add,
-            },
-            alpha_blend: BlendState {
-                src_factor: BlendFactor::One,
-                dst_factor: BlendFactor::One,
-                operation: BlendOperation::Add,
-            },
-            write_mask: ColorWrite::ALL,
-        }],
+        color_target_states: vec![ColorTargetState::new(
+            TextureFormat::default(),
+            BlendMode::Alpha,
+        )],
         primitive: PrimitiveState {
             topology: PrimitiveTopology::TriangleList,
             strip_index_format: None,
@@ -59,13 +52,17 @@ pub fn build_sprite_sheet_pipeline(shaders: &mut Assets<Shader>) -> PipelineDesc
             polygon_mode: PolygonMode::Fill,
         },
         ..PipelineDescriptor::new(ShaderStages {
-            vertex: shaders.add(Shader::from_glsl(
+            vertex: shaders.add(Shader::from_glsl_with_includes(
                 ShaderStage::Vertex,
+                "bevy_sprite/sprite_sheet.vert",
                 include_str!("sprite_sheet.vert"),
+                &[BILLBOARD_GLSL],
             )),
-            fragment: Some(shaders.add(Shader::from_glsl(
+            fragment: Some(shaders.add(Shader::from_glsl_with_includes(
                 ShaderStage::Fragment,
+                "bevy_sprite/sprite_sheet.frag",
                 include_str!("sprite_sheet.frag"),
+                &[NINE_SLICE_GLSL],
             ))),
         })
     }
@@ -77,12 +74,7 @@ pub fn build_sprite_pipeline(shaders: &mut Assets<Shader>) -> PipelineDescriptor
             format: TextureFormat::Depth32Float,
             depth_write_enabled: true,
             depth_compare: CompareFunction::LessEqual,
-            stencil: StencilState {
-                front: StencilFaceState::IGNORE,
-                back: StencilFaceState::IGNORE,
-                read_mask: 0,
-                write_mask: 0,
-            },
+            stencil: StencilState::IGNORE,
             bias: DepthBiasState {
                 constant: 0,
                 slope_scale: 0.0,
@@ -90,20 +82,10 @@ pub fn build_sprite_pipeline(shaders: &mut Assets<Shader>) -> PipelineDescriptor
             },
             clamp_depth: false,
         }),
-        color_target_states: vec![ColorTargetState {
-            format: TextureFormat::default(),
-            color_blend: BlendState {
-                src_factor: BlendFactor::SrcAlpha,
-                dst_factor: BlendFactor::OneMinusSrcAlpha,
-                operation: BlendOperation::Add,
-            },
-            alpha_blend: BlendState {
-                src_factor: BlendFactor::One,
-                dst_factor: BlendFactor::One,
-                operation: BlendOperation::Add,
-            },
-            write_mask: ColorWrite::ALL,
-        }],
+        color_target_states: vec![ColorTargetState::new(
+            TextureFormat::default(),
+            BlendMode::Alpha,
+        )],
         primitive: PrimitiveState {
             topology: PrimitiveTopology::TriangleList,
             strip_index_format: None,
@@ -112,13 +94,17 @@ pub fn build_sprite_pipeline(shaders: &mut Assets<Shader>) -> PipelineDescriptor
             polygon_mode: PolygonMode::Fill,
         },
         ..PipelineDescriptor::new(ShaderStages {
-            vertex: shaders.add(Shader::from_glsl(
+            vertex: shaders.add(Shader::from_glsl_with_includes(
                 ShaderStage::Vertex,
+                "bevy_sprite/sprite.vert",
                 include_str!("sprite.vert"),
+                &[BILLBOARD_GLSL],
             )),
-            fragment: Some(shaders.add(Shader::from_glsl(
+            fragment: Some(shaders.add(Shader::from_glsl_with_includes(
                 ShaderStage::Fragment,
+                "bevy_sprite/sprite.frag",
                 include_str!("sprite.frag"),
+                &[NINE_SLICE_GLSL],
             ))),
         })
     }
@@ -0,0 +1,13 @@
+// Maps a position along an axis of the drawn quad to a texel along the same axis of the texture,
+// keeping the insets `start` and `end` from stretching. See NineSlice::map_axis.
+float nine_slice_axis(float position, float size, float texture_size, float start, float end) {
+    float scale = min(size / max(start + end, 0.00000011920929), 1.0);
+    if (position < start * scale) {
+        return position / scale;
+    }
+    if (position > size - end * scale) {
+        return texture_size - (size - position) / scale;
+    }
+    float middle = max(size - (start + end) * scale, 0.00000011920929);
+    return start + (position - start * scale) / middle * (texture_size - start - end);
+}
@@ -1,6 +1,7 @@
 #version 450
 
 layout(location = 0) in vec2 v_Uv;
+layout(location = 1) in vec2 v_Size;
 
 layout(location = 0) out vec4 o_Target;
 
@@ -13,12 +14,31 @@ layout(set = 1, binding = 1) uniform texture2D ColorMaterial_texture;
 layout(set = 1, binding = 2) uniform sampler ColorMaterial_texture_sampler;
 # endif
 
+# ifdef COLORMATERIAL_NINE_SLICE
+layout(set = 1, binding = 3) uniform ColorMaterial_nine_slice {
+    // the left, right, top and bottom insets, in texels
+    vec4 NineSlice;
+};
+
+#include "bevy_sprite/nine_slice.glsl"
+# endif
+
 void main() {
     vec4 color = Color;
 # ifdef COLORMATERIAL_TEXTURE
+    vec2 uv = v_Uv;
+#   ifdef COLORMATERIAL_NINE_SLICE
+    vec2 texture_size = vec2(textureSize(
+        sampler2D(ColorMaterial_texture, ColorMaterial_texture_sampler),
+        0));
+    vec2 texel = vec2(
+        nine_slice_axis(uv.x * v_Size.x, v_Size.x, texture_size.x, NineSlice.x, NineSlice.y),
+        nine_slice_axis(uv.y * v_Size.y, v_Size.y, texture_size.y, NineSlice.z, NineSlice.w));
+    uv = texel / texture_size;
+#   endif
     color *= texture(
         sampler2D(ColorMaterial_texture, ColorMaterial_texture_sampler),
-        v_Uv);
+        uv);
 # endif
     o_Target = color;
 }
@@ -5,6 +5,7 @@ layout(location = 1) in vec3 Vertex_Normal;
 layout(location = 2) in vec2 Vertex_Uv;
 
 layout(location = 0) out vec2 v_Uv;
+layout(location = 1) out vec2 v_Size;
 
 layout(set = 0, binding = 0) uniform CameraViewProj {
     mat4 ViewProj;
@@ -18,6 +19,14 @@ layout(set = 2, binding = 1) uniform Sprite {
     uint flip;
 };
 
+#ifdef BILLBOARD
+layout(set = 0, binding = 1) uniform CameraView {
+    mat4 View;
+};
+
+#include "bevy_render/billboard.glsl"
+#endif
+
 void main() {
     vec2 uv = Vertex_Uv;
 
@@ -40,5 +49,12 @@ void main() {
     v_Uv = uv;
 
     vec3 position = Vertex_Position * vec3(size, 1.0);
-    gl_Position = ViewProj * Model * vec4(position, 1.0);
+#ifdef BILLBOARD
+    mat4 model = billboard(Model, View);
+#else
+    mat4 model = Model;
+#endif
+    // nine slice textures keep their borders from stretching over the drawn size
+    v_Size = size * vec2(length(Model[0].xyz), length(Model[1].xyz));
+    gl_Position = ViewProj * model * vec4(position, 1.0);
 }
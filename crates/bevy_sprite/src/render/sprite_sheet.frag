@@ -2,14 +2,31 @@
 
 layout(location = 0) in vec2 v_Uv;
 layout(location = 1) in vec4 v_Color;
+layout(location = 2) in vec2 v_Local;
+layout(location = 3) flat in vec2 v_Size;
+layout(location = 4) flat in vec4 v_Rect;
+layout(location = 5) flat in vec4 v_NineSlice;
 
 layout(location = 0) out vec4 o_Target;
 
 layout(set = 1, binding = 2) uniform texture2D TextureAtlas_texture;
 layout(set = 1, binding = 3) uniform sampler TextureAtlas_texture_sampler;
 
+#include "bevy_sprite/nine_slice.glsl"
+
 void main() {
+    vec2 uv = v_Uv;
+    if (v_NineSlice != vec4(0.0)) {
+        vec2 texture_size = (v_Rect.zw - v_Rect.xy) * vec2(textureSize(
+            sampler2D(TextureAtlas_texture, TextureAtlas_texture_sampler),
+            0));
+        vec2 position = v_Local * v_Size;
+        vec2 texel = vec2(
+            nine_slice_axis(position.x, v_Size.x, texture_size.x, v_NineSlice.x, v_NineSlice.y),
+            nine_slice_axis(position.y, v_Size.y, texture_size.y, v_NineSlice.z, v_NineSlice.w));
+        uv = mix(v_Rect.xy, v_Rect.zw, texel / texture_size);
+    }
     o_Target = v_Color * texture(
         sampler2D(TextureAtlas_texture, TextureAtlas_texture_sampler),
-        v_Uv);
+        uv);
 }
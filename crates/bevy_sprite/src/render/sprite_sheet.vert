@@ -6,6 +6,10 @@ layout(location = 2) in vec2 Vertex_Uv;
 
 layout(location = 0) out vec2 v_Uv;
 layout(location = 1) out vec4 v_Color;
+layout(location = 2) out vec2 v_Local;
+layout(location = 3) flat out vec2 v_Size;
+layout(location = 4) flat out vec4 v_Rect;
+layout(location = 5) flat out vec4 v_NineSlice;
 
 layout(set = 0, binding = 0) uniform CameraViewProj {
     mat4 ViewProj;
@@ -25,6 +29,11 @@ layout(set = 1, binding = 1) buffer TextureAtlas_textures {
     Rect[] Textures;
 };
 
+// the left, right, top and bottom insets of each texture, in texels
+layout(set = 1, binding = 4) buffer TextureAtlas_nine_slices {
+    vec4[] NineSlices;
+};
+
 
 layout(set = 2, binding = 0) uniform Transform {
     mat4 SpriteTransform;
@@ -36,6 +45,14 @@ layout(set = 2, binding = 1) uniform TextureAtlasSprite {
     uint flip;
 };
 
+#ifdef BILLBOARD
+layout(set = 0, binding = 1) uniform CameraView {
+    mat4 View;
+};
+
+#include "bevy_render/billboard.glsl"
+#endif
+
 void main() {
     Rect sprite_rect = Textures[index];
     vec2 sprite_dimensions = sprite_rect.end - sprite_rect.begin;
@@ -81,5 +98,25 @@ void main() {
     v_Uv = (atlas_positions[gl_VertexIndex]) / AtlasSize;
 
     v_Color = color;
-    gl_Position = ViewProj * SpriteTransform * vec4(vertex_position, 1.0);
+
+    // nine slice textures keep their borders from stretching when the sprite is scaled, which
+    // is done in the fragment shader from the position in the unflipped texture
+    vec2 local = Vertex_Uv;
+    if ((flip & x_flip_bit) == x_flip_bit) {
+        local.x = 1.0 - local.x;
+    }
+    if ((flip & y_flip_bit) == y_flip_bit) {
+        local.y = 1.0 - local.y;
+    }
+    v_Local = local;
+    vec2 scale = vec2(length(SpriteTransform[0].xyz), length(SpriteTransform[1].xyz));
+    v_Size = sprite_dimensions * scale;
+    v_Rect = vec4(sprite_rect.begin, sprite_rect.end) / AtlasSize.xyxy;
+    v_NineSlice = NineSlices[index];
+#ifdef BILLBOARD
+    mat4 sprite_transform = billboard(SpriteTransform, View);
+#else
+    mat4 sprite_transform = SpriteTransform;
+#endif
+    gl_Position = ViewProj * sprite_transform * vec4(vertex_position, 1.0);
 }
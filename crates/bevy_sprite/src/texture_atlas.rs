@@ -1,6 +1,8 @@
-use crate::Rect;
-use bevy_asset::Handle;
+use crate::{NineSlice, Rect};
+use bevy_app::EventReader;
+use bevy_asset::{AssetEvent, Assets, Handle};
 use bevy_core::Bytes;
+use bevy_ecs::system::ResMut;
 use bevy_math::Vec2;
 use bevy_reflect::TypeUuid;
 use bevy_render::{
@@ -21,6 +23,11 @@ pub struct TextureAtlas {
     /// The specific areas of the atlas where each texture can be found
     #[render_resources(buffer)]
     pub textures: Vec<Rect>,
+    /// The insets of each texture, in the same order as `textures`. Kept as long as `textures`
+    /// by [texture_atlas_nine_slices_system], as the sprite sheet shader reads the insets of the
+    /// drawn texture by its index.
+    #[render_resources(buffer)]
+    pub(crate) nine_slices: Vec<NineSlice>,
     #[render_resources(ignore)]
     pub texture_handles: Option<HashMap<Handle<Texture>, usize>>,
 }
@@ -94,6 +101,7 @@ impl TextureAtlas {
             size: dimensions,
             texture_handles: None,
             textures: Vec::new(),
+            nine_slices: Vec::new(),
         }
     }
 
@@ -148,6 +156,7 @@ impl TextureAtlas {
                 ((tile_size.x + x_padding) * columns as f32) - x_padding,
                 ((tile_size.y + y_padding) * rows as f32) - y_padding,
             ),
+            nine_slices: vec![NineSlice::NONE; sprites.len()],
             textures: sprites,
             texture,
             texture_handles: None,
@@ -162,6 +171,24 @@ impl TextureAtlas {
     /// from the top-left corner of the texture to the bottom-right corner
     pub fn add_texture(&mut self, rect: Rect) {
         self.textures.push(rect);
+        self.nine_slices.push(NineSlice::NONE);
+    }
+
+    /// Sets the insets of the texture at `index`, which keep its borders from stretching when
+    /// it is drawn scaled
+    pub fn set_nine_slice(&mut self, index: usize, nine_slice: NineSlice) {
+        if index >= self.nine_slices.len() {
+            self.nine_slices.resize(index + 1, NineSlice::NONE);
+        }
+        self.nine_slices[index] = nine_slice;
+    }
+
+    /// The insets of the texture at `index`
+    pub fn nine_slice(&self, index: usize) -> NineSlice {
+        self.nine_slices
+            .get(index)
+            .copied()
+            .unwrap_or(NineSlice::NONE)
     }
 
     /// How many textures are in the `TextureAtlas`
@@ -179,3 +206,30 @@ impl TextureAtlas {
             .and_then(|texture_handles| texture_handles.get(texture).cloned())
     }
 }
+
+/// Resizes the nine slices of created and modified atlases to the number of their textures, which
+/// can be added without nine slices through the public `textures` field
+pub fn texture_atlas_nine_slices_system(
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    mut texture_atlas_events: EventReader<AssetEvent<TextureAtlas>>,
+) {
+    let handles = texture_atlas_events
+        .iter()
+        .filter_map(|event| match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
+                Some(handle.clone_weak())
+            }
+            AssetEvent::Removed { .. } => None,
+        })
+        .collect::<Vec<_>>();
+    for handle in handles {
+        // only mutably borrowing mismatched atlases avoids sending modified events every frame
+        let len = match texture_atlases.get(&handle) {
+            Some(atlas) if atlas.nine_slices.len() != atlas.textures.len() => atlas.textures.len(),
+            _ => continue,
+        };
+        if let Some(atlas) = texture_atlases.get_mut(&handle) {
+            atlas.nine_slices.resize(len, NineSlice::NONE);
+        }
+    }
+}
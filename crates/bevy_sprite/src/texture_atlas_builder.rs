@@ -1,4 +1,4 @@
-use crate::{Rect, TextureAtlas};
+use crate::{NineSlice, Rect, TextureAtlas};
 use bevy_asset::{Assets, Handle};
 use bevy_log::{debug, error, warn};
 use bevy_math::Vec2;
@@ -214,6 +214,7 @@ impl TextureAtlasBuilder {
         Ok(TextureAtlas {
             size: atlas_texture.size.as_vec3().truncate(),
             texture: textures.add(atlas_texture),
+            nine_slices: vec![NineSlice::NONE; texture_rects.len()],
             textures: texture_rects,
             texture_handles: Some(texture_handles),
         })
@@ -4,8 +4,9 @@ use bevy_render::{
     mesh,
     mesh::Mesh,
     pipeline::{PipelineSpecialization, VertexBufferLayout},
-    prelude::Msaa,
+    prelude::{Depth, Msaa},
     renderer::{BindGroup, RenderResourceBindings, RenderResourceId},
+    texture::TextureFormat,
 };
 use bevy_sprite::TextureAtlasSprite;
 use bevy_utils::tracing::error;
@@ -20,6 +21,9 @@ pub struct DrawableText<'a> {
     pub sections: &'a [TextSection],
     pub text_glyphs: &'a Vec<PositionedGlyph>,
     pub msaa: &'a Msaa,
+    pub depth: &'a Depth,
+    /// The color target format of the pass the text is drawn in, `None` for the swap chain format
+    pub color_target_format: Option<TextureFormat>,
     pub font_quad_vertex_layout: &'a VertexBufferLayout,
 }
 
@@ -30,7 +34,10 @@ impl<'a> Drawable for DrawableText<'a> {
             &bevy_sprite::SPRITE_SHEET_PIPELINE_HANDLE.typed(),
             &PipelineSpecialization {
                 sample_count: self.msaa.samples,
+                color_target_format: self.color_target_format,
                 vertex_buffer_layout: self.font_quad_vertex_layout.clone(),
+                depth_format: Some(self.depth.format),
+                reversed_z: self.depth.reversed_z,
                 ..Default::default()
             },
         )?;
@@ -9,7 +9,7 @@ use bevy_math::{Size, Vec3};
 use bevy_render::{
     draw::{DrawContext, Drawable, OutsideFrustum},
     mesh::Mesh,
-    prelude::{Draw, Msaa, Texture, Visible},
+    prelude::{Depth, Draw, Hdr, Msaa, Texture, Visible},
     render_graph::base::MainPass,
     renderer::RenderResourceBindings,
 };
@@ -59,6 +59,8 @@ impl Default for Text2dBundle {
 pub fn draw_text2d_system(
     mut context: DrawContext,
     msaa: Res<Msaa>,
+    depth: Res<Depth>,
+    hdr: Res<Hdr>,
     meshes: Res<Assets<Mesh>>,
     windows: Res<Windows>,
     mut render_resource_bindings: ResMut<RenderResourceBindings>,
@@ -108,6 +110,8 @@ pub fn draw_text2d_system(
                 render_resource_bindings: &mut render_resource_bindings,
                 position,
                 msaa: &msaa,
+                depth: &depth,
+                color_target_format: hdr.main_pass_format(),
                 text_glyphs: &text_glyphs.glyphs,
                 font_quad_vertex_layout: &font_quad_vertex_layout,
                 scale_factor,
@@ -128,6 +132,7 @@ pub struct QueuedText2d {
 #[allow(clippy::too_many_arguments)]
 pub fn text2d_system(
     mut queued_text: Local<QueuedText2d>,
+    mut last_scale_factor: Local<f64>,
     mut textures: ResMut<Assets<Texture>>,
     fonts: Res<Assets<Font>>,
     windows: Res<Windows>,
@@ -136,27 +141,38 @@ pub fn text2d_system(
     mut text_pipeline: ResMut<DefaultTextPipeline>,
     mut text_queries: QuerySet<(
         Query<Entity, (With<MainPass>, Changed<Text>)>,
+        Query<Entity, (With<MainPass>, With<Text>)>,
         Query<(&Text, &mut Text2dSize), With<MainPass>>,
     )>,
 ) {
-    // Adds all entities where the text or the style has changed to the local queue
-    for entity in text_queries.q0_mut().iter_mut() {
-        queued_text.entities.push(entity);
-    }
-
-    if queued_text.entities.is_empty() {
-        return;
-    }
-
     let scale_factor = if let Some(window) = windows.get_primary() {
         window.scale_factor()
     } else {
         1.
     };
 
+    #[allow(clippy::float_cmp)]
+    if *last_scale_factor == scale_factor {
+        // Adds all entities where the text has changed to the local queue
+        for entity in text_queries.q0().iter() {
+            queued_text.entities.push(entity);
+        }
+    } else {
+        // If the scale factor has changed, queue all text so glyphs are rasterized at the new
+        // resolution
+        for entity in text_queries.q1().iter() {
+            queued_text.entities.push(entity);
+        }
+        *last_scale_factor = scale_factor;
+    }
+
+    if queued_text.entities.is_empty() {
+        return;
+    }
+
     // Computes all text in the local queue
     let mut new_queue = Vec::new();
-    let query = text_queries.q1_mut();
+    let query = text_queries.q2_mut();
     for entity in queued_text.entities.drain(..) {
         if let Ok((text, mut calculated_size)) = query.get_mut(entity) {
             match text_pipeline.queue_text(
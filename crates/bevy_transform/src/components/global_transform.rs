@@ -1,6 +1,6 @@
 use super::Transform;
 use bevy_ecs::reflect::ReflectComponent;
-use bevy_math::{Mat3, Mat4, Quat, Vec3};
+use bevy_math::{AffineDecomposition, Mat3, Mat4, Quat, Vec3};
 use bevy_reflect::Reflect;
 use std::ops::Mul;
 
@@ -62,12 +62,11 @@ impl GlobalTransform {
     #[doc(hidden)]
     #[inline]
     pub fn from_matrix(matrix: Mat4) -> Self {
-        let (scale, rotation, translation) = matrix.to_scale_rotation_translation();
-
+        let decomposition = AffineDecomposition::from_matrix(&matrix);
         GlobalTransform {
-            translation,
-            rotation,
-            scale,
+            translation: decomposition.translation,
+            rotation: decomposition.rotation,
+            scale: decomposition.scale,
         }
     }
 
@@ -186,6 +185,12 @@ impl GlobalTransform {
         }
     }
 
+    /// The local [`Transform`] of an entity at `self` whose parent is at `parent`, such as when
+    /// moving the entity to a new parent without moving it in the world
+    pub fn reparented_to(&self, parent: &GlobalTransform) -> Transform {
+        Transform::from_matrix(parent.compute_matrix().inverse() * self.compute_matrix())
+    }
+
     /// Returns a [`Vec3`] of this [`Transform`] applied to `value`.
     #[inline]
     pub fn mul_vec3(&self, mut value: Vec3) -> Vec3 {
@@ -1,6 +1,6 @@
 use super::GlobalTransform;
 use bevy_ecs::reflect::ReflectComponent;
-use bevy_math::{Mat3, Mat4, Quat, Vec3};
+use bevy_math::{AffineDecomposition, Mat3, Mat4, Quat, Vec3};
 use bevy_reflect::Reflect;
 use std::ops::Mul;
 
@@ -66,15 +66,14 @@ impl Transform {
     }
 
     /// Extracts the translation, rotation, and scale from `matrix`. It must be a 3d affine
-    /// transformation matrix.
+    /// transformation matrix. Any shear, which a [`Transform`] can't represent, is dropped.
     #[inline]
     pub fn from_matrix(matrix: Mat4) -> Self {
-        let (scale, rotation, translation) = matrix.to_scale_rotation_translation();
-
+        let decomposition = AffineDecomposition::from_matrix(&matrix);
         Transform {
-            translation,
-            rotation,
-            scale,
+            translation: decomposition.translation,
+            rotation: decomposition.rotation,
+            scale: decomposition.scale,
         }
     }
 
@@ -198,6 +197,17 @@ impl Transform {
         }
     }
 
+    /// Interpolates between `self` and `other`, linearly for the translation and scale and
+    /// spherically for the rotation. `t` is 0 at `self` and 1 at `other`.
+    #[inline]
+    pub fn lerp(&self, other: Transform, t: f32) -> Self {
+        Transform {
+            translation: self.translation.lerp(other.translation, t),
+            rotation: self.rotation.slerp(other.rotation, t),
+            scale: self.scale.lerp(other.scale, t),
+        }
+    }
+
     /// Returns a [`Vec3`] of this [`Transform`] applied to `value`.
     #[inline]
     pub fn mul_vec3(&self, mut value: Vec3) -> Vec3 {
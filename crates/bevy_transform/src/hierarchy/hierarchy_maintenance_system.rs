@@ -1,8 +1,8 @@
 use crate::components::*;
 use bevy_ecs::{
-    entity::Entity,
+    entity::{Entities, Entity},
     query::Without,
-    system::{Commands, Query},
+    system::{Commands, Query, RemovedComponents},
 };
 use bevy_utils::HashMap;
 use smallvec::SmallVec;
@@ -14,7 +14,22 @@ pub fn parent_update_system(
     // modifications later in the frame are lost. See issue 891: https://github.com/bevyengine/bevy/issues/891
     mut parent_query: Query<(Entity, &Parent, Option<&mut PreviousParent>)>,
     mut children_query: Query<&mut Children>,
+    removed_parents: RemovedComponents<Parent>,
+    entities: &Entities,
 ) {
+    // Children that were despawned without `despawn_recursive` can't be looked up anymore, so
+    // remove dead entities from every `Children` list instead.
+    if removed_parents
+        .iter()
+        .any(|entity| !entities.contains(entity))
+    {
+        for mut children in children_query.iter_mut() {
+            if children.iter().any(|e| !entities.contains(*e)) {
+                children.0.retain(|e| entities.contains(*e));
+            }
+        }
+    }
+
     // Entities with a missing `Parent` (ie. ones that have a `PreviousParent`), remove
     // them from the `Children` of the `PreviousParent`.
     for (entity, previous_parent) in removed_parent_query.iter() {
@@ -167,5 +182,7 @@ mod test {
                 .collect::<Vec<_>>(),
             vec![children[1]]
         );
+
+        assert!(world.get::<Children>(children[1]).unwrap().is_empty());
     }
 }
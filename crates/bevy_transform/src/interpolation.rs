@@ -0,0 +1,115 @@
+use crate::components::Transform;
+use bevy_core::FixedTimesteps;
+use bevy_ecs::system::{Query, Res};
+
+/// Smooths the movement of an entity whose [Transform] is updated at a fixed timestep, such as a
+/// physics body, when the frame rate doesn't match the timestep.
+///
+/// The transforms set by the last two steps are kept, and the entity is rendered between them
+/// according to the time left over in the timestep's accumulator, a step behind the simulation.
+/// The simulated transform is restored at the start of each frame, so systems keep reading and
+/// writing the [Transform] as usual. Changes made outside of a step move the entity without
+/// interpolation.
+#[derive(Debug, Clone)]
+pub struct TransformInterpolation {
+    /// The label of the [FixedTimestep](bevy_core::FixedTimestep) moving the entity
+    pub timestep: String,
+    previous: Transform,
+    current: Transform,
+    steps: Option<u64>,
+}
+
+impl TransformInterpolation {
+    pub fn new(timestep: &str) -> Self {
+        Self {
+            timestep: timestep.to_string(),
+            previous: Transform::identity(),
+            current: Transform::identity(),
+            steps: None,
+        }
+    }
+
+    /// The transform set by the step before the last one
+    pub fn previous(&self) -> Transform {
+        self.previous
+    }
+
+    /// The transform set by the last step, which is the one simulated
+    pub fn current(&self) -> Transform {
+        self.current
+    }
+
+    /// Records the simulated transform after `steps` steps of the timestep, then returns the
+    /// transform to render, `overstep` of the way from the previous step to the current one
+    pub fn update(&mut self, transform: Transform, steps: u64, overstep: f32) -> Transform {
+        match self.steps {
+            Some(last_steps) if last_steps != steps => {
+                self.previous = self.current;
+                self.current = transform;
+            }
+            Some(_) if transform == self.current => {}
+            // teleported outside of a step, or just added
+            _ => {
+                self.previous = transform;
+                self.current = transform;
+            }
+        }
+        self.steps = Some(steps);
+        self.previous.lerp(self.current, overstep.max(0.0).min(1.0))
+    }
+}
+
+/// Puts back the simulated transforms of interpolated entities, before any system reads them
+pub fn transform_interpolation_restore_system(
+    mut query: Query<(&TransformInterpolation, &mut Transform)>,
+) {
+    for (interpolation, mut transform) in query.iter_mut() {
+        if interpolation.steps.is_some() && *transform != interpolation.current {
+            *transform = interpolation.current;
+        }
+    }
+}
+
+/// Replaces the transforms of interpolated entities with the ones to render, before they are
+/// propagated
+pub fn transform_interpolation_system(
+    fixed_timesteps: Option<Res<FixedTimesteps>>,
+    mut query: Query<(&mut TransformInterpolation, &mut Transform)>,
+) {
+    let fixed_timesteps = match fixed_timesteps {
+        Some(fixed_timesteps) => fixed_timesteps,
+        None => return,
+    };
+    for (mut interpolation, mut transform) in query.iter_mut() {
+        if let Some(state) = fixed_timesteps.get(&interpolation.timestep) {
+            let steps = state.steps();
+            let overstep = state.overstep_percentage() as f32;
+            *transform = interpolation.update(*transform, steps, overstep);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bevy_math::Vec3;
+
+    #[test]
+    fn interpolates_between_steps() {
+        let mut interpolation = TransformInterpolation::new("physics");
+        let mut update = |x, steps, overstep| {
+            interpolation
+                .update(Transform::from_xyz(x, 0.0, 0.0), steps, overstep)
+                .translation
+        };
+
+        assert_eq!(update(0.0, 1, 0.5), Vec3::new(0.0, 0.0, 0.0));
+        // the step moved the entity
+        assert_eq!(update(2.0, 2, 0.25), Vec3::new(0.5, 0.0, 0.0));
+        // no step this frame
+        assert_eq!(update(2.0, 2, 0.75), Vec3::new(1.5, 0.0, 0.0));
+        // moved outside of a step
+        assert_eq!(update(10.0, 2, 0.5), Vec3::new(10.0, 0.0, 0.0));
+        assert_eq!(interpolation.previous(), interpolation.current());
+    }
+}
@@ -1,9 +1,12 @@
 pub mod components;
 pub mod hierarchy;
+pub mod interpolation;
 pub mod transform_propagate_system;
 
 pub mod prelude {
-    pub use crate::{components::*, hierarchy::*, TransformPlugin};
+    pub use crate::{
+        components::*, hierarchy::*, interpolation::TransformInterpolation, TransformPlugin,
+    };
 }
 
 use bevy_app::prelude::*;
@@ -20,6 +23,7 @@ pub struct TransformPlugin;
 pub enum TransformSystem {
     TransformPropagate,
     ParentUpdate,
+    Interpolate,
 }
 
 impl Plugin for TransformPlugin {
@@ -49,6 +53,17 @@ impl Plugin for TransformPlugin {
                     .system()
                     .label(TransformSystem::ParentUpdate),
             )
+            .add_system_to_stage(
+                CoreStage::First,
+                interpolation::transform_interpolation_restore_system.system(),
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                interpolation::transform_interpolation_system
+                    .system()
+                    .label(TransformSystem::Interpolate)
+                    .before(TransformSystem::TransformPropagate),
+            )
             .add_system_to_stage(
                 CoreStage::PostUpdate,
                 transform_propagate_system::transform_propagate_system
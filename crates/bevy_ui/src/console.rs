@@ -0,0 +1,676 @@
+use crate::{
+    entity::*,
+    widget::{TextInput, TextInputSubmitted},
+    FlexDirection, KeyboardFocus, PositionType, Style, UiSystem, Val,
+};
+use bevy_app::{prelude::*, EventReader};
+use bevy_asset::Assets;
+use bevy_ecs::{
+    entity::Entity,
+    query::With,
+    schedule::{ExclusiveSystemDescriptorCoercion, ParallelSystemDescriptorCoercion},
+    system::{Commands, IntoExclusiveSystem, IntoSystem, Local, Query, Res, ResMut},
+    world::{Mut, World},
+};
+use bevy_input::{keyboard::KeyCode, Input};
+use bevy_log::{Level, LogBuffer};
+use bevy_math::{Rect, Size};
+use bevy_render::color::Color;
+use bevy_sprite::ColorMaterial;
+use bevy_text::{Text, TextSection, TextStyle};
+use bevy_transform::hierarchy::{BuildChildren, DespawnRecursiveExt};
+use bevy_utils::tracing::warn;
+use std::collections::{BTreeMap, VecDeque};
+use thiserror::Error;
+
+/// Adds a drop-down developer console, running the commands of the [ConsoleCommands] resource and
+/// mirroring the [LogBuffer]. Configured with the [DevConsole] resource.
+///
+/// While the console is open its input has the [KeyboardFocus]. `Tab` completes command names,
+/// `Up` and `Down` browse the history. Keyboard input still reaches the rest of the app. The
+/// console is drawn with the ui, so a [UiCameraBundle] must be spawned.
+#[derive(Default)]
+pub struct DevConsolePlugin;
+
+impl Plugin for DevConsolePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<DevConsole>()
+            .init_resource::<DevConsoleState>()
+            .add_console_command(ConsoleCommand::new(
+                "clear",
+                "Clears the console output",
+                |world, _| {
+                    world.get_resource_mut::<DevConsoleState>().unwrap().clear();
+                    Ok(None)
+                },
+            ))
+            .add_system_to_stage(CoreStage::PreUpdate, dev_console_toggle_system.system())
+            .add_system(dev_console_spawn_system.system())
+            .add_system(dev_console_input_system.system())
+            .add_system(dev_console_log_system.system())
+            .add_system(dev_console_execute_system.exclusive_system().at_end())
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                dev_console_text_system.system().before(UiSystem::Text),
+            );
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DevConsole {
+    pub visible: bool,
+    /// Toggles `visible` when pressed
+    pub toggle_key: Option<KeyCode>,
+    /// The style of the console's text. There is no default font, so this must be set to a loaded
+    /// font for the text to be drawn.
+    pub text_style: TextStyle,
+    /// The number of output lines shown above the input
+    pub visible_lines: usize,
+    /// The number of output lines kept
+    pub max_lines: usize,
+    /// The least important level of the log events mirrored in the output, or `None` to not
+    /// mirror the log
+    pub log_level: Option<Level>,
+}
+
+impl Default for DevConsole {
+    fn default() -> Self {
+        DevConsole {
+            visible: false,
+            toggle_key: Some(KeyCode::Grave),
+            text_style: TextStyle {
+                font: Default::default(),
+                font_size: 16.0,
+                color: Color::WHITE,
+            },
+            visible_lines: 12,
+            max_lines: 200,
+            log_level: Some(Level::INFO),
+        }
+    }
+}
+
+/// A line of the console output
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConsoleLine {
+    pub text: String,
+    /// The color of the line, or `None` for the color of [DevConsole::text_style]
+    pub color: Option<Color>,
+}
+
+/// The output, history and entered lines of the console
+#[derive(Debug, Default)]
+pub struct DevConsoleState {
+    lines: VecDeque<ConsoleLine>,
+    history: Vec<String>,
+    /// Lines entered since the last run of [dev_console_execute_system]
+    pending: Vec<String>,
+}
+
+impl DevConsoleState {
+    pub fn lines(&self) -> impl Iterator<Item = &ConsoleLine> {
+        self.lines.iter()
+    }
+
+    /// The entered lines, from the oldest
+    pub fn history(&self) -> &[String] {
+        &self.history
+    }
+
+    /// Adds a line to the output. Text containing line breaks is split into several lines.
+    pub fn print(&mut self, text: &str, color: Option<Color>) {
+        for line in text.lines() {
+            self.lines.push_back(ConsoleLine {
+                text: line.to_string(),
+                color,
+            });
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.lines.clear();
+    }
+
+    /// Queues `line` to be run as a command, like a line entered in the console
+    pub fn submit(&mut self, line: impl Into<String>) {
+        self.pending.push(line.into());
+    }
+
+    fn truncate(&mut self, max_lines: usize) {
+        while self.lines.len() > max_lines {
+            self.lines.pop_front();
+        }
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ConsoleError {
+    #[error("unknown command `{0}`, `help` lists the commands")]
+    UnknownCommand(String),
+    #[error("usage: {0}")]
+    WrongArguments(String),
+    #[error("unclosed quote")]
+    UnclosedQuote,
+    /// Returned by the handler of the command
+    #[error("{0}")]
+    Failed(String),
+}
+
+type ConsoleHandler =
+    Box<dyn Fn(&mut World, &[String]) -> Result<Option<String>, String> + Send + Sync>;
+
+/// A command of the console, running its handler with the [World] and the arguments following
+/// its name. The handler returns the text to print, or an error message.
+pub struct ConsoleCommand {
+    pub name: String,
+    pub help: String,
+    /// The names of the arguments, shown in the usage of the command
+    pub args: Vec<String>,
+    /// The number of arguments that must be given, the others are optional
+    pub required_args: usize,
+    handler: ConsoleHandler,
+}
+
+impl std::fmt::Debug for ConsoleCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConsoleCommand")
+            .field("name", &self.name)
+            .field("help", &self.help)
+            .field("args", &self.args)
+            .field("required_args", &self.required_args)
+            .finish()
+    }
+}
+
+impl ConsoleCommand {
+    pub fn new(
+        name: impl Into<String>,
+        help: impl Into<String>,
+        handler: impl Fn(&mut World, &[String]) -> Result<Option<String>, String>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        ConsoleCommand {
+            name: name.into(),
+            help: help.into(),
+            args: Vec::new(),
+            required_args: 0,
+            handler: Box::new(handler),
+        }
+    }
+
+    /// Adds a required argument. Required arguments must come before optional ones.
+    pub fn with_arg(mut self, name: impl Into<String>) -> Self {
+        assert_eq!(
+            self.required_args,
+            self.args.len(),
+            "required arguments of `{}` must come before optional ones",
+            self.name
+        );
+        self.args.push(name.into());
+        self.required_args += 1;
+        self
+    }
+
+    pub fn with_optional_arg(mut self, name: impl Into<String>) -> Self {
+        self.args.push(name.into());
+        self
+    }
+
+    /// The name of the command followed by its arguments, optional ones in brackets
+    pub fn usage(&self) -> String {
+        let mut usage = self.name.clone();
+        for (i, arg) in self.args.iter().enumerate() {
+            if i < self.required_args {
+                usage.push_str(&format!(" <{}>", arg));
+            } else {
+                usage.push_str(&format!(" [{}]", arg));
+            }
+        }
+        usage
+    }
+}
+
+/// The commands of the console, by name. `help` is built in and lists them.
+#[derive(Debug, Default)]
+pub struct ConsoleCommands {
+    commands: BTreeMap<String, ConsoleCommand>,
+}
+
+impl ConsoleCommands {
+    /// Adds `command`, replacing the command with the same name
+    pub fn add(&mut self, command: ConsoleCommand) {
+        if command.name == "help" || command.name.contains(char::is_whitespace) {
+            warn!("Console command name `{}` is not allowed.", command.name);
+            return;
+        }
+        self.commands.insert(command.name.clone(), command);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ConsoleCommand> {
+        self.commands.get(name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ConsoleCommand> {
+        self.commands.values()
+    }
+
+    /// The names of the commands starting with `prefix`, sorted
+    pub fn complete(&self, prefix: &str) -> Vec<&str> {
+        let mut names = std::iter::once("help")
+            .chain(self.commands.keys().map(|name| name.as_str()))
+            .filter(|name| name.starts_with(prefix))
+            .collect::<Vec<_>>();
+        names.sort_unstable();
+        names
+    }
+
+    fn help(&self) -> String {
+        let mut help = "help: Lists the commands".to_string();
+        for command in self.commands.values() {
+            help.push_str(&format!("\n{}: {}", command.usage(), command.help));
+        }
+        help
+    }
+
+    /// Runs the command of `line`, returning the text to print
+    pub fn run(&self, world: &mut World, line: &str) -> Result<Option<String>, ConsoleError> {
+        let mut words = parse_line(line)?.into_iter();
+        let name = match words.next() {
+            Some(name) => name,
+            None => return Ok(None),
+        };
+        if name == "help" {
+            return Ok(Some(self.help()));
+        }
+        let command = self
+            .commands
+            .get(&name)
+            .ok_or(ConsoleError::UnknownCommand(name))?;
+        let args = words.collect::<Vec<_>>();
+        if args.len() < command.required_args || args.len() > command.args.len() {
+            return Err(ConsoleError::WrongArguments(command.usage()));
+        }
+        (command.handler)(world, &args).map_err(ConsoleError::Failed)
+    }
+}
+
+/// The longest prefix shared by all `names`
+fn shared_prefix<'a>(names: &[&'a str]) -> &'a str {
+    let first = match names.first() {
+        Some(first) => *first,
+        None => return "",
+    };
+    let shared = names.iter().fold(usize::MAX, |shared, name| {
+        first
+            .chars()
+            .zip(name.chars())
+            .take_while(|(a, b)| a == b)
+            .count()
+            .min(shared)
+    });
+    // the shared length is counted in chars, which can be longer than a byte
+    let end = first
+        .char_indices()
+        .nth(shared)
+        .map_or(first.len(), |(i, _)| i);
+    &first[..end]
+}
+
+/// Splits `line` at whitespace, keeping text in double quotes together
+pub fn parse_line(line: &str) -> Result<Vec<String>, ConsoleError> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut quoted = false;
+    for c in line.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                word.get_or_insert_with(String::new);
+            }
+            c if c.is_whitespace() && !quoted => words.extend(word.take()),
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if quoted {
+        return Err(ConsoleError::UnclosedQuote);
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+pub trait AddConsoleCommand {
+    fn add_console_command(&mut self, command: ConsoleCommand) -> &mut Self;
+}
+
+impl AddConsoleCommand for AppBuilder {
+    fn add_console_command(&mut self, command: ConsoleCommand) -> &mut Self {
+        self.world_mut()
+            .get_resource_or_insert_with(ConsoleCommands::default)
+            .add(command);
+        self
+    }
+}
+
+/// The root ui node of the console
+#[derive(Debug, Default)]
+pub struct DevConsoleRoot;
+
+#[derive(Debug, Default)]
+pub struct DevConsoleOutput;
+
+#[derive(Debug, Default)]
+pub struct DevConsoleInput;
+
+pub fn dev_console_toggle_system(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut console: ResMut<DevConsole>,
+) {
+    if let Some(toggle_key) = console.toggle_key {
+        if keyboard_input.just_pressed(toggle_key) {
+            console.visible = !console.visible;
+        }
+    }
+}
+
+pub fn dev_console_spawn_system(
+    mut commands: Commands,
+    console: Res<DevConsole>,
+    mut keyboard_focus: ResMut<KeyboardFocus>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    roots: Query<Entity, With<DevConsoleRoot>>,
+    inputs: Query<Entity, With<DevConsoleInput>>,
+) {
+    if !console.is_changed() {
+        return;
+    }
+
+    for root in roots.iter() {
+        commands.entity(root).despawn_recursive();
+    }
+    if let Some(focused) = keyboard_focus.entity {
+        if inputs.get(focused).is_ok() {
+            keyboard_focus.entity = None;
+        }
+    }
+    if !console.visible {
+        return;
+    }
+
+    let mut input = None;
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(0.0),
+                    left: Val::Px(0.0),
+                    ..Default::default()
+                },
+                size: Size::new(Val::Percent(100.0), Val::Auto),
+                flex_direction: FlexDirection::ColumnReverse,
+                padding: Rect::all(Val::Px(4.0)),
+                ..Default::default()
+            },
+            material: materials.add(Color::rgba(0.0, 0.0, 0.0, 0.8).into()),
+            ..Default::default()
+        })
+        .insert(DevConsoleRoot)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle {
+                    text: Text {
+                        sections: vec![TextSection {
+                            value: String::new(),
+                            style: console.text_style.clone(),
+                        }],
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .insert(DevConsoleOutput);
+            input = Some(
+                parent
+                    .spawn_bundle(TextInputBundle {
+                        text: Text {
+                            sections: vec![TextSection {
+                                value: String::new(),
+                                style: console.text_style.clone(),
+                            }],
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    })
+                    .insert(DevConsoleInput)
+                    .id(),
+            );
+        });
+    keyboard_focus.entity = input;
+}
+
+/// Submits the entered lines, and handles completion and history in the focused console input
+pub fn dev_console_input_system(
+    keyboard_input: Res<Input<KeyCode>>,
+    keyboard_focus: Res<KeyboardFocus>,
+    commands: Res<ConsoleCommands>,
+    mut state: ResMut<DevConsoleState>,
+    mut submitted_events: EventReader<TextInputSubmitted>,
+    mut history_index: Local<Option<usize>>,
+    mut query: Query<(Entity, &mut TextInput), With<DevConsoleInput>>,
+) {
+    for event in submitted_events.iter() {
+        if let Ok((_, mut text_input)) = query.get_mut(event.entity) {
+            let line = event.value.trim().to_string();
+            text_input.set_value("");
+            *history_index = None;
+            if line.is_empty() {
+                continue;
+            }
+            state.print(&format!("> {}", line), None);
+            if state.history.last() != Some(&line) {
+                state.history.push(line.clone());
+            }
+            state.submit(line);
+        }
+    }
+
+    let (_, mut text_input) = match keyboard_focus.entity.and_then(|e| query.get_mut(e).ok()) {
+        Some(focused) => focused,
+        None => return,
+    };
+
+    if keyboard_input.just_pressed(KeyCode::Tab) && !text_input.value.contains(' ') {
+        let candidates = commands.complete(&text_input.value);
+        match candidates.as_slice() {
+            [] => {}
+            [name] => text_input.set_value(format!("{} ", name)),
+            [_, ..] => {
+                // complete the prefix shared by the candidates and list them
+                text_input.set_value(shared_prefix(&candidates));
+                state.print(&candidates.join("  "), None);
+            }
+        }
+    }
+
+    let history_len = state.history.len();
+    let index = if keyboard_input.just_pressed(KeyCode::Up) && history_len > 0 {
+        Some(history_index.map_or(history_len - 1, |index| index.saturating_sub(1)))
+    } else if keyboard_input.just_pressed(KeyCode::Down) {
+        history_index.map(|index| index + 1)
+    } else {
+        return;
+    };
+    *history_index = index.filter(|index| *index < history_len);
+    let value = history_index.map_or(String::new(), |index| state.history[index].clone());
+    text_input.set_value(value);
+}
+
+/// Mirrors the new records of the [LogBuffer] in the console output
+pub fn dev_console_log_system(
+    console: Res<DevConsole>,
+    log_buffer: Option<Res<LogBuffer>>,
+    mut cursor: Local<usize>,
+    mut state: ResMut<DevConsoleState>,
+) {
+    let log_buffer = match log_buffer {
+        Some(log_buffer) => log_buffer,
+        None => return,
+    };
+    let records = log_buffer.read(&mut cursor);
+    let log_level = match console.log_level {
+        Some(log_level) => log_level,
+        None => return,
+    };
+    for record in records {
+        // more verbose levels compare greater
+        if record.level > log_level {
+            continue;
+        }
+        let color = match record.level {
+            Level::ERROR => Some(Color::rgb(1.0, 0.3, 0.3)),
+            Level::WARN => Some(Color::rgb(1.0, 0.8, 0.2)),
+            Level::INFO => None,
+            _ => Some(Color::GRAY),
+        };
+        let text = format!("{} {}: {}", record.level, record.target, record.message);
+        state.print(&text, color);
+    }
+}
+
+/// Runs the lines submitted to the console
+pub fn dev_console_execute_system(world: &mut World) {
+    let pending = {
+        let mut state = world.get_resource_mut::<DevConsoleState>().unwrap();
+        if state.pending.is_empty() {
+            return;
+        }
+        std::mem::take(&mut state.pending)
+    };
+    world.get_resource_or_insert_with(ConsoleCommands::default);
+    world.resource_scope(|world, commands: Mut<ConsoleCommands>| {
+        for line in pending {
+            let result = commands.run(world, &line);
+            let mut state = world.get_resource_mut::<DevConsoleState>().unwrap();
+            match result {
+                Ok(Some(output)) => state.print(&output, None),
+                Ok(None) => {}
+                Err(err) => state.print(&err.to_string(), Some(Color::rgb(1.0, 0.3, 0.3))),
+            }
+        }
+    });
+}
+
+/// Shows the last lines of the console output
+pub fn dev_console_text_system(
+    console: Res<DevConsole>,
+    mut state: ResMut<DevConsoleState>,
+    mut query: Query<&mut Text, With<DevConsoleOutput>>,
+) {
+    if !state.is_changed() && !console.is_changed() {
+        return;
+    }
+    if state.lines.len() > console.max_lines {
+        state.truncate(console.max_lines);
+    }
+    let skip = state.lines.len().saturating_sub(console.visible_lines);
+    let sections = state
+        .lines
+        .iter()
+        .skip(skip)
+        .enumerate()
+        .map(|(i, line)| TextSection {
+            value: if i == 0 {
+                line.text.clone()
+            } else {
+                format!("\n{}", line.text)
+            },
+            style: TextStyle {
+                color: line.color.unwrap_or(console.text_style.color),
+                ..console.text_style.clone()
+            },
+        })
+        .collect::<Vec<_>>();
+    for mut text in query.iter_mut() {
+        text.sections = if sections.is_empty() {
+            vec![TextSection {
+                value: String::new(),
+                style: console.text_style.clone(),
+            }]
+        } else {
+            sections.clone()
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Counter(usize);
+
+    #[test]
+    fn runs_commands() {
+        let mut world = World::default();
+        world.insert_resource(Counter(0));
+        let mut commands = ConsoleCommands::default();
+        commands.add(
+            ConsoleCommand::new("add", "Adds to the counter", |world, args| {
+                let amount = match args.get(0) {
+                    Some(arg) => arg.parse::<usize>().map_err(|err| err.to_string())?,
+                    None => 1,
+                };
+                let mut counter = world.get_resource_mut::<Counter>().unwrap();
+                counter.0 += amount;
+                Ok(Some(counter.0.to_string()))
+            })
+            .with_optional_arg("amount"),
+        );
+
+        assert_eq!(commands.run(&mut world, "add"), Ok(Some("1".to_string())));
+        assert_eq!(
+            commands.run(&mut world, " add 2 "),
+            Ok(Some("3".to_string()))
+        );
+        assert_eq!(commands.run(&mut world, ""), Ok(None));
+        assert_eq!(
+            commands.run(&mut world, "add 1 2"),
+            Err(ConsoleError::WrongArguments("add [amount]".to_string()))
+        );
+        assert!(matches!(
+            commands.run(&mut world, "add x"),
+            Err(ConsoleError::Failed(_))
+        ));
+        assert_eq!(
+            commands.run(&mut world, "remove"),
+            Err(ConsoleError::UnknownCommand("remove".to_string()))
+        );
+        assert_eq!(commands.complete("a"), vec!["add"]);
+        assert_eq!(commands.complete(""), vec!["add", "help"]);
+    }
+
+    #[test]
+    fn completes_shared_prefixes_of_non_ascii_names() {
+        assert_eq!(shared_prefix(&["größe", "grün"]), "gr");
+        assert_eq!(shared_prefix(&["über", "übel"]), "übe");
+        assert_eq!(shared_prefix(&["ü", "ü"]), "ü");
+        assert_eq!(shared_prefix(&["spawn", "speed"]), "sp");
+        assert_eq!(shared_prefix(&[]), "");
+    }
+
+    #[test]
+    fn parses_quoted_arguments() {
+        assert_eq!(
+            parse_line(r#"say "hello world" """#),
+            Ok(vec![
+                "say".to_string(),
+                "hello world".to_string(),
+                String::new()
+            ])
+        );
+        assert_eq!(
+            parse_line(r#"say "hello"#),
+            Err(ConsoleError::UnclosedQuote)
+        );
+    }
+}
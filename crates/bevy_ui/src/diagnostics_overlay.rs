@@ -0,0 +1,216 @@
+use crate::{entity::*, AlignItems, FlexDirection, PositionType, Style, Val};
+use bevy_app::prelude::*;
+use bevy_asset::Assets;
+use bevy_diagnostic::{Diagnostics, FrameTimeDiagnosticsPlugin};
+use bevy_ecs::{
+    entity::Entity,
+    query::With,
+    system::{Commands, IntoSystem, Query, Res, ResMut},
+};
+use bevy_input::{keyboard::KeyCode, Input};
+use bevy_math::{Rect, Size};
+use bevy_render::color::Color;
+use bevy_sprite::ColorMaterial;
+use bevy_text::{Text, TextSection, TextStyle};
+use bevy_transform::hierarchy::{BuildChildren, DespawnRecursiveExt};
+use std::fmt::Write;
+
+/// The number of frame times shown in the frame time graph
+const FRAME_TIME_GRAPH_LEN: usize = 20;
+
+/// Adds a debug overlay that shows the average of every
+/// [Diagnostic](bevy_diagnostic::Diagnostic) in [Diagnostics], and a graph of recent frame times
+/// if [FrameTimeDiagnosticsPlugin] is added. Configured with the [DiagnosticsOverlay] resource.
+///
+/// The overlay is drawn with the ui, so a [UiCameraBundle] must be spawned.
+#[derive(Default)]
+pub struct DiagnosticsOverlayPlugin;
+
+impl Plugin for DiagnosticsOverlayPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<DiagnosticsOverlay>()
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                diagnostics_overlay_toggle_system.system(),
+            )
+            .add_system(diagnostics_overlay_spawn_system.system())
+            .add_system(diagnostics_overlay_text_system.system())
+            .add_system(diagnostics_overlay_graph_system.system());
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DiagnosticsOverlay {
+    pub visible: bool,
+    /// Toggles `visible` when pressed
+    pub toggle_key: Option<KeyCode>,
+    /// The style of the overlay's text. There is no default font, so this must be set to a loaded
+    /// font for the text to be drawn.
+    pub text_style: TextStyle,
+    /// The frame time, in seconds, shown as a full height bar in the frame time graph
+    pub graph_max_frame_time: f64,
+}
+
+impl Default for DiagnosticsOverlay {
+    fn default() -> Self {
+        DiagnosticsOverlay {
+            visible: true,
+            toggle_key: Some(KeyCode::F12),
+            text_style: TextStyle {
+                font: Default::default(),
+                font_size: 16.0,
+                color: Color::WHITE,
+            },
+            graph_max_frame_time: 1.0 / 30.0,
+        }
+    }
+}
+
+/// The root ui node of the diagnostics overlay
+#[derive(Debug, Default)]
+pub struct DiagnosticsOverlayRoot;
+
+#[derive(Debug, Default)]
+pub struct DiagnosticsOverlayText;
+
+/// A bar of the frame time graph. Bar `0` shows the oldest frame time.
+#[derive(Debug)]
+pub struct DiagnosticsOverlayBar(pub usize);
+
+pub fn diagnostics_overlay_toggle_system(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut overlay: ResMut<DiagnosticsOverlay>,
+) {
+    if let Some(toggle_key) = overlay.toggle_key {
+        if keyboard_input.just_pressed(toggle_key) {
+            overlay.visible = !overlay.visible;
+        }
+    }
+}
+
+pub fn diagnostics_overlay_spawn_system(
+    mut commands: Commands,
+    overlay: Res<DiagnosticsOverlay>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    roots: Query<Entity, With<DiagnosticsOverlayRoot>>,
+) {
+    if !overlay.is_changed() {
+        return;
+    }
+
+    for root in roots.iter() {
+        commands.entity(root).despawn_recursive();
+    }
+    if !overlay.visible {
+        return;
+    }
+
+    let background = materials.add(Color::rgba(0.0, 0.0, 0.0, 0.6).into());
+    let bar = materials.add(Color::rgb(0.2, 0.9, 0.3).into());
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(4.0),
+                    left: Val::Px(4.0),
+                    ..Default::default()
+                },
+                flex_direction: FlexDirection::ColumnReverse,
+                padding: Rect::all(Val::Px(4.0)),
+                ..Default::default()
+            },
+            material: background,
+            ..Default::default()
+        })
+        .insert(DiagnosticsOverlayRoot)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle {
+                    text: Text {
+                        sections: vec![TextSection {
+                            value: String::new(),
+                            style: overlay.text_style.clone(),
+                        }],
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .insert(DiagnosticsOverlayText);
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(FRAME_TIME_GRAPH_LEN as f32 * 4.0), Val::Px(40.0)),
+                        align_items: AlignItems::FlexEnd,
+                        margin: Rect {
+                            top: Val::Px(4.0),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                    material: materials.add(Color::NONE.into()),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    for i in 0..FRAME_TIME_GRAPH_LEN {
+                        parent
+                            .spawn_bundle(NodeBundle {
+                                style: Style {
+                                    size: Size::new(Val::Px(3.0), Val::Percent(0.0)),
+                                    margin: Rect {
+                                        right: Val::Px(1.0),
+                                        ..Default::default()
+                                    },
+                                    ..Default::default()
+                                },
+                                material: bar.clone(),
+                                ..Default::default()
+                            })
+                            .insert(DiagnosticsOverlayBar(i));
+                    }
+                });
+        });
+}
+
+pub fn diagnostics_overlay_text_system(
+    diagnostics: Res<Diagnostics>,
+    mut query: Query<&mut Text, With<DiagnosticsOverlayText>>,
+) {
+    for mut text in query.iter_mut() {
+        let value = &mut text.sections[0].value;
+        value.clear();
+        for diagnostic in diagnostics.iter() {
+            if let Some(average) = diagnostic.average() {
+                if !value.is_empty() {
+                    value.push('\n');
+                }
+                let _ = write!(
+                    value,
+                    "{}: {:.3}{}",
+                    diagnostic.name, average, diagnostic.suffix
+                );
+            }
+        }
+    }
+}
+
+pub fn diagnostics_overlay_graph_system(
+    overlay: Res<DiagnosticsOverlay>,
+    diagnostics: Res<Diagnostics>,
+    mut query: Query<(&DiagnosticsOverlayBar, &mut Style)>,
+) {
+    let frame_times = match diagnostics.get(FrameTimeDiagnosticsPlugin::FRAME_TIME) {
+        Some(frame_time) => frame_time.values().rev().collect::<Vec<_>>(),
+        None => Vec::new(),
+    };
+    for (bar, mut style) in query.iter_mut() {
+        // the newest frame time is drawn by the last bar
+        let frame_time = FRAME_TIME_GRAPH_LEN
+            .checked_sub(bar.0 + 1)
+            .and_then(|age| frame_times.get(age))
+            .copied()
+            .unwrap_or(0.0);
+        let height = (frame_time / overlay.graph_max_frame_time).min(1.0) * 100.0;
+        style.size.height = Val::Percent(height as f32);
+    }
+}
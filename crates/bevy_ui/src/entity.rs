@@ -1,7 +1,7 @@
 use super::Node;
 use crate::{
     render::UI_PIPELINE_HANDLE,
-    widget::{Button, Image},
+    widget::{Button, Image, TextInput},
     CalculatedSize, FocusPolicy, Interaction, Style,
 };
 use bevy_asset::Handle;
@@ -163,6 +163,43 @@ impl Default for ButtonBundle {
     }
 }
 
+#[derive(Bundle, Clone, Debug)]
+pub struct TextInputBundle {
+    pub node: Node,
+    pub text_input: TextInput,
+    pub style: Style,
+    pub draw: Draw,
+    pub visible: Visible,
+    /// The style of the first section is used for the whole value
+    pub text: Text,
+    pub calculated_size: CalculatedSize,
+    pub interaction: Interaction,
+    pub focus_policy: FocusPolicy,
+    pub transform: Transform,
+    pub global_transform: GlobalTransform,
+}
+
+impl Default for TextInputBundle {
+    fn default() -> Self {
+        TextInputBundle {
+            visible: Visible {
+                is_transparent: true,
+                ..Default::default()
+            },
+            text_input: Default::default(),
+            interaction: Default::default(),
+            focus_policy: Default::default(),
+            draw: Default::default(),
+            text: Default::default(),
+            node: Default::default(),
+            calculated_size: Default::default(),
+            style: Default::default(),
+            transform: Default::default(),
+            global_transform: Default::default(),
+        }
+    }
+}
+
 #[derive(Bundle, Debug)]
 pub struct UiCameraBundle {
     pub camera: Camera,
@@ -1,6 +1,6 @@
 use crate::{
     AlignContent, AlignItems, AlignSelf, Direction, Display, FlexDirection, FlexWrap,
-    JustifyContent, PositionType, Style, Val,
+    JustifyContent, Overflow, PositionType, Style, Val,
 };
 use bevy_math::{Rect, Size};
 
@@ -36,7 +36,7 @@ pub fn from_val_size(
 
 pub fn from_style(scale_factor: f64, value: &Style) -> stretch::style::Style {
     stretch::style::Style {
-        overflow: stretch::style::Overflow::Visible,
+        overflow: value.overflow.into(),
         display: value.display.into(),
         position_type: value.position_type.into(),
         direction: value.direction.into(),
@@ -153,6 +153,15 @@ impl From<JustifyContent> for stretch::style::JustifyContent {
     }
 }
 
+impl From<Overflow> for stretch::style::Overflow {
+    fn from(value: Overflow) -> Self {
+        match value {
+            Overflow::Visible => stretch::style::Overflow::Visible,
+            Overflow::Hidden => stretch::style::Overflow::Hidden,
+        }
+    }
+}
+
 impl From<PositionType> for stretch::style::PositionType {
     fn from(value: PositionType) -> Self {
         match value {
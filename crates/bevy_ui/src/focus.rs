@@ -5,6 +5,7 @@ use bevy_ecs::{
     system::{Local, Query, Res},
 };
 use bevy_input::{mouse::MouseButton, touch::Touches, Input};
+use bevy_math::Aabb2d;
 use bevy_transform::components::GlobalTransform;
 use bevy_window::Windows;
 use smallvec::SmallVec;
@@ -34,6 +35,12 @@ impl Default for FocusPolicy {
     }
 }
 
+/// The entity receiving keyboard input, such as a focused [TextInput](crate::widget::TextInput)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct KeyboardFocus {
+    pub entity: Option<Entity>,
+}
+
 #[derive(Default)]
 pub struct State {
     entities_to_reset: SmallVec<[Entity; 1]>,
@@ -89,15 +96,10 @@ pub fn ui_focus_system(
         .filter_map(
             |(entity, node, global_transform, interaction, focus_policy)| {
                 let position = global_transform.translation;
-                let ui_position = position.truncate();
-                let extents = node.size / 2.0;
-                let min = ui_position - extents;
-                let max = ui_position + extents;
+                let bounds = Aabb2d::from_center_size(position.truncate(), node.size);
                 // if the current cursor position is within the bounds of the node, consider it for
                 // clicking
-                if (min.x..max.x).contains(&cursor_position.x)
-                    && (min.y..max.y).contains(&cursor_position.y)
-                {
+                if bounds.contains_point(cursor_position) {
                     Some((entity, focus_policy, interaction, FloatOrd(position.z)))
                 } else {
                     if let Some(mut interaction) = interaction {
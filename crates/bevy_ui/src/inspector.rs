@@ -0,0 +1,835 @@
+use crate::{
+    entity::*, AlignItems, FlexDirection, Interaction, PositionType, Style, UiSystem, Val,
+};
+use bevy_app::prelude::*;
+use bevy_asset::{Assets, Handle};
+use bevy_core::Name;
+use bevy_ecs::{
+    entity::Entity,
+    query::{Changed, With},
+    reflect::ReflectComponent,
+    schedule::ParallelSystemDescriptorCoercion,
+    system::{Commands, IntoExclusiveSystem, IntoSystem, Local, Query, Res, ResMut},
+    world::{FromWorld, World},
+};
+use bevy_input::{keyboard::KeyCode, Input};
+use bevy_math::{Rect, Vec2, Vec3, Vec4};
+use bevy_reflect::{Reflect, ReflectMut, ReflectRef, TypeRegistryArc};
+use bevy_render::color::Color;
+use bevy_sprite::ColorMaterial;
+use bevy_text::{Text, TextSection, TextStyle};
+use bevy_transform::hierarchy::{BuildChildren, ChildBuilder, DespawnRecursiveExt};
+use std::{any::TypeId, fmt::Write, mem::Discriminant};
+
+/// The number of items of a list shown by the inspector
+const MAX_LIST_ITEMS: usize = 16;
+
+/// Adds a window listing the entities of the [World], showing the reflected components of the
+/// selected one and editing their numbers and booleans live. Configured with the [Inspector]
+/// resource.
+///
+/// Components are shown when their type is registered with `#[reflect(Component)]`. The inspector
+/// is drawn with the ui, so a [UiCameraBundle] must be spawned.
+#[derive(Default)]
+pub struct InspectorPlugin;
+
+impl Plugin for InspectorPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<Inspector>()
+            .init_resource::<InspectorState>()
+            .init_resource::<InspectorMaterials>()
+            .add_system_to_stage(CoreStage::PreUpdate, inspector_toggle_system.system())
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                inspector_interaction_system.system().after(UiSystem::Focus),
+            )
+            .add_system(inspector_world_system.exclusive_system())
+            .add_system(inspector_spawn_system.system())
+            .add_system(inspector_text_system.system());
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Inspector {
+    pub visible: bool,
+    /// Toggles `visible` when pressed
+    pub toggle_key: Option<KeyCode>,
+    /// The style of the inspector's text. There is no default font, so this must be set to a
+    /// loaded font for the text to be drawn.
+    pub text_style: TextStyle,
+    /// How much a click on `-` or `+` changes a floating point field
+    pub float_step: f64,
+    /// The maximum number of entities listed
+    pub max_entities: usize,
+}
+
+impl Default for Inspector {
+    fn default() -> Self {
+        Inspector {
+            visible: false,
+            toggle_key: Some(KeyCode::F11),
+            text_style: TextStyle {
+                font: Default::default(),
+                font_size: 14.0,
+                color: Color::WHITE,
+            },
+            float_step: 0.1,
+            max_entities: 40,
+        }
+    }
+}
+
+/// A step of the path from a component to one of its fields
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldPathSegment {
+    /// A named field of a struct
+    Field(String),
+    /// A field of a tuple or tuple struct, an item of a list, or an axis of a vector
+    Index(usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InspectorValue {
+    Float(f64),
+    Integer(i64),
+    Bool(bool),
+    /// A value that can't be edited by the inspector
+    Text(String),
+}
+
+impl InspectorValue {
+    pub fn is_editable(&self) -> bool {
+        !matches!(self, InspectorValue::Text(_))
+    }
+}
+
+/// A field of a component of the selected entity
+#[derive(Debug, Clone, PartialEq)]
+pub struct InspectorField {
+    /// The full type name of the component
+    pub component: String,
+    pub path: Vec<FieldPathSegment>,
+    pub value: InspectorValue,
+}
+
+impl InspectorField {
+    pub fn label(&self) -> String {
+        let mut label = self
+            .component
+            .rsplit("::")
+            .next()
+            .unwrap_or(&self.component)
+            .to_string();
+        for segment in self.path.iter() {
+            let _ = match segment {
+                FieldPathSegment::Field(name) => write!(label, ".{}", name),
+                FieldPathSegment::Index(index) => write!(label, "[{}]", index),
+            };
+        }
+        label
+    }
+
+    fn text(&self) -> String {
+        match &self.value {
+            InspectorValue::Float(value) => format!("{}: {:.3}", self.label(), value),
+            InspectorValue::Integer(value) => format!("{}: {}", self.label(), value),
+            InspectorValue::Bool(value) => format!("{}: {}", self.label(), value),
+            InspectorValue::Text(value) => format!("{}: {}", self.label(), value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldChange {
+    /// Adds a number of steps to a number
+    Step(f64),
+    Toggle,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum InspectorAction {
+    Select(Entity),
+    Edit {
+        entity: Entity,
+        field: InspectorField,
+        change: FieldChange,
+    },
+}
+
+/// What the inspector shows, updated each frame from the [World] while it is visible
+#[derive(Debug, Default)]
+pub struct InspectorState {
+    /// The listed entities, with their label
+    pub entities: Vec<(Entity, String)>,
+    pub selected: Option<Entity>,
+    /// The fields of the components of the selected entity
+    pub fields: Vec<InspectorField>,
+    actions: Vec<InspectorAction>,
+}
+
+impl InspectorState {
+    /// Selects an entity, whether it's listed or not
+    pub fn select(&mut self, entity: Entity) {
+        self.actions.push(InspectorAction::Select(entity));
+    }
+}
+
+pub struct InspectorMaterials {
+    pub background: Handle<ColorMaterial>,
+    pub button: Handle<ColorMaterial>,
+    pub selected: Handle<ColorMaterial>,
+    pub transparent: Handle<ColorMaterial>,
+}
+
+impl FromWorld for InspectorMaterials {
+    fn from_world(world: &mut World) -> Self {
+        let mut materials = world.get_resource_mut::<Assets<ColorMaterial>>().unwrap();
+        InspectorMaterials {
+            background: materials.add(Color::rgba(0.0, 0.0, 0.0, 0.7).into()),
+            button: materials.add(Color::rgb(0.2, 0.2, 0.25).into()),
+            selected: materials.add(Color::rgb(0.25, 0.4, 0.7).into()),
+            transparent: materials.add(Color::NONE.into()),
+        }
+    }
+}
+
+/// The root ui node of the inspector
+#[derive(Debug, Default)]
+pub struct InspectorRoot;
+
+/// Marks the ui nodes of the inspector, which aren't listed
+#[derive(Debug, Default)]
+pub struct InspectorNode;
+
+/// A button of the inspector, selecting an entity or editing a field of [InspectorState::fields]
+#[derive(Debug, Clone, PartialEq)]
+pub enum InspectorButton {
+    Select(Entity),
+    Edit { field: usize, change: FieldChange },
+}
+
+/// The text of the field at this index in [InspectorState::fields]
+#[derive(Debug)]
+pub struct InspectorFieldText(pub usize);
+
+fn integer_value(value: &dyn Reflect) -> Option<i64> {
+    macro_rules! downcast {
+        ($($ty:ty),*) => {
+            $(if let Some(value) = value.downcast_ref::<$ty>() {
+                return Some(*value as i64);
+            })*
+        };
+    }
+    downcast!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+    None
+}
+
+fn add_to_integer(value: &mut dyn Reflect, steps: i64) -> bool {
+    macro_rules! downcast {
+        ($($ty:ty),*) => {
+            $(if let Some(value) = value.downcast_mut::<$ty>() {
+                let sum = *value as i128 + steps as i128;
+                *value = sum.max(<$ty>::MIN as i128).min(<$ty>::MAX as i128) as $ty;
+                return true;
+            })*
+        };
+    }
+    downcast!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+    false
+}
+
+/// The axes of the vectors, which are reflected as values
+fn axes(value: &dyn Reflect) -> Option<Vec<f32>> {
+    if let Some(value) = value.downcast_ref::<Vec2>() {
+        Some(vec![value.x, value.y])
+    } else if let Some(value) = value.downcast_ref::<Vec3>() {
+        Some(vec![value.x, value.y, value.z])
+    } else if let Some(value) = value.downcast_ref::<Vec4>() {
+        Some(vec![value.x, value.y, value.z, value.w])
+    } else {
+        None
+    }
+}
+
+fn axis_mut(value: &mut dyn Reflect, index: usize) -> Option<&mut dyn Reflect> {
+    let axis = if value.is::<Vec2>() {
+        let value = value.downcast_mut::<Vec2>().unwrap();
+        match index {
+            0 => &mut value.x,
+            1 => &mut value.y,
+            _ => return None,
+        }
+    } else if value.is::<Vec3>() {
+        let value = value.downcast_mut::<Vec3>().unwrap();
+        match index {
+            0 => &mut value.x,
+            1 => &mut value.y,
+            2 => &mut value.z,
+            _ => return None,
+        }
+    } else {
+        let value = value.downcast_mut::<Vec4>()?;
+        match index {
+            0 => &mut value.x,
+            1 => &mut value.y,
+            2 => &mut value.z,
+            3 => &mut value.w,
+            _ => return None,
+        }
+    };
+    Some(axis)
+}
+
+fn value_of(value: &dyn Reflect) -> InspectorValue {
+    if let Some(value) = value.downcast_ref::<f32>() {
+        InspectorValue::Float(*value as f64)
+    } else if let Some(value) = value.downcast_ref::<f64>() {
+        InspectorValue::Float(*value)
+    } else if let Some(value) = value.downcast_ref::<bool>() {
+        InspectorValue::Bool(*value)
+    } else if let Some(value) = integer_value(value) {
+        InspectorValue::Integer(value)
+    } else if let Some(value) = value.downcast_ref::<String>() {
+        InspectorValue::Text(format!("{:?}", value))
+    } else {
+        InspectorValue::Text(value.type_name().to_string())
+    }
+}
+
+/// Lists the fields of a reflected value, down to the values that aren't structs, tuples or lists
+pub fn collect_fields(
+    value: &dyn Reflect,
+    path: &mut Vec<FieldPathSegment>,
+    fields: &mut Vec<(Vec<FieldPathSegment>, InspectorValue)>,
+) {
+    let mut field = |segment, value: &dyn Reflect, fields: &mut Vec<_>| {
+        path.push(segment);
+        collect_fields(value, path, fields);
+        path.pop();
+    };
+    match value.reflect_ref() {
+        ReflectRef::Struct(value) => {
+            for (i, field_value) in value.iter_fields().enumerate() {
+                let name = value.name_at(i).unwrap().to_string();
+                field(FieldPathSegment::Field(name), field_value, fields);
+            }
+        }
+        ReflectRef::TupleStruct(value) => {
+            for (i, field_value) in value.iter_fields().enumerate() {
+                field(FieldPathSegment::Index(i), field_value, fields);
+            }
+        }
+        ReflectRef::Tuple(value) => {
+            for (i, field_value) in value.iter_fields().enumerate() {
+                field(FieldPathSegment::Index(i), field_value, fields);
+            }
+        }
+        ReflectRef::List(value) => {
+            for (i, item) in value.iter().take(MAX_LIST_ITEMS).enumerate() {
+                field(FieldPathSegment::Index(i), item, fields);
+            }
+        }
+        ReflectRef::Map(value) => fields.push((
+            path.clone(),
+            InspectorValue::Text(format!("{} entries", value.len())),
+        )),
+        ReflectRef::Value(value) => match axes(value) {
+            Some(axes) => {
+                for (i, axis) in axes.into_iter().enumerate() {
+                    path.push(FieldPathSegment::Index(i));
+                    fields.push((path.clone(), InspectorValue::Float(axis as f64)));
+                    path.pop();
+                }
+            }
+            None => fields.push((path.clone(), value_of(value))),
+        },
+    }
+}
+
+/// Finds the field of a reflected value at the end of a path, as listed by [collect_fields]
+pub fn field_mut<'a>(
+    value: &'a mut dyn Reflect,
+    path: &[FieldPathSegment],
+) -> Option<&'a mut dyn Reflect> {
+    let (segment, rest) = match path.split_first() {
+        Some(split) => split,
+        None => return Some(value),
+    };
+    let field = match (value.reflect_mut(), segment) {
+        (ReflectMut::Struct(value), FieldPathSegment::Field(name)) => value.field_mut(name)?,
+        (ReflectMut::TupleStruct(value), FieldPathSegment::Index(i)) => value.field_mut(*i)?,
+        (ReflectMut::Tuple(value), FieldPathSegment::Index(i)) => value.field_mut(*i)?,
+        (ReflectMut::List(value), FieldPathSegment::Index(i)) => value.get_mut(*i)?,
+        (ReflectMut::Value(value), FieldPathSegment::Index(i)) => axis_mut(value, *i)?,
+        _ => return None,
+    };
+    field_mut(field, rest)
+}
+
+fn apply_change(value: &mut dyn Reflect, change: &FieldChange, float_step: f64) {
+    match change {
+        FieldChange::Step(steps) => {
+            if let Some(value) = value.downcast_mut::<f32>() {
+                *value += (steps * float_step) as f32;
+            } else if let Some(value) = value.downcast_mut::<f64>() {
+                *value += steps * float_step;
+            } else {
+                add_to_integer(value, *steps as i64);
+            }
+        }
+        FieldChange::Toggle => {
+            if let Some(value) = value.downcast_mut::<bool>() {
+                *value = !*value;
+            }
+        }
+    }
+}
+
+fn entity_label(world: &World, entity: Entity) -> String {
+    match world.get::<Name>(entity) {
+        Some(name) => format!("{} ({:?})", name.as_str(), entity),
+        None => format!("{:?}", entity),
+    }
+}
+
+fn inspected_entities(world: &World, max_entities: usize) -> Vec<Entity> {
+    let inspector_node = world.components().get_id(TypeId::of::<InspectorNode>());
+    let mut entities = world
+        .archetypes()
+        .iter()
+        .filter(|archetype| inspector_node.map_or(true, |id| !archetype.contains(id)))
+        .flat_map(|archetype| archetype.entities().iter().copied())
+        .collect::<Vec<_>>();
+    entities.sort_by_key(|entity| entity.id());
+    entities.truncate(max_entities);
+    entities
+}
+
+fn component_fields(
+    world: &World,
+    registry: &TypeRegistryArc,
+    entity: Entity,
+) -> Vec<InspectorField> {
+    let component_ids = match world.get_entity(entity) {
+        Some(entity) => entity.archetype().components().collect::<Vec<_>>(),
+        None => return Vec::new(),
+    };
+    let registry = registry.read();
+    let mut fields = Vec::new();
+    for component_id in component_ids {
+        let info = world.components().get_info(component_id).unwrap();
+        let reflected = info
+            .type_id()
+            .and_then(|type_id| registry.get(type_id))
+            .and_then(|registration| registration.data::<ReflectComponent>())
+            .and_then(|reflect_component| reflect_component.reflect_component(world, entity));
+        let component = info.name().to_string();
+        match reflected {
+            Some(reflected) => {
+                let mut component_fields = Vec::new();
+                collect_fields(reflected, &mut Vec::new(), &mut component_fields);
+                fields.extend(
+                    component_fields
+                        .into_iter()
+                        .map(|(path, value)| InspectorField {
+                            component: component.clone(),
+                            path,
+                            value,
+                        }),
+                );
+            }
+            None => fields.push(InspectorField {
+                component,
+                path: Vec::new(),
+                value: InspectorValue::Text("not reflected".to_string()),
+            }),
+        }
+    }
+    fields
+}
+
+pub fn inspector_toggle_system(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut inspector: ResMut<Inspector>,
+) {
+    if let Some(toggle_key) = inspector.toggle_key {
+        if keyboard_input.just_pressed(toggle_key) {
+            inspector.visible = !inspector.visible;
+        }
+    }
+}
+
+pub fn inspector_interaction_system(
+    mut state: ResMut<InspectorState>,
+    buttons: Query<(&Interaction, &InspectorButton), Changed<Interaction>>,
+) {
+    for (interaction, button) in buttons.iter() {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+        let action = match button {
+            InspectorButton::Select(entity) => InspectorAction::Select(*entity),
+            InspectorButton::Edit { field, change } => {
+                match (state.selected, state.fields.get(*field)) {
+                    (Some(entity), Some(field)) => InspectorAction::Edit {
+                        entity,
+                        field: field.clone(),
+                        change: change.clone(),
+                    },
+                    _ => continue,
+                }
+            }
+        };
+        state.actions.push(action);
+    }
+}
+
+/// Applies the edits made in the inspector, and reads the entities and fields it shows
+pub fn inspector_world_system(world: &mut World) {
+    let (visible, float_step, max_entities) = {
+        let inspector = world.get_resource::<Inspector>().unwrap();
+        (
+            inspector.visible,
+            inspector.float_step,
+            inspector.max_entities,
+        )
+    };
+    let registry = world.get_resource::<TypeRegistryArc>().unwrap().clone();
+    let actions = std::mem::take(&mut world.get_resource_mut::<InspectorState>().unwrap().actions);
+    let mut selected = world.get_resource::<InspectorState>().unwrap().selected;
+    for action in actions {
+        match action {
+            InspectorAction::Select(entity) => selected = Some(entity),
+            InspectorAction::Edit {
+                entity,
+                field,
+                change,
+            } => {
+                let registry = registry.read();
+                let reflect_component = registry
+                    .get_with_name(&field.component)
+                    .and_then(|registration| registration.data::<ReflectComponent>());
+                if let Some(mut component) = reflect_component.and_then(|reflect_component| {
+                    reflect_component.reflect_component_mut(world, entity)
+                }) {
+                    if let Some(value) = field_mut(&mut *component, &field.path) {
+                        apply_change(value, &change, float_step);
+                    }
+                }
+            }
+        }
+    }
+    if !visible {
+        return;
+    }
+
+    let entities = inspected_entities(world, max_entities)
+        .into_iter()
+        .map(|entity| (entity, entity_label(world, entity)))
+        .collect::<Vec<_>>();
+    let selected = selected.filter(|entity| world.get_entity(*entity).is_some());
+    let fields = selected
+        .map(|entity| component_fields(world, &registry, entity))
+        .unwrap_or_default();
+
+    // only the changed parts are set, to only update the ui when the world changed
+    let mut state = world.get_resource_mut::<InspectorState>().unwrap();
+    if state.entities != entities {
+        state.entities = entities;
+    }
+    if state.selected != selected {
+        state.selected = selected;
+    }
+    if state.fields != fields {
+        state.fields = fields;
+    }
+}
+
+/// What the ui of the inspector was spawned for. It's spawned again when this changes, and only
+/// its text is updated otherwise.
+#[derive(Debug, Default, PartialEq)]
+pub struct InspectorLayout {
+    visible: bool,
+    entities: Vec<(Entity, String)>,
+    selected: Option<Entity>,
+    fields: Vec<(String, Discriminant<InspectorValue>)>,
+}
+
+fn spawn_text(parent: &mut ChildBuilder, value: String, style: &TextStyle) {
+    parent
+        .spawn_bundle(TextBundle {
+            text: Text {
+                sections: vec![TextSection {
+                    value,
+                    style: style.clone(),
+                }],
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(InspectorNode);
+}
+
+fn spawn_button(
+    parent: &mut ChildBuilder,
+    button: InspectorButton,
+    label: String,
+    material: Handle<ColorMaterial>,
+    style: &TextStyle,
+) {
+    parent
+        .spawn_bundle(ButtonBundle {
+            style: Style {
+                padding: Rect::all(Val::Px(2.0)),
+                margin: Rect::all(Val::Px(1.0)),
+                ..Default::default()
+            },
+            material,
+            ..Default::default()
+        })
+        .insert_bundle((button, InspectorNode))
+        .with_children(|parent| spawn_text(parent, label, style));
+}
+
+fn spawn_column(
+    parent: &mut ChildBuilder,
+    materials: &InspectorMaterials,
+    children: impl FnOnce(&mut ChildBuilder),
+) {
+    parent
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::ColumnReverse,
+                align_items: AlignItems::FlexStart,
+                margin: Rect::all(Val::Px(4.0)),
+                ..Default::default()
+            },
+            material: materials.transparent.clone(),
+            ..Default::default()
+        })
+        .insert(InspectorNode)
+        .with_children(children);
+}
+
+pub fn inspector_spawn_system(
+    mut commands: Commands,
+    inspector: Res<Inspector>,
+    state: Res<InspectorState>,
+    materials: Res<InspectorMaterials>,
+    mut layout: Local<InspectorLayout>,
+    roots: Query<Entity, With<InspectorRoot>>,
+) {
+    let new_layout = InspectorLayout {
+        visible: inspector.visible,
+        entities: state.entities.clone(),
+        selected: state.selected,
+        fields: state
+            .fields
+            .iter()
+            .map(|field| (field.label(), std::mem::discriminant(&field.value)))
+            .collect(),
+    };
+    if !inspector.is_changed() && *layout == new_layout {
+        return;
+    }
+    *layout = new_layout;
+
+    for root in roots.iter() {
+        commands.entity(root).despawn_recursive();
+    }
+    if !inspector.visible {
+        return;
+    }
+
+    let style = &inspector.text_style;
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(4.0),
+                    right: Val::Px(4.0),
+                    ..Default::default()
+                },
+                align_items: AlignItems::FlexStart,
+                padding: Rect::all(Val::Px(4.0)),
+                ..Default::default()
+            },
+            material: materials.background.clone(),
+            ..Default::default()
+        })
+        .insert_bundle((InspectorRoot, InspectorNode))
+        .with_children(|parent| {
+            spawn_column(parent, &materials, |parent| {
+                spawn_text(parent, "Entities".to_string(), style);
+                for (entity, label) in state.entities.iter() {
+                    let material = if state.selected == Some(*entity) {
+                        materials.selected.clone()
+                    } else {
+                        materials.button.clone()
+                    };
+                    let button = InspectorButton::Select(*entity);
+                    spawn_button(parent, button, label.clone(), material, style);
+                }
+            });
+
+            let selected = match state.selected {
+                Some(selected) => selected,
+                None => return,
+            };
+            spawn_column(parent, &materials, |parent| {
+                spawn_text(parent, format!("Components of {:?}", selected), style);
+                for (i, field) in state.fields.iter().enumerate() {
+                    parent
+                        .spawn_bundle(NodeBundle {
+                            style: Style {
+                                align_items: AlignItems::Center,
+                                ..Default::default()
+                            },
+                            material: materials.transparent.clone(),
+                            ..Default::default()
+                        })
+                        .insert(InspectorNode)
+                        .with_children(|parent| {
+                            let changes = match field.value {
+                                InspectorValue::Float(_) | InspectorValue::Integer(_) => vec![
+                                    (FieldChange::Step(-1.0), "-"),
+                                    (FieldChange::Step(1.0), "+"),
+                                ],
+                                InspectorValue::Bool(_) => vec![(FieldChange::Toggle, "toggle")],
+                                InspectorValue::Text(_) => Vec::new(),
+                            };
+                            for (change, label) in changes {
+                                let button = InspectorButton::Edit { field: i, change };
+                                let material = materials.button.clone();
+                                spawn_button(parent, button, label.to_string(), material, style);
+                            }
+                            parent
+                                .spawn_bundle(TextBundle {
+                                    text: Text {
+                                        sections: vec![TextSection {
+                                            value: field.text(),
+                                            style: style.clone(),
+                                        }],
+                                        ..Default::default()
+                                    },
+                                    style: Style {
+                                        margin: Rect {
+                                            left: Val::Px(4.0),
+                                            ..Default::default()
+                                        },
+                                        ..Default::default()
+                                    },
+                                    ..Default::default()
+                                })
+                                .insert_bundle((InspectorFieldText(i), InspectorNode));
+                        });
+                }
+            });
+        });
+}
+
+pub fn inspector_text_system(
+    state: Res<InspectorState>,
+    mut query: Query<(&InspectorFieldText, &mut Text)>,
+) {
+    if !state.is_changed() {
+        return;
+    }
+    for (field_text, mut text) in query.iter_mut() {
+        if let Some(field) = state.fields.get(field_text.0) {
+            let value = field.text();
+            if text.sections[0].value != value {
+                text.sections[0].value = value;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_reflect::Reflect;
+
+    #[derive(Reflect, Default)]
+    #[reflect(Component)]
+    struct Player {
+        position: Vec3,
+        lives: u8,
+        invincible: bool,
+        speeds: Vec<f32>,
+    }
+
+    #[test]
+    fn edits_fields_of_selected_entity() {
+        let registry = TypeRegistryArc::default();
+        {
+            let mut registry = registry.write();
+            registry.register::<Player>();
+        }
+        let mut world = World::new();
+        world.insert_resource(registry);
+        world.insert_resource(Inspector {
+            visible: true,
+            ..Default::default()
+        });
+        world.insert_resource(InspectorState::default());
+        let player = world
+            .spawn()
+            .insert(Player {
+                lives: 255,
+                speeds: vec![1.0, 2.0],
+                ..Default::default()
+            })
+            .id();
+
+        world
+            .get_resource_mut::<InspectorState>()
+            .unwrap()
+            .select(player);
+        inspector_world_system(&mut world);
+        let state = world.get_resource::<InspectorState>().unwrap();
+        assert_eq!(state.entities.len(), 1);
+        assert_eq!(state.selected, Some(player));
+        let labels = state
+            .fields
+            .iter()
+            .map(|field| field.label())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            labels,
+            vec![
+                "Player.position[0]",
+                "Player.position[1]",
+                "Player.position[2]",
+                "Player.lives",
+                "Player.invincible",
+                "Player.speeds[0]",
+                "Player.speeds[1]",
+            ]
+        );
+
+        let edits = vec![
+            (1, FieldChange::Step(2.0)),
+            (3, FieldChange::Step(1.0)),
+            (4, FieldChange::Toggle),
+            (6, FieldChange::Step(-10.0)),
+        ];
+        let mut state = world.get_resource_mut::<InspectorState>().unwrap();
+        for (field, change) in edits {
+            let field = state.fields[field].clone();
+            state.actions.push(InspectorAction::Edit {
+                entity: player,
+                field,
+                change,
+            });
+        }
+        inspector_world_system(&mut world);
+
+        let player = world.get::<Player>(player).unwrap();
+        assert!((player.position.y - 0.2).abs() < 1e-6);
+        assert_eq!(player.lives, 255);
+        assert!(player.invincible);
+        assert!((player.speeds[1] - 1.0).abs() < 1e-6);
+    }
+}
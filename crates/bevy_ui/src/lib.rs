@@ -1,6 +1,9 @@
 mod anchors;
+mod console;
+mod diagnostics_overlay;
 mod flex;
 mod focus;
+mod inspector;
 mod margins;
 mod render;
 mod ui_node;
@@ -10,14 +13,22 @@ pub mod update;
 pub mod widget;
 
 pub use anchors::*;
+pub use console::*;
+pub use diagnostics_overlay::*;
 pub use flex::*;
 pub use focus::*;
+pub use inspector::*;
 pub use margins::*;
 pub use render::*;
 pub use ui_node::*;
 
 pub mod prelude {
-    pub use crate::{entity::*, ui_node::*, widget::Button, Anchors, Interaction, Margins};
+    pub use crate::{
+        entity::*,
+        ui_node::*,
+        widget::{Button, TextInput, TextInputSubmitted},
+        Anchors, Interaction, KeyboardFocus, Margins,
+    };
 }
 
 use bevy_app::prelude::*;
@@ -27,7 +38,7 @@ use bevy_ecs::{
 };
 use bevy_input::InputSystem;
 use bevy_math::{Rect, Size};
-use bevy_render::RenderStage;
+use bevy_render::{RenderStage, RenderSystem};
 use bevy_transform::TransformSystem;
 use update::ui_z_system;
 
@@ -39,11 +50,17 @@ pub enum UiSystem {
     /// After this label, the ui flex state has been updated
     Flex,
     Focus,
+    /// After this label, the glyphs of ui text have been queued
+    Text,
+    /// Records the [Draw](bevy_render::draw::Draw) commands of ui nodes
+    Draw,
 }
 
 impl Plugin for UiPlugin {
     fn build(&self, app: &mut AppBuilder) {
         app.init_resource::<FlexSurface>()
+            .init_resource::<KeyboardFocus>()
+            .add_event::<widget::TextInputSubmitted>()
             .register_type::<AlignContent>()
             .register_type::<AlignItems>()
             .register_type::<AlignSelf>()
@@ -53,6 +70,7 @@ impl Plugin for UiPlugin {
             .register_type::<FlexWrap>()
             .register_type::<JustifyContent>()
             .register_type::<Node>()
+            .register_type::<Overflow>()
             .register_type::<PositionType>()
             .register_type::<Size<f32>>()
             .register_type::<Size<Val>>()
@@ -66,10 +84,23 @@ impl Plugin for UiPlugin {
                     .label(UiSystem::Focus)
                     .after(InputSystem),
             )
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                widget::text_input_system.system().after(UiSystem::Focus),
+            )
             // add these stages to front because these must run before transform update systems
             .add_system_to_stage(
                 CoreStage::PostUpdate,
-                widget::text_system.system().before(UiSystem::Flex),
+                widget::text_input_text_system
+                    .system()
+                    .before(UiSystem::Text),
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                widget::text_system
+                    .system()
+                    .label(UiSystem::Text)
+                    .before(UiSystem::Flex),
             )
             .add_system_to_stage(
                 CoreStage::PostUpdate,
@@ -89,7 +120,23 @@ impl Plugin for UiPlugin {
                     .after(UiSystem::Flex)
                     .before(TransformSystem::TransformPropagate),
             )
-            .add_system_to_stage(RenderStage::Draw, widget::draw_text_system.system());
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                update::update_clipping_system
+                    .system()
+                    .after(TransformSystem::TransformPropagate),
+            )
+            .add_system_to_stage(
+                RenderStage::Draw,
+                widget::draw_text_system.system().label(UiSystem::Draw),
+            )
+            .add_system_to_stage(
+                RenderStage::Draw,
+                render::draw_ui_clip_system
+                    .system()
+                    .after(RenderSystem::Draw)
+                    .after(UiSystem::Draw),
+            );
 
         crate::render::add_ui_graph(app.world_mut());
     }
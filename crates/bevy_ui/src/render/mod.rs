@@ -1,15 +1,20 @@
-use crate::Node;
+use crate::{CalculatedClip, Node};
 use bevy_asset::{Assets, HandleUntyped};
-use bevy_ecs::world::World;
+use bevy_ecs::{
+    query::With,
+    system::{Query, Res},
+    world::World,
+};
 use bevy_reflect::TypeUuid;
 use bevy_render::{
     camera::ActiveCameras,
+    draw::{Draw, RenderCommand},
     pass::{
         LoadOp, Operations, PassDescriptor, RenderPassDepthStencilAttachmentDescriptor,
         TextureAttachment,
     },
     pipeline::*,
-    prelude::Msaa,
+    prelude::{Depth, Hdr, Msaa},
     render_graph::{
         base, CameraNode, PassNode, RenderGraph, RenderResourcesNode, WindowSwapChainNode,
         WindowTextureNode,
@@ -17,6 +22,8 @@ use bevy_render::{
     shader::{Shader, ShaderStage, ShaderStages},
     texture::TextureFormat,
 };
+use bevy_sprite::NINE_SLICE_GLSL;
+use bevy_window::Windows;
 
 pub const UI_PIPELINE_HANDLE: HandleUntyped =
     HandleUntyped::weak_from_u64(PipelineDescriptor::TYPE_UUID, 3234320022263993878);
@@ -27,12 +34,7 @@ pub fn build_ui_pipeline(shaders: &mut Assets<Shader>) -> PipelineDescriptor {
             format: TextureFormat::Depth32Float,
             depth_write_enabled: true,
             depth_compare: CompareFunction::Less,
-            stencil: StencilState {
-                front: StencilFaceState::IGNORE,
-                back: StencilFaceState::IGNORE,
-                read_mask: 0,
-                write_mask: 0,
-            },
+            stencil: StencilState::IGNORE,
             bias: DepthBiasState {
                 constant: 0,
                 slope_scale: 0.0,
@@ -40,28 +42,20 @@ pub fn build_ui_pipeline(shaders: &mut Assets<Shader>) -> PipelineDescriptor {
             },
             clamp_depth: false,
         }),
-        color_target_states: vec![ColorTargetState {
-            format: TextureFormat::default(),
-            color_blend: BlendState {
-                src_factor: BlendFactor::SrcAlpha,
-                dst_factor: BlendFactor::OneMinusSrcAlpha,
-                operation: BlendOperation::Add,
-            },
-            alpha_blend: BlendState {
-                src_factor: BlendFactor::One,
-                dst_factor: BlendFactor::One,
-                operation: BlendOperation::Add,
-            },
-            write_mask: ColorWrite::ALL,
-        }],
+        color_target_states: vec![ColorTargetState::new(
+            TextureFormat::default(),
+            BlendMode::Alpha,
+        )],
         ..PipelineDescriptor::new(ShaderStages {
             vertex: shaders.add(Shader::from_glsl(
                 ShaderStage::Vertex,
                 include_str!("ui.vert"),
             )),
-            fragment: Some(shaders.add(Shader::from_glsl(
+            fragment: Some(shaders.add(Shader::from_glsl_with_includes(
                 ShaderStage::Fragment,
+                "bevy_ui/ui.frag",
                 include_str!("ui.frag"),
+                &[NINE_SLICE_GLSL],
             ))),
         })
     }
@@ -86,6 +80,8 @@ pub(crate) fn add_ui_graph(world: &mut World) {
     let mut shaders = world.get_resource_mut::<Assets<Shader>>().unwrap();
     let mut active_cameras = world.get_resource_mut::<ActiveCameras>().unwrap();
     let msaa = world.get_resource::<Msaa>().unwrap();
+    let hdr = world.get_resource::<Hdr>().unwrap();
+    let depth = world.get_resource::<Depth>().unwrap();
 
     pipelines.set_untracked(UI_PIPELINE_HANDLE, build_ui_pipeline(&mut shaders));
 
@@ -101,10 +97,10 @@ pub(crate) fn add_ui_graph(world: &mut World) {
         depth_stencil_attachment: Some(RenderPassDepthStencilAttachmentDescriptor {
             attachment: TextureAttachment::Input("depth".to_string()),
             depth_ops: Some(Operations {
-                load: LoadOp::Clear(1.0),
+                load: LoadOp::Clear(depth.clear_depth()),
                 store: true,
             }),
-            stencil_ops: None,
+            stencil_ops: depth.stencil_ops(),
         }),
         sample_count: msaa.samples,
     });
@@ -145,10 +141,19 @@ pub(crate) fn add_ui_graph(world: &mut World) {
             .unwrap();
     }
 
-    // ensure ui pass runs after main pass
-    graph
-        .add_node_edge(base::node::MAIN_PASS, node::UI_PASS)
-        .unwrap();
+    // ensure ui pass runs after main pass, if there is one
+    if graph.get_node_id(base::node::MAIN_PASS).is_ok() {
+        graph
+            .add_node_edge(base::node::MAIN_PASS, node::UI_PASS)
+            .unwrap();
+    }
+    // the ui is drawn directly to the swap chain, on top of the tonemapped main pass. Without a
+    // main pass, there is nothing to tonemap
+    if hdr.enabled && graph.get_node_id(base::node::TONEMAPPING).is_ok() {
+        graph
+            .add_node_edge(base::node::TONEMAPPING, node::UI_PASS)
+            .unwrap();
+    }
 
     // setup ui camera
     graph.add_system_node(node::CAMERA_UI, CameraNode::new(camera::CAMERA_UI));
@@ -157,3 +162,49 @@ pub(crate) fn add_ui_graph(world: &mut World) {
     graph.add_node_edge(node::NODE, node::UI_PASS).unwrap();
     active_cameras.add(camera::CAMERA_UI);
 }
+
+/// Restricts the draws of every node to its [CalculatedClip], and those of unclipped nodes to the
+/// window, as the scissor rect of the ui pass is shared by all nodes
+pub fn draw_ui_clip_system(
+    windows: Res<Windows>,
+    mut query: Query<(&mut Draw, Option<&CalculatedClip>), With<Node>>,
+) {
+    let window = match windows.get_primary() {
+        Some(window) => window,
+        None => return,
+    };
+    let scale_factor = window.scale_factor() as f32;
+    let (width, height) = (window.physical_width(), window.physical_height());
+    for (mut draw, clip) in query.iter_mut() {
+        let (x, y, clip_width, clip_height) = match clip {
+            Some(clip) => {
+                // ui coordinates start at the bottom left of the window, scissor rects at the top
+                // left
+                let min = (clip.clip.min * scale_factor).round();
+                let max = (clip.clip.max * scale_factor).round();
+                let x = (min.x.max(0.0) as u32).min(width);
+                let y = ((height as f32 - max.y).max(0.0) as u32).min(height);
+                let right = (max.x.max(0.0) as u32).min(width);
+                let bottom = ((height as f32 - min.y).max(0.0) as u32).min(height);
+                if right <= x || bottom <= y {
+                    draw.clear_render_commands();
+                    continue;
+                }
+                (x, y, right - x, bottom - y)
+            }
+            None => (0, 0, width, height),
+        };
+        if draw.render_commands.is_empty() {
+            continue;
+        }
+        draw.render_commands.insert(
+            0,
+            RenderCommand::SetScissorRect {
+                x,
+                y,
+                width: clip_width,
+                height: clip_height,
+            },
+        );
+    }
+}
@@ -5,6 +5,7 @@ layout(location = 1) in vec3 Vertex_Normal;
 layout(location = 2) in vec2 Vertex_Uv;
 
 layout(location = 0) out vec2 v_Uv;
+layout(location = 1) out vec2 v_Size;
 
 layout(set = 0, binding = 0) uniform CameraViewProj {
     mat4 ViewProj;
@@ -19,6 +20,7 @@ layout(set = 1, binding = 1) uniform Node_size {
 
 void main() {
     v_Uv = Vertex_Uv;
+    v_Size = NodeSize * vec2(length(Object[0].xyz), length(Object[1].xyz));
     vec3 position = Vertex_Position * vec3(NodeSize, 0.0);
     gl_Position = ViewProj * Object * vec4(position, 1.0);
 }
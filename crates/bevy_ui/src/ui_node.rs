@@ -1,5 +1,5 @@
 use bevy_ecs::reflect::ReflectComponent;
-use bevy_math::{Rect, Size, Vec2};
+use bevy_math::{Aabb2d, Rect, Size, Vec2};
 use bevy_reflect::{Reflect, ReflectDeserialize};
 use bevy_render::renderer::RenderResources;
 use serde::{Deserialize, Serialize};
@@ -72,6 +72,7 @@ pub struct Style {
     pub min_size: Size<Val>,
     pub max_size: Size<Val>,
     pub aspect_ratio: Option<f32>,
+    pub overflow: Overflow,
 }
 
 impl Default for Style {
@@ -97,6 +98,7 @@ impl Default for Style {
             min_size: Size::new(Val::Auto, Val::Auto),
             max_size: Size::new(Val::Auto, Val::Auto),
             aspect_ratio: Default::default(),
+            overflow: Default::default(),
         }
     }
 }
@@ -210,19 +212,20 @@ impl Default for JustifyContent {
     }
 }
 
-// TODO: add support for overflow settings
-// #[derive(Copy, Clone, PartialEq, Debug)]
-// pub enum Overflow {
-//     Visible,
-//     Hidden,
-//     Scroll,
-// }
+/// Whether the children of a node can be seen outside of it
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize, Reflect)]
+#[reflect_value(PartialEq, Serialize, Deserialize)]
+pub enum Overflow {
+    Visible,
+    /// Clips the children to the node, such as for scroll views and masked panels
+    Hidden,
+}
 
-// impl Default for Overflow {
-//     fn default() -> Overflow {
-//         Overflow::Visible
-//     }
-// }
+impl Default for Overflow {
+    fn default() -> Overflow {
+        Overflow::Visible
+    }
+}
 
 #[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize, Reflect)]
 #[reflect_value(PartialEq, Serialize, Deserialize)]
@@ -255,3 +258,10 @@ impl Default for FlexWrap {
 pub struct CalculatedSize {
     pub size: Size,
 }
+
+/// The area a node is visible in, in the same logical pixels as its [Node::size]. Set on the
+/// descendants of nodes with [Overflow::Hidden].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CalculatedClip {
+    pub clip: Aabb2d,
+}
@@ -1,10 +1,11 @@
-use super::Node;
+use super::{CalculatedClip, Node, Overflow, Style};
 use bevy_ecs::{
     entity::Entity,
     query::{With, Without},
-    system::Query,
+    system::{Commands, Query},
 };
-use bevy_transform::prelude::{Children, Parent, Transform};
+use bevy_math::Aabb2d;
+use bevy_transform::prelude::{Children, GlobalTransform, Parent, Transform};
 
 pub const UI_Z_STEP: f32 = 0.001;
 
@@ -50,6 +51,78 @@ fn update_hierarchy(
     }
     current_global_z
 }
+/// Updates the [CalculatedClip] of the descendants of nodes with [Overflow::Hidden]
+pub fn update_clipping_system(
+    mut commands: Commands,
+    root_node_query: Query<Entity, (With<Node>, Without<Parent>)>,
+    mut node_query: Query<(
+        &Node,
+        &GlobalTransform,
+        Option<&Style>,
+        Option<&mut CalculatedClip>,
+    )>,
+    children_query: Query<&Children>,
+) {
+    for root_node in root_node_query.iter() {
+        update_clipping(
+            &mut commands,
+            &children_query,
+            &mut node_query,
+            root_node,
+            None,
+        );
+    }
+}
+
+fn update_clipping(
+    commands: &mut Commands,
+    children_query: &Query<&Children>,
+    node_query: &mut Query<(
+        &Node,
+        &GlobalTransform,
+        Option<&Style>,
+        Option<&mut CalculatedClip>,
+    )>,
+    entity: Entity,
+    clip: Option<Aabb2d>,
+) {
+    let children_clip = match node_query.get_mut(entity) {
+        Ok((node, global_transform, style, calculated_clip)) => {
+            match (clip, calculated_clip) {
+                (None, None) => {}
+                (None, Some(_)) => {
+                    commands.entity(entity).remove::<CalculatedClip>();
+                }
+                (Some(clip), None) => {
+                    commands.entity(entity).insert(CalculatedClip { clip });
+                }
+                (Some(clip), Some(mut calculated_clip)) => {
+                    if calculated_clip.clip != clip {
+                        calculated_clip.clip = clip;
+                    }
+                }
+            }
+
+            if style.map_or(false, |style| style.overflow == Overflow::Hidden) {
+                let bounds = Aabb2d::from_center_size(
+                    global_transform.translation.truncate(),
+                    node.size * global_transform.scale.truncate(),
+                );
+                Some(clip.map_or(bounds, |clip| clip.intersection(&bounds)))
+            } else {
+                clip
+            }
+        }
+        Err(_) => clip,
+    };
+
+    if let Ok(children) = children_query.get(entity) {
+        for child in children.iter().cloned() {
+            update_clipping(commands, children_query, node_query, child, children_clip);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use bevy_ecs::{
@@ -57,11 +130,15 @@ mod tests {
         system::{CommandQueue, Commands, IntoSystem},
         world::World,
     };
-    use bevy_transform::{components::Transform, hierarchy::BuildChildren};
+    use bevy_math::{Aabb2d, Vec2};
+    use bevy_transform::{
+        components::{GlobalTransform, Transform},
+        hierarchy::BuildChildren,
+    };
 
-    use crate::Node;
+    use crate::{CalculatedClip, Node, Overflow, Style};
 
-    use super::{ui_z_system, UI_Z_STEP};
+    use super::{ui_z_system, update_clipping_system, UI_Z_STEP};
 
     fn node_with_transform(name: &str) -> (String, Node, Transform) {
         (name.to_owned(), Node::default(), Transform::identity())
@@ -153,4 +230,69 @@ mod tests {
         ];
         assert_eq!(actual_result, expected_result);
     }
+
+    fn clipping_node(x: f32, size: f32) -> (Node, GlobalTransform, Style) {
+        (
+            Node {
+                size: Vec2::splat(size),
+            },
+            GlobalTransform::from_xyz(x, 0.0, 0.0),
+            Style {
+                overflow: Overflow::Hidden,
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn test_update_clipping_system() {
+        let mut world = World::default();
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, &world);
+        let mut inner = None;
+        let mut leaf = None;
+        let root = commands
+            .spawn_bundle(clipping_node(0.0, 100.0))
+            .with_children(|parent| {
+                inner = Some(
+                    parent
+                        .spawn_bundle(clipping_node(40.0, 50.0))
+                        .with_children(|parent| {
+                            leaf = Some(parent.spawn_bundle(clipping_node(40.0, 10.0)).id());
+                        })
+                        .id(),
+                );
+            })
+            .id();
+        queue.apply(&mut world);
+        let (inner, leaf) = (inner.unwrap(), leaf.unwrap());
+
+        let mut schedule = Schedule::default();
+        let mut update_stage = SystemStage::parallel();
+        update_stage.add_system(update_clipping_system.system());
+        schedule.add_stage("update", update_stage);
+        schedule.run(&mut world);
+
+        let clip = |world: &World, entity| world.get::<CalculatedClip>(entity).map(|c| c.clip);
+        assert_eq!(clip(&world, root), None);
+        assert_eq!(
+            clip(&world, inner),
+            Some(Aabb2d::from_center_size(Vec2::ZERO, Vec2::splat(100.0)))
+        );
+        assert_eq!(
+            clip(&world, leaf),
+            Some(Aabb2d {
+                min: Vec2::new(15.0, -25.0),
+                max: Vec2::new(50.0, 25.0),
+            })
+        );
+
+        world.get_mut::<Style>(root).unwrap().overflow = Overflow::Visible;
+        schedule.run(&mut world);
+        assert_eq!(clip(&world, inner), None);
+        assert_eq!(
+            clip(&world, leaf),
+            Some(Aabb2d::from_center_size(Vec2::X * 40.0, Vec2::splat(50.0)))
+        );
+    }
 }
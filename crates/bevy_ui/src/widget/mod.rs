@@ -1,7 +1,9 @@
 mod button;
 mod image;
 mod text;
+mod text_input;
 
 pub use button::*;
 pub use image::*;
 pub use text::*;
+pub use text_input::*;
@@ -9,7 +9,7 @@ use bevy_math::Size;
 use bevy_render::{
     draw::{Draw, DrawContext, Drawable, OutsideFrustum},
     mesh::Mesh,
-    prelude::{Msaa, Visible},
+    prelude::{Depth, Msaa, Visible},
     renderer::RenderResourceBindings,
     texture::Texture,
 };
@@ -143,6 +143,7 @@ pub fn text_system(
 pub fn draw_text_system(
     mut context: DrawContext,
     msaa: Res<Msaa>,
+    depth: Res<Depth>,
     windows: Res<Windows>,
     meshes: Res<Assets<Mesh>>,
     mut render_resource_bindings: ResMut<RenderResourceBindings>,
@@ -174,6 +175,8 @@ pub fn draw_text_system(
                 position,
                 scale_factor: scale_factor as f32,
                 msaa: &msaa,
+                depth: &depth,
+                color_target_format: None,
                 text_glyphs: &text_glyphs.glyphs,
                 font_quad_vertex_layout: &vertex_buffer_layout,
                 sections: &text.sections,
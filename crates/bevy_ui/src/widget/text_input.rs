@@ -0,0 +1,336 @@
+use crate::{Interaction, KeyboardFocus, Node};
+use bevy_app::{EventReader, EventWriter};
+use bevy_ecs::{
+    entity::Entity,
+    query::{Changed, With},
+    system::{Query, Res, ResMut},
+};
+use bevy_input::{keyboard::KeyCode, mouse::MouseButton, Input};
+use bevy_math::Vec2;
+use bevy_render::color::Color;
+use bevy_text::{Text, TextSection};
+use bevy_transform::prelude::GlobalTransform;
+use bevy_window::{Clipboard, ReceivedCharacter, Windows};
+use std::ops::Range;
+
+/// A single line text field, edited while it has the [KeyboardFocus].
+///
+/// Clicking the node focuses it, clicking anywhere else removes the focus. Its [Text] is replaced
+/// by the value, with the selection drawn in `selection_color` and a caret at the cursor.
+///
+/// Text committed by input methods arrives as characters. The window backend doesn't report the
+/// text being composed, which is only shown in the candidate window of the input method.
+#[derive(Debug, Clone)]
+pub struct TextInput {
+    pub value: String,
+    /// The position of the cursor, in characters
+    pub cursor: usize,
+    /// The position the selection extends from to the cursor, in characters. Nothing is selected
+    /// when it is at the cursor.
+    pub anchor: usize,
+    /// The maximum number of characters of the value
+    pub max_length: Option<usize>,
+    pub selection_color: Color,
+}
+
+impl Default for TextInput {
+    fn default() -> Self {
+        TextInput {
+            value: String::new(),
+            cursor: 0,
+            anchor: 0,
+            max_length: None,
+            selection_color: Color::rgb(0.3, 0.5, 0.9),
+        }
+    }
+}
+
+/// Sent when Enter is pressed in a [TextInput]
+#[derive(Debug, Clone)]
+pub struct TextInputSubmitted {
+    pub entity: Entity,
+    pub value: String,
+}
+
+impl TextInput {
+    /// A text input holding `value`, with the cursor at its end
+    pub fn new(value: impl Into<String>) -> Self {
+        let value = value.into();
+        let end = value.chars().count();
+        TextInput {
+            value,
+            cursor: end,
+            anchor: end,
+            ..Default::default()
+        }
+    }
+
+    /// Replaces the value, moving the cursor to its end
+    pub fn set_value(&mut self, value: impl Into<String>) {
+        self.value = value.into();
+        self.cursor = self.len();
+        self.anchor = self.cursor;
+    }
+
+    fn len(&self) -> usize {
+        self.value.chars().count()
+    }
+
+    fn byte_index(&self, index: usize) -> usize {
+        self.value
+            .char_indices()
+            .nth(index)
+            .map_or(self.value.len(), |(byte_index, _)| byte_index)
+    }
+
+    /// The selected characters
+    pub fn selection(&self) -> Range<usize> {
+        self.cursor.min(self.anchor)..self.cursor.max(self.anchor)
+    }
+
+    pub fn selected_text(&self) -> &str {
+        let selection = self.selection();
+        &self.value[self.byte_index(selection.start)..self.byte_index(selection.end)]
+    }
+
+    pub fn select_all(&mut self) {
+        self.anchor = 0;
+        self.cursor = self.len();
+    }
+
+    /// Removes the selection from the value and returns it
+    pub fn take_selection(&mut self) -> String {
+        let selection = self.selection();
+        let bytes = self.byte_index(selection.start)..self.byte_index(selection.end);
+        let taken = self.value[bytes.clone()].to_string();
+        self.value.replace_range(bytes, "");
+        self.cursor = selection.start;
+        self.anchor = selection.start;
+        taken
+    }
+
+    /// Replaces the selection with `text`, or inserts it at the cursor, keeping the value within
+    /// `max_length`
+    pub fn insert(&mut self, text: &str) {
+        self.take_selection();
+        let available = self.max_length.map_or(usize::MAX, |max_length| {
+            max_length.saturating_sub(self.len())
+        });
+        let text = text.chars().take(available).collect::<String>();
+        let byte_index = self.byte_index(self.cursor);
+        self.value.insert_str(byte_index, &text);
+        self.cursor += text.chars().count();
+        self.anchor = self.cursor;
+    }
+
+    /// Deletes the selection, or the character before the cursor
+    pub fn delete_backward(&mut self) {
+        if self.cursor == self.anchor {
+            self.anchor = self.cursor.saturating_sub(1);
+        }
+        self.take_selection();
+    }
+
+    /// Deletes the selection, or the character after the cursor
+    pub fn delete_forward(&mut self) {
+        if self.cursor == self.anchor {
+            self.anchor = (self.cursor + 1).min(self.len());
+        }
+        self.take_selection();
+    }
+
+    /// Moves the cursor to `index`, extending the selection if `select` is true
+    pub fn move_cursor(&mut self, index: usize, select: bool) {
+        self.cursor = index.min(self.len());
+        if !select {
+            self.anchor = self.cursor;
+        }
+    }
+
+    pub fn move_left(&mut self, select: bool) {
+        let selection = self.selection();
+        if !select && !selection.is_empty() {
+            self.move_cursor(selection.start, false);
+        } else {
+            self.move_cursor(self.cursor.saturating_sub(1), select);
+        }
+    }
+
+    pub fn move_right(&mut self, select: bool) {
+        let selection = self.selection();
+        if !select && !selection.is_empty() {
+            self.move_cursor(selection.end, false);
+        } else {
+            self.move_cursor(self.cursor + 1, select);
+        }
+    }
+
+    /// The sections displaying the value, with `style` taken from the first section of the text
+    fn sections(&self, style: &TextSection, focused: bool) -> Vec<TextSection> {
+        let selection = self.selection();
+        let section = |value: &str, color: Color| TextSection {
+            value: value.to_string(),
+            style: bevy_text::TextStyle {
+                color,
+                ..style.style.clone()
+            },
+        };
+        let before = &self.value[..self.byte_index(selection.start)];
+        let after = &self.value[self.byte_index(selection.end)..];
+        let middle = if !focused {
+            section(self.selected_text(), style.style.color)
+        } else if selection.is_empty() {
+            section("|", style.style.color)
+        } else {
+            section(self.selected_text(), self.selection_color)
+        };
+        vec![
+            section(before, style.style.color),
+            middle,
+            section(after, style.style.color),
+        ]
+    }
+}
+
+/// Moves the [KeyboardFocus] to clicked text inputs, and edits the focused one with the keyboard
+#[allow(clippy::too_many_arguments)]
+pub fn text_input_system(
+    mut keyboard_focus: ResMut<KeyboardFocus>,
+    mut clipboard: ResMut<Clipboard>,
+    mut windows: ResMut<Windows>,
+    mouse_button_input: Res<Input<MouseButton>>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut character_events: EventReader<ReceivedCharacter>,
+    mut submitted_events: EventWriter<TextInputSubmitted>,
+    interaction_query: Query<(Entity, &Interaction), (With<TextInput>, Changed<Interaction>)>,
+    mut text_input_query: Query<(&mut TextInput, &Node, &GlobalTransform)>,
+) {
+    // characters typed while nothing is focused are dropped, instead of reaching the next focus
+    let characters = character_events
+        .iter()
+        .map(|event| event.char)
+        .collect::<Vec<_>>();
+    let clicked = interaction_query
+        .iter()
+        .find(|(_, interaction)| **interaction == Interaction::Clicked)
+        .map(|(entity, _)| entity);
+    if let Some(entity) = clicked {
+        if keyboard_focus.entity != Some(entity) {
+            keyboard_focus.entity = Some(entity);
+            // input methods show their candidates below the field
+            if let (Some(window), Ok((_, node, global_transform))) =
+                (windows.get_primary_mut(), text_input_query.get_mut(entity))
+            {
+                let position = global_transform.translation.truncate() - node.size / 2.0;
+                window.set_ime_position(Vec2::new(position.x, position.y));
+            }
+        }
+    } else if mouse_button_input.just_pressed(MouseButton::Left)
+        && keyboard_focus
+            .entity
+            .map_or(false, |entity| text_input_query.get_mut(entity).is_ok())
+    {
+        keyboard_focus.entity = None;
+    }
+
+    let entity = match keyboard_focus.entity {
+        Some(entity) => entity,
+        None => return,
+    };
+    let mut text_input = match text_input_query.get_mut(entity) {
+        Ok((text_input, _, _)) => text_input,
+        Err(_) => return,
+    };
+
+    let shift = keyboard_input.pressed(KeyCode::LShift) || keyboard_input.pressed(KeyCode::RShift);
+    let control = keyboard_input.pressed(KeyCode::LControl)
+        || keyboard_input.pressed(KeyCode::RControl)
+        || keyboard_input.pressed(KeyCode::LWin)
+        || keyboard_input.pressed(KeyCode::RWin);
+
+    for key in keyboard_input.get_just_pressed() {
+        match key {
+            KeyCode::Back => text_input.delete_backward(),
+            KeyCode::Delete => text_input.delete_forward(),
+            KeyCode::Left => text_input.move_left(shift),
+            KeyCode::Right => text_input.move_right(shift),
+            KeyCode::Home => text_input.move_cursor(0, shift),
+            KeyCode::End => text_input.move_cursor(usize::MAX, shift),
+            KeyCode::Return | KeyCode::NumpadEnter => {
+                submitted_events.send(TextInputSubmitted {
+                    entity,
+                    value: text_input.value.clone(),
+                });
+            }
+            KeyCode::A if control => text_input.select_all(),
+            KeyCode::C if control => clipboard.set_text(text_input.selected_text()),
+            KeyCode::X if control => clipboard.set_text(text_input.take_selection()),
+            KeyCode::V if control => {
+                // the field is a single line
+                let text = clipboard.get_text().replace(&['\n', '\r'][..], " ");
+                text_input.insert(&text);
+            }
+            _ => {}
+        }
+    }
+
+    for c in characters {
+        if !control && !c.is_control() {
+            text_input.insert(c.encode_utf8(&mut [0; 4]));
+        }
+    }
+}
+
+/// Displays the value of text inputs in their [Text]
+pub fn text_input_text_system(
+    keyboard_focus: Res<KeyboardFocus>,
+    mut query: Query<(Entity, &TextInput, &mut Text)>,
+) {
+    for (entity, text_input, mut text) in query.iter_mut() {
+        let focused = keyboard_focus.entity == Some(entity);
+        let style = match text.sections.first() {
+            Some(section) => section.clone(),
+            None => continue,
+        };
+        let sections = text_input.sections(&style, focused);
+        let changed = text.sections.len() != sections.len()
+            || text
+                .sections
+                .iter()
+                .zip(sections.iter())
+                .any(|(a, b)| a.value != b.value || a.style.color != b.style.color);
+        if changed {
+            text.sections = sections;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edits_selection() {
+        let mut input = TextInput::new("héllo");
+        input.move_left(false);
+        input.move_left(true);
+        input.move_left(true);
+        assert_eq!(input.selected_text(), "ll");
+        input.insert("y");
+        assert_eq!(input.value, "héyo");
+        assert_eq!(input.cursor, 3);
+        input.delete_backward();
+        input.delete_forward();
+        assert_eq!(input.value, "hé");
+        input.select_all();
+        assert_eq!(input.take_selection(), "hé");
+        assert_eq!((input.cursor, input.anchor), (0, 0));
+
+        input.max_length = Some(3);
+        input.insert("abcd");
+        assert_eq!(input.value, "abc");
+        input.move_cursor(0, false);
+        input.delete_backward();
+        assert_eq!(input.value, "abc");
+    }
+}
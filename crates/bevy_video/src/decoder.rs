@@ -0,0 +1,253 @@
+use crate::{video_source::WebmDemuxer, VideoSource};
+use bevy_utils::Duration;
+use crossbeam_channel::{Receiver, Sender, TryRecvError};
+use dav1d::{PixelLayout, PlanarImageComponent};
+use thiserror::Error;
+
+/// The number of decoded frames waiting to be shown before the decoder thread blocks
+const FRAMES_AHEAD: usize = 4;
+
+#[derive(Error, Debug)]
+pub enum VideoError {
+    #[error("failed to read the WebM container: {0}")]
+    Container(#[from] matroska_demuxer::DemuxError),
+    #[error("the file has no video track")]
+    NoVideoTrack,
+    #[error("unsupported video codec {0}, only AV1 can be decoded")]
+    UnsupportedCodec(String),
+    #[error("unsupported {0} bit pixels, only 8 bit pixels can be decoded")]
+    UnsupportedBitDepth(usize),
+    #[error("failed to decode the video: {0}")]
+    Decode(#[from] dav1d::Error),
+}
+
+/// A frame converted to RGBA, shown once the playback reaches `time`
+pub(crate) struct DecodedFrame {
+    pub time: Duration,
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+pub(crate) enum DecoderMessage {
+    Frame(DecodedFrame),
+    Finished,
+    Failed(VideoError),
+}
+
+/// Decodes a video on its own thread, a few frames ahead of the playback. Dropping it stops the
+/// thread once it tries to send its next frame.
+pub(crate) struct DecoderThread {
+    receiver: Receiver<DecoderMessage>,
+}
+
+impl DecoderThread {
+    pub fn spawn(video: VideoSource, looped: bool) -> Self {
+        let (sender, receiver) = crossbeam_channel::bounded(FRAMES_AHEAD);
+        std::thread::Builder::new()
+            .name("video decoder".to_string())
+            .spawn(move || {
+                let message = match decode(&video, looped, &sender) {
+                    Ok(()) => DecoderMessage::Finished,
+                    Err(err) => DecoderMessage::Failed(err),
+                };
+                let _ = sender.send(message);
+            })
+            .expect("failed to spawn the video decoder thread");
+        DecoderThread { receiver }
+    }
+
+    /// Returns the next message if the decoder sent it. A disconnected decoder is finished.
+    pub fn try_recv(&self) -> Option<DecoderMessage> {
+        match self.receiver.try_recv() {
+            Ok(message) => Some(message),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(DecoderMessage::Finished),
+        }
+    }
+}
+
+/// Returns Ok once the video ends, or as soon as the receiver is dropped
+fn decode(
+    video: &VideoSource,
+    looped: bool,
+    sender: &Sender<DecoderMessage>,
+) -> Result<(), VideoError> {
+    // the time of the start of the current loop
+    let mut offset = Duration::from_secs(0);
+    loop {
+        let mut demuxer = WebmDemuxer::open(video.bytes.clone())?;
+        let mut decoder = Decoder {
+            decoder: dav1d::Decoder::new(),
+            sender,
+            offset,
+            previous: offset,
+            end: offset,
+        };
+        while let Some((time, data)) = demuxer.next_packet()? {
+            let timestamp = time.as_nanos() as i64;
+            match decoder.decoder.send_data(data, None, Some(timestamp), None) {
+                Err(err) if err.is_again() => loop {
+                    if !decoder.send_pictures()? {
+                        return Ok(());
+                    }
+                    match decoder.decoder.send_pending_data() {
+                        Err(err) if err.is_again() => continue,
+                        result => break result?,
+                    }
+                },
+                result => result?,
+            }
+            if !decoder.send_pictures()? {
+                return Ok(());
+            }
+        }
+        if !decoder.send_pictures()? || !looped {
+            return Ok(());
+        }
+        // the last frame is shown as long as the one before it
+        offset = decoder.end + (decoder.end - decoder.previous);
+    }
+}
+
+struct Decoder<'a> {
+    decoder: dav1d::Decoder,
+    sender: &'a Sender<DecoderMessage>,
+    offset: Duration,
+    /// The times of the last two frames sent
+    previous: Duration,
+    end: Duration,
+}
+
+impl<'a> Decoder<'a> {
+    /// Sends the decoded pictures, returning false if the receiver was dropped
+    fn send_pictures(&mut self) -> Result<bool, VideoError> {
+        loop {
+            let picture = match self.decoder.get_picture() {
+                Ok(picture) => picture,
+                Err(err) if err.is_again() => return Ok(true),
+                Err(err) => return Err(err.into()),
+            };
+            let time = self.offset + Duration::from_nanos(picture.timestamp().unwrap_or(0) as u64);
+            let frame = convert_picture(&picture, time)?;
+            self.previous = self.end;
+            self.end = time;
+            if self.sender.send(DecoderMessage::Frame(frame)).is_err() {
+                return Ok(false);
+            }
+        }
+    }
+}
+
+fn convert_picture(picture: &dav1d::Picture, time: Duration) -> Result<DecodedFrame, VideoError> {
+    if picture.bit_depth() != 8 {
+        return Err(VideoError::UnsupportedBitDepth(picture.bit_depth()));
+    }
+    let width = picture.width();
+    let height = picture.height();
+    let chroma_shift = match picture.pixel_layout() {
+        PixelLayout::I400 => None,
+        PixelLayout::I420 => Some((1, 1)),
+        PixelLayout::I422 => Some((1, 0)),
+        PixelLayout::I444 => Some((0, 0)),
+    };
+    let y = picture.plane(PlanarImageComponent::Y);
+    // monochrome pictures have no chroma planes
+    let chroma_planes = chroma_shift.map(|shift| {
+        (
+            picture.plane(PlanarImageComponent::U),
+            picture.plane(PlanarImageComponent::V),
+            shift,
+        )
+    });
+    let planes = YuvPlanes {
+        y: y.as_ref(),
+        y_stride: picture.stride(PlanarImageComponent::Y) as usize,
+        chroma: chroma_planes.as_ref().map(|(u, v, shift)| Chroma {
+            u: u.as_ref(),
+            v: v.as_ref(),
+            stride: picture.stride(PlanarImageComponent::U) as usize,
+            shift: *shift,
+        }),
+    };
+    Ok(DecodedFrame {
+        time,
+        width,
+        height,
+        rgba: planes.to_rgba(width, height),
+    })
+}
+
+struct Chroma<'a> {
+    u: &'a [u8],
+    v: &'a [u8],
+    stride: usize,
+    /// The horizontal and vertical subsampling, as a shift of the luma coordinates
+    shift: (u32, u32),
+}
+
+/// The 8 bit planes of a decoded picture, in the limited range BT.709 color space
+struct YuvPlanes<'a> {
+    y: &'a [u8],
+    y_stride: usize,
+    /// None for monochrome pictures
+    chroma: Option<Chroma<'a>>,
+}
+
+impl<'a> YuvPlanes<'a> {
+    fn to_rgba(&self, width: u32, height: u32) -> Vec<u8> {
+        let mut rgba = Vec::with_capacity(width as usize * height as usize * 4);
+        for row in 0..height {
+            for column in 0..width {
+                let y = self.y[row as usize * self.y_stride + column as usize] as f32;
+                let (u, v) = match &self.chroma {
+                    Some(chroma) => {
+                        let index = (row >> chroma.shift.1) as usize * chroma.stride
+                            + (column >> chroma.shift.0) as usize;
+                        (chroma.u[index] as f32, chroma.v[index] as f32)
+                    }
+                    None => (128.0, 128.0),
+                };
+                let y = 1.164 * (y - 16.0);
+                let (u, v) = (u - 128.0, v - 128.0);
+                let r = y + 1.793 * v;
+                let g = y - 0.213 * u - 0.533 * v;
+                let b = y + 2.112 * u;
+                rgba.extend_from_slice(&[
+                    r.round().max(0.0).min(255.0) as u8,
+                    g.round().max(0.0).min(255.0) as u8,
+                    b.round().max(0.0).min(255.0) as u8,
+                    255,
+                ]);
+            }
+        }
+        rgba
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_subsampled_yuv() {
+        // a 2x2 picture with a single chroma sample, and one padding byte per luma row
+        let planes = YuvPlanes {
+            y: &[16, 235, 0, 126, 16, 0],
+            y_stride: 3,
+            chroma: Some(Chroma {
+                u: &[128],
+                v: &[128],
+                stride: 1,
+                shift: (1, 1),
+            }),
+        };
+        assert_eq!(
+            planes.to_rgba(2, 2),
+            vec![
+                0, 0, 0, 255, 255, 255, 255, 255, //
+                128, 128, 128, 255, 0, 0, 0, 255,
+            ]
+        );
+    }
+}
@@ -0,0 +1,63 @@
+mod decoder;
+mod player;
+mod video_source;
+mod video_texture_node;
+
+pub use decoder::VideoError;
+pub use player::*;
+pub use video_source::*;
+pub use video_texture_node::VideoTextureNode;
+
+pub mod prelude {
+    pub use crate::{VideoEvent, VideoPlayer, VideoSource};
+}
+
+use bevy_app::prelude::*;
+use bevy_asset::AddAsset;
+use bevy_ecs::system::IntoSystem;
+use bevy_render::render_graph::{base, RenderGraph};
+use video_texture_node::VideoFrameUploads;
+
+/// the names of video graph nodes
+pub mod node {
+    pub const VIDEO_TEXTURE: &str = "video_texture";
+}
+
+/// Adds playback of videos in WebM files to textures, with the [VideoPlayer] component.
+///
+/// Only the AV1 codec is supported, with 8 bit pixels. Videos with 10 or 12 bit pixels fail with
+/// [VideoError::UnsupportedBitDepth]. Frames are converted from YUV to RGBA on the CPU, on the
+/// decoder thread, and copied straight to the GPU texture of the player, so the [Texture]
+/// asset keeps its initial data. Must be added after
+/// [RenderPlugin](bevy_render::RenderPlugin).
+///
+/// [Texture]: bevy_render::texture::Texture
+#[derive(Default)]
+pub struct VideoPlugin;
+
+impl Plugin for VideoPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_asset::<VideoSource>()
+            .init_asset_loader::<WebmLoader>()
+            .init_resource::<VideoFrameUploads>()
+            .add_event::<VideoEvent>()
+            .add_system_to_stage(CoreStage::PostUpdate, video_player_system.system());
+
+        let mut render_graph = app
+            .world_mut()
+            .get_resource_mut::<RenderGraph>()
+            .expect("VideoPlugin must be added after RenderPlugin");
+        render_graph.add_system_node(node::VIDEO_TEXTURE, VideoTextureNode::default());
+        // frames are copied after the texture copy node uploads the data of resized textures
+        if render_graph.get_node_id(base::node::TEXTURE_COPY).is_ok() {
+            render_graph
+                .add_node_edge(base::node::TEXTURE_COPY, node::VIDEO_TEXTURE)
+                .unwrap();
+        }
+        if render_graph.get_node_id(base::node::MAIN_PASS).is_ok() {
+            render_graph
+                .add_node_edge(node::VIDEO_TEXTURE, base::node::MAIN_PASS)
+                .unwrap();
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use crate::{
+    decoder::{DecodedFrame, DecoderMessage, DecoderThread},
+    video_texture_node::VideoFrameUploads,
+    VideoSource,
+};
+use bevy_asset::{Assets, Handle};
+use bevy_core::Time;
+use bevy_ecs::{
+    entity::Entity,
+    event::EventWriter,
+    system::{Query, Res, ResMut},
+};
+use bevy_render::texture::{Extent3d, Texture, TextureDimension, TextureFormat};
+use bevy_utils::{tracing::warn, Duration};
+
+/// Plays a [VideoSource] into a [Texture] that any material can sample. The video starts once
+/// its source has loaded, and is decoded on its own thread a few frames ahead.
+pub struct VideoPlayer {
+    pub video: Handle<VideoSource>,
+    /// Receives the frames of the video. It stays black until the first frame is decoded.
+    pub texture: Handle<Texture>,
+    /// Restarts the video when it ends. Only read when the playback starts.
+    pub looped: bool,
+    pub paused: bool,
+    time: Duration,
+    state: PlayerState,
+}
+
+enum PlayerState {
+    Loading,
+    Playing {
+        decoder: DecoderThread,
+        /// The first frame not shown yet
+        next: Option<DecodedFrame>,
+        /// The time only advances once the first frame is shown
+        started: bool,
+    },
+    Finished,
+}
+
+impl VideoPlayer {
+    /// Plays `video` into a new texture added to `textures`
+    pub fn new(video: Handle<VideoSource>, textures: &mut Assets<Texture>) -> Self {
+        let texture = Texture::new_fill(
+            Extent3d::new(1, 1, 1),
+            TextureDimension::D2,
+            &[0, 0, 0, 255],
+            TextureFormat::Rgba8UnormSrgb,
+        );
+        VideoPlayer::with_texture(video, textures.add(texture))
+    }
+
+    /// Plays `video` into `texture`, which is resized to the size of the video
+    pub fn with_texture(video: Handle<VideoSource>, texture: Handle<Texture>) -> Self {
+        VideoPlayer {
+            video,
+            texture,
+            looped: false,
+            paused: false,
+            time: Duration::from_secs(0),
+            state: PlayerState::Loading,
+        }
+    }
+
+    pub fn looped(mut self) -> Self {
+        self.looped = true;
+        self
+    }
+
+    /// The time since the start of the video, including the previous loops
+    pub fn time(&self) -> Duration {
+        self.time
+    }
+
+    /// Returns true once the video has ended or failed to decode
+    pub fn is_finished(&self) -> bool {
+        matches!(self.state, PlayerState::Finished)
+    }
+
+    /// Plays the video again from the start
+    pub fn restart(&mut self) {
+        self.time = Duration::from_secs(0);
+        self.state = PlayerState::Loading;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VideoEvent {
+    Finished(Entity),
+    /// The video of the player couldn't be decoded. Contains the error message.
+    Failed(Entity, String),
+}
+
+/// Starts the decoders of the loaded videos and queues the frames due to be copied to the
+/// textures of the [VideoPlayer]s. The [Texture] assets are only modified when the size of the
+/// video changes.
+pub fn video_player_system(
+    time: Res<Time>,
+    videos: Res<Assets<VideoSource>>,
+    mut textures: ResMut<Assets<Texture>>,
+    mut uploads: ResMut<VideoFrameUploads>,
+    mut video_events: EventWriter<VideoEvent>,
+    mut players: Query<(Entity, &mut VideoPlayer)>,
+) {
+    for (entity, mut player) in players.iter_mut() {
+        let player = &mut *player;
+        if let PlayerState::Loading = player.state {
+            match videos.get(&player.video) {
+                Some(video) => {
+                    player.state = PlayerState::Playing {
+                        decoder: DecoderThread::spawn(video.clone(), player.looped),
+                        next: None,
+                        started: false,
+                    };
+                }
+                None => continue,
+            }
+        }
+        let (decoder, next, started) = match &mut player.state {
+            PlayerState::Playing {
+                decoder,
+                next,
+                started,
+            } if !player.paused => (decoder, next, started),
+            _ => continue,
+        };
+        if *started {
+            player.time += time.delta();
+        }
+
+        let mut shown = None;
+        let mut finished = None;
+        loop {
+            let frame = match next
+                .take()
+                .map(DecoderMessage::Frame)
+                .or_else(|| decoder.try_recv())
+            {
+                Some(DecoderMessage::Frame(frame)) => frame,
+                Some(DecoderMessage::Finished) => {
+                    finished = Some(VideoEvent::Finished(entity));
+                    break;
+                }
+                Some(DecoderMessage::Failed(err)) => {
+                    warn!("Failed to play a video. {}", err);
+                    finished = Some(VideoEvent::Failed(entity, err.to_string()));
+                    break;
+                }
+                None => break,
+            };
+            if frame.time > player.time {
+                *next = Some(frame);
+                break;
+            }
+            // frames that are late are skipped
+            shown = Some(frame);
+            *started = true;
+        }
+
+        if let Some(frame) = shown {
+            let size = Extent3d::new(frame.width, frame.height, 1);
+            let matches_frame = |texture: &Texture| {
+                texture.size == size
+                    && texture.format == TextureFormat::Rgba8UnormSrgb
+                    && texture.mip_level_count == 1
+            };
+            if let Some(resize) = textures.get(&player.texture).map(|t| !matches_frame(t)) {
+                // the GPU texture is recreated before the frame is copied to it
+                if resize {
+                    let texture = textures.get_mut(&player.texture).unwrap();
+                    texture.format = TextureFormat::Rgba8UnormSrgb;
+                    texture.resize(size);
+                }
+                uploads.frames.push((player.texture.clone_weak(), frame));
+            }
+        }
+        if let Some(event) = finished {
+            player.state = PlayerState::Finished;
+            video_events.send(event);
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use crate::VideoError;
+use anyhow::Result;
+use bevy_asset::{AssetLoader, LoadContext, LoadedAsset};
+use bevy_reflect::TypeUuid;
+use bevy_utils::{BoxedFuture, Duration};
+use matroska_demuxer::{Frame, MatroskaFile, TrackType};
+use std::{io::Cursor, sync::Arc};
+
+/// The size and length of a video, read when it loads
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VideoInfo {
+    pub width: u32,
+    pub height: u32,
+    pub duration: Option<Duration>,
+}
+
+/// An encoded video. The bytes are kept in memory and decoded while the video plays.
+#[derive(Debug, Clone, TypeUuid)]
+#[uuid = "3d2e7f1c-58a4-4c39-9b0e-6f1a2d84c5b7"]
+pub struct VideoSource {
+    pub bytes: Arc<[u8]>,
+    pub info: VideoInfo,
+}
+
+impl AsRef<[u8]> for VideoSource {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+/// Loads AV1 videos in WebM files as [VideoSource] [Assets](bevy_asset::Assets)
+#[derive(Default)]
+pub struct WebmLoader;
+
+impl AssetLoader for WebmLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let bytes: Arc<[u8]> = bytes.into();
+            let info = WebmDemuxer::open(bytes.clone())?.info;
+            load_context.set_default_asset(LoadedAsset::new(VideoSource { bytes, info }));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["webm"]
+    }
+}
+
+/// Reads the packets of the video track of a WebM file
+pub(crate) struct WebmDemuxer {
+    file: MatroskaFile<Cursor<Arc<[u8]>>>,
+    track: u64,
+    /// The length of a timestamp unit, in nanoseconds
+    timestamp_scale: u64,
+    frame: Frame,
+    pub info: VideoInfo,
+}
+
+impl WebmDemuxer {
+    pub fn open(bytes: Arc<[u8]>) -> Result<Self, VideoError> {
+        let file = MatroskaFile::open(Cursor::new(bytes))?;
+        let track = file
+            .tracks()
+            .iter()
+            .find(|track| track.track_type() == TrackType::Video)
+            .ok_or(VideoError::NoVideoTrack)?;
+        if track.codec_id() != "V_AV1" {
+            return Err(VideoError::UnsupportedCodec(track.codec_id().to_string()));
+        }
+        let video = track.video().ok_or(VideoError::NoVideoTrack)?;
+        let timestamp_scale = file.info().timestamp_scale().get();
+        let info = VideoInfo {
+            width: video.pixel_width().get() as u32,
+            height: video.pixel_height().get() as u32,
+            duration: file
+                .info()
+                .duration()
+                .map(|duration| Duration::from_nanos((duration * timestamp_scale as f64) as u64)),
+        };
+        Ok(WebmDemuxer {
+            track: track.track_number().get(),
+            file,
+            timestamp_scale,
+            frame: Frame::default(),
+            info,
+        })
+    }
+
+    /// Returns the data of the next packet and its time, or None at the end of the video
+    pub fn next_packet(&mut self) -> Result<Option<(Duration, Vec<u8>)>, VideoError> {
+        while self.file.next_frame(&mut self.frame)? {
+            if self.frame.track == self.track {
+                let time = Duration::from_nanos(self.frame.timestamp * self.timestamp_scale);
+                return Ok(Some((time, std::mem::take(&mut self.frame.data))));
+            }
+        }
+        Ok(None)
+    }
+}
@@ -0,0 +1,99 @@
+use crate::decoder::DecodedFrame;
+use bevy_asset::Handle;
+use bevy_ecs::{
+    system::{BoxedSystem, IntoSystem, Local, Res, ResMut},
+    world::World,
+};
+use bevy_render::{
+    render_graph::{CommandQueue, Node, ResourceSlots, SystemNode},
+    renderer::{BufferInfo, BufferUsage, RenderContext, RenderResourceContext},
+    texture::{Extent3d, Texture, TEXTURE_ASSET_INDEX},
+};
+
+/// The frames shown this frame, waiting to be copied to the textures of their players
+#[derive(Default)]
+pub(crate) struct VideoFrameUploads {
+    pub frames: Vec<(Handle<Texture>, DecodedFrame)>,
+}
+
+/// A Render Graph [Node] that copies the frames of the [VideoPlayer](crate::VideoPlayer)s to
+/// their textures on the GPU, without modifying the [Texture] assets
+#[derive(Debug, Default)]
+pub struct VideoTextureNode {
+    command_queue: CommandQueue,
+}
+
+impl Node for VideoTextureNode {
+    fn update(
+        &mut self,
+        _world: &World,
+        render_context: &mut dyn RenderContext,
+        _input: &ResourceSlots,
+        _output: &mut ResourceSlots,
+    ) {
+        self.command_queue.execute(render_context);
+    }
+}
+
+impl SystemNode for VideoTextureNode {
+    fn get_system(&self) -> BoxedSystem {
+        let system = video_texture_node_system.system().config(|config| {
+            config.0 = Some(VideoTextureNodeState {
+                command_queue: self.command_queue.clone(),
+            })
+        });
+        Box::new(system)
+    }
+}
+
+#[derive(Debug, Default)]
+struct VideoTextureNodeState {
+    command_queue: CommandQueue,
+}
+
+fn video_texture_node_system(
+    mut state: Local<VideoTextureNodeState>,
+    render_resource_context: Res<Box<dyn RenderResourceContext>>,
+    mut uploads: ResMut<VideoFrameUploads>,
+) {
+    let render_resource_context = &**render_resource_context;
+    for (handle, frame) in uploads.frames.drain(..) {
+        // the texture is created by the texture resource system once the asset exists
+        let texture = match render_resource_context
+            .get_asset_resource(&handle, TEXTURE_ASSET_INDEX)
+            .and_then(|resource| resource.get_texture())
+        {
+            Some(texture) => texture,
+            None => continue,
+        };
+
+        let row_size = frame.width as usize * 4;
+        let aligned_row_size =
+            render_resource_context.get_aligned_texture_size(frame.width as usize) * 4;
+        let mut data = vec![0; aligned_row_size * frame.height as usize];
+        for (row, aligned_row) in frame
+            .rgba
+            .chunks_exact(row_size)
+            .zip(data.chunks_exact_mut(aligned_row_size))
+        {
+            aligned_row[..row_size].copy_from_slice(row);
+        }
+        let buffer = render_resource_context.create_buffer_with_data(
+            BufferInfo {
+                buffer_usage: BufferUsage::COPY_SRC,
+                ..Default::default()
+            },
+            &data,
+        );
+        state.command_queue.copy_buffer_to_texture(
+            buffer,
+            0,
+            aligned_row_size as u32,
+            texture,
+            [0, 0, 0],
+            0,
+            Extent3d::new(frame.width, frame.height, 1),
+        );
+        state.command_queue.free_buffer(buffer);
+    }
+}
@@ -0,0 +1,57 @@
+#[cfg(feature = "renderdoc")]
+use bevy_utils::tracing::warn;
+#[cfg(feature = "renderdoc")]
+use parking_lot::Mutex;
+
+/// The in-app API of RenderDoc, used to capture the frames requested with
+/// [GpuCapture](bevy_render::renderer::GpuCapture)
+pub struct GpuCaptureApi {
+    #[cfg(feature = "renderdoc")]
+    renderdoc: Mutex<renderdoc_api::RenderDoc<renderdoc_api::V120>>,
+}
+
+impl GpuCaptureApi {
+    /// Loads RenderDoc, returning None if the `renderdoc` feature is disabled or RenderDoc isn't
+    /// installed. It must be loaded before the device is created to hook into it.
+    #[cfg(feature = "renderdoc")]
+    pub fn load() -> Option<Self> {
+        match renderdoc_api::RenderDoc::new() {
+            Ok(renderdoc) => Some(GpuCaptureApi {
+                renderdoc: Mutex::new(renderdoc),
+            }),
+            Err(err) => {
+                warn!(
+                    "Failed to load RenderDoc, frames can't be captured. {}",
+                    err
+                );
+                None
+            }
+        }
+    }
+
+    #[cfg(not(feature = "renderdoc"))]
+    pub fn load() -> Option<Self> {
+        None
+    }
+
+    pub fn start_frame_capture(&self) {
+        #[cfg(feature = "renderdoc")]
+        {
+            // null device and window pointers capture every device and window
+            self.renderdoc
+                .lock()
+                .start_frame_capture(std::ptr::null(), std::ptr::null());
+        }
+    }
+
+    /// Ends the capture, naming it after the frame number
+    #[allow(unused_variables)]
+    pub fn end_frame_capture(&self, frame: u64) {
+        #[cfg(feature = "renderdoc")]
+        {
+            let mut renderdoc = self.renderdoc.lock();
+            renderdoc.end_frame_capture(std::ptr::null(), std::ptr::null());
+            renderdoc.set_capture_file_comments(None::<&str>, format!("frame {}", frame));
+        }
+    }
+}
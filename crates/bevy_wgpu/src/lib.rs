@@ -1,10 +1,18 @@
 pub mod diagnostic;
+mod gpu_capture;
 pub mod renderer;
+#[cfg(target_arch = "wasm32")]
+mod web;
+mod wgpu_compute_pass;
+mod wgpu_raw;
 mod wgpu_render_pass;
 mod wgpu_renderer;
 mod wgpu_resources;
 mod wgpu_type_converter;
 
+pub use gpu_capture::*;
+pub use wgpu_compute_pass::*;
+pub use wgpu_raw::*;
 pub use wgpu_render_pass::*;
 pub use wgpu_renderer::*;
 pub use wgpu_resources::*;
@@ -15,9 +23,13 @@ use bevy_ecs::{
     world::World,
 };
 use bevy_render::{
-    renderer::{shared_buffers_update_system, RenderResourceContext, SharedBuffers},
+    renderer::{
+        indirect_buffers_update_system, shared_buffers_update_system, IndirectBuffers,
+        RenderResourceContext, SharedBuffers,
+    },
     RenderStage,
 };
+#[cfg(not(target_arch = "wasm32"))]
 use futures_lite::future;
 use renderer::WgpuRenderResourceContext;
 use std::borrow::Cow;
@@ -89,28 +101,69 @@ pub struct WgpuPlugin;
 
 impl Plugin for WgpuPlugin {
     fn build(&self, app: &mut AppBuilder) {
-        let render_system = get_wgpu_render_system(app.world_mut());
-        app.add_system_to_stage(RenderStage::Render, render_system.exclusive_system())
-            .add_system_to_stage(
-                RenderStage::PostRender,
-                shared_buffers_update_system.system(),
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let render_system = get_wgpu_render_system(app.world_mut());
+            app.add_system_to_stage(RenderStage::Render, render_system.exclusive_system());
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            web::run_after_wgpu_renderer_creation(app);
+            app.add_system_to_stage(
+                RenderStage::Render,
+                web::wgpu_render_system.exclusive_system(),
+            );
+        }
+        #[cfg(all(target_arch = "wasm32", not(feature = "bevy_winit")))]
+        {
+            app.init_resource::<web::WebCanvases>().add_system_to_stage(
+                CoreStage::PreUpdate,
+                web::create_canvas_windows_system.system(),
             );
+        }
+        app.add_system_to_stage(
+            RenderStage::PostRender,
+            shared_buffers_update_system.system(),
+        )
+        .add_system_to_stage(
+            RenderStage::PostRender,
+            indirect_buffers_update_system.system(),
+        );
     }
 }
 
+/// Creates the wgpu render system. If the world already contains a [WgpuRenderer] it is used,
+/// otherwise one is created from the [WgpuOptions] resource.
+///
+/// Adapter and device requests are asynchronous and browsers can't block on them, so on wasm32
+/// [WgpuPlugin] instead creates the renderer before the app's runner starts and surfaces are
+/// created from the windows' canvas elements.
+#[cfg(not(target_arch = "wasm32"))]
 pub fn get_wgpu_render_system(world: &mut World) -> impl FnMut(&mut World) {
-    let options = world
-        .get_resource::<WgpuOptions>()
-        .cloned()
-        .unwrap_or_else(WgpuOptions::default);
-    let mut wgpu_renderer = future::block_on(WgpuRenderer::new(options));
+    if !world.contains_resource::<WgpuRenderer>() {
+        let options = world
+            .get_resource::<WgpuOptions>()
+            .cloned()
+            .unwrap_or_else(WgpuOptions::default);
+        world.insert_resource(future::block_on(WgpuRenderer::new(options)));
+    }
+    insert_render_resources(world);
+    let mut wgpu_renderer = world.remove_resource::<WgpuRenderer>().unwrap();
+    move |world| {
+        wgpu_renderer.update(world);
+    }
+}
 
+/// Inserts the render resources backed by the [WgpuRenderer] resource
+fn insert_render_resources(world: &mut World) {
+    let wgpu_renderer = world.get_resource::<WgpuRenderer>().unwrap();
     let resource_context = WgpuRenderResourceContext::new(wgpu_renderer.device.clone());
+    let raw_context =
+        WgpuRawContext::new(wgpu_renderer.device.clone(), wgpu_renderer.queue.clone());
+    world.insert_resource(raw_context);
     world.insert_resource::<Box<dyn RenderResourceContext>>(Box::new(resource_context));
     world.insert_resource(SharedBuffers::new(4096));
-    move |world| {
-        wgpu_renderer.update(world);
-    }
+    world.insert_resource(IndirectBuffers::new(4096));
 }
 
 #[derive(Default, Clone)]
@@ -1,9 +1,9 @@
 use super::WgpuRenderResourceContext;
-use crate::{wgpu_type_converter::WgpuInto, WgpuRenderPass, WgpuResourceRefs};
+use crate::{wgpu_type_converter::WgpuInto, WgpuComputePass, WgpuRenderPass, WgpuResourceRefs};
 
 use bevy_render::{
     pass::{
-        PassDescriptor, RenderPass, RenderPassColorAttachmentDescriptor,
+        ComputePass, PassDescriptor, RenderPass, RenderPassColorAttachmentDescriptor,
         RenderPassDepthStencilAttachmentDescriptor, TextureAttachment,
     },
     renderer::{
@@ -13,7 +13,20 @@ use bevy_render::{
     texture::Extent3d,
 };
 
+use bevy_utils::tracing::error;
 use std::sync::Arc;
+use thiserror::Error;
+
+/// An error that occurred while setting up rendering work
+#[derive(Error, Debug)]
+pub enum RendererError {
+    #[error("Pass attachment \"{name}\" does not exist. Known texture bindings: {known:?}")]
+    MissingAttachment { name: String, known: Vec<String> },
+    #[error("Pass attachment texture {0:?} does not exist.")]
+    MissingTexture(TextureId),
+    #[error("Encountered unset `TextureAttachment::Input`. The `RenderGraph` executor should always set `TextureAttachment::Inputs` to `TextureAttachment::RenderResource` before running. This is a bug, please report it!")]
+    UnsetInputAttachment,
+}
 
 #[derive(Debug, Default)]
 pub struct LazyCommandEncoder {
@@ -173,6 +186,10 @@ impl RenderContext for WgpuRenderContext {
         render_resource_bindings: &RenderResourceBindings,
         run_pass: &mut dyn FnMut(&mut dyn RenderPass),
     ) {
+        #[cfg(feature = "trace")]
+        let pass_span = bevy_utils::tracing::info_span!("render_pass");
+        #[cfg(feature = "trace")]
+        let _pass_guard = pass_span.enter();
         if !self.command_encoder.is_some() {
             self.command_encoder.create(&self.device);
         }
@@ -180,12 +197,20 @@ impl RenderContext for WgpuRenderContext {
         let refs = resource_lock.refs();
         let mut encoder = self.command_encoder.take().unwrap();
         {
-            let render_pass = create_render_pass(
+            let render_pass = match create_render_pass(
                 pass_descriptor,
                 render_resource_bindings,
                 &refs,
                 &mut encoder,
-            );
+            ) {
+                Ok(render_pass) => render_pass,
+                Err(err) => {
+                    // a broken pass shouldn't take down the rest of the frame
+                    error!("Skipping render pass: {}", err);
+                    self.command_encoder.set(encoder);
+                    return;
+                }
+            };
             let mut wgpu_render_pass = WgpuRenderPass {
                 render_pass,
                 render_context: self,
@@ -198,6 +223,50 @@ impl RenderContext for WgpuRenderContext {
 
         self.command_encoder.set(encoder);
     }
+
+    fn begin_compute_pass(&mut self, run_pass: &mut dyn FnMut(&mut dyn ComputePass)) {
+        #[cfg(feature = "trace")]
+        let pass_span = bevy_utils::tracing::info_span!("compute_pass");
+        #[cfg(feature = "trace")]
+        let _pass_guard = pass_span.enter();
+        if !self.command_encoder.is_some() {
+            self.command_encoder.create(&self.device);
+        }
+        let resource_lock = self.render_resource_context.resources.read();
+        let refs = resource_lock.refs();
+        let mut encoder = self.command_encoder.take().unwrap();
+        {
+            let compute_pass =
+                encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: None });
+            let mut wgpu_compute_pass = WgpuComputePass {
+                compute_pass,
+                render_context: self,
+                wgpu_resources: refs,
+            };
+
+            run_pass(&mut wgpu_compute_pass);
+        }
+
+        self.command_encoder.set(encoder);
+    }
+
+    fn push_debug_group(&mut self, label: &str) {
+        self.command_encoder
+            .get_or_create(&self.device)
+            .push_debug_group(label);
+    }
+
+    fn pop_debug_group(&mut self) {
+        self.command_encoder
+            .get_or_create(&self.device)
+            .pop_debug_group();
+    }
+
+    fn insert_debug_marker(&mut self, label: &str) {
+        self.command_encoder
+            .get_or_create(&self.device)
+            .insert_debug_marker(label);
+    }
 }
 
 pub fn create_render_pass<'a, 'b>(
@@ -205,40 +274,65 @@ pub fn create_render_pass<'a, 'b>(
     global_render_resource_bindings: &'b RenderResourceBindings,
     refs: &WgpuResourceRefs<'a>,
     encoder: &'a mut wgpu::CommandEncoder,
-) -> wgpu::RenderPass<'a> {
-    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-        label: None,
-        color_attachments: &pass_descriptor
-            .color_attachments
-            .iter()
-            .map(|c| {
-                create_wgpu_color_attachment_descriptor(global_render_resource_bindings, refs, c)
-            })
-            .collect::<Vec<wgpu::RenderPassColorAttachmentDescriptor>>(),
-        depth_stencil_attachment: pass_descriptor.depth_stencil_attachment.as_ref().map(|d| {
+) -> Result<wgpu::RenderPass<'a>, RendererError> {
+    let color_attachments = pass_descriptor
+        .color_attachments
+        .iter()
+        .map(|c| create_wgpu_color_attachment_descriptor(global_render_resource_bindings, refs, c))
+        .collect::<Result<Vec<wgpu::RenderPassColorAttachmentDescriptor>, RendererError>>()?;
+    let depth_stencil_attachment = pass_descriptor
+        .depth_stencil_attachment
+        .as_ref()
+        .map(|d| {
             create_wgpu_depth_stencil_attachment_descriptor(
                 global_render_resource_bindings,
                 refs,
                 d,
             )
-        }),
-    })
+        })
+        .transpose()?;
+    Ok(encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: None,
+        color_attachments: &color_attachments,
+        depth_stencil_attachment,
+    }))
 }
 
 fn get_texture_view<'a>(
     global_render_resource_bindings: &RenderResourceBindings,
     refs: &WgpuResourceRefs<'a>,
     attachment: &TextureAttachment,
-) -> &'a wgpu::TextureView {
+) -> Result<&'a wgpu::TextureView, RendererError> {
     match attachment {
         TextureAttachment::Name(name) => match global_render_resource_bindings.get(&name) {
-            Some(RenderResourceBinding::Texture(resource)) => refs.textures.get(&resource).unwrap(),
+            Some(RenderResourceBinding::Texture(resource)) => refs
+                .textures
+                .get(&resource)
+                .ok_or(RendererError::MissingTexture(*resource)),
             _ => {
-                panic!("Color attachment {} does not exist.", name);
+                let mut known = global_render_resource_bindings
+                    .bindings
+                    .iter()
+                    .filter(|(_, binding)| binding.get_texture().is_some())
+                    .map(|(name, _)| name.clone())
+                    .collect::<Vec<_>>();
+                known.sort();
+                Err(RendererError::MissingAttachment {
+                    name: name.clone(),
+                    known,
+                })
             }
         },
-        TextureAttachment::Id(render_resource) => refs.textures.get(&render_resource).unwrap_or_else(|| &refs.swap_chain_frames.get(&render_resource).unwrap().output.view),
-        TextureAttachment::Input(_) => panic!("Encountered unset `TextureAttachment::Input`. The `RenderGraph` executor should always set `TextureAttachment::Inputs` to `TextureAttachment::RenderResource` before running. This is a bug, please report it!"),
+        TextureAttachment::Id(render_resource) => refs
+            .textures
+            .get(&render_resource)
+            .or_else(|| {
+                refs.swap_chain_frames
+                    .get(&render_resource)
+                    .map(|frame| &frame.output.view)
+            })
+            .ok_or(RendererError::MissingTexture(*render_resource)),
+        TextureAttachment::Input(_) => Err(RendererError::UnsetInputAttachment),
     }
 }
 
@@ -246,37 +340,38 @@ fn create_wgpu_color_attachment_descriptor<'a>(
     global_render_resource_bindings: &RenderResourceBindings,
     refs: &WgpuResourceRefs<'a>,
     color_attachment_descriptor: &RenderPassColorAttachmentDescriptor,
-) -> wgpu::RenderPassColorAttachmentDescriptor<'a> {
+) -> Result<wgpu::RenderPassColorAttachmentDescriptor<'a>, RendererError> {
     let attachment = get_texture_view(
         global_render_resource_bindings,
         refs,
         &color_attachment_descriptor.attachment,
-    );
+    )?;
 
     let resolve_target = color_attachment_descriptor
         .resolve_target
         .as_ref()
-        .map(|target| get_texture_view(global_render_resource_bindings, refs, &target));
+        .map(|target| get_texture_view(global_render_resource_bindings, refs, &target))
+        .transpose()?;
 
-    wgpu::RenderPassColorAttachmentDescriptor {
+    Ok(wgpu::RenderPassColorAttachmentDescriptor {
         ops: (&color_attachment_descriptor.ops).wgpu_into(),
         attachment,
         resolve_target,
-    }
+    })
 }
 
 fn create_wgpu_depth_stencil_attachment_descriptor<'a>(
     global_render_resource_bindings: &RenderResourceBindings,
     refs: &WgpuResourceRefs<'a>,
     depth_stencil_attachment_descriptor: &RenderPassDepthStencilAttachmentDescriptor,
-) -> wgpu::RenderPassDepthStencilAttachmentDescriptor<'a> {
+) -> Result<wgpu::RenderPassDepthStencilAttachmentDescriptor<'a>, RendererError> {
     let attachment = get_texture_view(
         global_render_resource_bindings,
         refs,
         &depth_stencil_attachment_descriptor.attachment,
-    );
+    )?;
 
-    wgpu::RenderPassDepthStencilAttachmentDescriptor {
+    Ok(wgpu::RenderPassDepthStencilAttachmentDescriptor {
         attachment,
         depth_ops: depth_stencil_attachment_descriptor
             .depth_ops
@@ -286,5 +381,5 @@ fn create_wgpu_depth_stencil_attachment_descriptor<'a>(
             .stencil_ops
             .as_ref()
             .map(|ops| ops.wgpu_into()),
-    }
+    })
 }
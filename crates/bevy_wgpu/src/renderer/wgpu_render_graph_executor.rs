@@ -1,9 +1,10 @@
 use super::{WgpuRenderContext, WgpuRenderResourceContext};
 use bevy_ecs::world::World;
 use bevy_render::{
-    render_graph::{Edge, NodeId, ResourceSlots, StageBorrow},
-    renderer::RenderResourceContext,
+    render_graph::{Edge, NodeId, OrderedJobBorrow, ResourceSlots, StageBorrow},
+    renderer::{RenderContext, RenderResourceContext},
 };
+use bevy_tasks::ComputeTaskPool;
 use bevy_utils::HashMap;
 use parking_lot::RwLock;
 use std::sync::Arc;
@@ -18,7 +19,7 @@ impl WgpuRenderGraphExecutor {
         &self,
         world: &World,
         device: Arc<wgpu::Device>,
-        queue: &mut wgpu::Queue,
+        queue: &wgpu::Queue,
         stages: &mut [StageBorrow],
     ) {
         let render_resource_context = {
@@ -30,75 +31,117 @@ impl WgpuRenderGraphExecutor {
                 .unwrap()
                 .clone()
         };
+        let task_pool = world.get_resource::<ComputeTaskPool>();
+        let thread_count = task_pool
+            .map_or(1, |task_pool| task_pool.thread_num())
+            .min(self.max_thread_count)
+            .max(1);
         let node_outputs: Arc<RwLock<HashMap<NodeId, ResourceSlots>>> = Default::default();
         for stage in stages.iter_mut() {
             // TODO: sort jobs and slice by "amount of work" / weights
             // stage.jobs.sort_by_key(|j| j.node_states.len());
 
-            let (sender, receiver) = crossbeam_channel::bounded(self.max_thread_count);
-            let chunk_size = (stage.jobs.len() + self.max_thread_count - 1) / self.max_thread_count; // divide ints rounding remainder up
-            let mut actual_thread_count = 0;
-            // crossbeam_utils::thread::scope(|s| {
-            for jobs_chunk in stage.jobs.chunks_mut(chunk_size) {
-                let sender = sender.clone();
-                let world = &*world;
-                actual_thread_count += 1;
-                let device = device.clone();
-                let render_resource_context = render_resource_context.clone();
-                let node_outputs = node_outputs.clone();
-                // s.spawn(move |_| {
-                let mut render_context = WgpuRenderContext::new(device, render_resource_context);
-                for job in jobs_chunk.iter_mut() {
-                    for node_state in job.node_states.iter_mut() {
-                        // bind inputs from connected node outputs
-                        for (i, mut input_slot) in node_state.input_slots.iter_mut().enumerate() {
-                            if let Edge::SlotEdge {
-                                output_node,
-                                output_index,
-                                ..
-                            } = node_state.edges.get_input_slot_edge(i).unwrap()
-                            {
-                                let node_outputs = node_outputs.read();
-                                let outputs = if let Some(outputs) = node_outputs.get(output_node) {
-                                    outputs
-                                } else {
-                                    panic!("Node inputs not set.")
-                                };
-
-                                let output_resource =
-                                    outputs.get(*output_index).expect("Output should be set.");
-                                input_slot.resource = Some(output_resource);
-                            } else {
-                                panic!("No edge connected to input.")
-                            }
-                        }
-                        node_state.node.update(
+            if stage.jobs.is_empty() {
+                continue;
+            }
+            // divide ints rounding remainder up
+            let chunk_size = (stage.jobs.len() + thread_count - 1) / thread_count;
+            // each chunk of jobs is recorded into its own command encoder. the resulting command
+            // buffers are submitted in job order.
+            let mut command_buffers = match task_pool {
+                Some(task_pool) if thread_count > 1 => task_pool.scope(|scope| {
+                    for jobs_chunk in stage.jobs.chunks_mut(chunk_size) {
+                        let device = device.clone();
+                        let render_resource_context = render_resource_context.clone();
+                        let node_outputs = node_outputs.clone();
+                        scope.spawn(async move {
+                            encode_jobs(
+                                world,
+                                device,
+                                render_resource_context,
+                                &node_outputs,
+                                jobs_chunk,
+                            )
+                        });
+                    }
+                }),
+                _ => stage
+                    .jobs
+                    .chunks_mut(chunk_size)
+                    .map(|jobs_chunk| {
+                        encode_jobs(
                             world,
-                            &mut render_context,
-                            &node_state.input_slots,
-                            &mut node_state.output_slots,
-                        );
+                            device.clone(),
+                            render_resource_context.clone(),
+                            &node_outputs,
+                            jobs_chunk,
+                        )
+                    })
+                    .collect(),
+            };
 
-                        node_outputs
-                            .write()
-                            .insert(node_state.id, node_state.output_slots.clone());
-                    }
-                }
-                sender.send(render_context.finish()).unwrap();
-                // });
-            }
-            // })
-            // .unwrap();
+            queue.submit(command_buffers.drain(..).flatten());
+        }
+    }
+}
 
-            let mut command_buffers = Vec::new();
-            for _i in 0..actual_thread_count {
-                let command_buffer = receiver.recv().unwrap();
-                if let Some(command_buffer) = command_buffer {
-                    command_buffers.push(command_buffer);
+fn encode_jobs(
+    world: &World,
+    device: Arc<wgpu::Device>,
+    render_resource_context: WgpuRenderResourceContext,
+    node_outputs: &RwLock<HashMap<NodeId, ResourceSlots>>,
+    jobs: &mut [OrderedJobBorrow],
+) -> Option<wgpu::CommandBuffer> {
+    let mut render_context = WgpuRenderContext::new(device, render_resource_context);
+    for job in jobs.iter_mut() {
+        for node_state in job.node_states.iter_mut() {
+            #[cfg(feature = "trace")]
+            let node_span = bevy_utils::tracing::info_span!(
+                "render_graph_node",
+                name = node_state.name.as_deref().unwrap_or("")
+            );
+            #[cfg(feature = "trace")]
+            let _node_guard = node_span.enter();
+            // bind inputs from connected node outputs
+            for (i, mut input_slot) in node_state.input_slots.iter_mut().enumerate() {
+                if let Edge::SlotEdge {
+                    output_node,
+                    output_index,
+                    ..
+                } = node_state.edges.get_input_slot_edge(i).unwrap()
+                {
+                    let node_outputs = node_outputs.read();
+                    let outputs = if let Some(outputs) = node_outputs.get(output_node) {
+                        outputs
+                    } else {
+                        panic!("Node inputs not set.")
+                    };
+
+                    let output_resource =
+                        outputs.get(*output_index).expect("Output should be set.");
+                    input_slot.resource = Some(output_resource);
+                } else {
+                    panic!("No edge connected to input.")
                 }
             }
+            // name the commands of each node in GPU captures
+            if let Some(name) = &node_state.name {
+                render_context.push_debug_group(name);
+            }
+            node_state.node.update(
+                world,
+                &mut render_context,
+                &node_state.input_slots,
+                &mut node_state.output_slots,
+            );
+            if node_state.name.is_some() {
+                render_context.pop_debug_group();
+            }
 
-            queue.submit(command_buffers.drain(..));
+            node_outputs
+                .write()
+                .insert(node_state.id, node_state.output_slots.clone());
         }
     }
+    render_context.finish()
 }
@@ -1,22 +1,23 @@
-use crate::{wgpu_type_converter::WgpuInto, WgpuBindGroupInfo, WgpuResources};
+use crate::{wgpu_type_converter::WgpuInto, BufferMap, WgpuBindGroupInfo, WgpuResources};
 
 use crate::wgpu_type_converter::OwnedWgpuVertexBufferLayout;
 use bevy_asset::{Assets, Handle, HandleUntyped};
 use bevy_render::{
     pipeline::{
-        BindGroupDescriptor, BindGroupDescriptorId, BindingShaderStage, PipelineDescriptor,
+        BindGroupDescriptor, BindGroupDescriptorId, BindingShaderStage, ComputePipelineDescriptor,
+        PipelineDescriptor,
     },
     renderer::{
-        BindGroup, BufferId, BufferInfo, BufferMapMode, RenderResourceBinding,
+        BindGroup, BufferId, BufferInfo, BufferMapMode, BufferMapState, RenderResourceBinding,
         RenderResourceContext, RenderResourceId, SamplerId, TextureId,
     },
-    shader::{glsl_to_spirv, Shader, ShaderError, ShaderSource},
-    texture::{Extent3d, SamplerDescriptor, TextureDescriptor},
+    shader::{Shader, ShaderError},
+    texture::{Extent3d, SamplerDescriptor, TextureCompression, TextureDescriptor, TextureUsage},
 };
 use bevy_utils::tracing::trace;
 use bevy_window::{Window, WindowId};
 use futures_lite::future;
-use std::{borrow::Cow, num::NonZeroU64, ops::Range, sync::Arc};
+use std::{borrow::Cow, future::Future, num::NonZeroU64, ops::Range, sync::Arc};
 use wgpu::util::DeviceExt;
 
 #[derive(Clone, Debug)]
@@ -30,6 +31,20 @@ pub const BIND_BUFFER_ALIGNMENT: usize = wgpu::BIND_BUFFER_ALIGNMENT as usize;
 pub const COPY_BUFFER_ALIGNMENT: usize = wgpu::COPY_BUFFER_ALIGNMENT as usize;
 pub const PUSH_CONSTANT_ALIGNMENT: u32 = wgpu::PUSH_CONSTANT_ALIGNMENT;
 
+/// Blocks until `map_future` resolves. wgpu can't wait for a single submission, so when the map
+/// isn't done yet this sleeps until the device has finished all the work submitted so far.
+pub(crate) fn wait_for_map<F: Future + Unpin>(
+    device: &wgpu::Device,
+    mut map_future: F,
+) -> F::Output {
+    device.poll(wgpu::Maintain::Poll);
+    if let Some(result) = future::block_on(future::poll_once(&mut map_future)) {
+        return result;
+    }
+    device.poll(wgpu::Maintain::Wait);
+    future::block_on(map_future)
+}
+
 impl WgpuRenderResourceContext {
     pub fn new(device: Arc<wgpu::Device>) -> Self {
         WgpuRenderResourceContext {
@@ -43,6 +58,52 @@ impl WgpuRenderResourceContext {
         window_surfaces.insert(window_id, surface);
     }
 
+    /// Releases every window surface along with its swap chain. Surfaces must be set again with
+    /// [WgpuRenderResourceContext::set_window_surface] before rendering to the windows.
+    pub fn remove_window_surfaces(&self) {
+        // swap chains reference their surface, so they have to go first
+        self.resources.window_swap_chains.write().clear();
+        self.resources.window_surfaces.write().clear();
+    }
+
+    /// Polls the device without blocking and records the buffer mappings started with
+    /// [RenderResourceContext::map_buffer_async] that completed. Called once per frame.
+    pub fn poll_buffer_maps(&self) {
+        let mut buffer_maps = self.resources.buffer_maps.lock();
+        if buffer_maps
+            .values()
+            .all(|buffer_map| buffer_map.future.is_none())
+        {
+            return;
+        }
+        self.device.poll(wgpu::Maintain::Poll);
+        for buffer_map in buffer_maps.values_mut() {
+            let result = match buffer_map.future.as_mut() {
+                Some(map_future) => future::block_on(future::poll_once(map_future)),
+                None => continue,
+            };
+            if let Some(result) = result {
+                buffer_map.state = if result.is_ok() {
+                    BufferMapState::Mapped
+                } else {
+                    BufferMapState::Failed
+                };
+                buffer_map.future = None;
+            }
+        }
+    }
+
+    /// Blocks until the mapping requested with `map_buffer_async` resolves, and returns its state.
+    /// Like [wait_for_map], this waits for all the submitted work if the map isn't done yet.
+    pub fn wait_for_buffer_map(&self, id: BufferId) -> BufferMapState {
+        self.poll_buffer_maps();
+        if self.get_buffer_map_state(id) == BufferMapState::Pending {
+            self.device.poll(wgpu::Maintain::Wait);
+            self.poll_buffer_maps();
+        }
+        self.get_buffer_map_state(id)
+    }
+
     pub fn copy_buffer_to_buffer(
         &self,
         command_encoder: &mut wgpu::CommandEncoder,
@@ -206,6 +267,8 @@ impl WgpuRenderResourceContext {
                     wgpu::ShaderStage::VERTEX
                 } else if binding.shader_stage == BindingShaderStage::FRAGMENT {
                     wgpu::ShaderStage::FRAGMENT
+                } else if binding.shader_stage == BindingShaderStage::COMPUTE {
+                    wgpu::ShaderStage::COMPUTE
                 } else {
                     panic!("Invalid binding shader stage.")
                 };
@@ -239,6 +302,12 @@ impl WgpuRenderResourceContext {
 
 impl RenderResourceContext for WgpuRenderResourceContext {
     fn create_sampler(&self, sampler_descriptor: &SamplerDescriptor) -> SamplerId {
+        let mut sampler_cache = self.resources.sampler_cache.write();
+        if let Some(id) = sampler_cache.ids.get(sampler_descriptor).copied() {
+            sampler_cache.users.get_mut(&id).unwrap().1 += 1;
+            return id;
+        }
+
         let mut samplers = self.resources.samplers.write();
 
         let descriptor: wgpu::SamplerDescriptor = (*sampler_descriptor).wgpu_into();
@@ -246,6 +315,8 @@ impl RenderResourceContext for WgpuRenderResourceContext {
 
         let id = SamplerId::new();
         samplers.insert(id, sampler);
+        sampler_cache.ids.insert(*sampler_descriptor, id);
+        sampler_cache.users.insert(id, (*sampler_descriptor, 1));
         id
     }
 
@@ -256,9 +327,26 @@ impl RenderResourceContext for WgpuRenderResourceContext {
 
         let descriptor: wgpu::TextureDescriptor = (&texture_descriptor).wgpu_into();
         let texture = self.device.create_texture(&descriptor);
-        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor {
+            dimension: Some(texture_descriptor.view_dimension().wgpu_into()),
+            ..Default::default()
+        });
 
         let id = TextureId::new();
+        // shaders can only sample the depth aspect of depth-stencil textures
+        if texture_descriptor.format.has_stencil()
+            && texture_descriptor.usage.contains(TextureUsage::SAMPLED)
+        {
+            let depth_texture_view = texture.create_view(&wgpu::TextureViewDescriptor {
+                dimension: Some(texture_descriptor.view_dimension().wgpu_into()),
+                aspect: wgpu::TextureAspect::DepthOnly,
+                ..Default::default()
+            });
+            self.resources
+                .depth_texture_views
+                .write()
+                .insert(id, depth_texture_view);
+        }
         texture_descriptors.insert(id, texture_descriptor);
         texture_views.insert(id, texture_view);
         textures.insert(id, texture);
@@ -309,6 +397,7 @@ impl RenderResourceContext for WgpuRenderResourceContext {
 
         buffers.remove(&buffer);
         buffer_infos.remove(&buffer);
+        self.resources.buffer_maps.lock().remove(&buffer);
     }
 
     fn remove_texture(&self, texture: TextureId) {
@@ -318,10 +407,22 @@ impl RenderResourceContext for WgpuRenderResourceContext {
 
         textures.remove(&texture);
         texture_views.remove(&texture);
+        self.resources.depth_texture_views.write().remove(&texture);
         texture_descriptors.remove(&texture);
     }
 
     fn remove_sampler(&self, sampler: SamplerId) {
+        let mut sampler_cache = self.resources.sampler_cache.write();
+        if let Some((descriptor, users)) = sampler_cache.users.get_mut(&sampler) {
+            *users -= 1;
+            if *users > 0 {
+                return;
+            }
+            let descriptor = *descriptor;
+            sampler_cache.ids.remove(&descriptor);
+            sampler_cache.users.remove(&sampler);
+        }
+
         let mut samplers = self.resources.samplers.write();
         samplers.remove(&sampler);
     }
@@ -516,6 +617,58 @@ impl RenderResourceContext for WgpuRenderResourceContext {
         render_pipelines.insert(pipeline_handle, render_pipeline);
     }
 
+    fn create_compute_pipeline(
+        &self,
+        pipeline_handle: Handle<ComputePipelineDescriptor>,
+        pipeline_descriptor: &ComputePipelineDescriptor,
+        shaders: &Assets<Shader>,
+    ) {
+        if self
+            .resources
+            .compute_pipelines
+            .read()
+            .get(&pipeline_handle)
+            .is_some()
+        {
+            return;
+        }
+
+        let layout = pipeline_descriptor.get_layout().unwrap();
+        for bind_group_descriptor in layout.bind_groups.iter() {
+            self.create_bind_group_layout(&bind_group_descriptor);
+        }
+
+        let bind_group_layouts = self.resources.bind_group_layouts.read();
+        let bind_group_layouts = layout
+            .bind_groups
+            .iter()
+            .map(|bind_group| bind_group_layouts.get(&bind_group.id).unwrap())
+            .collect::<Vec<&wgpu::BindGroupLayout>>();
+
+        let pipeline_layout = self
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: None,
+                bind_group_layouts: bind_group_layouts.as_slice(),
+                push_constant_ranges: &[],
+            });
+
+        self.create_shader_module(&pipeline_descriptor.shader, shaders);
+        let shader_modules = self.resources.shader_modules.read();
+        let shader_module = shader_modules.get(&pipeline_descriptor.shader).unwrap();
+
+        let compute_pipeline =
+            self.device
+                .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                    label: None,
+                    layout: Some(&pipeline_layout),
+                    module: shader_module,
+                    entry_point: "main",
+                });
+        let mut compute_pipelines = self.resources.compute_pipelines.write();
+        compute_pipelines.insert(pipeline_handle, compute_pipeline);
+    }
+
     fn bind_group_descriptor_exists(
         &self,
         bind_group_descriptor_id: BindGroupDescriptorId,
@@ -538,6 +691,7 @@ impl RenderResourceContext for WgpuRenderResourceContext {
                 bind_group.id
             );
             let texture_views = self.resources.texture_views.read();
+            let depth_texture_views = self.resources.depth_texture_views.read();
             let samplers = self.resources.samplers.read();
             let buffers = self.resources.buffers.read();
             let bind_group_layouts = self.resources.bind_group_layouts.read();
@@ -549,8 +703,9 @@ impl RenderResourceContext for WgpuRenderResourceContext {
                 .map(|indexed_binding| {
                     let wgpu_resource = match &indexed_binding.entry {
                         RenderResourceBinding::Texture(resource) => {
-                            let texture_view = texture_views
+                            let texture_view = depth_texture_views
                                 .get(&resource)
+                                .or_else(|| texture_views.get(&resource))
                                 .unwrap_or_else(|| panic!("{:?}", resource));
                             wgpu::BindingResource::TextureView(texture_view)
                         }
@@ -648,22 +803,65 @@ impl RenderResourceContext for WgpuRenderResourceContext {
             BufferMapMode::Write => wgpu::MapMode::Write,
         };
         let data = buffer_slice.map_async(wgpu_mode);
-        self.device.poll(wgpu::Maintain::Wait);
-        if future::block_on(data).is_err() {
+        if wait_for_map(&self.device, Box::pin(data)).is_err() {
             panic!("Failed to map buffer to host.");
         }
     }
 
+    fn map_buffer_async(&self, id: BufferId, mode: BufferMapMode) {
+        let buffers = self.resources.buffers.read();
+        let buffer = buffers.get(&id).unwrap();
+        let wgpu_mode = match mode {
+            BufferMapMode::Read => wgpu::MapMode::Read,
+            BufferMapMode::Write => wgpu::MapMode::Write,
+        };
+        let future = buffer.slice(..).map_async(wgpu_mode);
+        self.resources.buffer_maps.lock().insert(
+            id,
+            BufferMap {
+                state: BufferMapState::Pending,
+                future: Some(Box::pin(future)),
+            },
+        );
+    }
+
+    fn get_buffer_map_state(&self, id: BufferId) -> BufferMapState {
+        self.resources
+            .buffer_maps
+            .lock()
+            .get(&id)
+            .map_or(BufferMapState::Unmapped, |buffer_map| buffer_map.state)
+    }
+
     fn unmap_buffer(&self, id: BufferId) {
         let buffers = self.resources.buffers.read();
         let buffer = buffers.get(&id).unwrap();
         buffer.unmap();
+        self.resources.buffer_maps.lock().remove(&id);
     }
 
     fn get_aligned_texture_size(&self, size: usize) -> usize {
         (size + COPY_BYTES_PER_ROW_ALIGNMENT - 1) & !(COPY_BYTES_PER_ROW_ALIGNMENT - 1)
     }
 
+    fn supported_texture_compression(&self) -> TextureCompression {
+        let features = self.device.features();
+        let mut compression = TextureCompression::empty();
+        compression.set(
+            TextureCompression::BC,
+            features.contains(wgpu::Features::TEXTURE_COMPRESSION_BC),
+        );
+        compression.set(
+            TextureCompression::ETC2,
+            features.contains(wgpu::Features::TEXTURE_COMPRESSION_ETC2),
+        );
+        compression.set(
+            TextureCompression::ASTC_LDR,
+            features.contains(wgpu::Features::TEXTURE_COMPRESSION_ASTC_LDR),
+        );
+        compression
+    }
+
     fn get_aligned_uniform_size(&self, size: usize, dynamic: bool) -> usize {
         if dynamic {
             (size + BIND_BUFFER_ALIGNMENT - 1) & !(BIND_BUFFER_ALIGNMENT - 1)
@@ -677,13 +875,6 @@ impl RenderResourceContext for WgpuRenderResourceContext {
         shader: &Shader,
         macros: Option<&[String]>,
     ) -> Result<Shader, ShaderError> {
-        let spirv_data = match shader.source {
-            ShaderSource::Spirv(ref bytes) => bytes.clone(),
-            ShaderSource::Glsl(ref source) => glsl_to_spirv(&source, shader.stage, macros)?,
-        };
-        Ok(Shader {
-            source: ShaderSource::Spirv(spirv_data),
-            ..*shader
-        })
+        shader.get_spirv_shader(macros)
     }
 }
@@ -0,0 +1,220 @@
+use crate::{insert_render_resources, WgpuOptions, WgpuRenderer};
+use bevy_app::prelude::*;
+use bevy_ecs::world::World;
+use bevy_window::Window;
+use raw_window_handle::{web::WebHandle, HasRawWindowHandle, RawWindowHandle};
+use std::{
+    rc::Rc,
+    sync::atomic::{AtomicU32, Ordering},
+};
+use wasm_bindgen::{prelude::*, JsCast};
+use web_sys::HtmlCanvasElement;
+
+#[cfg(not(feature = "bevy_winit"))]
+pub(crate) use canvas_windows::*;
+
+/// Browsers can't block on the adapter and device requests, so the app's runner is wrapped to
+/// create the [WgpuRenderer] asynchronously first, unless one was already inserted. The app then
+/// runs from `requestAnimationFrame` callbacks: winit's event loop polls once per animation frame,
+/// and without winit the app is updated once per animation frame.
+pub(crate) fn run_after_wgpu_renderer_creation(app: &mut AppBuilder) {
+    #[cfg(feature = "bevy_winit")]
+    let runner = Rc::new(std::mem::replace(&mut app.app.runner, Box::new(|_| {})));
+    app.set_runner(move |mut app: App| {
+        #[cfg(feature = "bevy_winit")]
+        let runner = runner.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            if !app.world.contains_resource::<WgpuRenderer>() {
+                let options = app
+                    .world
+                    .get_resource::<WgpuOptions>()
+                    .cloned()
+                    .unwrap_or_else(WgpuOptions::default);
+                let wgpu_renderer = WgpuRenderer::new(options).await;
+                app.world.insert_resource(wgpu_renderer);
+            }
+            insert_render_resources(&mut app.world);
+
+            // winit's event loop throws a JS exception to never return, which must not unwind
+            // through the executor polling this future
+            #[cfg(feature = "bevy_winit")]
+            request_animation_frame(&Closure::once_into_js(move || (runner)(app)));
+            #[cfg(not(feature = "bevy_winit"))]
+            run_animation_frames(app);
+        });
+    });
+}
+
+/// Renders a frame with the [WgpuRenderer] resource, once it was created
+pub(crate) fn wgpu_render_system(world: &mut World) {
+    if let Some(mut wgpu_renderer) = world.remove_resource::<WgpuRenderer>() {
+        wgpu_renderer.update(world);
+        world.insert_resource(wgpu_renderer);
+    }
+}
+
+#[cfg(not(feature = "bevy_winit"))]
+fn run_animation_frames(mut app: App) {
+    use bevy_app::{AppExit, ManualEventReader};
+    use std::cell::RefCell;
+
+    let mut app_exit_event_reader = ManualEventReader::<AppExit>::default();
+    let callback = Rc::new(RefCell::new(None::<Closure<dyn FnMut()>>));
+    let next_callback = callback.clone();
+    *callback.borrow_mut() = Some(Closure::wrap(Box::new(move || {
+        app.update();
+        // the loop stops by not requesting another frame
+        if !app.exit_requested(&mut app_exit_event_reader) {
+            request_animation_frame(next_callback.borrow().as_ref().unwrap().as_ref());
+        }
+    }) as Box<dyn FnMut()>));
+    request_animation_frame(callback.borrow().as_ref().unwrap().as_ref());
+}
+
+fn request_animation_frame(callback: &JsValue) {
+    web_sys::window()
+        .unwrap()
+        .request_animation_frame(callback.unchecked_ref())
+        .expect("Should register `requestAnimationFrame`.");
+}
+
+/// The raw handle of a canvas element, which wgpu finds the canvas by to create a surface for it
+pub(crate) struct CanvasHandle(u32);
+
+impl CanvasHandle {
+    /// Returns the handle winit assigned to `canvas`, or assigns it a new one
+    pub fn new(canvas: &HtmlCanvasElement) -> Self {
+        // counts down, so that handles never collide with the ones winit counts up from 1
+        static NEXT_ID: AtomicU32 = AtomicU32::new(u32::MAX);
+
+        if let Some(id) = canvas
+            .get_attribute("data-raw-handle")
+            .and_then(|id| id.parse().ok())
+        {
+            return CanvasHandle(id);
+        }
+        let id = NEXT_ID.fetch_sub(1, Ordering::Relaxed);
+        canvas
+            .set_attribute("data-raw-handle", &id.to_string())
+            .expect("Cannot set the canvas handle attribute.");
+        CanvasHandle(id)
+    }
+}
+
+unsafe impl HasRawWindowHandle for CanvasHandle {
+    fn raw_window_handle(&self) -> RawWindowHandle {
+        RawWindowHandle::Web(WebHandle {
+            id: self.0,
+            ..WebHandle::empty()
+        })
+    }
+}
+
+/// Finds the canvas `window` draws to
+pub(crate) fn window_canvas(world: &World, window: &Window) -> HtmlCanvasElement {
+    #[cfg(feature = "bevy_winit")]
+    let id = {
+        let winit_windows = world.get_resource::<bevy_winit::WinitWindows>().unwrap();
+        match winit_windows
+            .get_window(window.id())
+            .unwrap()
+            .raw_window_handle()
+        {
+            RawWindowHandle::Web(handle) => handle.id,
+            _ => unreachable!("winit windows are canvases in the browser"),
+        }
+    };
+    #[cfg(not(feature = "bevy_winit"))]
+    let id = world.get_resource::<WebCanvases>().unwrap().handles[&window.id()];
+
+    web_sys::window()
+        .unwrap()
+        .document()
+        .unwrap()
+        .query_selector(&format!("canvas[data-raw-handle=\"{}\"]", id))
+        .ok()
+        .flatten()
+        .and_then(|canvas| canvas.dyn_into::<HtmlCanvasElement>().ok())
+        .expect("Cannot find the window's canvas.")
+}
+
+/// Without winit, windows are created as canvases by the wgpu plugin
+#[cfg(not(feature = "bevy_winit"))]
+mod canvas_windows {
+    use super::CanvasHandle;
+    use bevy_app::{EventReader, EventWriter};
+    use bevy_ecs::system::ResMut;
+    use bevy_utils::HashMap;
+    use bevy_window::{CreateWindow, Window, WindowCreated, WindowDescriptor, WindowId, Windows};
+    use wasm_bindgen::JsCast;
+    use web_sys::HtmlCanvasElement;
+
+    /// The canvas handles of windows, by window
+    #[derive(Default)]
+    pub struct WebCanvases {
+        pub handles: HashMap<WindowId, u32>,
+    }
+
+    pub fn create_canvas_windows_system(
+        mut windows: ResMut<Windows>,
+        mut canvases: ResMut<WebCanvases>,
+        mut create_window_events: EventReader<CreateWindow>,
+        mut window_created_events: EventWriter<WindowCreated>,
+    ) {
+        for create_window_event in create_window_events.iter() {
+            let descriptor = &create_window_event.descriptor;
+            let canvas = find_or_create_canvas(descriptor);
+            let scale_factor = web_sys::window().unwrap().device_pixel_ratio();
+            let width = (descriptor.width as f64 * scale_factor) as u32;
+            let height = (descriptor.height as f64 * scale_factor) as u32;
+            canvas.set_width(width);
+            canvas.set_height(height);
+            canvas
+                .set_attribute(
+                    "style",
+                    &format!(
+                        "width:{}px;height:{}px",
+                        descriptor.width, descriptor.height
+                    ),
+                )
+                .expect("Cannot set the canvas size.");
+
+            let CanvasHandle(handle) = CanvasHandle::new(&canvas);
+            canvases.handles.insert(create_window_event.id, handle);
+            windows.add(Window::new(
+                create_window_event.id,
+                descriptor,
+                width,
+                height,
+                scale_factor,
+                None,
+            ));
+            window_created_events.send(WindowCreated {
+                id: create_window_event.id,
+            });
+        }
+    }
+
+    fn find_or_create_canvas(descriptor: &WindowDescriptor) -> HtmlCanvasElement {
+        let document = web_sys::window().unwrap().document().unwrap();
+        let canvas = if let Some(selector) = &descriptor.canvas {
+            document
+                .query_selector(selector)
+                .expect("Cannot query for canvas element.")
+                .unwrap_or_else(|| panic!("Cannot find element: {}.", selector))
+        } else {
+            let canvas = document
+                .create_element("canvas")
+                .expect("Cannot create a canvas element.");
+            document
+                .body()
+                .unwrap()
+                .append_child(&canvas)
+                .expect("Append canvas to HTML body.");
+            canvas
+        };
+        canvas
+            .dyn_into::<HtmlCanvasElement>()
+            .expect("Element is not a canvas.")
+    }
+}
@@ -0,0 +1,134 @@
+use crate::{renderer::WgpuRenderContext, WgpuResources, WgpuResourcesReadLock};
+use bevy_ecs::world::World;
+use bevy_render::{
+    render_graph::{Node, ResourceSlotInfo, ResourceSlots},
+    renderer::{BufferId, RenderContext},
+};
+use std::sync::Arc;
+
+/// Direct access to the [wgpu::Device] and [wgpu::Queue] used by the
+/// [WgpuRenderer](crate::WgpuRenderer).
+///
+/// This is an escape hatch for advanced users that want to prototype rendering techniques bevy
+/// doesn't support yet. Resources created here are not tracked by bevy, so they are not visible
+/// to the render graph unless a [WgpuNode] exposes them. Prefer
+/// [RenderResourceContext](bevy_render::renderer::RenderResourceContext) whenever it is
+/// sufficient.
+#[derive(Debug, Clone)]
+pub struct WgpuRawContext {
+    device: Arc<wgpu::Device>,
+    queue: Arc<wgpu::Queue>,
+}
+
+impl WgpuRawContext {
+    pub fn new(device: Arc<wgpu::Device>, queue: Arc<wgpu::Queue>) -> Self {
+        WgpuRawContext { device, queue }
+    }
+
+    #[inline]
+    pub fn device(&self) -> &wgpu::Device {
+        &self.device
+    }
+
+    #[inline]
+    pub fn queue(&self) -> &wgpu::Queue {
+        &self.queue
+    }
+}
+
+/// The state available to a [WgpuNode] while it records commands for the current frame
+pub struct WgpuNodeContext<'a> {
+    pub device: &'a wgpu::Device,
+    /// The encoder shared with the rest of the render graph. Commands recorded here are submitted
+    /// in graph order, after the node's inputs have been written.
+    pub command_encoder: &'a mut wgpu::CommandEncoder,
+    resources: &'a WgpuResources,
+}
+
+impl<'a> WgpuNodeContext<'a> {
+    pub fn buffer(&self, buffer: BufferId) -> Option<Arc<wgpu::Buffer>> {
+        self.resources.buffers.read().get(&buffer).cloned()
+    }
+
+    /// Grabs a read lock on all wgpu resources (including swap chain frames). The lock must be
+    /// acquired _before_ beginning a [wgpu::RenderPass] that references those resources. See
+    /// [WgpuResourcesReadLock] for details.
+    pub fn read_resources(&self) -> WgpuResourcesReadLock<'a> {
+        self.resources.read()
+    }
+}
+
+/// A render graph node that records raw wgpu commands. Reads and writes of render graph resources
+/// are declared through the node's input and output slots, which lets the graph order it relative
+/// to other nodes.
+///
+/// Add it to the graph by wrapping it in a [WgpuRawNode].
+pub trait WgpuNode: Send + Sync + 'static {
+    fn input(&self) -> &[ResourceSlotInfo] {
+        &[]
+    }
+
+    fn output(&self) -> &[ResourceSlotInfo] {
+        &[]
+    }
+
+    /// Prepare the node with unique world access. This runs once per graph run before
+    /// [WgpuNode::record] is called.
+    fn prepare(&mut self, _world: &mut World) {}
+
+    /// Record the node's commands for this frame
+    fn record(
+        &mut self,
+        world: &World,
+        context: &mut WgpuNodeContext,
+        input: &ResourceSlots,
+        output: &mut ResourceSlots,
+    );
+}
+
+/// Adapts a [WgpuNode] to the backend agnostic [Node] trait
+pub struct WgpuRawNode<T: WgpuNode> {
+    node: T,
+}
+
+impl<T: WgpuNode> WgpuRawNode<T> {
+    pub fn new(node: T) -> Self {
+        WgpuRawNode { node }
+    }
+}
+
+impl<T: WgpuNode> Node for WgpuRawNode<T> {
+    fn input(&self) -> &[ResourceSlotInfo] {
+        self.node.input()
+    }
+
+    fn output(&self) -> &[ResourceSlotInfo] {
+        self.node.output()
+    }
+
+    fn prepare(&mut self, world: &mut World) {
+        self.node.prepare(world);
+    }
+
+    fn update(
+        &mut self,
+        world: &World,
+        render_context: &mut dyn RenderContext,
+        input: &ResourceSlots,
+        output: &mut ResourceSlots,
+    ) {
+        let WgpuRenderContext {
+            device,
+            command_encoder,
+            render_resource_context,
+        } = render_context
+            .downcast_mut::<WgpuRenderContext>()
+            .expect("WgpuRawNode can only run on the wgpu render backend.");
+        let mut context = WgpuNodeContext {
+            device: &**device,
+            command_encoder: command_encoder.get_or_create(&device),
+            resources: &render_resource_context.resources,
+        };
+        self.node.record(world, &mut context, input, output);
+    }
+}
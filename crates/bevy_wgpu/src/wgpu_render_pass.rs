@@ -1,9 +1,10 @@
 use crate::{renderer::WgpuRenderContext, wgpu_type_converter::WgpuInto, WgpuResourceRefs};
 use bevy_asset::Handle;
 use bevy_render::{
+    color::Color,
     pass::RenderPass,
     pipeline::{BindGroupDescriptorId, IndexFormat, PipelineDescriptor},
-    renderer::{BindGroupId, BufferId, RenderContext},
+    renderer::{BindGroupId, BufferId, DrawIndexedIndirectArgs, DrawIndirectArgs, RenderContext},
 };
 use bevy_utils::tracing::trace;
 use std::ops::Range;
@@ -16,6 +17,16 @@ pub struct WgpuRenderPass<'a> {
     pub pipeline_descriptor: Option<&'a PipelineDescriptor>,
 }
 
+impl<'a> WgpuRenderPass<'a> {
+    /// Without the `MultiDrawIndirect` feature, multi draws are issued one draw at a time
+    fn supports_multi_draw_indirect(&self) -> bool {
+        self.render_context
+            .device
+            .features()
+            .contains(wgpu::Features::MULTI_DRAW_INDIRECT)
+    }
+}
+
 impl<'a> RenderPass for WgpuRenderPass<'a> {
     fn get_render_context(&self) -> &dyn RenderContext {
         self.render_context
@@ -40,6 +51,10 @@ impl<'a> RenderPass for WgpuRenderPass<'a> {
         self.render_pass.set_stencil_reference(reference);
     }
 
+    fn set_blend_color(&mut self, color: Color) {
+        self.render_pass.set_blend_color(color.wgpu_into());
+    }
+
     fn set_index_buffer(&mut self, buffer_id: BufferId, offset: u64, index_format: IndexFormat) {
         let buffer = self.wgpu_resources.buffers.get(&buffer_id).unwrap();
         self.render_pass
@@ -55,6 +70,32 @@ impl<'a> RenderPass for WgpuRenderPass<'a> {
         self.render_pass.draw(vertices, instances);
     }
 
+    fn draw_indirect(&mut self, buffer_id: BufferId, offset: u64, count: u32) {
+        let buffer = self.wgpu_resources.buffers.get(&buffer_id).unwrap();
+        if count > 1 && self.supports_multi_draw_indirect() {
+            self.render_pass.multi_draw_indirect(buffer, offset, count);
+            return;
+        }
+        let stride = std::mem::size_of::<DrawIndirectArgs>() as u64;
+        for i in 0..count as u64 {
+            self.render_pass.draw_indirect(buffer, offset + i * stride);
+        }
+    }
+
+    fn draw_indexed_indirect(&mut self, buffer_id: BufferId, offset: u64, count: u32) {
+        let buffer = self.wgpu_resources.buffers.get(&buffer_id).unwrap();
+        if count > 1 && self.supports_multi_draw_indirect() {
+            self.render_pass
+                .multi_draw_indexed_indirect(buffer, offset, count);
+            return;
+        }
+        let stride = std::mem::size_of::<DrawIndexedIndirectArgs>() as u64;
+        for i in 0..count as u64 {
+            self.render_pass
+                .draw_indexed_indirect(buffer, offset + i * stride);
+        }
+    }
+
     fn set_bind_group(
         &mut self,
         index: u32,
@@ -92,6 +133,18 @@ impl<'a> RenderPass for WgpuRenderPass<'a> {
         }
     }
 
+    fn push_debug_group(&mut self, label: &str) {
+        self.render_pass.push_debug_group(label);
+    }
+
+    fn pop_debug_group(&mut self) {
+        self.render_pass.pop_debug_group();
+    }
+
+    fn insert_debug_marker(&mut self, label: &str) {
+        self.render_pass.insert_debug_marker(label);
+    }
+
     fn set_pipeline(&mut self, pipeline_handle: &Handle<PipelineDescriptor>) {
         let pipeline = self
             .wgpu_resources
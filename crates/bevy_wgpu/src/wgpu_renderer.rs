@@ -1,24 +1,41 @@
 use crate::{
     renderer::{WgpuRenderGraphExecutor, WgpuRenderResourceContext},
     wgpu_type_converter::WgpuInto,
-    WgpuBackend, WgpuOptions, WgpuPowerOptions,
+    GpuCaptureApi, WgpuBackend, WgpuOptions, WgpuPowerOptions,
 };
 use bevy_app::{Events, ManualEventReader};
 use bevy_ecs::world::{Mut, World};
 use bevy_render::{
-    render_graph::{DependentNodeStager, RenderGraph, RenderGraphStager},
-    renderer::RenderResourceContext,
+    render_graph::{DependentNodeStager, RenderGraph, RenderGraphStager, TransientTexturePool},
+    renderer::{
+        BufferId, BufferInfo, BufferMapMode, BufferMapState, BufferUsage, FramesInFlight,
+        GpuCapture, RenderResourceContext,
+    },
 };
-use bevy_window::{WindowCreated, WindowResized, Windows};
-use std::{ops::Deref, sync::Arc};
+use bevy_utils::tracing::warn;
+use bevy_window::{AppLifecycle, Window, WindowCreated, WindowResized, Windows};
+use std::{collections::VecDeque, ops::Deref, sync::Arc};
 
 pub struct WgpuRenderer {
     pub instance: wgpu::Instance,
     pub device: Arc<wgpu::Device>,
-    pub queue: wgpu::Queue,
+    pub queue: Arc<wgpu::Queue>,
     pub window_resized_event_reader: ManualEventReader<WindowResized>,
     pub window_created_event_reader: ManualEventReader<WindowCreated>,
+    pub app_lifecycle_event_reader: ManualEventReader<AppLifecycle>,
     pub initialized: bool,
+    /// Set while the app is suspended and window surfaces are unavailable
+    pub suspended: bool,
+    pub transient_textures: TransientTexturePool,
+    /// Buffers mapped once the GPU has finished the frames it may still be rendering, oldest
+    /// first
+    pub frame_fences: VecDeque<BufferId>,
+    /// Fence buffers of finished frames, reused by the next frames
+    pub free_fence_buffers: Vec<BufferId>,
+    /// None if no GPU debugger was found to capture frames with
+    pub gpu_capture_api: Option<GpuCaptureApi>,
+    /// The number of the frame being captured
+    pub capturing_frame: Option<u64>,
 }
 
 impl WgpuRenderer {
@@ -32,6 +49,7 @@ impl WgpuRenderer {
             WgpuBackend::Gl => wgpu::BackendBit::GL,
             WgpuBackend::BrowserWgpu => wgpu::BackendBit::BROWSER_WEBGPU,
         };
+        let gpu_capture_api = GpuCaptureApi::load();
         let instance = wgpu::Instance::new(backend);
 
         let adapter = instance
@@ -46,16 +64,24 @@ impl WgpuRenderer {
             .await
             .expect("Unable to find a GPU! Make sure you have installed required drivers!");
 
-        #[cfg(feature = "trace")]
+        #[cfg(feature = "wgpu_trace")]
         let trace_path = Some(std::path::Path::new("wgpu_trace"));
-        #[cfg(not(feature = "trace"))]
+        #[cfg(not(feature = "wgpu_trace"))]
         let trace_path = None;
 
+        // compressed textures are decompressed on the cpu when unsupported, so enable every
+        // compression feature the adapter has
+        let texture_compression = adapter.features()
+            & (wgpu::Features::TEXTURE_COMPRESSION_BC
+                | wgpu::Features::TEXTURE_COMPRESSION_ETC2
+                | wgpu::Features::TEXTURE_COMPRESSION_ASTC_LDR);
+        let features: wgpu::Features = options.features.wgpu_into();
+
         let (device, queue) = adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
                     label: options.device_label.as_ref().map(|a| a.as_ref()),
-                    features: options.features.wgpu_into(),
+                    features: features | texture_compression,
                     limits: options.limits.wgpu_into(),
                 },
                 trace_path,
@@ -63,67 +89,243 @@ impl WgpuRenderer {
             .await
             .unwrap();
         let device = Arc::new(device);
+        let queue = Arc::new(queue);
         WgpuRenderer {
             instance,
             device,
             queue,
             window_resized_event_reader: Default::default(),
             window_created_event_reader: Default::default(),
+            app_lifecycle_event_reader: Default::default(),
             initialized: false,
+            suspended: false,
+            transient_textures: Default::default(),
+            frame_fences: VecDeque::new(),
+            free_fence_buffers: Vec::new(),
+            gpu_capture_api,
+            capturing_frame: None,
         }
     }
 
     pub fn handle_window_created_events(&mut self, world: &mut World) {
-        let world = world.cell();
-        let mut render_resource_context = world
-            .get_resource_mut::<Box<dyn RenderResourceContext>>()
+        let render_resource_context = world
+            .get_resource::<Box<dyn RenderResourceContext>>()
             .unwrap();
         let render_resource_context = render_resource_context
-            .downcast_mut::<WgpuRenderResourceContext>()
+            .downcast_ref::<WgpuRenderResourceContext>()
             .unwrap();
         let windows = world.get_resource::<Windows>().unwrap();
         let window_created_events = world.get_resource::<Events<WindowCreated>>().unwrap();
-        for window_created_event in self
+        let created_windows = self
             .window_created_event_reader
             .iter(&window_created_events)
-        {
+            .map(|window_created_event| window_created_event.id)
+            .collect::<Vec<_>>();
+        for window_id in created_windows {
             let window = windows
-                .get(window_created_event.id)
+                .get(window_id)
                 .expect("Received window created event for non-existent window.");
-            #[cfg(feature = "bevy_winit")]
-            {
-                let winit_windows = world.get_resource::<bevy_winit::WinitWindows>().unwrap();
-                let winit_window = winit_windows.get_window(window.id()).unwrap();
-                let surface = unsafe { self.instance.create_surface(winit_window.deref()) };
-                render_resource_context.set_window_surface(window.id(), surface);
+            self.create_window_surface(world, render_resource_context, window);
+        }
+    }
+
+    #[allow(unused_variables)]
+    fn create_window_surface(
+        &self,
+        world: &World,
+        render_resource_context: &WgpuRenderResourceContext,
+        window: &Window,
+    ) {
+        #[cfg(target_arch = "wasm32")]
+        {
+            let canvas = crate::web::window_canvas(world, window);
+            let surface = unsafe {
+                self.instance
+                    .create_surface(&crate::web::CanvasHandle::new(&canvas))
+            };
+            render_resource_context.set_window_surface(window.id(), surface);
+        }
+        #[cfg(all(feature = "bevy_winit", not(target_arch = "wasm32")))]
+        {
+            let winit_windows = world.get_resource::<bevy_winit::WinitWindows>().unwrap();
+            let winit_window = winit_windows.get_window(window.id()).unwrap();
+            let surface = unsafe { self.instance.create_surface(winit_window.deref()) };
+            render_resource_context.set_window_surface(window.id(), surface);
+        }
+    }
+
+    /// Releases window surfaces when the app is suspended and recreates them when it resumes.
+    /// Mobile platforms destroy the native window in the background, so rendering to the old
+    /// surfaces would crash.
+    pub fn handle_app_lifecycle_events(&mut self, world: &mut World) {
+        let lifecycle_events = world.get_resource::<Events<AppLifecycle>>().unwrap();
+        let suspended = match self
+            .app_lifecycle_event_reader
+            .iter(&lifecycle_events)
+            .next_back()
+        {
+            Some(AppLifecycle::Suspended) => true,
+            Some(AppLifecycle::Resumed) => false,
+            None => return,
+        };
+        if suspended == self.suspended {
+            return;
+        }
+        self.suspended = suspended;
+
+        let render_resource_context = world
+            .get_resource::<Box<dyn RenderResourceContext>>()
+            .unwrap();
+        let render_resource_context = render_resource_context
+            .downcast_ref::<WgpuRenderResourceContext>()
+            .unwrap();
+        if suspended {
+            render_resource_context.drop_all_swap_chain_textures();
+            render_resource_context.remove_window_surfaces();
+        } else {
+            let windows = world.get_resource::<Windows>().unwrap();
+            for window in windows.iter() {
+                self.create_window_surface(world, render_resource_context, window);
             }
         }
     }
 
     pub fn run_graph(&mut self, world: &mut World) {
+        #[cfg(feature = "trace")]
+        let render_graph_span = bevy_utils::tracing::info_span!("render_graph");
+        #[cfg(feature = "trace")]
+        let _render_graph_guard = render_graph_span.enter();
         world.resource_scope(|world, mut render_graph: Mut<RenderGraph>| {
             render_graph.prepare(world);
             // stage nodes
             let mut stager = DependentNodeStager::loose_grouping();
             let stages = stager.get_stages(&render_graph).unwrap();
             let mut borrowed = stages.borrow(&mut render_graph);
+            let render_resource_context = world
+                .get_resource::<Box<dyn RenderResourceContext>>()
+                .unwrap();
+            self.transient_textures
+                .assign(&mut borrowed, &**render_resource_context);
 
             // execute stages
             let graph_executor = WgpuRenderGraphExecutor {
                 max_thread_count: 2,
             };
-            graph_executor.execute(world, self.device.clone(), &mut self.queue, &mut borrowed);
+            graph_executor.execute(world, self.device.clone(), &self.queue, &mut borrowed);
         })
     }
 
+    /// Waits until fewer than [FramesInFlight] frames are still rendering on the GPU, then starts
+    /// the next frame. The transient resources of its [FramesInFlight::index] are free from then.
+    pub fn begin_frame(&mut self, world: &mut World) {
+        let frames_in_flight = match world.get_resource::<FramesInFlight>() {
+            Some(frames_in_flight) => frames_in_flight.count(),
+            None => return,
+        };
+        let render_resource_context = world
+            .get_resource::<Box<dyn RenderResourceContext>>()
+            .unwrap()
+            .downcast_ref::<WgpuRenderResourceContext>()
+            .unwrap();
+        while self.frame_fences.len() >= frames_in_flight {
+            let fence = self.frame_fences.pop_front().unwrap();
+            match render_resource_context.wait_for_buffer_map(fence) {
+                BufferMapState::Mapped => {
+                    render_resource_context.unmap_buffer(fence);
+                    self.free_fence_buffers.push(fence);
+                }
+                // a failed map means the device was lost, so there is nothing left to wait for
+                // and the fence can't be reused
+                _ => render_resource_context.remove_buffer(fence),
+            }
+        }
+        world
+            .get_resource_mut::<FramesInFlight>()
+            .unwrap()
+            .advance();
+    }
+
+    /// Submits a fence that is mapped once the GPU has finished the work submitted this frame
+    pub fn end_frame(&mut self, world: &mut World) {
+        let render_resource_context = world
+            .get_resource::<Box<dyn RenderResourceContext>>()
+            .unwrap()
+            .downcast_ref::<WgpuRenderResourceContext>()
+            .unwrap();
+        let fence = self.free_fence_buffers.pop().unwrap_or_else(|| {
+            render_resource_context.create_buffer(BufferInfo {
+                size: wgpu::COPY_BUFFER_ALIGNMENT as usize,
+                buffer_usage: BufferUsage::MAP_READ | BufferUsage::COPY_DST,
+                mapped_at_creation: false,
+            })
+        });
+        {
+            // the map only resolves after the last submission that uses the buffer
+            let buffers = render_resource_context.resources.buffers.read();
+            self.queue.write_buffer(
+                buffers.get(&fence).unwrap(),
+                0,
+                &[0; wgpu::COPY_BUFFER_ALIGNMENT as usize],
+            );
+        }
+        self.queue.submit(std::iter::empty());
+        render_resource_context.map_buffer_async(fence, BufferMapMode::Read);
+        self.frame_fences.push_back(fence);
+    }
+
+    /// Starts capturing the frame if [GpuCapture] requested it
+    pub fn begin_capture(&mut self, world: &mut World) {
+        let frame = world
+            .get_resource::<FramesInFlight>()
+            .map_or(0, |frames_in_flight| frames_in_flight.frame());
+        let mut gpu_capture = match world.get_resource_mut::<GpuCapture>() {
+            Some(gpu_capture) => gpu_capture,
+            None => return,
+        };
+        gpu_capture.set_available(self.gpu_capture_api.is_some());
+        if !gpu_capture.take_request() {
+            return;
+        }
+        match &self.gpu_capture_api {
+            Some(gpu_capture_api) => {
+                gpu_capture_api.start_frame_capture();
+                self.capturing_frame = Some(frame);
+            }
+            None => warn!(
+                "Can't capture the frame without RenderDoc. Install it and enable the \
+                `renderdoc` feature."
+            ),
+        }
+    }
+
+    pub fn end_capture(&mut self) {
+        if let (Some(frame), Some(gpu_capture_api)) =
+            (self.capturing_frame.take(), &self.gpu_capture_api)
+        {
+            gpu_capture_api.end_frame_capture(frame);
+        }
+    }
+
     pub fn update(&mut self, world: &mut World) {
+        self.handle_app_lifecycle_events(world);
+        if self.suspended {
+            return;
+        }
+        self.begin_frame(world);
+        self.begin_capture(world);
         self.handle_window_created_events(world);
         self.run_graph(world);
+        self.end_frame(world);
+        self.end_capture();
 
         let render_resource_context = world
             .get_resource::<Box<dyn RenderResourceContext>>()
             .unwrap();
         render_resource_context.drop_all_swap_chain_textures();
         render_resource_context.remove_stale_bind_groups();
+        render_resource_context
+            .downcast_ref::<WgpuRenderResourceContext>()
+            .unwrap()
+            .poll_buffer_maps();
     }
 }
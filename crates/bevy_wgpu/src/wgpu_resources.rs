@@ -1,15 +1,17 @@
 use bevy_asset::{Handle, HandleUntyped};
 use bevy_render::{
-    pipeline::{BindGroupDescriptorId, PipelineDescriptor},
-    renderer::{BindGroupId, BufferId, BufferInfo, RenderResourceId, SamplerId, TextureId},
+    pipeline::{BindGroupDescriptorId, ComputePipelineDescriptor, PipelineDescriptor},
+    renderer::{
+        BindGroupId, BufferId, BufferInfo, BufferMapState, RenderResourceId, SamplerId, TextureId,
+    },
     shader::Shader,
-    texture::TextureDescriptor,
+    texture::{SamplerDescriptor, TextureDescriptor},
 };
 use bevy_utils::HashMap;
 use bevy_window::WindowId;
 use crossbeam_channel::{Receiver, Sender, TryRecvError};
-use parking_lot::{RwLock, RwLockReadGuard};
-use std::sync::Arc;
+use parking_lot::{Mutex, RwLock, RwLockReadGuard};
+use std::{fmt, future::Future, pin::Pin, sync::Arc};
 
 #[derive(Debug, Default)]
 pub struct WgpuBindGroupInfo {
@@ -50,6 +52,8 @@ pub struct WgpuResourcesReadLock<'a> {
     pub swap_chain_frames: RwLockReadGuard<'a, HashMap<TextureId, wgpu::SwapChainFrame>>,
     pub render_pipelines:
         RwLockReadGuard<'a, HashMap<Handle<PipelineDescriptor>, wgpu::RenderPipeline>>,
+    pub compute_pipelines:
+        RwLockReadGuard<'a, HashMap<Handle<ComputePipelineDescriptor>, wgpu::ComputePipeline>>,
     pub bind_groups: RwLockReadGuard<'a, HashMap<BindGroupDescriptorId, WgpuBindGroupInfo>>,
     pub used_bind_group_sender: Sender<BindGroupId>,
 }
@@ -61,6 +65,7 @@ impl<'a> WgpuResourcesReadLock<'a> {
             textures: &self.textures,
             swap_chain_frames: &self.swap_chain_frames,
             render_pipelines: &self.render_pipelines,
+            compute_pipelines: &self.compute_pipelines,
             bind_groups: &self.bind_groups,
             used_bind_group_sender: &self.used_bind_group_sender,
         }
@@ -75,10 +80,35 @@ pub struct WgpuResourceRefs<'a> {
     pub textures: &'a HashMap<TextureId, wgpu::TextureView>,
     pub swap_chain_frames: &'a HashMap<TextureId, wgpu::SwapChainFrame>,
     pub render_pipelines: &'a HashMap<Handle<PipelineDescriptor>, wgpu::RenderPipeline>,
+    pub compute_pipelines: &'a HashMap<Handle<ComputePipelineDescriptor>, wgpu::ComputePipeline>,
     pub bind_groups: &'a HashMap<BindGroupDescriptorId, WgpuBindGroupInfo>,
     pub used_bind_group_sender: &'a Sender<BindGroupId>,
 }
 
+/// Shares samplers between equal [SamplerDescriptor]s, counting their users so they are only
+/// removed once unused
+#[derive(Default, Debug)]
+pub struct SamplerCache {
+    pub ids: HashMap<SamplerDescriptor, SamplerId>,
+    pub users: HashMap<SamplerId, (SamplerDescriptor, usize)>,
+}
+
+type BufferMapFuture = Pin<Box<dyn Future<Output = Result<(), wgpu::BufferAsyncError>> + Send>>;
+
+/// A buffer mapped without blocking. The future completes when the device is polled.
+pub struct BufferMap {
+    pub state: BufferMapState,
+    pub future: Option<BufferMapFuture>,
+}
+
+impl fmt::Debug for BufferMap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BufferMap")
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
 #[derive(Default, Clone, Debug)]
 pub struct WgpuResources {
     pub buffer_infos: Arc<RwLock<HashMap<BufferId, BufferInfo>>>,
@@ -87,11 +117,18 @@ pub struct WgpuResources {
     pub window_swap_chains: Arc<RwLock<HashMap<WindowId, wgpu::SwapChain>>>,
     pub swap_chain_frames: Arc<RwLock<HashMap<TextureId, wgpu::SwapChainFrame>>>,
     pub buffers: Arc<RwLock<HashMap<BufferId, Arc<wgpu::Buffer>>>>,
+    pub buffer_maps: Arc<Mutex<HashMap<BufferId, BufferMap>>>,
     pub texture_views: Arc<RwLock<HashMap<TextureId, wgpu::TextureView>>>,
+    /// Depth-only views of sampled depth-stencil textures, which bind groups use instead of the
+    /// attachment view covering both aspects
+    pub depth_texture_views: Arc<RwLock<HashMap<TextureId, wgpu::TextureView>>>,
     pub textures: Arc<RwLock<HashMap<TextureId, wgpu::Texture>>>,
     pub samplers: Arc<RwLock<HashMap<SamplerId, wgpu::Sampler>>>,
+    pub sampler_cache: Arc<RwLock<SamplerCache>>,
     pub shader_modules: Arc<RwLock<HashMap<Handle<Shader>, wgpu::ShaderModule>>>,
     pub render_pipelines: Arc<RwLock<HashMap<Handle<PipelineDescriptor>, wgpu::RenderPipeline>>>,
+    pub compute_pipelines:
+        Arc<RwLock<HashMap<Handle<ComputePipelineDescriptor>, wgpu::ComputePipeline>>>,
     pub bind_groups: Arc<RwLock<HashMap<BindGroupDescriptorId, WgpuBindGroupInfo>>>,
     pub bind_group_layouts: Arc<RwLock<HashMap<BindGroupDescriptorId, wgpu::BindGroupLayout>>>,
     pub asset_resources: Arc<RwLock<HashMap<(HandleUntyped, u64), RenderResourceId>>>,
@@ -105,6 +142,7 @@ impl WgpuResources {
             textures: self.texture_views.read(),
             swap_chain_frames: self.swap_chain_frames.read(),
             render_pipelines: self.render_pipelines.read(),
+            compute_pipelines: self.compute_pipelines.read(),
             bind_groups: self.bind_groups.read(),
             used_bind_group_sender: self.bind_group_counter.used_bind_group_sender.clone(),
         }
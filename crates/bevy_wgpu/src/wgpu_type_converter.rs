@@ -36,6 +36,20 @@ where
     }
 }
 
+/// Converts bevy flags to wgpu flags one flag at a time, so the bevy flags don't depend on the
+/// bit layout of wgpu
+macro_rules! convert_flags {
+    ($val:expr, $bevy:ty => $wgpu:ty, { $($flag:ident => $wgpu_flag:ident),* $(,)? }) => {{
+        let mut flags = <$wgpu>::empty();
+        $(
+            if $val.contains(<$bevy>::$flag) {
+                flags |= <$wgpu>::$wgpu_flag;
+            }
+        )*
+        flags
+    }};
+}
+
 impl WgpuFrom<VertexFormat> for wgpu::VertexFormat {
     fn from(val: VertexFormat) -> Self {
         match val {
@@ -139,7 +153,17 @@ impl WgpuFrom<Color> for wgpu::Color {
 
 impl WgpuFrom<BufferUsage> for wgpu::BufferUsage {
     fn from(val: BufferUsage) -> Self {
-        wgpu::BufferUsage::from_bits(val.bits()).unwrap()
+        convert_flags!(val, BufferUsage => wgpu::BufferUsage, {
+            MAP_READ => MAP_READ,
+            MAP_WRITE => MAP_WRITE,
+            COPY_SRC => COPY_SRC,
+            COPY_DST => COPY_DST,
+            INDEX => INDEX,
+            VERTEX => VERTEX,
+            UNIFORM => UNIFORM,
+            STORAGE => STORAGE,
+            INDIRECT => INDIRECT,
+        })
     }
 }
 
@@ -342,13 +366,71 @@ impl WgpuFrom<TextureFormat> for wgpu::TextureFormat {
             TextureFormat::Depth32Float => wgpu::TextureFormat::Depth32Float,
             TextureFormat::Depth24Plus => wgpu::TextureFormat::Depth24Plus,
             TextureFormat::Depth24PlusStencil8 => wgpu::TextureFormat::Depth24PlusStencil8,
+            TextureFormat::Bc1RgbaUnorm => wgpu::TextureFormat::Bc1RgbaUnorm,
+            TextureFormat::Bc1RgbaUnormSrgb => wgpu::TextureFormat::Bc1RgbaUnormSrgb,
+            TextureFormat::Bc2RgbaUnorm => wgpu::TextureFormat::Bc2RgbaUnorm,
+            TextureFormat::Bc2RgbaUnormSrgb => wgpu::TextureFormat::Bc2RgbaUnormSrgb,
+            TextureFormat::Bc3RgbaUnorm => wgpu::TextureFormat::Bc3RgbaUnorm,
+            TextureFormat::Bc3RgbaUnormSrgb => wgpu::TextureFormat::Bc3RgbaUnormSrgb,
+            TextureFormat::Bc4RUnorm => wgpu::TextureFormat::Bc4RUnorm,
+            TextureFormat::Bc4RSnorm => wgpu::TextureFormat::Bc4RSnorm,
+            TextureFormat::Bc5RgUnorm => wgpu::TextureFormat::Bc5RgUnorm,
+            TextureFormat::Bc5RgSnorm => wgpu::TextureFormat::Bc5RgSnorm,
+            TextureFormat::Bc6hRgbUfloat => wgpu::TextureFormat::Bc6hRgbUfloat,
+            TextureFormat::Bc6hRgbSfloat => wgpu::TextureFormat::Bc6hRgbSfloat,
+            TextureFormat::Bc7RgbaUnorm => wgpu::TextureFormat::Bc7RgbaUnorm,
+            TextureFormat::Bc7RgbaUnormSrgb => wgpu::TextureFormat::Bc7RgbaUnormSrgb,
+            TextureFormat::Etc2RgbUnorm => wgpu::TextureFormat::Etc2RgbUnorm,
+            TextureFormat::Etc2RgbUnormSrgb => wgpu::TextureFormat::Etc2RgbUnormSrgb,
+            TextureFormat::Etc2RgbA1Unorm => wgpu::TextureFormat::Etc2RgbA1Unorm,
+            TextureFormat::Etc2RgbA1UnormSrgb => wgpu::TextureFormat::Etc2RgbA1UnormSrgb,
+            TextureFormat::Etc2RgbA8Unorm => wgpu::TextureFormat::Etc2RgbA8Unorm,
+            TextureFormat::Etc2RgbA8UnormSrgb => wgpu::TextureFormat::Etc2RgbA8UnormSrgb,
+            TextureFormat::EacRUnorm => wgpu::TextureFormat::EacRUnorm,
+            TextureFormat::EacRSnorm => wgpu::TextureFormat::EacRSnorm,
+            TextureFormat::EtcRgUnorm => wgpu::TextureFormat::EtcRgUnorm,
+            TextureFormat::EtcRgSnorm => wgpu::TextureFormat::EtcRgSnorm,
+            TextureFormat::Astc4x4RgbaUnorm => wgpu::TextureFormat::Astc4x4RgbaUnorm,
+            TextureFormat::Astc4x4RgbaUnormSrgb => wgpu::TextureFormat::Astc4x4RgbaUnormSrgb,
+            TextureFormat::Astc5x4RgbaUnorm => wgpu::TextureFormat::Astc5x4RgbaUnorm,
+            TextureFormat::Astc5x4RgbaUnormSrgb => wgpu::TextureFormat::Astc5x4RgbaUnormSrgb,
+            TextureFormat::Astc5x5RgbaUnorm => wgpu::TextureFormat::Astc5x5RgbaUnorm,
+            TextureFormat::Astc5x5RgbaUnormSrgb => wgpu::TextureFormat::Astc5x5RgbaUnormSrgb,
+            TextureFormat::Astc6x5RgbaUnorm => wgpu::TextureFormat::Astc6x5RgbaUnorm,
+            TextureFormat::Astc6x5RgbaUnormSrgb => wgpu::TextureFormat::Astc6x5RgbaUnormSrgb,
+            TextureFormat::Astc6x6RgbaUnorm => wgpu::TextureFormat::Astc6x6RgbaUnorm,
+            TextureFormat::Astc6x6RgbaUnormSrgb => wgpu::TextureFormat::Astc6x6RgbaUnormSrgb,
+            TextureFormat::Astc8x5RgbaUnorm => wgpu::TextureFormat::Astc8x5RgbaUnorm,
+            TextureFormat::Astc8x5RgbaUnormSrgb => wgpu::TextureFormat::Astc8x5RgbaUnormSrgb,
+            TextureFormat::Astc8x6RgbaUnorm => wgpu::TextureFormat::Astc8x6RgbaUnorm,
+            TextureFormat::Astc8x6RgbaUnormSrgb => wgpu::TextureFormat::Astc8x6RgbaUnormSrgb,
+            TextureFormat::Astc8x8RgbaUnorm => wgpu::TextureFormat::Astc8x8RgbaUnorm,
+            TextureFormat::Astc8x8RgbaUnormSrgb => wgpu::TextureFormat::Astc8x8RgbaUnormSrgb,
+            TextureFormat::Astc10x5RgbaUnorm => wgpu::TextureFormat::Astc10x5RgbaUnorm,
+            TextureFormat::Astc10x5RgbaUnormSrgb => wgpu::TextureFormat::Astc10x5RgbaUnormSrgb,
+            TextureFormat::Astc10x6RgbaUnorm => wgpu::TextureFormat::Astc10x6RgbaUnorm,
+            TextureFormat::Astc10x6RgbaUnormSrgb => wgpu::TextureFormat::Astc10x6RgbaUnormSrgb,
+            TextureFormat::Astc10x8RgbaUnorm => wgpu::TextureFormat::Astc10x8RgbaUnorm,
+            TextureFormat::Astc10x8RgbaUnormSrgb => wgpu::TextureFormat::Astc10x8RgbaUnormSrgb,
+            TextureFormat::Astc10x10RgbaUnorm => wgpu::TextureFormat::Astc10x10RgbaUnorm,
+            TextureFormat::Astc10x10RgbaUnormSrgb => wgpu::TextureFormat::Astc10x10RgbaUnormSrgb,
+            TextureFormat::Astc12x10RgbaUnorm => wgpu::TextureFormat::Astc12x10RgbaUnorm,
+            TextureFormat::Astc12x10RgbaUnormSrgb => wgpu::TextureFormat::Astc12x10RgbaUnormSrgb,
+            TextureFormat::Astc12x12RgbaUnorm => wgpu::TextureFormat::Astc12x12RgbaUnorm,
+            TextureFormat::Astc12x12RgbaUnormSrgb => wgpu::TextureFormat::Astc12x12RgbaUnormSrgb,
         }
     }
 }
 
 impl WgpuFrom<TextureUsage> for wgpu::TextureUsage {
     fn from(val: TextureUsage) -> Self {
-        wgpu::TextureUsage::from_bits(val.bits()).unwrap()
+        convert_flags!(val, TextureUsage => wgpu::TextureUsage, {
+            COPY_SRC => COPY_SRC,
+            COPY_DST => COPY_DST,
+            SAMPLED => SAMPLED,
+            STORAGE => STORAGE,
+            OUTPUT_ATTACHMENT => RENDER_ATTACHMENT,
+        })
     }
 }
 
@@ -529,7 +611,12 @@ impl WgpuFrom<PrimitiveState> for wgpu::PrimitiveState {
 
 impl WgpuFrom<ColorWrite> for wgpu::ColorWrite {
     fn from(val: ColorWrite) -> Self {
-        wgpu::ColorWrite::from_bits(val.bits()).unwrap()
+        convert_flags!(val, ColorWrite => wgpu::ColorWrite, {
+            RED => RED,
+            GREEN => GREEN,
+            BLUE => BLUE,
+            ALPHA => ALPHA,
+        })
     }
 }
 
@@ -713,3 +800,25 @@ impl WgpuFrom<WgpuLimits> for wgpu::Limits {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_flags() {
+        let usage: wgpu::BufferUsage = (BufferUsage::COPY_DST | BufferUsage::UNIFORM).wgpu_into();
+        assert_eq!(
+            usage,
+            wgpu::BufferUsage::COPY_DST | wgpu::BufferUsage::UNIFORM
+        );
+        let usage: wgpu::TextureUsage =
+            (TextureUsage::SAMPLED | TextureUsage::OUTPUT_ATTACHMENT).wgpu_into();
+        assert_eq!(
+            usage,
+            wgpu::TextureUsage::SAMPLED | wgpu::TextureUsage::RENDER_ATTACHMENT
+        );
+        let write: wgpu::ColorWrite = ColorWrite::COLOR.wgpu_into();
+        assert_eq!(write, wgpu::ColorWrite::COLOR);
+    }
+}
@@ -0,0 +1,52 @@
+use std::sync::Mutex;
+
+/// Access to the clipboard of the system, set on the [Clipboard] by the window backend
+pub trait ClipboardProvider: Send {
+    /// The text of the clipboard, or `None` if it holds something other than text
+    fn get_text(&mut self) -> Option<String>;
+    fn set_text(&mut self, text: String);
+}
+
+/// Text copied and pasted by text fields.
+///
+/// When the window backend provides a [ClipboardProvider] the text is exchanged with the
+/// clipboard of the system, otherwise it is only shared within the app.
+#[derive(Default)]
+pub struct Clipboard {
+    text: String,
+    provider: Option<Mutex<Box<dyn ClipboardProvider>>>,
+}
+
+impl std::fmt::Debug for Clipboard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Clipboard")
+            .field("text", &self.text)
+            .field("has_provider", &self.provider.is_some())
+            .finish()
+    }
+}
+
+impl Clipboard {
+    /// The text of the clipboard. Empty if the system clipboard holds something other than text.
+    pub fn get_text(&self) -> String {
+        match &self.provider {
+            Some(provider) => provider.lock().unwrap().get_text().unwrap_or_default(),
+            None => self.text.clone(),
+        }
+    }
+
+    pub fn set_text(&mut self, text: impl Into<String>) {
+        self.text = text.into();
+        if let Some(provider) = &self.provider {
+            provider.lock().unwrap().set_text(self.text.clone());
+        }
+    }
+
+    pub fn set_provider(&mut self, provider: impl ClipboardProvider + 'static) {
+        self.provider = Some(Mutex::new(Box::new(provider)));
+    }
+
+    pub fn has_provider(&self) -> bool {
+        self.provider.is_some()
+    }
+}
@@ -67,6 +67,17 @@ pub struct WindowFocused {
     pub focused: bool,
 }
 
+/// An event that indicates a window has been minimized or restored.
+///
+/// The OS doesn't report minimization directly, so this is derived from the window's client area
+/// collapsing to zero size (and growing back), which is how minimized windows are reported on
+/// most desktop platforms.
+#[derive(Debug, Clone)]
+pub struct WindowMinimized {
+    pub id: WindowId,
+    pub minimized: bool,
+}
+
 /// An event that indicates a window's scale factor has changed.
 #[derive(Debug, Clone)]
 pub struct WindowScaleFactorChanged {
@@ -81,12 +92,17 @@ pub struct WindowBackendScaleFactorChanged {
 }
 
 /// Events related to files being dragged and dropped on a window.
+///
+/// When several files are dragged at once, an event is sent for each of them.
 #[derive(Debug, Clone)]
 pub enum FileDragAndDrop {
+    /// A file was dropped on the window
     DroppedFile { id: WindowId, path_buf: PathBuf },
 
+    /// A file is dragged over the window
     HoveredFile { id: WindowId, path_buf: PathBuf },
 
+    /// The hovered files left the window without being dropped
     HoveredFileCancelled { id: WindowId },
 }
 
@@ -96,3 +112,14 @@ pub struct WindowMoved {
     pub id: WindowId,
     pub position: IVec2,
 }
+
+/// An event that is sent when the OS suspends or resumes the application.
+///
+/// On mobile platforms the native window (and with it the rendering surface) is destroyed while the
+/// application is suspended in the background. Anything holding on to the surface must release it
+/// on [AppLifecycle::Suspended] and recreate it on [AppLifecycle::Resumed].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppLifecycle {
+    Suspended,
+    Resumed,
+}
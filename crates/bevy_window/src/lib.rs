@@ -1,9 +1,13 @@
+mod clipboard;
+mod cursor;
 mod event;
 mod system;
 mod window;
 mod windows;
 
 use bevy_ecs::system::IntoSystem;
+pub use clipboard::*;
+pub use cursor::*;
 pub use event::*;
 pub use system::*;
 pub use window::*;
@@ -11,8 +15,8 @@ pub use windows::*;
 
 pub mod prelude {
     pub use crate::{
-        CursorEntered, CursorLeft, CursorMoved, FileDragAndDrop, ReceivedCharacter, Window,
-        WindowDescriptor, WindowMoved, Windows,
+        Clipboard, CursorEntered, CursorIcon, CursorLeft, CursorMoved, FileDragAndDrop,
+        ReceivedCharacter, Window, WindowDescriptor, WindowMoved, Windows,
     };
 }
 
@@ -44,11 +48,14 @@ impl Plugin for WindowPlugin {
             .add_event::<CursorLeft>()
             .add_event::<ReceivedCharacter>()
             .add_event::<WindowFocused>()
+            .add_event::<WindowMinimized>()
             .add_event::<WindowScaleFactorChanged>()
             .add_event::<WindowBackendScaleFactorChanged>()
             .add_event::<FileDragAndDrop>()
             .add_event::<WindowMoved>()
-            .init_resource::<Windows>();
+            .add_event::<AppLifecycle>()
+            .init_resource::<Windows>()
+            .init_resource::<Clipboard>();
 
         if self.add_primary_window {
             let world = app.world_mut();
@@ -1,3 +1,4 @@
+use crate::CursorIcon;
 use bevy_math::{IVec2, Vec2};
 use bevy_utils::{tracing::warn, Uuid};
 
@@ -122,6 +123,7 @@ pub struct Window {
     decorations: bool,
     cursor_visible: bool,
     cursor_locked: bool,
+    cursor_icon: CursorIcon,
     cursor_position: Option<Vec2>,
     focused: bool,
     mode: WindowMode,
@@ -161,6 +163,9 @@ pub enum WindowCommand {
     SetCursorVisibility {
         visible: bool,
     },
+    SetCursorIcon {
+        icon: CursorIcon,
+    },
     SetCursorPosition {
         position: Vec2,
     },
@@ -176,6 +181,9 @@ pub enum WindowCommand {
     SetResizeConstraints {
         resize_constraints: WindowResizeConstraints,
     },
+    SetImePosition {
+        position: Vec2,
+    },
 }
 
 /// Defines the way a window is displayed
@@ -215,6 +223,7 @@ impl Window {
             decorations: window_descriptor.decorations,
             cursor_visible: window_descriptor.cursor_visible,
             cursor_locked: window_descriptor.cursor_locked,
+            cursor_icon: CursorIcon::default(),
             cursor_position: None,
             focused: true,
             mode: window_descriptor.mode,
@@ -466,6 +475,18 @@ impl Window {
         });
     }
 
+    #[inline]
+    pub fn cursor_icon(&self) -> CursorIcon {
+        self.cursor_icon
+    }
+
+    /// Sets the icon displayed while the cursor is over the window
+    pub fn set_cursor_icon(&mut self, icon: CursorIcon) {
+        self.cursor_icon = icon;
+        self.command_queue
+            .push(WindowCommand::SetCursorIcon { icon });
+    }
+
     #[inline]
     pub fn cursor_position(&self) -> Option<Vec2> {
         self.cursor_position
@@ -476,6 +497,14 @@ impl Window {
             .push(WindowCommand::SetCursorPosition { position });
     }
 
+    /// Moves the candidate window of input methods next to `position`, in logical pixels from the
+    /// bottom left corner of the window like [Window::cursor_position]. Text fields should call
+    /// this when they are focused.
+    pub fn set_ime_position(&mut self, position: Vec2) {
+        self.command_queue
+            .push(WindowCommand::SetImePosition { position });
+    }
+
     #[allow(missing_docs)]
     #[inline]
     pub fn update_focused_status_from_backend(&mut self, focused: bool) {
@@ -510,12 +539,22 @@ impl Window {
     pub fn is_focused(&self) -> bool {
         self.focused
     }
+
+    /// Whether the window is currently minimized. See [WindowMinimized](crate::WindowMinimized)
+    /// for how this is detected.
+    #[inline]
+    pub fn is_minimized(&self) -> bool {
+        self.physical_width == 0 || self.physical_height == 0
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct WindowDescriptor {
     pub width: f32,
     pub height: f32,
+    /// The initial position of the window in physical pixels. When `None`, the position is left
+    /// up to the OS.
+    pub position: Option<IVec2>,
     pub resize_constraints: WindowResizeConstraints,
     pub scale_factor_override: Option<f64>,
     pub title: String,
@@ -535,6 +574,7 @@ impl Default for WindowDescriptor {
             title: "bevy".to_string(),
             width: 1280.,
             height: 720.,
+            position: None,
             resize_constraints: WindowResizeConstraints::default(),
             scale_factor_override: None,
             vsync: true,
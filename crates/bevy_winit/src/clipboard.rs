@@ -0,0 +1,30 @@
+use bevy_utils::tracing::warn;
+use bevy_window::ClipboardProvider;
+
+/// The clipboard of the system, through `arboard`
+pub struct SystemClipboard(arboard::Clipboard);
+
+impl SystemClipboard {
+    pub fn new() -> Option<Self> {
+        match arboard::Clipboard::new() {
+            Ok(clipboard) => Some(SystemClipboard(clipboard)),
+            Err(err) => {
+                warn!("Failed to access the system clipboard. {}", err);
+                None
+            }
+        }
+    }
+}
+
+impl ClipboardProvider for SystemClipboard {
+    fn get_text(&mut self) -> Option<String> {
+        // the clipboard may hold something other than text
+        self.0.get_text().ok()
+    }
+
+    fn set_text(&mut self, text: String) {
+        if let Err(err) = self.0.set_text(text) {
+            warn!("Failed to set the system clipboard. {}", err);
+        }
+    }
+}
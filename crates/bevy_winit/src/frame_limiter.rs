@@ -0,0 +1,66 @@
+use bevy_utils::{Duration, Instant};
+
+// OS sleeps are coarse (up to ~15ms on some platforms), so the limiter sleeps until shortly
+// before the deadline and spins for the rest
+const SPIN_DURATION: Duration = Duration::from_millis(2);
+
+/// Paces app updates to a fixed frame rate, independent of vsync
+#[derive(Debug, Default)]
+pub(crate) struct FrameLimiter {
+    next_frame: Option<Instant>,
+}
+
+impl FrameLimiter {
+    /// Blocks until the next frame is due for the given frame rate. Browsers pace frames
+    /// themselves (and can't block), so this does nothing on `wasm32`. Frame rates that aren't
+    /// positive, or whose frame time doesn't fit in a [Duration], don't limit anything.
+    pub fn wait(&mut self, frame_rate_limit: f64) {
+        let frame_time = 1.0 / frame_rate_limit;
+        // also false for NaN
+        let valid = frame_time > 0.0 && frame_time < u64::MAX as f64;
+        if cfg!(target_arch = "wasm32") || !valid {
+            self.next_frame = None;
+            return;
+        }
+        if let Some(next_frame) = self.next_frame {
+            sleep_until(next_frame);
+        }
+
+        let frame_time = Duration::from_secs_f64(frame_time);
+        let now = Instant::now();
+        self.next_frame = Some(match self.next_frame {
+            // keep a steady cadence, but don't try to catch up after a slow frame
+            Some(next_frame) if now < next_frame + frame_time => next_frame + frame_time,
+            _ => now + frame_time,
+        });
+    }
+
+    /// Forgets the frame cadence, e.g. after updates were paused
+    pub fn reset(&mut self) {
+        self.next_frame = None;
+    }
+}
+
+fn sleep_until(deadline: Instant) {
+    let now = Instant::now();
+    if deadline > now + SPIN_DURATION {
+        std::thread::sleep(deadline - now - SPIN_DURATION);
+    }
+    while Instant::now() < deadline {
+        std::hint::spin_loop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ignores_invalid_frame_rates() {
+        let mut frame_limiter = FrameLimiter::default();
+        for frame_rate_limit in [0.0, -30.0, f64::NAN, f64::INFINITY, 1e-300].iter() {
+            frame_limiter.wait(*frame_rate_limit);
+            assert_eq!(frame_limiter.next_frame, None);
+        }
+    }
+}
@@ -1,4 +1,7 @@
+#[cfg(all(feature = "clipboard", not(target_arch = "wasm32")))]
+mod clipboard;
 mod converters;
+mod frame_limiter;
 mod winit_config;
 mod winit_windows;
 
@@ -7,6 +10,7 @@ use bevy_input::{
     mouse::{MouseButtonInput, MouseMotion, MouseScrollUnit, MouseWheel},
     touch::TouchInput,
 };
+use frame_limiter::FrameLimiter;
 pub use winit_config::*;
 pub use winit_windows::*;
 
@@ -15,9 +19,9 @@ use bevy_ecs::{system::IntoExclusiveSystem, world::World};
 use bevy_math::{ivec2, Vec2};
 use bevy_utils::tracing::{error, trace, warn};
 use bevy_window::{
-    CreateWindow, CursorEntered, CursorLeft, CursorMoved, FileDragAndDrop, ReceivedCharacter,
-    WindowBackendScaleFactorChanged, WindowCloseRequested, WindowCreated, WindowFocused,
-    WindowMoved, WindowResized, WindowScaleFactorChanged, Windows,
+    AppLifecycle, CreateWindow, CursorEntered, CursorLeft, CursorMoved, FileDragAndDrop,
+    ReceivedCharacter, WindowBackendScaleFactorChanged, WindowCloseRequested, WindowCreated,
+    WindowFocused, WindowMinimized, WindowMoved, WindowResized, WindowScaleFactorChanged, Windows,
 };
 use winit::{
     dpi::PhysicalPosition,
@@ -43,6 +47,13 @@ impl Plugin for WinitPlugin {
         app.init_resource::<WinitWindows>()
             .set_runner(winit_runner)
             .add_system_to_stage(CoreStage::PostUpdate, change_window.exclusive_system());
+
+        #[cfg(all(feature = "clipboard", not(target_arch = "wasm32")))]
+        if let Some(system_clipboard) = clipboard::SystemClipboard::new() {
+            app.world_mut()
+                .get_resource_or_insert_with(bevy_window::Clipboard::default)
+                .set_provider(system_clipboard);
+        }
     }
 }
 
@@ -116,6 +127,10 @@ fn change_window(world: &mut World) {
                     let window = winit_windows.get_window(id).unwrap();
                     window.set_cursor_visible(visible);
                 }
+                bevy_window::WindowCommand::SetCursorIcon { icon } => {
+                    let window = winit_windows.get_window(id).unwrap();
+                    window.set_cursor_icon(converters::convert_cursor_icon(icon));
+                }
                 bevy_window::WindowCommand::SetCursorPosition { position } => {
                     let window = winit_windows.get_window(id).unwrap();
                     let inner_size = window.inner_size().to_logical::<f32>(window.scale_factor());
@@ -126,6 +141,14 @@ fn change_window(world: &mut World) {
                         ))
                         .unwrap_or_else(|e| error!("Unable to set cursor position: {}", e));
                 }
+                bevy_window::WindowCommand::SetImePosition { position } => {
+                    let window = winit_windows.get_window(id).unwrap();
+                    let inner_size = window.inner_size().to_logical::<f32>(window.scale_factor());
+                    window.set_ime_position(winit::dpi::LogicalPosition::new(
+                        position.x,
+                        inner_size.height - position.y,
+                    ));
+                }
                 bevy_window::WindowCommand::SetMaximized { maximized } => {
                     let window = winit_windows.get_window(id).unwrap();
                     window.set_maximized(maximized)
@@ -225,6 +248,10 @@ pub fn winit_runner_any_thread(app: App) {
 pub fn winit_runner_with(mut app: App, mut event_loop: EventLoop<()>) {
     let mut create_window_event_reader = ManualEventReader::<CreateWindow>::default();
     let mut app_exit_event_reader = ManualEventReader::<AppExit>::default();
+    let mut active = true;
+    // set while WinitConfig::pause_when_inactive stops updates of an app without an active window
+    let mut paused = false;
+    let mut frame_limiter = FrameLimiter::default();
     app.world.insert_non_send(event_loop.create_proxy());
 
     trace!("Entering winit event loop");
@@ -237,7 +264,27 @@ pub fn winit_runner_with(mut app: App, mut event_loop: EventLoop<()>) {
     let event_handler = move |event: Event<()>,
                               event_loop: &EventLoopWindowTarget<()>,
                               control_flow: &mut ControlFlow| {
-        *control_flow = ControlFlow::Poll;
+        // focus changes, restored windows and resumes can make the app active again
+        if matches!(
+            event,
+            event::Event::Resumed
+                | event::Event::WindowEvent {
+                    event: WindowEvent::Focused(_),
+                    ..
+                }
+                | event::Event::WindowEvent {
+                    event: WindowEvent::Resized(_),
+                    ..
+                }
+        ) {
+            paused = false;
+        }
+        // while suspended or paused there is nothing to update, so wait for the OS to wake us up
+        *control_flow = if active && !paused {
+            ControlFlow::Poll
+        } else {
+            ControlFlow::Wait
+        };
 
         if let Some(app_exit_events) = app.world.get_resource_mut::<Events<AppExit>>() {
             if app_exit_event_reader
@@ -278,7 +325,16 @@ pub fn winit_runner_with(mut app: App, mut event_loop: EventLoop<()>) {
 
                 match event {
                     WindowEvent::Resized(size) => {
+                        let was_minimized = window.is_minimized();
                         window.update_actual_size_from_backend(size.width, size.height);
+                        if was_minimized != window.is_minimized() {
+                            let mut minimized_events =
+                                world.get_resource_mut::<Events<WindowMinimized>>().unwrap();
+                            minimized_events.send(WindowMinimized {
+                                id: window_id,
+                                minimized: window.is_minimized(),
+                            });
+                        }
                         let mut resize_events =
                             world.get_resource_mut::<Events<WindowResized>>().unwrap();
                         resize_events.send(WindowResized {
@@ -476,14 +532,54 @@ pub fn winit_runner_with(mut app: App, mut event_loop: EventLoop<()>) {
                     delta: Vec2::new(delta.0 as f32, delta.1 as f32),
                 });
             }
-            event::Event::MainEventsCleared => {
-                handle_create_window_events(
-                    &mut app.world,
-                    event_loop,
-                    &mut create_window_event_reader,
-                );
+            event::Event::Suspended => {
+                active = false;
+                let mut lifecycle_events = app
+                    .world
+                    .get_resource_mut::<Events<AppLifecycle>>()
+                    .unwrap();
+                lifecycle_events.send(AppLifecycle::Suspended);
+                // run one last update so the event is observed (and surfaces are released) before
+                // the app stops updating
                 app.update();
             }
+            event::Event::Resumed => {
+                // the first Resumed is sent when the event loop starts, which is not a resume from
+                // the background
+                if !active {
+                    active = true;
+                    let mut lifecycle_events = app
+                        .world
+                        .get_resource_mut::<Events<AppLifecycle>>()
+                        .unwrap();
+                    lifecycle_events.send(AppLifecycle::Resumed);
+                }
+            }
+            event::Event::MainEventsCleared => {
+                if active {
+                    handle_create_window_events(
+                        &mut app.world,
+                        event_loop,
+                        &mut create_window_event_reader,
+                    );
+                    let (frame_rate_limit, pause_when_inactive) = app
+                        .world
+                        .get_resource::<WinitConfig>()
+                        .map_or((None, false), |config| {
+                            (config.frame_rate_limit, config.pause_when_inactive)
+                        });
+                    if pause_when_inactive && !any_window_active(&app.world) {
+                        paused = true;
+                        *control_flow = ControlFlow::Wait;
+                        frame_limiter.reset();
+                        return;
+                    }
+                    if let Some(frame_rate_limit) = frame_rate_limit {
+                        frame_limiter.wait(frame_rate_limit);
+                    }
+                    app.update();
+                }
+            }
             _ => (),
         }
     };
@@ -494,6 +590,13 @@ pub fn winit_runner_with(mut app: App, mut event_loop: EventLoop<()>) {
     }
 }
 
+fn any_window_active(world: &World) -> bool {
+    let windows = world.get_resource::<Windows>().unwrap();
+    let mut windows = windows.iter().peekable();
+    // an app without windows has nothing to become inactive
+    windows.peek().is_none() || windows.any(|window| window.is_focused() && !window.is_minimized())
+}
+
 fn handle_create_window_events(
     world: &mut World,
     event_loop: &EventLoopWindowTarget<()>,
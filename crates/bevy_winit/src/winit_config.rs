@@ -12,4 +12,13 @@ pub struct WinitConfig {
     /// `openbsd`. If set to true on an unsupported platform
     /// [run](bevy_app::App::run) will panic.
     pub return_from_run: bool,
+    /// Caps how many times per second the app is updated, independent of vsync. Useful to save
+    /// power in menus and tools. `None` updates as fast as possible, and so do limits that
+    /// aren't positive.
+    ///
+    /// This has no effect on `wasm32`, where the browser paces frames.
+    pub frame_rate_limit: Option<f64>,
+    /// Stops updating the app while none of its windows are focused, or all of them are minimized.
+    /// Updates resume as soon as a window becomes active again.
+    pub pause_when_inactive: bool,
 }
@@ -112,6 +112,11 @@ impl WinitWindows {
 
         winit_window.set_cursor_visible(window_descriptor.cursor_visible);
 
+        if let Some(position) = window_descriptor.position {
+            winit_window
+                .set_outer_position(winit::dpi::PhysicalPosition::new(position.x, position.y));
+        }
+
         self.window_id_to_winit.insert(window_id, winit_window.id());
         self.winit_to_window_id.insert(winit_window.id(), window_id);
 
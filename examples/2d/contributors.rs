@@ -94,10 +94,10 @@ fn setup(
                     flip_x: flipped,
                     ..Default::default()
                 },
-                material: materials.add(ColorMaterial {
-                    color: Color::hsla(hue, SATURATION_DESELECTED, LIGHTNESS_DESELECTED, ALPHA),
-                    texture: Some(texture_handle.clone()),
-                }),
+                material: materials.add(ColorMaterial::modulated_texture(
+                    texture_handle.clone(),
+                    Color::hsla(hue, SATURATION_DESELECTED, LIGHTNESS_DESELECTED, ALPHA),
+                )),
                 transform,
                 ..Default::default()
             })
@@ -0,0 +1,38 @@
+use bevy::prelude::*;
+
+/// This example plays a video on a sprite. Pass the path of an AV1 video in a WebM file,
+/// relative to the assets folder:
+/// `cargo run --example video --features bevy_video -- videos/intro.webm`
+fn main() {
+    App::build()
+        .add_plugins(DefaultPlugins)
+        .add_startup_system(setup.system())
+        .add_system(video_events_system.system())
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut textures: ResMut<Assets<Texture>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let path = std::env::args()
+        .nth(1)
+        .expect("pass the path of a WebM video in the assets folder");
+    let player = VideoPlayer::new(asset_server.load(path.as_str()), &mut textures).looped();
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands
+        .spawn_bundle(SpriteBundle {
+            // the sprite takes the size of the video once its first frame is decoded
+            material: materials.add(player.texture.clone().into()),
+            ..Default::default()
+        })
+        .insert(player);
+}
+
+fn video_events_system(mut events: EventReader<VideoEvent>) {
+    for event in events.iter() {
+        println!("{:?}", event);
+    }
+}
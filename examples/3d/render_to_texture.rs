@@ -11,10 +11,7 @@ use bevy::{
             base::{node::MAIN_PASS, MainPass},
             CameraNode, PassNode, RenderGraph, TextureNode,
         },
-        texture::{
-            Extent3d, SamplerDescriptor, TextureDescriptor, TextureDimension, TextureFormat,
-            TextureUsage,
-        },
+        texture::{Extent3d, SamplerDescriptor, TextureDescriptor, TextureDimension, TextureUsage},
     },
     window::WindowId,
 };
@@ -29,7 +26,7 @@ pub const DEPTH_TEXTURE_NODE: &str = "depth_texure_node";
 pub const FIRST_PASS: &str = "first_pass";
 pub const FIRST_PASS_CAMERA: &str = "first_pass_camera";
 
-fn add_render_to_texture_graph(graph: &mut RenderGraph, size: Extent3d) {
+fn add_render_to_texture_graph(graph: &mut RenderGraph, size: Extent3d, depth: &Depth) {
     let mut pass_node = PassNode::<&FirstPass>::new(PassDescriptor {
         color_attachments: vec![RenderPassColorAttachmentDescriptor {
             attachment: TextureAttachment::Input("color_attachment".to_string()),
@@ -42,7 +39,7 @@ fn add_render_to_texture_graph(graph: &mut RenderGraph, size: Extent3d) {
         depth_stencil_attachment: Some(RenderPassDepthStencilAttachmentDescriptor {
             attachment: TextureAttachment::Input("depth".to_string()),
             depth_ops: Some(Operations {
-                load: LoadOp::Clear(1.0),
+                load: LoadOp::Clear(depth.clear_depth()),
                 store: true,
             }),
             stencil_ops: None,
@@ -66,6 +63,7 @@ fn add_render_to_texture_graph(graph: &mut RenderGraph, size: Extent3d) {
                 dimension: TextureDimension::D2,
                 format: Default::default(),
                 usage: TextureUsage::OUTPUT_ATTACHMENT | TextureUsage::SAMPLED,
+                view_dimension: None,
             },
             Some(SamplerDescriptor::default()),
             Some(RENDER_TEXTURE_HANDLE),
@@ -80,8 +78,9 @@ fn add_render_to_texture_graph(graph: &mut RenderGraph, size: Extent3d) {
                 mip_level_count: 1,
                 sample_count: 1,
                 dimension: TextureDimension::D2,
-                format: TextureFormat::Depth32Float,
+                format: depth.format,
                 usage: TextureUsage::OUTPUT_ATTACHMENT | TextureUsage::SAMPLED,
+                view_dimension: None,
             },
             None,
             None,
@@ -134,9 +133,10 @@ fn setup(
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut render_graph: ResMut<RenderGraph>,
     mut active_cameras: ResMut<ActiveCameras>,
+    depth: Res<Depth>,
 ) {
     let size = Extent3d::new(512, 512, 1);
-    add_render_to_texture_graph(&mut render_graph, size);
+    add_render_to_texture_graph(&mut render_graph, size, &depth);
 
     let cube_handle = meshes.add(Mesh::from(shape::Cube { size: 4.0 }));
     let cube_material_handle = materials.add(StandardMaterial {
@@ -180,7 +180,8 @@ fn setup(
 
     let camera_projection = &mut first_pass_camera.perspective_projection;
     camera_projection.update(size.width as f32, size.height as f32);
-    first_pass_camera.camera.projection_matrix = camera_projection.get_projection_matrix();
+    first_pass_camera.camera.projection_matrix =
+        depth.projection(camera_projection.get_projection_matrix());
     first_pass_camera.camera.depth_calculation = camera_projection.depth_calculation();
 
     commands.spawn_bundle(first_pass_camera);
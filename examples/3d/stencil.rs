@@ -0,0 +1,108 @@
+use bevy::{
+    pbr::render_graph::PBR_PIPELINE_HANDLE,
+    prelude::*,
+    render::{
+        camera::DrawOrder,
+        pipeline::{ColorWrite, CompareFunction, PipelineDescriptor, RenderPipeline, StencilState},
+        render_graph::base::Depth,
+        texture::TextureFormat,
+    },
+};
+
+/// This example shows how to mask draws with the stencil buffer. A "portal" quad writes the
+/// stencil reference without drawing any color, and the cube behind it is only drawn where the
+/// stencil buffer holds that reference, so it is only visible through the portal.
+fn main() {
+    App::build()
+        // stencil tests need a depth buffer format with a stencil aspect
+        .insert_resource(Depth {
+            format: TextureFormat::Depth24PlusStencil8,
+            ..Default::default()
+        })
+        .add_plugins(DefaultPlugins)
+        .add_startup_system(setup.system())
+        .add_system(rotate.system())
+        .run();
+}
+
+const PORTAL_REFERENCE: u32 = 1;
+
+struct Rotates;
+
+fn setup(
+    mut commands: Commands,
+    mut pipelines: ResMut<Assets<PipelineDescriptor>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let pbr_pipeline = pipelines.get(PBR_PIPELINE_HANDLE).unwrap().clone();
+
+    // the portal only writes the stencil buffer
+    let mut portal_pipeline = pbr_pipeline.clone();
+    let depth_stencil = portal_pipeline.depth_stencil.as_mut().unwrap();
+    depth_stencil.depth_write_enabled = false;
+    depth_stencil.stencil = StencilState::write_reference();
+    for color_target_state in portal_pipeline.color_target_states.iter_mut() {
+        color_target_state.write_mask = ColorWrite::empty();
+    }
+    let portal_pipeline = pipelines.add(portal_pipeline);
+
+    // the cube is only drawn where the portal wrote the stencil buffer
+    let mut masked_pipeline = pbr_pipeline;
+    masked_pipeline.depth_stencil.as_mut().unwrap().stencil =
+        StencilState::compare_reference(CompareFunction::Equal);
+    let masked_pipeline = pipelines.add(masked_pipeline);
+
+    let stencil_pipelines = |pipeline| {
+        RenderPipelines::from_pipelines(vec![RenderPipeline {
+            stencil_reference: Some(PORTAL_REFERENCE),
+            ..RenderPipeline::new(pipeline)
+        }])
+    };
+
+    // portal
+    commands
+        .spawn_bundle(PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Quad::new(Vec2::new(2.0, 2.0)))),
+            material: materials.add(Color::WHITE.into()),
+            render_pipelines: stencil_pipelines(portal_pipeline),
+            transform: Transform::from_xyz(0.0, 1.0, 2.0),
+            ..Default::default()
+        })
+        // the portal must write the stencil buffer before the cube tests it
+        .insert(DrawOrder(-1.0));
+    // cube
+    commands
+        .spawn_bundle(PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Cube { size: 1.5 })),
+            material: materials.add(Color::rgb(0.8, 0.7, 0.6).into()),
+            render_pipelines: stencil_pipelines(masked_pipeline),
+            transform: Transform::from_xyz(0.0, 1.0, 0.0),
+            ..Default::default()
+        })
+        .insert(DrawOrder(0.0))
+        .insert(Rotates);
+    // plane
+    commands.spawn_bundle(PbrBundle {
+        mesh: meshes.add(Mesh::from(shape::Plane { size: 5.0 })),
+        material: materials.add(Color::rgb(0.3, 0.5, 0.3).into()),
+        ..Default::default()
+    });
+    // light
+    commands.spawn_bundle(PointLightBundle {
+        transform: Transform::from_xyz(4.0, 8.0, 4.0),
+        ..Default::default()
+    });
+    // camera
+    commands.spawn_bundle(PerspectiveCameraBundle {
+        transform: Transform::from_xyz(-2.0, 2.5, 6.0)
+            .looking_at(Vec3::new(0.0, 1.0, 0.0), Vec3::Y),
+        ..Default::default()
+    });
+}
+
+fn rotate(time: Res<Time>, mut query: Query<&mut Transform, With<Rotates>>) {
+    for mut transform in query.iter_mut() {
+        transform.rotate(Quat::from_rotation_y(time.delta_seconds()));
+    }
+}
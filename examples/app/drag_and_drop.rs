@@ -7,8 +7,15 @@ fn main() {
         .run();
 }
 
-fn file_drag_and_drop_system(mut events: EventReader<FileDragAndDrop>) {
+fn file_drag_and_drop_system(
+    mut events: EventReader<FileDragAndDrop>,
+    mut clipboard: ResMut<Clipboard>,
+) {
     for event in events.iter() {
         println!("{:?}", event);
+        // the path of the last dropped file can be pasted in other applications
+        if let FileDragAndDrop::DroppedFile { path_buf, .. } = event {
+            clipboard.set_text(path_buf.display().to_string());
+        }
     }
 }
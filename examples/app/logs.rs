@@ -7,6 +7,7 @@ fn main() {
         // .insert_resource(bevy::log::LogSettings {
         //     level: bevy::log::Level::TRACE,
         //     filter: "wgpu=warn,bevy_ecs=info".to_string(),
+        //     ..Default::default()
         // })
         .add_plugins(DefaultPlugins)
         .add_system(log_system.system())
@@ -8,7 +8,15 @@ fn main() {
         .run();
 }
 
-fn setup(asset_server: Res<AssetServer>, audio: Res<Audio>) {
+fn setup(asset_server: Res<AssetServer>, audio: Res<Audio>, mut mixer: ResMut<AudioMixer>) {
     let music = asset_server.load("sounds/Windless Slopes.mp3");
-    audio.play(music);
+    // the music fades in, and loops until the returned playback is stopped
+    audio.play_with(
+        music,
+        PlaybackSettings {
+            fade_in: Some(std::time::Duration::from_secs(2)),
+            ..PlaybackSettings::on_bus(AudioBus::MUSIC).looped()
+        },
+    );
+    mixer.set_volume(&AudioBus::MUSIC, 0.8);
 }
@@ -0,0 +1,51 @@
+use bevy::prelude::*;
+
+/// This example scales a sprite with the loudness of the sound captured by the microphone
+fn main() {
+    App::build()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(MicrophonePlugin)
+        .add_startup_system(setup.system())
+        .add_system(level_system.system())
+        .run();
+}
+
+struct LevelMeter;
+
+fn setup(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    microphone: Res<Microphone>,
+) {
+    match microphone.device_name() {
+        Some(name) => println!("Capturing from {}", name),
+        None => println!("No microphone is available"),
+    }
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands
+        .spawn_bundle(SpriteBundle {
+            material: materials.add(Color::rgb(0.3, 0.8, 0.4).into()),
+            sprite: Sprite::new(Vec2::new(200.0, 200.0)),
+            ..Default::default()
+        })
+        .insert(LevelMeter);
+}
+
+fn level_system(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut microphone: ResMut<Microphone>,
+    mut query: Query<&mut Transform, With<LevelMeter>>,
+) {
+    // space pauses and resumes the capture
+    if keyboard_input.just_pressed(KeyCode::Space) {
+        microphone.capturing = !microphone.capturing;
+    }
+    // frames without new samples keep the previous scale
+    if microphone.samples().is_empty() {
+        return;
+    }
+    let scale = 0.2 + microphone.level() * 4.0;
+    for mut transform in query.iter_mut() {
+        transform.scale = Vec3::splat(scale.min(2.0));
+    }
+}
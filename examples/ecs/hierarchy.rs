@@ -20,10 +20,10 @@ fn setup(
     let parent = commands
         .spawn_bundle(SpriteBundle {
             transform: Transform::from_scale(Vec3::splat(0.75)),
-            material: materials.add(ColorMaterial {
-                color: Color::WHITE,
-                texture: Some(texture.clone()),
-            }),
+            material: materials.add(ColorMaterial::modulated_texture(
+                texture.clone(),
+                Color::WHITE,
+            )),
             ..Default::default()
         })
         // With that entity as a parent, run a lambda that spawns its children
@@ -35,10 +35,10 @@ fn setup(
                     scale: Vec3::splat(0.75),
                     ..Default::default()
                 },
-                material: materials.add(ColorMaterial {
-                    color: Color::BLUE,
-                    texture: Some(texture.clone()),
-                }),
+                material: materials.add(ColorMaterial::modulated_texture(
+                    texture.clone(),
+                    Color::BLUE,
+                )),
                 ..Default::default()
             });
         })
@@ -56,10 +56,10 @@ fn setup(
                 scale: Vec3::splat(0.75),
                 ..Default::default()
             },
-            material: materials.add(ColorMaterial {
-                color: Color::RED,
-                texture: Some(texture.clone()),
-            }),
+            material: materials.add(ColorMaterial::modulated_texture(
+                texture.clone(),
+                Color::RED,
+            )),
             ..Default::default()
         })
         // Using the entity from the previous section as the parent:
@@ -74,10 +74,7 @@ fn setup(
                 scale: Vec3::splat(0.75),
                 ..Default::default()
             },
-            material: materials.add(ColorMaterial {
-                color: Color::GREEN,
-                texture: Some(texture),
-            }),
+            material: materials.add(ColorMaterial::modulated_texture(texture, Color::GREEN)),
             ..Default::default()
         })
         .id();
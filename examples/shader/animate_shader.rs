@@ -4,8 +4,7 @@ use bevy::{
     render::{
         mesh::shape,
         pipeline::{PipelineDescriptor, RenderPipeline},
-        render_graph::{base, RenderGraph, RenderResourcesNode},
-        renderer::RenderResources,
+        render_graph::{AddUniform, Uniform},
         shader::{ShaderStage, ShaderStages},
     },
 };
@@ -15,12 +14,14 @@ use bevy::{
 pub fn main() {
     App::build()
         .add_plugins(DefaultPlugins)
+        // Bind `TimeUniform` to shaders drawn in the main pass.
+        .add_uniform::<TimeUniform>()
         .add_startup_system(setup.system())
         .add_system(animate_shader.system())
         .run();
 }
 
-#[derive(RenderResources, Default, TypeUuid)]
+#[derive(Uniform, Default, TypeUuid)]
 #[uuid = "463e4b8a-d555-4fc2-ba9f-4c880063ba92"]
 struct TimeUniform {
     value: f32,
@@ -76,7 +77,6 @@ fn setup(
     mut pipelines: ResMut<Assets<PipelineDescriptor>>,
     mut shaders: ResMut<Assets<Shader>>,
     mut meshes: ResMut<Assets<Mesh>>,
-    mut render_graph: ResMut<RenderGraph>,
 ) {
     // Create a new shader pipeline.
     let pipeline_handle = pipelines.add(PipelineDescriptor::default_config(ShaderStages {
@@ -84,18 +84,6 @@ fn setup(
         fragment: Some(shaders.add(Shader::from_glsl(ShaderStage::Fragment, FRAGMENT_SHADER))),
     }));
 
-    // Add a `RenderResourcesNode` to our `RenderGraph`. This will bind `TimeComponent` to our shader.
-    render_graph.add_system_node(
-        "time_uniform",
-        RenderResourcesNode::<TimeUniform>::new(true),
-    );
-
-    // Add a `RenderGraph` edge connecting our new "time_component" node to the main pass node. This
-    // ensures that "time_component" runs before the main pass.
-    render_graph
-        .add_node_edge("time_uniform", base::node::MAIN_PASS)
-        .unwrap();
-
     // Spawn a quad and insert the `TimeComponent`.
     commands
         .spawn_bundle(MeshBundle {
@@ -123,10 +123,10 @@ fn mouse_handler(
 
         let texture_handle = asset_server.load("branding/icon.png");
 
-        bird_material.0 = materials.add(ColorMaterial {
-            color: BASE_COLOR * color,
-            texture: Some(texture_handle),
-        });
+        bird_material.0 = materials.add(ColorMaterial::modulated_texture(
+            texture_handle,
+            BASE_COLOR * color,
+        ));
     }
 
     if mouse_button_input.pressed(MouseButton::Left) {
@@ -62,6 +62,7 @@ fn setup_pipeline(
     mut render_graph: ResMut<RenderGraph>,
     asset_server: Res<AssetServer>,
     msaa: Res<Msaa>,
+    depth: Res<Depth>,
     mut app_state: ResMut<State<AppState>>,
 ) {
     // get the non-default window id
@@ -89,7 +90,7 @@ fn setup_pipeline(
         WindowTextureNode::new(
             window_id,
             TextureDescriptor {
-                format: TextureFormat::Depth32Float,
+                format: depth.format,
                 usage: TextureUsage::OUTPUT_ATTACHMENT,
                 sample_count: msaa.samples,
                 ..Default::default()
@@ -113,7 +114,7 @@ fn setup_pipeline(
         depth_stencil_attachment: Some(RenderPassDepthStencilAttachmentDescriptor {
             attachment: TextureAttachment::Input("depth".to_string()),
             depth_ops: Some(Operations {
-                load: LoadOp::Clear(1.0),
+                load: LoadOp::Clear(depth.clear_depth()),
                 store: true,
             }),
             stencil_ops: None,
@@ -168,6 +169,7 @@ fn setup_pipeline(
                     dimension: TextureDimension::D2,
                     format: TextureFormat::default(),
                     usage: TextureUsage::OUTPUT_ATTACHMENT,
+                    view_dimension: None,
                 },
             ),
         );
@@ -16,16 +16,21 @@ crates=(
     bevy_core
     bevy_diagnostic
     bevy_transform
+    bevy_physics
     bevy_window
     bevy_render
     bevy_input
     bevy_gilrs
     bevy_pbr
+    bevy_navmesh
     bevy_gltf
     bevy_scene
+    bevy_net
+    bevy_script
     bevy_sprite
     bevy_text
     bevy_ui
+    bevy_video
     bevy_winit
     bevy_wgpu
     bevy_internal